@@ -0,0 +1 @@
+DROP TABLE IF EXISTS ont_rule_continuations;
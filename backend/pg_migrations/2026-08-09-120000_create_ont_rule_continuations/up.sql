@@ -0,0 +1,21 @@
+-- Suspended rule flows. A Delay node persists the rest of its flow here and
+-- the per-minute rule job resumes it once resume_at has passed.
+CREATE TABLE ont_rule_continuations (
+    id SERIAL PRIMARY KEY,
+    rule_id INT4 NOT NULL REFERENCES ont_rules(id) ON DELETE CASCADE,
+    user_id INT4 NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    flow_node TEXT NOT NULL,
+    flow_context TEXT NOT NULL,
+    resume_at INT4 NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'running', 'done', 'cancelled')),
+    created_at INT4 NOT NULL,
+    updated_at INT4 NOT NULL
+);
+
+CREATE INDEX ont_rule_continuations_due_idx
+    ON ont_rule_continuations (resume_at)
+    WHERE status IN ('pending', 'running');
+
+CREATE INDEX ont_rule_continuations_rule_idx
+    ON ont_rule_continuations (rule_id);
@@ -0,0 +1,16 @@
+-- CalDAV calendar accounts. Server URL, credentials and the discovered
+-- calendar list are encrypted with utils::encryption, like MCP servers.
+CREATE TABLE caldav_connections (
+    id SERIAL PRIMARY KEY,
+    user_id INT4 NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL CHECK (char_length(name) BETWEEN 1 AND 64),
+    url_encrypted TEXT NOT NULL,
+    username_encrypted TEXT NOT NULL,
+    password_encrypted TEXT NOT NULL,
+    calendars_encrypted TEXT NOT NULL,
+    is_enabled INT4 NOT NULL DEFAULT 1,
+    created_at INT4 NOT NULL,
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX caldav_connections_user_idx ON caldav_connections (user_id);
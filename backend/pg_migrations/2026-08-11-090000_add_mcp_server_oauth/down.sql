@@ -0,0 +1,4 @@
+ALTER TABLE mcp_servers DROP COLUMN IF EXISTS oauth_expires_at;
+ALTER TABLE mcp_servers DROP COLUMN IF EXISTS oauth_refresh_token_encrypted;
+ALTER TABLE mcp_servers DROP COLUMN IF EXISTS oauth_access_token_encrypted;
+ALTER TABLE mcp_servers DROP COLUMN IF EXISTS oauth_config_encrypted;
@@ -0,0 +1,7 @@
+-- OAuth 2.1 authorization for MCP servers. The discovered endpoints and
+-- registered client live in oauth_config_encrypted (JSON); tokens are
+-- refreshed in place. All encrypted with utils::encryption.
+ALTER TABLE mcp_servers ADD COLUMN oauth_config_encrypted TEXT;
+ALTER TABLE mcp_servers ADD COLUMN oauth_access_token_encrypted TEXT;
+ALTER TABLE mcp_servers ADD COLUMN oauth_refresh_token_encrypted TEXT;
+ALTER TABLE mcp_servers ADD COLUMN oauth_expires_at INT8;
@@ -0,0 +1,15 @@
+-- Cursor and counters for the background re-encryption job
+-- (services::key_rotation), one row per encrypted column. A row whose
+-- target_key_id is not the current key is restarted from the beginning.
+CREATE TABLE encryption_rotation_progress (
+    table_name TEXT NOT NULL,
+    column_name TEXT NOT NULL,
+    target_key_id INT4 NOT NULL,
+    last_key TEXT,
+    rows_scanned INT8 NOT NULL DEFAULT 0,
+    rows_rewritten INT8 NOT NULL DEFAULT 0,
+    rows_failed INT8 NOT NULL DEFAULT 0,
+    completed_at INT4,
+    updated_at INT4 NOT NULL,
+    PRIMARY KEY (table_name, column_name)
+);
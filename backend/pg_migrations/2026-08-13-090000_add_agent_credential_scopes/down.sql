@@ -0,0 +1,22 @@
+DELETE FROM agent_action_audit
+    WHERE action_kind NOT IN ('reminder', 'reply_watch_email');
+ALTER TABLE agent_action_audit DROP CONSTRAINT agent_action_audit_action_kind_check;
+ALTER TABLE agent_action_audit ADD CONSTRAINT agent_action_audit_action_kind_check
+    CHECK (action_kind IN ('reminder', 'reply_watch_email'));
+
+DELETE FROM agent_action_idempotency
+    WHERE action_kind NOT IN ('reminder', 'reply_watch_email');
+ALTER TABLE agent_action_idempotency DROP CONSTRAINT agent_action_idempotency_action_kind_check;
+ALTER TABLE agent_action_idempotency ADD CONSTRAINT agent_action_idempotency_action_kind_check
+    CHECK (action_kind IN ('reminder', 'reply_watch_email'));
+
+ALTER TABLE agent_pairing_sessions DROP COLUMN scopes;
+
+-- Credentials with anything but the original scopes cannot be represented.
+DELETE FROM agent_credentials WHERE scopes <> 'reminders:write,reply_watch:write';
+ALTER TABLE agent_credentials DROP CONSTRAINT agent_credentials_scopes_check;
+UPDATE agent_credentials SET scopes = 'reminders,reply_watch_email';
+ALTER TABLE agent_credentials
+    ALTER COLUMN scopes SET DEFAULT 'reminders,reply_watch_email';
+ALTER TABLE agent_credentials ADD CONSTRAINT agent_credentials_scopes_check
+    CHECK (scopes = 'reminders,reply_watch_email');
@@ -0,0 +1,24 @@
+-- Credentials carry the scopes chosen when the pairing was approved instead of
+-- one fixed write-only pair. Existing credentials keep exactly what they had.
+ALTER TABLE agent_credentials DROP CONSTRAINT agent_credentials_scopes_check;
+UPDATE agent_credentials SET scopes = 'reminders:write,reply_watch:write';
+ALTER TABLE agent_credentials
+    ALTER COLUMN scopes SET DEFAULT 'reminders:write,reply_watch:write';
+ALTER TABLE agent_credentials ADD CONSTRAINT agent_credentials_scopes_check CHECK (
+    scopes ~ '^(reminders:write|reply_watch:write|events:read|digest:read|sms:self|rules:toggle)(,(reminders:write|reply_watch:write|events:read|digest:read|sms:self|rules:toggle))*$'
+);
+
+-- Set by the approving user, copied onto the credential when it is issued.
+ALTER TABLE agent_pairing_sessions ADD COLUMN scopes TEXT;
+
+ALTER TABLE agent_action_idempotency DROP CONSTRAINT agent_action_idempotency_action_kind_check;
+ALTER TABLE agent_action_idempotency ADD CONSTRAINT agent_action_idempotency_action_kind_check
+    CHECK (action_kind IN ('reminder', 'reply_watch_email', 'sms_self', 'rule_toggle'));
+
+-- Still content-free: reads are recorded by kind and outcome only.
+ALTER TABLE agent_action_audit DROP CONSTRAINT agent_action_audit_action_kind_check;
+ALTER TABLE agent_action_audit ADD CONSTRAINT agent_action_audit_action_kind_check
+    CHECK (action_kind IN (
+        'reminder', 'reply_watch_email', 'events_read', 'digest_read', 'rules_read',
+        'sms_self', 'rule_toggle'
+    ));
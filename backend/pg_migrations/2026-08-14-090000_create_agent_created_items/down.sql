@@ -0,0 +1,18 @@
+DELETE FROM agent_action_audit WHERE action_kind IN (
+    'reminders_read', 'reminder_cancel', 'reminder_snooze', 'reply_watches_read',
+    'reply_watch_cancel'
+);
+ALTER TABLE agent_action_audit DROP CONSTRAINT agent_action_audit_action_kind_check;
+ALTER TABLE agent_action_audit ADD CONSTRAINT agent_action_audit_action_kind_check
+    CHECK (action_kind IN (
+        'reminder', 'reply_watch_email', 'events_read', 'digest_read', 'rules_read',
+        'sms_self', 'rule_toggle'
+    ));
+
+DELETE FROM agent_action_idempotency
+    WHERE action_kind IN ('reminder_cancel', 'reminder_snooze', 'reply_watch_cancel');
+ALTER TABLE agent_action_idempotency DROP CONSTRAINT agent_action_idempotency_action_kind_check;
+ALTER TABLE agent_action_idempotency ADD CONSTRAINT agent_action_idempotency_action_kind_check
+    CHECK (action_kind IN ('reminder', 'reply_watch_email', 'sms_self', 'rule_toggle'));
+
+DROP TABLE IF EXISTS agent_created_items;
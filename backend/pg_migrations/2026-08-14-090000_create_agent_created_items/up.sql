@@ -0,0 +1,33 @@
+-- Which credential created which reminder or reply watch, so an agent can list,
+-- cancel and snooze only its own items. Holds ids and times only: reply
+-- watches are matched by a SHA-256 digest of the sender, never the address.
+CREATE TABLE agent_created_items (
+    id SERIAL PRIMARY KEY,
+    credential_id INT4 NOT NULL REFERENCES agent_credentials(id) ON DELETE CASCADE,
+    user_id INT4 NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    item_kind TEXT NOT NULL CHECK (item_kind IN ('reminder', 'reply_watch')),
+    event_id INT4 REFERENCES ont_events(id) ON DELETE CASCADE,
+    contact_hash TEXT CHECK (char_length(contact_hash) = 64),
+    created_at INT4 NOT NULL,
+    expires_at INT4,
+    CHECK ((item_kind = 'reminder') = (event_id IS NOT NULL)),
+    CHECK ((item_kind = 'reply_watch') = (contact_hash IS NOT NULL AND expires_at IS NOT NULL))
+);
+
+CREATE INDEX agent_created_items_credential_idx
+    ON agent_created_items (credential_id, item_kind, created_at DESC);
+
+ALTER TABLE agent_action_idempotency DROP CONSTRAINT agent_action_idempotency_action_kind_check;
+ALTER TABLE agent_action_idempotency ADD CONSTRAINT agent_action_idempotency_action_kind_check
+    CHECK (action_kind IN (
+        'reminder', 'reply_watch_email', 'sms_self', 'rule_toggle',
+        'reminder_cancel', 'reminder_snooze', 'reply_watch_cancel'
+    ));
+
+ALTER TABLE agent_action_audit DROP CONSTRAINT agent_action_audit_action_kind_check;
+ALTER TABLE agent_action_audit ADD CONSTRAINT agent_action_audit_action_kind_check
+    CHECK (action_kind IN (
+        'reminder', 'reply_watch_email', 'events_read', 'digest_read', 'rules_read',
+        'sms_self', 'rule_toggle', 'reminders_read', 'reminder_cancel', 'reminder_snooze',
+        'reply_watches_read', 'reply_watch_cancel'
+    ));
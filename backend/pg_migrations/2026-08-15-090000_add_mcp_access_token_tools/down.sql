@@ -0,0 +1 @@
+ALTER TABLE mcp_access_tokens DROP COLUMN allowed_tools;
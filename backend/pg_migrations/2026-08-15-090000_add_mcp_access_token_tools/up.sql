@@ -0,0 +1,7 @@
+-- Each MCP access token lists the Lightfriend tools it may call. Tokens are
+-- minted with an explicit list; nothing outside the catalog can be granted.
+ALTER TABLE mcp_access_tokens
+    ADD COLUMN allowed_tools TEXT NOT NULL DEFAULT 'query_events';
+ALTER TABLE mcp_access_tokens ADD CONSTRAINT mcp_access_tokens_allowed_tools_check CHECK (
+    allowed_tools ~ '^(set_reminder|query_events|send_sms_to_self|get_weather)(,(set_reminder|query_events|send_sms_to_self|get_weather))*$'
+);
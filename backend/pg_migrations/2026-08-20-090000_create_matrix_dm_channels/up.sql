@@ -0,0 +1,23 @@
+-- A user's Matrix DM channel: a room on their Lightfriend Matrix account
+-- shared with one Matrix ID they own elsewhere (Element, Beeper, ...).
+-- Each notification category is delivered by SMS, Matrix or both.
+CREATE TABLE matrix_dm_channels (
+    user_id INT4 PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    target_mxid TEXT NOT NULL CHECK (target_mxid ~ '^@[^:]+:.+$'),
+    room_id TEXT,
+    critical_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (critical_delivery IN ('sms', 'matrix', 'both')),
+    digest_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (digest_delivery IN ('sms', 'matrix', 'both')),
+    reminder_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (reminder_delivery IN ('sms', 'matrix', 'both')),
+    rule_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (rule_delivery IN ('sms', 'matrix', 'both')),
+    other_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (other_delivery IN ('sms', 'matrix', 'both')),
+    created_at INT4 NOT NULL,
+    updated_at INT4 NOT NULL
+);
+
+CREATE UNIQUE INDEX matrix_dm_channels_room_idx ON matrix_dm_channels (room_id)
+    WHERE room_id IS NOT NULL;
@@ -0,0 +1,16 @@
+-- A user's UnifiedPush / ntfy endpoint. The URL and the Web Push auth
+-- secret are encrypted; endpoint_hash lets deliveries delete an expired
+-- endpoint without racing a re-registration. With p256dh and the auth
+-- secret set, payloads are encrypted end to end (RFC 8291).
+CREATE TABLE push_endpoints (
+    user_id INT4 PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    encrypted_endpoint TEXT NOT NULL,
+    endpoint_hash TEXT NOT NULL,
+    p256dh TEXT,
+    encrypted_auth_secret TEXT,
+    push_digests BOOLEAN NOT NULL DEFAULT FALSE,
+    push_rules BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at INT4 NOT NULL,
+    updated_at INT4 NOT NULL,
+    CHECK ((p256dh IS NULL) = (encrypted_auth_secret IS NULL))
+);
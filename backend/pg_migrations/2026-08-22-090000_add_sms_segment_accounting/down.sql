@@ -0,0 +1,2 @@
+ALTER TABLE usage_logs DROP COLUMN segments;
+ALTER TABLE users DROP COLUMN sms_gsm7_transliteration;
@@ -0,0 +1,8 @@
+-- Opt-in folding of curly quotes, dashes and common emoji to GSM-7, so a
+-- single character can't switch a whole SMS to UCS-2.
+ALTER TABLE users
+    ADD COLUMN sms_gsm7_transliteration BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Segments counted for the body we sent; NULL for non-SMS usage and rows
+-- logged before this column existed.
+ALTER TABLE usage_logs ADD COLUMN segments INT4 CHECK (segments > 0);
@@ -0,0 +1,21 @@
+-- A user's email notification channel: the address notifications go to
+-- and whether they are sent through the operator's SMTP relay (NULL
+-- imap_connection_id) or the user's own connected mail account. Each
+-- notification category is delivered by SMS, email or both.
+CREATE TABLE email_notification_channels (
+    user_id INT4 PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    address TEXT NOT NULL CHECK (address LIKE '%_@_%'),
+    imap_connection_id INT4 REFERENCES imap_connection(id) ON DELETE SET NULL,
+    critical_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (critical_delivery IN ('sms', 'email', 'both')),
+    digest_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (digest_delivery IN ('sms', 'email', 'both')),
+    reminder_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (reminder_delivery IN ('sms', 'email', 'both')),
+    rule_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (rule_delivery IN ('sms', 'email', 'both')),
+    other_delivery TEXT NOT NULL DEFAULT 'sms'
+        CHECK (other_delivery IN ('sms', 'email', 'both')),
+    created_at INT4 NOT NULL,
+    updated_at INT4 NOT NULL
+);
@@ -0,0 +1,14 @@
+-- Per-country ordered list of voice providers for outbound calls, the
+-- voice counterpart of provider_routes. The router dials providers in
+-- this order and moves on to the next when one refuses the call.
+-- provider_order is a JSON array of voice channel ids registered in
+-- ChannelRouter, e.g. '["telnyx","twilio"]'.
+--
+-- Countries with no row inherit the router default ("twilio" only), so
+-- calls keep going through Twilio until an operator adds a route via
+-- POST /api/admin/voice-routes.
+CREATE TABLE IF NOT EXISTS voice_routes (
+    country_code TEXT PRIMARY KEY,
+    provider_order TEXT NOT NULL,
+    updated_at INT4 NOT NULL
+);
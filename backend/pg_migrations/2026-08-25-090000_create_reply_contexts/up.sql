@@ -0,0 +1,28 @@
+-- Ring buffer of recent outbound notifications per user, keyed by the short
+-- reference code ("A7") printed in front of each one. Replies like "A7 yes"
+-- or "stop A7" are routed to the item the code points at instead of the
+-- latest prompt of whatever kind.
+--
+-- kind is one of 'alert', 'commitment', 'reply_watch', 'rule' or
+-- 'notification'. target_id / target_ref point at the row behind it:
+--   alert        target_ref = usage_logs.sid of the delivered alert
+--   commitment   target_id  = commitment_prompts.id
+--   reply_watch  target_ref = bridge room id, or target_id = imap
+--                connection id with target_ref = sender address
+--   rule         target_id  = ont_rules.id
+-- Only the newest rows per user are kept; a code is reused once it falls
+-- out of the buffer. The summary quotes the notification, which is private
+-- message content, so it is kept encrypted like message_history.
+CREATE TABLE IF NOT EXISTS reply_contexts (
+    id SERIAL PRIMARY KEY,
+    user_id INT4 NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    target_id INT4,
+    target_ref TEXT,
+    created_at INT4 NOT NULL,
+    encrypted_summary TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_reply_contexts_user_code
+    ON reply_contexts (user_id, code, created_at DESC);
@@ -0,0 +1 @@
+ALTER TABLE ont_messages DROP COLUMN IF EXISTS transcript;
@@ -0,0 +1,5 @@
+-- Speech-to-text output for inbound bridged voice notes. content holds the
+-- readable "[Voice message 0:42] ..." form that rules, digests and message
+-- queries already read; transcript keeps the raw text on its own, and is
+-- NULL for every message that wasn't a transcribed voice note.
+ALTER TABLE ont_messages ADD COLUMN IF NOT EXISTS transcript TEXT;
@@ -0,0 +1,4 @@
+ALTER TABLE ont_messages DROP COLUMN IF EXISTS location_place;
+ALTER TABLE ont_messages DROP COLUMN IF EXISTS location_label;
+ALTER TABLE ont_messages DROP COLUMN IF EXISTS location_lon;
+ALTER TABLE ont_messages DROP COLUMN IF EXISTS location_lat;
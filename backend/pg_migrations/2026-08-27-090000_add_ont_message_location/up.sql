@@ -0,0 +1,7 @@
+-- Locations shared in bridged chats. content keeps a readable
+-- "[Location] Café Regatta (60.18402,24.92200)" line; these columns keep the
+-- coordinates, the sender's label and the gazetteer place for forwarding.
+ALTER TABLE ont_messages ADD COLUMN IF NOT EXISTS location_lat DOUBLE PRECISION;
+ALTER TABLE ont_messages ADD COLUMN IF NOT EXISTS location_lon DOUBLE PRECISION;
+ALTER TABLE ont_messages ADD COLUMN IF NOT EXISTS location_label TEXT;
+ALTER TABLE ont_messages ADD COLUMN IF NOT EXISTS location_place TEXT;
@@ -242,7 +242,7 @@ impl AiConfig {
         }
     }
 
-    fn provider_configured(&self, provider: AiProvider) -> bool {
+    pub fn provider_configured(&self, provider: AiProvider) -> bool {
         match provider {
             AiProvider::OpenRouter => self.openrouter_api_key.is_some(),
             AiProvider::Tinfoil => self.tinfoil_api_key.is_some(),
@@ -0,0 +1,1130 @@
+//! Minimal CalDAV (RFC 4791) client and iCalendar (RFC 5545) handling.
+//!
+//! Covers what the assistant needs from a calendar server: discovering the
+//! user's calendars, listing events in a time range (with recurrences expanded
+//! by the server), and creating or updating single VEVENTs. Parsing is kept
+//! in pure functions so it can be tested without a server.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::services::inbound_media::{read_capped, InboundMediaError};
+
+const DAV_NS: &str = "DAV:";
+const CALDAV_NS: &str = "urn:ietf:params:xml:ns:caldav";
+const PRODID: &str = "-//Lightfriend//CalDAV//EN";
+const MAX_REDIRECTS: usize = 3;
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+/// Responses larger than this are refused rather than read into memory.
+const MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+const DISCOVERY_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:resourcetype/>
+    <d:displayname/>
+    <d:current-user-principal/>
+    <c:calendar-home-set/>
+  </d:prop>
+</d:propfind>"#;
+
+const CALENDAR_LIST_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:resourcetype/>
+    <d:displayname/>
+    <c:supported-calendar-component-set/>
+  </d:prop>
+</d:propfind>"#;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A calendar collection on the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarInfo {
+    /// Absolute URL of the collection.
+    pub url: String,
+    pub name: String,
+}
+
+/// Start or end of an event as written in the iCalendar data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventTime {
+    /// All-day date (`VALUE=DATE`).
+    Date(NaiveDate),
+    /// An absolute instant: UTC, or a local time with a known TZID.
+    Instant(DateTime<Utc>),
+    /// Local time without a zone; read in the user's own timezone.
+    Floating(NaiveDateTime),
+}
+
+impl EventTime {
+    /// Resolve to a UTC instant, reading dates and floating times in `tz`.
+    pub fn to_utc<Z: TimeZone>(&self, tz: &Z) -> DateTime<Utc> {
+        let local = match self {
+            EventTime::Instant(at) => return *at,
+            EventTime::Date(date) => date.and_hms_opt(0, 0, 0).unwrap_or_default(),
+            EventTime::Floating(local) => *local,
+        };
+        tz.from_local_datetime(&local)
+            .earliest()
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+    }
+
+    pub fn is_all_day(&self) -> bool {
+        matches!(self, EventTime::Date(_))
+    }
+}
+
+/// One VEVENT (or one expanded occurrence of a recurring VEVENT).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub start: EventTime,
+    pub end: Option<EventTime>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+impl CalendarEvent {
+    /// One-line human description in the given timezone, e.g.
+    /// "Wed 04 Mar 09:00-10:00 Dentist @ Clinic".
+    pub fn describe<Z: TimeZone>(&self, tz: &Z) -> String
+    where
+        Z::Offset: std::fmt::Display,
+    {
+        let when = match self.start {
+            EventTime::Date(date) => {
+                let last_day = match self.end {
+                    Some(EventTime::Date(end)) if end > date + Duration::days(1) => {
+                        Some(end - Duration::days(1))
+                    }
+                    _ => None,
+                };
+                match last_day {
+                    Some(last) => format!(
+                        "{}-{} (all day)",
+                        date.format("%a %d %b"),
+                        last.format("%a %d %b")
+                    ),
+                    None => format!("{} (all day)", date.format("%a %d %b")),
+                }
+            }
+            _ => {
+                let start = self.start.to_utc(tz).with_timezone(tz);
+                let mut text = start.format("%a %d %b %H:%M").to_string();
+                if let Some(end) = self.end {
+                    let end = end.to_utc(tz).with_timezone(tz);
+                    if end.date_naive() == start.date_naive() {
+                        text.push_str(&end.format("-%H:%M").to_string());
+                    } else if end > start {
+                        text.push_str(&end.format(" - %a %d %b %H:%M").to_string());
+                    }
+                }
+                text
+            }
+        };
+        match self.location.as_deref().filter(|l| !l.trim().is_empty()) {
+            Some(location) => format!("{} {} @ {}", when, self.summary, location.trim()),
+            None => format!("{} {}", when, self.summary),
+        }
+    }
+}
+
+/// Fields to change on an existing event. `None` leaves the field as is.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventPatch {
+    pub summary: Option<String>,
+    pub start: Option<EventTime>,
+    pub end: Option<EventTime>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+impl EventPatch {
+    pub fn is_empty(&self) -> bool {
+        self == &EventPatch::default()
+    }
+}
+
+/// A calendar object resource as returned by a REPORT.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarObject {
+    /// Absolute URL of the `.ics` resource.
+    pub url: String,
+    pub etag: Option<String>,
+    pub data: String,
+}
+
+/// Properties read from a Depth 0 discovery PROPFIND.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiscoveryProps {
+    pub is_calendar: bool,
+    pub display_name: Option<String>,
+    pub principal: Option<String>,
+    pub calendar_home: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// iCalendar parsing
+// ---------------------------------------------------------------------------
+
+/// Undo RFC 5545 line folding (CRLF followed by a space or tab).
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(rest) = raw.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+/// (NAME, params, value) of one unfolded content line.
+type ContentLine = (String, Vec<(String, String)>, String);
+
+/// Split a content line into its parts. Colons inside quoted parameter
+/// values don't end the name part.
+fn split_content_line(line: &str) -> Option<ContentLine> {
+    let mut in_quotes = false;
+    let mut colon = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                colon = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let colon = colon?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| {
+            let (key, val) = p.split_once('=')?;
+            Some((
+                key.trim().to_ascii_uppercase(),
+                val.trim().trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+    Some((name, params, value.to_string()))
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Resolve a TZID to an IANA zone. Accepts plain names and the
+/// "/vendor/…/Europe/Helsinki" style some clients emit.
+fn resolve_tzid(tzid: &str) -> Option<chrono_tz::Tz> {
+    let segments: Vec<&str> = tzid.split('/').filter(|s| !s.is_empty()).collect();
+    (0..segments.len()).find_map(|i| segments[i..].join("/").parse::<chrono_tz::Tz>().ok())
+}
+
+fn parse_event_time(params: &[(String, String)], value: &str) -> Option<EventTime> {
+    let value = value.trim();
+    let param = |key: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    if param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(EventTime::Date);
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let local = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(EventTime::Instant(Utc.from_utc_datetime(&local)));
+    }
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    match param("TZID").and_then(resolve_tzid) {
+        Some(tz) => tz
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|at| EventTime::Instant(at.with_timezone(&Utc))),
+        None => Some(EventTime::Floating(local)),
+    }
+}
+
+/// Parse a simple RFC 5545 DURATION such as "PT1H30M", "P1D" or "P2W".
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut rest = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut in_time = false;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = after;
+            continue;
+        }
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        total += match (unit, in_time) {
+            ('W', false) => Duration::weeks(amount),
+            ('D', false) => Duration::days(amount),
+            ('H', true) => Duration::hours(amount),
+            ('M', true) => Duration::minutes(amount),
+            ('S', true) => Duration::seconds(amount),
+            _ => return None,
+        };
+        rest = &rest[digits + 1..];
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn add_duration(start: EventTime, duration: Duration) -> EventTime {
+    match start {
+        EventTime::Date(date) => EventTime::Date(date + duration),
+        EventTime::Instant(at) => EventTime::Instant(at + duration),
+        EventTime::Floating(local) => EventTime::Floating(local + duration),
+    }
+}
+
+/// Parse every VEVENT in an iCalendar document. Nested components such as
+/// VALARM are skipped; events without a UID or DTSTART are dropped.
+pub fn parse_ics_events(ics: &str) -> Vec<CalendarEvent> {
+    struct Draft {
+        uid: Option<String>,
+        summary: Option<String>,
+        start: Option<EventTime>,
+        end: Option<EventTime>,
+        duration: Option<Duration>,
+        location: Option<String>,
+        description: Option<String>,
+    }
+
+    let mut events = Vec::new();
+    let mut draft: Option<Draft> = None;
+    let mut nested = 0usize;
+
+    for line in unfold_lines(ics) {
+        let Some((name, params, value)) = split_content_line(&line) else {
+            continue;
+        };
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") && draft.is_none() => {
+                draft = Some(Draft {
+                    uid: None,
+                    summary: None,
+                    start: None,
+                    end: None,
+                    duration: None,
+                    location: None,
+                    description: None,
+                });
+                continue;
+            }
+            "BEGIN" if draft.is_some() => {
+                nested += 1;
+                continue;
+            }
+            "END" if draft.is_some() && nested > 0 => {
+                nested -= 1;
+                continue;
+            }
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                if let Some(d) = draft.take() {
+                    if let (Some(uid), Some(start)) = (d.uid, d.start) {
+                        let end = d
+                            .end
+                            .or_else(|| d.duration.map(|dur| add_duration(start, dur)));
+                        events.push(CalendarEvent {
+                            uid,
+                            summary: d
+                                .summary
+                                .filter(|s| !s.trim().is_empty())
+                                .unwrap_or_else(|| "Untitled event".to_string()),
+                            start,
+                            end,
+                            location: d.location.filter(|s| !s.trim().is_empty()),
+                            description: d.description.filter(|s| !s.trim().is_empty()),
+                        });
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+        let Some(d) = draft.as_mut() else {
+            continue;
+        };
+        if nested > 0 {
+            continue;
+        }
+        match name.as_str() {
+            "UID" => d.uid = Some(value.trim().to_string()),
+            "SUMMARY" => d.summary = Some(unescape_text(&value)),
+            "LOCATION" => d.location = Some(unescape_text(&value)),
+            "DESCRIPTION" => d.description = Some(unescape_text(&value)),
+            "DTSTART" => d.start = parse_event_time(&params, &value),
+            "DTEND" => d.end = parse_event_time(&params, &value),
+            "DURATION" => d.duration = parse_duration(&value),
+            _ => {}
+        }
+    }
+    events
+}
+
+// ---------------------------------------------------------------------------
+// iCalendar generation
+// ---------------------------------------------------------------------------
+
+/// Fold a content line at 75 octets without splitting UTF-8 characters.
+fn fold_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+fn time_line(name: &str, time: &EventTime) -> String {
+    match time {
+        EventTime::Date(date) => format!("{};VALUE=DATE:{}", name, date.format("%Y%m%d")),
+        EventTime::Instant(at) => format!("{}:{}", name, at.format("%Y%m%dT%H%M%SZ")),
+        EventTime::Floating(local) => format!("{}:{}", name, local.format("%Y%m%dT%H%M%S")),
+    }
+}
+
+fn stamp_line(name: &str, now: DateTime<Utc>) -> String {
+    format!("{}:{}", name, now.format("%Y%m%dT%H%M%SZ"))
+}
+
+/// Render a single-event VCALENDAR document.
+pub fn build_event_ics(event: &CalendarEvent, now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        stamp_line("DTSTAMP", now),
+        time_line("DTSTART", &event.start),
+    ];
+    if let Some(end) = &event.end {
+        lines.push(time_line("DTEND", end));
+    }
+    lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+    if let Some(location) = &event.location {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+    if let Some(description) = &event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in &lines {
+        fold_line(line, &mut out);
+    }
+    out
+}
+
+/// Apply a patch to the master VEVENT of an existing calendar object,
+/// keeping every property the patch doesn't touch (attendees, alarms,
+/// recurrence rules…). Moving the start without a new end keeps the
+/// event's duration.
+pub fn update_event_ics(
+    ics: &str,
+    patch: &EventPatch,
+    now: DateTime<Utc>,
+) -> Result<String, String> {
+    let lines = unfold_lines(ics);
+
+    // Locate the master VEVENT: the first one without a RECURRENCE-ID.
+    let mut master: Option<(usize, usize)> = None;
+    let mut open: Option<usize> = None;
+    let mut nested = 0usize;
+    let mut is_override = false;
+    for (i, line) in lines.iter().enumerate() {
+        let Some((name, _, value)) = split_content_line(line) else {
+            continue;
+        };
+        match (name.as_str(), open) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                open = Some(i);
+                is_override = false;
+            }
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(start)) if value.eq_ignore_ascii_case("VEVENT") => {
+                if !is_override {
+                    master = Some((start, i));
+                    break;
+                }
+                open = None;
+            }
+            ("RECURRENCE-ID", Some(_)) if nested == 0 => is_override = true,
+            _ => {}
+        }
+    }
+    let (begin, end) = master.ok_or_else(|| "Calendar object has no VEVENT".to_string())?;
+
+    let existing = parse_ics_events(&lines[begin..=end].join("\r\n"))
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Event is missing UID or start time".to_string())?;
+    let new_start = patch.start.unwrap_or(existing.start);
+    let new_end = match (patch.end, patch.start, existing.end) {
+        (Some(end), _, _) => Some(end),
+        (None, Some(start), Some(old_end)) => {
+            let utc = chrono::Utc;
+            let duration = old_end.to_utc(&utc) - existing.start.to_utc(&utc);
+            Some(add_duration(start, duration))
+        }
+        (None, _, old_end) => old_end,
+    };
+    let times_changed = patch.start.is_some() || patch.end.is_some();
+
+    let mut replaced: Vec<&'static str> = vec!["DTSTAMP", "SEQUENCE", "LAST-MODIFIED"];
+    if patch.summary.is_some() {
+        replaced.push("SUMMARY");
+    }
+    if patch.location.is_some() {
+        replaced.push("LOCATION");
+    }
+    if patch.description.is_some() {
+        replaced.push("DESCRIPTION");
+    }
+    if times_changed {
+        replaced.extend(["DTSTART", "DTEND", "DURATION"]);
+    }
+
+    let mut sequence = 0i64;
+    let mut body: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+    for line in &lines[begin + 1..end] {
+        let parsed = split_content_line(line);
+        if let Some((name, _, value)) = &parsed {
+            match name.as_str() {
+                "BEGIN" => depth += 1,
+                "END" => depth = depth.saturating_sub(1),
+                "SEQUENCE" if depth == 0 => {
+                    sequence = value.trim().parse().unwrap_or(0);
+                }
+                _ => {}
+            }
+            if depth == 0 && replaced.contains(&name.as_str()) {
+                continue;
+            }
+        }
+        body.push(line.clone());
+    }
+
+    let mut added = vec![
+        stamp_line("DTSTAMP", now),
+        stamp_line("LAST-MODIFIED", now),
+        format!("SEQUENCE:{}", sequence + 1),
+    ];
+    if times_changed {
+        added.push(time_line("DTSTART", &new_start));
+        if let Some(end) = &new_end {
+            added.push(time_line("DTEND", end));
+        }
+    }
+    if let Some(summary) = &patch.summary {
+        added.push(format!("SUMMARY:{}", escape_text(summary)));
+    }
+    if let Some(location) = &patch.location {
+        added.push(format!("LOCATION:{}", escape_text(location)));
+    }
+    if let Some(description) = &patch.description {
+        added.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    // New properties go right after UID so nested components stay last.
+    let insert_at = body
+        .iter()
+        .position(|l| split_content_line(l).is_some_and(|(name, _, _)| name == "UID"))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    body.splice(insert_at..insert_at, added);
+
+    let mut out = String::new();
+    for line in lines[..=begin]
+        .iter()
+        .chain(body.iter())
+        .chain(lines[end..].iter())
+    {
+        fold_line(line, &mut out);
+    }
+    Ok(out)
+}
+
+/// Read a user-supplied time: "2026-03-19" (all day), "2026-03-19T14:30"
+/// in `tz`, or an RFC 3339 timestamp with an offset.
+pub fn parse_user_time<Z: TimeZone>(input: &str, tz: &Z) -> Result<EventTime, String> {
+    let input = input.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok(EventTime::Date(date));
+    }
+    if let Ok(at) = DateTime::parse_from_rfc3339(input) {
+        return Ok(EventTime::Instant(at.with_timezone(&Utc)));
+    }
+    let local = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(input, fmt).ok())
+        .ok_or_else(|| {
+            format!(
+                "Couldn't read time '{}'; use YYYY-MM-DD or YYYY-MM-DDTHH:MM",
+                input
+            )
+        })?;
+    tz.from_local_datetime(&local)
+        .earliest()
+        .map(|at| EventTime::Instant(at.with_timezone(&Utc)))
+        .ok_or_else(|| format!("'{}' doesn't exist in your timezone", input))
+}
+
+// ---------------------------------------------------------------------------
+// WebDAV XML
+// ---------------------------------------------------------------------------
+
+fn parse_xml(xml: &str) -> Result<roxmltree::Document<'_>, String> {
+    roxmltree::Document::parse(xml).map_err(|e| format!("Invalid CalDAV response: {}", e))
+}
+
+fn responses<'a, 'input>(
+    doc: &'a roxmltree::Document<'input>,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    doc.descendants()
+        .filter(|n| n.has_tag_name((DAV_NS, "response")))
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    ns: &str,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name((ns, name)))
+}
+
+fn response_href(response: roxmltree::Node) -> Option<String> {
+    child(response, DAV_NS, "href")
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+}
+
+/// A property from a propstat with a 2xx status.
+fn ok_prop<'a, 'input>(
+    response: roxmltree::Node<'a, 'input>,
+    ns: &str,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    response
+        .children()
+        .filter(|n| n.has_tag_name((DAV_NS, "propstat")))
+        .filter(|ps| {
+            child(*ps, DAV_NS, "status")
+                .and_then(|s| s.text())
+                .is_none_or(|s| {
+                    s.split_whitespace()
+                        .nth(1)
+                        .is_some_and(|c| c.starts_with('2'))
+                })
+        })
+        .find_map(|ps| child(ps, DAV_NS, "prop").and_then(|p| child(p, ns, name)))
+}
+
+fn prop_text(response: roxmltree::Node, ns: &str, name: &str) -> Option<String> {
+    ok_prop(response, ns, name)
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn prop_href(response: roxmltree::Node, ns: &str, name: &str) -> Option<String> {
+    ok_prop(response, ns, name)
+        .and_then(|p| child(p, DAV_NS, "href"))
+        .and_then(|h| h.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn is_calendar_collection(response: roxmltree::Node) -> bool {
+    ok_prop(response, DAV_NS, "resourcetype")
+        .is_some_and(|rt| child(rt, CALDAV_NS, "calendar").is_some())
+}
+
+/// Parse a Depth 0 PROPFIND made with the discovery body.
+pub fn parse_discovery(xml: &str) -> Result<DiscoveryProps, String> {
+    let doc = parse_xml(xml)?;
+    let Some(response) = responses(&doc).next() else {
+        return Ok(DiscoveryProps::default());
+    };
+    Ok(DiscoveryProps {
+        is_calendar: is_calendar_collection(response),
+        display_name: prop_text(response, DAV_NS, "displayname"),
+        principal: prop_href(response, DAV_NS, "current-user-principal"),
+        calendar_home: prop_href(response, CALDAV_NS, "calendar-home-set"),
+    })
+}
+
+/// Parse a Depth 1 PROPFIND of a calendar home into the calendars that can
+/// hold events. Relative hrefs are resolved against `base`; hrefs pointing
+/// at another origin are dropped.
+pub fn parse_calendar_list(xml: &str, base: &str) -> Result<Vec<CalendarInfo>, String> {
+    let base = Url::parse(base).map_err(|_| "Invalid calendar home URL".to_string())?;
+    let doc = parse_xml(xml)?;
+    let mut calendars = Vec::new();
+    for response in responses(&doc) {
+        if !is_calendar_collection(response) {
+            continue;
+        }
+        // Calendars that declare their components must include VEVENT;
+        // this skips task-only (VTODO) lists.
+        if let Some(set) = ok_prop(response, CALDAV_NS, "supported-calendar-component-set") {
+            let has_events = set
+                .children()
+                .filter(|c| c.has_tag_name((CALDAV_NS, "comp")))
+                .any(|c| {
+                    c.attribute("name")
+                        .is_some_and(|n| n.eq_ignore_ascii_case("VEVENT"))
+                });
+            if !has_events {
+                continue;
+            }
+        }
+        let Some(href) = response_href(response) else {
+            continue;
+        };
+        let Ok(url) = base.join(&href) else {
+            continue;
+        };
+        if url.origin() != base.origin() {
+            continue;
+        }
+        let name = prop_text(response, DAV_NS, "displayname").unwrap_or_else(|| {
+            url.path_segments()
+                .and_then(|mut s| s.rfind(|p| !p.is_empty()))
+                .map(|s| {
+                    urlencoding::decode(s)
+                        .map(|d| d.into_owned())
+                        .unwrap_or(s.to_string())
+                })
+                .unwrap_or_else(|| "Calendar".to_string())
+        });
+        calendars.push(CalendarInfo {
+            url: url.to_string(),
+            name,
+        });
+    }
+    Ok(calendars)
+}
+
+/// Parse a calendar-query REPORT into calendar objects. Objects whose href
+/// points at another origin are dropped.
+pub fn parse_calendar_objects(xml: &str, base: &str) -> Result<Vec<CalendarObject>, String> {
+    let base = Url::parse(base).map_err(|_| "Invalid calendar URL".to_string())?;
+    let doc = parse_xml(xml)?;
+    Ok(responses(&doc)
+        .filter_map(|response| {
+            let data = ok_prop(response, CALDAV_NS, "calendar-data")?
+                .text()?
+                .to_string();
+            let url = base.join(&response_href(response)?).ok()?;
+            if url.origin() != base.origin() {
+                return None;
+            }
+            Some(CalendarObject {
+                url: url.to_string(),
+                etag: prop_text(response, DAV_NS, "getetag"),
+                data,
+            })
+        })
+        .collect())
+}
+
+fn calendar_query_body(filter: &str, expand: Option<(DateTime<Utc>, DateTime<Utc>)>) -> String {
+    let data = match expand {
+        Some((start, end)) => format!(
+            r#"<c:calendar-data><c:expand start="{}" end="{}"/></c:calendar-data>"#,
+            start.format("%Y%m%dT%H%M%SZ"),
+            end.format("%Y%m%dT%H%M%SZ")
+        ),
+        None => "<c:calendar-data/>".to_string(),
+    };
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><d:getetag/>{}</d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT">{}</c:comp-filter></c:comp-filter></c:filter>
+</c:calendar-query>"#,
+        data, filter
+    )
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ---------------------------------------------------------------------------
+// HTTP client
+// ---------------------------------------------------------------------------
+
+/// Self-hosters running Radicale next to the backend (and the integration
+/// tests) need to reach private addresses; everyone else is limited to
+/// public hosts like the MCP server URLs.
+fn private_hosts_allowed() -> bool {
+    std::env::var("CALDAV_ALLOW_PRIVATE_HOSTS")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Check a CalDAV URL before connecting to it. Runs when a connection is
+/// added and again before every request, so a host whose DNS later points
+/// at a private address is refused on the next sync.
+pub async fn validate_caldav_url(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|_| "Invalid URL".to_string())?;
+    if parsed.scheme() != "https" && parsed.scheme() != "http" {
+        return Err("URL must start with http:// or https://".to_string());
+    }
+    crate::handlers::mcp_handlers::validate_public_url(url, "CalDAV", private_hosts_allowed()).await
+}
+
+pub struct CalDavClient {
+    http: reqwest::Client,
+    username: String,
+    password: String,
+}
+
+impl CalDavClient {
+    pub fn new(username: &str, password: &str) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        Ok(Self {
+            http,
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    /// Send a WebDAV request. Redirects are followed only within the same
+    /// origin, keeping the method and body (reqwest would turn PROPFIND into
+    /// GET), so a server can't bounce credentials to another host. Every
+    /// hop is re-validated with `validate_caldav_url`, and the body is read
+    /// up to `MAX_RESPONSE_BYTES`.
+    async fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<String>,
+    ) -> Result<(StatusCode, String, String), String> {
+        let method =
+            Method::from_bytes(method.as_bytes()).map_err(|e| format!("Bad method: {}", e))?;
+        let mut current = Url::parse(url).map_err(|_| format!("Invalid URL: {}", url))?;
+        for _ in 0..=MAX_REDIRECTS {
+            validate_caldav_url(current.as_str()).await?;
+            let mut request = self
+                .http
+                .request(method.clone(), current.clone())
+                .basic_auth(&self.username, Some(&self.password));
+            for (name, value) in headers {
+                request = request.header(*name, *value);
+            }
+            if let Some(body) = &body {
+                request = request
+                    .header("Content-Type", "application/xml; charset=utf-8")
+                    .body(body.clone());
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("CalDAV request failed: {}", e))?;
+            let status = response.status();
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(reqwest::header::LOCATION)
+                    .and_then(|l| l.to_str().ok())
+                    .ok_or_else(|| "CalDAV redirect without a location".to_string())?;
+                let next = current
+                    .join(location)
+                    .map_err(|_| "CalDAV redirect to an invalid URL".to_string())?;
+                if next.origin() != current.origin() {
+                    return Err(format!(
+                        "CalDAV server redirected to another host ({}); use that URL instead",
+                        next.host_str().unwrap_or("unknown")
+                    ));
+                }
+                current = next;
+                continue;
+            }
+            let bytes = read_capped(response, MAX_RESPONSE_BYTES)
+                .await
+                .map_err(|e| match e {
+                    InboundMediaError::Download(reason) => {
+                        format!("Failed to read CalDAV response: {}", reason)
+                    }
+                    other => other.to_string(),
+                })?;
+            let text = String::from_utf8_lossy(&bytes).into_owned();
+            return Ok((status, text, current.to_string()));
+        }
+        Err("Too many CalDAV redirects".to_string())
+    }
+
+    /// PROPFIND/REPORT expecting 207 Multi-Status. Returns the body and the
+    /// final URL (after redirects) that relative hrefs resolve against.
+    async fn multistatus(
+        &self,
+        method: &str,
+        url: &str,
+        depth: &str,
+        body: String,
+    ) -> Result<(String, String), String> {
+        let (status, text, final_url) = self
+            .send(method, url, &[("Depth", depth)], Some(body))
+            .await?;
+        match status {
+            StatusCode::MULTI_STATUS => Ok((text, final_url)),
+            status => Err(status_error(status)),
+        }
+    }
+
+    /// Find the event calendars reachable from `url`, which may be the
+    /// server root, a principal, a calendar home or a single calendar.
+    pub async fn discover_calendars(&self, url: &str) -> Result<Vec<CalendarInfo>, String> {
+        let (xml, base) = self
+            .multistatus("PROPFIND", url, "0", DISCOVERY_BODY.to_string())
+            .await?;
+        let props = parse_discovery(&xml)?;
+        if props.is_calendar {
+            return Ok(vec![CalendarInfo {
+                url: base,
+                name: props.display_name.unwrap_or_else(|| "Calendar".to_string()),
+            }]);
+        }
+
+        let home = match props.calendar_home {
+            Some(home) => home,
+            None => {
+                let principal = props
+                    .principal
+                    .ok_or_else(|| "Server did not report a CalDAV principal".to_string())?;
+                let principal = join_url(&base, &principal)?;
+                let (xml, _) = self
+                    .multistatus("PROPFIND", &principal, "0", DISCOVERY_BODY.to_string())
+                    .await?;
+                parse_discovery(&xml)?
+                    .calendar_home
+                    .ok_or_else(|| "Server did not report a calendar home".to_string())?
+            }
+        };
+        let home = join_url(&base, &home)?;
+        let (xml, home_base) = self
+            .multistatus("PROPFIND", &home, "1", CALENDAR_LIST_BODY.to_string())
+            .await?;
+        let calendars = parse_calendar_list(&xml, &home_base)?;
+        if calendars.is_empty() {
+            return Err("No event calendars found on the server".to_string());
+        }
+        Ok(calendars)
+    }
+
+    /// Events overlapping [start, end), recurring events expanded by the
+    /// server into individual occurrences.
+    pub async fn list_events(
+        &self,
+        calendar_url: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, String> {
+        let filter = format!(
+            r#"<c:time-range start="{}" end="{}"/>"#,
+            start.format("%Y%m%dT%H%M%SZ"),
+            end.format("%Y%m%dT%H%M%SZ")
+        );
+        let (xml, base) = self
+            .multistatus(
+                "REPORT",
+                calendar_url,
+                "1",
+                calendar_query_body(&filter, Some((start, end))),
+            )
+            .await?;
+        Ok(parse_calendar_objects(&xml, &base)?
+            .iter()
+            .flat_map(|object| parse_ics_events(&object.data))
+            .collect())
+    }
+
+    /// The calendar object holding the event with this UID, if any.
+    pub async fn find_event(
+        &self,
+        calendar_url: &str,
+        uid: &str,
+    ) -> Result<Option<CalendarObject>, String> {
+        let filter = format!(
+            r#"<c:prop-filter name="UID"><c:text-match collation="i;octet">{}</c:text-match></c:prop-filter>"#,
+            xml_escape(uid)
+        );
+        let (xml, base) = self
+            .multistatus(
+                "REPORT",
+                calendar_url,
+                "1",
+                calendar_query_body(&filter, None),
+            )
+            .await?;
+        Ok(parse_calendar_objects(&xml, &base)?
+            .into_iter()
+            .find(|object| parse_ics_events(&object.data).iter().any(|e| e.uid == uid)))
+    }
+
+    /// Store a new event in the calendar. Fails rather than overwrite when a
+    /// resource with the same name already exists.
+    pub async fn create_event(
+        &self,
+        calendar_url: &str,
+        event: &CalendarEvent,
+    ) -> Result<String, String> {
+        let url = event_url(calendar_url, &event.uid)?;
+        let ics = build_event_ics(event, Utc::now());
+        self.put(&url, ics, ("If-None-Match", "*")).await?;
+        Ok(url)
+    }
+
+    /// Overwrite an existing calendar object, guarded by its ETag when the
+    /// server gave one.
+    pub async fn replace_event(&self, object: &CalendarObject, ics: String) -> Result<(), String> {
+        match &object.etag {
+            Some(etag) => self.put(&object.url, ics, ("If-Match", etag)).await,
+            None => self.put(&object.url, ics, ("If-Match", "*")).await,
+        }
+    }
+
+    async fn put(&self, url: &str, ics: String, condition: (&str, &str)) -> Result<(), String> {
+        let response = self
+            .http
+            .put(url)
+            .basic_auth(&self.username, Some(&self.password))
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .header(condition.0, condition.1)
+            .body(ics)
+            .send()
+            .await
+            .map_err(|e| format!("CalDAV request failed: {}", e))?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::PRECONDITION_FAILED => {
+                Err("The event changed on the server in the meantime; try again".to_string())
+            }
+            status => Err(status_error(status)),
+        }
+    }
+}
+
+fn status_error(status: StatusCode) -> String {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            "CalDAV server rejected the username or password".to_string()
+        }
+        StatusCode::NOT_FOUND => "CalDAV resource not found; check the server URL".to_string(),
+        status => format!("CalDAV server returned {}", status),
+    }
+}
+
+/// Resolve an href from the server against `base`. The result must stay on
+/// the same scheme, host and port: every request carries the user's
+/// credentials, and the URLs are stored and fetched again later.
+pub fn join_url(base: &str, href: &str) -> Result<String, String> {
+    let base = Url::parse(base).map_err(|_| format!("Invalid CalDAV URL: {}", base))?;
+    let url = base
+        .join(href)
+        .map_err(|_| format!("Invalid href from CalDAV server: {}", href))?;
+    if url.origin() != base.origin() {
+        return Err(format!(
+            "CalDAV server pointed to another host ({}); use that URL instead",
+            url.host_str().unwrap_or("unknown")
+        ));
+    }
+    Ok(url.to_string())
+}
+
+/// Resource URL for a new event: the UID, reduced to URL-safe characters.
+fn event_url(calendar_url: &str, uid: &str) -> Result<String, String> {
+    let name: String = uid
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let collection = if calendar_url.ends_with('/') {
+        calendar_url.to_string()
+    } else {
+        format!("{}/", calendar_url)
+    };
+    join_url(&collection, &format!("{}.ics", name))
+}
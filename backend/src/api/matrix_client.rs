@@ -59,7 +59,10 @@ pub enum IncomingMessageContent {
         body: String,
         url: Option<String>,
     },
-    Location,
+    Location {
+        body: String,
+        geo_uri: String,
+    },
     Emote {
         body: String,
     },
@@ -71,12 +74,14 @@ impl IncomingMessageContent {
     /// Extract the body text from the message content
     pub fn body(&self) -> Option<&str> {
         match self {
-            Self::Text { body } | Self::Notice { body } | Self::Emote { body } => Some(body),
+            Self::Text { body }
+            | Self::Notice { body }
+            | Self::Emote { body }
+            | Self::Location { body, .. } => Some(body),
             Self::Image { body, .. }
             | Self::Video { body, .. }
             | Self::Audio { body, .. }
             | Self::File { body, .. } => Some(body),
-            Self::Location => Some("[Location]"),
             Self::Other => None,
         }
     }
@@ -90,7 +95,7 @@ impl IncomingMessageContent {
             Self::Video { .. } => "video",
             Self::Audio { .. } => "audio",
             Self::File { .. } => "file",
-            Self::Location => "location",
+            Self::Location { .. } => "location",
             Self::Emote { .. } => "emote",
             Self::Other => "other",
         }
@@ -306,7 +311,10 @@ impl RoomInterface for RoomWrapper {
                             a.body
                         },
                     ),
-                    MessageType::Location(_) => ("location", "📍 LOCATION".into()),
+                    MessageType::Location(l) => (
+                        "location",
+                        crate::services::shared_location::history_text(&l.body, &l.geo_uri),
+                    ),
                     MessageType::Emote(t) => ("emote", t.body),
                     _ => continue,
                 };
@@ -321,6 +329,7 @@ impl RoomInterface for RoomWrapper {
                     room_name: room_name.clone(),
                     media_url: None,
                     room_id: Some(self.room.room_id().to_string()),
+                    event_id: None,
                 });
             }
         }
@@ -450,7 +459,10 @@ impl IncomingBridgeEvent {
                 body: f.body.clone(),
                 url: extract_media_url(&f.source),
             },
-            MessageType::Location(_) => IncomingMessageContent::Location,
+            MessageType::Location(l) => IncomingMessageContent::Location {
+                body: l.body.clone(),
+                geo_uri: l.geo_uri.clone(),
+            },
             MessageType::Emote(e) => IncomingMessageContent::Emote {
                 body: e.body.clone(),
             },
@@ -788,7 +800,8 @@ pub fn infer_service_from_room(room_name: &str, sender_localpart: &str) -> Optio
     {
         return Some("signal".to_string());
     }
-    None
+    crate::utils::bridgev2_provisioning::infer_network(&room_name, &sender_localpart)
+        .map(|network| network.id.to_string())
 }
 
 // ============================================================================
@@ -909,8 +922,11 @@ mod tests {
         };
         assert_eq!(text.body(), Some("hello"));
 
-        let location = IncomingMessageContent::Location;
-        assert_eq!(location.body(), Some("[Location]"));
+        let location = IncomingMessageContent::Location {
+            body: "Location: Café Regatta".to_string(),
+            geo_uri: "geo:60.18402,24.92200".to_string(),
+        };
+        assert_eq!(location.body(), Some("Location: Café Regatta"));
 
         let other = IncomingMessageContent::Other;
         assert_eq!(other.body(), None);
@@ -934,7 +950,11 @@ mod tests {
             "image"
         );
         assert_eq!(
-            IncomingMessageContent::Location.message_type_str(),
+            IncomingMessageContent::Location {
+                body: String::new(),
+                geo_uri: String::new()
+            }
+            .message_type_str(),
             "location"
         );
     }
@@ -164,6 +164,13 @@ pub trait TwilioClient: Send + Sync {
         media_sid: &str,
     ) -> Result<(), TwilioClientError>;
 
+    /// Download the bytes of inbound MMS media (a `MediaUrlN` webhook URL).
+    async fn fetch_message_media(
+        &self,
+        credentials: &TwilioCredentials,
+        media_url: &str,
+    ) -> Result<Vec<u8>, TwilioClientError>;
+
     /// Fetch the price of a sent message.
     async fn fetch_message_price(
         &self,
@@ -462,6 +469,37 @@ impl TwilioClient for RealTwilioClient {
         Ok(())
     }
 
+    async fn fetch_message_media(
+        &self,
+        credentials: &TwilioCredentials,
+        media_url: &str,
+    ) -> Result<Vec<u8>, TwilioClientError> {
+        // Twilio answers with a redirect to short-lived storage; the auth
+        // header is only needed (and only sent) for the first hop.
+        let response = self
+            .http_client
+            .get(media_url)
+            .basic_auth(&credentials.account_sid, Some(&credentials.auth_token))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(TwilioClientError::ApiError {
+                status: status.as_u16(),
+                message: text,
+            });
+        }
+
+        crate::services::inbound_media::read_capped(
+            response,
+            crate::services::inbound_media::MAX_DOWNLOAD_BYTES,
+        )
+        .await
+        .map_err(|e| TwilioClientError::RequestFailed(e.to_string()))
+    }
+
     async fn fetch_message_price(
         &self,
         credentials: &TwilioCredentials,
@@ -817,6 +855,7 @@ pub mod mock {
         pub send_message_calls: Vec<SendMessageOptions>,
         pub delete_message_calls: Vec<String>,
         pub delete_media_calls: Vec<(String, String)>,
+        pub fetch_media_calls: Vec<String>,
         pub fetch_price_calls: Vec<String>,
         pub configure_webhook_calls: Vec<(String, String)>,
         pub configure_webhook_voice_urls: Vec<Option<String>>,
@@ -831,6 +870,7 @@ pub mod mock {
         pub send_message_result: Mutex<Result<SendMessageResult, String>>,
         pub delete_message_result: Mutex<Result<(), String>>,
         pub delete_media_result: Mutex<Result<(), String>>,
+        pub fetch_media_result: Mutex<Result<Vec<u8>, String>>,
         pub fetch_price_result: Mutex<Result<Option<MessagePrice>, String>>,
         pub configure_webhook_result: Mutex<Result<String, String>>,
         pub phone_config_result: Mutex<Result<IncomingPhoneNumberConfig, TwilioClientError>>,
@@ -853,6 +893,7 @@ pub mod mock {
                 })),
                 delete_message_result: Mutex::new(Ok(())),
                 delete_media_result: Mutex::new(Ok(())),
+                fetch_media_result: Mutex::new(Ok(Vec::new())),
                 fetch_price_result: Mutex::new(Ok(None)),
                 configure_webhook_result: Mutex::new(Ok("PN_mock_sid".to_string())),
                 phone_config_result: Mutex::new(Ok(IncomingPhoneNumberConfig {
@@ -886,6 +927,12 @@ pub mod mock {
             self
         }
 
+        /// Configure the bytes (or error) returned for fetch_message_media calls.
+        pub fn with_fetch_media_result(self, result: Result<Vec<u8>, String>) -> Self {
+            *self.fetch_media_result.lock().unwrap() = result;
+            self
+        }
+
         /// Configure the mock to return an error for fetch_message_price calls.
         pub fn with_fetch_price_error(self, error: String) -> Self {
             *self.fetch_price_result.lock().unwrap() = Err(error);
@@ -1046,6 +1093,23 @@ pub mod mock {
                 .map_err(TwilioClientError::Other)
         }
 
+        async fn fetch_message_media(
+            &self,
+            _credentials: &TwilioCredentials,
+            media_url: &str,
+        ) -> Result<Vec<u8>, TwilioClientError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .fetch_media_calls
+                .push(media_url.to_string());
+            self.fetch_media_result
+                .lock()
+                .unwrap()
+                .clone()
+                .map_err(TwilioClientError::Other)
+        }
+
         async fn fetch_message_price(
             &self,
             _credentials: &TwilioCredentials,
@@ -33,6 +33,8 @@ pub struct NotificationPricing {
     pub inbound_voice_price: f32,
     /// Legacy field - same as regular_message_price for backwards compatibility
     pub calculated_sms_price: f32,
+    /// Raw Twilio price of one outbound SMS segment, before margin
+    pub raw_sms_price: f32,
 }
 
 impl NotificationPricing {
@@ -51,6 +53,7 @@ impl NotificationPricing {
             calculated_voice_price: voice_outbound * VAT_MARGIN_MULTIPLIER,
             inbound_voice_price: voice_inbound * VAT_MARGIN_MULTIPLIER,
             calculated_sms_price: regular,
+            raw_sms_price: sms_price,
         }
     }
 }
@@ -195,6 +195,48 @@ pub struct TwilioWebhookPayload {
     pub media_content_type0: Option<String>,
     #[serde(rename = "MessageSid")]
     pub message_sid: String,
+    /// Every other form parameter. Multi-image MMS puts attachments after
+    /// the first in `MediaUrl1..N` / `MediaContentType1..N`; see `media()`.
+    #[serde(flatten)]
+    pub extra_params: std::collections::HashMap<String, String>,
+}
+
+/// One MMS attachment as announced by the webhook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookMedia {
+    pub url: String,
+    pub content_type: String,
+}
+
+impl TwilioWebhookPayload {
+    /// All attachments in order. Twilio sends up to ten per message; the
+    /// other providers reshape theirs into the same numbered parameters.
+    pub fn media(&self) -> Vec<WebhookMedia> {
+        let count = self
+            .num_media
+            .as_deref()
+            .and_then(|n| n.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        (0..count)
+            .filter_map(|i| {
+                let (url, content_type) = if i == 0 {
+                    (self.media_url0.clone(), self.media_content_type0.clone())
+                } else {
+                    (
+                        self.extra_params.get(&format!("MediaUrl{}", i)).cloned(),
+                        self.extra_params
+                            .get(&format!("MediaContentType{}", i))
+                            .cloned(),
+                    )
+                };
+                Some(WebhookMedia {
+                    url: url?,
+                    content_type: content_type
+                        .unwrap_or_else(|| "application/octet-stream".to_string()),
+                })
+            })
+            .collect()
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -220,6 +262,8 @@ pub enum MessageChannel {
     #[default]
     Sms,
     WebChat,
+    /// The user's Matrix DM room (`channels::matrix_channel`).
+    Matrix,
 }
 
 impl MessageChannel {
@@ -227,12 +271,39 @@ impl MessageChannel {
         matches!(self, Self::Sms)
     }
 
+    /// Replies are pushed to the user rather than returned to the caller.
+    fn pushes_replies(self) -> bool {
+        matches!(self, Self::Sms | Self::Matrix)
+    }
+
+    /// `usage_logs.activity_type` for a pushed reply.
+    fn usage_activity(self) -> &'static str {
+        match self {
+            Self::Matrix => "matrix",
+            _ => "sms",
+        }
+    }
+
     fn agent_mode(self) -> crate::agent_core::ChannelMode {
         match self {
-            Self::Sms => crate::agent_core::ChannelMode::Sms,
+            Self::Sms | Self::Matrix => crate::agent_core::ChannelMode::Sms,
             Self::WebChat => crate::agent_core::ChannelMode::WebChat,
         }
     }
+
+    /// Push a reply to the user on this channel.
+    async fn push_reply(
+        self,
+        state: &Arc<AppState>,
+        user: &crate::models::user_models::User,
+        body: &str,
+    ) -> Result<crate::channels::traits::ChannelMessageId, crate::channels::traits::ChannelError>
+    {
+        match self {
+            Self::Matrix => crate::channels::matrix_channel::send_to_dm(state, user, body).await,
+            _ => state.channel_router.send_to_user(user, body, None).await,
+        }
+    }
 }
 
 /// Options for process_sms to control test behavior
@@ -327,6 +398,7 @@ pub async fn handle_textbee_sms(
         media_url0: None,
         media_content_type0: None,
         message_sid: format!("tb_{}", Utc::now().timestamp()),
+        extra_params: Default::default(),
     };
 
     // Check for STOP command
@@ -404,7 +476,7 @@ pub async fn handle_regular_sms(
 
 pub async fn process_sms(
     state: &Arc<AppState>,
-    payload: TwilioWebhookPayload,
+    mut payload: TwilioWebhookPayload,
     mut options: ProcessSmsOptions,
 ) -> (
     StatusCode,
@@ -427,6 +499,14 @@ pub async fn process_sms(
         payload.from
     );
 
+    // A reply that names a notification by its code ("A7 yes") is routed
+    // to that notification; bare 1/2/3/4 below keep guessing the latest.
+    match early_flow::handle_coded_reply(state, &user, &payload.body, options.channel).await {
+        Some(early_flow::CodedReply::Handled(response)) => return response,
+        Some(early_flow::CodedReply::Rewritten(body)) => payload.body = body,
+        None => {}
+    }
+
     if let Some(response) = early_flow::handle_sms_early_response(
         state,
         &user,
@@ -463,7 +543,9 @@ pub async fn process_sms(
     // Record intent before the external AI action. Normal completion turns
     // this into the durable usage outbox before the response is delivered;
     // a crash leaves an auditable open intent instead of silent revenue loss.
-    let billing_intent = if options.channel == MessageChannel::WebChat
+    // Matrix replies have no carrier callback to bill from, so they meter
+    // like web chat.
+    let billing_intent = if !options.channel.sends_sms()
         && crate::services::metronome_billing::metronome_enabled()
     {
         match crate::services::metronome_billing::begin_usage_intent(state, user.id, "web_chat") {
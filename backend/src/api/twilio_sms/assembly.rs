@@ -1,6 +1,7 @@
-use super::{MessageChannel, TwilioWebhookPayload};
+use super::{MessageChannel, TwilioWebhookPayload, WebhookMedia};
 use crate::context::{AgentContext, ContextBuilder, ContextError};
 use crate::models::user_models::User;
+use crate::services::inbound_media::{self, InboundImage};
 use crate::tool_call_utils::utils::ChatMessage;
 use crate::AppState;
 use openai_api_rs::v1::chat_completion;
@@ -27,11 +28,13 @@ pub(super) async fn build_sms_agent_input(
     let user_given_info = ctx.user_given_info.clone().unwrap_or_default();
     let mut chat_messages = build_initial_messages(&ctx, channel);
     let processed_body = strip_forget_prefix(&payload.body);
-    delete_incoming_media_if_present(state, user, payload).await;
+    let media = payload.media();
+    let images = inbound_media::process_inbound_images(state, user, &media).await;
+    delete_incoming_media(state, user, &media).await;
 
     add_history_messages(&mut chat_messages, &ctx);
 
-    let image_url = add_current_user_message(&mut chat_messages, payload, &processed_body);
+    let image_url = add_current_user_message(&mut chat_messages, &images, &processed_body);
     let tools = crate::agent_core::build_tools(state, user.id, true).await;
     let completion_messages = to_completion_messages(chat_messages);
 
@@ -117,35 +120,28 @@ fn strip_forget_prefix(body: &str) -> String {
     }
 }
 
-async fn delete_incoming_media_if_present(
-    state: &Arc<AppState>,
-    user: &User,
-    payload: &TwilioWebhookPayload,
-) {
-    if let (Some(num_media), Some(media_url), Some(_)) = (
-        payload.num_media.as_ref(),
-        payload.media_url0.as_ref(),
-        payload.media_content_type0.as_ref(),
-    ) {
-        if num_media != "0" {
-            if let (Some(msg_part), Some(media_sid)) = (
-                media_url.split("/Messages/").nth(1),
-                media_url.split("/Media/").nth(1),
-            ) {
-                if let Some(message_sid) = msg_part.split("/Media/").next() {
-                    tracing::debug!(
-                        "Attempting to delete media {} from message {}",
-                        media_sid,
-                        message_sid
-                    );
-                    match state
-                        .twilio_message_service
-                        .delete_message_media(user, message_sid, media_sid)
-                        .await
-                    {
-                        Ok(_) => tracing::debug!("Successfully deleted media: {}", media_sid),
-                        Err(e) => tracing::error!("Failed to delete media {}: {}", media_sid, e),
-                    }
+/// Delete every attachment from the provider once the pipeline has its own
+/// copy. Only Twilio keeps inbound media around; other providers' URLs
+/// don't match and are left to expire.
+async fn delete_incoming_media(state: &Arc<AppState>, user: &User, media: &[WebhookMedia]) {
+    for item in media {
+        if let (Some(msg_part), Some(media_sid)) = (
+            item.url.split("/Messages/").nth(1),
+            item.url.split("/Media/").nth(1),
+        ) {
+            if let Some(message_sid) = msg_part.split("/Media/").next() {
+                tracing::debug!(
+                    "Attempting to delete media {} from message {}",
+                    media_sid,
+                    message_sid
+                );
+                match state
+                    .twilio_message_service
+                    .delete_message_media(user, message_sid, media_sid)
+                    .await
+                {
+                    Ok(_) => tracing::debug!("Successfully deleted media: {}", media_sid),
+                    Err(e) => tracing::error!("Failed to delete media {}: {}", media_sid, e),
                 }
             }
         }
@@ -171,42 +167,47 @@ fn add_history_messages(chat_messages: &mut Vec<ChatMessage>, ctx: &AgentContext
     }
 }
 
+/// Returns the first photo's data URL, which tools like `scan_qr_code`
+/// and `send_chat_message` pick up as the message's image.
 fn add_current_user_message(
     chat_messages: &mut Vec<ChatMessage>,
-    payload: &TwilioWebhookPayload,
+    images: &[InboundImage],
     processed_body: &str,
 ) -> Option<String> {
-    if let (Some(num_media), Some(media_url), Some(content_type)) = (
-        payload.num_media.as_ref(),
-        payload.media_url0.as_ref(),
-        payload.media_content_type0.as_ref(),
-    ) {
-        if num_media != "0" && content_type.starts_with("image/") {
-            let image_url = Some(media_url.clone());
-            tracing::debug!("setting image_url var to: {:#?}", image_url);
-            chat_messages.push(ChatMessage {
-                role: "user".to_string(),
-                content: chat_completion::Content::ImageUrl(build_image_content(
-                    processed_body,
-                    media_url,
-                )),
-                tool_calls: None,
-                tool_call_id: None,
-            });
-            return image_url;
-        }
+    if images.is_empty() {
+        chat_messages.push(ChatMessage {
+            role: "user".to_string(),
+            content: chat_completion::Content::Text(processed_body.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+        });
+        return None;
     }
 
+    let data_urls: Vec<String> = images.iter().filter_map(InboundImage::data_url).collect();
+    tracing::debug!(
+        "Attaching {} of {} inbound photos to the user message",
+        data_urls.len(),
+        images.len()
+    );
     chat_messages.push(ChatMessage {
         role: "user".to_string(),
-        content: chat_completion::Content::Text(processed_body.to_string()),
+        content: chat_completion::Content::ImageUrl(build_image_content(
+            processed_body,
+            &inbound_media::describe_for_model(images),
+            &data_urls,
+        )),
         tool_calls: None,
         tool_call_id: None,
     });
-    None
+    data_urls.into_iter().next()
 }
 
-fn build_image_content(processed_body: &str, media_url: &str) -> Vec<chat_completion::ImageUrl> {
+fn build_image_content(
+    processed_body: &str,
+    image_context: &str,
+    data_urls: &[String],
+) -> Vec<chat_completion::ImageUrl> {
     let mut content_parts = vec![];
 
     if !processed_body.trim().is_empty() {
@@ -218,13 +219,19 @@ fn build_image_content(processed_body: &str, media_url: &str) -> Vec<chat_comple
     }
 
     content_parts.push(chat_completion::ImageUrl {
-        r#type: chat_completion::ContentType::image_url,
-        text: None,
-        image_url: Some(chat_completion::ImageUrlType {
-            url: media_url.to_string(),
-        }),
+        r#type: chat_completion::ContentType::text,
+        text: Some(image_context.to_string()),
+        image_url: None,
     });
 
+    for url in data_urls {
+        content_parts.push(chat_completion::ImageUrl {
+            r#type: chat_completion::ContentType::image_url,
+            text: None,
+            image_url: Some(chat_completion::ImageUrlType { url: url.clone() }),
+        });
+    }
+
     content_parts
 }
 
@@ -2,6 +2,7 @@ use super::{MessageChannel, SmsProcessResponse, TwilioResponse, TwilioWebhookPay
 use crate::channels::traits::MediaRef;
 use crate::models::user_models::User;
 use crate::repositories::user_repository::LogUsageParams;
+use crate::utils::sms_encoding::outbound_sms_segments;
 use crate::AppState;
 use axum::{http::StatusCode, Json};
 use std::cell::RefCell;
@@ -26,6 +27,10 @@ pub(super) struct DeliverSmsResponseInput<'a> {
 pub(super) async fn deliver_sms_response(input: DeliverSmsResponseInput<'_>) -> SmsProcessResponse {
     persist_assistant_history(input.state, input.user.id, &input.history_for_storage);
 
+    if input.channel == MessageChannel::Matrix {
+        return deliver_matrix_reply(input).await;
+    }
+
     if !input.channel.sends_sms() {
         log_web_chat_usage(input.state, input.user.id, input.processing_time_secs);
         return (
@@ -84,6 +89,7 @@ fn log_web_chat_usage(state: &Arc<AppState>, user_id: i32, processing_time_secs:
         status: None,
         recharge_threshold_timestamp: None,
         zero_credits_timestamp: None,
+        segments: None,
     }) {
         tracing::error!("Failed to log test SMS usage: {}", e);
     }
@@ -106,6 +112,7 @@ async fn deliver_real_sms(input: DeliverSmsResponseInput<'_>) -> SmsProcessRespo
                 input.state,
                 input.user.id,
                 Some(message_sid),
+                Some(outbound_sms_segments(input.user, &clean_response)),
                 input.processing_time_secs,
                 None,
             );
@@ -126,6 +133,7 @@ async fn deliver_real_sms(input: DeliverSmsResponseInput<'_>) -> SmsProcessRespo
                 input.state,
                 input.user.id,
                 None,
+                None,
                 input.processing_time_secs,
                 Some(error_status),
             );
@@ -142,6 +150,57 @@ async fn deliver_real_sms(input: DeliverSmsResponseInput<'_>) -> SmsProcessRespo
     }
 }
 
+/// Replies to the Matrix DM room. Media SIDs only exist on the SMS path, so
+/// the room gets the text alone.
+async fn deliver_matrix_reply(input: DeliverSmsResponseInput<'_>) -> SmsProcessResponse {
+    let (clean_response, _) = extract_media_sids(&input.response_for_delivery);
+    let sent = input
+        .channel
+        .push_reply(input.state, input.user, &clean_response)
+        .await;
+    let (sid, error_status) = match sent {
+        Ok(event_id) => (Some(event_id.into_inner()), None),
+        Err(e) => {
+            tracing::error!(
+                "Failed to send Matrix reply to user {}: {}",
+                input.user.id,
+                e
+            );
+            (None, Some(format!("failed to send: {}", e)))
+        }
+    };
+    let failed = error_status.is_some();
+    if let Err(e) = input.state.user_repository.log_usage(LogUsageParams {
+        user_id: input.user.id,
+        sid,
+        activity_type: input.channel.usage_activity().to_string(),
+        credits: None,
+        time_consumed: Some(input.processing_time_secs as i32),
+        success: failed.then_some(false),
+        reason: None,
+        status: error_status,
+        recharge_threshold_timestamp: None,
+        zero_credits_timestamp: None,
+        segments: None,
+    }) {
+        tracing::error!("Failed to log Matrix reply usage: {}", e);
+    }
+
+    let (status, message) = if failed {
+        (StatusCode::INTERNAL_SERVER_ERROR, "Failed to send message")
+    } else {
+        (StatusCode::OK, "Message sent successfully")
+    };
+    (
+        status,
+        [(axum::http::header::CONTENT_TYPE, "application/json")],
+        Json(TwilioResponse {
+            message: message.to_string(),
+            created_item_id: None,
+        }),
+    )
+}
+
 fn extract_media_sids(response: &str) -> (String, Vec<String>) {
     let mut media_sids = Vec::new();
     let clean_response = response
@@ -185,6 +244,7 @@ fn log_sms_usage(
     state: &Arc<AppState>,
     user_id: i32,
     sid: Option<String>,
+    segments: Option<i32>,
     processing_time_secs: u64,
     error_status: Option<String>,
 ) {
@@ -205,6 +265,7 @@ fn log_sms_usage(
         status: error_status,
         recharge_threshold_timestamp: None,
         zero_credits_timestamp: None,
+        segments,
     }) {
         if success == Some(false) {
             tracing::error!("Failed to log SMS usage after send error: {}", e);
@@ -55,7 +55,7 @@ fn send_early_reply_if_needed(
     channel: MessageChannel,
     failure_context: &'static str,
 ) {
-    if !channel.sends_sms() {
+    if !channel.pushes_replies() {
         return;
     }
 
@@ -63,9 +63,8 @@ fn send_early_reply_if_needed(
     let user_clone = user.clone();
     let reply_clone = reply.to_string();
     tokio::spawn(async move {
-        if let Err(e) = state_clone
-            .channel_router
-            .send_to_user(&user_clone, &reply_clone, None)
+        if let Err(e) = channel
+            .push_reply(&state_clone, &user_clone, &reply_clone)
             .await
         {
             tracing::error!(
@@ -82,6 +81,7 @@ fn send_cancel_reply_in_background(
     state: &Arc<AppState>,
     user: &User,
     response_msg: &str,
+    channel: MessageChannel,
     start_time: std::time::Instant,
 ) {
     let state_clone = state.clone();
@@ -89,9 +89,8 @@ fn send_cancel_reply_in_background(
     let response_msg_clone = response_msg.to_string();
 
     tokio::spawn(async move {
-        match state_clone
-            .channel_router
-            .send_to_user(&user_clone, &response_msg_clone, None)
+        match channel
+            .push_reply(&state_clone, &user_clone, &response_msg_clone)
             .await
         {
             Ok(message_sid) => {
@@ -100,7 +99,7 @@ fn send_cancel_reply_in_background(
                 if let Err(e) = state_clone.user_repository.log_usage(LogUsageParams {
                     user_id: user_clone.id,
                     sid: Some(message_sid.clone()),
-                    activity_type: "sms".to_string(),
+                    activity_type: channel.usage_activity().to_string(),
                     credits: None,
                     time_consumed: Some(processing_time_secs as i32),
                     success: Some(true),
@@ -108,6 +107,7 @@ fn send_cancel_reply_in_background(
                     status: None,
                     recharge_threshold_timestamp: None,
                     zero_credits_timestamp: None,
+                    segments: None,
                 }) {
                     tracing::error!("Failed to log SMS usage for cancel: {}", e);
                 }
@@ -120,7 +120,7 @@ fn send_cancel_reply_in_background(
                 if let Err(log_err) = state_clone.user_repository.log_usage(LogUsageParams {
                     user_id: user_clone.id,
                     sid: None,
-                    activity_type: "sms".to_string(),
+                    activity_type: channel.usage_activity().to_string(),
                     credits: None,
                     time_consumed: Some(processing_time_secs as i32),
                     success: Some(false),
@@ -128,6 +128,7 @@ fn send_cancel_reply_in_background(
                     status: Some(error_status),
                     recharge_threshold_timestamp: None,
                     zero_credits_timestamp: None,
+                    segments: None,
                 }) {
                     tracing::error!(
                         "Failed to log SMS usage after send error for cancel: {}",
@@ -186,11 +187,12 @@ async fn handle_cancel_reply(
                 "Couldn't find a message to cancel".to_string()
             };
 
-            if channel.sends_sms() {
+            if channel.pushes_replies() {
                 send_cancel_reply_in_background(
                     state,
                     user,
                     &response_msg,
+                    channel,
                     std::clone::Clone::clone(start_time),
                 );
             }
@@ -214,6 +216,32 @@ async fn handle_cancel_reply(
     }
 }
 
+/// Outcome of an SMS that names a notification by its reply code.
+pub(super) enum CodedReply {
+    Handled(SmsProcessResponse),
+    /// Continue to the agent with this body, which quotes the notification.
+    Rewritten(String),
+}
+
+pub(super) async fn handle_coded_reply(
+    state: &Arc<AppState>,
+    user: &User,
+    body: &str,
+    channel: MessageChannel,
+) -> Option<CodedReply> {
+    use crate::proactive::reply_context::ReplyResolution;
+
+    match crate::proactive::reply_context::try_resolve(state, user, body).await? {
+        ReplyResolution::Handled(reply) => {
+            send_early_reply_if_needed(state, user, &reply, channel, "reply-code confirmation");
+            Some(CodedReply::Handled(
+                SmsResult::Success { response: reply }.into_response(),
+            ))
+        }
+        ReplyResolution::Agent(rewritten) => Some(CodedReply::Rewritten(rewritten)),
+    }
+}
+
 pub(super) async fn handle_sms_early_response(
     state: &Arc<AppState>,
     user: &User,
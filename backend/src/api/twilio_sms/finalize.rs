@@ -1,5 +1,8 @@
 use super::{llm_call_with_gateway, status, ChatStatus};
-use crate::{AiChatOptions, AiProvider, AppState, ModelPurpose};
+use crate::utils::sms_encoding::{
+    gsm7_chars_for_segments, max_chars_within_segments, segment_count, transliterate_to_gsm7,
+};
+use crate::{AiChatOptions, AiProvider, AppState, ModelPurpose, UserCoreOps};
 use openai_api_rs::v1::chat_completion;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -226,7 +229,8 @@ pub(super) struct SmsResponse {
 }
 
 impl SmsResponse {
-    const MAX_LENGTH: usize = 480;
+    /// Replies that would take more SMS parts than this are condensed.
+    const MAX_SEGMENTS: usize = 3;
 
     async fn new(
         raw: String,
@@ -234,10 +238,28 @@ impl SmsResponse {
         user_id: i32,
         sticky_provider: Option<AiProvider>,
     ) -> Self {
-        let (content, provider_cost_usd) = if raw.chars().count() > Self::MAX_LENGTH {
-            condense_response(state, &raw, Self::MAX_LENGTH, user_id, sticky_provider)
-                .await
-                .unwrap_or_else(|_| (truncate_nicely(&raw, Self::MAX_LENGTH), 0.0))
+        // Measure the text the router will actually send: users who opted
+        // in get it folded to GSM-7 there, so fold it here first.
+        let transliterate = matches!(
+            state.user_core.find_by_id(user_id),
+            Ok(Some(user)) if user.sms_gsm7_transliteration
+        );
+        let raw = if transliterate {
+            transliterate_to_gsm7(&raw)
+        } else {
+            raw
+        };
+        let (content, provider_cost_usd) = if segment_count(&raw) > Self::MAX_SEGMENTS {
+            condense_response(
+                state,
+                &raw,
+                Self::MAX_SEGMENTS,
+                transliterate,
+                user_id,
+                sticky_provider,
+            )
+            .await
+            .unwrap_or_else(|_| (truncate_to_segments(&raw, Self::MAX_SEGMENTS), 0.0))
         } else {
             (raw, 0.0)
         };
@@ -248,13 +270,8 @@ impl SmsResponse {
     }
 
     fn truncated(raw: String) -> Self {
-        let content = if raw.chars().count() > Self::MAX_LENGTH {
-            truncate_nicely(&raw, Self::MAX_LENGTH)
-        } else {
-            raw
-        };
         Self {
-            content,
+            content: truncate_to_segments(&raw, Self::MAX_SEGMENTS),
             provider_cost_usd: 0.0,
         }
     }
@@ -264,6 +281,12 @@ impl SmsResponse {
     }
 }
 
+/// Cut `text` down to `max_segments` SMS parts in whatever encoding it
+/// needs, at a sentence or word boundary where possible.
+fn truncate_to_segments(text: &str, max_segments: usize) -> String {
+    truncate_nicely(text, max_chars_within_segments(text, max_segments))
+}
+
 fn truncate_nicely(text: &str, max_chars: usize) -> String {
     if text.chars().count() <= max_chars {
         return text.to_string();
@@ -297,7 +320,8 @@ fn truncate_nicely(text: &str, max_chars: usize) -> String {
 async fn condense_response(
     state: &Arc<AppState>,
     original: &str,
-    max_chars: usize,
+    max_segments: usize,
+    transliterate: bool,
     user_id: i32,
     sticky_provider: Option<AiProvider>,
 ) -> Result<(String, f64), String> {
@@ -306,10 +330,13 @@ async fn condense_response(
     };
 
     let prompt = format!(
-        "Condense the following message to fit within {} characters while preserving the key information. \
+        "Condense the following message to fit within {} SMS segments ({} characters) while preserving the key information. \
+        Use only plain keyboard characters: a single emoji, curly quote or special symbol cuts the space to less than half. \
         Keep it natural and conversational. Do NOT use markdown, bullets, or special formatting. \
         Just output the condensed message, nothing else.\n\nOriginal message:\n{}",
-        max_chars, original
+        max_segments,
+        gsm7_chars_for_segments(max_segments),
+        original
     );
 
     let req = ChatCompletionRequest::new(
@@ -341,10 +368,14 @@ async fn condense_response(
         Ok(result) => {
             if let Some(choice) = result.response.choices.first() {
                 if let Some(content) = &choice.message.content {
-                    let condensed = content.trim().to_string();
-                    if condensed.chars().count() > max_chars {
+                    let condensed = if transliterate {
+                        transliterate_to_gsm7(content.trim())
+                    } else {
+                        content.trim().to_string()
+                    };
+                    if segment_count(&condensed) > max_segments {
                         return Ok((
-                            truncate_nicely(&condensed, max_chars),
+                            truncate_to_segments(&condensed, max_segments),
                             result.provider_cost_usd,
                         ));
                     }
@@ -51,7 +51,7 @@ fn mulaw_decode(encoded: u8) -> i16 {
 }
 
 /// ITU-T G.711 mu-law encode
-fn mulaw_encode(sample: i16) -> u8 {
+pub(crate) fn mulaw_encode(sample: i16) -> u8 {
     const BIAS: i16 = 0x84;
     const CLIP: i16 = 32635;
 
@@ -517,25 +517,25 @@ fn twiml_reject() -> Response {
 // Outbound notification call
 // ---------------------------------------------------------------------------
 
-/// Initiate an outbound voice call to a user using Twilio + our voice pipeline.
-/// The call connects to our WebSocket and speaks the notification message as the greeting.
+/// Call the user and speak `notification_message` as the greeting. The
+/// provider comes from the user's country voice route on the channel router
+/// (Twilio by default); every provider streams into this pipeline.
 pub async fn make_notification_call(
     state: &Arc<AppState>,
     user: &crate::models::user_models::User,
     notification_message: &str,
 ) -> Result<String, String> {
-    make_notification_call_with_from(state, user, notification_message, None).await
+    state
+        .channel_router
+        .place_call(user, notification_message)
+        .await
+        .map(|call_id| call_id.into_inner())
+        .map_err(|e| format!("Voice call failed: {}", e))
 }
 
-/// Same as `make_notification_call`, but optionally uses an explicit FROM
-/// number instead of the user's preferred number. Used by the US voice
-/// fallback path when the primary outbound number is blocked by carriers.
-pub async fn make_notification_call_with_from(
-    state: &Arc<AppState>,
-    user: &crate::models::user_models::User,
-    notification_message: &str,
-    from_override: Option<&str>,
-) -> Result<String, String> {
+/// TwiML that connects an answered Twilio call to our media stream, with
+/// `greeting` spoken first.
+pub fn notification_call_twiml(user_id: i32, greeting: &str) -> String {
     let server_url =
         std::env::var("SERVER_URL").unwrap_or_else(|_| "https://localhost:3000".to_string());
     let ws_url = server_url
@@ -543,10 +543,10 @@ pub async fn make_notification_call_with_from(
         .replace("http://", "ws://");
 
     // XML-escape then URL-encode the greeting for safe TwiML inclusion
-    let greeting_escaped = xml_escape(notification_message);
+    let greeting_escaped = xml_escape(greeting);
     let greeting_encoded = urlencoding::encode(&greeting_escaped);
 
-    let twiml = format!(
+    format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
 <Response>
   <Connect>
@@ -556,35 +556,32 @@ pub async fn make_notification_call_with_from(
     </Stream>
   </Connect>
 </Response>"#,
-        ws_url, user.id, greeting_encoded
-    );
-
-    // Get credentials and from number
-    let credentials = state
-        .twilio_message_service
-        .resolve_credentials(user)
-        .map_err(|e| format!("Failed to resolve Twilio credentials: {}", e))?;
-
-    let from_number = match from_override {
-        Some(f) => f.to_string(),
-        None => user
-            .preferred_number
-            .clone()
-            .ok_or_else(|| "No from number available for voice call".to_string())?,
-    };
+        ws_url, user_id, greeting_encoded
+    )
+}
 
-    // Initiate the call
-    use crate::api::twilio_client::TwilioClient;
+/// Place a Twilio notification call, optionally from an explicit FROM
+/// number instead of the user's preferred number. Used by the US voice
+/// fallback path when the primary outbound number is blocked by carriers.
+pub async fn make_notification_call_with_from(
+    state: &Arc<AppState>,
+    user: &crate::models::user_models::User,
+    notification_message: &str,
+    from_override: Option<&str>,
+) -> Result<String, String> {
+    let twiml = notification_call_twiml(user.id, notification_message);
     let call_sid = state
-        .twilio_client
-        .make_call(&credentials, &user.phone_number, &from_number, &twiml)
+        .twilio_message_service
+        .place_call(user, &twiml, from_override)
         .await
         .map_err(|e| format!("Twilio call failed: {}", e))?;
 
     tracing::info!(
         "Outbound notification call initiated for user {} from {}",
         user.id,
-        from_number
+        from_override
+            .or(user.preferred_number.as_deref())
+            .unwrap_or_default()
     );
 
     Ok(call_sid)
@@ -947,7 +944,7 @@ pub async fn voice_ws(State(state): State<Arc<AppState>>, ws: WebSocketUpgrade)
 async fn handle_voice_ws(state: Arc<AppState>, socket: WebSocket) {
     use futures::stream::StreamExt;
 
-    let (ws_tx, mut ws_rx) = socket.split();
+    let (ws_tx, ws_rx) = socket.split();
 
     // Channel for sending messages back through the WebSocket
     let (send_tx, send_rx) = mpsc::channel::<Message>(64);
@@ -955,6 +952,21 @@ async fn handle_voice_ws(state: Arc<AppState>, socket: WebSocket) {
     // Sender task: drains send_rx -> ws_tx
     tokio::spawn(sender_loop(ws_tx, send_rx));
 
+    run_media_stream(state, ws_rx, send_tx).await;
+}
+
+/// The phone call loop over Twilio media-stream events. Telnyx and Sinch
+/// calls arrive here too, translated to the same framing by
+/// `voice_stream_bridge`.
+pub(crate) async fn run_media_stream<S>(
+    state: Arc<AppState>,
+    mut ws_rx: S,
+    send_tx: mpsc::Sender<Message>,
+) where
+    S: futures::Stream<Item = Result<Message, axum::Error>> + Unpin + Send,
+{
+    use futures::stream::StreamExt;
+
     let mut session: Option<CallSession> = None;
 
     while let Some(msg) = ws_rx.next().await {
@@ -1924,15 +1936,17 @@ async fn start_openai_realtime_session(
     Ok(openai_tx)
 }
 
-async fn handle_openai_twilio_session(
+async fn handle_openai_twilio_session<S>(
     state: Arc<AppState>,
     send_tx: &mpsc::Sender<Message>,
-    ws_rx: &mut futures::stream::SplitStream<WebSocket>,
+    ws_rx: &mut S,
     user_id: i32,
     stream_sid: String,
     custom_greeting: Option<String>,
     is_outbound: bool,
-) {
+) where
+    S: futures::Stream<Item = Result<Message, axum::Error>> + Unpin + Send,
+{
     use futures::stream::StreamExt;
 
     let greeting = custom_greeting.unwrap_or_else(|| build_greeting(&state, user_id));
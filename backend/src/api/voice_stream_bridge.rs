@@ -0,0 +1,549 @@
+//! Media-stream bridge for calls placed through Telnyx and Sinch.
+//!
+//! The voice pipeline speaks Twilio's media-stream protocol: JSON `start`,
+//! `media` (base64 mu-law, 8kHz), `mark` and `stop` events in, `media`,
+//! `mark` and `clear` events out. Rather than teach every pipeline path a
+//! second and third framing, the provider sockets are translated at the
+//! edge and handed to `voice_pipeline::run_media_stream` unchanged:
+//!
+//! - Telnyx bidirectional streaming is already JSON with base64 PCMU
+//!   payloads; only the envelope differs.
+//! - Sinch `connectStream` sends one JSON `websocket:connected` message and
+//!   then raw 16-bit little-endian PCM in binary frames, both directions.
+//!
+//! Neither provider echoes Twilio-style marks back, so the bridge replays
+//! each mark once the audio queued before it should have finished playing.
+//!
+//! Twilio passes the user and greeting as TwiML stream parameters. Telnyx
+//! and Sinch only get a URL, so it carries an opaque one-time token, the
+//! same way the web voice WebSocket does. The user and greeting stay in
+//! memory until the provider opens the stream, which consumes the ticket;
+//! unused tickets lapse after `STREAM_TICKET_TTL_SECONDS` (long enough for
+//! the phone to ring out).
+
+use crate::api::voice_pipeline::{mulaw_encode, run_media_stream};
+use crate::AppState;
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{Query, State, WebSocketUpgrade};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use dashmap::DashMap;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How long a stream URL stays valid after the call is placed.
+pub const STREAM_TICKET_TTL_SECONDS: i64 = 600;
+
+/// The pipeline and both bridges run at the telephone rate.
+const PIPELINE_RATE: u32 = 8000;
+
+/// 20ms of audio at `PIPELINE_RATE`, the frame size sent to Sinch.
+const SINCH_FRAME_SAMPLES: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamProvider {
+    Telnyx,
+    Sinch,
+}
+
+impl StreamProvider {
+    pub fn id(self) -> &'static str {
+        match self {
+            StreamProvider::Telnyx => "telnyx",
+            StreamProvider::Sinch => "sinch",
+        }
+    }
+
+    fn ws_path(self) -> &'static str {
+        match self {
+            StreamProvider::Telnyx => "/api/voice/telnyx/ws",
+            StreamProvider::Sinch => "/api/voice/sinch/ws",
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// One-time stream URLs
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+pub struct StreamParams {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamTicket {
+    pub user_id: i32,
+    pub greeting: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StreamTicketError {
+    Expired,
+    Invalid,
+}
+
+struct PendingStream {
+    provider: StreamProvider,
+    ticket: StreamTicket,
+    expires: i64,
+}
+
+/// Issues and consumes the WebSocket URLs handed to Telnyx and Sinch.
+/// Clones share the pending tickets, so the voice channels and the
+/// WebSocket endpoints (through `AppState`) hold the same store.
+#[derive(Clone)]
+pub struct StreamTickets {
+    ws_base: String,
+    pending: Arc<DashMap<String, PendingStream>>,
+}
+
+impl StreamTickets {
+    /// `SERVER_URL` defaults the same way as the Twilio stream URL does.
+    pub fn from_env() -> Self {
+        let server_url =
+            std::env::var("SERVER_URL").unwrap_or_else(|_| "https://localhost:3000".to_string());
+        Self::new(&server_url)
+    }
+
+    pub fn new(server_url: &str) -> Self {
+        let ws_base = server_url
+            .trim_end_matches('/')
+            .replace("https://", "wss://")
+            .replace("http://", "ws://");
+        Self {
+            ws_base,
+            pending: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Store a ticket for `user_id` and return the stream URL that redeems
+    /// it. Tickets that lapsed unused are dropped here.
+    pub fn stream_url(
+        &self,
+        provider: StreamProvider,
+        user_id: i32,
+        greeting: &str,
+        now: i64,
+    ) -> String {
+        self.pending.retain(|_, pending| pending.expires > now);
+        let token = uuid::Uuid::new_v4().to_string();
+        self.pending.insert(
+            token.clone(),
+            PendingStream {
+                provider,
+                ticket: StreamTicket {
+                    user_id,
+                    greeting: greeting.to_string(),
+                },
+                expires: now + STREAM_TICKET_TTL_SECONDS,
+            },
+        );
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("token", &token)
+            .finish();
+        format!("{}{}?{}", self.ws_base, provider.ws_path(), query)
+    }
+
+    /// Atomically consumes a ticket so the URL cannot be replayed. A token
+    /// presented on the other provider's endpoint is consumed too.
+    pub fn consume(
+        &self,
+        provider: StreamProvider,
+        token: &str,
+        now: i64,
+    ) -> Result<StreamTicket, StreamTicketError> {
+        match self.pending.remove(token) {
+            Some((_, pending)) if pending.provider != provider => Err(StreamTicketError::Invalid),
+            Some((_, pending)) if pending.expires > now => Ok(pending.ticket),
+            Some(_) => Err(StreamTicketError::Expired),
+            None => Err(StreamTicketError::Invalid),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Framing
+// ---------------------------------------------------------------------------
+
+/// A provider event, reduced to what the pipeline consumes.
+#[derive(Debug, PartialEq)]
+pub enum InboundFrame {
+    Start {
+        stream_id: String,
+        call_id: String,
+    },
+    /// Caller audio as 8kHz mu-law.
+    Media(Vec<u8>),
+    Stop,
+    Ignore,
+}
+
+/// Pipeline output, parsed back out of its Twilio framing.
+#[derive(Debug, PartialEq)]
+pub enum OutboundFrame {
+    /// 8kHz mu-law to play to the caller.
+    Media(Vec<u8>),
+    Mark(String),
+    Clear,
+}
+
+/// Parse one Telnyx media-streaming message.
+pub fn parse_telnyx_frame(text: &str) -> InboundFrame {
+    let Ok(event) = serde_json::from_str::<serde_json::Value>(text) else {
+        tracing::warn!("Failed to parse Telnyx stream message");
+        return InboundFrame::Ignore;
+    };
+    match event["event"].as_str().unwrap_or("") {
+        "start" => {
+            let encoding = event["start"]["media_format"]["encoding"]
+                .as_str()
+                .unwrap_or("PCMU");
+            if !encoding.eq_ignore_ascii_case("PCMU") {
+                tracing::warn!("Telnyx stream started with {} instead of PCMU", encoding);
+            }
+            InboundFrame::Start {
+                stream_id: event["stream_id"].as_str().unwrap_or_default().to_string(),
+                call_id: event["start"]["call_control_id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            }
+        }
+        "media" => {
+            // Only the caller's leg is streamed, but stay safe if both are.
+            if event["media"]["track"].as_str().unwrap_or("inbound") != "inbound" {
+                return InboundFrame::Ignore;
+            }
+            match event["media"]["payload"]
+                .as_str()
+                .and_then(|payload| BASE64.decode(payload).ok())
+            {
+                Some(audio) => InboundFrame::Media(audio),
+                None => InboundFrame::Ignore,
+            }
+        }
+        "stop" => InboundFrame::Stop,
+        "error" => {
+            tracing::warn!("Telnyx stream error: {}", event["payload"]);
+            InboundFrame::Ignore
+        }
+        _ => InboundFrame::Ignore,
+    }
+}
+
+/// Sample rate from Sinch's `websocket:connected` message, e.g.
+/// `"contentType": "audio/l16;rate=8000"`.
+pub fn sinch_stream_rate(text: &str) -> Option<u32> {
+    let event: serde_json::Value = serde_json::from_str(text).ok()?;
+    event["contentType"]
+        .as_str()?
+        .split(';')
+        .find_map(|part| part.trim().strip_prefix("rate="))?
+        .parse()
+        .ok()
+}
+
+/// How many provider samples make up one pipeline sample. Sinch is asked
+/// for 8kHz; anything that isn't a whole multiple of it is treated as 8kHz.
+fn rate_factor(rate: u32) -> usize {
+    if rate >= PIPELINE_RATE && rate % PIPELINE_RATE == 0 {
+        (rate / PIPELINE_RATE) as usize
+    } else {
+        1
+    }
+}
+
+/// Sinch L16 audio to the pipeline's 8kHz mu-law.
+pub fn sinch_audio_to_mulaw(bytes: &[u8], rate: u32) -> Vec<u8> {
+    let samples: Vec<i16> = bytes
+        .chunks_exact(2)
+        .map(|c| i16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    samples
+        .chunks(rate_factor(rate))
+        .map(|group| {
+            let sum: i32 = group.iter().map(|&s| s as i32).sum();
+            mulaw_encode((sum / group.len() as i32) as i16)
+        })
+        .collect()
+}
+
+/// Standard G.711 mu-law expansion, the exact inverse of `mulaw_encode`.
+/// `voice_pipeline::mulaw_decode` is not, so it isn't used for audio the
+/// caller hears.
+fn pcmu_to_linear(byte: u8) -> i16 {
+    let u = !byte;
+    let t = (((u & 0x0f) as i32) << 3) + 0x84;
+    let t = t << ((u & 0x70) >> 4);
+    (if u & 0x80 != 0 { 0x84 - t } else { t - 0x84 }) as i16
+}
+
+/// Pipeline mu-law to Sinch L16 frames of 20ms each.
+pub fn sinch_audio_frames(mulaw: &[u8], rate: u32) -> Vec<Vec<u8>> {
+    let factor = rate_factor(rate);
+    mulaw
+        .chunks(SINCH_FRAME_SAMPLES)
+        .map(|chunk| {
+            let mut frame = Vec::with_capacity(chunk.len() * factor * 2);
+            for &byte in chunk {
+                let sample = pcmu_to_linear(byte).to_le_bytes();
+                for _ in 0..factor {
+                    frame.extend_from_slice(&sample);
+                }
+            }
+            frame
+        })
+        .collect()
+}
+
+/// Parse one message the pipeline sent towards Twilio.
+pub fn parse_pipeline_frame(text: &str) -> Option<OutboundFrame> {
+    let event: serde_json::Value = serde_json::from_str(text).ok()?;
+    match event["event"].as_str()? {
+        "media" => BASE64
+            .decode(event["media"]["payload"].as_str()?)
+            .ok()
+            .map(OutboundFrame::Media),
+        "mark" => Some(OutboundFrame::Mark(
+            event["mark"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        )),
+        "clear" => Some(OutboundFrame::Clear),
+        _ => None,
+    }
+}
+
+/// The Twilio-framed event the pipeline expects for `frame`.
+pub fn pipeline_event(frame: &InboundFrame, ticket: &StreamTicket) -> Option<String> {
+    let event = match frame {
+        InboundFrame::Start { stream_id, call_id } => serde_json::json!({
+            "event": "start",
+            "start": {
+                "streamSid": stream_id,
+                "callSid": call_id,
+                "customParameters": {
+                    "user_id": ticket.user_id.to_string(),
+                    "greeting": urlencoding::encode(&ticket.greeting),
+                }
+            }
+        }),
+        InboundFrame::Media(audio) => serde_json::json!({
+            "event": "media",
+            "media": { "payload": BASE64.encode(audio) }
+        }),
+        InboundFrame::Stop => serde_json::json!({"event": "stop"}),
+        InboundFrame::Ignore => return None,
+    };
+    Some(event.to_string())
+}
+
+fn mark_event(name: &str) -> String {
+    serde_json::json!({"event": "mark", "mark": {"name": name}}).to_string()
+}
+
+// ---------------------------------------------------------------------------
+// WebSocket endpoints: GET /api/voice/telnyx/ws, GET /api/voice/sinch/ws
+// ---------------------------------------------------------------------------
+
+pub async fn telnyx_stream_ws(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<StreamParams>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    stream_ws(state, StreamProvider::Telnyx, params, ws)
+}
+
+pub async fn sinch_stream_ws(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<StreamParams>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    stream_ws(state, StreamProvider::Sinch, params, ws)
+}
+
+fn stream_ws(
+    state: Arc<AppState>,
+    provider: StreamProvider,
+    params: StreamParams,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let ticket = match state
+        .voice_stream_tickets
+        .consume(provider, &params.token, now)
+    {
+        Ok(ticket) => ticket,
+        Err(StreamTicketError::Expired) => {
+            tracing::warn!("Expired {} voice stream token", provider.id());
+            return (StatusCode::UNAUTHORIZED, "Token expired").into_response();
+        }
+        Err(StreamTicketError::Invalid) => {
+            tracing::warn!("Invalid {} voice stream token", provider.id());
+            return (StatusCode::UNAUTHORIZED, "Invalid token").into_response();
+        }
+    };
+
+    ws.on_upgrade(move |socket| bridge_stream(state, socket, provider, ticket))
+}
+
+/// Run the pipeline on a provider socket: provider frames are translated to
+/// Twilio events on the way in and back on the way out.
+async fn bridge_stream(
+    state: Arc<AppState>,
+    socket: WebSocket,
+    provider: StreamProvider,
+    ticket: StreamTicket,
+) {
+    use futures::stream::StreamExt;
+    use futures_util::SinkExt;
+
+    let (mut sock_tx, mut sock_rx) = socket.split();
+    let (inbound_tx, inbound_rx) =
+        futures::channel::mpsc::unbounded::<Result<Message, axum::Error>>();
+    let (send_tx, mut send_rx) = mpsc::channel::<Message>(64);
+    let sinch_rate = Arc::new(AtomicU32::new(PIPELINE_RATE));
+
+    // Provider -> pipeline. Closing the channel when the socket ends also
+    // stops the mark replays, so the pipeline's stream ends with the call.
+    let reader_tx = inbound_tx.clone();
+    let reader_rate = sinch_rate.clone();
+    tokio::spawn(async move {
+        let mut started = false;
+        let mut stopped = false;
+        let start = || InboundFrame::Start {
+            stream_id: format!("{}_{}", provider.id(), uuid::Uuid::new_v4()),
+            call_id: String::new(),
+        };
+        while let Some(msg) = sock_rx.next().await {
+            let msg = match msg {
+                Ok(m) => m,
+                Err(e) => {
+                    tracing::warn!("{} stream receive error: {}", provider.id(), e);
+                    break;
+                }
+            };
+            let frames = match (provider, msg) {
+                (_, Message::Close(_)) => break,
+                (StreamProvider::Telnyx, Message::Text(text)) => vec![parse_telnyx_frame(&text)],
+                (StreamProvider::Sinch, Message::Text(text)) => {
+                    if let Some(rate) = sinch_stream_rate(&text) {
+                        reader_rate.store(rate, Ordering::Relaxed);
+                    }
+                    vec![start()]
+                }
+                (StreamProvider::Sinch, Message::Binary(audio)) => {
+                    let audio = InboundFrame::Media(sinch_audio_to_mulaw(
+                        &audio,
+                        reader_rate.load(Ordering::Relaxed),
+                    ));
+                    if started {
+                        vec![audio]
+                    } else {
+                        vec![start(), audio]
+                    }
+                }
+                _ => continue,
+            };
+            for frame in frames {
+                match frame {
+                    InboundFrame::Start { .. } if started => continue,
+                    InboundFrame::Start { .. } => started = true,
+                    InboundFrame::Media(_) if !started => continue,
+                    InboundFrame::Stop => stopped = true,
+                    _ => {}
+                }
+                if let Some(event) = pipeline_event(&frame, &ticket) {
+                    if reader_tx
+                        .unbounded_send(Ok(Message::Text(event.into())))
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                if stopped {
+                    reader_tx.close_channel();
+                    return;
+                }
+            }
+        }
+        // Hang-ups can close the socket without a stop event; billing runs
+        // on stop, so always send one.
+        if started {
+            if let Some(event) = pipeline_event(&InboundFrame::Stop, &ticket) {
+                let _ = reader_tx.unbounded_send(Ok(Message::Text(event.into())));
+            }
+        }
+        reader_tx.close_channel();
+    });
+
+    // Pipeline -> provider.
+    tokio::spawn(async move {
+        let mut playout_end = tokio::time::Instant::now();
+        while let Some(msg) = send_rx.recv().await {
+            let Message::Text(text) = msg else {
+                continue;
+            };
+            let Some(frame) = parse_pipeline_frame(&text) else {
+                continue;
+            };
+            let out: Vec<Message> = match frame {
+                OutboundFrame::Media(audio) => {
+                    playout_end = playout_end.max(tokio::time::Instant::now())
+                        + Duration::from_millis(audio.len() as u64 * 1000 / PIPELINE_RATE as u64);
+                    match provider {
+                        StreamProvider::Telnyx => {
+                            let msg = serde_json::json!({
+                                "event": "media",
+                                "media": { "payload": BASE64.encode(&audio) }
+                            });
+                            vec![Message::Text(msg.to_string().into())]
+                        }
+                        StreamProvider::Sinch => {
+                            sinch_audio_frames(&audio, sinch_rate.load(Ordering::Relaxed))
+                                .into_iter()
+                                .map(|frame| Message::Binary(frame.into()))
+                                .collect()
+                        }
+                    }
+                }
+                OutboundFrame::Mark(name) => {
+                    let tx = inbound_tx.clone();
+                    let at = playout_end;
+                    tokio::spawn(async move {
+                        tokio::time::sleep_until(at).await;
+                        let _ = tx.unbounded_send(Ok(Message::Text(mark_event(&name).into())));
+                    });
+                    continue;
+                }
+                OutboundFrame::Clear => {
+                    playout_end = tokio::time::Instant::now();
+                    match provider {
+                        StreamProvider::Telnyx => vec![Message::Text(
+                            serde_json::json!({"event": "clear"}).to_string().into(),
+                        )],
+                        // Sinch has no way to drop queued audio.
+                        StreamProvider::Sinch => continue,
+                    }
+                }
+            };
+            for msg in out {
+                if sock_tx.send(msg).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    run_media_stream(state, inbound_rx, send_tx).await;
+    tracing::info!("{} voice stream closed", provider.id());
+}
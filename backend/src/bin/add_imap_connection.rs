@@ -42,9 +42,14 @@ fn main() {
     let args = Args::parse();
 
     let pg_url = env::var("PG_DATABASE_URL").expect("PG_DATABASE_URL must be set");
-    // Sanity: ENCRYPTION_KEY must be present because set_imap_credentials
-    // will use it via the encrypt() helper.
-    env::var("ENCRYPTION_KEY").expect("ENCRYPTION_KEY must be set (from .env)");
+    // Sanity: the key ring must load because set_imap_credentials will use
+    // it via the encrypt() helper. Same rules as the server.
+    if let Err(e) = backend::utils::encryption::KeyRing::from_env() {
+        panic!(
+            "ENCRYPTION_KEYS or ENCRYPTION_KEY must be set (from .env): {}",
+            e
+        );
+    }
 
     let manager = ConnectionManager::<PgConnection>::new(&pg_url);
     let pool: backend::PgDbPool = r2d2::Pool::builder()
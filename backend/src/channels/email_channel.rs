@@ -0,0 +1,461 @@
+//! Email channel. Notifications the user routes to email are sent as a
+//! plain text + HTML message to one address they own: their account email
+//! or a mail account they connected over IMAP.
+//!
+//! Mail goes out through one of two senders:
+//! - the operator's SMTP relay (`SMTP_RELAY_*`), from `SMTP_RELAY_FROM`;
+//! - the user's own connected account, over the SMTP server that pairs
+//!   with its IMAP server (`smtp_server_for_imap`), from that address.
+//!
+//! Digests are rendered as sectioned HTML from the text `build_digest_for_user`
+//! produces; everything else becomes simple paragraphs. Which notifications
+//! use email is a per-category choice stored in `email_notification_channels`;
+//! see `notification_delivery`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, Message, SmtpTransport};
+
+use crate::channels::traits::{ChannelError, ChannelMessageId, MediaRef, MessageChannel};
+use crate::handlers::imap_handlers::{send_smtp_message, smtp_server_for_imap};
+use crate::models::email_channel_models::EmailDeliveryMode;
+use crate::models::matrix_channel_models::NotificationCategory;
+use crate::models::user_models::User;
+use crate::repositories::email_channel_repository::EmailChannelRepository;
+use crate::repositories::user_repository::UserRepository;
+use crate::{AppState, PgDbPool};
+
+pub const CHANNEL_ID: &str = "email";
+
+/// Submission port of the SMTP servers paired with connected IMAP accounts.
+const ACCOUNT_SMTP_PORT: u16 = 587;
+const SENDER_NAME: &str = "Lightfriend";
+const MAX_SUBJECT_CHARS: usize = 60;
+
+/// How the relay connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayTls {
+    StartTls,
+    Tls,
+    /// Plain SMTP, for a local catcher such as Mailpit.
+    None,
+}
+
+/// The operator's SMTP relay.
+#[derive(Debug, Clone)]
+pub struct SmtpRelay {
+    pub host: String,
+    pub port: u16,
+    pub tls: RelayTls,
+    pub credentials: Option<(String, String)>,
+    pub from: Mailbox,
+}
+
+impl SmtpRelay {
+    /// `None` unless `SMTP_RELAY_HOST` and a valid `SMTP_RELAY_FROM` are set.
+    pub fn from_env() -> Option<Self> {
+        let host = std::env::var("SMTP_RELAY_HOST")
+            .ok()
+            .filter(|host| !host.trim().is_empty())?;
+        let from = match std::env::var("SMTP_RELAY_FROM")
+            .unwrap_or_default()
+            .parse::<Mailbox>()
+        {
+            Ok(from) => from,
+            Err(e) => {
+                tracing::warn!(
+                    "SMTP_RELAY_FROM is missing or invalid; relay disabled: {}",
+                    e
+                );
+                return None;
+            }
+        };
+        let tls = match std::env::var("SMTP_RELAY_TLS")
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "tls" | "smtps" => RelayTls::Tls,
+            "none" => RelayTls::None,
+            _ => RelayTls::StartTls,
+        };
+        let port = std::env::var("SMTP_RELAY_PORT")
+            .ok()
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(match tls {
+                RelayTls::StartTls => 587,
+                RelayTls::Tls => 465,
+                RelayTls::None => 25,
+            });
+        let credentials = match (
+            std::env::var("SMTP_RELAY_USERNAME"),
+            std::env::var("SMTP_RELAY_PASSWORD"),
+        ) {
+            (Ok(username), Ok(password)) if !username.is_empty() => Some((username, password)),
+            _ => None,
+        };
+        Some(Self {
+            host: host.trim().to_string(),
+            port,
+            tls,
+            credentials,
+            from,
+        })
+    }
+
+    fn transport(&self) -> Result<SmtpTransport, ChannelError> {
+        let builder = match self.tls {
+            RelayTls::StartTls => SmtpTransport::starttls_relay(&self.host)
+                .map_err(|e| ChannelError::NotConfigured(e.to_string()))?,
+            RelayTls::Tls => SmtpTransport::relay(&self.host)
+                .map_err(|e| ChannelError::NotConfigured(e.to_string()))?,
+            RelayTls::None => SmtpTransport::builder_dangerous(&self.host),
+        };
+        let builder = builder.port(self.port);
+        Ok(match &self.credentials {
+            Some((username, password)) => builder
+                .credentials(Credentials::new(username.clone(), password.clone()))
+                .build(),
+            None => builder.build(),
+        })
+    }
+}
+
+/// Subject and both bodies of one notification email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailContent {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+impl EmailContent {
+    pub fn for_notification(category: NotificationCategory, body: &str) -> Self {
+        let subject = match category {
+            NotificationCategory::Digest => "Your Lightfriend digest".to_string(),
+            _ => {
+                let first_line = body.lines().find(|line| !line.trim().is_empty());
+                let mut headline: String = first_line
+                    .unwrap_or_default()
+                    .trim()
+                    .chars()
+                    .take(MAX_SUBJECT_CHARS)
+                    .collect();
+                if first_line.is_some_and(|line| line.trim().chars().count() > MAX_SUBJECT_CHARS) {
+                    headline.push_str("...");
+                }
+                if headline.is_empty() {
+                    SENDER_NAME.to_string()
+                } else {
+                    format!("{}: {}", SENDER_NAME, headline)
+                }
+            }
+        };
+        let inner = match category {
+            NotificationCategory::Digest => render_digest_html(body),
+            _ => render_paragraphs_html(body),
+        };
+        Self {
+            subject,
+            text: body.to_string(),
+            html: wrap_html(&inner),
+        }
+    }
+}
+
+pub struct EmailChannel {
+    repository: EmailChannelRepository,
+    user_repository: Arc<UserRepository>,
+    relay: Option<SmtpRelay>,
+}
+
+impl EmailChannel {
+    pub fn new(
+        pool: PgDbPool,
+        user_repository: Arc<UserRepository>,
+        relay: Option<SmtpRelay>,
+    ) -> Self {
+        Self {
+            repository: EmailChannelRepository::new(pool),
+            user_repository,
+            relay,
+        }
+    }
+
+    /// A channel reading the relay from the environment, for callers that
+    /// need more than `MessageChannel::send` (a subject, digest HTML).
+    pub fn from_state(state: &Arc<AppState>) -> Self {
+        Self::new(
+            state.pg_pool.clone(),
+            state.user_repository.clone(),
+            SmtpRelay::from_env(),
+        )
+    }
+
+    pub fn relay_available(&self) -> bool {
+        self.relay.is_some()
+    }
+
+    /// Send `content` to `address`, which must be the user's channel
+    /// address, from the sender the channel is set up with.
+    pub async fn deliver(
+        &self,
+        user: &User,
+        address: &str,
+        content: &EmailContent,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let channel = self
+            .repository
+            .find(user.id)
+            .map_err(|e| ChannelError::Other(e.to_string()))?
+            .filter(|channel| channel.address.eq_ignore_ascii_case(address))
+            .ok_or_else(|| ChannelError::InvalidAddress("not the user's email channel".into()))?;
+        let to = channel
+            .address
+            .parse::<Address>()
+            .map_err(|e| ChannelError::InvalidAddress(e.to_string()))?;
+
+        let (transport, from) = match channel.imap_connection_id {
+            Some(connection_id) => self.account_sender(user.id, connection_id)?,
+            None => {
+                let relay = self
+                    .relay
+                    .as_ref()
+                    .ok_or_else(|| ChannelError::NotConfigured("no SMTP relay".into()))?;
+                (relay.transport()?, relay.from.clone())
+            }
+        };
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let message = Message::builder()
+            .message_id(Some(format!("<{}@{}>", id, from.email.domain())))
+            .from(from)
+            .to(Mailbox::new(None, to))
+            .subject(content.subject.clone())
+            .multipart(MultiPart::alternative_plain_html(
+                content.text.clone(),
+                content.html.clone(),
+            ))
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+        send_smtp_message(transport, message)
+            .await
+            .map_err(ChannelError::SendFailed)?;
+        Ok(ChannelMessageId(format!("email_{}", id)))
+    }
+
+    /// SMTP transport and sender for the user's connected account.
+    fn account_sender(
+        &self,
+        user_id: i32,
+        connection_id: i32,
+    ) -> Result<(SmtpTransport, Mailbox), ChannelError> {
+        let (owner, account, status) = self
+            .user_repository
+            .get_imap_connection_by_id(connection_id)
+            .map_err(|e| ChannelError::Other(e.to_string()))?
+            .ok_or_else(|| ChannelError::NotConfigured("mail account removed".into()))?;
+        if owner != user_id || status != "active" {
+            return Err(ChannelError::NotConfigured(
+                "mail account is not active".into(),
+            ));
+        }
+        let from = account
+            .email
+            .parse::<Address>()
+            .map_err(|e| ChannelError::NotConfigured(e.to_string()))?;
+        let server = smtp_server_for_imap(account.imap_server.as_deref());
+        let transport = SmtpTransport::starttls_relay(&server)
+            .map_err(|e| ChannelError::NotConfigured(e.to_string()))?
+            .port(ACCOUNT_SMTP_PORT)
+            .credentials(Credentials::new(account.email, account.password))
+            .build();
+        Ok((transport, Mailbox::new(Some(SENDER_NAME.to_string()), from)))
+    }
+}
+
+#[async_trait]
+impl MessageChannel for EmailChannel {
+    fn id(&self) -> &'static str {
+        CHANNEL_ID
+    }
+
+    async fn send(
+        &self,
+        user: &User,
+        address: &str,
+        body: &str,
+        media: Option<MediaRef>,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let body = match media {
+            None => body.to_string(),
+            Some(MediaRef::Url(url)) => format!("{}\n{}", body, url),
+            Some(MediaRef::Bytes { .. }) => return Err(ChannelError::MediaNotSupported),
+        };
+        if body.trim().is_empty() {
+            return Err(ChannelError::SendFailed("empty body".into()));
+        }
+        let content = EmailContent::for_notification(NotificationCategory::Other, &body);
+        self.deliver(user, address, &content).await
+    }
+}
+
+/// Email a notification of `content_type` to the user's channel address.
+pub async fn send_to_email(
+    state: &Arc<AppState>,
+    user: &User,
+    content_type: &str,
+    body: &str,
+) -> Result<ChannelMessageId, ChannelError> {
+    if state.channel_router.channel(CHANNEL_ID).is_none() {
+        return Err(ChannelError::NotConfigured(CHANNEL_ID.to_string()));
+    }
+    if body.trim().is_empty() {
+        return Err(ChannelError::SendFailed("empty body".into()));
+    }
+    let address = EmailChannelRepository::new(state.pg_pool.clone())
+        .find(user.id)
+        .map_err(|e| ChannelError::Other(e.to_string()))?
+        .map(|channel| channel.address)
+        .ok_or_else(|| ChannelError::NotConfigured("no email channel".into()))?;
+    let content =
+        EmailContent::for_notification(NotificationCategory::from_content_type(content_type), body);
+    EmailChannel::from_state(state)
+        .deliver(user, &address, &content)
+        .await
+}
+
+/// How a notification of `content_type` reaches the user as far as email is
+/// concerned. SMS unless the user set up an address and picked email for
+/// that category.
+pub fn notification_delivery(
+    state: &Arc<AppState>,
+    user_id: i32,
+    content_type: &str,
+) -> EmailDeliveryMode {
+    if state.channel_router.channel(CHANNEL_ID).is_none() {
+        return EmailDeliveryMode::Sms;
+    }
+    match EmailChannelRepository::new(state.pg_pool.clone()).find(user_id) {
+        Ok(Some(channel)) => {
+            channel.delivery_for(NotificationCategory::from_content_type(content_type))
+        }
+        Ok(None) => EmailDeliveryMode::Sms,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to read email delivery modes for user {}; using SMS: {}",
+                user_id,
+                e
+            );
+            EmailDeliveryMode::Sms
+        }
+    }
+}
+
+/// Render digest text as HTML. The first block is the title; a block that
+/// opens with `Label:` becomes a section, with `- ` lines as a list
+/// (`sender: summary` items get the sender in bold) and a `+ N more` tail.
+pub fn render_digest_html(text: &str) -> String {
+    let mut blocks = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|block| !block.is_empty());
+    let mut html = String::new();
+    if let Some(title) = blocks.next() {
+        html.push_str(&format!(
+            "<h1 style=\"font-size:20px;margin:0 0 16px\">{}</h1>",
+            escape_html(title)
+        ));
+    }
+    for block in blocks {
+        let (first, rest) = match block.split_once('\n') {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (block, None),
+        };
+        let Some((label, inline)) = first.split_once(':').filter(|(label, _)| {
+            !label.is_empty() && label.len() <= 24 && !label.starts_with(['-', '+'])
+        }) else {
+            html.push_str(&paragraph(block));
+            continue;
+        };
+        html.push_str(&format!(
+            "<h2 style=\"font-size:15px;margin:20px 0 6px\">{}</h2>",
+            escape_html(label.trim())
+        ));
+        if !inline.trim().is_empty() {
+            html.push_str(&paragraph(inline.trim()));
+        }
+        let Some(rest) = rest else { continue };
+        let mut items = Vec::new();
+        let mut tail = Vec::new();
+        for line in rest.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            match line.strip_prefix("- ") {
+                Some(item) => items.push(list_item(item)),
+                None => tail.push(line),
+            }
+        }
+        if !items.is_empty() {
+            html.push_str(&format!(
+                "<ul style=\"margin:0;padding-left:20px\">{}</ul>",
+                items.concat()
+            ));
+        }
+        for line in tail {
+            html.push_str(&format!(
+                "<p style=\"margin:6px 0;color:#666\">{}</p>",
+                escape_html(line)
+            ));
+        }
+    }
+    html
+}
+
+fn render_paragraphs_html(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|block| !block.is_empty())
+        .map(paragraph)
+        .collect()
+}
+
+fn paragraph(text: &str) -> String {
+    let lines: Vec<String> = text.lines().map(escape_html).collect();
+    format!("<p style=\"margin:0 0 12px\">{}</p>", lines.join("<br>"))
+}
+
+fn list_item(item: &str) -> String {
+    match item.split_once(": ") {
+        Some((sender, summary)) => format!(
+            "<li style=\"margin:4px 0\"><strong>{}</strong>: {}</li>",
+            escape_html(sender),
+            escape_html(summary)
+        ),
+        None => format!("<li style=\"margin:4px 0\">{}</li>", escape_html(item)),
+    }
+}
+
+fn wrap_html(inner: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"></head>\
+<body style=\"margin:0;padding:24px;background:#fff;color:#111;font:15px/1.5 -apple-system,Segoe UI,Helvetica,Arial,sans-serif\">\
+<div style=\"max-width:560px;margin:0 auto\">{}</div></body></html>",
+        inner
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,500 @@
+//! Per-provider, per-country delivery health for the SMS router.
+//!
+//! A provider's API accepting a message says little about whether the
+//! handset got it, so health is scored from the delivery status callbacks
+//! Twilio, Telnyx and Sinch send afterwards. Each `(provider, country)`
+//! pair keeps a rolling window of final outcomes and delivery latencies.
+//!
+//! When a pair's success rate drops below the threshold, its circuit opens
+//! and `ChannelRouter::pick_channels_for` moves the provider behind the
+//! healthy ones for that country. After the cooldown the circuit is
+//! half-open: the provider is tried in its normal slot again, and the next
+//! final outcome either closes the circuit or re-opens it.
+//!
+//! Sends made through `ChannelRouter::send_to_user` are also remembered
+//! for the retry window, keyed by provider message id, so a delivery that
+//! fails asynchronously can be re-sent through the providers that were
+//! still left in the order. Everything here is in memory only: a restart
+//! forgets the scores and the pending bodies, which is the intended
+//! trade-off for not persisting message text.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::channels::traits::MediaRef;
+use crate::{AppState, UserCoreOps};
+
+/// Pending sends tracked at once. Past this, new sends just aren't
+/// eligible for an asynchronous retry.
+const MAX_PENDING_DELIVERIES: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// Final outcomes kept per `(provider, country)`.
+    pub window: usize,
+    /// Outcomes needed before the circuit can open.
+    pub min_samples: usize,
+    /// The circuit opens when the success rate falls below this.
+    pub min_success_rate: f64,
+    /// How long an open circuit keeps the provider demoted.
+    pub cooldown: Duration,
+    /// How long after sending a failed delivery is still re-sent.
+    pub retry_window: Duration,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            window: 50,
+            min_samples: 10,
+            min_success_rate: 0.8,
+            cooldown: Duration::from_secs(10 * 60),
+            retry_window: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl HealthConfig {
+    /// Defaults, with `SMS_CIRCUIT_COOLDOWN_MINUTES` and
+    /// `SMS_DELIVERY_RETRY_MINUTES` overriding the two time spans.
+    pub fn from_env() -> Self {
+        let minutes = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(|m| Duration::from_secs(m * 60))
+        };
+        let defaults = Self::default();
+        Self {
+            cooldown: minutes("SMS_CIRCUIT_COOLDOWN_MINUTES").unwrap_or(defaults.cooldown),
+            retry_window: minutes("SMS_DELIVERY_RETRY_MINUTES").unwrap_or(defaults.retry_window),
+            ..defaults
+        }
+    }
+}
+
+/// Delivery outcome of one status callback, in Twilio's vocabulary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    Delivered,
+    Failed,
+    /// Queued, sending, sent and anything else non-final.
+    Pending,
+}
+
+impl DeliveryOutcome {
+    /// Classify a status already mapped to Twilio's taxonomy
+    /// (`map_telnyx_status`, `map_sinch_status`).
+    pub fn from_status(status: &str) -> Self {
+        match status {
+            "delivered" => Self::Delivered,
+            "failed" | "undelivered" => Self::Failed,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// A send that failed after the provider accepted it and should go out
+/// again through the providers still left in its order.
+#[derive(Debug, Clone)]
+pub struct AsyncRetry {
+    pub user_id: i32,
+    pub failed_provider: &'static str,
+    pub country: String,
+    /// Sanitized body without any fallback prefix.
+    pub body: String,
+    pub media: Option<MediaRef>,
+    pub remaining: Vec<&'static str>,
+    /// Attempts already made, so the retry knows to add the prefix.
+    pub attempts: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// One scoreboard row for the admin dashboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealthRow {
+    pub provider: String,
+    pub country: String,
+    pub samples: usize,
+    pub success_rate: Option<f64>,
+    pub median_latency_ms: Option<u64>,
+    pub p90_latency_ms: Option<u64>,
+    pub sent: u64,
+    pub delivered: u64,
+    pub failed: u64,
+    pub retried: u64,
+    pub circuit: CircuitState,
+    /// Seconds until an open circuit turns half-open.
+    pub reopens_in_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealthSnapshot {
+    pub rows: Vec<ProviderHealthRow>,
+    pub pending_deliveries: usize,
+    pub min_success_rate: f64,
+    pub cooldown_secs: u64,
+    pub retry_window_secs: u64,
+}
+
+#[derive(Default)]
+struct PairStats {
+    outcomes: VecDeque<bool>,
+    latencies_ms: VecDeque<u64>,
+    sent: u64,
+    delivered: u64,
+    failed: u64,
+    retried: u64,
+    open_until: Option<Instant>,
+}
+
+impl PairStats {
+    fn success_rate(&self) -> Option<f64> {
+        if self.outcomes.is_empty() {
+            return None;
+        }
+        let ok = self.outcomes.iter().filter(|ok| **ok).count();
+        Some(ok as f64 / self.outcomes.len() as f64)
+    }
+
+    fn circuit(&self, now: Instant) -> CircuitState {
+        match self.open_until {
+            None => CircuitState::Closed,
+            Some(until) if until > now => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    fn latency_percentile(&self, pct: usize) -> Option<u64> {
+        if self.latencies_ms.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<u64> = self.latencies_ms.iter().copied().collect();
+        sorted.sort_unstable();
+        Some(sorted[(sorted.len() - 1) * pct / 100])
+    }
+}
+
+struct PendingDelivery {
+    provider: &'static str,
+    message_id: String,
+    user_id: i32,
+    country: String,
+    body: String,
+    media: Option<MediaRef>,
+    remaining: Vec<&'static str>,
+    attempts: usize,
+    sent_at: Instant,
+}
+
+#[derive(Default)]
+struct HealthState {
+    pairs: HashMap<(&'static str, String), PairStats>,
+    /// Keyed by `provider:message_id`; ids are only unique per provider.
+    pending: HashMap<String, PendingDelivery>,
+}
+
+pub struct ProviderHealth {
+    config: HealthConfig,
+    state: Mutex<HealthState>,
+}
+
+impl ProviderHealth {
+    pub fn new(config: HealthConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(HealthState::default()),
+        }
+    }
+
+    /// Whether `provider` is currently demoted for `country`.
+    pub fn is_demoted(&self, provider: &str, country: &str) -> bool {
+        let now = Instant::now();
+        self.state
+            .lock()
+            .map(|s| {
+                s.pairs
+                    .iter()
+                    .find(|((p, c), _)| *p == provider && c == country)
+                    .is_some_and(|(_, stats)| stats.circuit(now) == CircuitState::Open)
+            })
+            .unwrap_or(false)
+    }
+
+    /// A provider accepted a send. `remaining` is what's left of the order
+    /// should the delivery fail later.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_accepted(
+        &self,
+        provider: &'static str,
+        message_id: &str,
+        user_id: i32,
+        country: &str,
+        body: &str,
+        media: Option<MediaRef>,
+        remaining: Vec<&'static str>,
+        attempts: usize,
+    ) {
+        let now = Instant::now();
+        let Ok(mut s) = self.state.lock() else {
+            return;
+        };
+        s.pairs
+            .entry((provider, country.to_string()))
+            .or_default()
+            .sent += 1;
+
+        let retry_window = self.config.retry_window;
+        s.pending
+            .retain(|_, p| now.duration_since(p.sent_at) < retry_window);
+        if s.pending.len() >= MAX_PENDING_DELIVERIES {
+            tracing::warn!(
+                "Pending delivery table full; {} message {} won't be retried on failure",
+                provider,
+                message_id
+            );
+            return;
+        }
+        s.pending.insert(
+            pending_key(provider, message_id),
+            PendingDelivery {
+                provider,
+                message_id: message_id.to_string(),
+                user_id,
+                country: country.to_string(),
+                body: body.to_string(),
+                media,
+                remaining,
+                attempts,
+                sent_at: now,
+            },
+        );
+    }
+
+    /// A provider refused a send outright. Counts against its score like
+    /// an undelivered status would.
+    pub fn record_rejected(&self, provider: &'static str, country: &str) {
+        let now = Instant::now();
+        if let Ok(mut s) = self.state.lock() {
+            let stats = s.pairs.entry((provider, country.to_string())).or_default();
+            self.record_outcome(stats, false, None, now);
+        }
+    }
+
+    /// Feed one status callback. `fallback_country` scores messages the
+    /// router didn't send itself (or no longer remembers). Returns the
+    /// retry to run when a remembered delivery failed within the window
+    /// and providers are left to try.
+    pub fn record_status(
+        &self,
+        provider: &'static str,
+        message_id: &str,
+        status: &str,
+        fallback_country: Option<&str>,
+    ) -> Option<AsyncRetry> {
+        let outcome = DeliveryOutcome::from_status(status);
+        if outcome == DeliveryOutcome::Pending {
+            return None;
+        }
+        let now = Instant::now();
+        let Ok(mut s) = self.state.lock() else {
+            return None;
+        };
+        let pending = s.pending.remove(&pending_key(provider, message_id));
+        let country = match (&pending, fallback_country) {
+            (Some(p), _) => p.country.clone(),
+            (None, Some(country)) => country.to_uppercase(),
+            (None, None) => return None,
+        };
+        let latency_ms = pending
+            .as_ref()
+            .map(|p| now.duration_since(p.sent_at).as_millis() as u64);
+
+        let stats = s.pairs.entry((provider, country)).or_default();
+        let delivered = outcome == DeliveryOutcome::Delivered;
+        self.record_outcome(stats, delivered, latency_ms.filter(|_| delivered), now);
+
+        let pending = pending?;
+        if delivered
+            || pending.remaining.is_empty()
+            || now.duration_since(pending.sent_at) >= self.config.retry_window
+        {
+            return None;
+        }
+        stats.retried += 1;
+        tracing::warn!(
+            "{} reported {} for message {} to user {}; re-sending via {:?}",
+            pending.provider,
+            status,
+            pending.message_id,
+            pending.user_id,
+            pending.remaining
+        );
+        Some(AsyncRetry {
+            user_id: pending.user_id,
+            failed_provider: pending.provider,
+            country: pending.country,
+            body: pending.body,
+            media: pending.media,
+            remaining: pending.remaining,
+            attempts: pending.attempts,
+        })
+    }
+
+    fn record_outcome(
+        &self,
+        stats: &mut PairStats,
+        delivered: bool,
+        latency_ms: Option<u64>,
+        now: Instant,
+    ) {
+        if delivered {
+            stats.delivered += 1;
+        } else {
+            stats.failed += 1;
+        }
+        push_capped(&mut stats.outcomes, delivered, self.config.window);
+        if let Some(ms) = latency_ms {
+            push_capped(&mut stats.latencies_ms, ms, self.config.window);
+        }
+
+        match stats.circuit(now) {
+            CircuitState::HalfOpen if delivered => {
+                // The probe got through: start scoring afresh so the
+                // failures that opened the circuit don't re-open it.
+                stats.open_until = None;
+                stats.outcomes.clear();
+                stats.outcomes.push_back(true);
+            }
+            CircuitState::HalfOpen => {
+                stats.open_until = Some(now + self.config.cooldown);
+            }
+            CircuitState::Closed => {
+                if stats.outcomes.len() >= self.config.min_samples
+                    && stats
+                        .success_rate()
+                        .is_some_and(|rate| rate < self.config.min_success_rate)
+                {
+                    stats.open_until = Some(now + self.config.cooldown);
+                }
+            }
+            CircuitState::Open => {}
+        }
+    }
+
+    /// Scoreboard for the admin dashboard, sorted by country then provider.
+    pub fn snapshot(&self) -> ProviderHealthSnapshot {
+        let now = Instant::now();
+        let (mut rows, pending_deliveries) = match self.state.lock() {
+            Ok(s) => (
+                s.pairs
+                    .iter()
+                    .map(|((provider, country), stats)| ProviderHealthRow {
+                        provider: provider.to_string(),
+                        country: country.clone(),
+                        samples: stats.outcomes.len(),
+                        success_rate: stats.success_rate(),
+                        median_latency_ms: stats.latency_percentile(50),
+                        p90_latency_ms: stats.latency_percentile(90),
+                        sent: stats.sent,
+                        delivered: stats.delivered,
+                        failed: stats.failed,
+                        retried: stats.retried,
+                        circuit: stats.circuit(now),
+                        reopens_in_secs: stats
+                            .open_until
+                            .filter(|until| *until > now)
+                            .map(|until| until.duration_since(now).as_secs()),
+                    })
+                    .collect::<Vec<_>>(),
+                s.pending.len(),
+            ),
+            Err(_) => (Vec::new(), 0),
+        };
+        rows.sort_by(|a, b| (&a.country, &a.provider).cmp(&(&b.country, &b.provider)));
+        ProviderHealthSnapshot {
+            rows,
+            pending_deliveries,
+            min_success_rate: self.config.min_success_rate,
+            cooldown_secs: self.config.cooldown.as_secs(),
+            retry_window_secs: self.config.retry_window.as_secs(),
+        }
+    }
+}
+
+impl Default for ProviderHealth {
+    fn default() -> Self {
+        Self::new(HealthConfig::default())
+    }
+}
+
+/// Status-webhook entry point shared by the Twilio, Telnyx and Sinch
+/// handlers. Scores the callback and, when it reports an asynchronous
+/// failure of a recent send, re-sends through the remaining providers in
+/// the background. Returns whether a re-send was started.
+pub fn handle_delivery_status(
+    state: &Arc<AppState>,
+    provider: &str,
+    message_id: &str,
+    status: &str,
+    to_phone: Option<&str>,
+) -> bool {
+    let Some(retry) = state
+        .channel_router
+        .record_delivery_status(provider, message_id, status, to_phone)
+    else {
+        return false;
+    };
+    let state = state.clone();
+    tokio::spawn(async move {
+        let user = match state.user_core.find_by_id(retry.user_id) {
+            Ok(Some(user)) => user,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::error!(
+                    "Delivery retry: failed to load user {}: {}",
+                    retry.user_id,
+                    e
+                );
+                return;
+            }
+        };
+        let failed_provider = retry.failed_provider;
+        match state.channel_router.retry_undelivered(&user, retry).await {
+            Ok(id) => tracing::info!(
+                "Re-sent undelivered {} message for user {} as {}",
+                failed_provider,
+                user.id,
+                id
+            ),
+            Err(e) => tracing::error!(
+                "Re-send of undelivered {} message for user {} failed: {}",
+                failed_provider,
+                user.id,
+                e
+            ),
+        }
+    });
+    true
+}
+
+fn pending_key(provider: &str, message_id: &str) -> String {
+    format!("{}:{}", provider, message_id)
+}
+
+fn push_capped<T>(queue: &mut VecDeque<T>, value: T, cap: usize) {
+    queue.push_back(value);
+    while queue.len() > cap {
+        queue.pop_front();
+    }
+}
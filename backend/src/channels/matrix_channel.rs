@@ -0,0 +1,254 @@
+//! Matrix DM channel. The user's own Lightfriend Matrix account (the one
+//! the bridges run on, hosted by the embedded homeserver) shares one direct
+//! room with a Matrix ID the user reads elsewhere, e.g. Element or Beeper.
+//!
+//! Outbound, the address is that room's id and the send only succeeds once
+//! the user has joined, so a pending invite never swallows a notification:
+//! callers fall back to SMS on error. Inbound, `handle_dm_message` hands
+//! text the user types in the room to the same agent pipeline as SMS, and
+//! the reply comes back into the room.
+//!
+//! Which notifications use the room is a per-category choice stored in
+//! `matrix_dm_channels`; see `notification_delivery`.
+
+use std::sync::{Arc, Weak};
+
+use async_trait::async_trait;
+use matrix_sdk::room::Room;
+use matrix_sdk::ruma::events::room::message::{
+    MessageType, OriginalSyncRoomMessageEvent, RoomMessageEventContent,
+};
+use matrix_sdk::ruma::{OwnedRoomId, OwnedUserId};
+
+use crate::api::twilio_sms::{
+    process_sms, MessageChannel as AgentChannel, ProcessSmsOptions, TwilioWebhookPayload,
+};
+use crate::channels::traits::{ChannelError, ChannelMessageId, MediaRef, MessageChannel};
+use crate::models::matrix_channel_models::{DeliveryMode, NotificationCategory};
+use crate::models::user_models::User;
+use crate::repositories::matrix_dm_channel_repository::MatrixDmChannelRepository;
+use crate::AppState;
+
+pub const CHANNEL_ID: &str = "matrix";
+
+/// Longest message accepted from the room. Matches what the agent would
+/// see from a long concatenated SMS.
+const MAX_INBOUND_CHARS: usize = 1600;
+
+pub struct MatrixChannel {
+    state: Weak<AppState>,
+}
+
+impl MatrixChannel {
+    /// Holds the state weakly: the router lives inside `AppState`.
+    pub fn new(state: Weak<AppState>) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl MessageChannel for MatrixChannel {
+    fn id(&self) -> &'static str {
+        CHANNEL_ID
+    }
+
+    async fn send(
+        &self,
+        user: &User,
+        address: &str,
+        body: &str,
+        media: Option<MediaRef>,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let state = self
+            .state
+            .upgrade()
+            .ok_or_else(|| ChannelError::NotConfigured("app state dropped".into()))?;
+        let body = match media {
+            None => body.to_string(),
+            Some(MediaRef::Url(url)) => format!("{}\n{}", body, url),
+            Some(MediaRef::Bytes { .. }) => return Err(ChannelError::MediaNotSupported),
+        };
+        if body.trim().is_empty() {
+            return Err(ChannelError::SendFailed("empty body".into()));
+        }
+
+        let channel = MatrixDmChannelRepository::new(state.pg_pool.clone())
+            .find(user.id)
+            .map_err(|e| ChannelError::Other(e.to_string()))?
+            .filter(|channel| channel.room_id.as_deref() == Some(address))
+            .ok_or_else(|| ChannelError::InvalidAddress(address.to_string()))?;
+        let room_id = OwnedRoomId::try_from(address)
+            .map_err(|e| ChannelError::InvalidAddress(e.to_string()))?;
+        let target = OwnedUserId::try_from(channel.target_mxid.as_str())
+            .map_err(|e| ChannelError::InvalidAddress(e.to_string()))?;
+
+        let client = crate::utils::matrix_auth::get_cached_client(user.id, &state)
+            .await
+            .map_err(|e| ChannelError::NotConfigured(e.to_string()))?;
+        let room = client
+            .get_room(&room_id)
+            .ok_or_else(|| ChannelError::SendFailed(format!("room {} not found", room_id)))?;
+        if !has_joined(&room, &target).await {
+            return Err(ChannelError::SendFailed(format!(
+                "{} has not joined the room yet",
+                target
+            )));
+        }
+
+        let sent = room
+            .send(RoomMessageEventContent::text_plain(body))
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+        Ok(ChannelMessageId(sent.event_id.to_string()))
+    }
+}
+
+/// Parse the Matrix ID a user wants their DM channel with. IDs on our own
+/// homeserver belong to other Lightfriend accounts and bridge puppets, so
+/// they are refused.
+pub fn validate_target_mxid(raw: &str, own_server: &str) -> Result<OwnedUserId, &'static str> {
+    if own_server.is_empty() {
+        return Err("Matrix is unavailable");
+    }
+    let target =
+        OwnedUserId::try_from(raw.trim()).map_err(|_| "Enter a Matrix ID like @you:matrix.org")?;
+    if target
+        .server_name()
+        .as_str()
+        .eq_ignore_ascii_case(own_server)
+    {
+        return Err("Use a Matrix ID from another homeserver");
+    }
+    Ok(target)
+}
+
+pub(crate) async fn has_joined(room: &Room, target: &OwnedUserId) -> bool {
+    match room.members(matrix_sdk::RoomMemberships::JOIN).await {
+        Ok(members) => members
+            .iter()
+            .any(|member| member.user_id().as_str() == target.as_str()),
+        Err(e) => {
+            tracing::warn!("Failed to read members of {}: {}", room.room_id(), e);
+            false
+        }
+    }
+}
+
+/// Send `body` into the user's DM room.
+pub async fn send_to_dm(
+    state: &Arc<AppState>,
+    user: &User,
+    body: &str,
+) -> Result<ChannelMessageId, ChannelError> {
+    let channel = state
+        .channel_router
+        .channel(CHANNEL_ID)
+        .ok_or_else(|| ChannelError::NotConfigured(CHANNEL_ID.to_string()))?;
+    let room_id = MatrixDmChannelRepository::new(state.pg_pool.clone())
+        .find(user.id)
+        .map_err(|e| ChannelError::Other(e.to_string()))?
+        .and_then(|channel| channel.room_id)
+        .ok_or_else(|| ChannelError::NotConfigured("no Matrix DM room".into()))?;
+    channel.send(user, &room_id, body, None).await
+}
+
+/// How a notification of `content_type` reaches the user. SMS unless the
+/// user has a Matrix room and picked it for that category.
+pub fn notification_delivery(
+    state: &Arc<AppState>,
+    user_id: i32,
+    content_type: &str,
+) -> DeliveryMode {
+    if state.channel_router.channel(CHANNEL_ID).is_none() {
+        return DeliveryMode::Sms;
+    }
+    match MatrixDmChannelRepository::new(state.pg_pool.clone()).find(user_id) {
+        Ok(Some(channel)) if channel.room_id.is_some() => {
+            channel.delivery_for(NotificationCategory::from_content_type(content_type))
+        }
+        Ok(_) => DeliveryMode::Sms,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to read Matrix delivery modes for user {}; using SMS: {}",
+                user_id,
+                e
+            );
+            DeliveryMode::Sms
+        }
+    }
+}
+
+/// Route a message from the user's DM room. Returns `true` when `room` is
+/// that room, including for Lightfriend's own echoes, so the bridge handler
+/// never treats it as bridged chat.
+pub async fn handle_dm_message(
+    state: &Arc<AppState>,
+    user: &User,
+    room: &Room,
+    event: &OriginalSyncRoomMessageEvent,
+) -> bool {
+    let channel = match MatrixDmChannelRepository::new(state.pg_pool.clone()).find(user.id) {
+        Ok(Some(channel)) => channel,
+        Ok(None) => return false,
+        Err(e) => {
+            tracing::error!(
+                "Failed to load Matrix DM channel for user {}: {}",
+                user.id,
+                e
+            );
+            return false;
+        }
+    };
+    if channel.room_id.as_deref() != Some(room.room_id().as_str()) {
+        return false;
+    }
+    if event.sender.as_str() != channel.target_mxid {
+        return true;
+    }
+
+    let body = match &event.content.msgtype {
+        MessageType::Text(text) => text.body.trim().to_string(),
+        _ => {
+            let reply = "Only text messages are supported here.";
+            if let Err(e) = room.send(RoomMessageEventContent::text_plain(reply)).await {
+                tracing::warn!("Failed to answer non-text Matrix DM: {}", e);
+            }
+            return true;
+        }
+    };
+    if body.is_empty() {
+        return true;
+    }
+    let body = body.chars().take(MAX_INBOUND_CHARS).collect::<String>();
+
+    tracing::info!("Matrix DM message from user {}", user.id);
+    let payload = TwilioWebhookPayload {
+        from: user.phone_number.clone(),
+        to: user.preferred_number.clone().unwrap_or_default(),
+        body,
+        num_media: None,
+        media_url0: None,
+        media_content_type0: None,
+        message_sid: format!("matrix_{}", event.event_id),
+        extra_params: Default::default(),
+    };
+    let state = state.clone();
+    tokio::spawn(async move {
+        let result = process_sms(
+            &state,
+            payload,
+            ProcessSmsOptions {
+                channel: AgentChannel::Matrix,
+                ..ProcessSmsOptions::default()
+            },
+        )
+        .await;
+        if result.0 != axum::http::StatusCode::OK {
+            tracing::error!(
+                "Background Matrix DM processing failed with status: {:?}",
+                result.0
+            );
+        }
+    });
+    true
+}
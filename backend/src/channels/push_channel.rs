@@ -0,0 +1,402 @@
+//! UnifiedPush / ntfy channel. The user registers one endpoint URL (an ntfy
+//! topic, or the endpoint a UnifiedPush distributor handed their app) and
+//! the notifications they route here are POSTed to it instead of costing an
+//! SMS.
+//!
+//! Without keys the body goes out as plain text with an ntfy `Title`
+//! header, which any ntfy client shows as is. With the Web Push `p256dh` and
+//! `auth` keys of a UnifiedPush subscription, the body is encrypted end to
+//! end (RFC 8291, `aes128gcm`) so the push server only relays ciphertext.
+//!
+//! Endpoints must be https. Debug builds also accept http on localhost, so
+//! a local ntfy server (`docker-compose.ntfy.yml`) works for development.
+//! The host is resolved when the endpoint is registered and again before
+//! every send, and refused if it points at a private or link-local address.
+//! `PUSH_CHANNEL_ALLOWED_HOSTS` (comma separated) restricts endpoints to
+//! known push servers.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes128Gcm, Nonce,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use hmac::{Hmac, Mac};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::{AffinePoint, PublicKey, SecretKey};
+use rand::RngCore;
+use reqwest::{redirect::Policy, StatusCode};
+use sha2::Sha256;
+use url::{Host, Url};
+
+use crate::channels::traits::{ChannelError, ChannelMessageId, MediaRef, MessageChannel};
+use crate::handlers::mcp_handlers::validate_public_mcp_url;
+use crate::models::matrix_channel_models::NotificationCategory;
+use crate::models::user_models::User;
+use crate::repositories::push_endpoint_repository::{
+    endpoint_hash, PushEndpointRepository, PushKeys,
+};
+use crate::{AppState, PgDbPool};
+
+pub const CHANNEL_ID: &str = "push";
+
+const ALLOWED_HOSTS_ENV: &str = "PUSH_CHANNEL_ALLOWED_HOSTS";
+const MAX_ENDPOINT_LEN: usize = 2048;
+/// Record size advertised in the `aes128gcm` header. Everything fits in one
+/// record.
+const RECORD_SIZE: u32 = 4096;
+/// Push services only promise to carry 4096 bytes. That leaves this much
+/// text once the 86-byte header, the padding delimiter and the 16-byte tag
+/// are taken off.
+const MAX_PLAINTEXT_BYTES: usize = 3993;
+/// How long the push server should hold a message for an offline device.
+const TTL_SECONDS: u32 = 24 * 60 * 60;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub struct PushChannel {
+    repository: PushEndpointRepository,
+    http: reqwest::Client,
+}
+
+impl PushChannel {
+    pub fn new(pool: PgDbPool) -> Result<Self, ChannelError> {
+        let http = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(10))
+            .redirect(Policy::none())
+            .build()
+            .map_err(|e| ChannelError::NotConfigured(e.to_string()))?;
+        Ok(Self {
+            repository: PushEndpointRepository::new(pool),
+            http,
+        })
+    }
+}
+
+#[async_trait]
+impl MessageChannel for PushChannel {
+    fn id(&self) -> &'static str {
+        CHANNEL_ID
+    }
+
+    async fn send(
+        &self,
+        user: &User,
+        address: &str,
+        body: &str,
+        media: Option<MediaRef>,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let body = match media {
+            None => body.to_string(),
+            Some(MediaRef::Url(url)) => format!("{}\n{}", body, url),
+            Some(MediaRef::Bytes { .. }) => return Err(ChannelError::MediaNotSupported),
+        };
+        if body.trim().is_empty() {
+            return Err(ChannelError::SendFailed("empty body".into()));
+        }
+        let body = truncate_to_bytes(&body, MAX_PLAINTEXT_BYTES);
+
+        let registration = self
+            .repository
+            .find(user.id)
+            .map_err(|e| ChannelError::Other(e.to_string()))?
+            .filter(|registration| registration.endpoint_hash == endpoint_hash(address))
+            .ok_or_else(|| ChannelError::InvalidAddress("not the user's push endpoint".into()))?;
+        // Resolved again on every send: the name may point somewhere else
+        // since it was registered.
+        let url = resolve_endpoint(&registration.endpoint)
+            .await
+            .map_err(|e| ChannelError::InvalidAddress(e.to_string()))?;
+
+        let request = self.http.post(url).header("TTL", TTL_SECONDS.to_string());
+        let request = match registration.keys.as_ref() {
+            Some(keys) => request
+                .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                .header(reqwest::header::CONTENT_ENCODING, "aes128gcm")
+                .body(
+                    encrypt_payload(body.as_bytes(), keys)
+                        .map_err(|e| ChannelError::InvalidAddress(e.to_string()))?,
+                ),
+            None => request
+                .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .header("Title", "Lightfriend")
+                .body(body.to_string()),
+        };
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND || status == StatusCode::GONE {
+            if let Err(e) = self
+                .repository
+                .delete_if_endpoint_hash(user.id, &registration.endpoint_hash)
+            {
+                tracing::error!(
+                    "Failed to drop expired push endpoint for user {}: {}",
+                    user.id,
+                    e
+                );
+            }
+            return Err(ChannelError::SendFailed(format!(
+                "push endpoint expired (HTTP {})",
+                status
+            )));
+        }
+        if !status.is_success() {
+            return Err(ChannelError::SendFailed(format!(
+                "push endpoint returned HTTP {}",
+                status
+            )));
+        }
+
+        // Web Push servers name the message in `Location`; ntfy returns it
+        // as JSON.
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|location| location.rsplit('/').next())
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+        let id = match location {
+            Some(location) => location,
+            None => response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|value| value["id"].as_str().map(str::to_string))
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        };
+        Ok(ChannelMessageId(format!("push_{}", id)))
+    }
+}
+
+/// Check an endpoint URL a user wants to register. The server POSTs to it,
+/// so internal hosts are refused. This only looks at the URL text; use
+/// [`resolve_endpoint`] before actually storing or posting to it.
+pub fn validate_endpoint(raw: &str) -> Result<Url, &'static str> {
+    let raw = raw.trim();
+    if raw.len() > MAX_ENDPOINT_LEN {
+        return Err("Push endpoint URL is too long");
+    }
+    let url =
+        Url::parse(raw).map_err(|_| "Enter a push endpoint URL like https://ntfy.sh/your-topic")?;
+    if !url.username().is_empty() || url.password().is_some() || url.fragment().is_some() {
+        return Err("Push endpoint URL can't contain credentials or a fragment");
+    }
+    if url.host().is_none() {
+        return Err("Push endpoint URL needs a host");
+    }
+    if is_dev_local(&url) {
+        return if matches!(url.scheme(), "http" | "https") {
+            Ok(url)
+        } else {
+            Err("Push endpoint must use https")
+        };
+    }
+    if url.scheme() != "https" {
+        return Err("Push endpoint must use https");
+    }
+    let host = match url.host() {
+        Some(Host::Domain(domain)) => domain.trim_end_matches('.').to_ascii_lowercase(),
+        _ => return Err("Push endpoint must use a host name, not an IP address"),
+    };
+    if host == "localhost"
+        || [".localhost", ".local", ".internal"]
+            .iter()
+            .any(|suffix| host.ends_with(suffix))
+    {
+        return Err("Push endpoint host is not reachable from Lightfriend");
+    }
+    let allowed = std::env::var(ALLOWED_HOSTS_ENV).unwrap_or_default();
+    let allowed = allowed
+        .split(',')
+        .map(|host| host.trim().trim_end_matches('.').to_ascii_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect::<Vec<_>>();
+    if !allowed.is_empty() && !allowed.contains(&host) {
+        return Err("Push endpoint host is not allowed on this server");
+    }
+    Ok(url)
+}
+
+/// [`validate_endpoint`], then resolve the host and refuse it if any of its
+/// addresses is private, loopback or link-local.
+pub async fn resolve_endpoint(raw: &str) -> Result<Url, &'static str> {
+    let url = validate_endpoint(raw)?;
+    if is_dev_local(&url) {
+        return Ok(url);
+    }
+    validate_public_mcp_url(url.as_str())
+        .await
+        .map_err(|_| "Push endpoint host is not reachable from Lightfriend")?;
+    Ok(url)
+}
+
+/// A local push server, accepted in debug builds only.
+fn is_dev_local(url: &Url) -> bool {
+    let local = match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    };
+    local && cfg!(debug_assertions)
+}
+
+/// Decode and check the Web Push subscription keys. Both or neither.
+pub fn parse_keys(
+    p256dh: Option<&str>,
+    auth: Option<&str>,
+) -> Result<Option<PushKeys>, &'static str> {
+    let p256dh = p256dh.map(str::trim).filter(|value| !value.is_empty());
+    let auth = auth.map(str::trim).filter(|value| !value.is_empty());
+    let (p256dh, auth) = match (p256dh, auth) {
+        (None, None) => return Ok(None),
+        (Some(p256dh), Some(auth)) => (p256dh, auth),
+        _ => return Err("Send both p256dh and auth, or neither"),
+    };
+    let public = decode_base64url(p256dh).ok_or("p256dh must be base64url")?;
+    PublicKey::from_sec1_bytes(&public).map_err(|_| "p256dh is not a P-256 public key")?;
+    if decode_base64url(auth).map(|secret| secret.len()) != Some(16) {
+        return Err("auth must be 16 bytes of base64url");
+    }
+    Ok(Some(PushKeys {
+        p256dh: p256dh.to_string(),
+        auth: auth.to_string(),
+    }))
+}
+
+/// Encrypt `plaintext` for the subscription with a fresh key pair and salt.
+pub fn encrypt_payload(plaintext: &[u8], keys: &PushKeys) -> Result<Vec<u8>, &'static str> {
+    let ua_public = decode_base64url(&keys.p256dh).ok_or("p256dh must be base64url")?;
+    let auth = decode_base64url(&keys.auth).ok_or("auth must be base64url")?;
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let secret = SecretKey::random(&mut rand::thread_rng());
+    encrypt_payload_with(plaintext, &ua_public, &auth, &secret, salt)
+}
+
+/// RFC 8291 `aes128gcm` body with the given application server key and
+/// salt: `salt || rs || idlen || as_public || ciphertext`.
+pub fn encrypt_payload_with(
+    plaintext: &[u8],
+    ua_public: &[u8],
+    auth: &[u8],
+    as_secret: &SecretKey,
+    salt: [u8; 16],
+) -> Result<Vec<u8>, &'static str> {
+    if plaintext.len() > MAX_PLAINTEXT_BYTES {
+        return Err("payload too large for one push message");
+    }
+    let ua_key = PublicKey::from_sec1_bytes(ua_public).map_err(|_| "invalid p256dh key")?;
+    let as_public = as_secret.public_key().to_encoded_point(false);
+    let shared = AffinePoint::from(ua_key.to_projective() * *as_secret.to_nonzero_scalar())
+        .to_encoded_point(false);
+    let shared_x = shared.x().ok_or("invalid shared secret")?;
+
+    let prk_key = hmac_sha256(auth, &[shared_x.as_slice()]);
+    let ikm = hmac_sha256(
+        &prk_key,
+        &[
+            &b"WebPush: info\0"[..],
+            ua_public,
+            as_public.as_bytes(),
+            &[1],
+        ],
+    );
+    let prk = hmac_sha256(&salt, &[&ikm]);
+    let cek = hmac_sha256(&prk, &[&b"Content-Encoding: aes128gcm\0"[..], &[1]]);
+    let nonce = hmac_sha256(&prk, &[&b"Content-Encoding: nonce\0"[..], &[1]]);
+
+    let mut record = Vec::with_capacity(plaintext.len() + 1);
+    record.extend_from_slice(plaintext);
+    // Padding delimiter of the last (only) record.
+    record.push(2);
+    let ciphertext = Aes128Gcm::new_from_slice(&cek[..16])
+        .map_err(|_| "invalid content key")?
+        .encrypt(Nonce::from_slice(&nonce[..12]), record.as_slice())
+        .map_err(|_| "encryption failed")?;
+
+    let mut body = Vec::with_capacity(86 + ciphertext.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public.as_bytes().len() as u8);
+    body.extend_from_slice(as_public.as_bytes());
+    body.extend_from_slice(&ciphertext);
+    Ok(body)
+}
+
+/// Send `body` to the user's push endpoint.
+pub async fn send_to_push(
+    state: &Arc<AppState>,
+    user: &User,
+    body: &str,
+) -> Result<ChannelMessageId, ChannelError> {
+    let channel = state
+        .channel_router
+        .channel(CHANNEL_ID)
+        .ok_or_else(|| ChannelError::NotConfigured(CHANNEL_ID.to_string()))?;
+    let endpoint = PushEndpointRepository::new(state.pg_pool.clone())
+        .find(user.id)
+        .map_err(|e| ChannelError::Other(e.to_string()))?
+        .map(|registration| registration.endpoint)
+        .ok_or_else(|| ChannelError::NotConfigured("no push endpoint".into()))?;
+    channel.send(user, &endpoint, body, None).await
+}
+
+/// Whether a notification of `content_type` should try push before SMS.
+/// Only digests and rule notifications can be routed here.
+pub fn routes_to_push(state: &Arc<AppState>, user_id: i32, content_type: &str) -> bool {
+    if state.channel_router.channel(CHANNEL_ID).is_none() {
+        return false;
+    }
+    let registration = match PushEndpointRepository::new(state.pg_pool.clone()).find(user_id) {
+        Ok(Some(registration)) => registration,
+        Ok(None) => return false,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to read push routing for user {}; using SMS: {}",
+                user_id,
+                e
+            );
+            return false;
+        }
+    };
+    match NotificationCategory::from_content_type(content_type) {
+        NotificationCategory::Digest => registration.push_digests,
+        NotificationCategory::Rules => registration.push_rules,
+        _ => false,
+    }
+}
+
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(key).expect("HMAC takes keys of any size");
+    for part in parts {
+        mac.update(part);
+    }
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&mac.finalize().into_bytes());
+    digest
+}
+
+fn decode_base64url(value: &str) -> Option<Vec<u8>> {
+    URL_SAFE_NO_PAD.decode(value.trim_end_matches('=')).ok()
+}
+
+fn truncate_to_bytes(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
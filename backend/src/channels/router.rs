@@ -13,14 +13,32 @@
 //! `users` overriding the per-country choice as the first attempt. Fallback
 //! attempts after the first prepend a short tag so the user can tell the
 //! message is still legit Lightfriend reaching out on a different carrier.
+//!
+//! Delivery health (`channels::health`) reorders that list: a provider whose
+//! status callbacks show it failing in the user's country is tried last
+//! until its circuit cools down, and a send that a provider accepted but
+//! later reported undelivered is re-sent through the rest of the order.
+//!
+//! SMS bodies pass through `sms_encoding::outbound_sms_body` first, which
+//! folds characters that would force UCS-2 for users who opted in, and each
+//! attempt is logged with its exact segment count. A provider that cannot
+//! carry MMS (`supports_media() == false`) gets the image's alt text, or
+//! its link, appended to the body instead of a hard failure.
+//!
+//! Voice has its own registry and its own order. `place_call` dials the
+//! country's `voice_routes` row (default `["twilio"]`) and moves on to the
+//! next provider when one refuses the call. Whether the user picks up is
+//! not known at this point, so there is no health scoring for voice.
 
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use crate::channels::health::{AsyncRetry, HealthConfig, ProviderHealth, ProviderHealthSnapshot};
 use crate::channels::traits::{
-    ChannelError, ChannelMessageId, IncomingMessage, MediaRef, MessageChannel,
+    ChannelError, ChannelMessageId, IncomingMessage, MediaRef, MessageChannel, VoiceChannel,
 };
 use crate::models::user_models::User;
+use crate::utils::sms_encoding::{outbound_sms_body, segment_info};
 use crate::utils::sms_sanitizer::{clamp_sms_body, SMS_BODY_CHARACTER_LIMIT};
 
 /// Tag prepended to fallback-provider sends so the recipient can tell the
@@ -35,13 +53,24 @@ pub struct ChannelRouter {
     /// primary at runtime without restarting. Keys are ISO-3166-1 alpha-2
     /// strings (e.g. "US").
     routes: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    health: ProviderHealth,
+    voice_channels: HashMap<&'static str, Arc<dyn VoiceChannel>>,
+    /// Per-country ordered voice provider ids, mirroring `voice_routes`.
+    voice_routes: Arc<RwLock<HashMap<String, Vec<String>>>>,
 }
 
 impl ChannelRouter {
     pub fn new() -> Self {
+        Self::with_health_config(HealthConfig::default())
+    }
+
+    pub fn with_health_config(config: HealthConfig) -> Self {
         Self {
             channels: HashMap::new(),
             routes: Arc::new(RwLock::new(HashMap::new())),
+            health: ProviderHealth::new(config),
+            voice_channels: HashMap::new(),
+            voice_routes: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -55,6 +84,16 @@ impl ChannelRouter {
         self.channels.get(id)
     }
 
+    /// Register a voice channel implementation. Last write wins per `id`.
+    pub fn register_voice(&mut self, ch: Arc<dyn VoiceChannel>) {
+        self.voice_channels.insert(ch.id(), ch);
+    }
+
+    /// Look up a voice channel impl by id.
+    pub fn voice_channel(&self, id: &str) -> Option<&Arc<dyn VoiceChannel>> {
+        self.voice_channels.get(id)
+    }
+
     /// Replace the in-memory routing order for a country. Called once per
     /// row at startup from `main.rs` after loading `provider_routes`, and
     /// again from the admin endpoint when an operator flips the order.
@@ -76,6 +115,48 @@ impl ChannelRouter {
         self.routes.read().map(|r| r.clone()).unwrap_or_default()
     }
 
+    /// Voice counterpart of `set_route`, fed from `voice_routes`.
+    pub fn set_voice_route(&self, country_code: &str, provider_order: Vec<String>) {
+        if let Ok(mut routes) = self.voice_routes.write() {
+            routes.insert(country_code.to_uppercase(), provider_order);
+        }
+    }
+
+    pub fn clear_voice_route(&self, country_code: &str) {
+        if let Ok(mut routes) = self.voice_routes.write() {
+            routes.remove(&country_code.to_uppercase());
+        }
+    }
+
+    pub fn current_voice_routes(&self) -> HashMap<String, Vec<String>> {
+        self.voice_routes
+            .read()
+            .map(|r| r.clone())
+            .unwrap_or_default()
+    }
+
+    /// Live delivery scoreboard — for the admin dashboard.
+    pub fn health_snapshot(&self) -> ProviderHealthSnapshot {
+        self.health.snapshot()
+    }
+
+    /// Feed a delivery status callback into the health scores. `status` is
+    /// in Twilio's vocabulary; `to_phone` places messages the router no
+    /// longer remembers in a country. Returns the re-send to run (via
+    /// `retry_undelivered`) when a recent send failed asynchronously.
+    pub fn record_delivery_status(
+        &self,
+        provider: &str,
+        message_id: &str,
+        status: &str,
+        to_phone: Option<&str>,
+    ) -> Option<AsyncRetry> {
+        let provider = canonical_channel_id(provider)?;
+        let country = to_phone.and_then(crate::utils::country::get_country_code_from_phone);
+        self.health
+            .record_status(provider, message_id, status, country.as_deref())
+    }
+
     /// Reply through whichever channel the user reached us on.
     pub async fn reply(
         &self,
@@ -105,8 +186,7 @@ impl ChannelRouter {
             .channels
             .get(channel_id)
             .ok_or_else(|| ChannelError::NotConfigured(channel_id.to_string()))?;
-        let body = crate::utils::sms_sanitizer::apply_sms_url_filter(body);
-        let body = clamp_sms_body(&body);
+        let body = clamp_sms_body(&outbound_sms_body(user, body));
         chan.send(user, address, &body, None).await
     }
 
@@ -117,9 +197,9 @@ impl ChannelRouter {
     /// carrier is reaching them.
     ///
     /// Provider-agnostic preprocessing happens here — every outbound SMS
-    /// goes through the same URL/defang sanitizer, the same empty-body
-    /// guard, and the same dev-mode skip regardless of which provider
-    /// ultimately delivers it.
+    /// goes through the same URL/defang sanitizer, the same opt-in GSM-7
+    /// transliteration, the same empty-body guard, and the same dev-mode
+    /// skip regardless of which provider ultimately delivers it.
     ///
     /// Message-history logging is intentionally NOT owned by the router:
     /// callers have richer context about what to log (e.g. citation-
@@ -132,8 +212,9 @@ impl ChannelRouter {
         media: Option<MediaRef>,
     ) -> Result<ChannelMessageId, ChannelError> {
         // 1. Sanitize URLs + re-fang defanged emails/domains so phishing-
-        //    pattern false positives don't trip carrier filters.
-        let body = crate::utils::sms_sanitizer::apply_sms_url_filter(body);
+        //    pattern false positives don't trip carrier filters, then fold
+        //    UCS-2-only characters if the user asked for plain GSM-7.
+        let body = outbound_sms_body(user, body);
 
         // 2. Refuse empty messages. Sending a body-less request to a provider
         //    is malformed (Twilio errors with 21619) and contributes to
@@ -160,19 +241,62 @@ impl ChannelRouter {
                 "no SMS providers registered".into(),
             ));
         }
+        self.walk(user, &body, media, &order, 0).await
+    }
 
-        let total = order.len();
+    /// Re-send a delivery that a provider accepted but then reported as
+    /// failed. Walks the providers that were left in the original order;
+    /// every attempt carries `FALLBACK_PREFIX`.
+    pub async fn retry_undelivered(
+        &self,
+        user: &User,
+        retry: AsyncRetry,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        if std::env::var("ENVIRONMENT").unwrap_or_default() == "development" {
+            tracing::info!("NOT SENDING MESSAGE SINCE ENVIRONMENT IS DEVELOPMENT");
+            return Ok(ChannelMessageId("dev_not_sending".to_string()));
+        }
+        self.walk(
+            user,
+            &retry.body,
+            retry.media,
+            &retry.remaining,
+            retry.attempts,
+        )
+        .await
+    }
+
+    /// Try `order` until one provider accepts. `prior_attempts` counts sends
+    /// already made for this message; any attempt after the very first gets
+    /// the fallback prefix. Accepted sends are remembered so an async
+    /// failure can continue with the rest of the order.
+    async fn walk(
+        &self,
+        user: &User,
+        body: &str,
+        media: Option<MediaRef>,
+        order: &[&'static str],
+        prior_attempts: usize,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let country = user_country(user);
+        let total = prior_attempts + order.len();
         let mut last_err: Option<ChannelError> = None;
         for (idx, channel_id) in order.iter().enumerate() {
             let chan = match self.channels.get(channel_id) {
                 Some(c) => c,
                 None => continue,
             };
+            let attempt = prior_attempts + idx;
 
-            let attempt_body = if idx == 0 {
-                body.clone()
+            // Plain-SMS providers get the image's text version instead.
+            let (chan_body, chan_media) = match &media {
+                Some(m) if !chan.supports_media() => (body_with_media_text(body, m), None),
+                _ => (body.to_string(), media.clone()),
+            };
+            let attempt_body = if attempt == 0 {
+                chan_body
             } else {
-                format!("{}{}", FALLBACK_PREFIX, body)
+                format!("{}{}", FALLBACK_PREFIX, chan_body)
             };
             let attempt_body_len = attempt_body.chars().count();
             let attempt_body = clamp_sms_body(&attempt_body);
@@ -185,21 +309,39 @@ impl ChannelRouter {
                     SMS_BODY_CHARACTER_LIMIT
                 );
             }
+            let segments = segment_info(&attempt_body);
+            tracing::debug!(
+                "Outbound SMS for user {} via '{}': {} segment(s), {:?}",
+                user.id,
+                channel_id,
+                segments.segments,
+                segments.encoding
+            );
 
             match chan
-                .send(user, &user.phone_number, &attempt_body, media.clone())
+                .send(user, &user.phone_number, &attempt_body, chan_media)
                 .await
             {
                 Ok(id) => {
-                    if idx > 0 {
+                    if attempt > 0 {
                         tracing::warn!(
                             "SMS delivered via fallback provider '{}' for user {} (attempt {}/{})",
                             channel_id,
                             user.id,
-                            idx + 1,
+                            attempt + 1,
                             total
                         );
                     }
+                    self.health.record_accepted(
+                        channel_id,
+                        id.as_str(),
+                        user.id,
+                        &country,
+                        body,
+                        media,
+                        order[idx + 1..].to_vec(),
+                        attempt + 1,
+                    );
                     return Ok(id);
                 }
                 Err(e) => {
@@ -207,10 +349,11 @@ impl ChannelRouter {
                         "SMS provider '{}' failed for user {} (attempt {}/{}): {}",
                         channel_id,
                         user.id,
-                        idx + 1,
+                        attempt + 1,
                         total,
                         e
                     );
+                    self.health.record_rejected(channel_id, &country);
                     last_err = Some(e);
                 }
             }
@@ -231,7 +374,13 @@ impl ChannelRouter {
     /// 2. The country's `provider_routes` row, filtered to registered
     ///    channels and de-duplicated.
     /// 3. Default: `["twilio"]` if it's registered, else any one
-    ///    registered channel.
+    ///    registered SMS provider. Non-SMS channels such as Matrix are
+    ///    never part of the order.
+    ///
+    /// Providers whose circuit is open for the user's country then move to
+    /// the back, keeping their relative order. That includes a pinned
+    /// provider; they are never dropped, so a country whose providers are
+    /// all unhealthy still gets every attempt.
     pub fn pick_channels_for(&self, user: &User) -> Vec<&'static str> {
         let mut order: Vec<&'static str> = Vec::with_capacity(3);
         let push = |v: &mut Vec<&'static str>,
@@ -248,9 +397,7 @@ impl ChannelRouter {
             }
         }
 
-        let country = crate::utils::country::get_country_code_from_phone(&user.phone_number)
-            .unwrap_or_default()
-            .to_uppercase();
+        let country = user_country(user);
         if let Ok(routes) = self.routes.read() {
             if let Some(ids) = routes.get(&country) {
                 for id in ids {
@@ -264,12 +411,108 @@ impl ChannelRouter {
         if order.is_empty() {
             push(&mut order, "twilio", &self.channels);
             if order.is_empty() {
-                if let Some(first) = self.channels.keys().next() {
+                if let Some(first) = self
+                    .channels
+                    .keys()
+                    .find(|id| canonical_channel_id(id).is_some())
+                {
                     order.push(*first);
                 }
             }
         }
 
+        let (healthy, demoted): (Vec<_>, Vec<_>) = order
+            .into_iter()
+            .partition(|id| !self.health.is_demoted(id, &country));
+        if !demoted.is_empty() {
+            tracing::info!(
+                "Demoting unhealthy SMS providers {:?} for country '{}'",
+                demoted,
+                country
+            );
+        }
+        healthy.into_iter().chain(demoted).collect()
+    }
+
+    /// Call the user, speaking `greeting` when they answer. Tries voice
+    /// providers in `pick_voice_channels_for` order and returns the first
+    /// provider call id; a provider that refuses the call hands off to the
+    /// next one.
+    pub async fn place_call(
+        &self,
+        user: &User,
+        greeting: &str,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let order = self.pick_voice_channels_for(user);
+        if order.is_empty() {
+            return Err(ChannelError::NotConfigured(
+                "no voice providers registered".into(),
+            ));
+        }
+        let total = order.len();
+        let mut last_err: Option<ChannelError> = None;
+        for (attempt, channel_id) in order.iter().enumerate() {
+            let Some(chan) = self.voice_channels.get(channel_id) else {
+                continue;
+            };
+            match chan.place_call(user, &user.phone_number, greeting).await {
+                Ok(id) => {
+                    if attempt > 0 {
+                        tracing::warn!(
+                            "Call placed via fallback voice provider '{}' for user {} (attempt {}/{})",
+                            channel_id,
+                            user.id,
+                            attempt + 1,
+                            total
+                        );
+                    }
+                    return Ok(id);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Voice provider '{}' failed for user {} (attempt {}/{}): {}",
+                        channel_id,
+                        user.id,
+                        attempt + 1,
+                        total,
+                        e
+                    );
+                    last_err = Some(e);
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| ChannelError::SendFailed("all providers exhausted".into())))
+    }
+
+    /// Ordered voice channel ids to dial for this user: the country's
+    /// `voice_routes` row filtered to registered voice channels, else
+    /// `["twilio"]` if registered, else any one registered voice channel.
+    /// `preferred_sms_provider` is an SMS pin and doesn't apply here.
+    pub fn pick_voice_channels_for(&self, user: &User) -> Vec<&'static str> {
+        let mut order: Vec<&'static str> = Vec::with_capacity(3);
+        let country = user_country(user);
+        if let Ok(routes) = self.voice_routes.read() {
+            if let Some(ids) = routes.get(&country) {
+                for id in ids {
+                    if let Some(static_id) = canonical_channel_id(id) {
+                        if !order.contains(&static_id)
+                            && self.voice_channels.contains_key(static_id)
+                        {
+                            order.push(static_id);
+                        }
+                    }
+                }
+            }
+        }
+
+        if order.is_empty() {
+            if self.voice_channels.contains_key("twilio") {
+                order.push("twilio");
+            } else if let Some(first) = self.voice_channels.keys().next() {
+                order.push(*first);
+            }
+        }
         order
     }
 
@@ -290,9 +533,16 @@ impl Default for ChannelRouter {
     }
 }
 
+fn user_country(user: &User) -> String {
+    crate::utils::country::get_country_code_from_phone(&user.phone_number)
+        .unwrap_or_default()
+        .to_uppercase()
+}
+
 /// Map a user-provided channel id string to the static id used in the
-/// registration table. Returns `None` for unknown ids so we don't poison
-/// the order with channels we'll never register.
+/// registration tables (SMS and voice use the same ids). Returns `None`
+/// for unknown ids so we don't poison the order with channels we'll never
+/// register.
 fn canonical_channel_id(s: &str) -> Option<&'static str> {
     match s {
         "twilio" => Some("twilio"),
@@ -301,3 +551,20 @@ fn canonical_channel_id(s: &str) -> Option<&'static str> {
         _ => None,
     }
 }
+
+/// Text stand-in for `media` on a channel that cannot attach it: the alt
+/// text of a rendered image, or the link itself. Raw bytes have no text
+/// form and are dropped.
+fn body_with_media_text(body: &str, media: &MediaRef) -> String {
+    let text = match media {
+        MediaRef::Url(url) => {
+            crate::rendering::store::alt_text_for_url(url).unwrap_or_else(|| url.clone())
+        }
+        MediaRef::Bytes { .. } => return body.to_string(),
+    };
+    if body.trim().is_empty() {
+        text
+    } else {
+        format!("{}\n\n{}", body, text)
+    }
+}
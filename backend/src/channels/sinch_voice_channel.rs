@@ -0,0 +1,148 @@
+//! Sinch Voice channel — a custom callout whose call is connected straight
+//! to our media stream. `from_env` returns `None` unless a Voice
+//! application is configured, and the channel simply isn't registered.
+//!
+//! Auth: Basic with the Voice application key and secret. Dial:
+//! `POST /calling/v1/callouts` with `method: customCallout`; its ICE SVAML
+//! is a `connectStream` action to a one-time `/api/voice/sinch/ws` URL at
+//! 8kHz, so audio matches the pipeline's rate and `api::voice_stream_bridge`
+//! only has to convert between L16 and mu-law.
+
+use async_trait::async_trait;
+
+use crate::api::voice_stream_bridge::{StreamProvider, StreamTickets};
+use crate::channels::traits::{ChannelError, ChannelMessageId, VoiceChannel};
+use crate::models::user_models::User;
+
+const SINCH_CALLING_DEFAULT_BASE: &str = "https://calling.api.sinch.com";
+
+/// Longest a notification call may stream, in seconds.
+const MAX_CALL_SECS: u32 = 3600;
+
+pub struct SinchVoiceChannel {
+    application_key: String,
+    application_secret: String,
+    from_number: String,
+    tickets: StreamTickets,
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl SinchVoiceChannel {
+    /// Needs `SINCH_VOICE_APPLICATION_KEY`, `SINCH_VOICE_APPLICATION_SECRET`,
+    /// a FROM number (`SINCH_VOICE_FROM_NUMBER`, else the SMS
+    /// `SINCH_US_FROM_NUMBER`). Stream URLs are issued from `tickets`,
+    /// shared with `AppState`.
+    pub fn from_env(tickets: StreamTickets) -> Option<Self> {
+        let application_key = std::env::var("SINCH_VOICE_APPLICATION_KEY")
+            .ok()
+            .filter(|s| !s.is_empty())?;
+        let application_secret = std::env::var("SINCH_VOICE_APPLICATION_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty())?;
+        let from_number = std::env::var("SINCH_VOICE_FROM_NUMBER")
+            .or_else(|_| std::env::var("SINCH_US_FROM_NUMBER"))
+            .ok()
+            .filter(|s| !s.is_empty())?;
+        Some(Self {
+            application_key,
+            application_secret,
+            from_number,
+            tickets,
+            base_url: SINCH_CALLING_DEFAULT_BASE.to_string(),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    /// Construct with an explicit base URL. Used by tests to point at wiremock.
+    pub fn with_base_url(
+        application_key: impl Into<String>,
+        application_secret: impl Into<String>,
+        from_number: impl Into<String>,
+        tickets: StreamTickets,
+        base_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            application_key: application_key.into(),
+            application_secret: application_secret.into(),
+            from_number: from_number.into(),
+            tickets,
+            base_url: base_url.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl VoiceChannel for SinchVoiceChannel {
+    fn id(&self) -> &'static str {
+        "sinch"
+    }
+
+    async fn place_call(
+        &self,
+        user: &User,
+        address: &str,
+        greeting: &str,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let stream_url = self.tickets.stream_url(
+            StreamProvider::Sinch,
+            user.id,
+            greeting,
+            chrono::Utc::now().timestamp(),
+        );
+        let svaml = serde_json::json!({
+            "action": {
+                "name": "connectStream",
+                "destination": {
+                    "type": "Websocket",
+                    "endpoint": stream_url,
+                },
+                "streamingOptions": { "version": 1, "sampleRate": 8000 },
+                "maxDuration": MAX_CALL_SECS,
+            }
+        });
+        let payload = serde_json::json!({
+            "method": "customCallout",
+            "customCallout": {
+                "cli": self.from_number,
+                "destination": { "type": "number", "endpoint": address },
+                "ice": svaml.to_string(),
+            }
+        });
+
+        let res = self
+            .http
+            .post(format!("{}/calling/v1/callouts", self.base_url))
+            .basic_auth(&self.application_key, Some(&self.application_secret))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let body = res.text().await.unwrap_or_default();
+            return Err(ChannelError::SendFailed(format!(
+                "sinch http {}: {}",
+                status, body
+            )));
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct R {
+            call_id: String,
+        }
+
+        let parsed: R = res
+            .json()
+            .await
+            .map_err(|e| ChannelError::SendFailed(format!("sinch parse: {}", e)))?;
+        tracing::info!(
+            "Outbound notification call initiated for user {} via Sinch",
+            user.id
+        );
+        Ok(ChannelMessageId(parsed.call_id))
+    }
+}
@@ -0,0 +1,135 @@
+//! Telnyx voice channel — Call Control dial with a bidirectional media
+//! stream. `from_env` returns `None` unless the Call Control application
+//! is configured, and the channel simply isn't registered.
+//!
+//! Dial: `POST /v2/calls` on the Call Control application (its
+//! `connection_id`), with `stream_url` pointing at a one-time
+//! `/api/voice/telnyx/ws` URL. Telnyx opens the stream when the callee
+//! answers; `api::voice_stream_bridge` runs the voice pipeline on it.
+
+use async_trait::async_trait;
+
+use crate::api::voice_stream_bridge::{StreamProvider, StreamTickets};
+use crate::channels::traits::{ChannelError, ChannelMessageId, VoiceChannel};
+use crate::models::user_models::User;
+
+const TELNYX_DEFAULT_BASE: &str = "https://api.telnyx.com";
+
+/// Hang up on calls nobody answers within this many seconds.
+const RING_TIMEOUT_SECS: u32 = 30;
+
+pub struct TelnyxVoiceChannel {
+    api_key: String,
+    connection_id: String,
+    from_number: String,
+    tickets: StreamTickets,
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl TelnyxVoiceChannel {
+    /// Needs `TELNYX_API_KEY`, `TELNYX_CALL_CONTROL_APP_ID`, a FROM number
+    /// (`TELNYX_VOICE_FROM_NUMBER`, else the SMS `TELNYX_US_FROM_NUMBER`).
+    /// Stream URLs are issued from `tickets`, shared with `AppState`.
+    pub fn from_env(tickets: StreamTickets) -> Option<Self> {
+        let api_key = std::env::var("TELNYX_API_KEY")
+            .ok()
+            .filter(|s| !s.is_empty())?;
+        let connection_id = std::env::var("TELNYX_CALL_CONTROL_APP_ID")
+            .ok()
+            .filter(|s| !s.is_empty())?;
+        let from_number = std::env::var("TELNYX_VOICE_FROM_NUMBER")
+            .or_else(|_| std::env::var("TELNYX_US_FROM_NUMBER"))
+            .ok()
+            .filter(|s| !s.is_empty())?;
+        Some(Self {
+            api_key,
+            connection_id,
+            from_number,
+            tickets,
+            base_url: TELNYX_DEFAULT_BASE.to_string(),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    /// Construct with an explicit base URL. Used by tests to point at wiremock.
+    pub fn with_base_url(
+        api_key: impl Into<String>,
+        connection_id: impl Into<String>,
+        from_number: impl Into<String>,
+        tickets: StreamTickets,
+        base_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            api_key: api_key.into(),
+            connection_id: connection_id.into(),
+            from_number: from_number.into(),
+            tickets,
+            base_url: base_url.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl VoiceChannel for TelnyxVoiceChannel {
+    fn id(&self) -> &'static str {
+        "telnyx"
+    }
+
+    async fn place_call(
+        &self,
+        user: &User,
+        address: &str,
+        greeting: &str,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let now = chrono::Utc::now().timestamp();
+        let payload = serde_json::json!({
+            "connection_id": self.connection_id,
+            "to": address,
+            "from": self.from_number,
+            "timeout_secs": RING_TIMEOUT_SECS,
+            "stream_url": self.tickets.stream_url(StreamProvider::Telnyx, user.id, greeting, now),
+            "stream_track": "inbound_track",
+            "stream_bidirectional_mode": "rtp",
+            "stream_bidirectional_codec": "PCMU",
+        });
+
+        let res = self
+            .http
+            .post(format!("{}/v2/calls", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let body = res.text().await.unwrap_or_default();
+            return Err(ChannelError::SendFailed(format!(
+                "telnyx http {}: {}",
+                status, body
+            )));
+        }
+
+        #[derive(serde::Deserialize)]
+        struct R {
+            data: D,
+        }
+        #[derive(serde::Deserialize)]
+        struct D {
+            call_control_id: String,
+        }
+
+        let parsed: R = res
+            .json()
+            .await
+            .map_err(|e| ChannelError::SendFailed(format!("telnyx parse: {}", e)))?;
+        tracing::info!(
+            "Outbound notification call initiated for user {} via Telnyx",
+            user.id
+        );
+        Ok(ChannelMessageId(parsed.data.call_control_id))
+    }
+}
@@ -1,5 +1,6 @@
 //! Twilio adapter — exposes the existing `TwilioMessageService` as a
-//! `MessageChannel` without rewriting it.
+//! `MessageChannel` without rewriting it, and Twilio calls as a
+//! `VoiceChannel` (TwiML that streams into the voice pipeline).
 //!
 //! All current behaviour is preserved: URL filtering, empty-body guard,
 //! preferred-number resolution, BYOT credentials, status callbacks, voice
@@ -14,7 +15,9 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use crate::api::twilio_client::TwilioClient;
-use crate::channels::traits::{ChannelError, ChannelMessageId, MediaRef, MessageChannel};
+use crate::channels::traits::{
+    ChannelError, ChannelMessageId, MediaRef, MessageChannel, VoiceChannel,
+};
 use crate::models::user_models::User;
 use crate::services::twilio_message_service::{TwilioMessageError, TwilioMessageService};
 
@@ -75,3 +78,30 @@ where
             .map_err(|e| ChannelError::SendFailed(e.to_string()))
     }
 }
+
+#[async_trait]
+impl<T> VoiceChannel for TwilioChannel<T>
+where
+    T: TwilioClient + 'static,
+{
+    fn id(&self) -> &'static str {
+        "twilio"
+    }
+
+    /// Calls `user.phone_number` like SMS sends do; `address` is ignored.
+    async fn place_call(
+        &self,
+        user: &User,
+        _address: &str,
+        greeting: &str,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        let twiml = crate::api::voice_pipeline::notification_call_twiml(user.id, greeting);
+        match self.inner.place_call(user, &twiml, None).await {
+            Ok(sid) => Ok(ChannelMessageId(sid)),
+            Err(TwilioMessageError::NoSendingNumber) => Err(ChannelError::NotConfigured(
+                "no from number available for voice call".into(),
+            )),
+            Err(e) => Err(ChannelError::SendFailed(e.to_string())),
+        }
+    }
+}
@@ -1317,6 +1317,37 @@ pub async fn get_alert_count(
     Ok(Json(AlertCountResponse { count }))
 }
 
+/// Progress of re-encrypting stored secrets under the newest key
+/// GET /api/admin/encryption/rotation
+pub async fn get_encryption_rotation(
+    State(state): State<Arc<AppState>>,
+) -> Result<
+    Json<crate::services::key_rotation::RotationStatus>,
+    (StatusCode, Json<serde_json::Value>),
+> {
+    crate::services::key_rotation::status(&state.pg_pool)
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to get encryption rotation status: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("Failed to get rotation status: {}", e)})),
+            )
+        })
+}
+
+/// Start (or resume) re-encrypting stored secrets under the newest key
+/// POST /api/admin/encryption/rotation
+pub async fn start_encryption_rotation(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let started = crate::services::key_rotation::start_background(state.pg_pool.clone());
+    Ok(Json(json!({
+        "started": started,
+        "message": if started { "Re-encryption started" } else { "Re-encryption is already running" },
+    })))
+}
+
 /// Acknowledge a single alert
 /// POST /api/admin/alerts/:id/acknowledge
 pub async fn acknowledge_alert(
@@ -3615,3 +3646,123 @@ pub async fn delete_provider_route(
     tracing::info!("Provider route deleted: {}", country);
     Ok(Json(json!({"deleted": country})))
 }
+
+/// Live per-provider, per-country delivery scoreboard from the router's
+/// in-memory health tracker. Resets on restart.
+pub async fn get_provider_health(
+    State(state): State<Arc<AppState>>,
+) -> Json<crate::channels::health::ProviderHealthSnapshot> {
+    Json(state.channel_router.health_snapshot())
+}
+
+// ===== Voice routes =====
+//
+// Per-country voice provider order for outbound calls. Same request and
+// response shapes as provider routes; changes hit both `voice_routes` and
+// the router's in-memory voice order.
+
+pub async fn list_voice_routes(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ProviderRouteResponse>>, (StatusCode, Json<serde_json::Value>)> {
+    let rows = crate::VoiceRoutesRepository::new(state.pg_pool.clone())
+        .list_all()
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    let resp = rows
+        .into_iter()
+        .map(|r| {
+            let order = serde_json::from_str::<Vec<String>>(&r.provider_order).unwrap_or_default();
+            ProviderRouteResponse {
+                country_code: r.country_code,
+                provider_order: order,
+                updated_at: r.updated_at,
+            }
+        })
+        .collect();
+    Ok(Json(resp))
+}
+
+pub async fn upsert_voice_route(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UpsertProviderRouteRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let country = req.country_code.trim().to_uppercase();
+    if country.len() != 2 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "country_code must be a 2-letter ISO code"})),
+        ));
+    }
+    if req.provider_order.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "provider_order must contain at least one channel id"})),
+        ));
+    }
+    for id in &req.provider_order {
+        if !KNOWN_PROVIDERS.contains(&id.as_str()) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": format!("unknown provider id '{}'; valid ids: {:?}", id, KNOWN_PROVIDERS)
+                })),
+            ));
+        }
+    }
+
+    let order_json = serde_json::to_string(&req.provider_order).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": format!("serialize: {}", e)})),
+        )
+    })?;
+
+    crate::VoiceRoutesRepository::new(state.pg_pool.clone())
+        .upsert(&country, &order_json)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    state
+        .channel_router
+        .set_voice_route(&country, req.provider_order.clone());
+
+    tracing::info!(
+        "Voice route upserted: {} -> {:?}",
+        country,
+        req.provider_order
+    );
+
+    Ok(Json(json!({
+        "country_code": country,
+        "provider_order": req.provider_order,
+    })))
+}
+
+pub async fn delete_voice_route(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(country_code): axum::extract::Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let country = country_code.trim().to_uppercase();
+    crate::VoiceRoutesRepository::new(state.pg_pool.clone())
+        .delete(&country)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": format!("Database error: {}", e)})),
+            )
+        })?;
+
+    state.channel_router.clear_voice_route(&country);
+
+    tracing::info!("Voice route deleted: {}", country);
+    Ok(Json(json!({"deleted": country})))
+}
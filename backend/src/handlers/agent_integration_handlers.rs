@@ -1,18 +1,27 @@
-//! Write-only API for local AI-agent clients.
+//! Scoped API for local AI-agent clients.
 //!
-//! This boundary intentionally has no read/list/search endpoint. A credential
-//! can only create a one-shot reminder or arm a sender-scoped email reply
-//! watch. Responses never contain reminder text, contacts, message content, or
-//! provider identifiers.
+//! A credential can only do what the user granted when approving its pairing.
+//! Write scopes create one-shot reminders, arm sender-scoped email reply
+//! watches, text the user's own number, or pause and resume rules; they share
+//! the daily action cap and their responses never contain content. Read scopes
+//! return a narrow projection of one kind of data: event descriptions and
+//! times, digest items (platform, summary and time, without the sender), or
+//! rule names. There is no search and no access to contacts, message bodies,
+//! or provider identifiers. Every call by a live credential, including scope
+//! denials, is recorded in the content-free audit trail.
 
 use crate::handlers::auth_middleware::AuthUser;
+use crate::models::agent_integration_models::{
+    canonical_scopes, AgentCreatedItem, AgentCredential, NewAgentCreatedItem, DEFAULT_AGENT_SCOPES,
+};
 use crate::models::ontology_models::NewOntEvent;
 use crate::repositories::agent_integration_repository::{
-    AgentIntegrationRepository, CredentialClaim, CredentialIssue, IdempotencyClaim, PairingPoll,
+    contact_hash, AgentIntegrationRepository, CredentialClaim, CredentialIssue, IdempotencyClaim,
+    PairingPoll,
 };
 use crate::{AppState, UserCoreOps};
 use axum::extract::{OriginalUri, Path, State};
-use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode, Uri};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use governor::{Quota, RateLimiter};
@@ -28,6 +37,9 @@ const PAIRING_TTL_SECONDS: i32 = 10 * 60;
 const CREDENTIAL_TTL_SECONDS: i32 = 90 * 24 * 60 * 60;
 const MAX_REMINDER_SECONDS: i64 = 365 * 24 * 60 * 60;
 const MAX_ACTIVE_REPLY_WATCHES: i64 = 5;
+const MAX_SELF_SMS_CHARS: usize = 320;
+const MAX_READ_ITEMS: usize = 50;
+const TOGGLEABLE_RULE_STATUSES: [&str; 2] = ["active", "paused"];
 const IDEMPOTENCY_HEADER: &str = "idempotency-key";
 
 #[derive(Deserialize)]
@@ -63,6 +75,9 @@ pub struct PollPairingResponse {
 #[derive(Deserialize)]
 pub struct ApprovePairingRequest {
     user_code: String,
+    /// Scope names to grant; the original write-only pair when omitted.
+    #[serde(default)]
+    scopes: Option<Vec<String>>,
 }
 
 #[derive(Serialize)]
@@ -93,11 +108,97 @@ pub struct CreateReplyWatchRequest {
     expires_in_seconds: i32,
 }
 
+/// Exactly one of `at` (RFC 3339 with offset) or `minutes` from now.
+#[derive(Deserialize)]
+pub struct SnoozeReminderRequest {
+    #[serde(default)]
+    at: Option<String>,
+    #[serde(default)]
+    minutes: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct SelfSmsRequest {
+    message: String,
+}
+
+#[derive(Deserialize)]
+pub struct RuleStatusRequest {
+    status: String,
+}
+
 #[derive(Serialize)]
 pub struct ActionResponse {
     status: &'static str,
 }
 
+#[derive(Serialize)]
+pub struct CreatedReminderSummary {
+    id: i32,
+    remind_at: Option<i32>,
+    status: &'static str,
+    created_at: i32,
+}
+
+#[derive(Serialize)]
+pub struct RemindersResponse {
+    status: &'static str,
+    reminders: Vec<CreatedReminderSummary>,
+}
+
+#[derive(Serialize)]
+pub struct CreatedReplyWatchSummary {
+    id: i32,
+    expires_at: Option<i32>,
+    created_at: i32,
+}
+
+#[derive(Serialize)]
+pub struct ReplyWatchesResponse {
+    status: &'static str,
+    reply_watches: Vec<CreatedReplyWatchSummary>,
+}
+
+#[derive(Serialize)]
+pub struct AgentEvent {
+    id: i32,
+    description: String,
+    remind_at: Option<i32>,
+    due_at: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct EventsResponse {
+    status: &'static str,
+    events: Vec<AgentEvent>,
+}
+
+#[derive(Serialize)]
+pub struct AgentDigestItem {
+    platform: String,
+    summary: Option<String>,
+    created_at: i32,
+}
+
+#[derive(Serialize)]
+pub struct DigestResponse {
+    status: &'static str,
+    items: Vec<AgentDigestItem>,
+}
+
+#[derive(Serialize)]
+pub struct AgentRule {
+    id: i32,
+    name: String,
+    status: String,
+}
+
+#[derive(Serialize)]
+pub struct RulesResponse {
+    status: &'static str,
+    rules: Vec<AgentRule>,
+}
+
 pub async fn start_pairing(
     State(state): State<Arc<AppState>>,
     OriginalUri(uri): OriginalUri,
@@ -220,8 +321,20 @@ pub async fn approve_pairing(
     if !valid_user_code(&normalized) {
         return minimal(StatusCode::BAD_REQUEST, "rejected");
     }
+    let scopes = match request.scopes.as_deref() {
+        None => DEFAULT_AGENT_SCOPES.to_string(),
+        Some(requested) => match canonical_scopes(requested) {
+            Some(scopes) => scopes,
+            None => return minimal(StatusCode::BAD_REQUEST, "rejected"),
+        },
+    };
     let repository = AgentIntegrationRepository::new(state.pg_pool.clone());
-    match repository.approve_pairing(auth_user.user_id, &hash_secret(&normalized), now_unix()) {
+    match repository.approve_pairing(
+        auth_user.user_id,
+        &hash_secret(&normalized),
+        &scopes,
+        now_unix(),
+    ) {
         Ok(Some(_)) => minimal(StatusCode::OK, "accepted"),
         Ok(None) => minimal(StatusCode::BAD_REQUEST, "rejected"),
         Err(error) => {
@@ -239,10 +352,10 @@ pub async fn list_credentials(State(state): State<Arc<AppState>>, auth_user: Aut
                 credentials
                     .into_iter()
                     .map(|credential| CredentialSummary {
+                        scopes: credential.scope_descriptions(),
                         id: credential.id,
                         label: credential.label,
                         token_prefix: credential.token_prefix,
-                        scopes: vec!["create reminders", "watch email replies"],
                         daily_cap: credential.daily_cap,
                         daily_used: credential.daily_used,
                         expires_at: credential.expires_at,
@@ -314,63 +427,12 @@ pub async fn create_reminder(
     else {
         return minimal(StatusCode::BAD_REQUEST, "rejected");
     };
-    let Some(idempotency_key) = idempotency_key(&headers) else {
-        return minimal(StatusCode::BAD_REQUEST, "rejected");
-    };
-    let Some(raw_token) =
-        bearer(&headers).filter(|token| valid_prefixed_hex(token, TOKEN_PREFIX, 64))
-    else {
-        return minimal(StatusCode::UNAUTHORIZED, "rejected");
-    };
-    let repository = AgentIntegrationRepository::new(state.pg_pool.clone());
-    let token_hash = hash_secret(raw_token);
-    let Some(preflight) = repository
-        .authenticate_credential(&token_hash, "reminders", now)
-        .ok()
-        .flatten()
-    else {
-        return minimal(StatusCode::UNAUTHORIZED, "rejected");
+    let call = match claim_action(&state, &headers, "reminders:write", "reminder", now) {
+        Ok(call) => call,
+        Err(response) => return response,
     };
-    let reservation = match repository.reserve_idempotency(
-        preflight.id,
-        "reminder",
-        &hash_secret(&idempotency_key),
-        now,
-    ) {
-        Ok(IdempotencyClaim::Replayed(outcome)) => {
-            return minimal(StatusCode::OK, static_outcome(&outcome));
-        }
-        Ok(IdempotencyClaim::InFlight) => return minimal(StatusCode::CONFLICT, "rejected"),
-        Ok(IdempotencyClaim::Fresh(id)) => id,
-        Err(error) => {
-            tracing::error!(error = %error, "agent reminder idempotency failed");
-            return minimal(StatusCode::INTERNAL_SERVER_ERROR, "failed");
-        }
-    };
-    let credential = match repository.claim_credential(&token_hash, "reminders", now) {
-        Ok(CredentialClaim::Accepted(credential)) if credential.id == preflight.id => credential,
-        Ok(CredentialClaim::OverCap) => {
-            let _ = repository.clear_idempotency(reservation);
-            return minimal(StatusCode::TOO_MANY_REQUESTS, "rejected");
-        }
-        _ => {
-            let _ = repository.clear_idempotency(reservation);
-            return minimal(StatusCode::UNAUTHORIZED, "rejected");
-        }
-    };
-    if !has_active_subscription(&state, credential.user_id) {
-        let _ = repository.complete_idempotency(reservation, "rejected");
-        let _ = repository.audit(
-            credential.id,
-            credential.user_id,
-            "reminder",
-            "rejected",
-            now,
-        );
-        return minimal(StatusCode::FORBIDDEN, "rejected");
-    }
     let event = NewOntEvent {
-        user_id: credential.user_id,
+        user_id: call.credential.user_id,
         description: message,
         remind_at: Some(remind_at),
         due_at: Some(remind_at),
@@ -379,19 +441,21 @@ pub async fn create_reminder(
         updated_at: now,
     };
     match state.ontology_repository.create_reminder(&event, "UTC") {
-        Ok(_) => finish_action(
-            &repository,
-            reservation,
-            &credential,
-            "reminder",
-            "accepted",
-            now,
-        ),
+        Ok(created) => {
+            call.record_created(NewAgentCreatedItem {
+                credential_id: call.credential.id,
+                user_id: call.credential.user_id,
+                item_kind: "reminder",
+                event_id: Some(created.id),
+                contact_hash: None,
+                created_at: now,
+                expires_at: None,
+            });
+            call.finish(StatusCode::OK, "accepted", now)
+        }
         Err(error) => {
-            tracing::error!(credential_id = credential.id, error = %error, "agent reminder create failed");
-            let _ = repository.clear_idempotency(reservation);
-            let _ = repository.audit(credential.id, credential.user_id, "reminder", "failed", now);
-            minimal(StatusCode::INTERNAL_SERVER_ERROR, "failed")
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent reminder create failed");
+            call.fail(now)
         }
     }
 }
@@ -401,11 +465,14 @@ pub async fn create_reminder(
 pub fn validate_reminder_input(message: &str, at: &str, now: i32) -> Option<(String, i32)> {
     let message = printable(message, 280)?;
     let parsed_at = chrono::DateTime::parse_from_rfc3339(at.trim()).ok()?;
-    let remind_at = parsed_at.timestamp();
+    validate_remind_at(parsed_at.timestamp(), now).map(|at| (message, at))
+}
+
+fn validate_remind_at(remind_at: i64, now: i32) -> Option<i32> {
     if remind_at < i64::from(now) + 60 || remind_at > i64::from(now) + MAX_REMINDER_SECONDS {
         return None;
     }
-    i32::try_from(remind_at).ok().map(|at| (message, at))
+    i32::try_from(remind_at).ok()
 }
 
 pub async fn create_reply_watch(
@@ -428,142 +495,661 @@ pub async fn create_reply_watch(
         },
         None => email.clone(),
     };
-    let Some(idempotency_key) = idempotency_key(&headers) else {
+    let now = now_unix();
+    let call = match claim_action(
+        &state,
+        &headers,
+        "reply_watch:write",
+        "reply_watch_email",
+        now,
+    ) {
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    let connection_ids = match call
+        .repository
+        .active_imap_connection_ids(call.credential.user_id)
+    {
+        Ok(ids) => ids,
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent IMAP account lookup failed");
+            return call.fail(now);
+        }
+    };
+    let expires_at = now + request.expires_in_seconds;
+    match call.repository.create_email_reply_watches(
+        call.credential.user_id,
+        &connection_ids,
+        &email,
+        &label,
+        now,
+        expires_at,
+        MAX_ACTIVE_REPLY_WATCHES,
+    ) {
+        Ok(true) => {
+            call.record_created(NewAgentCreatedItem {
+                credential_id: call.credential.id,
+                user_id: call.credential.user_id,
+                item_kind: "reply_watch",
+                event_id: None,
+                contact_hash: Some(&contact_hash(&email)),
+                created_at: now,
+                expires_at: Some(expires_at),
+            });
+            call.finish(StatusCode::OK, "accepted", now)
+        }
+        Ok(false) => call.finish(StatusCode::CONFLICT, "rejected", now),
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent reply watch create failed");
+            call.fail(now)
+        }
+    }
+}
+
+/// Reminders this credential created: ids, times and delivery state only.
+pub async fn list_created_reminders(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Response {
+    let now = now_unix();
+    let call = match authorize_read(
+        &state,
+        &uri,
+        &headers,
+        "reminders:write",
+        "reminders_read",
+        now,
+    ) {
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    match call
+        .repository
+        .created_reminders(call.credential.id, MAX_READ_ITEMS as i64)
+    {
+        Ok(reminders) => call.respond(
+            RemindersResponse {
+                status: "accepted",
+                reminders: reminders
+                    .into_iter()
+                    .map(|reminder| CreatedReminderSummary {
+                        id: reminder.id,
+                        remind_at: reminder.remind_at,
+                        status: match reminder.status.as_str() {
+                            "active" => "scheduled",
+                            "reminder_sending" => "sending",
+                            _ => "delivered",
+                        },
+                        created_at: reminder.created_at,
+                    })
+                    .collect(),
+            },
+            now,
+        ),
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent reminder list failed");
+            call.fail(now)
+        }
+    }
+}
+
+/// Cancels a scheduled reminder this credential created.
+pub async fn cancel_created_reminder(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Path(item_id): Path<i32>,
+) -> Response {
+    if uri.query().is_some() {
         return minimal(StatusCode::BAD_REQUEST, "rejected");
+    }
+    let now = now_unix();
+    let call = match claim_action(&state, &headers, "reminders:write", "reminder_cancel", now) {
+        Ok(call) => call,
+        Err(response) => return response,
     };
-    let Some(raw_token) =
-        bearer(&headers).filter(|token| valid_prefixed_hex(token, TOKEN_PREFIX, 64))
-    else {
-        return minimal(StatusCode::UNAUTHORIZED, "rejected");
+    let event_id = match call.created_item("reminder", item_id) {
+        Ok(Some(item)) => item.event_id.unwrap_or_default(),
+        Ok(None) => return call.finish(StatusCode::NOT_FOUND, "rejected", now),
+        Err(response) => return response,
     };
+    match state
+        .ontology_repository
+        .cancel_reminder(call.credential.user_id, event_id)
+    {
+        Ok(true) => call.finish(StatusCode::OK, "accepted", now),
+        Ok(false) => call.finish(StatusCode::CONFLICT, "rejected", now),
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent reminder cancel failed");
+            call.fail(now)
+        }
+    }
+}
+
+/// Moves a reminder this credential created to a new time. Delivered
+/// reminders can be snoozed too, which re-arms them.
+pub async fn snooze_created_reminder(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Path(item_id): Path<i32>,
+    Json(request): Json<SnoozeReminderRequest>,
+) -> Response {
+    if uri.query().is_some() {
+        return minimal(StatusCode::BAD_REQUEST, "rejected");
+    }
     let now = now_unix();
-    let repository = AgentIntegrationRepository::new(state.pg_pool.clone());
-    let token_hash = hash_secret(raw_token);
-    let Some(preflight) = repository
-        .authenticate_credential(&token_hash, "reply_watch_email", now)
-        .ok()
-        .flatten()
-    else {
-        return minimal(StatusCode::UNAUTHORIZED, "rejected");
+    let requested_at = match (request.at.as_deref(), request.minutes) {
+        (Some(at), None) => chrono::DateTime::parse_from_rfc3339(at.trim())
+            .ok()
+            .map(|at| at.timestamp()),
+        (None, Some(minutes)) => minutes
+            .checked_mul(60)
+            .and_then(|seconds| seconds.checked_add(i64::from(now))),
+        _ => None,
     };
-    let reservation = match repository.reserve_idempotency(
-        preflight.id,
-        "reply_watch_email",
-        &hash_secret(&idempotency_key),
+    let Some(remind_at) = requested_at.and_then(|at| validate_remind_at(at, now)) else {
+        return minimal(StatusCode::BAD_REQUEST, "rejected");
+    };
+    let call = match claim_action(&state, &headers, "reminders:write", "reminder_snooze", now) {
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    let event_id = match call.created_item("reminder", item_id) {
+        Ok(Some(item)) => item.event_id.unwrap_or_default(),
+        Ok(None) => return call.finish(StatusCode::NOT_FOUND, "rejected", now),
+        Err(response) => return response,
+    };
+    let user_id = call.credential.user_id;
+    let event = match state.ontology_repository.get_event(user_id, event_id) {
+        Ok(event) => event,
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent reminder lookup failed");
+            return call.fail(now);
+        }
+    };
+    if !matches!(event.status.as_str(), "active" | "notified") {
+        return call.finish(StatusCode::CONFLICT, "rejected", now);
+    }
+    let timezone = event.reminder_timezone.as_deref().unwrap_or("UTC");
+    let snoozed = state
+        .ontology_repository
+        .update_event(
+            user_id,
+            event_id,
+            None,
+            None,
+            Some(remind_at),
+            Some(remind_at),
+        )
+        .and_then(|_| {
+            state
+                .ontology_repository
+                .reset_event_reminder_delivery(user_id, event_id, timezone)
+        });
+    match snoozed {
+        Ok(_) => call.finish(StatusCode::OK, "accepted", now),
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent reminder snooze failed");
+            call.fail(now)
+        }
+    }
+}
+
+/// Reply watches this credential created that are still waiting: ids and
+/// times only, never the watched address.
+pub async fn list_created_reply_watches(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Response {
+    let now = now_unix();
+    let call = match authorize_read(
+        &state,
+        &uri,
+        &headers,
+        "reply_watch:write",
+        "reply_watches_read",
         now,
     ) {
-        Ok(IdempotencyClaim::Replayed(outcome)) => {
-            return minimal(StatusCode::OK, static_outcome(&outcome));
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    match call
+        .repository
+        .created_reply_watches(call.credential.id, call.credential.user_id, now)
+    {
+        Ok(watches) => call.respond(
+            ReplyWatchesResponse {
+                status: "accepted",
+                reply_watches: watches
+                    .into_iter()
+                    .take(MAX_READ_ITEMS)
+                    .map(|watch| CreatedReplyWatchSummary {
+                        id: watch.id,
+                        expires_at: watch.expires_at,
+                        created_at: watch.created_at,
+                    })
+                    .collect(),
+            },
+            now,
+        ),
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent reply watch list failed");
+            call.fail(now)
         }
-        Ok(IdempotencyClaim::InFlight) => return minimal(StatusCode::CONFLICT, "rejected"),
-        Ok(IdempotencyClaim::Fresh(id)) => id,
+    }
+}
+
+/// Cancels a reply watch this credential created, on every inbox.
+pub async fn cancel_created_reply_watch(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Path(item_id): Path<i32>,
+) -> Response {
+    if uri.query().is_some() {
+        return minimal(StatusCode::BAD_REQUEST, "rejected");
+    }
+    let now = now_unix();
+    let call = match claim_action(
+        &state,
+        &headers,
+        "reply_watch:write",
+        "reply_watch_cancel",
+        now,
+    ) {
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    let item = match call.created_item("reply_watch", item_id) {
+        Ok(Some(item)) => item,
+        Ok(None) => return call.finish(StatusCode::NOT_FOUND, "rejected", now),
+        Err(response) => return response,
+    };
+    match call.repository.cancel_created_reply_watch(&item, now) {
+        Ok(true) => call.finish(StatusCode::OK, "accepted", now),
+        Ok(false) => call.finish(StatusCode::CONFLICT, "rejected", now),
         Err(error) => {
-            tracing::error!(error = %error, "agent reply-watch idempotency failed");
-            return minimal(StatusCode::INTERNAL_SERVER_ERROR, "failed");
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent reply watch cancel failed");
+            call.fail(now)
         }
+    }
+}
+
+/// Texts the user themself, e.g. when a long-running agent task finishes.
+/// Only ever the account's own number; the text is not stored or echoed.
+pub async fn send_self_sms(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Json(request): Json<SelfSmsRequest>,
+) -> Response {
+    if uri.query().is_some() {
+        return minimal(StatusCode::BAD_REQUEST, "rejected");
+    }
+    let Some(message) = printable(&request.message, MAX_SELF_SMS_CHARS) else {
+        return minimal(StatusCode::BAD_REQUEST, "rejected");
     };
-    let credential = match repository.claim_credential(&token_hash, "reply_watch_email", now) {
-        Ok(CredentialClaim::Accepted(credential)) if credential.id == preflight.id => credential,
-        Ok(CredentialClaim::OverCap) => {
-            let _ = repository.clear_idempotency(reservation);
-            return minimal(StatusCode::TOO_MANY_REQUESTS, "rejected");
+    let now = now_unix();
+    let call = match claim_action(&state, &headers, "sms:self", "sms_self", now) {
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    if crate::proactive::utils::send_notification(
+        &state,
+        call.credential.user_id,
+        &message,
+        "agent_sms".to_string(),
+        None,
+    )
+    .await
+    {
+        call.finish(StatusCode::OK, "accepted", now)
+    } else {
+        tracing::warn!(
+            credential_id = call.credential.id,
+            "agent self SMS delivery failed"
+        );
+        call.fail(now)
+    }
+}
+
+/// Pauses or resumes one of the user's rules. Finished or expired rules
+/// can't be revived this way.
+pub async fn set_rule_status(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Path(rule_id): Path<i32>,
+    Json(request): Json<RuleStatusRequest>,
+) -> Response {
+    if uri.query().is_some() || !TOGGLEABLE_RULE_STATUSES.contains(&request.status.as_str()) {
+        return minimal(StatusCode::BAD_REQUEST, "rejected");
+    }
+    let now = now_unix();
+    let call = match claim_action(&state, &headers, "rules:toggle", "rule_toggle", now) {
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    let rule = match state
+        .ontology_repository
+        .get_rule(call.credential.user_id, rule_id)
+    {
+        Ok(rule) => rule,
+        Err(diesel::result::Error::NotFound) => {
+            return call.finish(StatusCode::NOT_FOUND, "rejected", now)
         }
-        _ => {
-            let _ = repository.clear_idempotency(reservation);
-            return minimal(StatusCode::UNAUTHORIZED, "rejected");
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent rule lookup failed");
+            return call.fail(now);
         }
     };
-    if !has_active_subscription(&state, credential.user_id) {
-        let _ = repository.complete_idempotency(reservation, "rejected");
-        let _ = repository.audit(
-            credential.id,
-            credential.user_id,
-            "reply_watch_email",
-            "rejected",
+    if !TOGGLEABLE_RULE_STATUSES.contains(&rule.status.as_str()) {
+        return call.finish(StatusCode::CONFLICT, "rejected", now);
+    }
+    match state
+        .ontology_repository
+        .update_rule_status(rule.id, &request.status)
+    {
+        Ok(()) => call.finish(StatusCode::OK, "accepted", now),
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent rule toggle failed");
+            call.fail(now)
+        }
+    }
+}
+
+/// Active reminders and events: description and times only.
+pub async fn list_events(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Response {
+    let now = now_unix();
+    let call = match authorize_read(&state, &uri, &headers, "events:read", "events_read", now) {
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    match state
+        .ontology_repository
+        .get_active_events(call.credential.user_id)
+    {
+        Ok(events) => call.respond(
+            EventsResponse {
+                status: "accepted",
+                events: events
+                    .into_iter()
+                    .take(MAX_READ_ITEMS)
+                    .map(|event| AgentEvent {
+                        id: event.id,
+                        description: event.description,
+                        remind_at: event.remind_at,
+                        due_at: event.due_at,
+                    })
+                    .collect(),
+            },
             now,
-        );
-        return minimal(StatusCode::FORBIDDEN, "rejected");
+        ),
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent event read failed");
+            call.fail(now)
+        }
     }
-    let connection_ids = match repository.active_imap_connection_ids(credential.user_id) {
-        Ok(ids) => ids,
+}
+
+/// Pending digest items: platform, summary and time. Never message bodies
+/// or who sent them.
+pub async fn list_digest(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Response {
+    let now = now_unix();
+    let call = match authorize_read(&state, &uri, &headers, "digest:read", "digest_read", now) {
+        Ok(call) => call,
+        Err(response) => return response,
+    };
+    match state
+        .ontology_repository
+        .get_pending_digest_messages(call.credential.user_id)
+    {
+        Ok(messages) => call.respond(
+            DigestResponse {
+                status: "accepted",
+                items: messages
+                    .into_iter()
+                    .take(MAX_READ_ITEMS)
+                    .map(|message| AgentDigestItem {
+                        platform: message.platform,
+                        summary: message.summary,
+                        created_at: message.created_at,
+                    })
+                    .collect(),
+            },
+            now,
+        ),
         Err(error) => {
-            tracing::error!(credential_id = credential.id, error = %error, "agent IMAP account lookup failed");
-            let _ = repository.clear_idempotency(reservation);
-            let _ = repository.audit(
-                credential.id,
-                credential.user_id,
-                "reply_watch_email",
-                "failed",
-                now,
-            );
-            return minimal(StatusCode::INTERNAL_SERVER_ERROR, "failed");
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent digest read failed");
+            call.fail(now)
         }
+    }
+}
+
+/// Rules that can be paused or resumed: id, name and status, no configuration.
+pub async fn list_rules(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Response {
+    let now = now_unix();
+    let call = match authorize_read(&state, &uri, &headers, "rules:toggle", "rules_read", now) {
+        Ok(call) => call,
+        Err(response) => return response,
     };
-    let expires_at = now + request.expires_in_seconds;
-    match repository.create_email_reply_watches(
-        credential.user_id,
-        &connection_ids,
-        &email,
-        &label,
+    match state.ontology_repository.get_rules(call.credential.user_id) {
+        Ok(rules) => call.respond(
+            RulesResponse {
+                status: "accepted",
+                rules: rules
+                    .into_iter()
+                    .filter(|rule| TOGGLEABLE_RULE_STATUSES.contains(&rule.status.as_str()))
+                    .take(MAX_READ_ITEMS)
+                    .map(|rule| AgentRule {
+                        id: rule.id,
+                        name: rule.name,
+                        status: rule.status,
+                    })
+                    .collect(),
+            },
+            now,
+        ),
+        Err(error) => {
+            tracing::error!(credential_id = call.credential.id, error = %error, "agent rule read failed");
+            call.fail(now)
+        }
+    }
+}
+
+/// An authenticated agent call. Every way it ends is audited.
+struct AgentCall {
+    repository: AgentIntegrationRepository,
+    credential: AgentCredential,
+    action_kind: &'static str,
+    /// Idempotency row of a write action; reads have none.
+    reservation: Option<i32>,
+}
+
+impl AgentCall {
+    fn finish(&self, status: StatusCode, outcome: &'static str, now: i32) -> Response {
+        if let Some(reservation) = self.reservation {
+            if let Err(error) = self.repository.complete_idempotency(reservation, outcome) {
+                tracing::warn!(error = %error, "agent idempotency completion failed");
+            }
+        }
+        self.audit(outcome, now);
+        minimal(status, outcome)
+    }
+
+    /// Clears the idempotency row so the client can retry.
+    fn fail(&self, now: i32) -> Response {
+        if let Some(reservation) = self.reservation {
+            let _ = self.repository.clear_idempotency(reservation);
+        }
+        self.audit("failed", now);
+        minimal(StatusCode::INTERNAL_SERVER_ERROR, "failed")
+    }
+
+    /// Links a created reminder or watch to this credential. The action has
+    /// already happened, so a failure here is logged rather than reported.
+    fn record_created(&self, item: NewAgentCreatedItem<'_>) {
+        if let Err(error) = self.repository.record_created_item(item) {
+            tracing::warn!(credential_id = self.credential.id, error = %error, "agent created item link failed");
+        }
+    }
+
+    /// An item this credential created, or the failure response.
+    fn created_item(&self, item_kind: &str, id: i32) -> Result<Option<AgentCreatedItem>, Response> {
+        self.repository
+            .find_created_item(self.credential.id, item_kind, id)
+            .map_err(|error| {
+                tracing::error!(credential_id = self.credential.id, error = %error, "agent created item lookup failed");
+                self.fail(now_unix())
+            })
+    }
+
+    fn respond<T: Serialize>(&self, body: T, now: i32) -> Response {
+        self.audit("accepted", now);
+        no_store(Json(body).into_response())
+    }
+
+    fn audit(&self, outcome: &str, now: i32) {
+        if let Err(error) = self.repository.audit(
+            self.credential.id,
+            self.credential.user_id,
+            self.action_kind,
+            outcome,
+            now,
+        ) {
+            tracing::warn!(error = %error, "agent action audit failed");
+        }
+    }
+}
+
+/// Authenticates a write action, reserves its idempotency key and claims one
+/// unit of the daily cap. Every early exit is already the response to send.
+fn claim_action(
+    state: &Arc<AppState>,
+    headers: &HeaderMap,
+    scope: &str,
+    action_kind: &'static str,
+    now: i32,
+) -> Result<AgentCall, Response> {
+    let Some(idempotency_key) = idempotency_key(headers) else {
+        return Err(minimal(StatusCode::BAD_REQUEST, "rejected"));
+    };
+    let Some(raw_token) =
+        bearer(headers).filter(|token| valid_prefixed_hex(token, TOKEN_PREFIX, 64))
+    else {
+        return Err(minimal(StatusCode::UNAUTHORIZED, "rejected"));
+    };
+    let repository = AgentIntegrationRepository::new(state.pg_pool.clone());
+    let token_hash = hash_secret(raw_token);
+    let Some(preflight) = repository.find_credential(&token_hash, now).ok().flatten() else {
+        return Err(minimal(StatusCode::UNAUTHORIZED, "rejected"));
+    };
+    let mut call = AgentCall {
+        repository,
+        credential: preflight,
+        action_kind,
+        reservation: None,
+    };
+    if !call.credential.has_scope(scope) {
+        return Err(call.finish(StatusCode::FORBIDDEN, "rejected", now));
+    }
+    let reservation = match call.repository.reserve_idempotency(
+        call.credential.id,
+        action_kind,
+        &hash_secret(&idempotency_key),
         now,
-        expires_at,
-        MAX_ACTIVE_REPLY_WATCHES,
     ) {
-        Ok(false) => {
-            let _ = repository.complete_idempotency(reservation, "rejected");
-            let _ = repository.audit(
-                credential.id,
-                credential.user_id,
-                "reply_watch_email",
-                "rejected",
-                now,
-            );
-            return minimal(StatusCode::CONFLICT, "rejected");
+        Ok(IdempotencyClaim::Replayed(outcome)) => {
+            return Err(minimal(StatusCode::OK, static_outcome(&outcome)));
         }
+        Ok(IdempotencyClaim::InFlight) => return Err(minimal(StatusCode::CONFLICT, "rejected")),
+        Ok(IdempotencyClaim::Fresh(id)) => id,
         Err(error) => {
-            tracing::error!(credential_id = credential.id, error = %error, "agent reply watch create failed");
-            let _ = repository.clear_idempotency(reservation);
-            let _ = repository.audit(
-                credential.id,
-                credential.user_id,
-                "reply_watch_email",
-                "failed",
-                now,
-            );
-            return minimal(StatusCode::INTERNAL_SERVER_ERROR, "failed");
-        }
-        Ok(true) => {}
-    }
-    finish_action(
-        &repository,
-        reservation,
-        &credential,
-        "reply_watch_email",
-        "accepted",
-        now,
-    )
+            tracing::error!(action_kind, error = %error, "agent action idempotency failed");
+            return Err(minimal(StatusCode::INTERNAL_SERVER_ERROR, "failed"));
+        }
+    };
+    match call.repository.claim_credential(&token_hash, scope, now) {
+        Ok(CredentialClaim::Accepted(credential)) if credential.id == call.credential.id => {
+            call.credential = credential;
+        }
+        Ok(CredentialClaim::OverCap) => {
+            let _ = call.repository.clear_idempotency(reservation);
+            return Err(minimal(StatusCode::TOO_MANY_REQUESTS, "rejected"));
+        }
+        _ => {
+            let _ = call.repository.clear_idempotency(reservation);
+            return Err(minimal(StatusCode::UNAUTHORIZED, "rejected"));
+        }
+    }
+    call.reservation = Some(reservation);
+    if !has_active_subscription(state, call.credential.user_id) {
+        return Err(call.finish(StatusCode::FORBIDDEN, "rejected", now));
+    }
+    Ok(call)
 }
 
-fn finish_action(
-    repository: &AgentIntegrationRepository,
-    reservation: i32,
-    credential: &crate::models::agent_integration_models::AgentCredential,
-    action_kind: &str,
-    outcome: &'static str,
+/// Authenticates a scoped read. Reads are rate limited and audited but do
+/// not consume the daily action cap.
+fn authorize_read(
+    state: &Arc<AppState>,
+    uri: &Uri,
+    headers: &HeaderMap,
+    scope: &str,
+    action_kind: &'static str,
     now: i32,
-) -> Response {
-    if let Err(error) = repository.complete_idempotency(reservation, outcome) {
-        tracing::warn!(error = %error, "agent idempotency completion failed");
+) -> Result<AgentCall, Response> {
+    if uri.query().is_some() {
+        return Err(minimal(StatusCode::BAD_REQUEST, "rejected"));
     }
-    if let Err(error) =
-        repository.audit(credential.id, credential.user_id, action_kind, outcome, now)
+    if !check_rate_limit(state, headers, "agent-read", 30) {
+        return Err(minimal(StatusCode::TOO_MANY_REQUESTS, "rejected"));
+    }
+    let Some(raw_token) =
+        bearer(headers).filter(|token| valid_prefixed_hex(token, TOKEN_PREFIX, 64))
+    else {
+        return Err(minimal(StatusCode::UNAUTHORIZED, "rejected"));
+    };
+    let repository = AgentIntegrationRepository::new(state.pg_pool.clone());
+    let Some(credential) = repository
+        .find_credential(&hash_secret(raw_token), now)
+        .ok()
+        .flatten()
+    else {
+        return Err(minimal(StatusCode::UNAUTHORIZED, "rejected"));
+    };
+    let call = AgentCall {
+        repository,
+        credential,
+        action_kind,
+        reservation: None,
+    };
+    if !call.credential.has_scope(scope) || !has_active_subscription(state, call.credential.user_id)
     {
-        tracing::warn!(error = %error, "agent action audit failed");
+        return Err(call.finish(StatusCode::FORBIDDEN, "rejected", now));
+    }
+    if let Err(error) = call.repository.touch_credential(call.credential.id, now) {
+        tracing::warn!(error = %error, "agent credential touch failed");
     }
-    minimal(StatusCode::OK, outcome)
+    Ok(call)
 }
 
-fn has_active_subscription(state: &Arc<AppState>, user_id: i32) -> bool {
+pub(crate) fn has_active_subscription(state: &Arc<AppState>, user_id: i32) -> bool {
     matches!(
         state.user_core.find_by_id(user_id),
         Ok(Some(user)) if user.sub_tier.as_deref() == Some("tier 2")
@@ -594,7 +1180,7 @@ fn check_rate_limit(
     limiter.check_key(&key).is_ok()
 }
 
-fn no_store(mut response: Response) -> Response {
+pub(crate) fn no_store(mut response: Response) -> Response {
     response
         .headers_mut()
         .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
@@ -609,7 +1195,7 @@ fn minimal(status: StatusCode, outcome: &'static str) -> Response {
     no_store((status, Json(ActionResponse { status: outcome })).into_response())
 }
 
-fn bearer(headers: &HeaderMap) -> Option<&str> {
+pub(crate) fn bearer(headers: &HeaderMap) -> Option<&str> {
     headers
         .get(header::AUTHORIZATION)?
         .to_str()
@@ -628,7 +1214,7 @@ fn idempotency_key(headers: &HeaderMap) -> Option<String> {
     }
 }
 
-fn valid_prefixed_hex(value: &str, prefix: &str, hex_len: usize) -> bool {
+pub(crate) fn valid_prefixed_hex(value: &str, prefix: &str, hex_len: usize) -> bool {
     let Some(hex) = value.strip_prefix(prefix) else {
         return false;
     };
@@ -692,17 +1278,17 @@ fn random_user_code() -> String {
     format!("{}-{}-{}", &raw[0..4], &raw[4..8], &raw[8..12])
 }
 
-fn random_hex(bytes: usize) -> String {
+pub(crate) fn random_hex(bytes: usize) -> String {
     let mut random = vec![0_u8; bytes];
     OsRng.fill_bytes(&mut random);
     hex::encode(random)
 }
 
-fn hash_secret(value: &str) -> String {
+pub(crate) fn hash_secret(value: &str) -> String {
     hex::encode(Sha256::digest(value.as_bytes()))
 }
 
-fn now_unix() -> i32 {
+pub(crate) fn now_unix() -> i32 {
     chrono::Utc::now().timestamp() as i32
 }
 
@@ -0,0 +1,786 @@
+//! Connect, status, health and disconnect for the bridgev2 networks in
+//! `bridgev2_provisioning::NETWORKS`, one set of handlers for all of them.
+//!
+//! A login is a process on the bridge that moves through steps. The frontend
+//! starts one with a flow id and gets the first step back. Steps that need
+//! input (phone number, code, password, cookies) are submitted through
+//! `submit_login_step`. Steps that display something (QR, pairing code) are
+//! waited on by a background task here, so the frontend only polls
+//! `get_login`. When the bridge reports the login complete, the bridge row
+//! flips to "connected" just like the WhatsApp/Signal monitors do.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json as AxumJson,
+};
+use base64::engine::general_purpose::STANDARD as Base64Engine;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::handlers::auth_middleware::AuthUser;
+use crate::pg_models::NewPgBridge;
+use crate::utils::bridge_responses::BridgeLoginHealth;
+use crate::utils::bridgev2_provisioning::{
+    self, BridgeNetwork, LoginProgress, LoginStep, ProvisioningClient, ProvisioningError, StepType,
+};
+use crate::utils::{disconnected_bridge_cleanup, matrix_auth};
+use crate::AppState;
+
+type HandlerError = (StatusCode, AxumJson<Value>);
+
+/// The login in progress for one user on one network.
+struct LoginSession {
+    flow_id: String,
+    process_id: String,
+    step: LoginStep,
+    error: Option<String>,
+}
+
+static LOGIN_SESSIONS: LazyLock<Mutex<HashMap<(i32, &'static str), LoginSession>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn sessions() -> std::sync::MutexGuard<'static, HashMap<(i32, &'static str), LoginSession>> {
+    LOGIN_SESSIONS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[derive(Deserialize)]
+pub struct StartLoginRequest {
+    pub flow_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct SubmitStepRequest {
+    #[serde(default)]
+    pub fields: HashMap<String, String>,
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> HandlerError {
+    (status, AxumJson(json!({"error": message.into()})))
+}
+
+fn resolve_network(network_id: &str) -> Result<&'static BridgeNetwork, HandlerError> {
+    bridgev2_provisioning::network(network_id)
+        .filter(|network| network.is_enabled())
+        .ok_or_else(|| {
+            error_response(
+                StatusCode::NOT_FOUND,
+                format!("Unknown network: {}", network_id),
+            )
+        })
+}
+
+fn provisioning_error(network: &BridgeNetwork, error: ProvisioningError) -> HandlerError {
+    match error {
+        // The bridge's own message is meant for the user ("Invalid code").
+        ProvisioningError::Api {
+            status, message, ..
+        } if (400..500).contains(&status) => error_response(StatusCode::BAD_REQUEST, message),
+        other => {
+            tracing::error!("{} provisioning request failed: {}", network.name, other);
+            error_response(
+                StatusCode::BAD_GATEWAY,
+                format!("{} bridge request failed: {}", network.name, other),
+            )
+        }
+    }
+}
+
+async fn provisioning_client(
+    state: &Arc<AppState>,
+    user_id: i32,
+    network: &BridgeNetwork,
+) -> Result<ProvisioningClient, HandlerError> {
+    ProvisioningClient::for_user(state, user_id, network)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to prepare {} provisioning client for user {}: {}",
+                network.name,
+                user_id,
+                e
+            );
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to initialize Matrix client: {}", e),
+            )
+        })
+}
+
+fn now_secs() -> i32 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i32
+}
+
+/// A step as the frontend renders it. QR payloads also come as a PNG data
+/// URL, the same shape the WhatsApp/Signal connect endpoints return.
+fn step_view(network: &BridgeNetwork, session: &LoginSession) -> Value {
+    let qr_image = session
+        .step
+        .display_and_wait
+        .as_ref()
+        .filter(|display| display.kind == "qr")
+        .and_then(|display| display.data.as_deref())
+        .and_then(|data| crate::rendering::qr::render_qr(data).ok())
+        .map(|image| format!("data:image/png;base64,{}", Base64Engine.encode(image.png)));
+    json!({
+        "network": network.id,
+        "flow_id": session.flow_id,
+        "step": session.step,
+        "qr_image": qr_image,
+        "error": session.error,
+    })
+}
+
+/// Networks the deployment has bridges for, with the user's status on each.
+pub async fn list_networks(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let mut networks = Vec::new();
+    for network in bridgev2_provisioning::enabled_networks() {
+        let status = state
+            .user_repository
+            .get_bridge(auth_user.user_id, network.id)
+            .map_err(|e| {
+                tracing::error!("Failed to get {} bridge: {}", network.name, e);
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to get bridges")
+            })?
+            .map(|bridge| bridge.status)
+            .unwrap_or_else(|| "not_connected".to_string());
+        networks.push(json!({
+            "id": network.id,
+            "name": network.name,
+            "status": status,
+        }));
+    }
+    Ok(AxumJson(json!({ "networks": networks })))
+}
+
+/// Login flows the bridge offers, in the order the adapter prefers.
+pub async fn get_login_flows(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(network_id): Path<String>,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let network = resolve_network(&network_id)?;
+    let client = provisioning_client(&state, auth_user.user_id, network).await?;
+    let flows = client
+        .login_flows()
+        .await
+        .map_err(|e| provisioning_error(network, e))?;
+    Ok(AxumJson(json!({ "flows": network.order_flows(flows) })))
+}
+
+pub async fn start_login(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(network_id): Path<String>,
+    AxumJson(request): AxumJson<StartLoginRequest>,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let network = resolve_network(&network_id)?;
+    let user_id = auth_user.user_id;
+    tracing::info!(
+        "🚀 {} connect request for user {} (flow {})",
+        network.name,
+        user_id,
+        request.flow_id
+    );
+
+    let reconnect_lease =
+        disconnected_bridge_cleanup::acquire_bridge_reconnect_lease(&state, user_id, network.id)
+            .map_err(|error| error_response(StatusCode::CONFLICT, error.to_string()))?;
+
+    if let Ok(Some(existing)) = state.user_repository.get_bridge(user_id, network.id) {
+        if existing.status == "cleaning_up" {
+            return Err(error_response(
+                StatusCode::CONFLICT,
+                format!("{} is still disconnecting; retry shortly", network.name),
+            ));
+        }
+        tracing::info!("Removing existing {} bridge for fresh start", network.name);
+        let _ = state.user_repository.delete_bridge(user_id, network.id);
+    }
+
+    let client = provisioning_client(&state, user_id, network).await?;
+    let progress = client
+        .start_login(&request.flow_id)
+        .await
+        .map_err(|e| provisioning_error(network, e))?;
+
+    state
+        .user_repository
+        .create_bridge(NewPgBridge {
+            user_id,
+            bridge_type: network.id.to_string(),
+            status: "connecting".to_string(),
+            room_id: None,
+            data: None,
+            created_at: Some(now_secs()),
+            last_native_activity_at: None,
+            native_activity_reminded_at: None,
+        })
+        .map_err(|e| {
+            tracing::error!("Failed to create {} bridge: {}", network.name, e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to store bridge information",
+            )
+        })?;
+    // finish_login takes its own lease if the flow completes right away.
+    drop(reconnect_lease);
+
+    let view = advance(&state, user_id, network, client, &request.flow_id, progress).await;
+    Ok(AxumJson(view))
+}
+
+/// The current step of the user's login, for polling while a QR or code is
+/// on screen.
+pub async fn get_login(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(network_id): Path<String>,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let network = resolve_network(&network_id)?;
+    if let Some(session) = sessions().get(&(auth_user.user_id, network.id)) {
+        return Ok(AxumJson(step_view(network, session)));
+    }
+    let status = state
+        .user_repository
+        .get_bridge(auth_user.user_id, network.id)
+        .ok()
+        .flatten()
+        .map(|bridge| bridge.status)
+        .unwrap_or_else(|| "not_connected".to_string());
+    Ok(AxumJson(json!({
+        "network": network.id,
+        "step": null,
+        "status": status,
+    })))
+}
+
+/// Submit the fields of the current `user_input` or `cookies` step.
+pub async fn submit_login_step(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(network_id): Path<String>,
+    AxumJson(request): AxumJson<SubmitStepRequest>,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let network = resolve_network(&network_id)?;
+    let user_id = auth_user.user_id;
+    let (flow_id, process_id, step) = {
+        let sessions = sessions();
+        let session = sessions.get(&(user_id, network.id)).ok_or_else(|| {
+            error_response(StatusCode::NOT_FOUND, "No login in progress; start again")
+        })?;
+        (
+            session.flow_id.clone(),
+            session.process_id.clone(),
+            session.step.clone(),
+        )
+    };
+
+    if let Some(missing) = step
+        .required_fields()
+        .into_iter()
+        .find(|id| request.fields.get(*id).is_none_or(|v| v.trim().is_empty()))
+    {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Missing field: {}", missing),
+        ));
+    }
+
+    let client = provisioning_client(&state, user_id, network).await?;
+    let progress = client
+        .submit_step(&process_id, &step, &request.fields)
+        .await
+        .map_err(|e| provisioning_error(network, e))?;
+    let view = advance(&state, user_id, network, client, &flow_id, progress).await;
+    Ok(AxumJson(view))
+}
+
+/// Abandon the login in progress. The bridge drops the process on its own
+/// once nothing waits on it.
+pub async fn cancel_login(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(network_id): Path<String>,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let network = resolve_network(&network_id)?;
+    let removed = sessions()
+        .remove(&(auth_user.user_id, network.id))
+        .is_some();
+    if let Ok(Some(bridge)) = state
+        .user_repository
+        .get_bridge(auth_user.user_id, network.id)
+    {
+        if bridge.status == "connecting" {
+            let _ = state
+                .user_repository
+                .delete_bridge(auth_user.user_id, network.id);
+        }
+    }
+    Ok(AxumJson(json!({ "cancelled": removed })))
+}
+
+/// Record a new step and act on it: keep waiting on display steps in the
+/// background, finish the connection on completion.
+async fn advance(
+    state: &Arc<AppState>,
+    user_id: i32,
+    network: &'static BridgeNetwork,
+    client: ProvisioningClient,
+    flow_id: &str,
+    progress: LoginProgress,
+) -> Value {
+    let session = LoginSession {
+        flow_id: flow_id.to_string(),
+        process_id: progress.login_id.clone(),
+        step: progress.step.clone(),
+        error: None,
+    };
+    let view = step_view(network, &session);
+    sessions().insert((user_id, network.id), session);
+
+    match progress.step.step_type {
+        StepType::DisplayAndWait => {
+            tokio::spawn(wait_for_login(
+                state.clone(),
+                user_id,
+                network,
+                client,
+                progress,
+            ));
+        }
+        StepType::Complete => {
+            finish_login(state, user_id, network, &client, &progress.step).await;
+        }
+        StepType::UserInput | StepType::Cookies => {}
+    }
+    view
+}
+
+/// Wait on display steps until the bridge asks for input, completes or
+/// fails. Stops quietly when the user restarted or cancelled meanwhile.
+async fn wait_for_login(
+    state: Arc<AppState>,
+    user_id: i32,
+    network: &'static BridgeNetwork,
+    client: ProvisioningClient,
+    mut progress: LoginProgress,
+) {
+    let key = (user_id, network.id);
+    let still_current = |process_id: &str| {
+        sessions()
+            .get(&key)
+            .is_some_and(|session| session.process_id == process_id)
+    };
+
+    while progress.step.step_type == StepType::DisplayAndWait {
+        let result = client
+            .wait_step(&progress.login_id, &progress.step.step_id)
+            .await;
+        if !still_current(&progress.login_id) {
+            return;
+        }
+        match result {
+            Ok(next) => {
+                if let Some(session) = sessions().get_mut(&key) {
+                    session.step = next.step.clone();
+                }
+                progress = next;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "{} login for user {} ended without completing: {}",
+                    network.name,
+                    user_id,
+                    e
+                );
+                if let Some(session) = sessions().get_mut(&key) {
+                    session.error = Some(e.to_string());
+                }
+                if let Ok(Some(bridge)) = state.user_repository.get_bridge(user_id, network.id) {
+                    if bridge.status == "connecting" {
+                        let _ = state.user_repository.delete_bridge(user_id, network.id);
+                    }
+                }
+                return;
+            }
+        }
+    }
+
+    if progress.step.step_type == StepType::Complete {
+        finish_login(&state, user_id, network, &client, &progress.step).await;
+    }
+}
+
+/// Mark the bridge connected once the bridge reports the login complete.
+async fn finish_login(
+    state: &Arc<AppState>,
+    user_id: i32,
+    network: &BridgeNetwork,
+    client: &ProvisioningClient,
+    step: &LoginStep,
+) {
+    let key = (user_id, network.id);
+    let fail = |message: String| {
+        tracing::error!("{}", message);
+        if let Some(session) = sessions().get_mut(&key) {
+            session.error = Some(message);
+        }
+    };
+
+    let _reconnect_lease = match disconnected_bridge_cleanup::acquire_bridge_reconnect_lease(
+        state, user_id, network.id,
+    ) {
+        Ok(lease) => lease,
+        Err(e) => return fail(e.to_string()),
+    };
+
+    let login_id = step
+        .complete
+        .as_ref()
+        .and_then(|complete| complete.user_login_id.clone());
+    let connected_account = match client.whoami().await {
+        Ok(whoami) => whoami
+            .logins
+            .iter()
+            .find(|login| login_id.as_deref().is_none_or(|id| id == login.id))
+            .and_then(|login| network.adapter.account_label(login)),
+        Err(e) => {
+            tracing::warn!(
+                "{} whoami after login failed for user {}: {}",
+                network.name,
+                user_id,
+                e
+            );
+            None
+        }
+    };
+
+    let new_bridge = NewPgBridge {
+        user_id,
+        bridge_type: network.id.to_string(),
+        status: "connected".to_string(),
+        room_id: None,
+        data: connected_account,
+        created_at: Some(now_secs()),
+        last_native_activity_at: None,
+        native_activity_reminded_at: None,
+    };
+    let stored = state
+        .user_repository
+        .delete_bridge(user_id, network.id)
+        .and_then(|_| state.user_repository.create_bridge(new_bridge));
+    if let Err(e) = stored {
+        return fail(format!(
+            "Failed to store {} connection for user {}: {}",
+            network.name, user_id, e
+        ));
+    }
+    sessions().remove(&key);
+    tracing::info!("🎉 {} connected for user {}", network.name, user_id);
+
+    // Portals from a previous login are gone; their room ids would fail sends.
+    match state
+        .ontology_repository
+        .clear_platform_room_ids(user_id, network.id)
+    {
+        Ok(n) if n > 0 => tracing::info!(
+            "Cleared {} stale {} room_ids for user {} on reconnect",
+            n,
+            network.name,
+            user_id
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::warn!(
+            "Failed to clear stale {} room_ids for user {}: {}",
+            network.name,
+            user_id,
+            e
+        ),
+    }
+
+    if let Err(e) = matrix_auth::ensure_matrix_user_running(user_id, state).await {
+        tracing::error!("Failed to start Matrix sync for user {}: {}", user_id, e);
+    }
+}
+
+pub async fn get_status(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(network_id): Path<String>,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let network = resolve_network(&network_id)?;
+    let bridge = state
+        .user_repository
+        .get_bridge(auth_user.user_id, network.id)
+        .map_err(|e| {
+            tracing::error!("Failed to get {} bridge status: {}", network.name, e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get {} status", network.name),
+            )
+        })?;
+    Ok(AxumJson(match bridge {
+        Some(bridge) => json!({
+            "connected": bridge.status == "connected",
+            "status": bridge.status,
+            "created_at": bridge.created_at.unwrap_or(0),
+            "connected_account": bridge.data,
+        }),
+        None => json!({
+            "connected": false,
+            "status": "not_connected",
+            "created_at": 0,
+            "connected_account": null,
+        }),
+    }))
+}
+
+/// Live login state from the bridge. Reports but never deletes; the
+/// scheduler's health check owns confirmed disconnects.
+pub async fn check_health(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(network_id): Path<String>,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let network = resolve_network(&network_id)?;
+    let bridge = state
+        .user_repository
+        .get_bridge(auth_user.user_id, network.id)
+        .map_err(|e| {
+            tracing::error!("Failed to get {} bridge: {}", network.name, e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get {} bridge info", network.name),
+            )
+        })?;
+    if bridge.is_none() {
+        return Ok(AxumJson(json!({
+            "healthy": false,
+            "message": format!("{} is not connected", network.name),
+        })));
+    }
+
+    let client = provisioning_client(&state, auth_user.user_id, network).await?;
+    let whoami = match client.whoami().await {
+        Ok(whoami) => whoami,
+        Err(e) => {
+            tracing::warn!("⚠️ {} whoami failed: {}", network.name, e);
+            return Ok(AxumJson(json!({
+                "healthy": false,
+                "ambiguous": true,
+                "message": format!("probe failed: {}", e),
+            })));
+        }
+    };
+
+    let states: Vec<&str> = whoami
+        .logins
+        .iter()
+        .map(|login| login.state_event().unwrap_or("UNKNOWN"))
+        .collect();
+    let message = if states.is_empty() {
+        "No logins".to_string()
+    } else {
+        states.join(", ")
+    };
+    match bridgev2_provisioning::classify_logins(&whoami.logins) {
+        BridgeLoginHealth::Connected => {
+            if let Some(label) = whoami
+                .logins
+                .iter()
+                .find(|login| login.state_event() == Some(bridgev2_provisioning::STATE_CONNECTED))
+                .and_then(|login| network.adapter.account_label(login))
+            {
+                if let Err(e) =
+                    state
+                        .user_repository
+                        .update_bridge_data(auth_user.user_id, network.id, &label)
+                {
+                    tracing::warn!("Failed to save connected account: {}", e);
+                }
+            }
+            Ok(AxumJson(json!({"healthy": true, "message": message})))
+        }
+        BridgeLoginHealth::BadCredentials | BridgeLoginHealth::Empty => {
+            Ok(AxumJson(json!({"healthy": false, "message": message})))
+        }
+        BridgeLoginHealth::Unknown => Ok(AxumJson(json!({
+            "healthy": false,
+            "ambiguous": true,
+            "message": message,
+        }))),
+    }
+}
+
+pub async fn disconnect(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(network_id): Path<String>,
+) -> Result<AxumJson<Value>, HandlerError> {
+    let network = resolve_network(&network_id)?;
+    let user_id = auth_user.user_id;
+    tracing::info!(
+        "🔌 Starting {} disconnection for user {}",
+        network.name,
+        user_id
+    );
+    sessions().remove(&(user_id, network.id));
+
+    let bridge = state
+        .user_repository
+        .get_bridge(user_id, network.id)
+        .map_err(|e| {
+            tracing::error!("Failed to get {} bridge info: {}", network.name, e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get {} bridge info", network.name),
+            )
+        })?;
+    let Some(bridge) = bridge else {
+        return Ok(AxumJson(json!({
+            "message": format!("{} was not connected", network.name)
+        })));
+    };
+    if bridge.status == "cleaning_up" {
+        return Ok(AxumJson(json!({
+            "message": format!("{} cleanup already in progress", network.name)
+        })));
+    }
+
+    let cleanup_job_id = disconnected_bridge_cleanup::enqueue_disconnect_cleanup(
+        &state,
+        &bridge,
+        "explicit_disconnect",
+    )
+    .map_err(|e| {
+        tracing::error!(
+            user_id,
+            service = network.id,
+            error = %e,
+            "Refusing disconnect because durable Tuwunel cleanup could not be queued"
+        );
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to queue durable connection cleanup",
+        )
+    })?;
+    let portal_cleanup_lease = disconnected_bridge_cleanup::acquire_bridge_portal_cleanup_lease(
+        &state,
+        user_id,
+        network.id,
+        cleanup_job_id,
+    )
+    .map_err(|error| error_response(StatusCode::CONFLICT, error.to_string()))?;
+
+    state
+        .user_repository
+        .update_bridge_status(user_id, network.id, "cleaning_up")
+        .map_err(|e| {
+            tracing::error!("Failed to update {} bridge status: {}", network.name, e);
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to update bridge status",
+            )
+        })?;
+
+    // Background task so a page refresh can't cut the cleanup short.
+    let state = state.clone();
+    tokio::spawn(async move {
+        let _portal_cleanup_lease = portal_cleanup_lease;
+        let client = match matrix_auth::get_cached_client(user_id, &state).await {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Background cleanup: Failed to get Matrix client: {}", e);
+                let _ = state.tuwunel_cleanup_repository.mark_bridge_portal_cleanup(
+                    cleanup_job_id,
+                    false,
+                    Some(&format!("failed to get Matrix client: {e}")),
+                );
+                let _ = state.user_repository.delete_bridge(user_id, network.id);
+                return;
+            }
+        };
+
+        let mut cleanup_errors = Vec::new();
+        match network
+            .bridge_url()
+            .ok_or(ProvisioningError::NotConfigured(network.name))
+            .and_then(|url| ProvisioningClient::from_matrix_client(&url, &client))
+        {
+            Ok(provisioning) => match provisioning.logout_all().await {
+                Ok(n) => tracing::info!(
+                    "{} background cleanup: logged out {} login(s) for user {}",
+                    network.name,
+                    n,
+                    user_id
+                ),
+                Err(e) => {
+                    tracing::warn!("{} background cleanup logout failed: {}", network.name, e);
+                    cleanup_errors.push(format!("logout failed: {e}"));
+                }
+            },
+            Err(e) => cleanup_errors.push(format!("provisioning unavailable: {e}")),
+        }
+
+        // Give the bridge a moment to tear down portals before we leave
+        // whatever is still around.
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        match crate::utils::bridge::get_service_rooms(&client, network.id).await {
+            Ok(rooms) => {
+                for room in rooms {
+                    let Ok(room_id) =
+                        matrix_sdk::ruma::OwnedRoomId::try_from(room.room_id.as_str())
+                    else {
+                        continue;
+                    };
+                    if let Some(room) = client.get_room(&room_id) {
+                        if let Err(e) = room.leave().await {
+                            cleanup_errors.push(format!("{room_id}: leave failed: {e}"));
+                        }
+                    }
+                }
+            }
+            Err(e) => cleanup_errors.push(format!("portal-room lookup failed: {e}")),
+        }
+
+        disconnected_bridge_cleanup::record_portal_cleanup_outcome(
+            &state,
+            cleanup_job_id,
+            user_id,
+            network.id,
+            &client,
+            cleanup_errors,
+        )
+        .await;
+
+        if let Err(e) = state.user_repository.delete_bridge(user_id, network.id) {
+            tracing::error!("Background cleanup: Failed to delete bridge record: {}", e);
+        } else {
+            tracing::info!(
+                "🧹 Background cleanup completed for {} user {}",
+                network.name,
+                user_id
+            );
+        }
+        matrix_auth::stop_matrix_user_if_no_bridges(user_id, &state)
+            .await
+            .ok();
+    });
+
+    Ok(AxumJson(json!({
+        "message": format!("{} disconnecting", network.name)
+    })))
+}
@@ -0,0 +1,238 @@
+//! CalDAV Calendar API Handlers
+//!
+//! Endpoints for connecting CalDAV servers (Nextcloud, Fastmail, Radicale…).
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::api::caldav::{validate_caldav_url, CalDavClient};
+use crate::handlers::auth_middleware::AuthUser;
+use crate::models::caldav_models::{
+    CalDavConnectionResponse, CalDavTestConnectionResponse, CreateCalDavConnectionRequest,
+};
+use crate::repositories::caldav_repository::{CalDavCredentials, CalDavRepository};
+use crate::AppState;
+
+type ApiError = (StatusCode, Json<Value>);
+
+fn api_error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(json!({ "error": message.into() })))
+}
+
+fn load_connection(
+    repository: &CalDavRepository,
+    connection_id: i32,
+    user_id: i32,
+) -> Result<crate::pg_models::PgCalDavConnection, ApiError> {
+    match repository.get_connection_by_id(connection_id, user_id) {
+        Ok(Some(connection)) => Ok(connection),
+        Ok(None) => Err(api_error(StatusCode::NOT_FOUND, "Connection not found")),
+        Err(e) => {
+            error!("Failed to get CalDAV connection: {}", e);
+            Err(api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to get connection",
+            ))
+        }
+    }
+}
+
+/// POST /api/calendar/connections - Connect a CalDAV account
+pub async fn create_caldav_connection(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<CreateCalDavConnectionRequest>,
+) -> Result<Json<CalDavConnectionResponse>, ApiError> {
+    info!(
+        "Creating CalDAV connection '{}' for user {}",
+        request.name, auth_user.user_id
+    );
+
+    let name = request.name.trim();
+    if name.is_empty() || name.chars().count() > 64 {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Name must be 1-64 characters",
+        ));
+    }
+    if request.username.trim().is_empty() || request.password.is_empty() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Username and password are required",
+        ));
+    }
+    let url = request.url.trim();
+    validate_caldav_url(url)
+        .await
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+
+    let repository = CalDavRepository::new(state.pg_pool.clone());
+    if let Ok(Some(_)) = repository.get_connection_by_name(auth_user.user_id, name) {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            format!("Calendar '{}' is already connected", name),
+        ));
+    }
+
+    // Discover calendars up front: this doubles as the credential check and
+    // saves a round of discovery on every later read.
+    let client = CalDavClient::new(request.username.trim(), &request.password)
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    let calendars = client
+        .discover_calendars(url)
+        .await
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+
+    let credentials = CalDavCredentials {
+        url: url.to_string(),
+        username: request.username.trim().to_string(),
+        password: request.password.clone(),
+        calendars,
+    };
+    let connection = repository
+        .create_connection(auth_user.user_id, name, &credentials)
+        .map_err(|e| {
+            error!("Failed to create CalDAV connection: {}", e);
+            api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to save connection",
+            )
+        })?;
+    repository.to_response(&connection).map(Json).map_err(|e| {
+        error!("Failed to create response: {}", e);
+        api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to create connection response",
+        )
+    })
+}
+
+/// GET /api/calendar/connections - List the user's CalDAV connections
+pub async fn list_caldav_connections(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<CalDavConnectionResponse>>, ApiError> {
+    let repository = CalDavRepository::new(state.pg_pool.clone());
+    let connections = repository
+        .get_connections_for_user(auth_user.user_id)
+        .map_err(|e| {
+            error!("Failed to list CalDAV connections: {}", e);
+            api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to list connections",
+            )
+        })?;
+    connections
+        .iter()
+        .map(|c| repository.to_response(c))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to convert CalDAV connections: {}", e);
+            api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to process connections",
+            )
+        })
+}
+
+/// POST /api/calendar/connections/{id}/test - Re-discover calendars and
+/// count the coming week's events
+pub async fn test_caldav_connection(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(connection_id): Path<i32>,
+) -> Result<Json<CalDavTestConnectionResponse>, ApiError> {
+    let repository = CalDavRepository::new(state.pg_pool.clone());
+    let connection = load_connection(&repository, connection_id, auth_user.user_id)?;
+    let credentials = repository.get_credentials(&connection).map_err(|e| {
+        error!("Failed to decrypt CalDAV connection: {}", e);
+        api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to read connection",
+        )
+    })?;
+
+    let failed = |error: String| {
+        Json(CalDavTestConnectionResponse {
+            success: false,
+            calendars: None,
+            upcoming_events: None,
+            error: Some(error),
+        })
+    };
+    let client = match CalDavClient::new(&credentials.username, &credentials.password) {
+        Ok(client) => client,
+        Err(e) => return Ok(failed(e)),
+    };
+    let calendars = match client.discover_calendars(&credentials.url).await {
+        Ok(calendars) => calendars,
+        Err(e) => return Ok(failed(e)),
+    };
+    // Calendars may have been added or removed since connecting.
+    if calendars != credentials.calendars {
+        if let Err(e) = repository.update_calendars(connection.id, auth_user.user_id, &calendars) {
+            error!("Failed to refresh CalDAV calendars: {}", e);
+        }
+    }
+
+    let start = chrono::Utc::now();
+    let end = start + chrono::Duration::days(7);
+    let mut upcoming = 0;
+    for calendar in &calendars {
+        match client.list_events(&calendar.url, start, end).await {
+            Ok(events) => upcoming += events.len(),
+            Err(e) => return Ok(failed(format!("{}: {}", calendar.name, e))),
+        }
+    }
+
+    Ok(Json(CalDavTestConnectionResponse {
+        success: true,
+        calendars: Some(calendars.into_iter().map(|c| c.name).collect()),
+        upcoming_events: Some(upcoming),
+        error: None,
+    }))
+}
+
+/// PATCH /api/calendar/connections/{id}/toggle - Enable/disable a connection
+pub async fn toggle_caldav_connection(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(connection_id): Path<i32>,
+) -> Result<Json<Value>, ApiError> {
+    let repository = CalDavRepository::new(state.pg_pool.clone());
+    match repository.toggle_connection(connection_id, auth_user.user_id) {
+        Ok(is_enabled) => Ok(Json(json!({ "is_enabled": is_enabled }))),
+        Err(e) => {
+            error!("Failed to toggle CalDAV connection: {}", e);
+            Err(api_error(StatusCode::NOT_FOUND, "Connection not found"))
+        }
+    }
+}
+
+/// DELETE /api/calendar/connections/{id} - Remove a connection
+pub async fn delete_caldav_connection(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(connection_id): Path<i32>,
+) -> Result<Json<Value>, ApiError> {
+    info!(
+        "Deleting CalDAV connection {} for user {}",
+        connection_id, auth_user.user_id
+    );
+
+    let repository = CalDavRepository::new(state.pg_pool.clone());
+    match repository.delete_connection(connection_id, auth_user.user_id) {
+        Ok(()) => Ok(Json(json!({ "success": true }))),
+        Err(e) => {
+            error!("Failed to delete CalDAV connection: {}", e);
+            Err(api_error(StatusCode::NOT_FOUND, "Connection not found"))
+        }
+    }
+}
@@ -1170,6 +1170,17 @@ pub async fn get_rule_sources(
         meta: serde_json::json!({ "count": events_count }),
     });
 
+    // Calendar: available if user has an enabled CalDAV connection
+    let has_calendar =
+        crate::repositories::caldav_repository::CalDavRepository::new(state.pg_pool.clone())
+            .has_enabled_connection(user_id);
+    sources.push(RuleSourceOption {
+        source_type: "calendar".to_string(),
+        label: "Calendar".to_string(),
+        available: has_calendar,
+        meta: serde_json::json!({}),
+    });
+
     // MCP: available if user has enabled MCP servers
     let mcp_repo = crate::repositories::mcp_repository::McpRepository::new(state.pg_pool.clone());
     let mcp_servers = mcp_repo
@@ -0,0 +1,239 @@
+//! The user's email notification channel: pick an address they own and
+//! whether mail comes from the operator's relay or one of their connected
+//! accounts, choose per notification category whether it goes by SMS,
+//! email or both, send a test and remove it. See `channels::email_channel`
+//! for delivery.
+
+use crate::channels::email_channel::{EmailChannel, EmailContent, CHANNEL_ID};
+use crate::handlers::agent_integration_handlers::{has_active_subscription, now_unix};
+use crate::handlers::auth_middleware::AuthUser;
+use crate::handlers::mcp_handlers::ErrorResponse;
+use crate::models::email_channel_models::{
+    ConnectEmailChannelRequest, EmailChannelResponse, EmailChannelStatus, EmailNotificationChannel,
+    EmailSenderAccount, UpdateEmailDeliveryRequest,
+};
+use crate::models::matrix_channel_models::NotificationCategory;
+use crate::models::user_models::User;
+use crate::repositories::email_channel_repository::EmailChannelRepository;
+use crate::{AppState, UserCoreOps};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use std::sync::Arc;
+use tracing::error;
+
+const TEST_MESSAGE: &str = "Lightfriend email notifications are working.";
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn api_error(status: StatusCode, message: &str) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message.to_string(),
+        }),
+    )
+}
+
+fn db_error(e: diesel::result::Error) -> ApiError {
+    error!("Email channel query failed: {}", e);
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+}
+
+fn email_enabled(state: &Arc<AppState>) -> bool {
+    state.channel_router.channel(CHANNEL_ID).is_some()
+}
+
+fn describe(channel: EmailNotificationChannel) -> EmailChannelResponse {
+    EmailChannelResponse {
+        delivery: channel.delivery_modes(),
+        address: channel.address,
+        imap_connection_id: channel.imap_connection_id,
+    }
+}
+
+fn load_user(state: &Arc<AppState>, user_id: i32) -> Result<User, ApiError> {
+    state
+        .user_core
+        .find_by_id(user_id)
+        .map_err(|e| {
+            error!("Failed to load user {}: {}", user_id, e);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        })?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "User not found"))
+}
+
+/// The user's active connected mail accounts.
+fn sender_accounts(
+    state: &Arc<AppState>,
+    user_id: i32,
+) -> Result<Vec<EmailSenderAccount>, ApiError> {
+    Ok(state
+        .user_repository
+        .get_all_imap_credentials(user_id)
+        .map_err(db_error)?
+        .into_iter()
+        .map(|account| EmailSenderAccount {
+            imap_connection_id: account.id,
+            email: account.email,
+        })
+        .collect())
+}
+
+/// Notifications may only go to the account email or a connected mail
+/// account, so the channel can't be pointed at someone else's inbox.
+fn allowed_addresses(user: &User, accounts: &[EmailSenderAccount]) -> Vec<String> {
+    let mut addresses: Vec<String> = Vec::new();
+    for address in std::iter::once(user.email.as_str())
+        .chain(accounts.iter().map(|account| account.email.as_str()))
+    {
+        let address = address.trim();
+        if address.parse::<lettre::Address>().is_ok()
+            && !addresses
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(address))
+        {
+            addresses.push(address.to_string());
+        }
+    }
+    addresses
+}
+
+/// GET /api/me/email-channel - The channel (or `null`) and the addresses
+/// and senders the user can pick from.
+pub async fn get_email_channel(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<EmailChannelStatus>, ApiError> {
+    let user = load_user(&state, auth_user.user_id)?;
+    let accounts = sender_accounts(&state, user.id)?;
+    let channel = EmailChannelRepository::new(state.pg_pool.clone())
+        .find(user.id)
+        .map_err(db_error)?;
+    Ok(Json(EmailChannelStatus {
+        channel: channel.map(describe),
+        relay_available: email_enabled(&state)
+            && EmailChannel::from_state(&state).relay_available(),
+        allowed_addresses: allowed_addresses(&user, &accounts),
+        sender_accounts: accounts,
+    }))
+}
+
+/// PUT /api/me/email-channel - Set the address and sender. Delivery modes
+/// are kept when the channel already exists.
+pub async fn connect_email_channel(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<ConnectEmailChannelRequest>,
+) -> Result<Json<EmailChannelResponse>, ApiError> {
+    if !has_active_subscription(&state, auth_user.user_id) {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Active subscription required",
+        ));
+    }
+    if !email_enabled(&state) {
+        return Err(api_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Email notifications are not available on this server",
+        ));
+    }
+    let user = load_user(&state, auth_user.user_id)?;
+    let accounts = sender_accounts(&state, user.id)?;
+    let address = allowed_addresses(&user, &accounts)
+        .into_iter()
+        .find(|allowed| allowed.eq_ignore_ascii_case(request.address.trim()))
+        .ok_or_else(|| {
+            api_error(
+                StatusCode::BAD_REQUEST,
+                "Use your account email or a connected mail account",
+            )
+        })?;
+    match request.imap_connection_id {
+        Some(connection_id) => {
+            if !accounts
+                .iter()
+                .any(|account| account.imap_connection_id == connection_id)
+            {
+                return Err(api_error(
+                    StatusCode::BAD_REQUEST,
+                    "That mail account is not connected",
+                ));
+            }
+        }
+        None => {
+            if !EmailChannel::from_state(&state).relay_available() {
+                return Err(api_error(
+                    StatusCode::BAD_REQUEST,
+                    "This server has no mail relay; send from a connected mail account",
+                ));
+            }
+        }
+    }
+
+    let channel = EmailChannelRepository::new(state.pg_pool.clone())
+        .upsert(user.id, &address, request.imap_connection_id, now_unix())
+        .map_err(db_error)?;
+    Ok(Json(describe(channel)))
+}
+
+/// PATCH /api/me/email-channel/delivery - Change delivery modes for some
+/// notification categories.
+pub async fn update_email_delivery(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<UpdateEmailDeliveryRequest>,
+) -> Result<Json<EmailChannelResponse>, ApiError> {
+    let repository = EmailChannelRepository::new(state.pg_pool.clone());
+    let channel = repository
+        .find(auth_user.user_id)
+        .map_err(db_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No email channel"))?;
+    let modes = request.apply(channel.delivery_modes());
+    let channel = repository
+        .set_delivery(auth_user.user_id, &modes, now_unix())
+        .map_err(db_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No email channel"))?;
+    Ok(Json(describe(channel)))
+}
+
+/// POST /api/me/email-channel/test - Send a test email. Free, and never
+/// falls back to SMS.
+pub async fn test_email_channel(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    let user = load_user(&state, auth_user.user_id)?;
+    let channel = EmailChannelRepository::new(state.pg_pool.clone())
+        .find(user.id)
+        .map_err(db_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No email channel"))?;
+    let content = EmailContent::for_notification(NotificationCategory::Other, TEST_MESSAGE);
+    EmailChannel::from_state(&state)
+        .deliver(&user, &channel.address, &content)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Test email for user {} failed: {}", user.id, e);
+            api_error(
+                StatusCode::BAD_GATEWAY,
+                "The mail server did not accept the message",
+            )
+        })?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /api/me/email-channel - Remove the channel; everything goes back
+/// to SMS.
+pub async fn delete_email_channel(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    if EmailChannelRepository::new(state.pg_pool.clone())
+        .delete(auth_user.user_id)
+        .map_err(db_error)?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "No email channel"))
+    }
+}
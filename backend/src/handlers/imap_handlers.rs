@@ -33,7 +33,10 @@ pub fn smtp_server_for_imap(imap_server: Option<&str>) -> String {
     }
 }
 
-async fn send_smtp_message(mailer: lettre::SmtpTransport, message: Message) -> Result<(), String> {
+pub(crate) async fn send_smtp_message(
+    mailer: lettre::SmtpTransport,
+    message: Message,
+) -> Result<(), String> {
     match tokio::time::timeout(
         std::time::Duration::from_secs(60),
         tokio::task::spawn_blocking(move || mailer.send(&message)),
@@ -707,8 +710,23 @@ pub async fn insert_email_into_ontology(
                 } else {
                     match state.user_core.find_by_id(user_id) {
                         Ok(Some(user)) => {
-                            match state.channel_router.send_to_user(&user, &body, None).await {
-                            Ok(_) => notified = true,
+                            let reply_code =
+                                crate::proactive::reply_context::reserve_code(state, user_id);
+                            let tagged = crate::proactive::reply_context::tag(&reply_code, &body);
+                            match state.channel_router.send_to_user(&user, &tagged, None).await {
+                            Ok(_) => {
+                                notified = true;
+                                crate::proactive::reply_context::record(
+                                    state,
+                                    user_id,
+                                    &reply_code,
+                                    &crate::proactive::reply_context::ReplyTarget::EmailReplyWatch {
+                                        imap_connection_id: conn_id,
+                                        sender: key.to_string(),
+                                    },
+                                    &body,
+                                );
+                            }
                             Err(e) => tracing::warn!(
                                 "REPLY_WATCH SMS failed user={} watch={}, leaving armed for retry: {}",
                                 user_id,
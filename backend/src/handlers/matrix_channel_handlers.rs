@@ -0,0 +1,229 @@
+//! The user's Matrix DM channel: connect a Matrix ID they own, pick per
+//! notification category whether it goes by SMS, Matrix or both, and
+//! disconnect. See `channels::matrix_channel` for delivery.
+
+use crate::channels::matrix_channel::{self, CHANNEL_ID};
+use crate::handlers::agent_integration_handlers::{has_active_subscription, now_unix};
+use crate::handlers::auth_middleware::AuthUser;
+use crate::handlers::mcp_handlers::ErrorResponse;
+use crate::models::matrix_channel_models::{
+    ConnectMatrixChannelRequest, MatrixChannelResponse, MatrixDmChannel,
+    UpdateMatrixDeliveryRequest,
+};
+use crate::repositories::matrix_dm_channel_repository::MatrixDmChannelRepository;
+use crate::AppState;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use matrix_sdk::ruma::{OwnedRoomId, OwnedUserId};
+use std::sync::Arc;
+use tracing::error;
+
+const WELCOME_MESSAGE: &str = "This room is your Lightfriend assistant. Message it here like you would by SMS; replies and the notifications you route to Matrix arrive here.";
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn api_error(status: StatusCode, message: &str) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message.to_string(),
+        }),
+    )
+}
+
+fn db_error(e: diesel::result::Error) -> ApiError {
+    error!("Matrix DM channel query failed: {}", e);
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+}
+
+fn matrix_enabled(state: &Arc<AppState>) -> bool {
+    state.channel_router.channel(CHANNEL_ID).is_some()
+}
+
+/// GET /api/me/matrix-channel - The channel, or `null` when none is set up.
+pub async fn get_matrix_channel(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Option<MatrixChannelResponse>>, ApiError> {
+    let repository = MatrixDmChannelRepository::new(state.pg_pool.clone());
+    let Some(channel) = repository.find(auth_user.user_id).map_err(db_error)? else {
+        return Ok(Json(None));
+    };
+    Ok(Json(Some(describe(&state, &channel).await)))
+}
+
+/// PUT /api/me/matrix-channel - Open a DM room with `target_mxid` from the
+/// user's Lightfriend Matrix account. Switching to another Matrix ID leaves
+/// the old room; repeating the current one re-sends a missed invite.
+pub async fn connect_matrix_channel(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<ConnectMatrixChannelRequest>,
+) -> Result<Json<MatrixChannelResponse>, ApiError> {
+    if !has_active_subscription(&state, auth_user.user_id) {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Active subscription required",
+        ));
+    }
+    if !matrix_enabled(&state) {
+        return Err(api_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Matrix is not available on this server",
+        ));
+    }
+    let client = crate::utils::matrix_auth::get_cached_client(auth_user.user_id, &state)
+        .await
+        .map_err(|e| {
+            error!(
+                "Matrix client unavailable for user {}: {}",
+                auth_user.user_id, e
+            );
+            api_error(StatusCode::SERVICE_UNAVAILABLE, "Matrix is unavailable")
+        })?;
+    let own_server = client
+        .user_id()
+        .map(|id| id.server_name().to_string())
+        .unwrap_or_default();
+    let target = matrix_channel::validate_target_mxid(&request.target_mxid, &own_server)
+        .map_err(|message| api_error(StatusCode::BAD_REQUEST, message))?;
+
+    let repository = MatrixDmChannelRepository::new(state.pg_pool.clone());
+    let existing = repository.find(auth_user.user_id).map_err(db_error)?;
+    let existing_room = existing
+        .as_ref()
+        .and_then(|channel| channel.room_id.as_deref())
+        .and_then(|room_id| OwnedRoomId::try_from(room_id).ok())
+        .and_then(|room_id| client.get_room(&room_id));
+
+    let room = match (existing.as_ref(), existing_room) {
+        (Some(channel), Some(room)) if channel.target_mxid == target.as_str() => {
+            if !matrix_channel::has_joined(&room, &target).await {
+                if let Err(e) = room.invite_user_by_id(&target).await {
+                    tracing::warn!("Failed to re-invite {} to Matrix DM: {}", target, e);
+                }
+            }
+            room
+        }
+        (_, old_room) => {
+            if let Some(old_room) = old_room {
+                if let Err(e) = old_room.leave().await {
+                    tracing::warn!("Failed to leave old Matrix DM room: {}", e);
+                }
+            }
+            let room = client.create_dm(&target).await.map_err(|e| {
+                error!("Failed to create Matrix DM with {}: {}", target, e);
+                api_error(
+                    StatusCode::BAD_GATEWAY,
+                    "Could not create a room with that Matrix ID",
+                )
+            })?;
+            if let Err(e) = room.set_name("Lightfriend".to_string()).await {
+                tracing::warn!("Failed to name Matrix DM room: {}", e);
+            }
+            if let Err(e) = room
+                .send(
+                    matrix_sdk::ruma::events::room::message::RoomMessageEventContent::text_plain(
+                        WELCOME_MESSAGE,
+                    ),
+                )
+                .await
+            {
+                tracing::warn!("Failed to send Matrix DM welcome: {}", e);
+            }
+            room
+        }
+    };
+
+    let channel = repository
+        .connect(
+            auth_user.user_id,
+            target.as_str(),
+            room.room_id().as_str(),
+            now_unix(),
+        )
+        .map_err(db_error)?;
+    Ok(Json(describe(&state, &channel).await))
+}
+
+/// PATCH /api/me/matrix-channel/delivery - Change delivery modes for some
+/// notification categories.
+pub async fn update_matrix_delivery(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<UpdateMatrixDeliveryRequest>,
+) -> Result<Json<MatrixChannelResponse>, ApiError> {
+    let repository = MatrixDmChannelRepository::new(state.pg_pool.clone());
+    let channel = repository
+        .find(auth_user.user_id)
+        .map_err(db_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No Matrix channel"))?;
+    let modes = request.apply(channel.delivery_modes());
+    let channel = repository
+        .set_delivery(auth_user.user_id, &modes, now_unix())
+        .map_err(db_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No Matrix channel"))?;
+    Ok(Json(describe(&state, &channel).await))
+}
+
+/// DELETE /api/me/matrix-channel - Leave the room and go back to SMS only.
+pub async fn delete_matrix_channel(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    let repository = MatrixDmChannelRepository::new(state.pg_pool.clone());
+    let Some(channel) = repository.find(auth_user.user_id).map_err(db_error)? else {
+        return Err(api_error(StatusCode::NOT_FOUND, "No Matrix channel"));
+    };
+    if let (true, Some(room_id)) = (matrix_enabled(&state), channel.room_id.as_deref()) {
+        match crate::utils::matrix_auth::get_cached_client(auth_user.user_id, &state).await {
+            Ok(client) => {
+                let room = OwnedRoomId::try_from(room_id)
+                    .ok()
+                    .and_then(|room_id| client.get_room(&room_id));
+                if let Some(room) = room {
+                    if let Err(e) = room.leave().await {
+                        tracing::warn!("Failed to leave Matrix DM room: {}", e);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Matrix client unavailable to leave DM room: {}", e),
+        }
+    }
+    repository.delete(auth_user.user_id).map_err(db_error)?;
+    crate::utils::matrix_auth::stop_matrix_user_if_no_bridges(auth_user.user_id, &state)
+        .await
+        .ok();
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn describe(state: &Arc<AppState>, channel: &MatrixDmChannel) -> MatrixChannelResponse {
+    let mut response = MatrixChannelResponse {
+        target_mxid: channel.target_mxid.clone(),
+        room_id: channel.room_id.clone(),
+        joined: false,
+        sender_mxid: None,
+        delivery: channel.delivery_modes(),
+    };
+    if !matrix_enabled(state) {
+        return response;
+    }
+    let client = match crate::utils::matrix_auth::get_cached_client(channel.user_id, state).await {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!("Matrix client unavailable for channel status: {}", e);
+            return response;
+        }
+    };
+    response.sender_mxid = client.user_id().map(|id| id.to_string());
+    let room = channel
+        .room_id
+        .as_deref()
+        .and_then(|room_id| OwnedRoomId::try_from(room_id).ok())
+        .and_then(|room_id| client.get_room(&room_id));
+    if let (Some(room), Ok(target)) = (room, OwnedUserId::try_from(channel.target_mxid.as_str())) {
+        response.joined = matrix_channel::has_joined(&room, &target).await;
+    }
+    response
+}
@@ -3,26 +3,37 @@
 //! Endpoints for managing custom MCP server connections.
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
+    response::Redirect,
     Json,
 };
+use oauth2::{CsrfToken, PkceCodeChallenge};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::net::IpAddr;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::net::lookup_host;
-use tracing::{error, info};
+use tower_sessions::{
+    session::{Id, Record},
+    session_store::SessionStore,
+};
+use tracing::{error, info, warn};
 use url::Url;
+use uuid::Uuid;
 
 use crate::handlers::auth_middleware::AuthUser;
 use crate::models::mcp_models::{
-    CreateMcpServerRequest, McpServerResponse, McpTestConnectionResponse, McpToolInfo,
+    CreateMcpServerRequest, McpOAuthStartResponse, McpPromptInfo, McpResourceInfo,
+    McpServerResponse, McpTestConnectionResponse, McpToolInfo, StartMcpOAuthRequest,
 };
 use crate::repositories::mcp_repository::McpRepository;
-use crate::services::mcp_client::McpClientService;
+use crate::services::mcp_client::{McpClientService, McpResource, McpServerProbe, McpTool};
+use crate::services::mcp_oauth::{self, McpOAuthClient, McpOAuthConfig};
 use crate::AppState;
 
-fn is_private_ip(ip: IpAddr) -> bool {
+pub(crate) fn is_private_ip(ip: IpAddr) -> bool {
     match ip {
         IpAddr::V4(ipv4) => {
             ipv4.is_private()
@@ -40,7 +51,19 @@ fn is_private_ip(ip: IpAddr) -> bool {
     }
 }
 
-async fn validate_public_mcp_url(url: &str) -> Result<(), String> {
+pub(crate) async fn validate_public_mcp_url(url: &str) -> Result<(), String> {
+    validate_public_url(url, "MCP", false).await
+}
+
+/// Reject URLs on localhost or whose host resolves to a private or local
+/// address. `allow_private` skips the address checks, for self-hosted
+/// deployments that opt in to reaching their own network. `label` names
+/// the kind of URL in error messages.
+pub(crate) async fn validate_public_url(
+    url: &str,
+    label: &str,
+    allow_private: bool,
+) -> Result<(), String> {
     let parsed = Url::parse(url).map_err(|_| "Invalid URL".to_string())?;
     let host = parsed
         .host_str()
@@ -49,43 +72,44 @@ async fn validate_public_mcp_url(url: &str) -> Result<(), String> {
         .port_or_known_default()
         .ok_or_else(|| "URL must include a valid port".to_string())?;
 
+    if allow_private {
+        return Ok(());
+    }
+
     if host.eq_ignore_ascii_case("localhost") {
-        return Err("Localhost MCP URLs are not allowed".to_string());
+        return Err(format!("Localhost {} URLs are not allowed", label));
     }
 
-    if let Ok(ip) = host.parse::<IpAddr>() {
+    if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
         if is_private_ip(ip) {
-            return Err("Private or local MCP URLs are not allowed".to_string());
+            return Err(format!("Private or local {} URLs are not allowed", label));
         }
         return Ok(());
     }
 
     let resolved = lookup_host((host, port))
         .await
-        .map_err(|_| "Failed to resolve MCP server host".to_string())?;
+        .map_err(|_| format!("Failed to resolve {} server host", label))?;
 
     for addr in resolved {
         if is_private_ip(addr.ip()) {
-            return Err("MCP URL resolves to a private or local address".to_string());
+            return Err(format!(
+                "{} URL resolves to a private or local address",
+                label
+            ));
         }
     }
 
     Ok(())
 }
 
-/// POST /api/mcp/servers - Add a new MCP server
-pub async fn create_mcp_server(
-    State(state): State<Arc<AppState>>,
-    auth_user: AuthUser,
-    Json(request): Json<CreateMcpServerRequest>,
-) -> Result<Json<McpServerResponse>, (StatusCode, Json<ErrorResponse>)> {
-    info!(
-        "Creating MCP server '{}' for user {}",
-        request.name, auth_user.user_id
-    );
-
+/// Name and URL checks shared by both ways of adding a server.
+async fn validate_new_server(
+    name: &str,
+    url: &str,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
     // Validate the name
-    if request.name.trim().is_empty() {
+    if name.trim().is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -95,8 +119,7 @@ pub async fn create_mcp_server(
     }
 
     // Validate the name doesn't contain special characters that could cause issues
-    if !request
-        .name
+    if !name
         .chars()
         .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
     {
@@ -110,7 +133,7 @@ pub async fn create_mcp_server(
     }
 
     // Validate URL format
-    if !request.url.starts_with("http://") && !request.url.starts_with("https://") {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -119,10 +142,26 @@ pub async fn create_mcp_server(
         ));
     }
 
-    validate_public_mcp_url(&request.url)
+    validate_public_mcp_url(url)
         .await
         .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })))?;
 
+    Ok(())
+}
+
+/// POST /api/mcp/servers - Add a new MCP server
+pub async fn create_mcp_server(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<CreateMcpServerRequest>,
+) -> Result<Json<McpServerResponse>, (StatusCode, Json<ErrorResponse>)> {
+    info!(
+        "Creating MCP server '{}' for user {}",
+        request.name, auth_user.user_id
+    );
+
+    validate_new_server(&request.name, &request.url).await?;
+
     let mcp_repository = McpRepository::new(state.pg_pool.clone());
 
     // Check if server with this name already exists for user
@@ -259,33 +298,41 @@ pub async fn list_server_tools(
         }
     };
 
-    // Connect and list tools
-    let mcp_client = McpClientService::new();
-    match mcp_client.list_tools(&url, auth_token.as_deref()).await {
-        Ok(tools) => {
-            let tool_infos: Vec<McpToolInfo> = tools
-                .into_iter()
-                .map(|t| McpToolInfo {
-                    name: t.name,
-                    description: t.description,
-                    input_schema: Some(t.input_schema),
-                })
-                .collect();
-
-            Ok(Json(McpTestConnectionResponse {
-                success: true,
-                tools_count: Some(tool_infos.len()),
-                tools: Some(tool_infos),
-                error: None,
-            }))
-        }
-        Err(e) => Ok(Json(McpTestConnectionResponse {
-            success: false,
-            tools_count: None,
-            tools: None,
-            error: Some(e),
-        })),
-    }
+    // Connect and list tools (plus resources, for the rule builder)
+    let mcp_client = mcp_repository.client_for_server(&server).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Failed to load OAuth settings: {}", e),
+            }),
+        )
+    })?;
+    let tools = match mcp_client.list_tools(&url, auth_token.as_deref()).await {
+        Ok(tools) => tools,
+        Err(e) => return Ok(Json(McpTestConnectionResponse::failed(e))),
+    };
+    let resources = mcp_client
+        .list_resources(&url, auth_token.as_deref())
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to list resources for MCP server {}: {}",
+                server_id, e
+            );
+            Vec::new()
+        });
+
+    let tool_infos = tool_infos(tools);
+    Ok(Json(McpTestConnectionResponse {
+        success: true,
+        tools_count: Some(tool_infos.len()),
+        tools: Some(tool_infos),
+        error: None,
+        server_name: None,
+        protocol_version: None,
+        resources: Some(resource_infos(resources)),
+        prompts: None,
+    }))
 }
 
 /// POST /api/mcp/servers/:id/test - Test connection to server
@@ -294,7 +341,7 @@ pub async fn test_server_connection(
     auth_user: AuthUser,
     Path(server_id): Path<i32>,
 ) -> Result<Json<McpTestConnectionResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Same as list_server_tools but uses test_connection
+    // Same as list_server_tools but probes with a fresh handshake
     let mcp_repository = McpRepository::new(state.pg_pool.clone());
 
     let server = match mcp_repository.get_server_by_id(server_id, auth_user.user_id) {
@@ -341,34 +388,17 @@ pub async fn test_server_connection(
         }
     };
 
-    let mcp_client = McpClientService::new();
-    match mcp_client
-        .test_connection(&url, auth_token.as_deref())
-        .await
-    {
-        Ok(tools) => {
-            let tool_infos: Vec<McpToolInfo> = tools
-                .into_iter()
-                .map(|t| McpToolInfo {
-                    name: t.name,
-                    description: t.description,
-                    input_schema: Some(t.input_schema),
-                })
-                .collect();
-
-            Ok(Json(McpTestConnectionResponse {
-                success: true,
-                tools_count: Some(tool_infos.len()),
-                tools: Some(tool_infos),
-                error: None,
-            }))
-        }
-        Err(e) => Ok(Json(McpTestConnectionResponse {
-            success: false,
-            tools_count: None,
-            tools: None,
-            error: Some(e),
-        })),
+    let mcp_client = mcp_repository.client_for_server(&server).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Failed to load OAuth settings: {}", e),
+            }),
+        )
+    })?;
+    match mcp_client.probe(&url, auth_token.as_deref()).await {
+        Ok(probe) => Ok(Json(probe_response(probe))),
+        Err(e) => Ok(Json(McpTestConnectionResponse::failed(e))),
     }
 }
 
@@ -415,6 +445,20 @@ pub async fn toggle_mcp_server(
 
     let mcp_repository = McpRepository::new(state.pg_pool.clone());
 
+    // An OAuth server can't be used until it has been authorized
+    if let Ok(Some(server)) = mcp_repository.get_server_by_id(server_id, auth_user.user_id) {
+        let awaiting_oauth = server.oauth_config_encrypted.is_some()
+            && server.oauth_access_token_encrypted.is_none();
+        if server.is_enabled == 0 && awaiting_oauth {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "Authorize this server before enabling it".to_string(),
+                }),
+            ));
+        }
+    }
+
     match mcp_repository.toggle_server(server_id, auth_user.user_id) {
         Ok(is_enabled) => Ok(Json(ToggleResponse { is_enabled })),
         Err(e) => {
@@ -451,32 +495,326 @@ pub async fn test_url_connection(
 
     let mcp_client = McpClientService::new();
     match mcp_client
-        .test_connection(&request.url, request.auth_token.as_deref())
+        .probe(&request.url, request.auth_token.as_deref())
         .await
     {
-        Ok(tools) => {
-            let tool_infos: Vec<McpToolInfo> = tools
-                .into_iter()
-                .map(|t| McpToolInfo {
-                    name: t.name,
-                    description: t.description,
-                    input_schema: Some(t.input_schema),
-                })
-                .collect();
+        Ok(probe) => Ok(Json(probe_response(probe))),
+        Err(e) => Ok(Json(McpTestConnectionResponse::failed(e))),
+    }
+}
+
+/// POST /api/mcp/oauth/start - Add a server that authorizes with OAuth.
+///
+/// Discovers the server's authorization server, registers Lightfriend as a
+/// client and returns the URL to send the user to. The server is stored
+/// disabled until the callback saves its tokens.
+pub async fn start_mcp_oauth(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<StartMcpOAuthRequest>,
+) -> Result<Json<McpOAuthStartResponse>, (StatusCode, Json<ErrorResponse>)> {
+    info!(
+        "Starting OAuth for MCP server '{}' for user {}",
+        request.name, auth_user.user_id
+    );
+
+    validate_new_server(&request.name, &request.url).await?;
+
+    let mcp_repository = McpRepository::new(state.pg_pool.clone());
+    if let Ok(Some(_)) = mcp_repository.get_server_by_name(auth_user.user_id, &request.name) {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: format!("Server '{}' already exists", request.name),
+            }),
+        ));
+    }
+
+    let config = McpOAuthClient::new()
+        .prepare(&request.url, &oauth_redirect_uri())
+        .await
+        .map_err(|error| {
+            warn!("MCP OAuth discovery failed for {}: {}", request.url, error);
+            (StatusCode::BAD_GATEWAY, Json(ErrorResponse { error }))
+        })?;
+
+    let server = mcp_repository
+        .create_oauth_server(auth_user.user_id, &request.name, &request.url, &config)
+        .map_err(|e| {
+            error!("Failed to create MCP server: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to create server: {}", e),
+                }),
+            )
+        })?;
+
+    let auth_url = begin_authorization(&state, auth_user.user_id, server.id, &config).await?;
+    Ok(Json(McpOAuthStartResponse {
+        server_id: server.id,
+        auth_url,
+    }))
+}
+
+/// POST /api/mcp/servers/:id/oauth/start - Re-authorize an OAuth server,
+/// e.g. after its refresh token was revoked.
+pub async fn reauthorize_mcp_oauth(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(server_id): Path<i32>,
+) -> Result<Json<McpOAuthStartResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mcp_repository = McpRepository::new(state.pg_pool.clone());
 
-            Ok(Json(McpTestConnectionResponse {
-                success: true,
-                tools_count: Some(tool_infos.len()),
-                tools: Some(tool_infos),
-                error: None,
-            }))
+    let server = match mcp_repository.get_server_by_id(server_id, auth_user.user_id) {
+        Ok(Some(s)) => s,
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "Server not found".to_string(),
+                }),
+            ));
         }
-        Err(e) => Ok(Json(McpTestConnectionResponse {
-            success: false,
-            tools_count: None,
-            tools: None,
-            error: Some(e),
-        })),
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to get server: {}", e),
+                }),
+            ));
+        }
+    };
+
+    let config = match mcp_repository.get_oauth_config(&server) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "Server does not use OAuth".to_string(),
+                }),
+            ));
+        }
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to load OAuth settings: {}", e),
+                }),
+            ));
+        }
+    };
+
+    let auth_url = begin_authorization(&state, auth_user.user_id, server.id, &config).await?;
+    Ok(Json(McpOAuthStartResponse {
+        server_id: server.id,
+        auth_url,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct McpOAuthCallbackParams {
+    pub state: String,
+    pub code: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+/// GET /api/mcp/oauth/callback - Authorization server redirect target.
+/// Always redirects back to the frontend with the outcome.
+pub async fn mcp_oauth_callback(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<McpOAuthCallbackParams>,
+) -> Redirect {
+    let Some((session_id, state_csrf)) = params
+        .state
+        .split_once(':')
+        .and_then(|(id, csrf)| Some((id.parse::<i128>().ok()?, csrf)))
+    else {
+        error!("Invalid MCP OAuth state: {}", params.state);
+        return mcp_oauth_redirect(Err("Invalid OAuth state"));
+    };
+
+    let record = match state.session_store.load(&Id(session_id)).await {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            return mcp_oauth_redirect(Err("Session expired. Please try connecting again."));
+        }
+        Err(e) => {
+            error!("Failed to load MCP OAuth session: {}", e);
+            return mcp_oauth_redirect(Err("Failed to load session"));
+        }
+    };
+    // Single use, whatever the outcome
+    let _ = state.session_store.delete(&Id(session_id)).await;
+
+    let stored_csrf = record.data.get("csrf_token").and_then(|v| v.as_str());
+    if stored_csrf != Some(state_csrf) {
+        error!("MCP OAuth CSRF token mismatch");
+        return mcp_oauth_redirect(Err("Security token mismatch"));
+    }
+    let (Some(user_id), Some(server_id), Some(verifier)) = (
+        record.data.get("user_id").and_then(|v| v.as_i64()),
+        record.data.get("server_id").and_then(|v| v.as_i64()),
+        record.data.get("pkce_verifier").and_then(|v| v.as_str()),
+    ) else {
+        error!("MCP OAuth session is missing fields");
+        return mcp_oauth_redirect(Err("User session invalid"));
+    };
+
+    if let Some(error) = params.error {
+        warn!(
+            "MCP OAuth denied for user {}: {} {}",
+            user_id,
+            error,
+            params.error_description.as_deref().unwrap_or("")
+        );
+        return mcp_oauth_redirect(Err("Authorization was denied"));
+    }
+    let Some(code) = params.code else {
+        return mcp_oauth_redirect(Err("No authorization code received"));
+    };
+
+    let mcp_repository = McpRepository::new(state.pg_pool.clone());
+    let config: Option<McpOAuthConfig> = mcp_repository
+        .get_server_by_id(server_id as i32, user_id as i32)
+        .ok()
+        .flatten()
+        .and_then(|server| mcp_repository.get_oauth_config(&server).ok().flatten());
+    let Some(config) = config else {
+        return mcp_oauth_redirect(Err("MCP server not found"));
+    };
+
+    let tokens = match McpOAuthClient::new()
+        .exchange_code(&config, &code, verifier)
+        .await
+    {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            error!("MCP OAuth code exchange failed for user {}: {}", user_id, e);
+            return mcp_oauth_redirect(Err("Failed to complete authorization"));
+        }
+    };
+
+    if let Err(e) = mcp_repository.complete_oauth(server_id as i32, user_id as i32, &tokens) {
+        error!("Failed to save MCP OAuth tokens: {}", e);
+        return mcp_oauth_redirect(Err("Failed to save authorization"));
+    }
+
+    info!(
+        "MCP server {} authorized via OAuth for user {}",
+        server_id, user_id
+    );
+    mcp_oauth_redirect(Ok(()))
+}
+
+/// Store the PKCE verifier in a short-lived session and build the
+/// authorization URL, with `{session_id}:{csrf}` as the state.
+async fn begin_authorization(
+    state: &Arc<AppState>,
+    user_id: i32,
+    server_id: i32,
+    config: &McpOAuthConfig,
+) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    let csrf_token = CsrfToken::new_random();
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+    let mut record = Record {
+        id: Id(Uuid::new_v4().as_u128() as i128),
+        data: Default::default(),
+        expiry_date: OffsetDateTime::now_utc() + time::Duration::hours(1),
+    };
+    record.data.insert(
+        "pkce_verifier".to_string(),
+        json!(pkce_verifier.secret().to_string()),
+    );
+    record.data.insert(
+        "csrf_token".to_string(),
+        json!(csrf_token.secret().to_string()),
+    );
+    record.data.insert("user_id".to_string(), json!(user_id));
+    record
+        .data
+        .insert("server_id".to_string(), json!(server_id));
+
+    if let Err(e) = state.session_store.create(&mut record).await {
+        error!("Failed to store MCP OAuth session: {}", e);
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: "Failed to start authorization".to_string(),
+            }),
+        ));
+    }
+
+    let state_token = format!("{}:{}", record.id.0, csrf_token.secret());
+    mcp_oauth::authorization_url(config, &state_token, pkce_challenge.as_str())
+        .map_err(|error| (StatusCode::BAD_GATEWAY, Json(ErrorResponse { error })))
+}
+
+fn oauth_redirect_uri() -> String {
+    let server_url =
+        std::env::var("SERVER_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+    format!("{}/api/mcp/oauth/callback", server_url)
+}
+
+fn mcp_oauth_redirect(outcome: Result<(), &str>) -> Redirect {
+    let frontend_url =
+        std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+    match outcome {
+        Ok(()) => Redirect::to(&format!("{}?mcp=success", frontend_url)),
+        Err(message) => Redirect::to(&format!(
+            "{}?mcp=error&message={}",
+            frontend_url,
+            urlencoding::encode(message)
+        )),
+    }
+}
+
+fn tool_infos(tools: Vec<McpTool>) -> Vec<McpToolInfo> {
+    tools
+        .into_iter()
+        .map(|t| McpToolInfo {
+            name: t.name,
+            description: t.description,
+            input_schema: Some(t.input_schema),
+        })
+        .collect()
+}
+
+fn resource_infos(resources: Vec<McpResource>) -> Vec<McpResourceInfo> {
+    resources
+        .into_iter()
+        .map(|r| McpResourceInfo {
+            uri: r.uri,
+            name: r.name,
+            description: r.description,
+            mime_type: r.mime_type,
+        })
+        .collect()
+}
+
+fn probe_response(probe: McpServerProbe) -> McpTestConnectionResponse {
+    let tools = tool_infos(probe.tools);
+    McpTestConnectionResponse {
+        success: true,
+        tools_count: Some(tools.len()),
+        tools: Some(tools),
+        error: None,
+        server_name: probe.info.name,
+        protocol_version: Some(probe.info.protocol_version),
+        resources: Some(resource_infos(probe.resources)),
+        prompts: Some(
+            probe
+                .prompts
+                .into_iter()
+                .map(|p| McpPromptInfo {
+                    name: p.name,
+                    description: p.description,
+                })
+                .collect(),
+        ),
     }
 }
 
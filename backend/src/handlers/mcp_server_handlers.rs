@@ -0,0 +1,264 @@
+//! Lightfriend's own MCP endpoint and the tokens that unlock it.
+//!
+//! JWT-authed endpoints mint, list, update and revoke
+//! `mcp_access_tokens`. The public `POST /api/mcp` endpoint speaks
+//! Streamable HTTP to desktop AI clients; see `services::mcp_server` for the
+//! protocol and tool surface.
+//!
+//! Security shape:
+//! - Tokens are `lfmcp_` + 64 hex chars. Only the SHA-256 hash is stored and
+//!   the raw token is shown once.
+//! - Each token carries an allow-list of tools chosen at mint time. Tools
+//!   outside it are indistinguishable from tools that don't exist.
+//! - The endpoint rejects query strings, so a token pasted into a URL is
+//!   never accepted, and browser requests from other origins, as the
+//!   transport spec requires.
+//! - Every authenticated request stamps the token's `last_used_at`.
+
+use crate::handlers::agent_integration_handlers::{
+    bearer, has_active_subscription, hash_secret, no_store, now_unix, random_hex,
+    valid_prefixed_hex,
+};
+use crate::handlers::auth_middleware::AuthUser;
+use crate::handlers::mcp_handlers::ErrorResponse;
+use crate::models::mcp_models::{
+    canonical_mcp_tools, CreateMcpAccessTokenRequest, McpAccessTokenResponse, NewMcpAccessToken,
+    UpdateMcpAccessTokenRequest, DEFAULT_MCP_SERVER_TOOLS,
+};
+use crate::repositories::mcp_access_token_repository::{
+    McpAccessTokenRepository, MAX_ACTIVE_MCP_TOKENS,
+};
+use crate::services::mcp_server::{self, SERVER_PROTOCOL_VERSIONS};
+use crate::AppState;
+use axum::body::Bytes;
+use axum::extract::{OriginalUri, Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use governor::{Quota, RateLimiter};
+use serde_json::Value;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use tracing::error;
+
+const TOKEN_PREFIX: &str = "lfmcp_";
+const PROTOCOL_HEADER: &str = "mcp-protocol-version";
+/// Clients open with initialize, initialized and tools/list back to back,
+/// so the burst is wider than the agent API's.
+const REQUESTS_PER_MINUTE: u32 = 120;
+const REQUEST_BURST: u32 = 20;
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn api_error(status: StatusCode, message: &str) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message.to_string(),
+        }),
+    )
+}
+
+fn db_error(e: diesel::result::Error) -> ApiError {
+    error!("MCP access token query failed: {}", e);
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+}
+
+/// GET /api/me/mcp-tokens - Active tokens, newest first. Never the token itself.
+pub async fn list_access_tokens(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<McpAccessTokenResponse>>, ApiError> {
+    let repository = McpAccessTokenRepository::new(state.pg_pool.clone());
+    let tokens = repository
+        .list_for_user(auth_user.user_id)
+        .map_err(db_error)?;
+    Ok(Json(
+        tokens.iter().map(McpAccessTokenResponse::from).collect(),
+    ))
+}
+
+/// POST /api/me/mcp-tokens - Mint a token. The response is the only time the
+/// raw token is shown.
+pub async fn create_access_token(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<CreateMcpAccessTokenRequest>,
+) -> Result<Response, ApiError> {
+    if !has_active_subscription(&state, auth_user.user_id) {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Active subscription required",
+        ));
+    }
+    let label = request.label.trim();
+    if label.is_empty() || label.chars().count() > 64 || label.chars().any(char::is_control) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "label must be 1..=64 chars",
+        ));
+    }
+    let allowed_tools = match &request.tools {
+        Some(tools) => canonical_mcp_tools(tools)
+            .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Unknown or empty tool list"))?,
+        None => DEFAULT_MCP_SERVER_TOOLS.to_string(),
+    };
+
+    let raw_token = format!("{}{}", TOKEN_PREFIX, random_hex(32));
+    let token_hash = hash_secret(&raw_token);
+    let token_prefix = raw_token.chars().take(14).collect::<String>();
+    let repository = McpAccessTokenRepository::new(state.pg_pool.clone());
+    let created = repository
+        .create(&NewMcpAccessToken {
+            user_id: auth_user.user_id,
+            token_hash: &token_hash,
+            token_prefix: &token_prefix,
+            label,
+            allowed_tools: &allowed_tools,
+            created_at: now_unix(),
+        })
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            api_error(
+                StatusCode::CONFLICT,
+                &format!(
+                    "At most {} MCP tokens can be active; revoke one first",
+                    MAX_ACTIVE_MCP_TOKENS
+                ),
+            )
+        })?;
+
+    let mut response = McpAccessTokenResponse::from(&created);
+    response.token = Some(raw_token);
+    Ok(no_store(
+        (StatusCode::CREATED, Json(response)).into_response(),
+    ))
+}
+
+/// PATCH /api/me/mcp-tokens/{id} - Replace a token's tool allow-list.
+pub async fn update_access_token(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(token_id): Path<i32>,
+    Json(request): Json<UpdateMcpAccessTokenRequest>,
+) -> Result<Json<McpAccessTokenResponse>, ApiError> {
+    let allowed_tools = canonical_mcp_tools(&request.tools)
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Unknown or empty tool list"))?;
+    let repository = McpAccessTokenRepository::new(state.pg_pool.clone());
+    let updated = repository
+        .set_allowed_tools(auth_user.user_id, token_id, &allowed_tools)
+        .map_err(db_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Token not found"))?;
+    Ok(Json(McpAccessTokenResponse::from(&updated)))
+}
+
+/// DELETE /api/me/mcp-tokens/{id} - Revoke a token. Repeat revokes succeed.
+pub async fn revoke_access_token(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(token_id): Path<i32>,
+) -> Result<StatusCode, ApiError> {
+    let repository = McpAccessTokenRepository::new(state.pg_pool.clone());
+    if repository
+        .revoke(auth_user.user_id, token_id, now_unix())
+        .map_err(db_error)?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "Token not found"))
+    }
+}
+
+/// POST /api/mcp - One JSON-RPC message from an MCP client.
+pub async fn mcp_endpoint(
+    State(state): State<Arc<AppState>>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if uri.query().is_some() {
+        return no_store(StatusCode::BAD_REQUEST.into_response());
+    }
+    if !allowed_origin(&headers) {
+        return no_store(StatusCode::FORBIDDEN.into_response());
+    }
+
+    let Some(raw_token) =
+        bearer(&headers).filter(|value| valid_prefixed_hex(value, TOKEN_PREFIX, 64))
+    else {
+        return unauthorized();
+    };
+    let repository = McpAccessTokenRepository::new(state.pg_pool.clone());
+    let token = match repository.find_active(&hash_secret(raw_token)) {
+        Ok(Some(token)) => token,
+        Ok(None) => return unauthorized(),
+        Err(e) => {
+            error!("MCP token lookup failed: {}", e);
+            return no_store(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+    if !within_rate_limit(&state, token.id) {
+        return no_store(StatusCode::TOO_MANY_REQUESTS.into_response());
+    }
+    if let Err(e) = repository.touch(token.id, now_unix()) {
+        error!("Failed to record MCP token use: {}", e);
+    }
+    if !has_active_subscription(&state, token.user_id) {
+        return no_store(StatusCode::FORBIDDEN.into_response());
+    }
+
+    // Clients send the negotiated version on every request after
+    // initialize; an absent header means the client predates it.
+    let version = headers
+        .get(PROTOCOL_HEADER)
+        .map(|value| value.to_str().unwrap_or_default());
+    if version.is_some_and(|version| !SERVER_PROTOCOL_VERSIONS.contains(&version)) {
+        return no_store(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    let message = match serde_json::from_slice::<Value>(&body) {
+        Ok(message) => message,
+        Err(_) => {
+            return no_store(
+                (StatusCode::BAD_REQUEST, Json(mcp_server::parse_error())).into_response(),
+            )
+        }
+    };
+    match mcp_server::handle_message(&state, &token, message).await {
+        Some(response) => no_store(Json(response).into_response()),
+        None => no_store(StatusCode::ACCEPTED.into_response()),
+    }
+}
+
+fn within_rate_limit(state: &Arc<AppState>, token_id: i32) -> bool {
+    let key = format!("mcp-server:{}", token_id);
+    let quota = Quota::per_minute(NonZeroU32::new(REQUESTS_PER_MINUTE).unwrap())
+        .allow_burst(NonZeroU32::new(REQUEST_BURST).unwrap());
+    let limiter = state
+        .api_rate_limiter
+        .entry(key.clone())
+        .or_insert_with(|| RateLimiter::keyed(quota));
+    limiter.check_key(&key).is_ok()
+}
+
+fn unauthorized() -> Response {
+    let mut response = no_store(StatusCode::UNAUTHORIZED.into_response());
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        HeaderValue::from_static("Bearer realm=\"lightfriend-mcp\""),
+    );
+    response
+}
+
+/// Desktop clients send no `Origin`. Browsers do, and only our own frontend
+/// may call the endpoint from one.
+fn allowed_origin(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let frontend =
+        std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+    origin
+        .to_str()
+        .is_ok_and(|origin| origin == frontend.trim_end_matches('/'))
+}
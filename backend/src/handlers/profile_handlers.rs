@@ -135,6 +135,7 @@ pub struct ProfileResponse {
     accountability_enabled: bool,
     accountability_friend_phone: Option<String>,
     accountability_friend_name: Option<String>,
+    sms_gsm7_transliteration: bool,
 }
 use crate::handlers::auth_middleware::AuthUser;
 
@@ -327,6 +328,7 @@ pub async fn get_profile(
                 accountability_enabled: user.accountability_enabled,
                 accountability_friend_phone: user.accountability_friend_phone,
                 accountability_friend_name: user.accountability_friend_name,
+                sms_gsm7_transliteration: user.sms_gsm7_transliteration,
             }))
         }
         None => Err((
@@ -893,6 +895,23 @@ pub async fn patch_profile_field(
                     )
                 })?;
         }
+        "sms_gsm7_transliteration" => {
+            let value = request.value.as_bool().ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({"error": "sms_gsm7_transliteration must be a boolean"})),
+                )
+            })?;
+            state
+                .user_core
+                .update_sms_gsm7_transliteration(user_id, value)
+                .map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": format!("Database error: {}", e)})),
+                    )
+                })?;
+        }
         "accountability_friend_phone" => {
             let value: Option<String> = if request.value.is_null() {
                 None
@@ -1964,6 +1983,7 @@ pub async fn web_chat(
         status: None,
         recharge_threshold_timestamp: None,
         zero_credits_timestamp: None,
+        segments: None,
     });
 
     // Create a mock Twilio payload to reuse existing SMS processing logic
@@ -1977,6 +1997,7 @@ pub async fn web_chat(
         media_url0: None,
         media_content_type0: None,
         message_sid: "".to_string(),
+        extra_params: Default::default(),
     };
 
     // Process using existing SMS handler (skip Twilio, credits handled above)
@@ -2110,6 +2131,7 @@ pub async fn web_chat_stream(
             status: None,
             recharge_threshold_timestamp: None,
             zero_credits_timestamp: None,
+            segments: None,
         });
 
         // Send initial thinking status
@@ -2129,6 +2151,7 @@ pub async fn web_chat_stream(
             media_url0: None,
             media_content_type0: None,
             message_sid: "".to_string(),
+            extra_params: Default::default(),
         };
 
         // Spawn process_sms as a task
@@ -2417,6 +2440,7 @@ pub async fn web_chat_with_image(
         status: None,
         recharge_threshold_timestamp: None,
         zero_credits_timestamp: None,
+        segments: None,
     });
 
     // Create mock Twilio payload with image support
@@ -2443,6 +2467,7 @@ pub async fn web_chat_with_image(
         media_url0: image_data_url,
         media_content_type0: image_content_type,
         message_sid: "".to_string(),
+        extra_params: Default::default(),
     };
 
     // Process using existing SMS handler (skip Twilio, credits handled above)
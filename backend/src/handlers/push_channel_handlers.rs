@@ -0,0 +1,158 @@
+//! The user's UnifiedPush / ntfy endpoint: register it, choose whether
+//! digests and rule notifications go there instead of SMS, send a test and
+//! remove it. See `channels::push_channel` for delivery.
+
+use crate::channels::push_channel;
+use crate::handlers::agent_integration_handlers::{has_active_subscription, now_unix};
+use crate::handlers::auth_middleware::AuthUser;
+use crate::handlers::mcp_handlers::ErrorResponse;
+use crate::models::push_channel_models::{
+    PushEndpointResponse, RegisterPushEndpointRequest, UpdatePushRoutingRequest,
+};
+use crate::repositories::push_endpoint_repository::{
+    PushEndpointRecord, PushEndpointRepository, PushEndpointRepositoryError,
+};
+use crate::{AppState, UserCoreOps};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use std::sync::Arc;
+use tracing::error;
+
+const TEST_MESSAGE: &str = "Lightfriend push notifications are working.";
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn api_error(status: StatusCode, message: &str) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message.to_string(),
+        }),
+    )
+}
+
+fn db_error(e: PushEndpointRepositoryError) -> ApiError {
+    error!("Push endpoint query failed: {}", e);
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+}
+
+fn describe(registration: PushEndpointRecord) -> PushEndpointResponse {
+    PushEndpointResponse {
+        endpoint: registration.endpoint,
+        encrypted: registration.keys.is_some(),
+        digests: registration.push_digests,
+        rules: registration.push_rules,
+    }
+}
+
+/// GET /api/me/push-endpoint - The endpoint, or `null` when none is set.
+pub async fn get_push_endpoint(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Option<PushEndpointResponse>>, ApiError> {
+    let registration = PushEndpointRepository::new(state.pg_pool.clone())
+        .find(auth_user.user_id)
+        .map_err(db_error)?;
+    Ok(Json(registration.map(describe)))
+}
+
+/// PUT /api/me/push-endpoint - Register or replace the endpoint.
+pub async fn register_push_endpoint(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<RegisterPushEndpointRequest>,
+) -> Result<Json<PushEndpointResponse>, ApiError> {
+    if !has_active_subscription(&state, auth_user.user_id) {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Active subscription required",
+        ));
+    }
+    let endpoint = push_channel::resolve_endpoint(&request.endpoint)
+        .await
+        .map_err(|message| api_error(StatusCode::BAD_REQUEST, message))?;
+    let keys = push_channel::parse_keys(request.p256dh.as_deref(), request.auth.as_deref())
+        .map_err(|message| api_error(StatusCode::BAD_REQUEST, message))?;
+    let registration = PushEndpointRepository::new(state.pg_pool.clone())
+        .upsert(
+            auth_user.user_id,
+            endpoint.as_str(),
+            keys.as_ref(),
+            now_unix(),
+        )
+        .map_err(db_error)?;
+    Ok(Json(describe(registration)))
+}
+
+/// PATCH /api/me/push-endpoint - Change which notifications go by push.
+pub async fn update_push_routing(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<UpdatePushRoutingRequest>,
+) -> Result<Json<PushEndpointResponse>, ApiError> {
+    let repository = PushEndpointRepository::new(state.pg_pool.clone());
+    let registration = repository
+        .find(auth_user.user_id)
+        .map_err(db_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No push endpoint"))?;
+    let registration = repository
+        .set_routing(
+            auth_user.user_id,
+            request.digests.unwrap_or(registration.push_digests),
+            request.rules.unwrap_or(registration.push_rules),
+            now_unix(),
+        )
+        .map_err(db_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No push endpoint"))?;
+    Ok(Json(describe(registration)))
+}
+
+/// POST /api/me/push-endpoint/test - Send a test notification. Free, and
+/// never falls back to SMS.
+pub async fn test_push_endpoint(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    let user = state
+        .user_core
+        .find_by_id(auth_user.user_id)
+        .map_err(|e| {
+            error!("Failed to load user {}: {}", auth_user.user_id, e);
+            api_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        })?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "User not found"))?;
+    if PushEndpointRepository::new(state.pg_pool.clone())
+        .find(user.id)
+        .map_err(db_error)?
+        .is_none()
+    {
+        return Err(api_error(StatusCode::NOT_FOUND, "No push endpoint"));
+    }
+    push_channel::send_to_push(&state, &user, TEST_MESSAGE)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Test push for user {} failed: {}", user.id, e);
+            api_error(
+                StatusCode::BAD_GATEWAY,
+                "The push endpoint did not accept the message",
+            )
+        })?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /api/me/push-endpoint - Remove the endpoint; everything goes back
+/// to SMS.
+pub async fn delete_push_endpoint(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    if PushEndpointRepository::new(state.pg_pool.clone())
+        .delete(auth_user.user_id)
+        .map_err(db_error)?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(api_error(StatusCode::NOT_FOUND, "No push endpoint"))
+    }
+}
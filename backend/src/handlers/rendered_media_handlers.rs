@@ -0,0 +1,28 @@
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+
+/// GET /api/media/rendered/{file}
+///
+/// Serves a PNG from `rendering::store` so the SMS provider can fetch it
+/// for an outbound MMS. Public because Twilio and Telnyx fetch without
+/// credentials; the token is an unguessable v4 UUID that expires with the
+/// image.
+pub async fn get_rendered_image(Path(file): Path<String>) -> impl IntoResponse {
+    let Some(token) = file.strip_suffix(".png") else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match crate::rendering::store::get(token) {
+        Some(image) => (
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, "private, no-store"),
+            ],
+            image.png,
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
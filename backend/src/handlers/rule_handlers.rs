@@ -12,8 +12,10 @@ use crate::handlers::auth_middleware::AuthUser;
 use crate::handlers::dashboard_handlers::Contact;
 use crate::models::ontology_models::{NewOntRule, OntRule};
 use crate::proactive::rules::{
-    compute_next_fire_at, evaluate_flow_test, ActionConfig, FlowNode, RuleTestStep, TriggerConfig,
+    compute_next_fire_at, evaluate_flow_test, ActionConfig, FlowContext, FlowNode, RuleTestStep,
+    TriggerConfig,
 };
+use crate::proactive::schedule::{pin_schedule_anchor, preview_fire_times, SchedulePattern};
 use crate::repositories::user_core::UserCoreOps;
 use crate::repositories::user_repository::LogUsageParams;
 use crate::AppState;
@@ -487,45 +489,24 @@ pub async fn create_rule(
         .as_secs() as i32;
 
     // Compute next_fire_at for schedule rules
-    let tz_offset = crate::proactive::utils::user_tz_offset_secs(&state, user_id);
-    let next_fire_at = if req.trigger_type == "schedule" {
-        match trigger.schedule.as_deref() {
-            Some("once") => trigger
-                .at
-                .as_ref()
-                .and_then(|at| crate::proactive::utils::parse_iso_to_timestamp(at, tz_offset)),
-            Some("recurring") => {
-                if let Some(ref pattern) = trigger.pattern {
-                    let user_tz = state
-                        .user_core
-                        .get_user_info(user_id)
-                        .ok()
-                        .and_then(|info| info.timezone)
-                        .unwrap_or_else(|| "UTC".to_string());
-                    compute_next_fire_at(pattern, &user_tz)
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        }
-    } else {
-        None
-    };
+    let (trigger_config, next_fire_at) = prepare_schedule_trigger(
+        &state,
+        user_id,
+        &req.trigger_type,
+        &req.trigger_config,
+        &trigger,
+    )?;
 
     let expires_at = req
         .expires_in_days
         .map(|days| now + (days * 86400.0) as i32);
 
-    // Validate flow_config depth if provided
+    // Validate flow_config structure (depth, delays, loops) if provided
     if let Some(ref fc) = req.flow_config {
         match serde_json::from_str::<crate::proactive::rules::FlowNode>(fc) {
             Ok(node) => {
-                if node.condition_depth() > 3 {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        Json(json!({ "error": "Flow config exceeds max depth of 3 conditions" })),
-                    ));
+                if let Err(msg) = node.validate() {
+                    return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": msg }))));
                 }
             }
             Err(e) => {
@@ -541,7 +522,7 @@ pub async fn create_rule(
         user_id,
         name: req.name,
         trigger_type: req.trigger_type,
-        trigger_config: req.trigger_config,
+        trigger_config,
         logic_type: req.logic_type,
         logic_prompt: req.logic_prompt,
         logic_fetch: req.logic_fetch,
@@ -567,6 +548,100 @@ pub async fn create_rule(
     }
 }
 
+fn user_timezone(state: &AppState, user_id: i32) -> String {
+    state
+        .user_core
+        .get_user_info(user_id)
+        .ok()
+        .and_then(|info| info.timezone)
+        .unwrap_or_else(|| "UTC".to_string())
+}
+
+/// Validate a schedule trigger and compute its first `next_fire_at`.
+///
+/// Recurring patterns that are invalid, or that can never fire again, are
+/// rejected with a 400 so the builder can show the parser's message. RRULEs
+/// whose phase depends on an anchor (INTERVAL > 1 or COUNT) get a DTSTART
+/// pinned to "now", so the returned trigger_config may differ from the input.
+fn prepare_schedule_trigger(
+    state: &Arc<AppState>,
+    user_id: i32,
+    trigger_type: &str,
+    trigger_config: &str,
+    trigger: &TriggerConfig,
+) -> Result<(String, Option<i32>), (StatusCode, Json<serde_json::Value>)> {
+    if trigger_type != "schedule" {
+        return Ok((trigger_config.to_string(), None));
+    }
+    match trigger.schedule.as_deref() {
+        Some("once") => {
+            let tz_offset = crate::proactive::utils::user_tz_offset_secs(state, user_id);
+            let next_fire_at = trigger
+                .at
+                .as_ref()
+                .and_then(|at| crate::proactive::utils::parse_iso_to_timestamp(at, tz_offset));
+            Ok((trigger_config.to_string(), next_fire_at))
+        }
+        Some("recurring") => {
+            let Some(ref pattern) = trigger.pattern else {
+                return Ok((trigger_config.to_string(), None));
+            };
+            SchedulePattern::parse(pattern).map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("Invalid schedule pattern: {}", e) })),
+                )
+            })?;
+            let user_tz = user_timezone(state, user_id);
+            let pinned = pin_schedule_anchor(pattern, &user_tz, chrono::Utc::now());
+            let next_fire_at = compute_next_fire_at(&pinned, &user_tz).ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": "Schedule pattern has no upcoming fire times" })),
+                )
+            })?;
+            let trigger_config = if pinned == *pattern {
+                trigger_config.to_string()
+            } else {
+                let mut value: serde_json::Value =
+                    serde_json::from_str(trigger_config).unwrap_or_else(|_| json!({}));
+                value["pattern"] = json!(pinned);
+                value.to_string()
+            };
+            Ok((trigger_config, Some(next_fire_at)))
+        }
+        _ => Ok((trigger_config.to_string(), None)),
+    }
+}
+
+fn default_preview_count() -> usize {
+    5
+}
+
+#[derive(Deserialize)]
+pub struct SchedulePreviewRequest {
+    pub pattern: String,
+    #[serde(default = "default_preview_count")]
+    pub count: usize,
+}
+
+/// POST /api/rules/schedule-preview - validate a recurring pattern and list
+/// its next fire times in the user's timezone, for the rule builder.
+pub async fn preview_schedule(
+    State(state): State<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(req): Json<SchedulePreviewRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let user_tz = user_timezone(&state, auth_user.user_id);
+    let pinned = pin_schedule_anchor(&req.pattern, &user_tz, chrono::Utc::now());
+    let next_fire_times = preview_fire_times(&pinned, &user_tz, req.count.clamp(1, 20))
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))))?;
+    Ok(Json(json!({
+        "timezone": user_tz,
+        "next_fire_times": next_fire_times,
+    })))
+}
+
 pub async fn get_rule(
     State(state): State<Arc<AppState>>,
     auth_user: AuthUser,
@@ -639,41 +714,20 @@ pub async fn update_rule(
     })?;
 
     // Recompute next_fire_at for schedule rules
-    let tz_offset = crate::proactive::utils::user_tz_offset_secs(&state, user_id);
-    let next_fire_at = if req.trigger_type == "schedule" {
-        match trigger.schedule.as_deref() {
-            Some("once") => trigger
-                .at
-                .as_ref()
-                .and_then(|at| crate::proactive::utils::parse_iso_to_timestamp(at, tz_offset)),
-            Some("recurring") => {
-                if let Some(ref pattern) = trigger.pattern {
-                    let user_tz = state
-                        .user_core
-                        .get_user_info(user_id)
-                        .ok()
-                        .and_then(|info| info.timezone)
-                        .unwrap_or_else(|| "UTC".to_string());
-                    compute_next_fire_at(pattern, &user_tz)
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        }
-    } else {
-        None
-    };
+    let (trigger_config, next_fire_at) = prepare_schedule_trigger(
+        &state,
+        user_id,
+        &req.trigger_type,
+        &req.trigger_config,
+        &trigger,
+    )?;
 
-    // Validate flow_config depth if provided
+    // Validate flow_config structure (depth, delays, loops) if provided
     if let Some(ref fc) = req.flow_config {
         match serde_json::from_str::<crate::proactive::rules::FlowNode>(fc) {
             Ok(node) => {
-                if node.condition_depth() > 3 {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        Json(json!({ "error": "Flow config exceeds max depth of 3 conditions" })),
-                    ));
+                if let Err(msg) = node.validate() {
+                    return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": msg }))));
                 }
             }
             Err(e) => {
@@ -690,7 +744,7 @@ pub async fn update_rule(
         rule_id,
         &req.name,
         &req.trigger_type,
-        &req.trigger_config,
+        &trigger_config,
         &req.logic_type,
         req.logic_prompt.as_deref(),
         req.logic_fetch.as_deref(),
@@ -699,7 +753,14 @@ pub async fn update_rule(
         next_fire_at,
         req.flow_config.as_deref(),
     ) {
-        Ok(rule) => Ok(Json(serde_json::to_value(rule).unwrap_or_default())),
+        Ok(rule) => {
+            // Suspended Delay continuations were captured from the old flow
+            // and may no longer match it, so drop them.
+            if let Err(e) = state.ontology_repository.cancel_rule_continuations(rule_id) {
+                tracing::warn!("Failed to cancel continuations for rule {}: {}", rule_id, e);
+            }
+            Ok(Json(serde_json::to_value(rule).unwrap_or_default()))
+        }
         Err(e) => {
             tracing::error!("Failed to update rule {}: {}", rule_id, e);
             Err((
@@ -764,6 +825,11 @@ pub struct StartRuleTestRequest {
     pub sender: String,
     #[serde(default)]
     pub rule_name: String,
+    /// Optional snapshot fields so structured conditions can be tested.
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub is_group: Option<bool>,
 }
 
 fn default_sender() -> String {
@@ -775,6 +841,8 @@ pub struct PendingRuleTest {
     pub message: String,
     pub sender: String,
     pub rule_name: String,
+    pub platform: Option<String>,
+    pub is_group: Option<bool>,
     pub user_id: i32,
     pub created_at: std::time::Instant,
 }
@@ -902,6 +970,7 @@ pub async fn start_rule_test(
         status: None,
         recharge_threshold_timestamp: None,
         zero_credits_timestamp: None,
+        segments: None,
     });
 
     let test_id = uuid::Uuid::new_v4().to_string();
@@ -912,6 +981,8 @@ pub async fn start_rule_test(
             message: req.message,
             sender: req.sender,
             rule_name: req.rule_name,
+            platform: req.platform,
+            is_group: req.is_group,
             user_id: auth_user.user_id,
             created_at: std::time::Instant::now(),
         },
@@ -985,6 +1056,15 @@ pub async fn test_rule_stream(
         };
 
         let trigger_context = format!("Message from {}: {}", pending.sender, pending.message);
+        let mut snapshot = json!({
+            "sender_name": pending.sender,
+            "content": pending.message,
+            "is_group": pending.is_group.unwrap_or(false),
+        });
+        if let Some(ref platform) = pending.platform {
+            snapshot["platform"] = json!(platform);
+        }
+        let ctx = FlowContext::new(&trigger_context, Some(&snapshot));
 
         // Build a synthetic OntRule
         let now = std::time::SystemTime::now()
@@ -1015,7 +1095,7 @@ pub async fn test_rule_stream(
         let (tx, mut rx) = tokio::sync::mpsc::channel::<RuleTestStep>(32);
         let state_clone = Arc::clone(&state);
         tokio::spawn(async move {
-            evaluate_flow_test(&state_clone, &rule, &trigger_context, &root, &tx).await;
+            evaluate_flow_test(&state_clone, &rule, &root, &ctx, &tx).await;
             let _ = tx.send(RuleTestStep::Complete).await;
         });
 
@@ -1018,14 +1018,14 @@ pub async fn disconnect_signal(
         .await;
 
         // Check for remaining active bridges and cleanup store if none left.
-        // Signal's status is "cleaning_up" here, so has_active_bridges only
-        // returns true if WhatsApp/Telegram are still connected.
-        let has_active_bridges = state_clone
+        // Signal's status is "cleaning_up" here, so matrix_account_in_use only
+        // returns true if WhatsApp/Telegram or the Matrix DM channel remain.
+        let account_in_use = state_clone
             .user_repository
-            .has_active_bridges(user_id)
+            .matrix_account_in_use(user_id)
             .unwrap_or(false);
 
-        if !has_active_bridges {
+        if !account_in_use {
             // Clear user store if no other bridges
             if let Some(user_id_matrix) = client.user_id() {
                 let username = user_id_matrix.localpart().to_string();
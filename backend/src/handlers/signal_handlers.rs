@@ -57,6 +57,7 @@ pub async fn send_message(
         request.image_url,
         None,
         None,
+        None,
     )
     .await
     {
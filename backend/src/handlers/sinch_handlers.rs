@@ -180,6 +180,7 @@ pub async fn sinch_inbound(
         media_url0: None,
         media_content_type0: None,
         message_sid: format!("sinch_{}", payload.id),
+        extra_params: Default::default(),
     };
 
     let state_clone = state.clone();
@@ -257,6 +258,14 @@ pub async fn sinch_status(
         );
     }
 
+    crate::channels::health::handle_delivery_status(
+        &state,
+        "sinch",
+        &payload.batch_id,
+        mapped_status,
+        to_phone.as_deref(),
+    );
+
     // Credit deduction on delivered. Sinch DLRs don't carry per-
     // message price (Sinch bills off-platform), so we use a configured
     // flat rate. Same margin treatment as Twilio prices. Only deduct
@@ -1857,12 +1857,12 @@ pub async fn disconnect_telegram(
         .await;
 
         // Check for remaining active bridges and cleanup if none left
-        let has_active_bridges = state_clone
+        let account_in_use = state_clone
             .user_repository
-            .has_active_bridges(user_id)
+            .matrix_account_in_use(user_id)
             .unwrap_or(false);
 
-        if !has_active_bridges {
+        if !account_in_use {
             // Clear user store if no other bridges
             if let Some(user_id_matrix) = client.user_id() {
                 let username = user_id_matrix.localpart().to_string();
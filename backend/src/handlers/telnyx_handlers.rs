@@ -203,6 +203,19 @@ pub async fn telnyx_inbound(
         media_url0: first_media.map(|m| m.url.clone()),
         media_content_type0: first_media.and_then(|m| m.content_type.clone()),
         message_sid: format!("telnyx_{}", payload.id),
+        extra_params: payload
+            .media
+            .iter()
+            .enumerate()
+            .skip(1)
+            .flat_map(|(i, m)| {
+                let mut params = vec![(format!("MediaUrl{}", i), m.url.clone())];
+                if let Some(content_type) = &m.content_type {
+                    params.push((format!("MediaContentType{}", i), content_type.clone()));
+                }
+                params
+            })
+            .collect(),
     };
 
     let state_clone = state.clone();
@@ -278,6 +291,14 @@ pub async fn telnyx_status(
         );
     }
 
+    crate::channels::health::handle_delivery_status(
+        &state,
+        "telnyx",
+        &payload.id,
+        mapped_status,
+        Some(to_phone.as_str()),
+    );
+
     // Credit deduction only on the final delivered status. Telnyx fires
     // both message.sent (intermediate, when carrier accepts) and
     // message.finalized (terminal). Charging on either would double-count
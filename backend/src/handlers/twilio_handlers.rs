@@ -143,7 +143,21 @@ pub async fn twilio_status_callback(
         }
     }
 
-    if should_attempt_telnyx_delivery_fallback(&payload.MessageStatus, payload.ErrorCode.as_deref())
+    // Sends the router still remembers are re-sent through the rest of
+    // their provider order; the Telnyx replay below covers the others.
+    let retried = crate::channels::health::handle_delivery_status(
+        &state,
+        "twilio",
+        &payload.MessageSid,
+        &payload.MessageStatus,
+        payload.To.as_deref(),
+    );
+
+    if !retried
+        && should_attempt_telnyx_delivery_fallback(
+            &payload.MessageStatus,
+            payload.ErrorCode.as_deref(),
+        )
     {
         let state_for_fallback = state.clone();
         let repository_for_fallback = repository.clone();
@@ -480,6 +494,14 @@ impl crate::api::twilio_client::TwilioClient for NoOpTwilioClient {
         Ok(())
     }
 
+    async fn fetch_message_media(
+        &self,
+        _credentials: &crate::api::twilio_client::TwilioCredentials,
+        _media_url: &str,
+    ) -> Result<Vec<u8>, crate::api::twilio_client::TwilioClientError> {
+        Ok(Vec::new())
+    }
+
     async fn fetch_message_price(
         &self,
         _credentials: &crate::api::twilio_client::TwilioCredentials,
@@ -348,6 +348,7 @@ pub async fn webhook_sms(
                     status: Some("suppressed".to_string()),
                     recharge_threshold_timestamp: None,
                     zero_credits_timestamp: None,
+                    segments: None,
                 });
         return Ok(no_store(
             Json(WebhookSmsResponse {
@@ -444,6 +445,9 @@ pub async fn webhook_sms(
                     status: Some("accepted".to_string()),
                     recharge_threshold_timestamp: None,
                     zero_credits_timestamp: None,
+                    segments: Some(crate::utils::sms_encoding::outbound_sms_segments(
+                        &user, &outbound,
+                    )),
                 },
             ) {
                 tracing::error!("Failed to log webhook_sms usage: {}", e);
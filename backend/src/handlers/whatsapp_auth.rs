@@ -697,8 +697,8 @@ async fn cleanup_whatsapp_if_needed(
 
     // Conditional store clear (only if no other bridges). Store clear needs the
     // client's username, so we read it before the stop call tears the client down.
-    let has_active_bridges = state.user_repository.has_active_bridges(user_id)?;
-    if !has_active_bridges {
+    let account_in_use = state.user_repository.matrix_account_in_use(user_id)?;
+    if !account_in_use {
         let username = client
             .user_id()
             .ok_or(anyhow!("User ID unavailable"))?
@@ -1402,12 +1402,12 @@ pub async fn disconnect_whatsapp(
         // Check for remaining active bridges and cleanup if none left.
         // Capture username before the stop call tears the client down, since
         // clear_user_store needs the Matrix user's localpart.
-        let has_active_bridges = state_clone
+        let account_in_use = state_clone
             .user_repository
-            .has_active_bridges(user_id)
+            .matrix_account_in_use(user_id)
             .unwrap_or(false);
 
-        if !has_active_bridges {
+        if !account_in_use {
             if let Some(user_id_matrix) = client.user_id() {
                 let username = user_id_matrix.localpart().to_string();
                 if let Err(e) = clear_user_store(&username).await {
@@ -67,6 +67,7 @@ pub async fn send_message(
         request.image_url,
         None,
         None,
+        None,
     )
     .await
     {
@@ -121,9 +121,11 @@ pub async fn check_whatsapp_native_activity_reminders(state: &Arc<AppState>) {
 /// (in a background task) and then on a 60s cron. Does three things under
 /// a single top-level mutex so ticks can't overlap and stampede Tuwunel:
 ///
-/// 1. Tear down any cell whose user no longer has a connected bridge.
+/// 1. Tear down any cell whose user no longer has a connected bridge or a
+///    Matrix DM channel.
 /// 2. Walk the DB-truth list of active-bridge users, ordered by most-recent
-///    bridge activity first (so warm users come up before cold ones).
+///    bridge activity first (so warm users come up before cold ones),
+///    followed by the remaining Matrix DM channel users.
 /// 3. For each, acquire a permit from `RECONCILE_CONCURRENCY` and call
 ///    `ensure_matrix_user_running`. Live users fast-path under the
 ///    per-user mutex; dead/zombie/missing users cold-rebuild.
@@ -146,13 +148,33 @@ pub async fn reconcile_matrix_users(state: Arc<AppState>) {
         }
     };
 
-    let active_users = match state.user_repository.get_active_bridge_users_prioritized() {
+    let mut active_users = match state.user_repository.get_active_bridge_users_prioritized() {
         Ok(u) => u,
         Err(e) => {
             error!("Reconciler: failed to fetch active bridge users: {}", e);
             return;
         }
     };
+    // Matrix DM channels need a live client to receive the user's replies,
+    // bridges or not. They go after bridge users in priority.
+    let dm_users =
+        crate::repositories::matrix_dm_channel_repository::MatrixDmChannelRepository::new(
+            state.pg_pool.clone(),
+        )
+        .user_ids_with_rooms();
+    match dm_users {
+        Ok(dm_users) => {
+            for uid in dm_users {
+                if !active_users.contains(&uid) {
+                    active_users.push(uid);
+                }
+            }
+        }
+        Err(e) => {
+            error!("Reconciler: failed to fetch Matrix DM channel users: {}", e);
+            return;
+        }
+    }
     let active_set: std::collections::HashSet<i32> = active_users.iter().copied().collect();
 
     // 1. Tear down stale cells (users with no remaining connected bridges).
@@ -333,6 +355,9 @@ pub async fn check_all_bridges_health(
 ///   either way we'd rather keep the bridge and retry next tick than
 ///   wrongly tear down a working connection.
 ///
+/// Networks from `bridgev2_provisioning::NETWORKS` report the same states
+/// through the provisioning API's `whoami`, classified the same way.
+///
 /// For telegram (still on the older mautrix-telegram v0.15.3, pre-bridgev2)
 /// we keep the previous behaviour: check Matrix client + room enumeration.
 /// There's no list-logins command on that bridge.
@@ -380,6 +405,13 @@ async fn probe_bridge_login_health(
         };
     }
 
+    // Networks onboarded through the provisioning API have no management
+    // room; the bridge reports login state over HTTP instead.
+    if let Some(network) = crate::utils::bridgev2_provisioning::network(&bridge.bridge_type) {
+        return crate::utils::bridgev2_provisioning::probe_login_health(state, user_id, network)
+            .await;
+    }
+
     // Bridgev2 path: need the management room to talk to the bot. We stored
     // it on the bridge record when the connection completed.
     let (room_id, bot_user_id, cmd_prefix) = match bridge.bridge_type.as_str() {
@@ -614,6 +646,16 @@ pub async fn start_scheduler(state: Arc<AppState>) {
         });
     }
 
+    // With more than one key configured a rotation is under way; resume
+    // re-encrypting from the saved cursors.
+    match crate::utils::encryption::KeyRing::from_env() {
+        Ok(ring) if ring.key_ids().len() > 1 => {
+            crate::services::key_rotation::start_background(state.pg_pool.clone());
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!("Encryption key ring is invalid: {}", e),
+    }
+
     // Initialize smartphone-free days metric if it doesn't exist
     initialize_smartphone_free_days_metric(Arc::clone(&state)).await;
 
@@ -1018,6 +1060,21 @@ pub async fn start_scheduler(state: Arc<AppState>) {
                 }
                 Err(e) => error!("Failed to expire stale ongoing calls: {}", e),
             }
+
+            // Delete rule continuations finished (or abandoned while
+            // running) more than 7 days ago
+            let continuation_cutoff = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i32
+                - (7 * 24 * 60 * 60);
+            match state
+                .ontology_repository
+                .prune_rule_continuations(continuation_cutoff)
+            {
+                Ok(count) => debug!("Pruned {} old rule continuations", count),
+                Err(e) => error!("Failed to prune rule continuations: {}", e),
+            }
         })
     })
     .expect("Failed to create task cleanup job");
@@ -1308,12 +1365,27 @@ pub async fn start_scheduler(state: Arc<AppState>) {
                             .ok()
                             .and_then(|info| info.timezone)
                             .unwrap_or_else(|| "UTC".to_string());
-                        if let Some(next) =
-                            crate::proactive::rules::compute_next_fire_at(pattern, &user_tz)
-                        {
-                            let _ = state
-                                .ontology_repository
-                                .update_rule_next_fire_at(rule_clone.id, next);
+                        match crate::proactive::rules::compute_next_fire_at(pattern, &user_tz) {
+                            Some(next) => {
+                                let _ = state
+                                    .ontology_repository
+                                    .update_rule_next_fire_at(rule_clone.id, next);
+                            }
+                            None => {
+                                // COUNT/UNTIL exhausted (or an unparseable legacy
+                                // pattern): park the rule so it doesn't re-fire
+                                // every minute. This run is its last.
+                                let _ = state
+                                    .ontology_repository
+                                    .update_rule_next_fire_at(rule_clone.id, i32::MAX);
+                                if crate::proactive::schedule::SchedulePattern::parse(pattern)
+                                    .is_ok()
+                                {
+                                    let _ = state
+                                        .ontology_repository
+                                        .update_rule_status(rule_clone.id, "completed");
+                                }
+                            }
                         }
                     }
                 }
@@ -1333,6 +1405,23 @@ pub async fn start_scheduler(state: Arc<AppState>) {
                 });
             }
 
+            // Resume flows suspended by a delay node. Each row is claimed
+            // once; a resume that dies midway is not retried.
+            match state.ontology_repository.claim_due_rule_continuations(now) {
+                Ok(continuations) => {
+                    for continuation in continuations {
+                        let state = state.clone();
+                        tokio::spawn(async move {
+                            crate::proactive::rules::resume_continuation(&state, continuation)
+                                .await;
+                        });
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to claim rule continuations: {}", e);
+                }
+            }
+
             // Also expire old rules
             let _ = state.ontology_repository.expire_old_rules(now);
         })
@@ -1772,6 +1861,21 @@ pub async fn build_digest_for_user(
         .ontology_repository
         .get_events_due_on_local_day(user_id, local_day_start, local_day_end)
         .unwrap_or_default();
+    // Events from connected CalDAV calendars join the same "Today" line.
+    let calendar_events = match (
+        chrono::DateTime::from_timestamp(local_day_start as i64, 0),
+        chrono::DateTime::from_timestamp(local_day_end as i64, 0),
+    ) {
+        (Some(start), Some(end)) => {
+            crate::services::calendar_service::events_between(state, user_id, start, end)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Digest calendar fetch failed for user {}: {}", user_id, e);
+                    Vec::new()
+                })
+        }
+        _ => Vec::new(),
+    };
 
     // -------- Section 2: Important ("now" urgency) --------
     // Filtered out when the user has push notifications ON, because in that
@@ -1948,15 +2052,32 @@ pub async fn build_digest_for_user(
     let mut digest_parts: Vec<String> = Vec::new();
 
     // Today's events: inline list with times. Cap at 5 (overflow → "+N more").
-    if !today_events.is_empty() {
-        let total_today = today_events.len();
-        let parts: Vec<String> = today_events
+    // Tracked obligations and calendar events are merged by time; untimed and
+    // all-day entries go first, and an event that shows up in both is listed once.
+    let mut today_entries: Vec<(Option<i32>, String)> = today_events
+        .iter()
+        .map(|event| (event.due_at, event.description.clone()))
+        .chain(calendar_events.iter().map(|entry| {
+            let at = if entry.event.start.is_all_day() {
+                None
+            } else {
+                Some(entry.starts_at.timestamp() as i32)
+            };
+            (at, entry.event.summary.clone())
+        }))
+        .collect();
+    today_entries.sort_by_key(|(at, _)| *at);
+    let mut seen_entries = std::collections::HashSet::new();
+    today_entries.retain(|(at, text)| seen_entries.insert((*at, text.trim().to_lowercase())));
+    if !today_entries.is_empty() {
+        let total_today = today_entries.len();
+        let parts: Vec<String> = today_entries
             .iter()
             .take(5)
-            .map(|event| {
-                let description = truncate_digest_piece(&event.description, DIGEST_INLINE_ITEM_CAP);
-                let time_str = if let Some(due) = event.due_at {
-                    let local_due = due as i64 + tz_offset_secs as i64;
+            .map(|(at, text)| {
+                let description = truncate_digest_piece(text, DIGEST_INLINE_ITEM_CAP);
+                let time_str = if let Some(due) = at {
+                    let local_due = *due as i64 + tz_offset_secs as i64;
                     let h = ((local_due % 86400 + 86400) % 86400 / 3600) as i32;
                     let m = (((local_due % 86400 + 86400) % 86400) % 3600 / 60) as i32;
                     format!("{:02}:{:02} {}", h, m, description)
@@ -9,17 +9,24 @@ pub mod handlers {
     pub mod auth_middleware;
     pub mod billing_handlers;
     pub mod bridge_auth_common;
+    pub mod bridgev2_auth;
+    pub mod caldav_handlers;
     pub mod commitment_handlers;
     pub mod dashboard_handlers;
+    pub mod email_channel_handlers;
     pub mod health_handlers;
     pub mod imap_auth;
     pub mod imap_handlers;
     pub mod light_tool_auth;
     pub mod light_tool_handlers;
+    pub mod matrix_channel_handlers;
     pub mod mcp_handlers;
+    pub mod mcp_server_handlers;
     pub mod person_handlers;
     pub mod pricing_handlers;
     pub mod profile_handlers;
+    pub mod push_channel_handlers;
+    pub mod rendered_media_handlers;
     pub mod rule_handlers;
 
     pub mod maintenance_handlers;
@@ -44,9 +51,15 @@ pub mod handlers {
     pub mod youtube_auth;
 }
 pub mod channels {
+    pub mod email_channel;
+    pub mod health;
+    pub mod matrix_channel;
+    pub mod push_channel;
     pub mod router;
     pub mod sinch_channel;
+    pub mod sinch_voice_channel;
     pub mod telnyx_channel;
+    pub mod telnyx_voice_channel;
     pub mod traits;
     pub mod twilio_channel;
 }
@@ -54,6 +67,7 @@ pub mod utils {
     pub mod bridge;
     pub mod bridge_contacts;
     pub mod bridge_responses;
+    pub mod bridgev2_provisioning;
     pub mod country;
     pub mod datafast_bot_tracking;
     pub mod disconnected_bridge_cleanup;
@@ -68,6 +82,7 @@ pub mod utils {
     pub mod plan_features;
     pub mod resend_contacts;
     pub mod seo_headers;
+    pub mod sms_encoding;
     pub mod sms_sanitizer;
     pub mod stripe_webhook;
     pub mod tesla_keys;
@@ -82,7 +97,10 @@ pub mod utils {
 pub mod proactive {
     pub mod alert_feedback;
     pub mod commitment_replies;
+    pub mod conditions;
+    pub mod reply_context;
     pub mod rules;
+    pub mod schedule;
     pub mod signal_extraction;
     pub mod system_behaviors;
     pub mod utils;
@@ -98,6 +116,7 @@ pub mod tool_call_utils {
 }
 pub mod cli;
 pub mod api {
+    pub mod caldav;
     pub mod matrix_client;
     pub mod sinch_utils;
     pub mod telnyx_utils;
@@ -110,12 +129,23 @@ pub mod api {
     pub mod twilio_sms;
     pub mod twilio_utils;
     pub mod voice_pipeline;
+    pub mod voice_stream_bridge;
 }
 pub mod agent_core;
 pub mod context;
+pub mod rendering {
+    pub mod canvas;
+    pub mod chart;
+    pub mod font;
+    pub mod map;
+    pub mod qr;
+    pub mod store;
+}
 pub mod tools {
     pub mod alerts;
+    pub mod calendar;
     pub mod email;
+    pub mod media;
     pub mod messaging;
     pub mod ontology;
     pub mod registry;
@@ -127,10 +157,14 @@ pub mod tools {
 }
 pub mod models {
     pub mod agent_integration_models;
+    pub mod caldav_models;
     pub mod commitment_models;
+    pub mod email_channel_models;
     pub mod light_tool_models;
+    pub mod matrix_channel_models;
     pub mod mcp_models;
     pub mod ontology_models;
+    pub mod push_channel_models;
     pub mod user_models;
 }
 pub mod repositories {
@@ -140,13 +174,17 @@ pub mod repositories {
     pub mod billing_repository;
     pub mod bridge_login_repository;
     pub mod byot_repository;
+    pub mod caldav_repository;
     pub mod commitment_repository;
+    pub mod email_channel_repository;
     pub mod light_tool_devices_repository;
     pub mod light_tool_pairing_repository;
     pub mod light_tool_push_outbox_repository;
     pub mod light_tool_push_repository;
     pub mod light_tool_runs_repository;
     pub mod llm_usage_repository;
+    pub mod matrix_dm_channel_repository;
+    pub mod mcp_access_token_repository;
     pub mod mcp_repository;
     pub mod metrics_repository;
     pub mod mock_signup_repository;
@@ -154,6 +192,8 @@ pub mod repositories {
     pub mod ontology_repository;
     pub mod pending_reply_watches_repository;
     pub mod provider_routes_repository;
+    pub mod push_endpoint_repository;
+    pub mod reply_contexts_repository;
     pub mod signup_repository;
     pub mod signup_repository_impl;
     pub mod telegram_bridge_repository;
@@ -165,14 +205,18 @@ pub mod repositories {
     pub mod user_core;
     pub mod user_repository;
     pub mod user_subscriptions;
+    pub mod voice_routes_repository;
     pub mod webauthn_repository;
     pub mod webhook_tokens_repository;
     pub mod whatsapp_bridge_repository;
 }
 pub mod services {
     pub mod byot_setup;
+    pub mod calendar_service;
     pub mod country_service;
     pub mod data_purge;
+    pub mod inbound_media;
+    pub mod key_rotation;
     pub mod light_tool_agent_responder;
     pub mod light_tool_bootstrap;
     pub mod light_tool_identity;
@@ -184,12 +228,17 @@ pub mod services {
     pub mod light_tool_run_supervisor;
     pub mod light_tool_trial;
     pub mod mcp_client;
+    pub mod mcp_oauth;
+    pub mod mcp_server;
     pub mod metrics_service;
     pub mod metronome_billing;
+    pub mod outgoing_media;
+    pub mod shared_location;
     pub mod signup_service;
     pub mod twilio_message_service;
     pub mod twilio_status_service;
     pub mod usage_pricing;
+    pub mod voice_notes;
 }
 pub mod pg_models;
 pub mod pg_schema;
@@ -234,6 +283,7 @@ pub use repositories::totp_repository::TotpRepository;
 pub use repositories::tuwunel_cleanup_repository::TuwunelCleanupRepository;
 pub use repositories::user_core::{UserCore, UserCoreOps};
 pub use repositories::user_repository::UserRepository;
+pub use repositories::voice_routes_repository::VoiceRoutesRepository;
 pub use repositories::webauthn_repository::WebauthnRepository;
 pub use repositories::webhook_tokens_repository::WebhookTokensRepository;
 pub use repositories::whatsapp_bridge_repository::{WhatsAppBridgeRepository, WhatsAppContact};
@@ -342,6 +392,8 @@ pub struct AppState {
     pub pending_reply_watches_repository: Arc<PendingReplyWatchesRepository>,
     pub webhook_tokens_repository: Arc<WebhookTokensRepository>,
     pub pending_totp_logins: DashMap<String, (i32, i64)>, // (totp_token, (user_id, expiry_timestamp))
+    /// One-time Telnyx/Sinch media stream tickets, shared with the voice channels.
+    pub voice_stream_tickets: crate::api::voice_stream_bridge::StreamTickets,
     pub pending_password_resets: DashMap<String, (i32, i64)>, // (reset_token, (user_id, expiry_timestamp))
     pub session_to_token: DashMap<String, String>, // stripe_session_id -> magic_token (temporary, for redirect flow)
     pub totp_verify_limiter:
@@ -360,6 +412,9 @@ pub struct AppState {
     /// None when TELEGRAM_BRIDGE_DATABASE_URL is unset (e.g. dev environments).
     pub telegram_bridge_repository: Option<Arc<TelegramBridgeRepository>>,
     pub ontology_registry: ontology::registry::OntologyRegistry,
+    /// Offline place names for shared locations, loaded at startup. None
+    /// when GAZETTEER_PATH is unset or unreadable.
+    pub gazetteer: Option<Arc<services::shared_location::Gazetteer>>,
     pub tool_registry: tools::registry::ToolRegistry,
     pub pending_rule_tests: Arc<DashMap<String, handlers::rule_handlers::PendingRuleTest>>,
     pub maintenance_mode: Arc<AtomicBool>,
@@ -414,12 +469,17 @@ pub fn build_tool_registry() -> tools::registry::ToolRegistry {
     // Weather
     registry.register(Arc::new(tools::weather::WeatherHandler));
 
+    // Rendered images (QR codes, maps, charts) sent as MMS
+    registry.register(Arc::new(tools::media::SendImageHandler));
+
     // Email tools
     registry.register(Arc::new(tools::email::SendEmailHandler));
     registry.register(Arc::new(tools::email::RespondEmailHandler));
 
     // Messaging tools
     registry.register(Arc::new(tools::messaging::SendMessageHandler));
+    registry.register(Arc::new(tools::messaging::ReactMessageHandler));
+    registry.register(Arc::new(tools::messaging::EditMessageHandler));
     registry.register(Arc::new(tools::messaging::WaitForReplyHandler));
     registry.register(Arc::new(tools::alerts::ManageAlertSuppressionHandler));
 
@@ -428,6 +488,9 @@ pub fn build_tool_registry() -> tools::registry::ToolRegistry {
     registry.register(Arc::new(tools::rules::CreateEventHandler));
     registry.register(Arc::new(tools::rules::UpdateEventHandler));
 
+    // Calendar (CalDAV)
+    registry.register(Arc::new(tools::calendar::CalendarHandler));
+
     // Tesla tools
     registry.register(Arc::new(tools::tesla::TeslaControlHandler));
 
@@ -35,10 +35,10 @@ use backend::{
 };
 use handlers::{
     admin_handlers, attestation_handlers, auth_handlers, billing_handlers, bridge_auth_common,
-    commitment_handlers, dashboard_handlers, imap_auth, imap_handlers, person_handlers,
-    profile_handlers, rule_handlers, self_host_handlers, signal_auth, signal_handlers,
-    stripe_handlers, telegram_auth, telegram_handlers, tesla_auth, trust_chain_handlers,
-    twilio_handlers, whatsapp_auth, whatsapp_handlers, youtube, youtube_auth,
+    bridgev2_auth, commitment_handlers, dashboard_handlers, imap_auth, imap_handlers,
+    person_handlers, profile_handlers, rule_handlers, self_host_handlers, signal_auth,
+    signal_handlers, stripe_handlers, telegram_auth, telegram_handlers, tesla_auth,
+    trust_chain_handlers, twilio_handlers, whatsapp_auth, whatsapp_handlers, youtube, youtube_auth,
 };
 
 async fn health_check() -> &'static str {
@@ -122,7 +122,6 @@ pub fn validate_env() {
         "JWT_SECRET_KEY",
         "JWT_REFRESH_KEY",
         "PG_DATABASE_URL",
-        "ENCRYPTION_KEY",
         "MATRIX_SHARED_SECRET",
     ];
 
@@ -130,6 +129,12 @@ pub fn validate_env() {
         std::env::var(var).unwrap_or_else(|_| panic!("{} must be set", var));
     }
 
+    // ENCRYPTION_KEYS, ENCRYPTION_KEY or both; the legacy key can be dropped
+    // once key rotation has moved everything off id 0.
+    if let Err(e) = backend::utils::encryption::KeyRing::from_env() {
+        panic!("ENCRYPTION_KEYS or ENCRYPTION_KEY must be set: {}", e);
+    }
+
     // Production-only validation for live application features
     let environment = std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
 
@@ -502,10 +507,14 @@ async fn main() {
     // (wraps the existing TwilioMessageService); Telnyx and Sinch only register
     // if their env vars are set, so behavior reverts to Twilio-only when those
     // vars are absent — no code change required to flip providers.
-    let mut router = backend::channels::router::ChannelRouter::new();
-    router.register(Arc::new(
-        backend::channels::twilio_channel::TwilioChannel::new(twilio_message_service.clone()),
+    let mut router = backend::channels::router::ChannelRouter::with_health_config(
+        backend::channels::health::HealthConfig::from_env(),
+    );
+    let twilio_channel = Arc::new(backend::channels::twilio_channel::TwilioChannel::new(
+        twilio_message_service.clone(),
     ));
+    router.register(twilio_channel.clone());
+    router.register_voice(twilio_channel);
     if let Some(telnyx) = backend::channels::telnyx_channel::TelnyxChannel::from_env() {
         tracing::info!("Registered Telnyx channel for outbound SMS");
         router.register(Arc::new(telnyx));
@@ -514,6 +523,19 @@ async fn main() {
         tracing::info!("Registered Sinch channel for outbound SMS");
         router.register(Arc::new(sinch));
     }
+    let voice_stream_tickets = backend::api::voice_stream_bridge::StreamTickets::from_env();
+    if let Some(telnyx) = backend::channels::telnyx_voice_channel::TelnyxVoiceChannel::from_env(
+        voice_stream_tickets.clone(),
+    ) {
+        tracing::info!("Registered Telnyx channel for outbound calls");
+        router.register_voice(Arc::new(telnyx));
+    }
+    if let Some(sinch) = backend::channels::sinch_voice_channel::SinchVoiceChannel::from_env(
+        voice_stream_tickets.clone(),
+    ) {
+        tracing::info!("Registered Sinch channel for outbound calls");
+        router.register_voice(Arc::new(sinch));
+    }
     if std::env::var("SINCH_CALLBACK_SECRET")
         .ok()
         .is_some_and(|s| !s.is_empty())
@@ -526,6 +548,25 @@ async fn main() {
     {
         tracing::info!("Telnyx inbound + status webhooks enabled (TELNYX_PUBLIC_KEY set)");
     }
+    // Push needs no provider account: users register their own UnifiedPush
+    // or ntfy endpoint, so the channel is always available.
+    match backend::channels::push_channel::PushChannel::new(pg_pool.clone()) {
+        Ok(push) => router.register(Arc::new(push)),
+        Err(e) => tracing::error!("Push channel unavailable: {}", e),
+    }
+    // Email works without a relay too: users can send from their own
+    // connected mail account.
+    let smtp_relay = backend::channels::email_channel::SmtpRelay::from_env();
+    if smtp_relay.is_some() {
+        tracing::info!("Email channel will use the SMTP relay (SMTP_RELAY_HOST set)");
+    }
+    router.register(Arc::new(
+        backend::channels::email_channel::EmailChannel::new(
+            pg_pool.clone(),
+            user_repository.clone(),
+            smtp_relay,
+        ),
+    ));
 
     // Load per-country provider order into the router's in-memory cache.
     // Rows without valid JSON are skipped with a warning rather than failing
@@ -561,10 +602,55 @@ async fn main() {
         }
     }
 
-    let channel_router = Arc::new(router);
+    // Same for the per-country voice provider order.
+    match backend::VoiceRoutesRepository::new(pg_pool.clone()).list_all() {
+        Ok(rows) => {
+            for row in rows {
+                match serde_json::from_str::<Vec<String>>(&row.provider_order) {
+                    Ok(order) => {
+                        tracing::info!("Loaded voice route: {} -> {:?}", row.country_code, order);
+                        router.set_voice_route(&row.country_code, order);
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Skipping voice_routes row for {} (invalid JSON: {}): {}",
+                            row.country_code,
+                            e,
+                            row.provider_order
+                        );
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to load voice_routes; falling back to router default: {}",
+                e
+            );
+        }
+    }
+
+    // Parsing a full GeoNames dump takes seconds; keep it off the runtime.
+    let gazetteer = tokio::task::spawn_blocking(backend::services::shared_location::load_gazetteer)
+        .await
+        .ok()
+        .flatten()
+        .map(Arc::new);
 
     let ai_config = AiConfig::from_env();
     let state = Arc::new_cyclic(|weak_state| {
+        // The Matrix DM channel sends through the user's Matrix client, which
+        // lives in AppState, so it can only be registered here.
+        if std::env::var("MATRIX_HOMESERVER")
+            .ok()
+            .is_some_and(|s| !s.is_empty())
+        {
+            tracing::info!("Registered Matrix DM channel");
+            router.register(Arc::new(
+                backend::channels::matrix_channel::MatrixChannel::new(weak_state.clone()),
+            ));
+        }
+        let channel_router = Arc::new(router);
         let light_tool_responder: Arc<dyn LightToolResponder> = Arc::new(
             LightToolAgentResponder::new(ai_config.clone(), weak_state.clone()),
         );
@@ -603,6 +689,7 @@ async fn main() {
             pending_reply_watches_repository,
             webhook_tokens_repository,
             pending_totp_logins: DashMap::new(),
+            voice_stream_tickets,
             pending_password_resets: DashMap::new(),
             session_to_token: DashMap::new(),
             totp_verify_limiter: DashMap::new(),
@@ -615,6 +702,7 @@ async fn main() {
             whatsapp_bridge_repository,
             telegram_bridge_repository,
             ontology_registry: backend::ontology::registry::OntologyRegistry::build(),
+            gazetteer,
             tool_registry: backend::build_tool_registry(),
             pending_rule_tests: Arc::new(DashMap::new()),
             maintenance_mode: Arc::new(AtomicBool::new(false)),
@@ -708,8 +796,9 @@ async fn main() {
             post(handlers::webhook_sms_handlers::webhook_sms),
         )
         .layer(DefaultBodyLimit::max(4096));
-    // Local agent clients authenticate with a dedicated write-only bearer.
-    // These routes expose no reads and reject credentials in query strings.
+    // Local agent clients authenticate with a dedicated scoped bearer. Reads
+    // return only what their scope allows; all routes reject credentials in
+    // query strings.
     let agent_routes = Router::new()
         .route(
             "/api/agent/pairing/start",
@@ -727,22 +816,80 @@ async fn main() {
             "/api/agent/actions/reply-watches",
             post(handlers::agent_integration_handlers::create_reply_watch),
         )
+        .route(
+            "/api/agent/actions/sms",
+            post(handlers::agent_integration_handlers::send_self_sms),
+        )
+        .route(
+            "/api/agent/reminders",
+            get(handlers::agent_integration_handlers::list_created_reminders),
+        )
+        .route(
+            "/api/agent/reminders/{item_id}",
+            delete(handlers::agent_integration_handlers::cancel_created_reminder),
+        )
+        .route(
+            "/api/agent/reminders/{item_id}/snooze",
+            post(handlers::agent_integration_handlers::snooze_created_reminder),
+        )
+        .route(
+            "/api/agent/reply-watches",
+            get(handlers::agent_integration_handlers::list_created_reply_watches),
+        )
+        .route(
+            "/api/agent/reply-watches/{item_id}",
+            delete(handlers::agent_integration_handlers::cancel_created_reply_watch),
+        )
+        .route(
+            "/api/agent/events",
+            get(handlers::agent_integration_handlers::list_events),
+        )
+        .route(
+            "/api/agent/digest",
+            get(handlers::agent_integration_handlers::list_digest),
+        )
+        .route(
+            "/api/agent/rules",
+            get(handlers::agent_integration_handlers::list_rules),
+        )
+        .route(
+            "/api/agent/rules/{rule_id}/status",
+            post(handlers::agent_integration_handlers::set_rule_status),
+        )
         .route(
             "/api/agent/credential",
             delete(handlers::agent_integration_handlers::revoke_current_credential),
         )
         .layer(DefaultBodyLimit::max(4096));
     // Voice pipeline: TwiML endpoint validated by Twilio signature
+    // Lightfriend's own MCP endpoint for desktop AI clients. Auth is an
+    // `mcp_access_tokens` bearer checked by the handler; GET and DELETE get
+    // 405 because the server keeps no sessions or streams.
+    let mcp_server_routes = Router::new()
+        .route(
+            "/api/mcp",
+            post(handlers::mcp_server_handlers::mcp_endpoint),
+        )
+        .layer(DefaultBodyLimit::max(64 * 1024));
     let voice_twiml_routes = Router::new()
         .route("/api/voice/incoming", post(voice_pipeline::voice_incoming))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             api::twilio_utils::validate_twilio_signature,
         ));
-    // Voice pipeline: WebSocket + debug endpoints (no auth - WS handshake)
+    // Voice pipeline: WebSocket + debug endpoints (no auth - WS handshake;
+    // Telnyx and Sinch streams carry a signed URL instead)
     let voice_ws_routes = Router::new()
         .route("/api/voice/ws", get(voice_pipeline::voice_ws))
-        .route("/api/voice/web-ws", get(voice_pipeline::voice_web_ws));
+        .route("/api/voice/web-ws", get(voice_pipeline::voice_web_ws))
+        .route(
+            "/api/voice/telnyx/ws",
+            get(api::voice_stream_bridge::telnyx_stream_ws),
+        )
+        .route(
+            "/api/voice/sinch/ws",
+            get(api::voice_stream_bridge::sinch_stream_ws),
+        );
     // Voice pipeline: authenticated endpoint for web call start
     let voice_auth_routes = Router::new()
         .route("/api/voice/web-start", get(voice_pipeline::voice_web_start))
@@ -760,6 +907,10 @@ async fn main() {
         .route(
             "/api/auth/youtube/callback",
             get(youtube_auth::youtube_callback),
+        )
+        .route(
+            "/api/mcp/oauth/callback",
+            get(handlers::mcp_handlers::mcp_oauth_callback),
         );
     // Public routes that don't need authentication. there's ratelimiting though
     let public_routes = Router::new()
@@ -804,6 +955,10 @@ async fn main() {
             get(handlers::health_handlers::deep_health),
         )
         .route("/api/geo/country", get(geo_country))
+        .route(
+            "/api/media/rendered/{file}",
+            get(handlers::rendered_media_handlers::get_rendered_image),
+        )
         .route(
             "/.well-known/lightfriend/attestation",
             get(attestation_handlers::attestation_metadata),
@@ -1007,6 +1162,11 @@ async fn main() {
             "/api/admin/alerts/enable/{alert_type}",
             post(admin_handlers::enable_alert_type),
         )
+        .route(
+            "/api/admin/encryption/rotation",
+            get(admin_handlers::get_encryption_rotation)
+                .post(admin_handlers::start_encryption_rotation),
+        )
         .route(
             "/api/admin/resend-sync",
             post(admin_handlers::sync_all_users_to_resend),
@@ -1071,6 +1231,18 @@ async fn main() {
             "/api/admin/provider-routes/{country_code}",
             delete(admin_handlers::delete_provider_route),
         )
+        .route(
+            "/api/admin/provider-health",
+            get(admin_handlers::get_provider_health),
+        )
+        .route(
+            "/api/admin/voice-routes",
+            get(admin_handlers::list_voice_routes).post(admin_handlers::upsert_voice_route),
+        )
+        .route(
+            "/api/admin/voice-routes/{country_code}",
+            delete(admin_handlers::delete_voice_route),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             handlers::auth_middleware::require_admin,
@@ -1449,6 +1621,33 @@ async fn main() {
             "/api/signal/search-rooms",
             get(signal_handlers::search_rooms_handler),
         )
+        .route("/api/auth/bridges", get(bridgev2_auth::list_networks))
+        .route(
+            "/api/auth/bridges/{network}/flows",
+            get(bridgev2_auth::get_login_flows),
+        )
+        .route(
+            "/api/auth/bridges/{network}/login",
+            get(bridgev2_auth::get_login)
+                .post(bridgev2_auth::start_login)
+                .delete(bridgev2_auth::cancel_login),
+        )
+        .route(
+            "/api/auth/bridges/{network}/login/step",
+            post(bridgev2_auth::submit_login_step),
+        )
+        .route(
+            "/api/auth/bridges/{network}/status",
+            get(bridgev2_auth::get_status),
+        )
+        .route(
+            "/api/auth/bridges/{network}/health",
+            get(bridgev2_auth::check_health),
+        )
+        .route(
+            "/api/auth/bridges/{network}/disconnect",
+            delete(bridgev2_auth::disconnect),
+        )
         .route(
             "/api/auth/whatsapp/status",
             get(whatsapp_auth::get_whatsapp_status),
@@ -1591,6 +1790,10 @@ async fn main() {
             get(rule_handlers::list_rules).post(rule_handlers::create_rule),
         )
         .route("/api/rules/test", post(rule_handlers::start_rule_test))
+        .route(
+            "/api/rules/schedule-preview",
+            post(rule_handlers::preview_schedule),
+        )
         .route(
             "/api/rules/test-stream",
             get(rule_handlers::test_rule_stream),
@@ -1652,6 +1855,32 @@ async fn main() {
             "/api/mcp/test",
             post(handlers::mcp_handlers::test_url_connection),
         )
+        .route(
+            "/api/mcp/oauth/start",
+            post(handlers::mcp_handlers::start_mcp_oauth),
+        )
+        .route(
+            "/api/mcp/servers/{id}/oauth/start",
+            post(handlers::mcp_handlers::reauthorize_mcp_oauth),
+        )
+        // CalDAV calendar connections
+        .route(
+            "/api/calendar/connections",
+            get(handlers::caldav_handlers::list_caldav_connections)
+                .post(handlers::caldav_handlers::create_caldav_connection),
+        )
+        .route(
+            "/api/calendar/connections/{id}",
+            delete(handlers::caldav_handlers::delete_caldav_connection),
+        )
+        .route(
+            "/api/calendar/connections/{id}/test",
+            post(handlers::caldav_handlers::test_caldav_connection),
+        )
+        .route(
+            "/api/calendar/connections/{id}/toggle",
+            patch(handlers::caldav_handlers::toggle_caldav_connection),
+        )
         .route(
             "/api/me/webhook-tokens",
             get(handlers::webhook_sms_handlers::list_tokens)
@@ -1661,6 +1890,51 @@ async fn main() {
             "/api/me/webhook-tokens/{token_id}",
             delete(handlers::webhook_sms_handlers::revoke_token),
         )
+        .route(
+            "/api/me/mcp-tokens",
+            get(handlers::mcp_server_handlers::list_access_tokens)
+                .post(handlers::mcp_server_handlers::create_access_token),
+        )
+        .route(
+            "/api/me/mcp-tokens/{token_id}",
+            patch(handlers::mcp_server_handlers::update_access_token)
+                .delete(handlers::mcp_server_handlers::revoke_access_token),
+        )
+        .route(
+            "/api/me/matrix-channel",
+            get(handlers::matrix_channel_handlers::get_matrix_channel)
+                .put(handlers::matrix_channel_handlers::connect_matrix_channel)
+                .delete(handlers::matrix_channel_handlers::delete_matrix_channel),
+        )
+        .route(
+            "/api/me/matrix-channel/delivery",
+            patch(handlers::matrix_channel_handlers::update_matrix_delivery),
+        )
+        .route(
+            "/api/me/push-endpoint",
+            get(handlers::push_channel_handlers::get_push_endpoint)
+                .put(handlers::push_channel_handlers::register_push_endpoint)
+                .patch(handlers::push_channel_handlers::update_push_routing)
+                .delete(handlers::push_channel_handlers::delete_push_endpoint),
+        )
+        .route(
+            "/api/me/push-endpoint/test",
+            post(handlers::push_channel_handlers::test_push_endpoint),
+        )
+        .route(
+            "/api/me/email-channel",
+            get(handlers::email_channel_handlers::get_email_channel)
+                .put(handlers::email_channel_handlers::connect_email_channel)
+                .delete(handlers::email_channel_handlers::delete_email_channel),
+        )
+        .route(
+            "/api/me/email-channel/delivery",
+            patch(handlers::email_channel_handlers::update_email_delivery),
+        )
+        .route(
+            "/api/me/email-channel/test",
+            post(handlers::email_channel_handlers::test_email_channel),
+        )
         .route(
             "/api/me/agent-credentials",
             get(handlers::agent_integration_handlers::list_credentials),
@@ -1739,6 +2013,7 @@ async fn main() {
         .merge(voice_routes)
         .merge(webhook_sms_routes)
         .merge(agent_routes)
+        .merge(mcp_server_routes)
         .nest_service("/uploads", ServeDir::new("uploads"))
         .route("/blog/md/{slug}", get(blog::handlers::blog_post_md_handler))
         .route("/blog/{slug}", get(blog::handlers::blog_post_handler))
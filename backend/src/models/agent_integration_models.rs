@@ -1,8 +1,72 @@
 use crate::pg_schema::{
-    agent_action_audit, agent_action_idempotency, agent_credentials, agent_pairing_sessions,
+    agent_action_audit, agent_action_idempotency, agent_created_items, agent_credentials,
+    agent_pairing_sessions,
 };
 use diesel::prelude::*;
 
+/// A capability a local agent credential can be granted at pairing approval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentScope {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Every grantable scope, in the order they are stored and displayed.
+pub const AGENT_SCOPES: &[AgentScope] = &[
+    AgentScope {
+        name: "reminders:write",
+        description: "create and manage its own reminders",
+    },
+    AgentScope {
+        name: "reply_watch:write",
+        description: "watch email replies and cancel its watches",
+    },
+    AgentScope {
+        name: "events:read",
+        description: "read upcoming events",
+    },
+    AgentScope {
+        name: "digest:read",
+        description: "read digest summaries",
+    },
+    AgentScope {
+        name: "sms:self",
+        description: "text you",
+    },
+    AgentScope {
+        name: "rules:toggle",
+        description: "pause and resume rules",
+    },
+];
+
+/// What a pairing grants when the approver does not pick scopes.
+pub const DEFAULT_AGENT_SCOPES: &str = "reminders:write,reply_watch:write";
+
+/// Validates requested scope names and returns them deduplicated in
+/// canonical order, comma-joined for storage. `None` if any name is unknown
+/// or nothing was requested.
+pub fn canonical_scopes<S: AsRef<str>>(requested: &[S]) -> Option<String> {
+    let requested = requested
+        .iter()
+        .map(|scope| scope.as_ref().trim())
+        .collect::<Vec<_>>();
+    if requested.is_empty()
+        || requested
+            .iter()
+            .any(|scope| !AGENT_SCOPES.iter().any(|known| known.name == *scope))
+    {
+        return None;
+    }
+    Some(
+        AGENT_SCOPES
+            .iter()
+            .filter(|known| requested.contains(&known.name))
+            .map(|known| known.name)
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
 #[derive(Queryable, Selectable, Clone, Debug)]
 #[diesel(table_name = agent_credentials)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -22,6 +86,21 @@ pub struct AgentCredential {
     pub revoked_at: Option<i32>,
 }
 
+impl AgentCredential {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.split(',').any(|granted| granted == scope)
+    }
+
+    /// Human-readable descriptions of the granted scopes.
+    pub fn scope_descriptions(&self) -> Vec<&'static str> {
+        AGENT_SCOPES
+            .iter()
+            .filter(|scope| self.has_scope(scope.name))
+            .map(|scope| scope.description)
+            .collect()
+    }
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = agent_credentials)]
 pub struct NewAgentCredential<'a> {
@@ -50,6 +129,7 @@ pub struct AgentPairingSession {
     pub approved_by_user_id: Option<i32>,
     pub approved_at: Option<i32>,
     pub consumed_at: Option<i32>,
+    pub scopes: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -81,3 +161,31 @@ pub struct NewAgentActionAudit<'a> {
     pub outcome: &'a str,
     pub created_at: i32,
 }
+
+/// A reminder or reply watch created through a credential. Only ids, times
+/// and a digest of the watched sender are kept.
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = agent_created_items)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AgentCreatedItem {
+    pub id: i32,
+    pub credential_id: i32,
+    pub user_id: i32,
+    pub item_kind: String,
+    pub event_id: Option<i32>,
+    pub contact_hash: Option<String>,
+    pub created_at: i32,
+    pub expires_at: Option<i32>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = agent_created_items)]
+pub struct NewAgentCreatedItem<'a> {
+    pub credential_id: i32,
+    pub user_id: i32,
+    pub item_kind: &'a str,
+    pub event_id: Option<i32>,
+    pub contact_hash: Option<&'a str>,
+    pub created_at: i32,
+    pub expires_at: Option<i32>,
+}
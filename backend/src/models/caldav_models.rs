@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Response for API - decrypted connection info (without the password)
+#[derive(Debug, Clone, Serialize)]
+pub struct CalDavConnectionResponse {
+    pub id: i32,
+    pub name: String,
+    pub url: String,
+    pub username: String,
+    /// Display names of the calendars found on the server
+    pub calendars: Vec<String>,
+    pub is_enabled: bool,
+    pub created_at: i32,
+}
+
+/// Request to connect a CalDAV account
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateCalDavConnectionRequest {
+    pub name: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// Response for test connection
+#[derive(Debug, Clone, Serialize)]
+pub struct CalDavTestConnectionResponse {
+    pub success: bool,
+    pub calendars: Option<Vec<String>>,
+    /// Events in the next seven days across all calendars
+    pub upcoming_events: Option<usize>,
+    pub error: Option<String>,
+}
@@ -0,0 +1,150 @@
+use crate::models::matrix_channel_models::NotificationCategory;
+use crate::pg_schema::email_notification_channels;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How one category of notification reaches the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailDeliveryMode {
+    #[default]
+    Sms,
+    Email,
+    Both,
+}
+
+impl EmailDeliveryMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sms => "sms",
+            Self::Email => "email",
+            Self::Both => "both",
+        }
+    }
+
+    /// Unknown values read as SMS, the delivery every user had before.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "email" => Self::Email,
+            "both" => Self::Both,
+            _ => Self::Sms,
+        }
+    }
+
+    pub fn includes_sms(self) -> bool {
+        matches!(self, Self::Sms | Self::Both)
+    }
+
+    pub fn includes_email(self) -> bool {
+        matches!(self, Self::Email | Self::Both)
+    }
+}
+
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = email_notification_channels)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct EmailNotificationChannel {
+    pub user_id: i32,
+    pub address: String,
+    /// The connected mail account notifications are sent from. `None`
+    /// sends through the operator's SMTP relay.
+    pub imap_connection_id: Option<i32>,
+    pub critical_delivery: String,
+    pub digest_delivery: String,
+    pub reminder_delivery: String,
+    pub rule_delivery: String,
+    pub other_delivery: String,
+    pub created_at: i32,
+    pub updated_at: i32,
+}
+
+impl EmailNotificationChannel {
+    pub fn delivery_for(&self, category: NotificationCategory) -> EmailDeliveryMode {
+        EmailDeliveryMode::parse(match category {
+            NotificationCategory::Critical => &self.critical_delivery,
+            NotificationCategory::Digest => &self.digest_delivery,
+            NotificationCategory::Reminders => &self.reminder_delivery,
+            NotificationCategory::Rules => &self.rule_delivery,
+            NotificationCategory::Other => &self.other_delivery,
+        })
+    }
+
+    pub fn delivery_modes(&self) -> EmailDeliveryModes {
+        EmailDeliveryModes {
+            critical: self.delivery_for(NotificationCategory::Critical),
+            digest: self.delivery_for(NotificationCategory::Digest),
+            reminders: self.delivery_for(NotificationCategory::Reminders),
+            rules: self.delivery_for(NotificationCategory::Rules),
+            other: self.delivery_for(NotificationCategory::Other),
+        }
+    }
+}
+
+/// Delivery mode per notification category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EmailDeliveryModes {
+    pub critical: EmailDeliveryMode,
+    pub digest: EmailDeliveryMode,
+    pub reminders: EmailDeliveryMode,
+    pub rules: EmailDeliveryMode,
+    pub other: EmailDeliveryMode,
+}
+
+/// Request to set (or change) the address notifications are emailed to
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConnectEmailChannelRequest {
+    pub address: String,
+    /// Send from this connected mail account instead of the relay.
+    #[serde(default)]
+    pub imap_connection_id: Option<i32>,
+}
+
+/// Request to change delivery modes. Omitted categories keep their mode.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateEmailDeliveryRequest {
+    pub critical: Option<EmailDeliveryMode>,
+    pub digest: Option<EmailDeliveryMode>,
+    pub reminders: Option<EmailDeliveryMode>,
+    pub rules: Option<EmailDeliveryMode>,
+    pub other: Option<EmailDeliveryMode>,
+}
+
+impl UpdateEmailDeliveryRequest {
+    pub fn apply(&self, modes: EmailDeliveryModes) -> EmailDeliveryModes {
+        EmailDeliveryModes {
+            critical: self.critical.unwrap_or(modes.critical),
+            digest: self.digest.unwrap_or(modes.digest),
+            reminders: self.reminders.unwrap_or(modes.reminders),
+            rules: self.rules.unwrap_or(modes.rules),
+            other: self.other.unwrap_or(modes.other),
+        }
+    }
+}
+
+/// A connected mail account the user can send notifications from
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailSenderAccount {
+    pub imap_connection_id: i32,
+    pub email: String,
+}
+
+/// The user's email channel as shown on the dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailChannelResponse {
+    pub address: String,
+    pub imap_connection_id: Option<i32>,
+    pub delivery: EmailDeliveryModes,
+}
+
+/// What the dashboard needs to set the channel up
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailChannelStatus {
+    pub channel: Option<EmailChannelResponse>,
+    /// Whether the operator configured an SMTP relay. Without one, only
+    /// connected accounts can send.
+    pub relay_available: bool,
+    /// Addresses notifications may go to: the account email and every
+    /// connected mail account.
+    pub allowed_addresses: Vec<String>,
+    pub sender_accounts: Vec<EmailSenderAccount>,
+}
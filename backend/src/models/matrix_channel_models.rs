@@ -0,0 +1,157 @@
+use crate::pg_schema::matrix_dm_channels;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How one category of notification reaches the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryMode {
+    #[default]
+    Sms,
+    Matrix,
+    Both,
+}
+
+impl DeliveryMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sms => "sms",
+            Self::Matrix => "matrix",
+            Self::Both => "both",
+        }
+    }
+
+    /// Unknown values read as SMS, the delivery every user had before.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "matrix" => Self::Matrix,
+            "both" => Self::Both,
+            _ => Self::Sms,
+        }
+    }
+
+    pub fn includes_sms(self) -> bool {
+        matches!(self, Self::Sms | Self::Both)
+    }
+
+    pub fn includes_matrix(self) -> bool {
+        matches!(self, Self::Matrix | Self::Both)
+    }
+}
+
+/// The notification types a user can route separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationCategory {
+    Critical,
+    Digest,
+    Reminders,
+    Rules,
+    Other,
+}
+
+impl NotificationCategory {
+    /// Maps a `send_notification` content type onto its category.
+    pub fn from_content_type(content_type: &str) -> Self {
+        if content_type.contains("critical") || content_type.starts_with("system_important") {
+            Self::Critical
+        } else if content_type == "digest" {
+            Self::Digest
+        } else if matches!(content_type, "event_notification" | "tracked_item_update") {
+            Self::Reminders
+        } else if content_type.starts_with("rule_") {
+            Self::Rules
+        } else {
+            Self::Other
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = matrix_dm_channels)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MatrixDmChannel {
+    pub user_id: i32,
+    pub target_mxid: String,
+    pub room_id: Option<String>,
+    pub critical_delivery: String,
+    pub digest_delivery: String,
+    pub reminder_delivery: String,
+    pub rule_delivery: String,
+    pub other_delivery: String,
+    pub created_at: i32,
+    pub updated_at: i32,
+}
+
+impl MatrixDmChannel {
+    pub fn delivery_for(&self, category: NotificationCategory) -> DeliveryMode {
+        DeliveryMode::parse(match category {
+            NotificationCategory::Critical => &self.critical_delivery,
+            NotificationCategory::Digest => &self.digest_delivery,
+            NotificationCategory::Reminders => &self.reminder_delivery,
+            NotificationCategory::Rules => &self.rule_delivery,
+            NotificationCategory::Other => &self.other_delivery,
+        })
+    }
+
+    pub fn delivery_modes(&self) -> MatrixDeliveryModes {
+        MatrixDeliveryModes {
+            critical: self.delivery_for(NotificationCategory::Critical),
+            digest: self.delivery_for(NotificationCategory::Digest),
+            reminders: self.delivery_for(NotificationCategory::Reminders),
+            rules: self.delivery_for(NotificationCategory::Rules),
+            other: self.delivery_for(NotificationCategory::Other),
+        }
+    }
+}
+
+/// Delivery mode per notification category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MatrixDeliveryModes {
+    pub critical: DeliveryMode,
+    pub digest: DeliveryMode,
+    pub reminders: DeliveryMode,
+    pub rules: DeliveryMode,
+    pub other: DeliveryMode,
+}
+
+/// Request to open (or re-open) the Matrix DM channel
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConnectMatrixChannelRequest {
+    pub target_mxid: String,
+}
+
+/// Request to change delivery modes. Omitted categories keep their mode.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateMatrixDeliveryRequest {
+    pub critical: Option<DeliveryMode>,
+    pub digest: Option<DeliveryMode>,
+    pub reminders: Option<DeliveryMode>,
+    pub rules: Option<DeliveryMode>,
+    pub other: Option<DeliveryMode>,
+}
+
+impl UpdateMatrixDeliveryRequest {
+    pub fn apply(&self, modes: MatrixDeliveryModes) -> MatrixDeliveryModes {
+        MatrixDeliveryModes {
+            critical: self.critical.unwrap_or(modes.critical),
+            digest: self.digest.unwrap_or(modes.digest),
+            reminders: self.reminders.unwrap_or(modes.reminders),
+            rules: self.rules.unwrap_or(modes.rules),
+            other: self.other.unwrap_or(modes.other),
+        }
+    }
+}
+
+/// The user's Matrix DM channel as shown on the dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixChannelResponse {
+    pub target_mxid: String,
+    pub room_id: Option<String>,
+    /// Whether `target_mxid` has accepted the room invite. Until then every
+    /// notification goes out by SMS.
+    pub joined: bool,
+    /// The Matrix ID Lightfriend sends from, so the user can recognise the
+    /// invite.
+    pub sender_mxid: Option<String>,
+    pub delivery: MatrixDeliveryModes,
+}
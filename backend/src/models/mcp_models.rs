@@ -1,3 +1,5 @@
+use crate::pg_schema::mcp_access_tokens;
+use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 /// MCP Tool discovered from a remote server
@@ -17,6 +19,8 @@ pub struct McpServerResponse {
     pub has_auth_token: bool,
     pub is_enabled: bool,
     pub created_at: i32,
+    pub uses_oauth: bool,
+    pub oauth_connected: bool,
 }
 
 /// Request to create a new MCP server
@@ -27,6 +31,20 @@ pub struct CreateMcpServerRequest {
     pub auth_token: Option<String>,
 }
 
+/// Request to add an MCP server that authorizes with OAuth
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartMcpOAuthRequest {
+    pub name: String,
+    pub url: String,
+}
+
+/// Where to send the user to authorize an MCP server
+#[derive(Debug, Clone, Serialize)]
+pub struct McpOAuthStartResponse {
+    pub server_id: i32,
+    pub auth_url: String,
+}
+
 /// Response for test connection
 #[derive(Debug, Clone, Serialize)]
 pub struct McpTestConnectionResponse {
@@ -34,6 +52,29 @@ pub struct McpTestConnectionResponse {
     pub tools_count: Option<usize>,
     pub tools: Option<Vec<McpToolInfo>>,
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Vec<McpResourceInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<Vec<McpPromptInfo>>,
+}
+
+impl McpTestConnectionResponse {
+    pub fn failed(error: String) -> Self {
+        Self {
+            success: false,
+            tools_count: None,
+            tools: None,
+            error: Some(error),
+            server_name: None,
+            protocol_version: None,
+            resources: None,
+            prompts: None,
+        }
+    }
 }
 
 /// Tool info for display
@@ -44,3 +85,157 @@ pub struct McpToolInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub input_schema: Option<serde_json::Value>,
 }
+
+/// Resource info for display (and the rule builder's resource picker)
+#[derive(Debug, Clone, Serialize)]
+pub struct McpResourceInfo {
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+/// Prompt info for display
+#[derive(Debug, Clone, Serialize)]
+pub struct McpPromptInfo {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// A Lightfriend tool that MCP clients can be allowed to call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct McpServerTool {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Every tool an MCP access token can be allowed, in storage and display order.
+pub const MCP_SERVER_TOOLS: &[McpServerTool] = &[
+    McpServerTool {
+        name: "set_reminder",
+        description: "set one-time reminders",
+    },
+    McpServerTool {
+        name: "query_events",
+        description: "read upcoming events",
+    },
+    McpServerTool {
+        name: "send_sms_to_self",
+        description: "text your own phone number",
+    },
+    McpServerTool {
+        name: "get_weather",
+        description: "look up the weather",
+    },
+];
+
+pub const DEFAULT_MCP_SERVER_TOOLS: &str = "query_events";
+
+/// Validates requested tool names and returns them deduplicated in catalog
+/// order, comma-joined for storage. `None` if any name is unknown or nothing
+/// was requested.
+pub fn canonical_mcp_tools<S: AsRef<str>>(requested: &[S]) -> Option<String> {
+    let requested = requested
+        .iter()
+        .map(|tool| tool.as_ref().trim())
+        .collect::<Vec<_>>();
+    if requested.is_empty()
+        || requested
+            .iter()
+            .any(|tool| !MCP_SERVER_TOOLS.iter().any(|known| known.name == *tool))
+    {
+        return None;
+    }
+    Some(
+        MCP_SERVER_TOOLS
+            .iter()
+            .filter(|known| requested.contains(&known.name))
+            .map(|known| known.name)
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = mcp_access_tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct McpAccessToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub token_hash: String,
+    pub token_prefix: String,
+    pub label: String,
+    pub created_at: i32,
+    pub last_used_at: Option<i32>,
+    pub revoked_at: Option<i32>,
+    pub allowed_tools: String,
+}
+
+impl McpAccessToken {
+    pub fn allows(&self, tool: &str) -> bool {
+        self.allowed_tools.split(',').any(|allowed| allowed == tool)
+    }
+
+    /// The allowed tools in catalog order.
+    pub fn tools(&self) -> Vec<&'static McpServerTool> {
+        MCP_SERVER_TOOLS
+            .iter()
+            .filter(|tool| self.allows(tool.name))
+            .collect()
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = mcp_access_tokens)]
+pub struct NewMcpAccessToken<'a> {
+    pub user_id: i32,
+    pub token_hash: &'a str,
+    pub token_prefix: &'a str,
+    pub label: &'a str,
+    pub allowed_tools: &'a str,
+    pub created_at: i32,
+}
+
+/// Request to mint an MCP access token
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMcpAccessTokenRequest {
+    pub label: String,
+    pub tools: Option<Vec<String>>,
+}
+
+/// Request to change which tools a token may call
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateMcpAccessTokenRequest {
+    pub tools: Vec<String>,
+}
+
+/// Token info for display. The token itself is only returned once, at mint.
+#[derive(Debug, Clone, Serialize)]
+pub struct McpAccessTokenResponse {
+    pub id: i32,
+    pub label: String,
+    pub token_prefix: String,
+    pub tools: Vec<String>,
+    pub created_at: i32,
+    pub last_used_at: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl From<&McpAccessToken> for McpAccessTokenResponse {
+    fn from(token: &McpAccessToken) -> Self {
+        Self {
+            id: token.id,
+            label: token.label.clone(),
+            token_prefix: token.token_prefix.clone(),
+            tools: token
+                .tools()
+                .iter()
+                .map(|tool| tool.name.to_string())
+                .collect(),
+            created_at: token.created_at,
+            last_used_at: token.last_used_at,
+            token: None,
+        }
+    }
+}
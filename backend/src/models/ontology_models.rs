@@ -1,6 +1,6 @@
 use crate::pg_schema::{
     ont_changelog, ont_channels, ont_events, ont_links, ont_messages, ont_person_edits,
-    ont_persons, ont_rules,
+    ont_persons, ont_rule_continuations, ont_rules,
 };
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -167,6 +167,13 @@ pub struct OntMessage {
     pub matrix_event_id: Option<String>,
     pub commitment_prompt: Option<String>,
     pub commitment_result: Option<String>,
+    /// Raw speech-to-text output when this was a voice note.
+    pub transcript: Option<String>,
+    /// Coordinates, sender label and gazetteer place of a shared location.
+    pub location_lat: Option<f64>,
+    pub location_lon: Option<f64>,
+    pub location_label: Option<String>,
+    pub location_place: Option<String>,
 }
 
 #[derive(Insertable, Debug)]
@@ -264,6 +271,36 @@ pub struct NewOntRule {
     pub flow_config: Option<String>,
 }
 
+// -- ont_rule_continuations --
+
+#[derive(Queryable, Selectable, Debug, Clone, Serialize, Deserialize)]
+#[diesel(table_name = ont_rule_continuations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OntRuleContinuation {
+    pub id: i32,
+    pub rule_id: i32,
+    pub user_id: i32,
+    pub flow_node: String,
+    pub flow_context: String,
+    pub resume_at: i32,
+    pub status: String,
+    pub created_at: i32,
+    pub updated_at: i32,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = ont_rule_continuations)]
+pub struct NewOntRuleContinuation {
+    pub rule_id: i32,
+    pub user_id: i32,
+    pub flow_node: String,
+    pub flow_context: String,
+    pub resume_at: i32,
+    pub status: String,
+    pub created_at: i32,
+    pub updated_at: i32,
+}
+
 // -- Bayesian signal primitives --
 
 #[derive(Debug, Clone, Serialize)]
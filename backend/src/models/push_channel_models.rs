@@ -0,0 +1,51 @@
+use crate::pg_schema::push_endpoints;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = push_endpoints)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PushEndpointRow {
+    pub user_id: i32,
+    pub encrypted_endpoint: String,
+    pub endpoint_hash: String,
+    pub p256dh: Option<String>,
+    pub encrypted_auth_secret: Option<String>,
+    pub push_digests: bool,
+    pub push_rules: bool,
+    pub created_at: i32,
+    pub updated_at: i32,
+}
+
+/// Request to register (or replace) the user's push endpoint. `p256dh` and
+/// `auth` are the base64url Web Push subscription keys; send both to have
+/// payloads encrypted, or neither for plain ntfy topics. Routing choices
+/// survive a re-registration.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegisterPushEndpointRequest {
+    pub endpoint: String,
+    #[serde(default)]
+    pub p256dh: Option<String>,
+    #[serde(default)]
+    pub auth: Option<String>,
+}
+
+/// Request to change which notifications go by push. Omitted fields keep
+/// their value.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdatePushRoutingRequest {
+    pub digests: Option<bool>,
+    pub rules: Option<bool>,
+}
+
+/// The user's push endpoint as shown on the dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct PushEndpointResponse {
+    pub endpoint: String,
+    /// Whether payloads are end-to-end encrypted.
+    pub encrypted: bool,
+    /// Digests go by push, falling back to SMS.
+    pub digests: bool,
+    /// Rule notifications go by push, falling back to SMS.
+    pub rules: bool,
+}
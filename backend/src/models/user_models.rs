@@ -1,7 +1,8 @@
 use crate::pg_schema::{
     admin_alerts, country_availability, disabled_alert_types, message_status_log,
-    pending_reply_watches, provider_routes, site_metrics, temporary_alert_suppressions,
-    user_settings, users, waitlist, webhook_idempotency_keys, webhook_tokens,
+    pending_reply_watches, provider_routes, reply_contexts, site_metrics,
+    temporary_alert_suppressions, user_settings, users, voice_routes, waitlist,
+    webhook_idempotency_keys, webhook_tokens,
 };
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -47,6 +48,9 @@ pub struct User {
     // Internal Lightfriend allowance window, independent from Stripe billing interval.
     pub included_usage_window_start_timestamp: Option<i32>,
     pub included_usage_window_end_timestamp: Option<i32>,
+    /// Fold curly quotes, dashes and common emoji to GSM-7 before sending
+    /// SMS, so they don't force the far costlier UCS-2 encoding.
+    pub sms_gsm7_transliteration: bool,
 }
 
 #[derive(Queryable, Selectable, Insertable, Clone)]
@@ -252,6 +256,17 @@ pub struct ProviderRoute {
     pub updated_at: i32,
 }
 
+// Voice Routes: per-country ordered list of voice providers for outbound
+// calls, stored like `provider_routes`. Absent rows mean "twilio" only.
+#[derive(Queryable, Selectable, Insertable, AsChangeset, Clone, Debug, Serialize, Deserialize)]
+#[diesel(table_name = voice_routes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct VoiceRoute {
+    pub country_code: String,
+    pub provider_order: String,
+    pub updated_at: i32,
+}
+
 #[derive(Queryable, Selectable, Clone, Debug)]
 #[diesel(table_name = pending_reply_watches)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -334,6 +349,37 @@ pub struct NewPendingReplyWatch {
     pub expires_at: i32,
 }
 
+// Reply contexts: recent outbound notifications keyed by the short code
+// printed on them, so a reply can name the item it answers.
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = reply_contexts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ReplyContext {
+    pub id: i32,
+    pub user_id: i32,
+    pub code: String,
+    pub kind: String,
+    pub target_id: Option<i32>,
+    pub target_ref: Option<String>,
+    pub created_at: i32,
+    /// The start of the notification text, encrypted.
+    pub encrypted_summary: Option<String>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = reply_contexts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewReplyContext {
+    pub user_id: i32,
+    pub code: String,
+    pub kind: String,
+    pub target_id: Option<i32>,
+    pub target_ref: Option<String>,
+    pub created_at: i32,
+    /// The start of the notification text, encrypted.
+    pub encrypted_summary: Option<String>,
+}
+
 #[derive(Queryable, Selectable, Clone, Debug, Serialize)]
 #[diesel(table_name = temporary_alert_suppressions)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -6,11 +6,11 @@
 use crate::pg_schema::{
     admin_alerts, billing_accounts, billing_usage_events, billing_usage_intents,
     billing_webhook_events, bridge_bandwidth_logs, bridge_disconnection_events, bridges,
-    byot_verifications, country_availability, disabled_alert_types, imap_connection,
-    llm_usage_logs, mcp_servers, message_history, message_status_log, processed_emails,
-    refund_info, site_metrics, tesla, totp_backup_codes, totp_secrets, tuwunel_cleanup_events,
-    usage_logs, user_info, user_secrets, waitlist, webauthn_challenges, webauthn_credentials,
-    youtube,
+    byot_verifications, caldav_connections, country_availability, disabled_alert_types,
+    imap_connection, llm_usage_logs, mcp_servers, message_history, message_status_log,
+    processed_emails, refund_info, site_metrics, tesla, totp_backup_codes, totp_secrets,
+    tuwunel_cleanup_events, usage_logs, user_info, user_secrets, waitlist, webauthn_challenges,
+    webauthn_credentials, youtube,
 };
 use diesel::prelude::*;
 use serde::Serialize;
@@ -340,6 +340,10 @@ pub struct PgMcpServer {
     pub auth_token_encrypted: Option<String>,
     pub is_enabled: i32,
     pub created_at: i32,
+    pub oauth_config_encrypted: Option<String>,
+    pub oauth_access_token_encrypted: Option<String>,
+    pub oauth_refresh_token_encrypted: Option<String>,
+    pub oauth_expires_at: Option<i64>,
 }
 
 #[derive(Insertable, Debug)]
@@ -351,6 +355,37 @@ pub struct NewPgMcpServer {
     pub auth_token_encrypted: Option<String>,
     pub is_enabled: i32,
     pub created_at: i32,
+    pub oauth_config_encrypted: Option<String>,
+}
+
+// -- caldav_connections --
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = caldav_connections)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PgCalDavConnection {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub url_encrypted: String,
+    pub username_encrypted: String,
+    pub password_encrypted: String,
+    pub calendars_encrypted: String,
+    pub is_enabled: i32,
+    pub created_at: i32,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = caldav_connections)]
+pub struct NewPgCalDavConnection {
+    pub user_id: i32,
+    pub name: String,
+    pub url_encrypted: String,
+    pub username_encrypted: String,
+    pub password_encrypted: String,
+    pub calendars_encrypted: String,
+    pub is_enabled: i32,
+    pub created_at: i32,
 }
 
 // -- totp_secrets --
@@ -507,6 +542,7 @@ pub struct PgUsageLog {
     pub recharge_threshold_timestamp: Option<i32>,
     pub zero_credits_timestamp: Option<i32>,
     pub call_duration: Option<i32>,
+    pub segments: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -523,6 +559,7 @@ pub struct NewPgUsageLog {
     pub status: Option<String>,
     pub recharge_threshold_timestamp: Option<i32>,
     pub zero_credits_timestamp: Option<i32>,
+    pub segments: Option<i32>,
 }
 
 // -- processed_emails --
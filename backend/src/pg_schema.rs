@@ -101,6 +101,24 @@ diesel::table! {
         auth_token_encrypted -> Nullable<Text>,
         is_enabled -> Int4,
         created_at -> Int4,
+        oauth_config_encrypted -> Nullable<Text>,
+        oauth_access_token_encrypted -> Nullable<Text>,
+        oauth_refresh_token_encrypted -> Nullable<Text>,
+        oauth_expires_at -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    caldav_connections (id) {
+        id -> Int4,
+        user_id -> Int4,
+        name -> Text,
+        url_encrypted -> Text,
+        username_encrypted -> Text,
+        password_encrypted -> Text,
+        calendars_encrypted -> Text,
+        is_enabled -> Int4,
+        created_at -> Int4,
     }
 }
 
@@ -202,6 +220,7 @@ diesel::table! {
         recharge_threshold_timestamp -> Nullable<Int4>,
         zero_credits_timestamp -> Nullable<Int4>,
         call_duration -> Nullable<Int4>,
+        segments -> Nullable<Int4>,
     }
 }
 
@@ -249,6 +268,7 @@ diesel::table! {
         accountability_enabled -> Bool,
         included_usage_window_start_timestamp -> Nullable<Int4>,
         included_usage_window_end_timestamp -> Nullable<Int4>,
+        sms_gsm7_transliteration -> Bool,
     }
 }
 
@@ -378,6 +398,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    voice_routes (country_code) {
+        country_code -> Text,
+        provider_order -> Text,
+        updated_at -> Int4,
+    }
+}
+
 diesel::table! {
     pending_reply_watches (id) {
         id -> Int4,
@@ -392,6 +420,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    reply_contexts (id) {
+        id -> Int4,
+        user_id -> Int4,
+        code -> Text,
+        kind -> Text,
+        target_id -> Nullable<Int4>,
+        target_ref -> Nullable<Text>,
+        created_at -> Int4,
+        encrypted_summary -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     temporary_alert_suppressions (id) {
         id -> Int4,
@@ -495,6 +536,11 @@ diesel::table! {
         matrix_event_id -> Nullable<Text>,
         commitment_prompt -> Nullable<Text>,
         commitment_result -> Nullable<Text>,
+        transcript -> Nullable<Text>,
+        location_lat -> Nullable<Float8>,
+        location_lon -> Nullable<Float8>,
+        location_label -> Nullable<Text>,
+        location_place -> Nullable<Text>,
     }
 }
 
@@ -529,6 +575,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    encryption_rotation_progress (table_name, column_name) {
+        table_name -> Text,
+        column_name -> Text,
+        target_key_id -> Int4,
+        last_key -> Nullable<Text>,
+        rows_scanned -> Int8,
+        rows_rewritten -> Int8,
+        rows_failed -> Int8,
+        completed_at -> Nullable<Int4>,
+        updated_at -> Int4,
+    }
+}
+
 diesel::table! {
     llm_usage_logs (id) {
         id -> Int4,
@@ -565,6 +625,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    ont_rule_continuations (id) {
+        id -> Int4,
+        rule_id -> Int4,
+        user_id -> Int4,
+        flow_node -> Text,
+        flow_context -> Text,
+        resume_at -> Int4,
+        status -> Text,
+        created_at -> Int4,
+        updated_at -> Int4,
+    }
+}
+
 diesel::table! {
     bridge_bandwidth_logs (id) {
         id -> Int4,
@@ -829,6 +903,7 @@ diesel::table! {
         created_at -> Int4,
         last_used_at -> Nullable<Int4>,
         revoked_at -> Nullable<Int4>,
+        allowed_tools -> Text,
     }
 }
 
@@ -861,6 +936,7 @@ diesel::table! {
         approved_by_user_id -> Nullable<Int4>,
         approved_at -> Nullable<Int4>,
         consumed_at -> Nullable<Int4>,
+        scopes -> Nullable<Text>,
     }
 }
 
@@ -886,8 +962,66 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    agent_created_items (id) {
+        id -> Int4,
+        credential_id -> Int4,
+        user_id -> Int4,
+        item_kind -> Text,
+        event_id -> Nullable<Int4>,
+        contact_hash -> Nullable<Text>,
+        created_at -> Int4,
+        expires_at -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    matrix_dm_channels (user_id) {
+        user_id -> Int4,
+        target_mxid -> Text,
+        room_id -> Nullable<Text>,
+        critical_delivery -> Text,
+        digest_delivery -> Text,
+        reminder_delivery -> Text,
+        rule_delivery -> Text,
+        other_delivery -> Text,
+        created_at -> Int4,
+        updated_at -> Int4,
+    }
+}
+
+diesel::table! {
+    push_endpoints (user_id) {
+        user_id -> Int4,
+        encrypted_endpoint -> Text,
+        endpoint_hash -> Text,
+        p256dh -> Nullable<Text>,
+        encrypted_auth_secret -> Nullable<Text>,
+        push_digests -> Bool,
+        push_rules -> Bool,
+        created_at -> Int4,
+        updated_at -> Int4,
+    }
+}
+
+diesel::table! {
+    email_notification_channels (user_id) {
+        user_id -> Int4,
+        address -> Text,
+        imap_connection_id -> Nullable<Int4>,
+        critical_delivery -> Text,
+        digest_delivery -> Text,
+        reminder_delivery -> Text,
+        rule_delivery -> Text,
+        other_delivery -> Text,
+        created_at -> Int4,
+        updated_at -> Int4,
+    }
+}
+
 diesel::joinable!(ont_person_edits -> ont_persons (person_id));
 diesel::joinable!(ont_channels -> ont_persons (person_id));
+diesel::joinable!(ont_rule_continuations -> ont_rules (rule_id));
 
 diesel::joinable!(refund_info -> users (user_id));
 diesel::joinable!(billing_accounts -> users (user_id));
@@ -907,6 +1041,14 @@ diesel::joinable!(agent_pairing_sessions -> users (approved_by_user_id));
 diesel::joinable!(agent_action_idempotency -> agent_credentials (credential_id));
 diesel::joinable!(agent_action_audit -> agent_credentials (credential_id));
 diesel::joinable!(agent_action_audit -> users (user_id));
+diesel::joinable!(agent_created_items -> agent_credentials (credential_id));
+diesel::joinable!(agent_created_items -> ont_events (event_id));
+diesel::joinable!(caldav_connections -> users (user_id));
+diesel::joinable!(matrix_dm_channels -> users (user_id));
+diesel::joinable!(push_endpoints -> users (user_id));
+diesel::joinable!(email_notification_channels -> users (user_id));
+diesel::joinable!(email_notification_channels -> imap_connection (imap_connection_id));
+diesel::joinable!(reply_contexts -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     user_secrets,
@@ -916,6 +1058,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     tesla,
     youtube,
     mcp_servers,
+    caldav_connections,
     totp_secrets,
     totp_backup_codes,
     webauthn_credentials,
@@ -942,6 +1085,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     ont_messages,
     ont_events,
     ont_rules,
+    ont_rule_continuations,
     llm_usage_logs,
     bridge_bandwidth_logs,
     tuwunel_cleanup_events,
@@ -949,7 +1093,9 @@ diesel::allow_tables_to_appear_in_same_query!(
     commitment_label_embeddings,
     commitment_prompts,
     provider_routes,
+    voice_routes,
     pending_reply_watches,
+    reply_contexts,
     temporary_alert_suppressions,
     light_tool_devices,
     light_tool_push_outbox,
@@ -965,8 +1111,13 @@ diesel::allow_tables_to_appear_in_same_query!(
     billing_usage_intents,
     billing_webhook_events,
     scheduler_health,
+    encryption_rotation_progress,
     agent_credentials,
     agent_pairing_sessions,
     agent_action_idempotency,
     agent_action_audit,
+    agent_created_items,
+    matrix_dm_channels,
+    push_endpoints,
+    email_notification_channels,
 );
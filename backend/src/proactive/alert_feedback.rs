@@ -10,6 +10,7 @@ use std::sync::Arc;
 use tracing::{info, warn};
 
 use crate::models::user_models::User;
+use crate::pg_models::PgUsageLog;
 use crate::repositories::user_repository::{
     LogUsageParams, SYSTEM_ALERT_FEEDBACK_SHOULD_WAIT, SYSTEM_ALERT_FEEDBACK_WORTH_IT,
 };
@@ -58,6 +59,17 @@ pub async fn try_handle_reply(state: &Arc<AppState>, user: &User, body: &str) ->
         }
     }
 
+    Some(record_feedback(state, user, &alert, worth_it))
+}
+
+/// Store a worth-it / should-wait rating for `alert` and return the
+/// confirmation text. Callers check for an earlier rating first.
+pub fn record_feedback(
+    state: &Arc<AppState>,
+    user: &User,
+    alert: &PgUsageLog,
+    worth_it: bool,
+) -> String {
     let activity_type = if worth_it {
         SYSTEM_ALERT_FEEDBACK_WORTH_IT
     } else {
@@ -79,19 +91,20 @@ pub async fn try_handle_reply(state: &Arc<AppState>, user: &User, body: &str) ->
         status: Some("recorded".to_string()),
         recharge_threshold_timestamp: None,
         zero_credits_timestamp: None,
+        segments: None,
     }) {
         warn!(
             "alert_feedback failed to record user={} alert={}: {}",
             user.id, alert.id, e
         );
-        return Some("I saw that rating, but couldn't save it right now.".to_string());
+        return "I saw that rating, but couldn't save it right now.".to_string();
     }
 
-    Some(if worth_it {
+    if worth_it {
         "Thanks. I'll keep interrupting for messages like that.".to_string()
     } else {
         "Got it. I'll be more careful before interrupting for messages like that.".to_string()
-    })
+    }
 }
 
 fn parse_reply(body: &str) -> Option<bool> {
@@ -55,20 +55,35 @@ pub async fn try_handle_reply(state: &Arc<AppState>, user: &User, body: &str) ->
         }
     };
 
+    Some(apply_reply(state, &prompt, reply).await)
+}
+
+/// Apply a parsed reply (`REPLY_TRACK`, `REPLY_ALWAYS`, `REPLY_MUTE` or
+/// `REPLY_WRONG`) to a specific prompt and return the confirmation text.
+/// Also used when the user names the prompt by its reply code.
+pub async fn apply_reply(
+    state: &Arc<AppState>,
+    prompt: &crate::models::commitment_models::CommitmentPrompt,
+    reply: &str,
+) -> String {
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs() as i32;
 
-    let confirmation = match reply {
-        REPLY_TRACK => apply_track(state, &prompt, now).await,
-        REPLY_ALWAYS => apply_always(state, &prompt, now).await,
-        REPLY_MUTE => apply_mute(state, &prompt),
-        REPLY_WRONG => apply_wrong(state, &prompt).await,
-        _ => unreachable!(),
-    };
-
-    Some(confirmation)
+    match reply {
+        REPLY_TRACK => apply_track(state, prompt, now).await,
+        REPLY_ALWAYS => apply_always(state, prompt, now).await,
+        REPLY_MUTE => apply_mute(state, prompt),
+        REPLY_WRONG => apply_wrong(state, prompt).await,
+        other => {
+            warn!(
+                "commitment_reply user={} unknown reply label '{}'",
+                prompt.user_id, other
+            );
+            "Reply 1=track, 2=always, 3=mute or 4=not commitment.".to_string()
+        }
+    }
 }
 
 /// Strict parser: leading whitespace tolerated, but the body must be exactly
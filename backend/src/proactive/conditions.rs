@@ -0,0 +1,454 @@
+//! Structured (non-LLM) conditions for `structured_condition` flow nodes.
+//!
+//! A `Predicate` compares fields of the trigger snapshot, the user's local
+//! clock, or a numeric reading (Tesla battery, outside temperature) and can
+//! be combined with `and` / `or` / `not`. Evaluation is deterministic and
+//! costs no tokens, so cheap checks like "weekdays after 18:00 and battery
+//! below 30%" can gate an expensive LLM step or run on their own.
+//!
+//! Everything here is pure: numeric readings are fetched by the rule engine
+//! and passed in through `PredicateInput`. Evaluation short-circuits and
+//! asks for a reading only when it reaches a numeric comparison that needs
+//! it, so a failed time check never costs a Tesla or weather call. Regex
+//! patterns are compiled once, when the rule is validated on save, and
+//! reused from a process-wide cache afterwards.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Predicates deeper than this are rejected at save time.
+pub const MAX_PREDICATE_DEPTH: usize = 6;
+/// Regexes longer than this are rejected at save time.
+pub const MAX_REGEX_LEN: usize = 200;
+/// Compiled-size cap so a pathological pattern can't eat memory.
+const REGEX_SIZE_LIMIT: usize = 256 * 1024;
+/// The regex cache is cleared once it holds this many patterns.
+const REGEX_CACHE_CAPACITY: usize = 512;
+
+/// Compiled patterns keyed by source. Compile errors are cached too, so a
+/// bad pattern saved before validation existed isn't recompiled on every
+/// evaluation.
+type RegexCache = HashMap<String, Result<regex::Regex, regex::Error>>;
+static REGEX_CACHE: LazyLock<Mutex<RegexCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Predicate {
+    /// Matches the message sender. Every field that is set must match.
+    Sender {
+        #[serde(default)]
+        person_id: Option<i32>,
+        #[serde(default)]
+        room_id: Option<String>,
+        /// Case-insensitive match on the canonical person name or the
+        /// room display name, same as the legacy trigger filter.
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Matches if the snapshot platform is any of `platforms`.
+    Platform {
+        platforms: Vec<String>,
+    },
+    IsGroup {
+        value: bool,
+    },
+    /// Local time-of-day window `[start, end)`, "HH:MM". Wraps midnight
+    /// when `start > end` (e.g. 22:00-06:00).
+    TimeWindow {
+        start: String,
+        end: String,
+    },
+    /// Local day of week. Accepts "mon".."sun", full names, "weekdays" and
+    /// "weekends".
+    DayOfWeek {
+        days: Vec<String>,
+    },
+    /// Regex over a snapshot field, or the message body when `field` is
+    /// unset (the loop item inside a for_each, else the snapshot content).
+    Regex {
+        pattern: String,
+        #[serde(default)]
+        field: Option<String>,
+    },
+    /// Compare a numeric reading. `location` is only used by
+    /// `weather_temperature`; empty means the user's saved location.
+    Numeric {
+        metric: NumericMetric,
+        op: CompareOp,
+        value: f64,
+        #[serde(default)]
+        location: String,
+    },
+    And {
+        conditions: Vec<Predicate>,
+    },
+    Or {
+        conditions: Vec<Predicate>,
+    },
+    Not {
+        condition: Box<Predicate>,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NumericMetric {
+    /// Battery state of charge, percent.
+    TeslaBattery,
+    /// Current outside temperature, degrees Celsius.
+    WeatherTemperature,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    fn apply(self, left: f64, right: f64) -> bool {
+        match self {
+            CompareOp::Lt => left < right,
+            CompareOp::Le => left <= right,
+            CompareOp::Gt => left > right,
+            CompareOp::Ge => left >= right,
+            CompareOp::Eq => (left - right).abs() < f64::EPSILON,
+            CompareOp::Ne => (left - right).abs() >= f64::EPSILON,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+        }
+    }
+}
+
+/// A numeric reading a predicate needs: the metric plus its location
+/// (empty for metrics that don't take one).
+pub type MetricKey = (NumericMetric, String);
+
+/// Everything a predicate is evaluated against.
+pub struct PredicateInput<'a> {
+    pub snapshot: Option<&'a serde_json::Value>,
+    /// Text for `regex` predicates without a `field`.
+    pub body: &'a str,
+    /// Current time in the user's timezone.
+    pub now: DateTime<Tz>,
+    /// Readings fetched so far. `None` is a reading that couldn't be
+    /// fetched, which makes its comparison false.
+    pub metrics: &'a HashMap<MetricKey, Option<f64>>,
+}
+
+impl Predicate {
+    /// Evaluate left to right, short-circuiting `and` / `or`. Returns
+    /// `Err(key)` when evaluation reaches a numeric comparison whose reading
+    /// isn't in `input.metrics` yet; the caller fetches it and evaluates
+    /// again.
+    pub fn evaluate(&self, input: &PredicateInput) -> Result<bool, MetricKey> {
+        match self {
+            Predicate::Numeric {
+                metric,
+                op,
+                value,
+                location,
+            } => {
+                let key = metric_key(*metric, location);
+                match input.metrics.get(&key) {
+                    Some(reading) => Ok(reading.is_some_and(|r| op.apply(r, *value))),
+                    None => Err(key),
+                }
+            }
+            Predicate::And { conditions } => {
+                for c in conditions {
+                    if !c.evaluate(input)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Predicate::Or { conditions } => {
+                for c in conditions {
+                    if c.evaluate(input)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Predicate::Not { condition } => Ok(!condition.evaluate(input)?),
+            leaf => Ok(leaf.matches(input)),
+        }
+    }
+
+    /// Leaf predicates that need no numeric reading.
+    fn matches(&self, input: &PredicateInput) -> bool {
+        match self {
+            Predicate::Sender {
+                person_id,
+                room_id,
+                name,
+            } => {
+                let Some(snap) = input.snapshot else {
+                    return false;
+                };
+                if person_id.is_none() && room_id.is_none() && name.is_none() {
+                    return false;
+                }
+                let person_ok = person_id.is_none_or(|expected| {
+                    snap.get("person_id").and_then(|v| v.as_i64()) == Some(expected as i64)
+                });
+                let room_ok = room_id.as_deref().is_none_or(|expected| {
+                    snap.get("room_id").and_then(|v| v.as_str()) == Some(expected)
+                });
+                let name_ok = name.as_deref().is_none_or(|expected| {
+                    let expected = expected.trim();
+                    ["person_name", "sender_name"].iter().any(|key| {
+                        snap.get(*key)
+                            .and_then(|v| v.as_str())
+                            .is_some_and(|got| got.trim().eq_ignore_ascii_case(expected))
+                    })
+                });
+                person_ok && room_ok && name_ok
+            }
+            Predicate::Platform { platforms } => input
+                .snapshot
+                .and_then(|snap| snap.get("platform"))
+                .and_then(|v| v.as_str())
+                .is_some_and(|got| platforms.iter().any(|p| p.eq_ignore_ascii_case(got))),
+            Predicate::IsGroup { value } => {
+                input
+                    .snapshot
+                    .and_then(|snap| snap.get("is_group"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false)
+                    == *value
+            }
+            Predicate::TimeWindow { start, end } => {
+                let (Some(start), Some(end)) = (parse_hhmm(start), parse_hhmm(end)) else {
+                    return false;
+                };
+                let now = NaiveTime::from_hms_opt(input.now.hour(), input.now.minute(), 0)
+                    .unwrap_or_default();
+                if start <= end {
+                    start <= now && now < end
+                } else {
+                    now >= start || now < end
+                }
+            }
+            Predicate::DayOfWeek { days } => {
+                let today = input.now.weekday();
+                days.iter()
+                    .filter_map(|d| parse_days(d))
+                    .any(|set| set.contains(&today))
+            }
+            Predicate::Regex { pattern, field } => {
+                let Ok(re) = cached_regex(pattern) else {
+                    return false;
+                };
+                match field {
+                    Some(key) => input
+                        .snapshot
+                        .and_then(|snap| snap.get(key))
+                        .map(|v| match v {
+                            serde_json::Value::String(s) => re.is_match(s),
+                            other => re.is_match(&other.to_string()),
+                        })
+                        .unwrap_or(false),
+                    None => re.is_match(input.body),
+                }
+            }
+            Predicate::Numeric { .. }
+            | Predicate::And { .. }
+            | Predicate::Or { .. }
+            | Predicate::Not { .. } => unreachable!("handled by evaluate"),
+        }
+    }
+
+    /// Check the predicate is well-formed. Errors are user-facing.
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_at(1)
+    }
+
+    fn validate_at(&self, depth: usize) -> Result<(), String> {
+        if depth > MAX_PREDICATE_DEPTH {
+            return Err(format!(
+                "Condition nesting exceeds max depth of {}",
+                MAX_PREDICATE_DEPTH
+            ));
+        }
+        match self {
+            Predicate::Sender {
+                person_id,
+                room_id,
+                name,
+            } => {
+                let name = name.as_deref().filter(|n| !n.trim().is_empty());
+                if person_id.is_none() && room_id.is_none() && name.is_none() {
+                    return Err("Sender condition needs a person, room or name".to_string());
+                }
+            }
+            Predicate::Platform { platforms } => {
+                if platforms.is_empty() {
+                    return Err("Platform condition needs at least one platform".to_string());
+                }
+            }
+            Predicate::IsGroup { .. } => {}
+            Predicate::TimeWindow { start, end } => {
+                let (Some(s), Some(e)) = (parse_hhmm(start), parse_hhmm(end)) else {
+                    return Err("Time window must use HH:MM".to_string());
+                };
+                if s == e {
+                    return Err("Time window start and end must differ".to_string());
+                }
+            }
+            Predicate::DayOfWeek { days } => {
+                if days.is_empty() {
+                    return Err("Day-of-week condition needs at least one day".to_string());
+                }
+                if let Some(bad) = days.iter().find(|d| parse_days(d).is_none()) {
+                    return Err(format!("Unknown day '{}'", bad));
+                }
+            }
+            Predicate::Regex { pattern, .. } => {
+                if pattern.is_empty() {
+                    return Err("Regex pattern cannot be empty".to_string());
+                }
+                if pattern.len() > MAX_REGEX_LEN {
+                    return Err(format!(
+                        "Regex exceeds max length of {} characters",
+                        MAX_REGEX_LEN
+                    ));
+                }
+                cached_regex(pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+            }
+            Predicate::Numeric { value, .. } => {
+                if !value.is_finite() {
+                    return Err("Numeric condition needs a finite value".to_string());
+                }
+            }
+            Predicate::And { conditions } | Predicate::Or { conditions } => {
+                if conditions.is_empty() {
+                    return Err("AND/OR needs at least one condition".to_string());
+                }
+                for c in conditions {
+                    c.validate_at(depth + 1)?;
+                }
+            }
+            Predicate::Not { condition } => condition.validate_at(depth + 1)?,
+        }
+        Ok(())
+    }
+
+    /// Short human-readable form, shown in the rule test panel.
+    pub fn describe(&self) -> String {
+        match self {
+            Predicate::Sender {
+                person_id,
+                room_id,
+                name,
+            } => {
+                let who = name
+                    .clone()
+                    .or_else(|| person_id.map(|id| format!("person #{}", id)))
+                    .or_else(|| room_id.clone())
+                    .unwrap_or_default();
+                format!("sender is {}", who)
+            }
+            Predicate::Platform { platforms } => format!("platform is {}", platforms.join(" or ")),
+            Predicate::IsGroup { value: true } => "is a group chat".to_string(),
+            Predicate::IsGroup { value: false } => "is a direct chat".to_string(),
+            Predicate::TimeWindow { start, end } => format!("time is {}-{}", start, end),
+            Predicate::DayOfWeek { days } => format!("day is {}", days.join(", ")),
+            Predicate::Regex { pattern, field } => format!(
+                "{} matches /{}/",
+                field.as_deref().unwrap_or("message"),
+                pattern
+            ),
+            Predicate::Numeric {
+                metric, op, value, ..
+            } => {
+                let name = match metric {
+                    NumericMetric::TeslaBattery => "battery %",
+                    NumericMetric::WeatherTemperature => "temperature °C",
+                };
+                format!("{} {} {}", name, op.symbol(), value)
+            }
+            Predicate::And { conditions } => join_described(conditions, " AND "),
+            Predicate::Or { conditions } => join_described(conditions, " OR "),
+            Predicate::Not { condition } => format!("NOT {}", condition.describe()),
+        }
+    }
+}
+
+fn join_described(conditions: &[Predicate], sep: &str) -> String {
+    let parts: Vec<String> = conditions.iter().map(|c| c.describe()).collect();
+    format!("({})", parts.join(sep))
+}
+
+/// Location only distinguishes weather readings; the battery has one value.
+fn metric_key(metric: NumericMetric, location: &str) -> MetricKey {
+    match metric {
+        NumericMetric::TeslaBattery => (metric, String::new()),
+        NumericMetric::WeatherTemperature => (metric, location.trim().to_string()),
+    }
+}
+
+fn compile_regex(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    regex::RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+}
+
+/// The compiled form of `pattern`, compiling it on first use.
+fn cached_regex(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    if let Some(cached) = REGEX_CACHE
+        .lock()
+        .ok()
+        .and_then(|c| c.get(pattern).cloned())
+    {
+        return cached;
+    }
+    let compiled = compile_regex(pattern);
+    if let Ok(mut cache) = REGEX_CACHE.lock() {
+        if cache.len() >= REGEX_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(pattern.to_string(), compiled.clone());
+    }
+    compiled
+}
+
+fn parse_hhmm(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+fn parse_days(s: &str) -> Option<Vec<Weekday>> {
+    use Weekday::*;
+    let day = match s.trim().to_lowercase().as_str() {
+        "weekdays" => return Some(vec![Mon, Tue, Wed, Thu, Fri]),
+        "weekends" => return Some(vec![Sat, Sun]),
+        "mon" | "monday" => Mon,
+        "tue" | "tuesday" => Tue,
+        "wed" | "wednesday" => Wed,
+        "thu" | "thursday" => Thu,
+        "fri" | "friday" => Fri,
+        "sat" | "saturday" => Sat,
+        "sun" | "sunday" => Sun,
+        _ => return None,
+    };
+    Some(vec![day])
+}
@@ -0,0 +1,452 @@
+//! Reply codes for SMS threading without quoting.
+//!
+//! Dumbphones can't quote a message, so "yes do it" is ambiguous once a few
+//! notifications have arrived. Every outbound notification is prefixed with
+//! a short code ("#A7 ...") and remembered, with its start encrypted, in a
+//! small per-user ring buffer (`reply_contexts`). A reply that carries the
+//! code at its start or end ("A7 yes", "stop A7") is routed to the item
+//! behind it:
+//!
+//! - alert: 1/yes rates it worth it, 2/no/wait/stop rates it should-wait
+//! - commitment prompt: 1-4 or track/always/mute/wrong apply that prompt
+//! - reply watch: stop cancels a watch still armed for that chat
+//! - rule: stop/pause pauses the rule
+//!
+//! Anything else goes to the agent with the quoted notification prepended,
+//! so it knows what the user is answering. Bare 1/2/3/4 replies keep their
+//! old "latest prompt" behavior in `alert_feedback` and
+//! `commitment_replies`.
+
+use std::sync::Arc;
+
+use rand::Rng;
+use tracing::{info, warn};
+
+use crate::models::user_models::{NewReplyContext, ReplyContext, User};
+use crate::repositories::commitment_repository::{
+    REPLY_ALWAYS, REPLY_MUTE, REPLY_TRACK, REPLY_WRONG,
+};
+use crate::repositories::reply_contexts_repository::ReplyContextsRepository;
+use crate::utils::encryption;
+use crate::AppState;
+
+pub const KIND_ALERT: &str = "alert";
+pub const KIND_COMMITMENT: &str = "commitment";
+pub const KIND_REPLY_WATCH: &str = "reply_watch";
+pub const KIND_RULE: &str = "rule";
+pub const KIND_NOTIFICATION: &str = "notification";
+
+/// Codes older than this no longer resolve, even if still buffered.
+const MAX_AGE_SECS: i32 = 3 * 24 * 3600;
+const SUMMARY_CHARS: usize = 160;
+/// No I or O, which read as 1 and 0 on small screens.
+const CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const CODE_DIGITS: &[u8] = b"23456789";
+
+/// What a tagged notification is about, and the row that handles replies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplyTarget {
+    /// An important-message alert, by the provider id it was delivered with.
+    Alert {
+        sid: String,
+    },
+    Commitment {
+        prompt_id: i32,
+    },
+    BridgeReplyWatch {
+        room_id: String,
+    },
+    EmailReplyWatch {
+        imap_connection_id: i32,
+        sender: String,
+    },
+    Rule {
+        rule_id: i32,
+    },
+    /// Anything else (digests, reminders, ...): replies only get context.
+    Notification,
+}
+
+impl ReplyTarget {
+    fn columns(&self) -> (&'static str, Option<i32>, Option<String>) {
+        match self {
+            ReplyTarget::Alert { sid } => (KIND_ALERT, None, Some(sid.clone())),
+            ReplyTarget::Commitment { prompt_id } => (KIND_COMMITMENT, Some(*prompt_id), None),
+            ReplyTarget::BridgeReplyWatch { room_id } => {
+                (KIND_REPLY_WATCH, None, Some(room_id.clone()))
+            }
+            ReplyTarget::EmailReplyWatch {
+                imap_connection_id,
+                sender,
+            } => (
+                KIND_REPLY_WATCH,
+                Some(*imap_connection_id),
+                Some(sender.clone()),
+            ),
+            ReplyTarget::Rule { rule_id } => (KIND_RULE, Some(*rule_id), None),
+            ReplyTarget::Notification => (KIND_NOTIFICATION, None, None),
+        }
+    }
+}
+
+/// Outcome of a coded reply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplyResolution {
+    /// Fully handled; send this text back and skip the agent.
+    Handled(String),
+    /// Hand this rewritten body to the agent instead of the raw SMS.
+    Agent(String),
+}
+
+fn now() -> i32 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i32
+}
+
+/// A random code not in `taken`. With 192 codes and a 20-row buffer a free
+/// one always exists; the scan only guards against a bad random streak.
+pub fn pick_code(taken: &[String], rng: &mut impl Rng) -> String {
+    let code_at = |i: usize| {
+        format!(
+            "{}{}",
+            CODE_LETTERS[i / CODE_DIGITS.len()] as char,
+            CODE_DIGITS[i % CODE_DIGITS.len()] as char
+        )
+    };
+    let total = CODE_LETTERS.len() * CODE_DIGITS.len();
+    let start = rng.gen_range(0..total);
+    (0..total)
+        .map(|offset| code_at((start + offset) % total))
+        .find(|code| !taken.contains(code))
+        .unwrap_or_else(|| code_at(start))
+}
+
+/// Pick the code for a notification about to go out. On a lookup error a
+/// random code is still returned; at worst it shadows an older one.
+pub fn reserve_code(state: &Arc<AppState>, user_id: i32) -> String {
+    let taken = ReplyContextsRepository::new(state.pg_pool.clone())
+        .codes_in_use(user_id, now() - MAX_AGE_SECS)
+        .unwrap_or_else(|e| {
+            warn!("reply_context user={} code lookup failed: {}", user_id, e);
+            Vec::new()
+        });
+    pick_code(&taken, &mut rand::thread_rng())
+}
+
+/// The notification text as sent, with its code in front.
+pub fn tag(code: &str, body: &str) -> String {
+    format!("#{} {}", code, body)
+}
+
+/// Remember a delivered notification under its code. Failures only cost
+/// the user the ability to reply by code, so they are logged and dropped.
+pub fn record(state: &Arc<AppState>, user_id: i32, code: &str, target: &ReplyTarget, body: &str) {
+    let (kind, target_id, target_ref) = target.columns();
+    // The code still works without the quote, so a failed encryption only
+    // drops the summary.
+    let encrypted_summary = match encryption::encrypt(&summarize(body)) {
+        Ok(summary) => Some(summary),
+        Err(e) => {
+            warn!(
+                "reply_context user={} failed to encrypt summary for {}: {}",
+                user_id, code, e
+            );
+            None
+        }
+    };
+    let row = NewReplyContext {
+        user_id,
+        code: code.to_string(),
+        kind: kind.to_string(),
+        target_id,
+        target_ref,
+        created_at: now(),
+        encrypted_summary,
+    };
+    if let Err(e) = ReplyContextsRepository::new(state.pg_pool.clone()).record(row) {
+        warn!(
+            "reply_context user={} failed to record code {} ({}): {}",
+            user_id, code, kind, e
+        );
+    }
+}
+
+fn summarize(body: &str) -> String {
+    let flat = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= SUMMARY_CHARS {
+        return flat;
+    }
+    let mut out: String = flat.chars().take(SUMMARY_CHARS - 1).collect();
+    out.push('…');
+    out
+}
+
+/// Split "A7 yes", "#a7: yes" or "stop A7" into ("A7", "yes" / "stop").
+/// The code must be the first or last word; `None` when neither is a code.
+pub fn parse_coded_reply(body: &str) -> Option<(String, String)> {
+    let words: Vec<&str> = body.split_whitespace().collect();
+    let (first, last) = (words.first()?, words.last()?);
+    let (code, rest) = if let Some(code) = as_code(first) {
+        (code, &words[1..])
+    } else if let Some(code) = as_code(last) {
+        (code, &words[..words.len() - 1])
+    } else {
+        return None;
+    };
+    let rest = rest
+        .join(" ")
+        .trim_start_matches(|c: char| matches!(c, ':' | ',' | '-' | '.'))
+        .trim()
+        .to_string();
+    Some((code, rest))
+}
+
+fn as_code(word: &str) -> Option<String> {
+    let word = word
+        .trim_start_matches(['(', '['])
+        .trim_start_matches('#')
+        .trim_end_matches(|c: char| matches!(c, ':' | ',' | '.' | '!' | '?' | ')' | ']'));
+    let bytes = word.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let letter = bytes[0].to_ascii_uppercase();
+    if CODE_LETTERS.contains(&letter) && CODE_DIGITS.contains(&bytes[1]) {
+        Some(format!("{}{}", letter as char, bytes[1] as char))
+    } else {
+        None
+    }
+}
+
+fn normalize(rest: &str) -> String {
+    rest.trim()
+        .trim_end_matches(|c: char| matches!(c, '.' | '!' | '?'))
+        .to_lowercase()
+}
+
+/// Alert rating from the words after the code: `Some(true)` = worth it.
+pub fn alert_answer(rest: &str) -> Option<bool> {
+    match normalize(rest).as_str() {
+        "1" | "yes" | "y" | "worth it" | "good" => Some(true),
+        "2" | "no" | "n" | "wait" | "should wait" | "stop" => Some(false),
+        _ => None,
+    }
+}
+
+/// Commitment-prompt label from the words after the code.
+pub fn commitment_answer(rest: &str) -> Option<&'static str> {
+    match normalize(rest).as_str() {
+        "1" | "track" | "yes" | "y" => Some(REPLY_TRACK),
+        "2" | "always" | "always track" => Some(REPLY_ALWAYS),
+        "3" | "mute" | "stop" => Some(REPLY_MUTE),
+        "4" | "wrong" | "no" | "n" | "not commitment" | "not a commitment" => Some(REPLY_WRONG),
+        _ => None,
+    }
+}
+
+/// Whether the words after the code ask to stop whatever sent it.
+pub fn is_stop(rest: &str) -> bool {
+    matches!(
+        normalize(rest).as_str(),
+        "stop" | "pause" | "cancel" | "stop it" | "stop this" | "off"
+    )
+}
+
+/// The agent input for a reply to `context`, so it knows which notification
+/// the user is answering.
+pub fn agent_body(context: &ReplyContext, rest: &str) -> String {
+    let summary = match context
+        .encrypted_summary
+        .as_deref()
+        .map(encryption::decrypt)
+    {
+        Some(Ok(summary)) => Some(summary),
+        Some(Err(e)) => {
+            warn!(
+                "reply_context user={} failed to decrypt summary for {}: {}",
+                context.user_id, context.code, e
+            );
+            None
+        }
+        None => None,
+    };
+    let body = match summary {
+        Some(summary) => format!("[Replying to #{}: \"{}\"] {}", context.code, summary, rest),
+        None => format!("[Replying to #{}] {}", context.code, rest),
+    };
+    body.trim_end().to_string()
+}
+
+/// Resolve an inbound SMS that names a notification by its code. `None`
+/// when the body carries no code or the code isn't in the user's buffer;
+/// the caller then processes the SMS as usual.
+pub async fn try_resolve(
+    state: &Arc<AppState>,
+    user: &User,
+    body: &str,
+) -> Option<ReplyResolution> {
+    let (code, rest) = parse_coded_reply(body)?;
+    let context = match ReplyContextsRepository::new(state.pg_pool.clone()).find_by_code(
+        user.id,
+        &code,
+        now() - MAX_AGE_SECS,
+    ) {
+        Ok(Some(context)) => context,
+        Ok(None) => return None,
+        Err(e) => {
+            warn!(
+                "reply_context user={} lookup of {} failed: {}",
+                user.id, code, e
+            );
+            return None;
+        }
+    };
+    info!(
+        "reply_context user={} code={} kind={} rest='{}'",
+        user.id, context.code, context.kind, rest
+    );
+
+    let handled = match context.kind.as_str() {
+        KIND_ALERT => resolve_alert(state, user, &context, &rest),
+        KIND_COMMITMENT => resolve_commitment(state, user, &context, &rest).await,
+        KIND_REPLY_WATCH if is_stop(&rest) => Some(stop_reply_watch(state, user, &context)),
+        KIND_RULE if is_stop(&rest) => Some(pause_rule(state, user, &context)),
+        _ => None,
+    };
+    Some(match handled {
+        Some(text) => ReplyResolution::Handled(text),
+        None => ReplyResolution::Agent(agent_body(&context, &rest)),
+    })
+}
+
+fn resolve_alert(
+    state: &Arc<AppState>,
+    user: &User,
+    context: &ReplyContext,
+    rest: &str,
+) -> Option<String> {
+    let worth_it = alert_answer(rest)?;
+    let sid = context.target_ref.as_deref()?;
+    let alert = match state.user_repository.system_alert_by_sid(user.id, sid) {
+        Ok(Some(alert)) => alert,
+        Ok(None) => return Some(format!("I can't find the alert #{} anymore.", context.code)),
+        Err(e) => {
+            warn!("reply_context user={} alert lookup failed: {}", user.id, e);
+            return None;
+        }
+    };
+    match state
+        .user_repository
+        .has_system_alert_feedback_for(user.id, alert.id)
+    {
+        Ok(true) => Some("Already got your rating for that alert.".to_string()),
+        Ok(false) => Some(crate::proactive::alert_feedback::record_feedback(
+            state, user, &alert, worth_it,
+        )),
+        Err(e) => {
+            warn!(
+                "reply_context user={} alert={} duplicate check failed: {}",
+                user.id, alert.id, e
+            );
+            None
+        }
+    }
+}
+
+async fn resolve_commitment(
+    state: &Arc<AppState>,
+    user: &User,
+    context: &ReplyContext,
+    rest: &str,
+) -> Option<String> {
+    let reply = commitment_answer(rest)?;
+    let prompt_id = context.target_id?;
+    match state
+        .commitment_repository
+        .find_prompt_for_user(user.id, prompt_id)
+    {
+        Ok(Some(prompt)) => {
+            Some(crate::proactive::commitment_replies::apply_reply(state, &prompt, reply).await)
+        }
+        Ok(None) => Some(format!(
+            "I can't find the prompt #{} anymore.",
+            context.code
+        )),
+        Err(e) => {
+            warn!(
+                "reply_context user={} prompt={} lookup failed: {}",
+                user.id, prompt_id, e
+            );
+            None
+        }
+    }
+}
+
+fn stop_reply_watch(state: &Arc<AppState>, user: &User, context: &ReplyContext) -> String {
+    let repo = &state.pending_reply_watches_repository;
+    let active = match (context.target_id, context.target_ref.as_deref()) {
+        (None, Some(room_id)) => repo.find_active_bridge(user.id, room_id),
+        (Some(connection_id), Some(sender)) => {
+            repo.find_active_email(user.id, connection_id, sender)
+        }
+        _ => Ok(None),
+    };
+    match active {
+        Ok(Some(watch)) => match repo.delete_for_user(user.id, watch.id) {
+            Ok(_) => format!(
+                "Stopped watching for replies from {}.",
+                watch.contact_display_name
+            ),
+            Err(e) => {
+                warn!(
+                    "reply_context user={} watch={} delete failed: {}",
+                    user.id, watch.id, e
+                );
+                "Couldn't stop that reply watch right now.".to_string()
+            }
+        },
+        Ok(None) => "That reply watch has already ended.".to_string(),
+        Err(e) => {
+            warn!("reply_context user={} watch lookup failed: {}", user.id, e);
+            "Couldn't stop that reply watch right now.".to_string()
+        }
+    }
+}
+
+fn pause_rule(state: &Arc<AppState>, user: &User, context: &ReplyContext) -> String {
+    let Some(rule_id) = context.target_id else {
+        return "That rule is gone.".to_string();
+    };
+    let rule = match state.ontology_repository.get_rule(user.id, rule_id) {
+        Ok(rule) => rule,
+        Err(diesel::result::Error::NotFound) => return "That rule is gone.".to_string(),
+        Err(e) => {
+            warn!(
+                "reply_context user={} rule={} lookup failed: {}",
+                user.id, rule_id, e
+            );
+            return "Couldn't pause that rule right now.".to_string();
+        }
+    };
+    if rule.status != "active" {
+        return format!("\"{}\" isn't running.", rule.name);
+    }
+    match state
+        .ontology_repository
+        .update_rule_status(rule.id, "paused")
+    {
+        Ok(()) => format!(
+            "Paused \"{}\". Turn it back on from the dashboard.",
+            rule.name
+        ),
+        Err(e) => {
+            warn!(
+                "reply_context user={} rule={} pause failed: {}",
+                user.id, rule.id, e
+            );
+            "Couldn't pause that rule right now.".to_string()
+        }
+    }
+}
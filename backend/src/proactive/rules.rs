@@ -1,19 +1,24 @@
 //! Rule evaluation engine: flow-based evaluation tree
 //!
 //! Every rule has a `flow_config` JSON column containing a FlowNode tree.
-//! Node types: llm_condition, keyword_condition, action.
+//! Node types: llm_condition, keyword_condition, structured_condition,
+//! action, plus the control nodes sequence, parallel, delay and for_each.
 //! The engine recursively walks the tree, evaluating conditions and executing actions.
+//! A delay node suspends the rest of its flow into `ont_rule_continuations`;
+//! the per-minute rule job resumes it via `resume_continuation`.
 
 use std::sync::Arc;
 
-use chrono::Datelike;
 use openai_api_rs::v1::{chat_completion, types};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{error, info, warn};
 
 use crate::context::ContextBuilder;
-use crate::models::ontology_models::OntRule;
+use crate::models::ontology_models::{NewOntRuleContinuation, OntRule, OntRuleContinuation};
+use crate::proactive::conditions::{MetricKey, NumericMetric, Predicate, PredicateInput};
+use crate::proactive::reply_context::ReplyTarget;
+use crate::proactive::schedule::SchedulePattern;
 use crate::proactive::utils::{
     compact_email_notification, notification_meta_from_snapshot, send_notification_with_context,
 };
@@ -30,6 +35,9 @@ fn default_platform() -> String {
 fn default_limit() -> i64 {
     50
 }
+fn default_calendar_days() -> u32 {
+    1
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -55,7 +63,17 @@ pub enum FetchSource {
         #[serde(default)]
         args: String,
     },
+    /// A resource read from one of the user's MCP servers (resources/read).
+    McpResource {
+        server: String,
+        uri: String,
+    },
     Events,
+    /// Upcoming events from the user's CalDAV calendars.
+    Calendar {
+        #[serde(default = "default_calendar_days")]
+        days: u32,
+    },
 }
 
 // ---------------------------------------------------------------------------
@@ -77,14 +95,144 @@ pub enum FlowNode {
         true_branch: Box<Option<FlowNode>>,
         false_branch: Box<Option<FlowNode>>,
     },
+    /// Deterministic check over the trigger snapshot, local time and
+    /// numeric readings. No LLM call, so it costs no tokens.
+    StructuredCondition {
+        condition: Predicate,
+        true_branch: Box<Option<FlowNode>>,
+        false_branch: Box<Option<FlowNode>>,
+    },
     Action {
         action_type: String,
         config: serde_json::Value,
     },
+    /// Run steps in order. If a step suspends on a delay, the remaining
+    /// steps are persisted with it and run after the delay.
+    Sequence { steps: Vec<FlowNode> },
+    /// Run branches concurrently. A delay inside a branch only suspends
+    /// that branch; the others carry on.
+    Parallel { branches: Vec<FlowNode> },
+    /// Suspend the flow for `seconds`, then resume at `next`.
+    Delay {
+        seconds: i64,
+        #[serde(default)]
+        next: Box<Option<FlowNode>>,
+    },
+    /// Run `body` once per item returned by `source`. Each item is
+    /// exposed to conditions and actions through `FlowContext::item`.
+    ForEach {
+        source: FetchSource,
+        #[serde(default = "default_for_each_limit")]
+        limit: usize,
+        body: Box<Option<FlowNode>>,
+    },
 }
 
+fn default_for_each_limit() -> usize {
+    10
+}
+
+pub const MAX_CONDITION_DEPTH: usize = 3;
+/// Upper bound on nodes in one flow, so parallel fan-out stays cheap.
+pub const MAX_FLOW_NODES: usize = 32;
+pub const MIN_DELAY_SECONDS: i64 = 60;
+pub const MAX_DELAY_SECONDS: i64 = 30 * 86400;
+pub const MAX_FOR_EACH_ITEMS: usize = 25;
+
 impl FlowNode {
-    /// Returns the depth of the deepest condition chain (actions don't count).
+    /// Direct child nodes, in evaluation order.
+    pub fn children(&self) -> Vec<&FlowNode> {
+        match self {
+            FlowNode::LlmCondition {
+                true_branch,
+                false_branch,
+                ..
+            }
+            | FlowNode::KeywordCondition {
+                true_branch,
+                false_branch,
+                ..
+            }
+            | FlowNode::StructuredCondition {
+                true_branch,
+                false_branch,
+                ..
+            } => true_branch
+                .as_ref()
+                .iter()
+                .chain(false_branch.as_ref().iter())
+                .collect(),
+            FlowNode::Action { .. } => Vec::new(),
+            FlowNode::Sequence { steps } => steps.iter().collect(),
+            FlowNode::Parallel { branches } => branches.iter().collect(),
+            FlowNode::Delay { next, .. } => next.as_ref().iter().collect(),
+            FlowNode::ForEach { body, .. } => body.as_ref().iter().collect(),
+        }
+    }
+
+    /// Total number of nodes in the tree, including this one.
+    pub fn node_count(&self) -> usize {
+        1 + self
+            .children()
+            .into_iter()
+            .map(|n| n.node_count())
+            .sum::<usize>()
+    }
+
+    /// Check the structural limits enforced when a rule is saved.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.condition_depth() > MAX_CONDITION_DEPTH {
+            return Err(format!(
+                "Flow config exceeds max depth of {} conditions",
+                MAX_CONDITION_DEPTH
+            ));
+        }
+        if self.node_count() > MAX_FLOW_NODES {
+            return Err(format!(
+                "Flow config exceeds max of {} nodes",
+                MAX_FLOW_NODES
+            ));
+        }
+        self.validate_node(false)
+    }
+
+    fn validate_node(&self, in_loop: bool) -> Result<(), String> {
+        match self {
+            FlowNode::Sequence { steps } if steps.is_empty() => {
+                return Err("Sequence needs at least one step".to_string());
+            }
+            FlowNode::Parallel { branches } if branches.is_empty() => {
+                return Err("Parallel needs at least one branch".to_string());
+            }
+            FlowNode::Delay { seconds, .. }
+                if !(MIN_DELAY_SECONDS..=MAX_DELAY_SECONDS).contains(seconds) =>
+            {
+                return Err(format!(
+                    "Delay must be between {} seconds and {} days",
+                    MIN_DELAY_SECONDS,
+                    MAX_DELAY_SECONDS / 86400
+                ));
+            }
+            FlowNode::ForEach { .. } if in_loop => {
+                return Err("For-each loops cannot be nested".to_string());
+            }
+            FlowNode::ForEach { limit, .. } if !(1..=MAX_FOR_EACH_ITEMS).contains(limit) => {
+                return Err(format!(
+                    "For-each limit must be between 1 and {}",
+                    MAX_FOR_EACH_ITEMS
+                ));
+            }
+            FlowNode::StructuredCondition { condition, .. } => condition.validate()?,
+            _ => {}
+        }
+        let in_loop = in_loop || matches!(self, FlowNode::ForEach { .. });
+        self.children()
+            .into_iter()
+            .try_for_each(|child| child.validate_node(in_loop))
+    }
+
+    /// Returns the depth of the deepest condition chain (actions and
+    /// control nodes don't count).
     pub fn condition_depth(&self) -> usize {
         match self {
             FlowNode::LlmCondition {
@@ -96,6 +244,11 @@ impl FlowNode {
                 true_branch,
                 false_branch,
                 ..
+            }
+            | FlowNode::StructuredCondition {
+                true_branch,
+                false_branch,
+                ..
             } => {
                 let t = true_branch
                     .as_ref()
@@ -110,10 +263,92 @@ impl FlowNode {
                 1 + t.max(f)
             }
             FlowNode::Action { .. } => 0,
+            FlowNode::Sequence { .. }
+            | FlowNode::Parallel { .. }
+            | FlowNode::Delay { .. }
+            | FlowNode::ForEach { .. } => self
+                .children()
+                .into_iter()
+                .map(|n| n.condition_depth())
+                .max()
+                .unwrap_or(0),
         }
     }
 }
 
+// ---------------------------------------------------------------------------
+// FlowContext: per-run state threaded down the tree
+// ---------------------------------------------------------------------------
+
+/// Everything a node needs besides the rule itself. Persisted alongside a
+/// suspended subtree so a resumed flow sees what the original run saw.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlowContext {
+    pub trigger_context: String,
+    #[serde(default)]
+    pub trigger_snapshot: Option<serde_json::Value>,
+    /// LLM-generated message from a parent condition, used by actions below it.
+    #[serde(default)]
+    pub prev_message: Option<String>,
+    /// Extra LLM-generated params for tool_call actions.
+    #[serde(default)]
+    pub prev_extras: Option<HashMap<String, serde_json::Value>>,
+    /// The current item when running inside a for_each body.
+    #[serde(default)]
+    pub item: Option<String>,
+}
+
+impl FlowContext {
+    pub fn new(trigger_context: &str, trigger_snapshot: Option<&serde_json::Value>) -> Self {
+        Self {
+            trigger_context: trigger_context.to_string(),
+            trigger_snapshot: trigger_snapshot.cloned(),
+            ..Default::default()
+        }
+    }
+
+    /// Text conditions are evaluated against: the trigger, plus the current
+    /// loop item if there is one.
+    pub fn condition_input(&self) -> String {
+        match self.item {
+            Some(ref item) => format!("{}\n\nCurrent item:\n{}", self.trigger_context, item),
+            None => self.trigger_context.clone(),
+        }
+    }
+}
+
+/// A subtree waiting on a delay node.
+#[derive(Debug, Clone)]
+pub struct Suspension {
+    pub resume_at: i32,
+    pub node: Option<FlowNode>,
+    pub context: FlowContext,
+}
+
+impl Suspension {
+    /// Queue `rest` to run after the suspended subtree. Used by sequences
+    /// so the steps after a delay wait for it too.
+    pub fn then_run(mut self, rest: &[FlowNode]) -> Self {
+        if rest.is_empty() {
+            return self;
+        }
+        let mut steps: Vec<FlowNode> = self.node.take().into_iter().collect();
+        steps.extend(rest.iter().cloned());
+        self.node = Some(if steps.len() == 1 {
+            steps.remove(0)
+        } else {
+            FlowNode::Sequence { steps }
+        });
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum FlowOutcome {
+    Completed,
+    Suspended(Box<Suspension>),
+}
+
 // ---------------------------------------------------------------------------
 // Prompt templates: stored as "template:<id>" in flow_config, resolved at eval time
 // ---------------------------------------------------------------------------
@@ -485,19 +720,7 @@ pub async fn evaluate_and_execute(
         }
     }
 
-    // Check daily token budget per user (166K tokens/day ~ 5M/month)
-    let day_start = (now / 86400) * 86400;
-    let used_today = state
-        .llm_usage_repository
-        .get_user_tokens_since(rule.user_id, day_start)
-        .unwrap_or(0);
-    if !crate::handlers::rule_handlers::is_always_show_rule(rule) && used_today >= 166_000 {
-        tracing::warn!(
-            "User {} exceeded daily token budget ({}/166000), skipping rule {}",
-            rule.user_id,
-            used_today,
-            rule.id
-        );
+    if exceeds_daily_token_budget(state, rule, now) {
         return;
     }
 
@@ -510,18 +733,15 @@ pub async fn evaluate_and_execute(
     if let Some(ref flow_json) = rule.flow_config {
         match serde_json::from_str::<FlowNode>(flow_json) {
             Ok(root) => {
-                if let Err(e) = evaluate_flow(
-                    state,
-                    rule,
-                    trigger_context,
-                    trigger_snapshot,
-                    &root,
-                    None,
-                    None,
-                )
-                .await
-                {
-                    error!("Rule {} flow evaluation failed: {}", rule.id, e);
+                let ctx = FlowContext::new(trigger_context, trigger_snapshot);
+                match evaluate_flow(state, rule, &root, &ctx).await {
+                    Ok(FlowOutcome::Suspended(suspension)) => {
+                        persist_suspension(state, rule, *suspension);
+                    }
+                    Ok(FlowOutcome::Completed) => {}
+                    Err(e) => {
+                        error!("Rule {} flow evaluation failed: {}", rule.id, e);
+                    }
                 }
             }
             Err(e) => {
@@ -533,23 +753,164 @@ pub async fn evaluate_and_execute(
     }
 }
 
+/// Check the daily token budget per user (166K tokens/day ~ 5M/month).
+/// Always-show rules are exempt since they don't call the LLM.
+fn exceeds_daily_token_budget(state: &Arc<AppState>, rule: &OntRule, now: i32) -> bool {
+    let day_start = (now / 86400) * 86400;
+    let used_today = state
+        .llm_usage_repository
+        .get_user_tokens_since(rule.user_id, day_start)
+        .unwrap_or(0);
+    if !crate::handlers::rule_handlers::is_always_show_rule(rule) && used_today >= 166_000 {
+        tracing::warn!(
+            "User {} exceeded daily token budget ({}/166000), skipping rule {}",
+            rule.user_id,
+            used_today,
+            rule.id
+        );
+        return true;
+    }
+    false
+}
+
+// ---------------------------------------------------------------------------
+// Delay continuations
+// ---------------------------------------------------------------------------
+
+/// Persist a suspended subtree so the rule job can resume it at `resume_at`.
+fn persist_suspension(state: &Arc<AppState>, rule: &OntRule, suspension: Suspension) {
+    let Some(node) = suspension.node else {
+        // A delay at the very end of a flow has nothing left to run.
+        return;
+    };
+    let (flow_node, flow_context) = match (
+        serde_json::to_string(&node),
+        serde_json::to_string(&suspension.context),
+    ) {
+        (Ok(n), Ok(c)) => (n, c),
+        (Err(e), _) | (_, Err(e)) => {
+            error!("Rule {} continuation serialization failed: {}", rule.id, e);
+            return;
+        }
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i32;
+    let new_continuation = NewOntRuleContinuation {
+        rule_id: rule.id,
+        user_id: rule.user_id,
+        flow_node,
+        flow_context,
+        resume_at: suspension.resume_at,
+        status: "pending".to_string(),
+        created_at: now,
+        updated_at: now,
+    };
+    match state
+        .ontology_repository
+        .create_rule_continuation(&new_continuation)
+    {
+        Ok(c) => info!(
+            "Rule {} ({}): suspended until {} (continuation {})",
+            rule.id, rule.name, c.resume_at, c.id
+        ),
+        Err(e) => error!("Rule {} failed to persist continuation: {}", rule.id, e),
+    }
+}
+
+/// Resume a continuation claimed by the rule job. The rule is re-read so a
+/// pause or expiry since the delay started cancels the rest of the flow.
+/// The claim is at-most-once: if this dies midway the row stays "running"
+/// and is never resumed again.
+pub async fn resume_continuation(state: &Arc<AppState>, continuation: OntRuleContinuation) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i32;
+    let final_status = match state
+        .ontology_repository
+        .get_rule(continuation.user_id, continuation.rule_id)
+    {
+        Ok(rule) if rule.status == "paused" || rule.status == "expired" => {
+            info!(
+                "Rule {} is {}, cancelling continuation {}",
+                rule.id, rule.status, continuation.id
+            );
+            "cancelled"
+        }
+        Ok(rule) => {
+            let node = serde_json::from_str::<FlowNode>(&continuation.flow_node);
+            let ctx = serde_json::from_str::<FlowContext>(&continuation.flow_context);
+            match (node, ctx) {
+                (Ok(node), Ok(ctx)) => {
+                    if exceeds_daily_token_budget(state, &rule, now) {
+                        "cancelled"
+                    } else {
+                        match evaluate_flow(state, &rule, &node, &ctx).await {
+                            Ok(FlowOutcome::Suspended(suspension)) => {
+                                persist_suspension(state, &rule, *suspension);
+                            }
+                            Ok(FlowOutcome::Completed) => {}
+                            Err(e) => {
+                                error!(
+                                    "Rule {} continuation {} failed: {}",
+                                    rule.id, continuation.id, e
+                                );
+                            }
+                        }
+                        "done"
+                    }
+                }
+                _ => {
+                    error!(
+                        "Rule {} continuation {} has an invalid payload",
+                        rule.id, continuation.id
+                    );
+                    "cancelled"
+                }
+            }
+        }
+        Err(e) => {
+            warn!(
+                "Continuation {} references missing rule {}: {}",
+                continuation.id, continuation.rule_id, e
+            );
+            "cancelled"
+        }
+    };
+    match state
+        .ontology_repository
+        .finish_rule_continuation(continuation.id, final_status)
+    {
+        Ok(true) => {}
+        Ok(false) => warn!(
+            "Continuation {} was no longer running when marked {}",
+            continuation.id, final_status
+        ),
+        Err(e) => error!(
+            "Failed to mark continuation {} {}: {}",
+            continuation.id, final_status, e
+        ),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Recursive flow evaluation
 // ---------------------------------------------------------------------------
 
 /// Walk the flow tree recursively.
-/// `prev_message` carries the LLM-generated message from a parent condition
-/// so that downstream action nodes can use it.
-/// `prev_extras` carries extra LLM-generated params for tool_call actions.
+/// `ctx.prev_message` carries the LLM-generated message from a parent
+/// condition so that downstream action nodes can use it, and
+/// `ctx.prev_extras` carries extra LLM-generated params for tool_call actions.
+/// Returns `Suspended` when a delay node paused the flow; the caller decides
+/// whether to persist it or fold more work into it (see `Sequence`).
 async fn evaluate_flow(
     state: &Arc<AppState>,
     rule: &OntRule,
-    trigger_context: &str,
-    trigger_snapshot: Option<&serde_json::Value>,
     node: &FlowNode,
-    prev_message: Option<&str>,
-    prev_extras: Option<&HashMap<String, serde_json::Value>>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ctx: &FlowContext,
+) -> Result<FlowOutcome, Box<dyn std::error::Error + Send + Sync>> {
     match node {
         FlowNode::LlmCondition {
             prompt,
@@ -567,18 +928,9 @@ async fn evaluate_flow(
                     rule.id, rule.name
                 );
                 if let Some(branch) = true_branch.as_ref() {
-                    return Box::pin(evaluate_flow(
-                        state,
-                        rule,
-                        trigger_context,
-                        trigger_snapshot,
-                        branch,
-                        prev_message,
-                        prev_extras,
-                    ))
-                    .await;
+                    return Box::pin(evaluate_flow(state, rule, branch, ctx)).await;
                 }
-                return Ok(());
+                return Ok(FlowOutcome::Completed);
             }
 
             // Resolve template prompts to actual text
@@ -589,7 +941,7 @@ async fn evaluate_flow(
             let result = call_llm_condition(
                 state,
                 rule,
-                trigger_context,
+                &ctx.condition_input(),
                 &resolved_prompt,
                 &prefetched,
                 extra_params.as_ref(),
@@ -601,27 +953,19 @@ async fn evaluate_flow(
                 } else {
                     Some(result.extra)
                 };
-                (
-                    true_branch,
-                    result.message.as_deref().map(|s| s.to_string()),
-                    extras,
-                )
+                (true_branch, result.message, extras)
             } else {
                 (false_branch, None, None)
             };
             if let Some(branch) = next.as_ref() {
-                let effective_msg = msg.as_deref().or(prev_message);
-                let effective_extras = extras.as_ref().or(prev_extras);
-                Box::pin(evaluate_flow(
-                    state,
-                    rule,
-                    trigger_context,
-                    trigger_snapshot,
-                    branch,
-                    effective_msg,
-                    effective_extras,
-                ))
-                .await?;
+                let mut branch_ctx = ctx.clone();
+                if msg.is_some() {
+                    branch_ctx.prev_message = msg;
+                }
+                if extras.is_some() {
+                    branch_ctx.prev_extras = extras;
+                }
+                return Box::pin(evaluate_flow(state, rule, branch, &branch_ctx)).await;
             }
         }
         FlowNode::KeywordCondition {
@@ -630,21 +974,13 @@ async fn evaluate_flow(
             false_branch,
         } => {
             let matched = !keyword.is_empty()
-                && trigger_context
+                && ctx
+                    .condition_input()
                     .to_lowercase()
                     .contains(&keyword.to_lowercase());
             let next = if matched { true_branch } else { false_branch };
             if let Some(branch) = next.as_ref() {
-                Box::pin(evaluate_flow(
-                    state,
-                    rule,
-                    trigger_context,
-                    trigger_snapshot,
-                    branch,
-                    prev_message,
-                    prev_extras,
-                ))
-                .await?;
+                return Box::pin(evaluate_flow(state, rule, branch, ctx)).await;
             } else if matched {
                 info!(
                     "Rule {} ({}): keyword matched but no true_branch",
@@ -652,6 +988,24 @@ async fn evaluate_flow(
                 );
             }
         }
+        FlowNode::StructuredCondition {
+            condition,
+            true_branch,
+            false_branch,
+        } => {
+            let matched = check_structured_condition(state, rule, condition, ctx).await;
+            info!(
+                "Rule {} ({}): structured condition [{}] -> {}",
+                rule.id,
+                rule.name,
+                condition.describe(),
+                matched
+            );
+            let next = if matched { true_branch } else { false_branch };
+            if let Some(branch) = next.as_ref() {
+                return Box::pin(evaluate_flow(state, rule, branch, ctx)).await;
+            }
+        }
         FlowNode::Action {
             action_type,
             config,
@@ -672,16 +1026,19 @@ async fn evaluate_flow(
             //      string ("Schedule trigger fired at <timestamp>")
             //      was sent instead.
             //
-            //   3. `trigger_snapshot` → `format_snapshot_message` —
+            //   3. `item` — the current for_each item, so a loop that
+            //      notifies without a fixed message sends each item.
+            //
+            //   4. `trigger_snapshot` → `format_snapshot_message` —
             //      formatted summary of an ontology change event (new
             //      message, etc.). Applies when the rule is driven by
             //      a snapshot and neither of the above is set.
             //
-            //   4. `trigger_context` — raw last-resort fallback. Only
+            //   5. `trigger_context` — raw last-resort fallback. Only
             //      reached for schedule-triggered rules that have no
             //      LLM step and no configured message.
             let fallback;
-            let message: &str = if let Some(msg) = prev_message {
+            let message: &str = if let Some(ref msg) = ctx.prev_message {
                 msg
             } else if let Some(cfg_msg) = config
                 .get("message")
@@ -689,25 +1046,104 @@ async fn evaluate_flow(
                 .filter(|s| !s.is_empty())
             {
                 cfg_msg
-            } else if let Some(snap) = trigger_snapshot {
+            } else if let Some(ref item) = ctx.item {
+                item
+            } else if let Some(ref snap) = ctx.trigger_snapshot {
                 fallback = format_snapshot_message(snap);
                 &fallback
             } else {
-                trigger_context
+                &ctx.trigger_context
             };
             execute_flow_action(
                 state,
                 rule,
-                trigger_snapshot,
+                ctx.trigger_snapshot.as_ref(),
                 action_type,
                 config,
                 message,
-                prev_extras,
+                ctx.prev_extras.as_ref(),
             )
             .await;
         }
+        FlowNode::Sequence { steps } => {
+            for (i, step) in steps.iter().enumerate() {
+                if let FlowOutcome::Suspended(suspension) =
+                    Box::pin(evaluate_flow(state, rule, step, ctx)).await?
+                {
+                    return Ok(FlowOutcome::Suspended(Box::new(
+                        suspension.then_run(&steps[i + 1..]),
+                    )));
+                }
+            }
+        }
+        FlowNode::Parallel { branches } => {
+            let runs = branches
+                .iter()
+                .map(|branch| Box::pin(evaluate_flow(state, rule, branch, ctx)));
+            let results = futures::future::join_all(runs).await;
+            for (i, result) in results.into_iter().enumerate() {
+                match result {
+                    Ok(FlowOutcome::Suspended(suspension)) => {
+                        persist_suspension(state, rule, *suspension);
+                    }
+                    Ok(FlowOutcome::Completed) => {}
+                    Err(e) => {
+                        warn!(
+                            "Rule {} ({}): parallel branch {} failed: {}",
+                            rule.id, rule.name, i, e
+                        );
+                    }
+                }
+            }
+        }
+        FlowNode::Delay { seconds, next } => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i32;
+            let seconds = (*seconds).clamp(MIN_DELAY_SECONDS, MAX_DELAY_SECONDS) as i32;
+            return Ok(FlowOutcome::Suspended(Box::new(Suspension {
+                resume_at: now.saturating_add(seconds),
+                node: next.as_ref().clone(),
+                context: ctx.clone(),
+            })));
+        }
+        FlowNode::ForEach {
+            source,
+            limit,
+            body,
+        } => {
+            let Some(body) = body.as_ref() else {
+                return Ok(FlowOutcome::Completed);
+            };
+            let items = fetch_items(state, rule, source, *limit).await;
+            info!(
+                "Rule {} ({}): for_each over {} item(s)",
+                rule.id,
+                rule.name,
+                items.len()
+            );
+            for item in items {
+                let mut item_ctx = ctx.clone();
+                item_ctx.item = Some(item);
+                // Each item runs to completion (or suspends) on its own, so
+                // one failing item doesn't stop the rest of the loop.
+                match Box::pin(evaluate_flow(state, rule, body, &item_ctx)).await {
+                    Ok(FlowOutcome::Suspended(suspension)) => {
+                        persist_suspension(state, rule, *suspension);
+                    }
+                    Ok(FlowOutcome::Completed) => {}
+                    Err(e) => {
+                        warn!(
+                            "Rule {} ({}): for_each item failed: {}",
+                            rule.id, rule.name, e
+                        );
+                    }
+                }
+            }
+        }
     }
-    Ok(())
+    Ok(FlowOutcome::Completed)
 }
 
 /// Peek at a branch node: if it's a tool_call Action, look up the tool's
@@ -741,6 +1177,97 @@ pub(crate) fn extract_tool_params(
     None
 }
 
+// ---------------------------------------------------------------------------
+// Structured condition evaluation
+// ---------------------------------------------------------------------------
+
+/// Evaluate a structured predicate against the flow context. Numeric
+/// readings are fetched only when evaluation reaches a comparison that
+/// needs them, and each one at most once.
+pub(crate) async fn check_structured_condition(
+    state: &Arc<AppState>,
+    rule: &OntRule,
+    condition: &Predicate,
+    ctx: &FlowContext,
+) -> bool {
+    let body = ctx
+        .item
+        .as_deref()
+        .or_else(|| {
+            ctx.trigger_snapshot
+                .as_ref()
+                .and_then(|snap| snap.get("content"))
+                .and_then(|v| v.as_str())
+        })
+        .unwrap_or(&ctx.trigger_context);
+    let now = user_local_now(state, rule.user_id);
+    let mut metrics = HashMap::new();
+    loop {
+        let outcome = condition.evaluate(&PredicateInput {
+            snapshot: ctx.trigger_snapshot.as_ref(),
+            body,
+            now,
+            metrics: &metrics,
+        });
+        match outcome {
+            Ok(result) => return result,
+            Err(key) => {
+                let reading = fetch_metric(state, rule, &key).await;
+                metrics.insert(key, reading);
+            }
+        }
+    }
+}
+
+/// Current time in the user's saved timezone, falling back to UTC.
+fn user_local_now(state: &Arc<AppState>, user_id: i32) -> chrono::DateTime<chrono_tz::Tz> {
+    let tz = state
+        .user_core
+        .get_user_info(user_id)
+        .ok()
+        .and_then(|info| info.timezone)
+        .and_then(|name| name.parse::<chrono_tz::Tz>().ok())
+        .unwrap_or(chrono_tz::UTC);
+    chrono::Utc::now().with_timezone(&tz)
+}
+
+/// Fetch one numeric reading. `None` if it can't be fetched, which makes
+/// its comparisons false.
+async fn fetch_metric(state: &Arc<AppState>, rule: &OntRule, key: &MetricKey) -> Option<f64> {
+    let reading = match key.0 {
+        NumericMetric::TeslaBattery => {
+            crate::tool_call_utils::tesla::get_battery_level(state, rule.user_id).await
+        }
+        NumericMetric::WeatherTemperature => {
+            let location = if !key.1.is_empty() {
+                Some(key.1.clone())
+            } else {
+                state
+                    .user_core
+                    .get_user_info(rule.user_id)
+                    .ok()
+                    .and_then(|i| i.location)
+            };
+            match location {
+                Some(loc) => crate::utils::tool_exec::get_current_temperature(&loc)
+                    .await
+                    .map_err(|e| e.to_string()),
+                None => Err("no location set".to_string()),
+            }
+        }
+    };
+    match reading {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!(
+                "Rule {} ({}): no reading for {:?}: {}",
+                rule.id, rule.name, key.0, e
+            );
+            None
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Prefetch sources helper
 // ---------------------------------------------------------------------------
@@ -890,21 +1417,7 @@ pub(crate) async fn prefetch_sources(
                     let formatted: Vec<String> = events
                         .iter()
                         .map(|e| {
-                            let deadline_tag = e
-                                .due_at
-                                .map(|due_at| {
-                                    if now > due_at {
-                                        " [OVERDUE]".to_string()
-                                    } else {
-                                        let days_left = (due_at - now) / 86400;
-                                        if days_left <= 2 {
-                                            format!(" [due in {} days]", days_left)
-                                        } else {
-                                            String::new()
-                                        }
-                                    }
-                                })
-                                .unwrap_or_default();
+                            let deadline_tag = deadline_tag(e.due_at, now);
                             let linked_messages = state
                                 .ontology_repository
                                 .get_messages_for_event(rule.user_id, e.id)
@@ -946,11 +1459,198 @@ pub(crate) async fn prefetch_sources(
                     ));
                 }
             }
+            FetchSource::Calendar { days } => {
+                let events = upcoming_calendar_events(state, rule, *days).await;
+                if !events.is_empty() {
+                    prefetched.push_str(&format!(
+                        "\n\n--- Upcoming calendar events ---\n{}",
+                        events.join("\n")
+                    ));
+                }
+            }
+            FetchSource::McpResource { server, uri } => {
+                match crate::tool_call_utils::mcp::read_mcp_resource(
+                    state,
+                    rule.user_id,
+                    server,
+                    uri,
+                )
+                .await
+                {
+                    Ok(text) if !text.trim().is_empty() => {
+                        prefetched.push_str(&format!(
+                            "\n\n--- MCP resource {}:{} ---\n{}",
+                            server, uri, text
+                        ));
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("Rule {} MCP resource fetch failed: {}", rule.id, e);
+                    }
+                }
+            }
         }
     }
     prefetched
 }
 
+/// Calendar events from now until `days` days ahead, one line each.
+async fn upcoming_calendar_events(state: &Arc<AppState>, rule: &OntRule, days: u32) -> Vec<String> {
+    let start = chrono::Utc::now();
+    let end = start + chrono::Duration::days(days.clamp(1, 31) as i64);
+    match crate::services::calendar_service::events_between(state, rule.user_id, start, end).await {
+        Ok(events) => {
+            let tz = crate::services::calendar_service::user_timezone(state, rule.user_id);
+            events
+                .iter()
+                .map(|e| format!("{} ({})", e.event.describe(&tz), e.calendar))
+                .collect()
+        }
+        Err(e) => {
+            warn!("Rule {} calendar fetch failed: {}", rule.id, e);
+            Vec::new()
+        }
+    }
+}
+
+/// " [OVERDUE]" / " [due in N days]" marker for a tracked obligation, empty
+/// when the deadline is further out than two days or unset.
+fn deadline_tag(due_at: Option<i32>, now: i32) -> String {
+    due_at
+        .map(|due_at| {
+            if now > due_at {
+                " [OVERDUE]".to_string()
+            } else {
+                let days_left = (due_at - now) / 86400;
+                if days_left <= 2 {
+                    format!(" [due in {} days]", days_left)
+                } else {
+                    String::new()
+                }
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Fetch the items a for_each node iterates over, at most `limit`.
+/// List-shaped sources yield one item per email, chat message, tracked
+/// obligation or calendar event; an MCP tool that returns a JSON array
+/// yields one item per element. Anything else is a single item holding the prefetched text.
+pub(crate) async fn fetch_items(
+    state: &Arc<AppState>,
+    rule: &OntRule,
+    source: &FetchSource,
+    limit: usize,
+) -> Vec<String> {
+    let limit = limit.clamp(1, MAX_FOR_EACH_ITEMS);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i32;
+    let twelve_hours_ago = now - 43200;
+    let mut items: Vec<String> = match source {
+        FetchSource::Email => state
+            .ontology_repository
+            .get_recent_messages_filtered(
+                rule.user_id,
+                Some("email"),
+                twelve_hours_ago,
+                limit as i64,
+            )
+            .unwrap_or_default()
+            .iter()
+            .map(|m| format!("From: {}\n{}", m.sender_name, m.content))
+            .collect(),
+        FetchSource::Chat {
+            platform,
+            limit: chat_limit,
+        } => {
+            let plat = if platform == "all" {
+                None
+            } else {
+                Some(platform.as_str())
+            };
+            state
+                .ontology_repository
+                .get_recent_messages_filtered(
+                    rule.user_id,
+                    plat,
+                    twelve_hours_ago,
+                    (*chat_limit).min(limit as i64),
+                )
+                .unwrap_or_default()
+                .iter()
+                .map(|m| format!("[{}] {}: {}", m.platform, m.sender_name, m.content))
+                .collect()
+        }
+        FetchSource::Events => state
+            .ontology_repository
+            .get_active_events(rule.user_id)
+            .unwrap_or_default()
+            .iter()
+            .map(|e| {
+                format!(
+                    "[event_id={}] [status={}]{} {}",
+                    e.id,
+                    e.status,
+                    deadline_tag(e.due_at, now),
+                    e.description
+                )
+            })
+            .collect(),
+        FetchSource::Calendar { days } => upcoming_calendar_events(state, rule, *days).await,
+        FetchSource::Mcp { server, tool, args } => {
+            let tool_name = format!("mcp:{}:{}", server, tool);
+            let args_str = if args.is_empty() { "{}" } else { args };
+            let result = crate::tool_call_utils::mcp::handle_mcp_tool_call(
+                state,
+                rule.user_id,
+                &tool_name,
+                args_str,
+            )
+            .await;
+            split_json_items(result)
+        }
+        FetchSource::McpResource { server, uri } => {
+            match crate::tool_call_utils::mcp::read_mcp_resource(state, rule.user_id, server, uri)
+                .await
+            {
+                Ok(text) => split_json_items(text),
+                Err(e) => {
+                    warn!("Rule {} MCP resource fetch failed: {}", rule.id, e);
+                    Vec::new()
+                }
+            }
+        }
+        FetchSource::Weather { .. } | FetchSource::Internet { .. } | FetchSource::Tesla => {
+            let text = prefetch_sources(state, rule, std::slice::from_ref(source)).await;
+            let text = text.trim();
+            if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![text.to_string()]
+            }
+        }
+    };
+    items.truncate(limit);
+    items
+}
+
+/// A JSON array becomes one item per element; anything else is one item.
+fn split_json_items(result: String) -> Vec<String> {
+    match serde_json::from_str::<Vec<serde_json::Value>>(&result) {
+        Ok(values) => values
+            .into_iter()
+            .map(|v| match v {
+                serde_json::Value::String(text) => text,
+                other => other.to_string(),
+            })
+            .collect(),
+        Err(_) if result.trim().is_empty() => Vec::new(),
+        Err(_) => vec![result],
+    }
+}
+
 // ---------------------------------------------------------------------------
 // LLM condition evaluation
 // ---------------------------------------------------------------------------
@@ -1123,14 +1823,17 @@ async fn execute_flow_action(
             let notification_message = trigger_snapshot
                 .map(|snap| compact_email_notification(message, snap))
                 .unwrap_or_else(|| message.to_string());
-            let notification_meta = trigger_snapshot.and_then(notification_meta_from_snapshot);
+            let mut notification_meta = trigger_snapshot
+                .and_then(notification_meta_from_snapshot)
+                .unwrap_or_default();
+            notification_meta.reply_target = Some(ReplyTarget::Rule { rule_id: rule.id });
             let delivered = send_notification_with_context(
                 state,
                 rule.user_id,
                 &notification_message,
                 content_type,
                 None,
-                notification_meta,
+                Some(notification_meta),
             )
             .await;
             if delivered && crate::handlers::rule_handlers::is_always_show_rule(rule) {
@@ -1287,102 +1990,15 @@ async fn execute_flow_action(
 // ---------------------------------------------------------------------------
 
 /// Compute the next fire timestamp (UTC) for a recurring schedule pattern.
-/// Pattern formats: "daily HH:MM", "weekdays HH:MM", "weekly DAY HH:MM", "hourly"
+/// Accepts the legacy shorthand ("daily HH:MM", "weekdays HH:MM",
+/// "weekly DAY HH:MM", "hourly"), RFC 5545 RRULEs and 5-field cron; see
+/// `proactive::schedule` for the grammar and DST handling.
 pub fn compute_next_fire_at(pattern: &str, user_tz: &str) -> Option<i32> {
     let tz: chrono_tz::Tz = user_tz.parse().unwrap_or(chrono_tz::UTC);
-    let now = chrono::Utc::now().with_timezone(&tz);
-
-    let parts: Vec<&str> = pattern.splitn(2, ' ').collect();
-    if parts.is_empty() {
-        return None;
-    }
-
-    let frequency = parts[0];
-
-    match frequency {
-        "hourly" => {
-            let next = now + chrono::Duration::hours(1);
-            Some(next.with_timezone(&chrono::Utc).timestamp() as i32)
-        }
-        "daily" => {
-            let rest = parts.get(1)?;
-            let (hour, minute) = parse_hhmm(rest)?;
-            let today = now.date_naive().and_hms_opt(hour, minute, 0)?;
-            let next = if today > now.naive_local() {
-                today
-            } else {
-                now.date_naive().succ_opt()?.and_hms_opt(hour, minute, 0)?
-            };
-            let local = next.and_local_timezone(tz).earliest()?;
-            Some(local.with_timezone(&chrono::Utc).timestamp() as i32)
-        }
-        "weekdays" => {
-            let rest = parts.get(1)?;
-            let (hour, minute) = parse_hhmm(rest)?;
-            let mut candidate = now.date_naive();
-            if let Some(today_time) = candidate.and_hms_opt(hour, minute, 0) {
-                if today_time > now.naive_local() && candidate.weekday().num_days_from_monday() < 5
-                {
-                    let local = today_time.and_local_timezone(tz).earliest()?;
-                    return Some(local.with_timezone(&chrono::Utc).timestamp() as i32);
-                }
-            }
-            for _ in 0..7 {
-                candidate = candidate.succ_opt()?;
-                if candidate.weekday().num_days_from_monday() < 5 {
-                    let next = candidate.and_hms_opt(hour, minute, 0)?;
-                    let local = next.and_local_timezone(tz).earliest()?;
-                    return Some(local.with_timezone(&chrono::Utc).timestamp() as i32);
-                }
-            }
-            None
-        }
-        "weekly" => {
-            let rest = parts.get(1)?;
-            let sub_parts: Vec<&str> = rest.splitn(2, ' ').collect();
-            if sub_parts.len() < 2 {
-                return None;
-            }
-            let day_name = sub_parts[0].to_lowercase();
-            let (hour, minute) = parse_hhmm(sub_parts[1])?;
-
-            let target_weekday = match day_name.as_str() {
-                "monday" | "mon" => chrono::Weekday::Mon,
-                "tuesday" | "tue" => chrono::Weekday::Tue,
-                "wednesday" | "wed" => chrono::Weekday::Wed,
-                "thursday" | "thu" => chrono::Weekday::Thu,
-                "friday" | "fri" => chrono::Weekday::Fri,
-                "saturday" | "sat" => chrono::Weekday::Sat,
-                "sunday" | "sun" => chrono::Weekday::Sun,
-                _ => return None,
-            };
-
-            let mut candidate = now.date_naive();
-            for _ in 0..8 {
-                if candidate.weekday() == target_weekday {
-                    let next = candidate.and_hms_opt(hour, minute, 0)?;
-                    if next > now.naive_local() {
-                        let local = next.and_local_timezone(tz).earliest()?;
-                        return Some(local.with_timezone(&chrono::Utc).timestamp() as i32);
-                    }
-                }
-                candidate = candidate.succ_opt()?;
-            }
-            None
-        }
-        _ => None,
-    }
-}
-
-fn parse_hhmm(s: &str) -> Option<(u32, u32)> {
-    let parts: Vec<&str> = s.split(':').collect();
-    if parts.len() >= 2 {
-        let hour: u32 = parts[0].parse().ok()?;
-        let minute: u32 = parts[1].parse().ok()?;
-        Some((hour, minute))
-    } else {
-        None
-    }
+    SchedulePattern::parse(pattern)
+        .ok()?
+        .next_after(chrono::Utc::now(), tz)
+        .map(|next| next.timestamp() as i32)
 }
 
 // ---------------------------------------------------------------------------
@@ -1795,6 +2411,12 @@ pub enum RuleTestStep {
     KeywordResult {
         matched: bool,
     },
+    CheckingCondition {
+        description: String,
+    },
+    ConditionResult {
+        matched: bool,
+    },
     WouldExecute {
         action_type: String,
         description: String,
@@ -1802,19 +2424,55 @@ pub enum RuleTestStep {
     NoAction {
         reason: String,
     },
+    WouldWait {
+        seconds: i64,
+    },
+    RunningBranch {
+        index: usize,
+        total: usize,
+    },
+    LoopItems {
+        source: String,
+        count: usize,
+    },
+    LoopItem {
+        index: usize,
+        preview: String,
+    },
     Error {
         message: String,
     },
     Complete,
 }
 
+/// Short label for a fetch source, shown in the test panel.
+fn describe_fetch_source(source: &FetchSource) -> String {
+    match source {
+        FetchSource::Email => "email".into(),
+        FetchSource::Chat { platform, .. } => format!("chat ({})", platform),
+        FetchSource::Weather { .. } => "weather".into(),
+        FetchSource::Internet { query } => format!("internet: {}", query),
+        FetchSource::Tesla => "tesla".into(),
+        FetchSource::Mcp { server, tool, .. } => format!("mcp {}:{}", server, tool),
+        FetchSource::McpResource { server, uri } => format!("mcp resource {}:{}", server, uri),
+        FetchSource::Events => "tracked obligations".into(),
+        FetchSource::Calendar { days } => format!("calendar ({}d)", days),
+    }
+}
+
+/// Loop items actually walked by a test run; the rest are only counted so a
+/// test doesn't burn an LLM call per item.
+const TEST_LOOP_ITEMS: usize = 3;
+
 /// Walk a flow tree for testing: real LLM calls, but actions are described
-/// instead of executed. Each step is sent through `tx`.
+/// instead of executed. Delays are reported and skipped, parallel branches
+/// run one after another, and loops walk their first few items. Each step
+/// is sent through `tx`.
 pub async fn evaluate_flow_test(
     state: &Arc<AppState>,
     rule: &OntRule,
-    trigger_context: &str,
     node: &FlowNode,
+    ctx: &FlowContext,
     tx: &tokio::sync::mpsc::Sender<RuleTestStep>,
 ) {
     match node {
@@ -1826,18 +2484,7 @@ pub async fn evaluate_flow_test(
         } => {
             // Prefetch
             if !fetch.is_empty() {
-                let source_names: Vec<String> = fetch
-                    .iter()
-                    .map(|s| match s {
-                        FetchSource::Email => "email".into(),
-                        FetchSource::Chat { platform, .. } => format!("chat ({})", platform),
-                        FetchSource::Weather { .. } => "weather".into(),
-                        FetchSource::Internet { query } => format!("internet: {}", query),
-                        FetchSource::Tesla => "tesla".into(),
-                        FetchSource::Mcp { server, tool, .. } => format!("mcp {}:{}", server, tool),
-                        FetchSource::Events => "tracked obligations".into(),
-                    })
-                    .collect();
+                let source_names: Vec<String> = fetch.iter().map(describe_fetch_source).collect();
                 let _ = tx
                     .send(RuleTestStep::Prefetching {
                         sources: source_names,
@@ -1861,7 +2508,7 @@ pub async fn evaluate_flow_test(
             match call_llm_condition(
                 state,
                 rule,
-                trigger_context,
+                &ctx.condition_input(),
                 &resolved_prompt,
                 &prefetched,
                 extra_params.as_ref(),
@@ -1880,8 +2527,7 @@ pub async fn evaluate_flow_test(
 
                     let next = if decided { true_branch } else { false_branch };
                     if let Some(branch) = next.as_ref() {
-                        Box::pin(evaluate_flow_test(state, rule, trigger_context, branch, tx))
-                            .await;
+                        Box::pin(evaluate_flow_test(state, rule, branch, ctx, tx)).await;
                     } else {
                         let reason = if decided {
                             "Condition was true but no action configured"
@@ -1915,14 +2561,15 @@ pub async fn evaluate_flow_test(
                 })
                 .await;
             let matched = !keyword.is_empty()
-                && trigger_context
+                && ctx
+                    .condition_input()
                     .to_lowercase()
                     .contains(&keyword.to_lowercase());
             let _ = tx.send(RuleTestStep::KeywordResult { matched }).await;
 
             let next = if matched { true_branch } else { false_branch };
             if let Some(branch) = next.as_ref() {
-                Box::pin(evaluate_flow_test(state, rule, trigger_context, branch, tx)).await;
+                Box::pin(evaluate_flow_test(state, rule, branch, ctx, tx)).await;
             } else {
                 let reason = if matched {
                     "Keyword matched but no action configured"
@@ -1936,6 +2583,35 @@ pub async fn evaluate_flow_test(
                     .await;
             }
         }
+        FlowNode::StructuredCondition {
+            condition,
+            true_branch,
+            false_branch,
+        } => {
+            let _ = tx
+                .send(RuleTestStep::CheckingCondition {
+                    description: condition.describe(),
+                })
+                .await;
+            let matched = check_structured_condition(state, rule, condition, ctx).await;
+            let _ = tx.send(RuleTestStep::ConditionResult { matched }).await;
+
+            let next = if matched { true_branch } else { false_branch };
+            if let Some(branch) = next.as_ref() {
+                Box::pin(evaluate_flow_test(state, rule, branch, ctx, tx)).await;
+            } else {
+                let reason = if matched {
+                    "Condition matched but no action configured"
+                } else {
+                    "Condition did not match and no else branch"
+                };
+                let _ = tx
+                    .send(RuleTestStep::NoAction {
+                        reason: reason.to_string(),
+                    })
+                    .await;
+            }
+        }
         FlowNode::Action {
             action_type,
             config,
@@ -1964,5 +2640,70 @@ pub async fn evaluate_flow_test(
                 })
                 .await;
         }
+        FlowNode::Sequence { steps } => {
+            for step in steps {
+                Box::pin(evaluate_flow_test(state, rule, step, ctx, tx)).await;
+            }
+        }
+        FlowNode::Parallel { branches } => {
+            for (index, branch) in branches.iter().enumerate() {
+                let _ = tx
+                    .send(RuleTestStep::RunningBranch {
+                        index: index + 1,
+                        total: branches.len(),
+                    })
+                    .await;
+                Box::pin(evaluate_flow_test(state, rule, branch, ctx, tx)).await;
+            }
+        }
+        FlowNode::Delay { seconds, next } => {
+            let _ = tx.send(RuleTestStep::WouldWait { seconds: *seconds }).await;
+            if let Some(next) = next.as_ref() {
+                Box::pin(evaluate_flow_test(state, rule, next, ctx, tx)).await;
+            }
+        }
+        FlowNode::ForEach {
+            source,
+            limit,
+            body,
+        } => {
+            let items = fetch_items(state, rule, source, *limit).await;
+            let _ = tx
+                .send(RuleTestStep::LoopItems {
+                    source: describe_fetch_source(source),
+                    count: items.len(),
+                })
+                .await;
+            let Some(body) = body.as_ref() else {
+                let _ = tx
+                    .send(RuleTestStep::NoAction {
+                        reason: "Loop has no steps configured".to_string(),
+                    })
+                    .await;
+                return;
+            };
+            if items.is_empty() {
+                let _ = tx
+                    .send(RuleTestStep::NoAction {
+                        reason: "Source returned no items".to_string(),
+                    })
+                    .await;
+            }
+            for (index, item) in items.iter().take(TEST_LOOP_ITEMS).enumerate() {
+                let preview = match item.char_indices().nth(120) {
+                    Some((idx, _)) => format!("{}...", &item[..idx]),
+                    None => item.clone(),
+                };
+                let _ = tx
+                    .send(RuleTestStep::LoopItem {
+                        index: index + 1,
+                        preview,
+                    })
+                    .await;
+                let mut item_ctx = ctx.clone();
+                item_ctx.item = Some(item.clone());
+                Box::pin(evaluate_flow_test(state, rule, body, &item_ctx, tx)).await;
+            }
+        }
     }
 }
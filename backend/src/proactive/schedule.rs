@@ -0,0 +1,986 @@
+//! Schedule patterns for `schedule` rule triggers.
+//!
+//! `TriggerConfig.pattern` accepts three syntaxes:
+//!   - legacy shorthand: `hourly`, `daily HH:MM`, `weekdays HH:MM`,
+//!     `weekly <day> HH:MM`
+//!   - RFC 5545 recurrence rules: `RRULE:FREQ=MONTHLY;BYDAY=1MO;BYHOUR=9`,
+//!     optionally with `DTSTART...` and `EXDATE...` lines. Lines may be
+//!     separated by newlines or spaces so the pattern fits a single-line
+//!     input.
+//!   - 5-field cron: `*/15 9-16 * * 1-5` (plus `@daily`-style macros).
+//!
+//! Every wall-clock field is interpreted in the user's timezone. A local
+//! time that falls into a DST gap is read with the offset in force before
+//! the gap (so 02:30 on a spring-forward night fires at 03:30), and an
+//! ambiguous local time on a fall-back night fires once, at the first
+//! occurrence. Both follow RFC 5545 section 3.3.5.
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone,
+    Timelike, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+/// How far past `after` the iterators look before giving up. Rules that can
+/// never match (e.g. `BYMONTH=2;BYMONTHDAY=30`) end here instead of spinning.
+const SEARCH_HORIZON_DAYS: i64 = 366 * 5;
+
+/// Hard cap on expanded periods, a second guard for sub-daily frequencies
+/// combined with `COUNT` (which must be enumerated from `DTSTART`).
+const MAX_PERIODS: i64 = 3_000_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchedulePattern {
+    /// Legacy `hourly`: one hour after the previous evaluation.
+    Hourly,
+    Rrule(RecurrenceRule),
+    Cron(CronSchedule),
+}
+
+impl SchedulePattern {
+    /// Parse any supported pattern syntax. The error is user-facing and is
+    /// returned verbatim by the rule handlers.
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let trimmed = pattern.trim();
+        if trimmed.is_empty() {
+            return Err("Schedule pattern is empty".to_string());
+        }
+        let first = trimmed
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        match first.as_str() {
+            "hourly" => return Ok(SchedulePattern::Hourly),
+            "daily" | "weekdays" | "weekly" => {
+                return parse_legacy(trimmed).map(SchedulePattern::Rrule)
+            }
+            _ => {}
+        }
+        if trimmed.to_uppercase().contains("FREQ=") {
+            return RecurrenceRule::parse(trimmed).map(SchedulePattern::Rrule);
+        }
+        CronSchedule::parse(trimmed).map(SchedulePattern::Cron)
+    }
+
+    /// Next fire time strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        self.upcoming(after, tz, 1).into_iter().next()
+    }
+
+    /// Up to `n` fire times strictly after `after`, in ascending order.
+    pub fn upcoming(&self, after: DateTime<Utc>, tz: Tz, n: usize) -> Vec<DateTime<Utc>> {
+        match self {
+            SchedulePattern::Hourly => (1..=n as i64).map(|i| after + Duration::hours(i)).collect(),
+            SchedulePattern::Rrule(rule) => rule.upcoming(after, tz, n),
+            SchedulePattern::Cron(cron) => cron.upcoming(after, tz, n),
+        }
+    }
+
+    /// Whether the occurrence phase depends on an anchor. Such RRULEs get a
+    /// `DTSTART` pinned at save time (see `pin_schedule_anchor`) so that
+    /// "every 2 weeks" counts from the day the rule was created.
+    pub fn needs_anchor(&self) -> bool {
+        match self {
+            SchedulePattern::Rrule(rule) => {
+                rule.dtstart.is_none() && (rule.interval > 1 || rule.count.is_some())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Validate a pattern and list the next `count` fire times as UTC unix
+/// timestamps. Used by the rule handlers and the builder's live preview.
+pub fn preview_fire_times(pattern: &str, user_tz: &str, count: usize) -> Result<Vec<i32>, String> {
+    let tz: Tz = user_tz.parse().unwrap_or(chrono_tz::UTC);
+    let parsed = SchedulePattern::parse(pattern)?;
+    Ok(parsed
+        .upcoming(Utc::now(), tz, count)
+        .into_iter()
+        .map(|dt| dt.timestamp() as i32)
+        .collect())
+}
+
+/// Prepend a floating `DTSTART` (the current local minute) to RRULEs whose
+/// occurrences depend on an anchor. Other patterns are returned unchanged.
+pub fn pin_schedule_anchor(pattern: &str, user_tz: &str, now: DateTime<Utc>) -> String {
+    let needs_anchor = SchedulePattern::parse(pattern)
+        .map(|p| p.needs_anchor())
+        .unwrap_or(false);
+    if !needs_anchor {
+        return pattern.to_string();
+    }
+    let tz: Tz = user_tz.parse().unwrap_or(chrono_tz::UTC);
+    let local = now.with_timezone(&tz).naive_local();
+    format!(
+        "DTSTART:{} {}",
+        local.format("%Y%m%dT%H%M00"),
+        pattern.trim()
+    )
+}
+
+/// Map a local wall-clock time to an instant, applying the DST rules from
+/// the module docs.
+pub fn resolve_local(local: NaiveDateTime, tz: Tz) -> Option<DateTime<Utc>> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(dt) => Some(dt.with_timezone(&Utc)),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest.with_timezone(&Utc)),
+        LocalResult::None => {
+            // Inside a spring-forward gap: use the offset in force just
+            // before the transition. Gaps are at most a few hours, so three
+            // hours back is always on the far side.
+            let before = tz
+                .from_local_datetime(&(local - Duration::hours(3)))
+                .earliest()?;
+            let offset_secs = before.offset().fix().local_minus_utc() as i64;
+            Some(Utc.from_utc_datetime(&(local - Duration::seconds(offset_secs))))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Legacy shorthand
+// ---------------------------------------------------------------------------
+
+fn parse_legacy(pattern: &str) -> Result<RecurrenceRule, String> {
+    let parts: Vec<&str> = pattern.split_whitespace().collect();
+    let (by_day, time) = match parts[0].to_lowercase().as_str() {
+        "daily" => (Vec::new(), parts.get(1)),
+        "weekdays" => (
+            [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ]
+            .iter()
+            .map(|wd| ByDay {
+                ordinal: None,
+                weekday: *wd,
+            })
+            .collect(),
+            parts.get(1),
+        ),
+        _ => {
+            let day = parts
+                .get(1)
+                .and_then(|d| parse_weekday_name(d))
+                .ok_or_else(|| format!("Unknown weekday in '{}'", pattern))?;
+            (
+                vec![ByDay {
+                    ordinal: None,
+                    weekday: day,
+                }],
+                parts.get(2),
+            )
+        }
+    };
+    let (hour, minute) = time
+        .and_then(|t| parse_hhmm(t))
+        .ok_or_else(|| format!("Expected a HH:MM time in '{}'", pattern))?;
+    Ok(RecurrenceRule {
+        freq: if by_day.is_empty() {
+            Frequency::Daily
+        } else {
+            Frequency::Weekly
+        },
+        by_day,
+        by_hour: vec![hour],
+        by_minute: vec![minute],
+        ..RecurrenceRule::default()
+    })
+}
+
+fn parse_hhmm(s: &str) -> Option<(u32, u32)> {
+    let mut parts = s.split(':');
+    let hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = parts.next()?.parse().ok()?;
+    (hour < 24 && minute < 60).then_some((hour, minute))
+}
+
+fn parse_weekday_name(s: &str) -> Option<Weekday> {
+    match s.to_lowercase().as_str() {
+        "monday" | "mon" | "mo" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tu" => Some(Weekday::Tue),
+        "wednesday" | "wed" | "we" => Some(Weekday::Wed),
+        "thursday" | "thu" | "th" => Some(Weekday::Thu),
+        "friday" | "fri" | "fr" => Some(Weekday::Fri),
+        "saturday" | "sat" | "sa" => Some(Weekday::Sat),
+        "sunday" | "sun" | "su" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RFC 5545 recurrence rules
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Frequency {
+    Yearly,
+    Monthly,
+    Weekly,
+    #[default]
+    Daily,
+    Hourly,
+    Minutely,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    /// `1MO` = first Monday, `-1FR` = last Friday of the month/year.
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+/// A DTSTART/EXDATE/UNTIL value in any of the RFC 5545 forms.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateSpec {
+    /// `20260105`: a whole local day.
+    Date(NaiveDate),
+    /// `20260105T090000`: wall-clock time in the user's timezone.
+    Floating(NaiveDateTime),
+    /// `20260105T090000Z`
+    Utc(NaiveDateTime),
+    /// `TZID=Europe/Helsinki:20260105T090000`
+    Zoned(NaiveDateTime, Tz),
+}
+
+impl DateSpec {
+    fn to_local(&self, tz: Tz) -> NaiveDateTime {
+        match self {
+            DateSpec::Date(d) => d.and_hms_opt(0, 0, 0).unwrap_or_default(),
+            DateSpec::Floating(ndt) => *ndt,
+            DateSpec::Utc(ndt) => Utc.from_utc_datetime(ndt).with_timezone(&tz).naive_local(),
+            DateSpec::Zoned(ndt, zone) => resolve_local(*ndt, *zone)
+                .map(|dt| dt.with_timezone(&tz).naive_local())
+                .unwrap_or(*ndt),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<DateSpec>,
+    pub by_month: Vec<u32>,
+    pub by_month_day: Vec<i32>,
+    pub by_day: Vec<ByDay>,
+    pub by_hour: Vec<u32>,
+    pub by_minute: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+    pub dtstart: Option<DateSpec>,
+    pub exdates: Vec<DateSpec>,
+}
+
+impl Default for RecurrenceRule {
+    fn default() -> Self {
+        RecurrenceRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_month: Vec::new(),
+            by_month_day: Vec::new(),
+            by_day: Vec::new(),
+            by_hour: Vec::new(),
+            by_minute: Vec::new(),
+            by_set_pos: Vec::new(),
+            dtstart: None,
+            exdates: Vec::new(),
+        }
+    }
+}
+
+impl RecurrenceRule {
+    /// Parse an RRULE with optional DTSTART/EXDATE lines.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut rule: Option<RecurrenceRule> = None;
+        let mut dtstart = None;
+        let mut exdates = Vec::new();
+        for token in text.split_whitespace() {
+            let upper = token.to_uppercase();
+            if upper.starts_with("DTSTART") {
+                let (params, value) = split_property(token)?;
+                dtstart = Some(parse_date_spec(&params, value)?);
+            } else if upper.starts_with("EXDATE") {
+                let (params, value) = split_property(token)?;
+                for v in value.split(',').filter(|v| !v.is_empty()) {
+                    exdates.push(parse_date_spec(&params, v)?);
+                }
+            } else if upper.starts_with("RRULE:") || upper.starts_with("FREQ=") {
+                if rule.is_some() {
+                    return Err("Only one RRULE is supported per schedule".to_string());
+                }
+                let body = if upper.starts_with("RRULE:") {
+                    &token["RRULE:".len()..]
+                } else {
+                    token
+                };
+                rule = Some(parse_rrule_body(body)?);
+            } else {
+                return Err(format!("Unsupported recurrence line '{}'", token));
+            }
+        }
+        let mut rule = rule.ok_or("Missing RRULE")?;
+        rule.dtstart = dtstart;
+        rule.exdates = exdates;
+        Ok(rule)
+    }
+
+    fn upcoming(&self, after: DateTime<Utc>, tz: Tz, n: usize) -> Vec<DateTime<Utc>> {
+        let mut out: Vec<DateTime<Utc>> = Vec::new();
+        if n == 0 {
+            return out;
+        }
+        // Without DTSTART the rule is anchored at the local Unix epoch:
+        // times default to midnight and intervals count from 1970.
+        let start = self
+            .dtstart
+            .as_ref()
+            .map(|d| d.to_local(tz))
+            .unwrap_or_else(|| {
+                NaiveDate::from_ymd_opt(1970, 1, 1)
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .unwrap_or_default()
+            });
+        let until = self.until.as_ref().map(|u| match u {
+            DateSpec::Date(d) => d.and_hms_opt(23, 59, 59).unwrap_or_default(),
+            other => other.to_local(tz),
+        });
+        let after_local = after.with_timezone(&tz).naive_local();
+        let horizon = after_local + Duration::days(SEARCH_HORIZON_DAYS);
+
+        // COUNT is defined over the whole set, so it has to be enumerated
+        // from DTSTART. Otherwise jump straight to the period holding `after`.
+        let first_period = if self.count.is_some() {
+            0
+        } else {
+            (self.units_between(start, after_local) / self.interval as i64 - 1).max(0)
+        };
+
+        let mut emitted: u32 = 0;
+        for k in first_period..first_period.saturating_add(MAX_PERIODS) {
+            let Some((period_start, candidates)) = self.expand_period(start, k) else {
+                break;
+            };
+            if period_start > horizon {
+                break;
+            }
+            for local in candidates {
+                if local < start {
+                    continue;
+                }
+                if until.is_some_and(|u| local > u) {
+                    return out;
+                }
+                // EXDATE removes occurrences after COUNT has been applied.
+                emitted += 1;
+                if self.count.is_some_and(|c| emitted > c) {
+                    return out;
+                }
+                if self.is_excluded(local, tz) {
+                    continue;
+                }
+                let Some(instant) = resolve_local(local, tz) else {
+                    continue;
+                };
+                if instant <= after || out.last() == Some(&instant) {
+                    continue;
+                }
+                out.push(instant);
+                if out.len() >= n {
+                    return out;
+                }
+            }
+        }
+        out
+    }
+
+    fn is_excluded(&self, local: NaiveDateTime, tz: Tz) -> bool {
+        self.exdates.iter().any(|ex| match ex {
+            DateSpec::Date(d) => local.date() == *d,
+            other => other.to_local(tz) == local,
+        })
+    }
+
+    /// Whole frequency units from the DTSTART period to `to`.
+    fn units_between(&self, start: NaiveDateTime, to: NaiveDateTime) -> i64 {
+        let units = match self.freq {
+            Frequency::Yearly => (to.year() - start.year()) as i64,
+            Frequency::Monthly => {
+                (to.year() - start.year()) as i64 * 12 + to.month() as i64 - start.month() as i64
+            }
+            Frequency::Weekly => (to.date() - week_start(start.date())).num_days() / 7,
+            Frequency::Daily => (to.date() - start.date()).num_days(),
+            Frequency::Hourly => (truncate_hour(to) - truncate_hour(start)).num_hours(),
+            Frequency::Minutely => (truncate_minute(to) - truncate_minute(start)).num_minutes(),
+        };
+        units.max(0)
+    }
+
+    /// Candidate local times for the k-th period, sorted and with BYSETPOS
+    /// applied. Returns the period's first instant alongside so the caller
+    /// can stop at the search horizon. `None` means the calendar overflowed.
+    fn expand_period(
+        &self,
+        start: NaiveDateTime,
+        k: i64,
+    ) -> Option<(NaiveDateTime, Vec<NaiveDateTime>)> {
+        let step = k.checked_mul(self.interval as i64)?;
+        let (period_start, mut candidates) = match self.freq {
+            Frequency::Yearly => {
+                let year = i32::try_from(start.year() as i64 + step).ok()?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                let days = self.year_days(year, start);
+                (first.and_hms_opt(0, 0, 0)?, self.with_times(&days, start))
+            }
+            Frequency::Monthly => {
+                let months = start.year() as i64 * 12 + start.month0() as i64 + step;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                let month = months.rem_euclid(12) as u32 + 1;
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let days = if self.by_month.is_empty() || self.by_month.contains(&month) {
+                    self.month_days(year, month, start)
+                } else {
+                    Vec::new()
+                };
+                (first.and_hms_opt(0, 0, 0)?, self.with_times(&days, start))
+            }
+            Frequency::Weekly => {
+                let first = week_start(start.date()).checked_add_signed(Duration::weeks(step))?;
+                let days: Vec<NaiveDate> = (0..7)
+                    .filter_map(|i| first.checked_add_signed(Duration::days(i)))
+                    .filter(|d| {
+                        if self.by_day.is_empty() {
+                            d.weekday() == start.weekday()
+                        } else {
+                            self.by_day.iter().any(|bd| bd.weekday == d.weekday())
+                        }
+                    })
+                    .filter(|d| self.by_month.is_empty() || self.by_month.contains(&d.month()))
+                    .collect();
+                (first.and_hms_opt(0, 0, 0)?, self.with_times(&days, start))
+            }
+            Frequency::Daily => {
+                let day = start.date().checked_add_signed(Duration::days(step))?;
+                let days = if self.day_passes_filters(day) {
+                    vec![day]
+                } else {
+                    Vec::new()
+                };
+                (day.and_hms_opt(0, 0, 0)?, self.with_times(&days, start))
+            }
+            Frequency::Hourly => {
+                let hour = truncate_hour(start).checked_add_signed(Duration::hours(step))?;
+                let mut out = Vec::new();
+                if self.day_passes_filters(hour.date())
+                    && (self.by_hour.is_empty() || self.by_hour.contains(&hour.hour()))
+                {
+                    for minute in self.minutes(start) {
+                        out.push(hour.with_minute(minute)?);
+                    }
+                }
+                (hour, out)
+            }
+            Frequency::Minutely => {
+                let minute = truncate_minute(start).checked_add_signed(Duration::minutes(step))?;
+                let passes = self.day_passes_filters(minute.date())
+                    && (self.by_hour.is_empty() || self.by_hour.contains(&minute.hour()))
+                    && (self.by_minute.is_empty() || self.by_minute.contains(&minute.minute()));
+                (minute, if passes { vec![minute] } else { Vec::new() })
+            }
+        };
+        candidates.sort();
+        candidates.dedup();
+        if !self.by_set_pos.is_empty() {
+            let len = candidates.len() as i32;
+            let mut picked: Vec<NaiveDateTime> = self
+                .by_set_pos
+                .iter()
+                .filter_map(|&pos| {
+                    let idx = if pos > 0 { pos - 1 } else { len + pos };
+                    (0..len).contains(&idx).then(|| candidates[idx as usize])
+                })
+                .collect();
+            picked.sort();
+            picked.dedup();
+            candidates = picked;
+        }
+        Some((period_start, candidates))
+    }
+
+    /// Days of `year` selected by BYMONTH/BYMONTHDAY/BYDAY. Ordinal BYDAY
+    /// values count within the month when BYMONTH is set, else within the year.
+    fn year_days(&self, year: i32, start: NaiveDateTime) -> Vec<NaiveDate> {
+        if !self.by_month.is_empty() {
+            let mut months = self.by_month.clone();
+            months.sort_unstable();
+            return months
+                .into_iter()
+                .flat_map(|m| self.month_days(year, m, start))
+                .collect();
+        }
+        if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            return NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                .into_iter()
+                .collect();
+        }
+        let Some(first) = NaiveDate::from_ymd_opt(year, 1, 1) else {
+            return Vec::new();
+        };
+        let len = days_in_year(year);
+        (0..len)
+            .filter_map(|i| first.checked_add_signed(Duration::days(i as i64)))
+            .filter(|d| self.month_day_matches(*d))
+            .filter(|d| self.by_day_matches(*d, d.ordinal0() as i32, len))
+            .collect()
+    }
+
+    /// Days of one month selected by BYMONTHDAY/BYDAY, defaulting to the
+    /// DTSTART day of month (skipped in months that are too short).
+    fn month_days(&self, year: i32, month: u32, start: NaiveDateTime) -> Vec<NaiveDate> {
+        if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            return NaiveDate::from_ymd_opt(year, month, start.day())
+                .into_iter()
+                .collect();
+        }
+        let len = days_in_month(year, month);
+        (1..=len)
+            .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+            .filter(|d| self.month_day_matches(*d))
+            .filter(|d| self.by_day_matches(*d, d.day0() as i32, len as i32))
+            .collect()
+    }
+
+    /// Filter-style check used by DAILY and sub-daily frequencies, where
+    /// BY* parts limit rather than expand the set.
+    fn day_passes_filters(&self, d: NaiveDate) -> bool {
+        (self.by_month.is_empty() || self.by_month.contains(&d.month()))
+            && self.month_day_matches(d)
+            && (self.by_day.is_empty() || self.by_day.iter().any(|bd| bd.weekday == d.weekday()))
+    }
+
+    fn month_day_matches(&self, d: NaiveDate) -> bool {
+        if self.by_month_day.is_empty() {
+            return true;
+        }
+        let len = days_in_month(d.year(), d.month()) as i32;
+        let day = d.day() as i32;
+        self.by_month_day.iter().any(|&md| {
+            if md > 0 {
+                md == day
+            } else {
+                len + md + 1 == day
+            }
+        })
+    }
+
+    /// `idx` is the zero-based position of `d` in its month or year and
+    /// `len` the period length, so `-1FR` can be checked from the end.
+    fn by_day_matches(&self, d: NaiveDate, idx: i32, len: i32) -> bool {
+        if self.by_day.is_empty() {
+            return true;
+        }
+        self.by_day.iter().any(|bd| {
+            bd.weekday == d.weekday()
+                && match bd.ordinal {
+                    None => true,
+                    Some(n) if n > 0 => idx / 7 + 1 == n,
+                    Some(n) => -((len - 1 - idx) / 7 + 1) == n,
+                }
+        })
+    }
+
+    fn minutes(&self, start: NaiveDateTime) -> Vec<u32> {
+        if self.by_minute.is_empty() {
+            vec![start.minute()]
+        } else {
+            self.by_minute.clone()
+        }
+    }
+
+    fn with_times(&self, days: &[NaiveDate], start: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let hours = if self.by_hour.is_empty() {
+            vec![start.hour()]
+        } else {
+            self.by_hour.clone()
+        };
+        let minutes = self.minutes(start);
+        let mut out = Vec::with_capacity(days.len() * hours.len() * minutes.len());
+        for day in days {
+            for &h in &hours {
+                for &m in &minutes {
+                    if let Some(t) = day.and_hms_opt(h, m, 0) {
+                        out.push(t);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn parse_rrule_body(body: &str) -> Result<RecurrenceRule, String> {
+    let mut rule = RecurrenceRule::default();
+    let mut freq = None;
+    for part in body.split(';').filter(|p| !p.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Malformed RRULE part '{}'", part))?;
+        let value_upper = value.to_uppercase();
+        match key.to_uppercase().as_str() {
+            "FREQ" => {
+                freq = Some(match value_upper.as_str() {
+                    "YEARLY" => Frequency::Yearly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "WEEKLY" => Frequency::Weekly,
+                    "DAILY" => Frequency::Daily,
+                    "HOURLY" => Frequency::Hourly,
+                    "MINUTELY" => Frequency::Minutely,
+                    other => return Err(format!("Unsupported FREQ '{}'", other)),
+                })
+            }
+            "INTERVAL" => {
+                rule.interval = value
+                    .parse()
+                    .ok()
+                    .filter(|i: &u32| *i >= 1)
+                    .ok_or_else(|| format!("INTERVAL must be a positive number, got '{}'", value))?
+            }
+            "COUNT" => {
+                rule.count = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|c: &u32| *c >= 1)
+                        .ok_or_else(|| {
+                            format!("COUNT must be a positive number, got '{}'", value)
+                        })?,
+                )
+            }
+            "UNTIL" => rule.until = Some(parse_date_spec(&[], value)?),
+            "BYMONTH" => rule.by_month = parse_number_list(value, 1, 12, false, "BYMONTH")?,
+            "BYMONTHDAY" => {
+                rule.by_month_day = parse_number_list(value, 1, 31, true, "BYMONTHDAY")?
+            }
+            "BYHOUR" => rule.by_hour = parse_number_list(value, 0, 23, false, "BYHOUR")?,
+            "BYMINUTE" => rule.by_minute = parse_number_list(value, 0, 59, false, "BYMINUTE")?,
+            "BYSETPOS" => rule.by_set_pos = parse_number_list(value, 1, 366, true, "BYSETPOS")?,
+            "BYDAY" => {
+                rule.by_day = value_upper
+                    .split(',')
+                    .map(parse_by_day)
+                    .collect::<Result<_, _>>()?
+            }
+            // Week start only matters for WEEKLY rules with BYDAY and an
+            // interval; weeks always start on Monday here, the RFC default.
+            "WKST" => {
+                if value_upper != "MO" {
+                    return Err("Only WKST=MO is supported".to_string());
+                }
+            }
+            "BYSECOND" | "BYYEARDAY" | "BYWEEKNO" => {
+                return Err(format!("{} is not supported", key.to_uppercase()))
+            }
+            other => return Err(format!("Unknown RRULE part '{}'", other)),
+        }
+    }
+    rule.freq = freq.ok_or("RRULE is missing FREQ")?;
+    if rule.count.is_some() && rule.until.is_some() {
+        return Err("RRULE cannot have both COUNT and UNTIL".to_string());
+    }
+    let has_ordinal = rule.by_day.iter().any(|bd| bd.ordinal.is_some());
+    if has_ordinal && !matches!(rule.freq, Frequency::Monthly | Frequency::Yearly) {
+        return Err("Numbered BYDAY values (like 1MO) need FREQ=MONTHLY or YEARLY".to_string());
+    }
+    if rule.freq == Frequency::Weekly && !rule.by_month_day.is_empty() {
+        return Err("BYMONTHDAY cannot be combined with FREQ=WEEKLY".to_string());
+    }
+    Ok(rule)
+}
+
+fn parse_number_list<T: TryFrom<i32>>(
+    value: &str,
+    min: i32,
+    max: i32,
+    allow_negative: bool,
+    name: &str,
+) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|v| {
+            let n: i32 = v
+                .trim()
+                .parse()
+                .map_err(|_| format!("{} has a non-numeric value '{}'", name, v))?;
+            let in_range = (min..=max).contains(&n) || (allow_negative && (-max..=-1).contains(&n));
+            if !in_range {
+                return Err(format!("{} value {} is out of range", name, n));
+            }
+            T::try_from(n).map_err(|_| format!("{} value {} is out of range", name, n))
+        })
+        .collect()
+}
+
+fn parse_by_day(value: &str) -> Result<ByDay, String> {
+    let value = value.trim();
+    if value.len() < 2 {
+        return Err(format!("Invalid BYDAY value '{}'", value));
+    }
+    let (ordinal, day) = value.split_at(value.len() - 2);
+    let weekday =
+        parse_weekday_name(day).ok_or_else(|| format!("Invalid BYDAY value '{}'", value))?;
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        let n: i32 = ordinal
+            .trim_start_matches('+')
+            .parse()
+            .map_err(|_| format!("Invalid BYDAY value '{}'", value))?;
+        if n == 0 || n.abs() > 53 {
+            return Err(format!("BYDAY ordinal {} is out of range", n));
+        }
+        Some(n)
+    };
+    Ok(ByDay { ordinal, weekday })
+}
+
+/// Split `DTSTART;TZID=Europe/Helsinki:20260105T090000` into its parameters
+/// and value.
+fn split_property(token: &str) -> Result<(Vec<String>, &str), String> {
+    let (head, value) = token
+        .split_once(':')
+        .ok_or_else(|| format!("Malformed recurrence line '{}'", token))?;
+    let params = head.split(';').skip(1).map(|p| p.to_string()).collect();
+    Ok((params, value))
+}
+
+fn parse_date_spec(params: &[String], value: &str) -> Result<DateSpec, String> {
+    let tzid = params.iter().find_map(|p| {
+        p.split_once('=')
+            .filter(|(k, _)| k.eq_ignore_ascii_case("TZID"))
+            .map(|(_, v)| v.to_string())
+    });
+    let invalid = || {
+        format!(
+            "Invalid date '{}' (expected YYYYMMDD or YYYYMMDDTHHMMSS)",
+            value
+        )
+    };
+    if value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map(DateSpec::Date)
+            .map_err(|_| invalid());
+    }
+    let (body, is_utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(body) => (body, true),
+        None => (value, false),
+    };
+    let ndt = NaiveDateTime::parse_from_str(body, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+    if is_utc {
+        return Ok(DateSpec::Utc(ndt));
+    }
+    match tzid {
+        Some(name) => name
+            .parse::<Tz>()
+            .map(|zone| DateSpec::Zoned(ndt, zone))
+            .map_err(|_| format!("Unknown TZID '{}'", name)),
+        None => Ok(DateSpec::Floating(ndt)),
+    }
+}
+
+fn week_start(d: NaiveDate) -> NaiveDate {
+    d - Duration::days(d.weekday().num_days_from_monday() as i64)
+}
+
+fn truncate_hour(t: NaiveDateTime) -> NaiveDateTime {
+    t.date().and_hms_opt(t.hour(), 0, 0).unwrap_or(t)
+}
+
+fn truncate_minute(t: NaiveDateTime) -> NaiveDateTime {
+    t.date().and_hms_opt(t.hour(), t.minute(), 0).unwrap_or(t)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+fn days_in_year(year: i32) -> i32 {
+    NaiveDate::from_ymd_opt(year, 12, 31)
+        .map(|d| d.ordinal() as i32)
+        .unwrap_or(365)
+}
+
+// ---------------------------------------------------------------------------
+// 5-field cron
+// ---------------------------------------------------------------------------
+
+/// `minute hour day-of-month month day-of-week`, Vixie-cron semantics: when
+/// both day fields are restricted a day matches if either does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DOW_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim().to_lowercase().as_str() {
+            "@hourly" => "0 * * * *".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            _ => expr.trim().to_string(),
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Unrecognized schedule '{}': expected daily/weekly shorthand, an RRULE, or a 5-field cron expression",
+                expr.trim()
+            ));
+        }
+        let mut days_of_week = parse_cron_field(fields[4], 0, 7, &DOW_NAMES, "day-of-week")?;
+        // 7 is an alias for Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+        Ok(CronSchedule {
+            minutes: parse_cron_field(fields[0], 0, 59, &[], "minute")?,
+            hours: parse_cron_field(fields[1], 0, 23, &[], "hour")?,
+            days_of_month: parse_cron_field(fields[2], 1, 31, &[], "day-of-month")?,
+            months: parse_cron_field(fields[3], 1, 12, &MONTH_NAMES, "month")?,
+            days_of_week,
+            dom_restricted: fields[2] != "*",
+            dow_restricted: fields[4] != "*",
+        })
+    }
+
+    fn day_matches(&self, d: NaiveDate) -> bool {
+        if self.months & (1 << d.month()) == 0 {
+            return false;
+        }
+        let dom = self.days_of_month & (1 << d.day()) != 0;
+        let dow = self.days_of_week & (1 << d.weekday().num_days_from_sunday()) != 0;
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    fn upcoming(&self, after: DateTime<Utc>, tz: Tz, n: usize) -> Vec<DateTime<Utc>> {
+        let mut out: Vec<DateTime<Utc>> = Vec::new();
+        if n == 0 {
+            return out;
+        }
+        let mut day = after.with_timezone(&tz).date_naive();
+        for _ in 0..SEARCH_HORIZON_DAYS {
+            if self.day_matches(day) {
+                for hour in (0..24).filter(|h| self.hours & (1u64 << h) != 0) {
+                    for minute in (0..60).filter(|m| self.minutes & (1u64 << m) != 0) {
+                        let Some(local) = day.and_hms_opt(hour, minute, 0) else {
+                            continue;
+                        };
+                        let Some(instant) = resolve_local(local, tz) else {
+                            continue;
+                        };
+                        if instant <= after || out.last() == Some(&instant) {
+                            continue;
+                        }
+                        out.push(instant);
+                        if out.len() >= n {
+                            return out;
+                        }
+                    }
+                }
+            }
+            day = match day.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        out
+    }
+}
+
+/// Parse one cron field (`*`, `5`, `1-5`, `*/15`, `9-17/2`, `MON,WED`) into
+/// a bitmask where bit `i` means value `i` is allowed.
+fn parse_cron_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    label: &str,
+) -> Result<u64, String> {
+    let value_of = |s: &str| -> Result<u32, String> {
+        let upper = s.to_uppercase();
+        if let Some(pos) = names.iter().position(|n| *n == upper) {
+            // Month names are 1-based, weekday names 0-based.
+            return Ok(pos as u32 + if min == 1 { 1 } else { 0 });
+        }
+        let v: u32 = s
+            .parse()
+            .map_err(|_| format!("Invalid {} value '{}' in cron expression", label, s))?;
+        if v < min || v > max {
+            return Err(format!("Cron {} value {} is out of range", label, v));
+        }
+        Ok(v)
+    };
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("Invalid cron step '{}'", item))?;
+                (range, Some(step))
+            }
+            None => (item, None),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (value_of(a)?, value_of(b)?)
+        } else {
+            let v = value_of(range)?;
+            // `5/10` means "from 5 to the end, every 10".
+            (v, if step.is_some() { max } else { v })
+        };
+        if lo > hi {
+            return Err(format!("Cron range '{}' is reversed", range));
+        }
+        let mut v = lo;
+        while v <= hi {
+            mask |= 1 << v;
+            v += step.unwrap_or(1);
+        }
+    }
+    Ok(mask)
+}
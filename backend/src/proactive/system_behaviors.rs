@@ -790,6 +790,7 @@ pub async fn run_urgency_classification(
                     status: None,
                     recharge_threshold_timestamp: None,
                     zero_credits_timestamp: None,
+                    segments: None,
                 });
             }
 
@@ -2199,11 +2200,13 @@ async fn try_send_commitment_prompt(
         None => return Ok(PromptSendOutcome::RaceLost),
     };
 
-    let body = format!(
+    let prompt_text = format!(
         "Commitment? \"{}\" (from {}). Reply: 1=track, 2=always, 3=mute, 4=not commitment",
         truncate_chars(description, 100),
         truncate_chars(sender_name, 40)
     );
+    let reply_code = crate::proactive::reply_context::reserve_code(state, user_id);
+    let body = crate::proactive::reply_context::tag(&reply_code, &prompt_text);
 
     let user = state
         .user_core
@@ -2213,6 +2216,15 @@ async fn try_send_commitment_prompt(
 
     match state.channel_router.send_to_user(&user, &body, None).await {
         Ok(channel_id) => {
+            crate::proactive::reply_context::record(
+                state,
+                user_id,
+                &reply_code,
+                &crate::proactive::reply_context::ReplyTarget::Commitment {
+                    prompt_id: prompt.id,
+                },
+                &prompt_text,
+            );
             if let Err(e) = state
                 .commitment_repository
                 .set_prompt_sms_sid(prompt.id, &channel_id.0)
@@ -202,11 +202,15 @@ pub fn parse_iso_to_timestamp(s: &str, tz_offset_secs: i32) -> Option<i32> {
 }
 
 /// Metadata for notification context.
+#[derive(Default)]
 pub struct NotificationMeta {
     pub platform: Option<String>,
     pub sender: Option<String>,
     pub content: Option<String>,
     pub history_annotation: Option<String>,
+    /// What a reply naming this notification's code acts on. `None` means
+    /// a plain notification (alerts are detected from the content type).
+    pub reply_target: Option<crate::proactive::reply_context::ReplyTarget>,
 }
 
 pub fn notification_meta_from_snapshot(snap: &serde_json::Value) -> Option<NotificationMeta> {
@@ -225,6 +229,7 @@ pub fn notification_meta_from_snapshot(snap: &serde_json::Value) -> Option<Notif
             .and_then(|v| v.as_str())
             .map(str::to_string),
         history_annotation: email_ref_annotation_from_snapshot(snap),
+        reply_target: None,
     })
 }
 
@@ -497,6 +502,7 @@ pub async fn send_notification_with_context(
     };
 
     let mut sms_delivered = false;
+    let reply_code = crate::proactive::reply_context::reserve_code(state, user_id);
     let outbound_notification = if content_type.starts_with("system_important") {
         format!(
             "{}\n\nReply 1=worth it, 2=should wait.",
@@ -505,6 +511,8 @@ pub async fn send_notification_with_context(
     } else {
         notification.to_string()
     };
+    let outbound_notification =
+        crate::proactive::reply_context::tag(&reply_code, &outbound_notification);
     let history_notification = meta
         .as_ref()
         .and_then(|m| m.history_annotation.as_deref())
@@ -550,6 +558,7 @@ pub async fn send_notification_with_context(
                             status: Some("ongoing".to_string()),
                             recharge_threshold_timestamp: None,
                             zero_credits_timestamp: None,
+                            segments: None,
                         }) {
                             tracing::error!("Failed to log call notification usage: {}", e);
                         }
@@ -561,15 +570,22 @@ pub async fn send_notification_with_context(
                 }
             }
 
-            match state
-                .channel_router
-                .send_to_user(&user, &outbound_notification, None)
+            match deliver_notification_text(state, &user, &content_type, &outbound_notification)
                 .await
             {
-                Ok(response_sid) => {
+                Ok((response_sid, segments)) => {
                     let response_sid = response_sid.into_inner();
                     sms_delivered = true;
                     tracing::info!("SMS sent for call notification user {}", user_id);
+                    remember_reply_code(
+                        state,
+                        user_id,
+                        &reply_code,
+                        &content_type,
+                        meta.as_ref(),
+                        &response_sid,
+                        notification,
+                    );
                     let entry = crate::pg_models::NewPgMessageHistory {
                         user_id: user.id,
                         role: "assistant".to_string(),
@@ -594,6 +610,7 @@ pub async fn send_notification_with_context(
                         status: Some("delivered".to_string()),
                         recharge_threshold_timestamp: None,
                         zero_credits_timestamp: None,
+                        segments,
                     }) {
                         tracing::error!("Failed to log call notification SMS usage: {}", e);
                     }
@@ -610,15 +627,22 @@ pub async fn send_notification_with_context(
                 tracing::warn!("User {} has insufficient credits: {}", user.id, e);
                 return false;
             }
-            match state
-                .channel_router
-                .send_to_user(&user, &outbound_notification, None)
+            match deliver_notification_text(state, &user, &content_type, &outbound_notification)
                 .await
             {
-                Ok(response_sid) => {
+                Ok((response_sid, segments)) => {
                     let response_sid = response_sid.into_inner();
                     sms_delivered = true;
                     tracing::info!("Sent notification to user {}", user_id);
+                    remember_reply_code(
+                        state,
+                        user_id,
+                        &reply_code,
+                        &content_type,
+                        meta.as_ref(),
+                        &response_sid,
+                        notification,
+                    );
                     let entry = crate::pg_models::NewPgMessageHistory {
                         user_id: user.id,
                         role: "assistant".to_string(),
@@ -643,6 +667,7 @@ pub async fn send_notification_with_context(
                         status: Some("delivered".to_string()),
                         recharge_threshold_timestamp: None,
                         zero_credits_timestamp: None,
+                        segments,
                     }) {
                         tracing::error!("Failed to log SMS notification usage: {}", e);
                     }
@@ -660,6 +685,7 @@ pub async fn send_notification_with_context(
                         status: Some("failed".to_string()),
                         recharge_threshold_timestamp: None,
                         zero_credits_timestamp: None,
+                        segments: None,
                     }) {
                         tracing::error!("Failed to log failed SMS notification: {}", log_err);
                     }
@@ -673,6 +699,144 @@ pub async fn send_notification_with_context(
     sms_delivered
 }
 
+/// File a delivered notification under its reply code. Important alerts
+/// are keyed by the id they went out with so a coded rating finds them.
+fn remember_reply_code(
+    state: &Arc<AppState>,
+    user_id: i32,
+    code: &str,
+    content_type: &str,
+    meta: Option<&NotificationMeta>,
+    sid: &str,
+    notification: &str,
+) {
+    use crate::proactive::reply_context::ReplyTarget;
+    let target = if content_type.starts_with("system_important") {
+        ReplyTarget::Alert {
+            sid: sid.to_string(),
+        }
+    } else {
+        meta.and_then(|m| m.reply_target.clone())
+            .unwrap_or(ReplyTarget::Notification)
+    };
+    crate::proactive::reply_context::record(state, user_id, code, &target, notification);
+}
+
+/// Whether a notification also goes out by SMS (or push) next to its
+/// Matrix and email legs. Each channel keeps its own per-category setting,
+/// so they are combined here: "both" on either channel keeps the SMS copy,
+/// whatever the other one says, and otherwise SMS only stands in when
+/// neither Matrix nor email `delivered`.
+pub fn sends_sms_copy(
+    matrix: crate::models::matrix_channel_models::DeliveryMode,
+    email: crate::models::email_channel_models::EmailDeliveryMode,
+    delivered: bool,
+) -> bool {
+    !delivered
+        || matrix == crate::models::matrix_channel_models::DeliveryMode::Both
+        || email == crate::models::email_channel_models::EmailDeliveryMode::Both
+}
+
+/// Send notification text over the channels the user picked for its
+/// category: SMS, their Matrix DM room, email or any mix, combined as
+/// [`sends_sms_copy`] describes. A failing room or mailbox falls back to
+/// SMS; the SMS leg goes to the user's push endpoint instead when they
+/// routed the category there. The returned id is the SMS (or push) one
+/// whenever that leg went out, so status callbacks still match; the
+/// segment count is set only when it was an SMS.
+async fn deliver_notification_text(
+    state: &Arc<AppState>,
+    user: &crate::models::user_models::User,
+    content_type: &str,
+    body: &str,
+) -> Result<
+    (crate::channels::traits::ChannelMessageId, Option<i32>),
+    crate::channels::traits::ChannelError,
+> {
+    let mode = crate::channels::matrix_channel::notification_delivery(state, user.id, content_type);
+    let matrix_event = if mode.includes_matrix() {
+        match crate::channels::matrix_channel::send_to_dm(state, user, body).await {
+            Ok(event_id) => Some(event_id),
+            Err(e) => {
+                tracing::warn!(
+                    "Matrix notification for user {} failed, falling back to SMS: {}",
+                    user.id,
+                    e
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let email_mode =
+        crate::channels::email_channel::notification_delivery(state, user.id, content_type);
+    let email_id = if email_mode.includes_email() {
+        match crate::channels::email_channel::send_to_email(state, user, content_type, body).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                tracing::warn!(
+                    "Email notification for user {} failed, falling back to SMS: {}",
+                    user.id,
+                    e
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let delivered = matrix_event.is_some() || email_id.is_some();
+    let sms_copy = sends_sms_copy(mode, email_mode, delivered);
+    match matrix_event.or(email_id) {
+        Some(id) if !sms_copy => Ok((id, None)),
+        Some(id) => match send_sms_or_push(state, user, content_type, body).await {
+            Ok(sent) => Ok(sent),
+            Err(e) => {
+                tracing::error!(
+                    "SMS copy of notification for user {} failed; another channel delivered: {}",
+                    user.id,
+                    e
+                );
+                Ok((id, None))
+            }
+        },
+        None => send_sms_or_push(state, user, content_type, body).await,
+    }
+}
+
+/// Try the user's push endpoint first for categories they routed there
+/// (digests and rules), then SMS. Only an SMS comes back with its segment
+/// count.
+async fn send_sms_or_push(
+    state: &Arc<AppState>,
+    user: &crate::models::user_models::User,
+    content_type: &str,
+    body: &str,
+) -> Result<
+    (crate::channels::traits::ChannelMessageId, Option<i32>),
+    crate::channels::traits::ChannelError,
+> {
+    if crate::channels::push_channel::routes_to_push(state, user.id, content_type) {
+        match crate::channels::push_channel::send_to_push(state, user, body).await {
+            Ok(id) => return Ok((id, None)),
+            Err(e) => tracing::warn!(
+                "Push notification for user {} failed, falling back to SMS: {}",
+                user.id,
+                e
+            ),
+        }
+    }
+    let id = state.channel_router.send_to_user(user, body, None).await?;
+    Ok((
+        id,
+        Some(crate::utils::sms_encoding::outbound_sms_segments(
+            user, body,
+        )),
+    ))
+}
+
 /// Send SMS to an arbitrary phone (not necessarily a Lightfriend user).
 /// Credits are deducted from `from_user`. Used for the accountability-friend
 /// nudge, where the recipient has no account.
@@ -735,6 +899,9 @@ pub async fn send_sms_to_external_phone(
                 status: Some("delivered".to_string()),
                 recharge_threshold_timestamp: None,
                 zero_credits_timestamp: None,
+                segments: Some(crate::utils::sms_encoding::outbound_sms_segments(
+                    from_user, &body,
+                )),
             }) {
                 tracing::error!("Failed to log accountability nudge usage: {}", e);
             }
@@ -0,0 +1,199 @@
+//! Minimal raster canvas shared by the QR, chart and map renderers.
+//!
+//! Everything is drawn with integer primitives onto an RGB buffer and
+//! encoded as PNG. Flat colors and hard edges keep the files small (a few
+//! KB) and survive carrier MMS recompression better than anti-aliasing.
+
+use image::{ImageEncoder, Rgb, RgbImage};
+use thiserror::Error;
+
+use crate::rendering::font::{glyph, text_width, ADVANCE, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+pub const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+pub const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+pub const GRAY: Rgb<u8> = Rgb([150, 150, 150]);
+pub const LIGHT_GRAY: Rgb<u8> = Rgb([225, 225, 225]);
+pub const ACCENT: Rgb<u8> = Rgb([30, 100, 200]);
+pub const RED: Rgb<u8> = Rgb([210, 40, 40]);
+
+#[derive(Debug, Error)]
+pub enum RenderError {
+    #[error("Invalid render input: {0}")]
+    InvalidInput(String),
+    #[error("Failed to encode image: {0}")]
+    Encode(String),
+}
+
+/// A rendered PNG plus the text a user without MMS gets instead.
+#[derive(Debug, Clone)]
+pub struct RenderedImage {
+    pub png: Vec<u8>,
+    pub alt_text: String,
+}
+
+pub struct Canvas {
+    img: RgbImage,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Rgb<u8>) -> Self {
+        Self {
+            img: RgbImage::from_pixel(width, height, background),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.img.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.img.height()
+    }
+
+    /// Set one pixel; coordinates outside the canvas are ignored.
+    pub fn put(&mut self, x: i64, y: i64, color: Rgb<u8>) {
+        if x >= 0 && y >= 0 && (x as u32) < self.img.width() && (y as u32) < self.img.height() {
+            self.img.put_pixel(x as u32, y as u32, color);
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i64, y: i64, w: u32, h: u32, color: Rgb<u8>) {
+        for dy in 0..h as i64 {
+            for dx in 0..w as i64 {
+                self.put(x + dx, y + dy, color);
+            }
+        }
+    }
+
+    /// One-pixel outline.
+    pub fn stroke_rect(&mut self, x: i64, y: i64, w: u32, h: u32, color: Rgb<u8>) {
+        let (x2, y2) = (x + w as i64 - 1, y + h as i64 - 1);
+        self.line(x, y, x2, y, 1, color);
+        self.line(x, y2, x2, y2, 1, color);
+        self.line(x, y, x, y2, 1, color);
+        self.line(x2, y, x2, y2, 1, color);
+    }
+
+    pub fn line(&mut self, x0: i64, y0: i64, x1: i64, y1: i64, thickness: u32, color: Rgb<u8>) {
+        self.walk_line(x0, y0, x1, y1, thickness, color, None);
+    }
+
+    /// Line drawn as `dash` pixels on, `dash` pixels off.
+    #[allow(clippy::too_many_arguments)]
+    pub fn dashed_line(
+        &mut self,
+        x0: i64,
+        y0: i64,
+        x1: i64,
+        y1: i64,
+        thickness: u32,
+        dash: u32,
+        color: Rgb<u8>,
+    ) {
+        self.walk_line(x0, y0, x1, y1, thickness, color, Some(dash.max(1)));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn walk_line(
+        &mut self,
+        x0: i64,
+        y0: i64,
+        x1: i64,
+        y1: i64,
+        thickness: u32,
+        color: Rgb<u8>,
+        dash: Option<u32>,
+    ) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        let half = thickness as i64 / 2;
+        let mut step: u32 = 0;
+        loop {
+            let on = dash.map(|d| (step / d) % 2 == 0).unwrap_or(true);
+            if on {
+                self.fill_rect(
+                    x - half,
+                    y - half,
+                    thickness.max(1),
+                    thickness.max(1),
+                    color,
+                );
+            }
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+            step += 1;
+        }
+    }
+
+    pub fn fill_circle(&mut self, cx: i64, cy: i64, r: i64, color: Rgb<u8>) {
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy <= r * r {
+                    self.put(cx + dx, cy + dy, color);
+                }
+            }
+        }
+    }
+
+    /// Draw `text` with its top-left corner at (x, y).
+    pub fn text(&mut self, x: i64, y: i64, scale: u32, text: &str, color: Rgb<u8>) {
+        let scale = scale.max(1);
+        let mut pen = x;
+        for c in text.chars() {
+            let rows = glyph(c);
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if (bits >> (GLYPH_WIDTH - 1 - col)) & 1 == 1 {
+                        self.fill_rect(
+                            pen + (col * scale) as i64,
+                            y + (row as u32 * scale) as i64,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+            pen += (ADVANCE * scale) as i64;
+        }
+    }
+
+    pub fn text_width(text: &str, scale: u32) -> u32 {
+        text_width(text, scale.max(1))
+    }
+
+    pub fn text_height(scale: u32) -> u32 {
+        GLYPH_HEIGHT * scale.max(1)
+    }
+
+    /// Copy `src` onto the canvas with its top-left corner at (x, y).
+    pub fn blit(&mut self, src: &RgbImage, x: i64, y: i64) {
+        for (sx, sy, px) in src.enumerate_pixels() {
+            self.put(x + sx as i64, y + sy as i64, *px);
+        }
+    }
+
+    pub fn into_png(self) -> Result<Vec<u8>, RenderError> {
+        let mut out = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut out)
+            .write_image(
+                self.img.as_raw(),
+                self.img.width(),
+                self.img.height(),
+                image::ColorType::Rgb8,
+            )
+            .map_err(|e| RenderError::Encode(e.to_string()))?;
+        Ok(out)
+    }
+}
@@ -0,0 +1,255 @@
+//! Small line and bar charts, e.g. the hourly temperature for tomorrow or a
+//! week of electricity prices.
+
+use serde::Deserialize;
+
+use crate::rendering::canvas::{
+    Canvas, RenderError, RenderedImage, ACCENT, BLACK, GRAY, LIGHT_GRAY, WHITE,
+};
+
+const WIDTH: u32 = 480;
+const HEIGHT: u32 = 320;
+const SCALE: u32 = 2;
+const MARGIN: i64 = 12;
+const MAX_POINTS: usize = 60;
+const MAX_X_LABELS: usize = 6;
+const MAX_X_LABEL_CHARS: usize = 6;
+/// Up to this many points the alt text lists every value.
+const ALT_TEXT_VALUE_LIMIT: usize = 12;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartStyle {
+    #[default]
+    Line,
+    Bar,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChartSpec {
+    pub title: String,
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// One per value; empty means the points are numbered 1..n.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub values: Vec<f64>,
+    #[serde(default)]
+    pub style: ChartStyle,
+}
+
+pub fn render_chart(spec: &ChartSpec) -> Result<RenderedImage, RenderError> {
+    validate(spec)?;
+    let labels = labels_for(spec);
+    let unit = spec.unit.clone().unwrap_or_default();
+
+    let data_min = spec.values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let data_max = spec
+        .values
+        .iter()
+        .cloned()
+        .fold(f64::NEG_INFINITY, f64::max);
+    // Bars grow from zero, so zero must be on the axis; lines may float.
+    let (lo, hi) = match spec.style {
+        ChartStyle::Bar => (data_min.min(0.0), data_max.max(0.0)),
+        ChartStyle::Line => (data_min, data_max),
+    };
+    let (axis_min, axis_max, step) = nice_axis(lo, hi);
+
+    let mut canvas = Canvas::new(WIDTH, HEIGHT, WHITE);
+
+    let title = if unit.is_empty() {
+        spec.title.clone()
+    } else {
+        format!("{} ({})", spec.title, unit)
+    };
+    canvas.text(MARGIN, MARGIN, SCALE, &title, BLACK);
+
+    let ticks: Vec<f64> = (0..)
+        .map(|i| axis_min + step * i as f64)
+        .take_while(|v| *v <= axis_max + step / 2.0)
+        .collect();
+    let tick_labels: Vec<String> = ticks.iter().map(|v| format_number(*v)).collect();
+    let y_label_width = tick_labels
+        .iter()
+        .map(|l| Canvas::text_width(l, SCALE))
+        .max()
+        .unwrap_or(0) as i64;
+
+    let text_h = Canvas::text_height(SCALE) as i64;
+    let plot_left = MARGIN + y_label_width + 8;
+    let plot_right = WIDTH as i64 - MARGIN;
+    let plot_top = MARGIN + text_h + 16;
+    let plot_bottom = HEIGHT as i64 - MARGIN - text_h - 8;
+    let plot_w = (plot_right - plot_left) as f64;
+    let plot_h = (plot_bottom - plot_top) as f64;
+
+    let y_of = |v: f64| -> i64 {
+        plot_bottom - ((v - axis_min) / (axis_max - axis_min) * plot_h).round() as i64
+    };
+
+    for (tick, label) in ticks.iter().zip(&tick_labels) {
+        let y = y_of(*tick);
+        canvas.line(plot_left, y, plot_right, y, 1, LIGHT_GRAY);
+        let lw = Canvas::text_width(label, SCALE) as i64;
+        canvas.text(plot_left - 8 - lw, y - text_h / 2, SCALE, label, GRAY);
+    }
+    canvas.line(plot_left, plot_top, plot_left, plot_bottom, 2, BLACK);
+    canvas.line(plot_left, plot_bottom, plot_right, plot_bottom, 2, BLACK);
+
+    let n = spec.values.len();
+    let slot = plot_w / n as f64;
+    let x_of = |i: usize| -> i64 { plot_left + (slot * (i as f64 + 0.5)).round() as i64 };
+
+    match spec.style {
+        ChartStyle::Bar => {
+            let bar_w = ((slot * 0.7).round() as u32).max(1);
+            let zero = y_of(0.0);
+            for (i, v) in spec.values.iter().enumerate() {
+                let top = y_of(*v).min(zero);
+                let bottom = y_of(*v).max(zero);
+                canvas.fill_rect(
+                    x_of(i) - bar_w as i64 / 2,
+                    top,
+                    bar_w,
+                    (bottom - top).max(1) as u32,
+                    ACCENT,
+                );
+            }
+        }
+        ChartStyle::Line => {
+            let points: Vec<(i64, i64)> = spec
+                .values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (x_of(i), y_of(*v)))
+                .collect();
+            for pair in points.windows(2) {
+                canvas.line(pair[0].0, pair[0].1, pair[1].0, pair[1].1, 3, ACCENT);
+            }
+            if n <= 24 {
+                for (x, y) in &points {
+                    canvas.fill_circle(*x, *y, 4, ACCENT);
+                }
+            }
+        }
+    }
+
+    for i in x_label_indices(n) {
+        let label: String = labels[i].chars().take(MAX_X_LABEL_CHARS).collect();
+        let lw = Canvas::text_width(&label, SCALE) as i64;
+        let x = (x_of(i) - lw / 2).clamp(0, WIDTH as i64 - lw);
+        canvas.text(x, plot_bottom + 8, SCALE, &label, BLACK);
+    }
+
+    Ok(RenderedImage {
+        png: canvas.into_png()?,
+        alt_text: alt_text(spec, &labels, &unit),
+    })
+}
+
+fn validate(spec: &ChartSpec) -> Result<(), RenderError> {
+    if spec.values.is_empty() {
+        return Err(RenderError::InvalidInput("chart has no values".to_string()));
+    }
+    if spec.values.len() > MAX_POINTS {
+        return Err(RenderError::InvalidInput(format!(
+            "chart has {} values, at most {} fit",
+            spec.values.len(),
+            MAX_POINTS
+        )));
+    }
+    if spec.values.iter().any(|v| !v.is_finite()) {
+        return Err(RenderError::InvalidInput(
+            "chart values must be finite numbers".to_string(),
+        ));
+    }
+    if !spec.labels.is_empty() && spec.labels.len() != spec.values.len() {
+        return Err(RenderError::InvalidInput(format!(
+            "chart has {} labels for {} values",
+            spec.labels.len(),
+            spec.values.len()
+        )));
+    }
+    Ok(())
+}
+
+fn labels_for(spec: &ChartSpec) -> Vec<String> {
+    if spec.labels.is_empty() {
+        (1..=spec.values.len()).map(|i| i.to_string()).collect()
+    } else {
+        spec.labels.clone()
+    }
+}
+
+/// Evenly spaced label positions, always including the first and last.
+fn x_label_indices(n: usize) -> Vec<usize> {
+    if n <= MAX_X_LABELS {
+        return (0..n).collect();
+    }
+    let mut out: Vec<usize> = (0..MAX_X_LABELS)
+        .map(|k| (k * (n - 1) + (MAX_X_LABELS - 1) / 2) / (MAX_X_LABELS - 1))
+        .collect();
+    out.dedup();
+    out
+}
+
+/// Round the data range out to a 1/2/2.5/5 x 10^k step with about four
+/// gridlines, so tick labels stay short.
+pub fn nice_axis(lo: f64, hi: f64) -> (f64, f64, f64) {
+    let (lo, hi) = if (hi - lo).abs() < f64::EPSILON {
+        (lo - 1.0, hi + 1.0)
+    } else {
+        (lo, hi)
+    };
+    let raw = (hi - lo) / 4.0;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|s| *s >= raw)
+        .unwrap_or(10.0 * magnitude);
+    ((lo / step).floor() * step, (hi / step).ceil() * step, step)
+}
+
+/// Up to two decimals, trailing zeros dropped.
+pub fn format_number(v: f64) -> String {
+    let s = format!("{:.2}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+fn alt_text(spec: &ChartSpec, labels: &[String], unit: &str) -> String {
+    let (mut min_i, mut max_i) = (0, 0);
+    for (i, v) in spec.values.iter().enumerate() {
+        if *v < spec.values[min_i] {
+            min_i = i;
+        }
+        if *v > spec.values[max_i] {
+            max_i = i;
+        }
+    }
+    let mut text = format!(
+        "{}: low {}{} ({}), high {}{} ({}).",
+        spec.title,
+        format_number(spec.values[min_i]),
+        unit,
+        labels[min_i],
+        format_number(spec.values[max_i]),
+        unit,
+        labels[max_i]
+    );
+    if spec.values.len() <= ALT_TEXT_VALUE_LIMIT {
+        let values: Vec<String> = labels
+            .iter()
+            .zip(&spec.values)
+            .map(|(l, v)| format!("{} {}", l, format_number(*v)))
+            .collect();
+        text.push_str(&format!(" {}", values.join(", ")));
+    }
+    text
+}
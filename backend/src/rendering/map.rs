@@ -0,0 +1,341 @@
+//! Static maps with lettered markers, e.g. the next Tesla supercharger or
+//! the place a calendar event happens.
+//!
+//! No map API is needed. With `MAP_TILE_URL` set (an XYZ template such as
+//! `https://tile.example.org/{z}/{x}/{y}.png`) the background is stitched
+//! from raster tiles; without it, or when any tile fails, the markers sit on
+//! a plain grid with a north arrow and scale bar, which is still enough to
+//! see direction and distance.
+
+use std::time::Duration;
+
+use image::Rgb;
+use serde::Deserialize;
+
+use crate::rendering::canvas::{
+    Canvas, RenderError, RenderedImage, BLACK, GRAY, LIGHT_GRAY, RED, WHITE,
+};
+
+const WIDTH: u32 = 480;
+const HEIGHT: u32 = 360;
+const TILE: f64 = 256.0;
+const MAX_MARKERS: usize = 8;
+/// Zoom for a single marker: a few streets around it.
+const SINGLE_MARKER_ZOOM: u8 = 15;
+const MAX_ZOOM: u8 = 17;
+/// Keep markers this far inside the edges so their labels fit.
+const PADDING: f64 = 48.0;
+const TILE_TIMEOUT: Duration = Duration::from_secs(5);
+const SCHEMATIC_BACKGROUND: Rgb<u8> = Rgb([242, 239, 233]);
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MapMarker {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Fractional world-pixel coordinates at `zoom` (web mercator).
+pub fn project(lat: f64, lon: f64, zoom: u8) -> (f64, f64) {
+    let size = TILE * 2f64.powi(zoom as i32);
+    let lat_rad = lat.to_radians();
+    let x = (lon + 180.0) / 360.0 * size;
+    let y = (1.0 - (lat_rad.tan() + 1.0 / lat_rad.cos()).ln() / std::f64::consts::PI) / 2.0 * size;
+    (x, y)
+}
+
+/// Highest zoom at which every marker fits inside the padded canvas.
+pub fn choose_zoom(markers: &[MapMarker]) -> u8 {
+    if markers.len() <= 1 {
+        return SINGLE_MARKER_ZOOM;
+    }
+    let avail_w = WIDTH as f64 - 2.0 * PADDING;
+    let avail_h = HEIGHT as f64 - 2.0 * PADDING;
+    for zoom in (1..=MAX_ZOOM).rev() {
+        let (min_x, min_y, max_x, max_y) = bounds(markers, zoom);
+        if max_x - min_x <= avail_w && max_y - min_y <= avail_h {
+            return zoom;
+        }
+    }
+    1
+}
+
+fn bounds(markers: &[MapMarker], zoom: u8) -> (f64, f64, f64, f64) {
+    markers.iter().fold(
+        (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(a, b, c, d), m| {
+            let (x, y) = project(m.latitude, m.longitude, zoom);
+            (a.min(x), b.min(y), c.max(x), d.max(y))
+        },
+    )
+}
+
+/// Great-circle distance in kilometres.
+pub fn haversine_km(a: &MapMarker, b: &MapMarker) -> f64 {
+    let (lat1, lat2) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (b.longitude - a.longitude).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+pub async fn render_map(markers: &[MapMarker]) -> Result<RenderedImage, RenderError> {
+    validate(markers)?;
+    let zoom = choose_zoom(markers);
+    let (min_x, min_y, max_x, max_y) = bounds(markers, zoom);
+    let left = ((min_x + max_x) / 2.0 - WIDTH as f64 / 2.0).round() as i64;
+    let top = ((min_y + max_y) / 2.0 - HEIGHT as f64 / 2.0).round() as i64;
+
+    let mut canvas = Canvas::new(WIDTH, HEIGHT, SCHEMATIC_BACKGROUND);
+    let attribution = match std::env::var("MAP_TILE_URL") {
+        Ok(template) if !template.trim().is_empty() => {
+            match fetch_background(&template, zoom, left, top).await {
+                Ok(tiles) => {
+                    for (img, x, y) in &tiles {
+                        canvas.blit(img, *x, *y);
+                    }
+                    Some(
+                        std::env::var("MAP_TILE_ATTRIBUTION")
+                            .unwrap_or_else(|_| "© OpenStreetMap".to_string()),
+                    )
+                }
+                Err(e) => {
+                    tracing::warn!("Map tiles unavailable, drawing schematic map: {}", e);
+                    draw_schematic(&mut canvas);
+                    None
+                }
+            }
+        }
+        _ => {
+            draw_schematic(&mut canvas);
+            None
+        }
+    };
+
+    let points: Vec<(i64, i64)> = markers
+        .iter()
+        .map(|m| {
+            let (x, y) = project(m.latitude, m.longitude, zoom);
+            (x.round() as i64 - left, y.round() as i64 - top)
+        })
+        .collect();
+    for pair in points.windows(2) {
+        canvas.dashed_line(pair[0].0, pair[0].1, pair[1].0, pair[1].1, 3, 8, RED);
+    }
+    for (i, (m, (x, y))) in markers.iter().zip(&points).enumerate() {
+        draw_marker(&mut canvas, *x, *y, marker_letter(i), m.label.as_deref());
+    }
+
+    let center_lat = markers.iter().map(|m| m.latitude).sum::<f64>() / markers.len() as f64;
+    draw_scale_bar(&mut canvas, center_lat, zoom);
+    if let Some(text) = attribution {
+        let w = Canvas::text_width(&text, 1) as i64;
+        let (x, y) = (WIDTH as i64 - w - 6, HEIGHT as i64 - 13);
+        canvas.fill_rect(x - 3, y - 3, w as u32 + 6, 13, WHITE);
+        canvas.text(x, y, 1, &text, BLACK);
+    }
+
+    Ok(RenderedImage {
+        png: canvas.into_png()?,
+        alt_text: alt_text(markers),
+    })
+}
+
+fn validate(markers: &[MapMarker]) -> Result<(), RenderError> {
+    if markers.is_empty() {
+        return Err(RenderError::InvalidInput(
+            "map needs at least one marker".to_string(),
+        ));
+    }
+    if markers.len() > MAX_MARKERS {
+        return Err(RenderError::InvalidInput(format!(
+            "map has {} markers, at most {} fit",
+            markers.len(),
+            MAX_MARKERS
+        )));
+    }
+    // Web mercator stops at ~85°; nobody needs a static map of the poles.
+    for m in markers {
+        if !(-85.0..=85.0).contains(&m.latitude) || !(-180.0..=180.0).contains(&m.longitude) {
+            return Err(RenderError::InvalidInput(format!(
+                "coordinates out of range: {}, {}",
+                m.latitude, m.longitude
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn marker_letter(i: usize) -> char {
+    (b'A' + i as u8) as char
+}
+
+/// Fetch every tile that overlaps the canvas. All or nothing: a map with
+/// holes is worse than the schematic one.
+async fn fetch_background(
+    template: &str,
+    zoom: u8,
+    left: i64,
+    top: i64,
+) -> Result<Vec<(image::RgbImage, i64, i64)>, String> {
+    let tile = TILE as i64;
+    let tiles_per_axis = 1i64 << zoom;
+    let mut wanted = Vec::new();
+    for ty in top.div_euclid(tile)..=(top + HEIGHT as i64 - 1).div_euclid(tile) {
+        if ty < 0 || ty >= tiles_per_axis {
+            continue;
+        }
+        for tx in left.div_euclid(tile)..=(left + WIDTH as i64 - 1).div_euclid(tile) {
+            let url = template
+                .replace("{z}", &zoom.to_string())
+                .replace("{x}", &tx.rem_euclid(tiles_per_axis).to_string())
+                .replace("{y}", &ty.to_string());
+            wanted.push((url, tx * tile - left, ty * tile - top));
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(TILE_TIMEOUT)
+        .user_agent("Lightfriend static maps")
+        .build()
+        .map_err(|e| e.to_string())?;
+    let fetches = wanted.into_iter().map(|(url, x, y)| {
+        let client = client.clone();
+        async move { fetch_tile(&client, &url).await.map(|img| (img, x, y)) }
+    });
+    futures::future::join_all(fetches)
+        .await
+        .into_iter()
+        .collect()
+}
+
+async fn fetch_tile(client: &reqwest::Client, url: &str) -> Result<image::RgbImage, String> {
+    let resp = client.get(url).send().await.map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("tile returned {}", resp.status()));
+    }
+    let bytes = resp.bytes().await.map_err(|e| e.to_string())?;
+    let img = image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+    Ok(img.to_rgb8())
+}
+
+fn draw_schematic(canvas: &mut Canvas) {
+    for x in (0..WIDTH as i64).step_by(60) {
+        canvas.line(x, 0, x, HEIGHT as i64 - 1, 1, LIGHT_GRAY);
+    }
+    for y in (0..HEIGHT as i64).step_by(60) {
+        canvas.line(0, y, WIDTH as i64 - 1, y, 1, LIGHT_GRAY);
+    }
+    // North arrow, top right.
+    let (x, y) = (WIDTH as i64 - 24, 14);
+    canvas.line(x, y + 28, x, y, 2, BLACK);
+    canvas.line(x, y, x - 6, y + 8, 2, BLACK);
+    canvas.line(x, y, x + 6, y + 8, 2, BLACK);
+    canvas.text(x - 4, y + 32, 2, "N", BLACK);
+}
+
+fn draw_marker(canvas: &mut Canvas, x: i64, y: i64, letter: char, label: Option<&str>) {
+    canvas.fill_circle(x, y, 12, WHITE);
+    canvas.fill_circle(x, y, 10, RED);
+    canvas.text(x - 4, y - 6, 2, &letter.to_string(), WHITE);
+
+    let Some(label) = label.map(str::trim).filter(|l| !l.is_empty()) else {
+        return;
+    };
+    let label: String = label.chars().take(18).collect();
+    let w = Canvas::text_width(&label, 2) as i64 + 8;
+    let h = Canvas::text_height(2) as i64 + 8;
+    // Right of the marker unless that runs off the canvas.
+    let bx = if x + 16 + w <= WIDTH as i64 {
+        x + 16
+    } else {
+        (x - 16 - w).max(0)
+    };
+    let by = (y - h / 2).clamp(0, HEIGHT as i64 - h);
+    canvas.fill_rect(bx, by, w as u32, h as u32, WHITE);
+    canvas.stroke_rect(bx, by, w as u32, h as u32, GRAY);
+    canvas.text(bx + 4, by + 4, 2, &label, BLACK);
+}
+
+fn draw_scale_bar(canvas: &mut Canvas, latitude: f64, zoom: u8) {
+    let metres_per_px = 156_543.033_92 * latitude.to_radians().cos() / 2f64.powi(zoom as i32);
+    let candidates = [
+        10.0,
+        20.0,
+        50.0,
+        100.0,
+        200.0,
+        500.0,
+        1_000.0,
+        2_000.0,
+        5_000.0,
+        10_000.0,
+        20_000.0,
+        50_000.0,
+        100_000.0,
+        200_000.0,
+        500_000.0,
+        1_000_000.0,
+    ];
+    let Some(metres) = candidates
+        .iter()
+        .rev()
+        .find(|m| *m / metres_per_px <= 120.0)
+        .copied()
+    else {
+        return;
+    };
+    let px = (metres / metres_per_px).round() as i64;
+    let label = if metres >= 1_000.0 {
+        format!("{} km", metres / 1_000.0)
+    } else {
+        format!("{} m", metres)
+    };
+    let (x, y) = (10, HEIGHT as i64 - 14);
+    let box_w = px.max(Canvas::text_width(&label, 2) as i64) + 12;
+    canvas.fill_rect(x - 4, y - 22, box_w as u32, 30, WHITE);
+    canvas.line(x, y, x + px, y, 3, BLACK);
+    canvas.line(x, y - 6, x, y, 2, BLACK);
+    canvas.line(x + px, y - 6, x + px, y, 2, BLACK);
+    canvas.text(x, y - 20, 2, &label, BLACK);
+}
+
+fn alt_text(markers: &[MapMarker]) -> String {
+    let places: Vec<String> = markers
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let name = m
+                .label
+                .as_deref()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .unwrap_or("Marker");
+            format!(
+                "{}) {} ({:.5}, {:.5})",
+                marker_letter(i),
+                name,
+                m.latitude,
+                m.longitude
+            )
+        })
+        .collect();
+    let mut text = format!("Map: {}.", places.join("; "));
+    if markers.len() > 1 {
+        let km: f64 = markers.windows(2).map(|p| haversine_km(&p[0], &p[1])).sum();
+        text.push_str(&format!(" Straight-line distance: {:.1} km.", km));
+    }
+    let first = &markers[0];
+    text.push_str(&format!(
+        " https://www.openstreetmap.org/?mlat={:.5}&mlon={:.5}#map=15/{:.5}/{:.5}",
+        first.latitude, first.longitude, first.latitude, first.longitude
+    ));
+    text
+}
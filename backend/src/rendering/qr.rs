@@ -0,0 +1,46 @@
+//! QR codes as PNG, e.g. a boarding pass or event ticket found in email,
+//! so a dumbphone with MMS can hold it up to a scanner.
+
+use qrcodegen::{QrCode, QrCodeEcc};
+
+use crate::rendering::canvas::{Canvas, RenderError, RenderedImage, BLACK, WHITE};
+
+/// Quiet zone required around the symbol by the QR spec, in modules.
+const QUIET_ZONE: i32 = 4;
+/// Target edge length. Scanners need crisp modules, so each module is an
+/// integer number of pixels and the final size lands at or just under this.
+const TARGET_PX: i32 = 360;
+/// Floor for dense codes; below 3 px per module phone screens blur them.
+const MIN_MODULE_PX: i32 = 3;
+
+pub fn render_qr(data: &str) -> Result<RenderedImage, RenderError> {
+    if data.trim().is_empty() {
+        return Err(RenderError::InvalidInput("QR data is empty".to_string()));
+    }
+    let qr = QrCode::encode_text(data, QrCodeEcc::Medium)
+        .map_err(|_| RenderError::InvalidInput("QR data is too long".to_string()))?;
+
+    let modules = qr.size() + 2 * QUIET_ZONE;
+    let module_px = (TARGET_PX / modules).max(MIN_MODULE_PX);
+    let side = (modules * module_px) as u32;
+
+    let mut canvas = Canvas::new(side, side, WHITE);
+    for y in 0..qr.size() {
+        for x in 0..qr.size() {
+            if qr.get_module(x, y) {
+                canvas.fill_rect(
+                    ((x + QUIET_ZONE) * module_px) as i64,
+                    ((y + QUIET_ZONE) * module_px) as i64,
+                    module_px as u32,
+                    module_px as u32,
+                    BLACK,
+                );
+            }
+        }
+    }
+
+    Ok(RenderedImage {
+        png: canvas.into_png()?,
+        alt_text: format!("QR code content: {}", data),
+    })
+}
@@ -0,0 +1,82 @@
+//! Short-lived in-memory home for rendered images.
+//!
+//! Twilio and Telnyx fetch MMS media from a public URL rather than taking
+//! the bytes, so a rendered PNG has to be reachable for the few seconds it
+//! takes the carrier to pull it. Images live here under a random token for
+//! `TTL` and are never written to disk or the database. The alt text rides
+//! along so the router can fall back to it on channels without media.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::rendering::canvas::RenderedImage;
+
+/// Long enough for a provider retry, short enough that a leaked URL dies
+/// quickly.
+pub const TTL: Duration = Duration::from_secs(15 * 60);
+const MAX_ENTRIES: usize = 256;
+const ROUTE_PREFIX: &str = "/api/media/rendered/";
+
+struct Entry {
+    image: RenderedImage,
+    created: Instant,
+}
+
+fn entries() -> &'static Mutex<HashMap<String, Entry>> {
+    static STORE: OnceLock<Mutex<HashMap<String, Entry>>> = OnceLock::new();
+    STORE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Keep `image` and return its token. Expired entries are dropped on every
+/// insert; past `MAX_ENTRIES` the oldest goes.
+pub fn put(image: RenderedImage) -> String {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let mut map = entries().lock().unwrap_or_else(|e| e.into_inner());
+    map.retain(|_, e| e.created.elapsed() < TTL);
+    if map.len() >= MAX_ENTRIES {
+        if let Some(oldest) = map
+            .iter()
+            .min_by_key(|(_, e)| e.created)
+            .map(|(k, _)| k.clone())
+        {
+            map.remove(&oldest);
+        }
+    }
+    map.insert(
+        token.clone(),
+        Entry {
+            image,
+            created: Instant::now(),
+        },
+    );
+    token
+}
+
+pub fn get(token: &str) -> Option<RenderedImage> {
+    let map = entries().lock().unwrap_or_else(|e| e.into_inner());
+    map.get(token)
+        .filter(|e| e.created.elapsed() < TTL)
+        .map(|e| e.image.clone())
+}
+
+/// Public URL the carrier fetches. None when `SERVER_URL` is unset, since
+/// a relative URL is useless to Twilio.
+pub fn public_url(token: &str) -> Option<String> {
+    let base = std::env::var("SERVER_URL")
+        .ok()
+        .filter(|s| !s.trim().is_empty())?;
+    Some(format!(
+        "{}{}{}.png",
+        base.trim_end_matches('/'),
+        ROUTE_PREFIX,
+        token
+    ))
+}
+
+/// Alt text for a URL minted by `public_url`, if it is still stored.
+pub fn alt_text_for_url(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once(ROUTE_PREFIX)?;
+    let token = rest.strip_suffix(".png")?;
+    get(token).map(|image| image.alt_text)
+}
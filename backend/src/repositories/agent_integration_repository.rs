@@ -1,15 +1,17 @@
 use crate::models::agent_integration_models::{
-    AgentCredential, AgentPairingSession, NewAgentActionAudit, NewAgentActionIdempotency,
-    NewAgentCredential, NewAgentPairingSession,
+    AgentCreatedItem, AgentCredential, AgentPairingSession, NewAgentActionAudit,
+    NewAgentActionIdempotency, NewAgentCreatedItem, NewAgentCredential, NewAgentPairingSession,
+    DEFAULT_AGENT_SCOPES,
 };
 use crate::models::user_models::NewPendingReplyWatch;
 use crate::pg_schema::{
-    agent_action_audit, agent_action_idempotency, agent_credentials, agent_pairing_sessions,
-    imap_connection, pending_reply_watches, users,
+    agent_action_audit, agent_action_idempotency, agent_created_items, agent_credentials,
+    agent_pairing_sessions, imap_connection, ont_events, pending_reply_watches, users,
 };
 use crate::PgDbPool;
 use diesel::prelude::*;
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use sha2::{Digest, Sha256};
 use std::collections::HashSet;
 
 const IDEMPOTENCY_TTL_SECONDS: i32 = 86_400;
@@ -39,6 +41,16 @@ pub enum IdempotencyClaim {
     InFlight,
 }
 
+/// A reminder created by a credential, as that credential may see it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CreatedReminder {
+    pub id: i32,
+    pub event_id: i32,
+    pub remind_at: Option<i32>,
+    pub status: String,
+    pub created_at: i32,
+}
+
 pub struct CredentialIssue<'a> {
     pub user_id: i32,
     pub token_hash: &'a str,
@@ -84,10 +96,13 @@ impl AgentIntegrationRepository {
         })
     }
 
+    /// Approve a pending pairing with the scopes the user picked. `scopes`
+    /// must already be canonical (see `canonical_scopes`).
     pub fn approve_pairing(
         &self,
         user_id: i32,
         user_code_hash: &str,
+        scopes: &str,
         now: i32,
     ) -> Result<Option<String>, DieselError> {
         let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
@@ -108,6 +123,7 @@ impl AgentIntegrationRepository {
                 .set((
                     agent_pairing_sessions::approved_by_user_id.eq(Some(user_id)),
                     agent_pairing_sessions::approved_at.eq(Some(now)),
+                    agent_pairing_sessions::scopes.eq(Some(scopes)),
                 ))
                 .execute(conn)?;
             Ok(Some(session.client_name))
@@ -156,9 +172,9 @@ impl AgentIntegrationRepository {
                 .select(AgentPairingSession::as_select())
                 .first::<AgentPairingSession>(conn)
                 .optional()?;
-            if session.is_none() {
+            let Some(session) = session else {
                 return Ok(None);
-            }
+            };
             users::table
                 .find(issue.user_id)
                 .select(users::id)
@@ -179,7 +195,7 @@ impl AgentIntegrationRepository {
                     token_hash: issue.token_hash,
                     token_prefix: issue.token_prefix,
                     label: issue.label,
-                    scopes: "reminders,reply_watch_email",
+                    scopes: session.scopes.as_deref().unwrap_or(DEFAULT_AGENT_SCOPES),
                     daily_cap: 20,
                     daily_used: 0,
                     daily_reset_at: next_utc_midnight(issue.issued_at),
@@ -237,26 +253,41 @@ impl AgentIntegrationRepository {
         Ok(count > 0)
     }
 
-    pub fn authenticate_credential(
+    /// The live credential for a token, whatever its scopes.
+    pub fn find_credential(
         &self,
         token_hash: &str,
-        required_scope: &str,
         now: i32,
     ) -> Result<Option<AgentCredential>, DieselError> {
         let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
-        let credential = agent_credentials::table
+        agent_credentials::table
             .filter(agent_credentials::token_hash.eq(token_hash))
             .filter(agent_credentials::revoked_at.is_null())
             .filter(agent_credentials::expires_at.gt(now))
             .select(AgentCredential::as_select())
             .first::<AgentCredential>(&mut conn)
-            .optional()?;
-        Ok(credential.filter(|credential| {
-            credential
-                .scopes
-                .split(',')
-                .any(|scope| scope == required_scope)
-        }))
+            .optional()
+    }
+
+    pub fn authenticate_credential(
+        &self,
+        token_hash: &str,
+        required_scope: &str,
+        now: i32,
+    ) -> Result<Option<AgentCredential>, DieselError> {
+        Ok(self
+            .find_credential(token_hash, now)?
+            .filter(|credential| credential.has_scope(required_scope)))
+    }
+
+    /// Record a read. Reads are audited but do not count against the daily
+    /// action cap.
+    pub fn touch_credential(&self, id: i32, now: i32) -> Result<(), DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::update(agent_credentials::table.find(id))
+            .set(agent_credentials::last_used_at.eq(Some(now)))
+            .execute(&mut conn)?;
+        Ok(())
     }
 
     pub fn claim_credential(
@@ -287,10 +318,7 @@ impl AgentIntegrationRepository {
             };
             if current.revoked_at.is_some()
                 || current.expires_at <= now
-                || !current
-                    .scopes
-                    .split(',')
-                    .any(|scope| scope == required_scope)
+                || !current.has_scope(required_scope)
             {
                 return Ok(CredentialClaim::Invalid);
             }
@@ -470,8 +498,152 @@ impl AgentIntegrationRepository {
             Ok(true)
         })
     }
+
+    pub fn record_created_item(&self, item: NewAgentCreatedItem<'_>) -> Result<(), DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::insert_into(agent_created_items::table)
+            .values(item)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    pub fn find_created_item(
+        &self,
+        credential_id: i32,
+        item_kind: &str,
+        id: i32,
+    ) -> Result<Option<AgentCreatedItem>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        agent_created_items::table
+            .filter(agent_created_items::id.eq(id))
+            .filter(agent_created_items::credential_id.eq(credential_id))
+            .filter(agent_created_items::item_kind.eq(item_kind))
+            .select(AgentCreatedItem::as_select())
+            .first::<AgentCreatedItem>(&mut conn)
+            .optional()
+    }
+
+    /// Reminders this credential created that are still scheduled, being
+    /// sent, or delivered. Cancelled ones drop out.
+    pub fn created_reminders(
+        &self,
+        credential_id: i32,
+        limit: i64,
+    ) -> Result<Vec<CreatedReminder>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        let rows = agent_created_items::table
+            .inner_join(ont_events::table)
+            .filter(agent_created_items::credential_id.eq(credential_id))
+            .filter(agent_created_items::item_kind.eq("reminder"))
+            .filter(ont_events::status.eq_any(&["active", "reminder_sending", "notified"]))
+            .order(agent_created_items::created_at.desc())
+            .limit(limit)
+            .select((
+                agent_created_items::id,
+                ont_events::id,
+                ont_events::remind_at,
+                ont_events::status,
+                agent_created_items::created_at,
+            ))
+            .load::<(i32, i32, Option<i32>, String, i32)>(&mut conn)?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, event_id, remind_at, status, created_at)| CreatedReminder {
+                    id,
+                    event_id,
+                    remind_at,
+                    status,
+                    created_at,
+                },
+            )
+            .collect())
+    }
+
+    /// Reply watches this credential created that have not fired, expired or
+    /// been cancelled. Links to watches that are gone are pruned on the way.
+    pub fn created_reply_watches(
+        &self,
+        credential_id: i32,
+        user_id: i32,
+        now: i32,
+    ) -> Result<Vec<AgentCreatedItem>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        conn.transaction(|conn| {
+            let active = active_email_watch_hashes(conn, user_id, now)?;
+            let items = agent_created_items::table
+                .filter(agent_created_items::credential_id.eq(credential_id))
+                .filter(agent_created_items::item_kind.eq("reply_watch"))
+                .order(agent_created_items::created_at.desc())
+                .select(AgentCreatedItem::as_select())
+                .load::<AgentCreatedItem>(conn)?;
+            let (live, gone): (Vec<_>, Vec<_>) = items.into_iter().partition(|item| {
+                item.expires_at.is_some_and(|expires_at| expires_at > now)
+                    && item
+                        .contact_hash
+                        .as_ref()
+                        .is_some_and(|hash| active.iter().any(|(_, active)| active == hash))
+            });
+            if !gone.is_empty() {
+                let gone_ids = gone.iter().map(|item| item.id).collect::<Vec<_>>();
+                diesel::delete(
+                    agent_created_items::table.filter(agent_created_items::id.eq_any(gone_ids)),
+                )
+                .execute(conn)?;
+            }
+            Ok(live)
+        })
+    }
+
+    /// Remove a credential's reply watch: every inbox row for that sender plus
+    /// the link. Returns whether any watch was still active.
+    pub fn cancel_created_reply_watch(
+        &self,
+        item: &AgentCreatedItem,
+        now: i32,
+    ) -> Result<bool, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        conn.transaction(|conn| {
+            let watch_ids = active_email_watch_hashes(conn, item.user_id, now)?
+                .into_iter()
+                .filter(|(_, hash)| Some(hash) == item.contact_hash.as_ref())
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+            let removed = diesel::delete(
+                pending_reply_watches::table.filter(pending_reply_watches::id.eq_any(&watch_ids)),
+            )
+            .execute(conn)?;
+            diesel::delete(agent_created_items::table.find(item.id)).execute(conn)?;
+            Ok(removed > 0)
+        })
+    }
+}
+
+/// Digest identifying a watched sender without storing the address.
+pub fn contact_hash(email: &str) -> String {
+    hex::encode(Sha256::digest(email.as_bytes()))
 }
 
 fn next_utc_midnight(now: i32) -> i32 {
     ((now / 86_400) + 1) * 86_400
 }
+
+/// Ids and sender digests of a user's active email reply watch rows.
+fn active_email_watch_hashes(
+    conn: &mut PgConnection,
+    user_id: i32,
+    now: i32,
+) -> Result<Vec<(i32, String)>, DieselError> {
+    Ok(pending_reply_watches::table
+        .filter(pending_reply_watches::user_id.eq(user_id))
+        .filter(pending_reply_watches::platform.eq("email"))
+        .filter(pending_reply_watches::expires_at.gt(now))
+        .select((
+            pending_reply_watches::id,
+            pending_reply_watches::contact_identifier,
+        ))
+        .load::<(i32, String)>(conn)?
+        .into_iter()
+        .map(|(id, contact)| (id, contact_hash(&contact)))
+        .collect())
+}
@@ -33,6 +33,9 @@ impl BridgeLoginRepository {
         match bridge_type {
             "whatsapp" | "signal" => bridgev2_login_count(&mut conn, matrix_user_ids, bridge_type),
             "telegram" => telegram_login_count(&mut conn, matrix_user_ids),
+            other if crate::utils::bridgev2_provisioning::network(other).is_some() => {
+                bridgev2_login_count(&mut conn, matrix_user_ids, bridge_type)
+            }
             _ => Err(anyhow!("unsupported bridge type {bridge_type}")),
         }
     }
@@ -43,7 +46,9 @@ pub fn bridge_database_env_name(bridge_type: &str) -> Option<&'static str> {
         "whatsapp" => Some("WHATSAPP_BRIDGE_DATABASE_URL"),
         "signal" => Some("SIGNAL_BRIDGE_DATABASE_URL"),
         "telegram" => Some("TELEGRAM_BRIDGE_DATABASE_URL"),
-        _ => None,
+        other => {
+            crate::utils::bridgev2_provisioning::network(other).map(|network| network.database_env)
+        }
     }
 }
 
@@ -0,0 +1,239 @@
+use crate::api::caldav::CalendarInfo;
+use crate::models::caldav_models::CalDavConnectionResponse;
+use crate::pg_models::{NewPgCalDavConnection, PgCalDavConnection};
+use crate::pg_schema::caldav_connections;
+use crate::utils::encryption::{decrypt, encrypt};
+use crate::PgDbPool;
+use diesel::prelude::*;
+
+/// Decrypted connection details needed to talk to the server.
+pub struct CalDavCredentials {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub calendars: Vec<CalendarInfo>,
+}
+
+pub struct CalDavRepository {
+    pool: PgDbPool,
+}
+
+impl CalDavRepository {
+    pub fn new(pool: PgDbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Store a new CalDAV connection with its discovered calendars
+    pub fn create_connection(
+        &self,
+        user_id: i32,
+        name: &str,
+        credentials: &CalDavCredentials,
+    ) -> Result<PgCalDavConnection, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        let calendars_json = serde_json::to_string(&credentials.calendars)
+            .map_err(|e| format!("Failed to serialize calendars: {}", e))?;
+        let new_connection = NewPgCalDavConnection {
+            user_id,
+            name: name.to_string(),
+            url_encrypted: encrypt(&credentials.url)
+                .map_err(|e| format!("Failed to encrypt URL: {}", e))?,
+            username_encrypted: encrypt(&credentials.username)
+                .map_err(|e| format!("Failed to encrypt username: {}", e))?,
+            password_encrypted: encrypt(&credentials.password)
+                .map_err(|e| format!("Failed to encrypt password: {}", e))?,
+            calendars_encrypted: encrypt(&calendars_json)
+                .map_err(|e| format!("Failed to encrypt calendars: {}", e))?,
+            is_enabled: 1,
+            created_at: chrono::Utc::now().timestamp() as i32,
+        };
+
+        diesel::insert_into(caldav_connections::table)
+            .values(&new_connection)
+            .get_result::<PgCalDavConnection>(&mut conn)
+            .map_err(|e| format!("Failed to insert CalDAV connection: {}", e))
+    }
+
+    /// Replace the stored calendar list after a fresh discovery
+    pub fn update_calendars(
+        &self,
+        connection_id: i32,
+        user_id: i32,
+        calendars: &[CalendarInfo],
+    ) -> Result<(), String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        let calendars_json = serde_json::to_string(calendars)
+            .map_err(|e| format!("Failed to serialize calendars: {}", e))?;
+        let calendars_encrypted =
+            encrypt(&calendars_json).map_err(|e| format!("Failed to encrypt calendars: {}", e))?;
+
+        diesel::update(
+            caldav_connections::table
+                .filter(caldav_connections::id.eq(connection_id))
+                .filter(caldav_connections::user_id.eq(user_id)),
+        )
+        .set(caldav_connections::calendars_encrypted.eq(calendars_encrypted))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to update calendars: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Get all CalDAV connections for a user
+    pub fn get_connections_for_user(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<PgCalDavConnection>, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        caldav_connections::table
+            .filter(caldav_connections::user_id.eq(user_id))
+            .order(caldav_connections::created_at.desc())
+            .load::<PgCalDavConnection>(&mut conn)
+            .map_err(|e| format!("Failed to get CalDAV connections: {}", e))
+    }
+
+    /// Get enabled CalDAV connections for a user, oldest first so the first
+    /// connected calendar stays the default for new events
+    pub fn get_enabled_connections_for_user(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<PgCalDavConnection>, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        caldav_connections::table
+            .filter(caldav_connections::user_id.eq(user_id))
+            .filter(caldav_connections::is_enabled.eq(1))
+            .order(caldav_connections::created_at.asc())
+            .load::<PgCalDavConnection>(&mut conn)
+            .map_err(|e| format!("Failed to get enabled CalDAV connections: {}", e))
+    }
+
+    /// Whether the user has at least one enabled calendar connection
+    pub fn has_enabled_connection(&self, user_id: i32) -> bool {
+        let Ok(mut conn) = self.pool.get() else {
+            return false;
+        };
+
+        diesel::select(diesel::dsl::exists(
+            caldav_connections::table
+                .filter(caldav_connections::user_id.eq(user_id))
+                .filter(caldav_connections::is_enabled.eq(1)),
+        ))
+        .get_result::<bool>(&mut conn)
+        .unwrap_or(false)
+    }
+
+    /// Get a specific connection by ID
+    pub fn get_connection_by_id(
+        &self,
+        connection_id: i32,
+        user_id: i32,
+    ) -> Result<Option<PgCalDavConnection>, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        caldav_connections::table
+            .filter(caldav_connections::id.eq(connection_id))
+            .filter(caldav_connections::user_id.eq(user_id))
+            .first::<PgCalDavConnection>(&mut conn)
+            .optional()
+            .map_err(|e| format!("Failed to get CalDAV connection: {}", e))
+    }
+
+    /// Get a specific connection by name
+    pub fn get_connection_by_name(
+        &self,
+        user_id: i32,
+        name: &str,
+    ) -> Result<Option<PgCalDavConnection>, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        caldav_connections::table
+            .filter(caldav_connections::user_id.eq(user_id))
+            .filter(caldav_connections::name.eq(name))
+            .first::<PgCalDavConnection>(&mut conn)
+            .optional()
+            .map_err(|e| format!("Failed to get CalDAV connection by name: {}", e))
+    }
+
+    /// Toggle connection enabled/disabled status
+    pub fn toggle_connection(&self, connection_id: i32, user_id: i32) -> Result<bool, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        let connection = caldav_connections::table
+            .filter(caldav_connections::id.eq(connection_id))
+            .filter(caldav_connections::user_id.eq(user_id))
+            .first::<PgCalDavConnection>(&mut conn)
+            .map_err(|e| format!("Connection not found: {}", e))?;
+
+        let new_status = if connection.is_enabled == 1 { 0 } else { 1 };
+
+        diesel::update(
+            caldav_connections::table
+                .filter(caldav_connections::id.eq(connection_id))
+                .filter(caldav_connections::user_id.eq(user_id)),
+        )
+        .set(caldav_connections::is_enabled.eq(new_status))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to toggle connection: {}", e))?;
+
+        Ok(new_status == 1)
+    }
+
+    /// Delete a CalDAV connection
+    pub fn delete_connection(&self, connection_id: i32, user_id: i32) -> Result<(), String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        let deleted = diesel::delete(
+            caldav_connections::table
+                .filter(caldav_connections::id.eq(connection_id))
+                .filter(caldav_connections::user_id.eq(user_id)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to delete connection: {}", e))?;
+
+        if deleted == 0 {
+            return Err("Connection not found".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Decrypt everything needed to open a client for this connection
+    pub fn get_credentials(
+        &self,
+        connection: &PgCalDavConnection,
+    ) -> Result<CalDavCredentials, String> {
+        let calendars_json = decrypt(&connection.calendars_encrypted)
+            .map_err(|e| format!("Failed to decrypt calendars: {}", e))?;
+        Ok(CalDavCredentials {
+            url: decrypt(&connection.url_encrypted)
+                .map_err(|e| format!("Failed to decrypt URL: {}", e))?,
+            username: decrypt(&connection.username_encrypted)
+                .map_err(|e| format!("Failed to decrypt username: {}", e))?,
+            password: decrypt(&connection.password_encrypted)
+                .map_err(|e| format!("Failed to decrypt password: {}", e))?,
+            calendars: serde_json::from_str(&calendars_json)
+                .map_err(|e| format!("Failed to parse calendars: {}", e))?,
+        })
+    }
+
+    /// Convert PgCalDavConnection to a response with decrypted URL and username
+    pub fn to_response(
+        &self,
+        connection: &PgCalDavConnection,
+    ) -> Result<CalDavConnectionResponse, String> {
+        let credentials = self.get_credentials(connection)?;
+        Ok(CalDavConnectionResponse {
+            id: connection.id,
+            name: connection.name.clone(),
+            url: credentials.url,
+            username: credentials.username,
+            calendars: credentials.calendars.into_iter().map(|c| c.name).collect(),
+            is_enabled: connection.is_enabled == 1,
+            created_at: connection.created_at,
+        })
+    }
+}
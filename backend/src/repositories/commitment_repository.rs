@@ -211,6 +211,22 @@ impl CommitmentRepository {
             .optional()
     }
 
+    /// A user's prompt by id, resolved or not. Replies that name a prompt by
+    /// its reply code land here; `claim_prompt` still guards against
+    /// applying one twice.
+    pub fn find_prompt_for_user(
+        &self,
+        user_id: i32,
+        prompt_id: i32,
+    ) -> Result<Option<CommitmentPrompt>, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        commitment_prompts::table
+            .filter(commitment_prompts::id.eq(prompt_id))
+            .filter(commitment_prompts::user_id.eq(user_id))
+            .first::<CommitmentPrompt>(&mut conn)
+            .optional()
+    }
+
     /// Live prompt for a (user, platform, sender_key) - used to dedup further
     /// detections from the same sender while the first prompt is unresolved.
     pub fn find_unresolved_for_sender(
@@ -0,0 +1,87 @@
+//! Storage for each user's email notification channel: the address
+//! notifications go to, the account they are sent from and how every
+//! notification category is delivered.
+
+use crate::models::email_channel_models::{EmailDeliveryModes, EmailNotificationChannel};
+use crate::pg_schema::email_notification_channels;
+use crate::PgDbPool;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+
+pub struct EmailChannelRepository {
+    pool: PgDbPool,
+}
+
+impl EmailChannelRepository {
+    pub fn new(pool: PgDbPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn find(&self, user_id: i32) -> Result<Option<EmailNotificationChannel>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        email_notification_channels::table
+            .find(user_id)
+            .select(EmailNotificationChannel::as_select())
+            .first(&mut conn)
+            .optional()
+    }
+
+    /// Point the channel at a (new) address and sender. Delivery modes
+    /// survive the change.
+    pub fn upsert(
+        &self,
+        user_id: i32,
+        address: &str,
+        imap_connection_id: Option<i32>,
+        now: i32,
+    ) -> Result<EmailNotificationChannel, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::insert_into(email_notification_channels::table)
+            .values((
+                email_notification_channels::user_id.eq(user_id),
+                email_notification_channels::address.eq(address),
+                email_notification_channels::imap_connection_id.eq(imap_connection_id),
+                email_notification_channels::created_at.eq(now),
+                email_notification_channels::updated_at.eq(now),
+            ))
+            .on_conflict(email_notification_channels::user_id)
+            .do_update()
+            .set((
+                email_notification_channels::address.eq(address),
+                email_notification_channels::imap_connection_id.eq(imap_connection_id),
+                email_notification_channels::updated_at.eq(now),
+            ))
+            .returning(EmailNotificationChannel::as_returning())
+            .get_result(&mut conn)
+    }
+
+    /// `None` when the user has no channel.
+    pub fn set_delivery(
+        &self,
+        user_id: i32,
+        modes: &EmailDeliveryModes,
+        now: i32,
+    ) -> Result<Option<EmailNotificationChannel>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::update(email_notification_channels::table.find(user_id))
+            .set((
+                email_notification_channels::critical_delivery.eq(modes.critical.as_str()),
+                email_notification_channels::digest_delivery.eq(modes.digest.as_str()),
+                email_notification_channels::reminder_delivery.eq(modes.reminders.as_str()),
+                email_notification_channels::rule_delivery.eq(modes.rules.as_str()),
+                email_notification_channels::other_delivery.eq(modes.other.as_str()),
+                email_notification_channels::updated_at.eq(now),
+            ))
+            .returning(EmailNotificationChannel::as_returning())
+            .get_result(&mut conn)
+            .optional()
+    }
+
+    /// `Ok(false)` when there was nothing to delete.
+    pub fn delete(&self, user_id: i32) -> Result<bool, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        let deleted =
+            diesel::delete(email_notification_channels::table.find(user_id)).execute(&mut conn)?;
+        Ok(deleted > 0)
+    }
+}
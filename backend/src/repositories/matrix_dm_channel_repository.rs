@@ -0,0 +1,95 @@
+//! Storage for each user's Matrix DM channel: the Matrix ID they read
+//! Lightfriend from, the room shared with it and how every notification
+//! category is delivered.
+
+use crate::models::matrix_channel_models::{MatrixDeliveryModes, MatrixDmChannel};
+use crate::pg_schema::matrix_dm_channels;
+use crate::PgDbPool;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+
+pub struct MatrixDmChannelRepository {
+    pool: PgDbPool,
+}
+
+impl MatrixDmChannelRepository {
+    pub fn new(pool: PgDbPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn find(&self, user_id: i32) -> Result<Option<MatrixDmChannel>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        matrix_dm_channels::table
+            .find(user_id)
+            .select(MatrixDmChannel::as_select())
+            .first(&mut conn)
+            .optional()
+    }
+
+    /// Point the channel at a (new) Matrix ID and room. Delivery modes
+    /// survive a reconnect.
+    pub fn connect(
+        &self,
+        user_id: i32,
+        target_mxid: &str,
+        room_id: &str,
+        now: i32,
+    ) -> Result<MatrixDmChannel, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::insert_into(matrix_dm_channels::table)
+            .values((
+                matrix_dm_channels::user_id.eq(user_id),
+                matrix_dm_channels::target_mxid.eq(target_mxid),
+                matrix_dm_channels::room_id.eq(room_id),
+                matrix_dm_channels::created_at.eq(now),
+                matrix_dm_channels::updated_at.eq(now),
+            ))
+            .on_conflict(matrix_dm_channels::user_id)
+            .do_update()
+            .set((
+                matrix_dm_channels::target_mxid.eq(target_mxid),
+                matrix_dm_channels::room_id.eq(room_id),
+                matrix_dm_channels::updated_at.eq(now),
+            ))
+            .returning(MatrixDmChannel::as_returning())
+            .get_result(&mut conn)
+    }
+
+    /// `None` when the user has no channel.
+    pub fn set_delivery(
+        &self,
+        user_id: i32,
+        modes: &MatrixDeliveryModes,
+        now: i32,
+    ) -> Result<Option<MatrixDmChannel>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::update(matrix_dm_channels::table.find(user_id))
+            .set((
+                matrix_dm_channels::critical_delivery.eq(modes.critical.as_str()),
+                matrix_dm_channels::digest_delivery.eq(modes.digest.as_str()),
+                matrix_dm_channels::reminder_delivery.eq(modes.reminders.as_str()),
+                matrix_dm_channels::rule_delivery.eq(modes.rules.as_str()),
+                matrix_dm_channels::other_delivery.eq(modes.other.as_str()),
+                matrix_dm_channels::updated_at.eq(now),
+            ))
+            .returning(MatrixDmChannel::as_returning())
+            .get_result(&mut conn)
+            .optional()
+    }
+
+    /// `Ok(false)` when there was nothing to delete.
+    pub fn delete(&self, user_id: i32) -> Result<bool, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        let deleted = diesel::delete(matrix_dm_channels::table.find(user_id)).execute(&mut conn)?;
+        Ok(deleted > 0)
+    }
+
+    /// Users whose Matrix client must stay running to serve their room.
+    pub fn user_ids_with_rooms(&self) -> Result<Vec<i32>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        matrix_dm_channels::table
+            .filter(matrix_dm_channels::room_id.is_not_null())
+            .select(matrix_dm_channels::user_id)
+            .load(&mut conn)
+    }
+}
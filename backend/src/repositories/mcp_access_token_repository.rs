@@ -0,0 +1,125 @@
+//! Storage for the bearer tokens MCP clients use to reach Lightfriend's own
+//! MCP endpoint. Rows are keyed by `token_hash` (SHA-256 hex of the raw
+//! token); the plaintext is only ever returned once, at mint time.
+
+use crate::models::mcp_models::{McpAccessToken, NewMcpAccessToken};
+use crate::pg_schema::{mcp_access_tokens, user_secrets};
+use crate::PgDbPool;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+
+/// Active tokens a user may hold at once.
+pub const MAX_ACTIVE_MCP_TOKENS: i64 = 10;
+
+pub struct McpAccessTokenRepository {
+    pool: PgDbPool,
+}
+
+impl McpAccessTokenRepository {
+    pub fn new(pool: PgDbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Insert a token row. `None` when the user already holds
+    /// `MAX_ACTIVE_MCP_TOKENS` active tokens.
+    pub fn create(&self, row: &NewMcpAccessToken) -> Result<Option<McpAccessToken>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        conn.transaction(|conn| {
+            // Tokens hang off user_secrets so deleting the user's data
+            // revokes them; the row is otherwise only created lazily.
+            diesel::insert_into(user_secrets::table)
+                .values(user_secrets::user_id.eq(row.user_id))
+                .on_conflict(user_secrets::user_id)
+                .do_nothing()
+                .execute(conn)?;
+            let active: i64 = mcp_access_tokens::table
+                .filter(mcp_access_tokens::user_id.eq(row.user_id))
+                .filter(mcp_access_tokens::revoked_at.is_null())
+                .count()
+                .get_result(conn)?;
+            if active >= MAX_ACTIVE_MCP_TOKENS {
+                return Ok(None);
+            }
+            diesel::insert_into(mcp_access_tokens::table)
+                .values(row)
+                .returning(McpAccessToken::as_returning())
+                .get_result(conn)
+                .map(Some)
+        })
+    }
+
+    /// Active tokens for the dashboard, newest first.
+    pub fn list_for_user(&self, user_id: i32) -> Result<Vec<McpAccessToken>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        mcp_access_tokens::table
+            .filter(mcp_access_tokens::user_id.eq(user_id))
+            .filter(mcp_access_tokens::revoked_at.is_null())
+            .order(mcp_access_tokens::created_at.desc())
+            .select(McpAccessToken::as_select())
+            .load(&mut conn)
+    }
+
+    /// Replace an active token's allow-list. `None` if the token isn't the
+    /// user's or is revoked.
+    pub fn set_allowed_tools(
+        &self,
+        user_id: i32,
+        token_id: i32,
+        allowed_tools: &str,
+    ) -> Result<Option<McpAccessToken>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::update(
+            mcp_access_tokens::table
+                .filter(mcp_access_tokens::id.eq(token_id))
+                .filter(mcp_access_tokens::user_id.eq(user_id))
+                .filter(mcp_access_tokens::revoked_at.is_null()),
+        )
+        .set(mcp_access_tokens::allowed_tools.eq(allowed_tools))
+        .returning(McpAccessToken::as_returning())
+        .get_result(&mut conn)
+        .optional()
+    }
+
+    /// Revoke a token. Idempotent: revoking an already revoked token of the
+    /// user still returns `Ok(true)` and keeps the original timestamp.
+    /// `Ok(false)` only when the token isn't the user's.
+    pub fn revoke(&self, user_id: i32, token_id: i32, now: i32) -> Result<bool, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        let owned: i64 = mcp_access_tokens::table
+            .filter(mcp_access_tokens::id.eq(token_id))
+            .filter(mcp_access_tokens::user_id.eq(user_id))
+            .count()
+            .get_result(&mut conn)?;
+        if owned == 0 {
+            return Ok(false);
+        }
+        diesel::update(
+            mcp_access_tokens::table
+                .filter(mcp_access_tokens::id.eq(token_id))
+                .filter(mcp_access_tokens::revoked_at.is_null()),
+        )
+        .set(mcp_access_tokens::revoked_at.eq(now))
+        .execute(&mut conn)?;
+        Ok(true)
+    }
+
+    /// The active token with this hash. Unknown and revoked tokens both
+    /// return `None` so callers answer them identically.
+    pub fn find_active(&self, token_hash: &str) -> Result<Option<McpAccessToken>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        mcp_access_tokens::table
+            .filter(mcp_access_tokens::token_hash.eq(token_hash))
+            .filter(mcp_access_tokens::revoked_at.is_null())
+            .select(McpAccessToken::as_select())
+            .first(&mut conn)
+            .optional()
+    }
+
+    pub fn touch(&self, token_id: i32, now: i32) -> Result<(), DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::update(mcp_access_tokens::table.filter(mcp_access_tokens::id.eq(token_id)))
+            .set(mcp_access_tokens::last_used_at.eq(now))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
 use crate::models::mcp_models::McpServerResponse;
 use crate::pg_models::{NewPgMcpServer, PgMcpServer};
 use crate::pg_schema::mcp_servers;
+use crate::services::mcp_client::McpClientService;
+use crate::services::mcp_oauth::{
+    McpOAuthClient, McpOAuthConfig, OAuthTokenSource, OAuthTokenStore, OAuthTokens,
+};
 use crate::utils::encryption::{decrypt, encrypt};
 use crate::PgDbPool;
 use diesel::prelude::*;
@@ -41,6 +45,7 @@ impl McpRepository {
             auth_token_encrypted,
             is_enabled: 1,
             created_at: now,
+            oauth_config_encrypted: None,
         };
 
         diesel::insert_into(mcp_servers::table)
@@ -56,6 +61,102 @@ impl McpRepository {
             .map_err(|e| format!("Failed to retrieve created server: {}", e))
     }
 
+    /// Create an OAuth-protected MCP server. It stays disabled until the
+    /// user completes authorization and tokens are saved.
+    pub fn create_oauth_server(
+        &self,
+        user_id: i32,
+        name: &str,
+        url: &str,
+        config: &McpOAuthConfig,
+    ) -> Result<PgMcpServer, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        let url_encrypted = encrypt(url).map_err(|e| format!("Failed to encrypt URL: {}", e))?;
+        let new_server = NewPgMcpServer {
+            user_id,
+            name: name.to_string(),
+            url_encrypted,
+            auth_token_encrypted: None,
+            is_enabled: 0,
+            created_at: chrono::Utc::now().timestamp() as i32,
+            oauth_config_encrypted: Some(encrypt_config(config)?),
+        };
+
+        diesel::insert_into(mcp_servers::table)
+            .values(&new_server)
+            .get_result::<PgMcpServer>(&mut conn)
+            .map_err(|e| format!("Failed to insert MCP server: {}", e))
+    }
+
+    /// Replace the OAuth client registration, e.g. after re-discovery.
+    pub fn set_oauth_config(
+        &self,
+        server_id: i32,
+        user_id: i32,
+        config: &McpOAuthConfig,
+    ) -> Result<(), String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        diesel::update(
+            mcp_servers::table
+                .filter(mcp_servers::id.eq(server_id))
+                .filter(mcp_servers::user_id.eq(user_id)),
+        )
+        .set(mcp_servers::oauth_config_encrypted.eq(Some(encrypt_config(config)?)))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to update OAuth config: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Store (possibly rotated) OAuth tokens for a server.
+    pub fn save_oauth_tokens(&self, server_id: i32, tokens: &OAuthTokens) -> Result<(), String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+
+        let access = encrypt(&tokens.access_token)
+            .map_err(|e| format!("Failed to encrypt access token: {}", e))?;
+        let refresh = tokens
+            .refresh_token
+            .as_deref()
+            .map(encrypt)
+            .transpose()
+            .map_err(|e| format!("Failed to encrypt refresh token: {}", e))?;
+
+        diesel::update(mcp_servers::table.filter(mcp_servers::id.eq(server_id)))
+            .set((
+                mcp_servers::oauth_access_token_encrypted.eq(Some(access)),
+                mcp_servers::oauth_refresh_token_encrypted.eq(refresh),
+                mcp_servers::oauth_expires_at.eq(tokens.expires_at),
+            ))
+            .execute(&mut conn)
+            .map_err(|e| format!("Failed to save OAuth tokens: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Save the tokens from a completed authorization and enable the server.
+    pub fn complete_oauth(
+        &self,
+        server_id: i32,
+        user_id: i32,
+        tokens: &OAuthTokens,
+    ) -> Result<(), String> {
+        self.save_oauth_tokens(server_id, tokens)?;
+
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+        diesel::update(
+            mcp_servers::table
+                .filter(mcp_servers::id.eq(server_id))
+                .filter(mcp_servers::user_id.eq(user_id)),
+        )
+        .set(mcp_servers::is_enabled.eq(1))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to enable MCP server: {}", e))?;
+
+        Ok(())
+    }
+
     /// Get all MCP servers for a user
     pub fn get_servers_for_user(&self, user_id: i32) -> Result<Vec<PgMcpServer>, String> {
         let mut conn = self.pool.get().map_err(|e| e.to_string())?;
@@ -166,6 +267,8 @@ impl McpRepository {
             has_auth_token: server.auth_token_encrypted.is_some(),
             is_enabled: server.is_enabled == 1,
             created_at: server.created_at,
+            uses_oauth: server.oauth_config_encrypted.is_some(),
+            oauth_connected: server.oauth_access_token_encrypted.is_some(),
         })
     }
 
@@ -183,6 +286,91 @@ impl McpRepository {
             .transpose()
             .map_err(|e| format!("Failed to decrypt auth token: {}", e))
     }
+
+    /// Get the decrypted OAuth configuration for a server (if it uses OAuth)
+    pub fn get_oauth_config(&self, server: &PgMcpServer) -> Result<Option<McpOAuthConfig>, String> {
+        let Some(encrypted) = &server.oauth_config_encrypted else {
+            return Ok(None);
+        };
+        let json =
+            decrypt(encrypted).map_err(|e| format!("Failed to decrypt OAuth config: {}", e))?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid OAuth config: {}", e))
+    }
+
+    /// Get the decrypted OAuth tokens for a server (if authorized)
+    pub fn get_oauth_tokens(&self, server: &PgMcpServer) -> Result<Option<OAuthTokens>, String> {
+        let Some(access) = &server.oauth_access_token_encrypted else {
+            return Ok(None);
+        };
+        let access_token =
+            decrypt(access).map_err(|e| format!("Failed to decrypt access token: {}", e))?;
+        let refresh_token = server
+            .oauth_refresh_token_encrypted
+            .as_ref()
+            .map(|token| decrypt(token))
+            .transpose()
+            .map_err(|e| format!("Failed to decrypt refresh token: {}", e))?;
+        Ok(Some(OAuthTokens {
+            access_token,
+            refresh_token,
+            expires_at: server.oauth_expires_at,
+        }))
+    }
+}
+
+impl McpRepository {
+    /// An MCP client for a server. OAuth servers get a token source that
+    /// refreshes their tokens as needed; others use the static auth token.
+    pub fn client_for_server(&self, server: &PgMcpServer) -> Result<McpClientService, String> {
+        let client = McpClientService::new();
+        let Some(config) = self.get_oauth_config(server)? else {
+            return Ok(client);
+        };
+        let store = McpServerTokenStore::new(self.pool.clone(), server.id);
+        let source = OAuthTokenSource::new(
+            McpOAuthClient::new(),
+            config,
+            store,
+            format!("mcp-server:{}", server.id),
+        );
+        Ok(client.with_token_source(Arc::new(source)))
+    }
+}
+
+fn encrypt_config(config: &McpOAuthConfig) -> Result<String, String> {
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    encrypt(&json).map_err(|e| format!("Failed to encrypt OAuth config: {}", e))
+}
+
+/// OAuth tokens for one MCP server, read and rotated in the database.
+pub struct McpServerTokenStore {
+    pool: PgDbPool,
+    server_id: i32,
+}
+
+impl McpServerTokenStore {
+    pub fn new(pool: PgDbPool, server_id: i32) -> Self {
+        Self { pool, server_id }
+    }
+}
+
+impl OAuthTokenStore for McpServerTokenStore {
+    fn load(&self) -> Result<OAuthTokens, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+        let server = mcp_servers::table
+            .filter(mcp_servers::id.eq(self.server_id))
+            .first::<PgMcpServer>(&mut conn)
+            .map_err(|e| format!("Failed to load MCP server: {}", e))?;
+        McpRepository::new(self.pool.clone())
+            .get_oauth_tokens(&server)?
+            .ok_or_else(|| "MCP server is not authorized yet. Reconnect it.".to_string())
+    }
+
+    fn save(&self, tokens: &OAuthTokens) -> Result<(), String> {
+        McpRepository::new(self.pool.clone()).save_oauth_tokens(self.server_id, tokens)
+    }
 }
 
 /// Arc wrapper for thread-safe sharing
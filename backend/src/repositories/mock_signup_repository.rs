@@ -57,6 +57,7 @@ impl MockUser {
             accountability_enabled: false,
             included_usage_window_start_timestamp: None,
             included_usage_window_end_timestamp: None,
+            sms_gsm7_transliteration: false,
         }
     }
 }
@@ -4,12 +4,13 @@ use diesel::sql_types::Text;
 
 use crate::models::ontology_models::{
     NewOntChangelog, NewOntChannel, NewOntEvent, NewOntLink, NewOntMessage, NewOntPerson,
-    NewOntPersonEdit, NewOntRule, OntChangelog, OntChannel, OntEvent, OntLink, OntMessage,
-    OntPerson, OntPersonEdit, OntRule, PersonWithChannels,
+    NewOntPersonEdit, NewOntRule, NewOntRuleContinuation, OntChangelog, OntChannel, OntEvent,
+    OntLink, OntMessage, OntPerson, OntPersonEdit, OntRule, OntRuleContinuation,
+    PersonWithChannels,
 };
 use crate::pg_schema::{
     ont_changelog, ont_channels, ont_events, ont_links, ont_messages, ont_person_edits,
-    ont_persons, ont_rules,
+    ont_persons, ont_rule_continuations, ont_rules,
 };
 use crate::PgDbPool;
 
@@ -1801,6 +1802,56 @@ impl OntologyRepository {
         Ok(())
     }
 
+    /// Whether a bridged event is already stored, so expensive enrichment
+    /// (voice-note transcription) can be skipped for redelivered events.
+    pub fn message_exists_for_event(
+        &self,
+        user_id: i32,
+        matrix_event_id: &str,
+    ) -> Result<bool, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        diesel::select(diesel::dsl::exists(
+            ont_messages::table
+                .filter(ont_messages::user_id.eq(user_id))
+                .filter(ont_messages::matrix_event_id.eq(matrix_event_id)),
+        ))
+        .get_result(&mut conn)
+    }
+
+    /// Attach the raw speech-to-text output to a voice-note message.
+    pub fn set_message_transcript(
+        &self,
+        message_id: i64,
+        transcript: &str,
+    ) -> Result<(), DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        diesel::update(ont_messages::table.filter(ont_messages::id.eq(message_id)))
+            .set(ont_messages::transcript.eq(Some(transcript)))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Attach a parsed location share to its message.
+    pub fn set_message_location(
+        &self,
+        message_id: i64,
+        lat: f64,
+        lon: f64,
+        label: Option<&str>,
+        place: Option<&str>,
+    ) -> Result<(), DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        diesel::update(ont_messages::table.filter(ont_messages::id.eq(message_id)))
+            .set((
+                ont_messages::location_lat.eq(Some(lat)),
+                ont_messages::location_lon.eq(Some(lon)),
+                ont_messages::location_label.eq(label),
+                ont_messages::location_place.eq(place),
+            ))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
     /// Update a message's commitment-detection result (prompt + LLM output JSON).
     /// Stored for activity-feed transparency / debugging.
     pub fn update_message_commitment(
@@ -2812,6 +2863,91 @@ impl OntologyRepository {
             .load(&mut conn)
     }
 
+    // -----------------------------------------------------------------------
+    // Rule continuations (suspended Delay flows)
+    // -----------------------------------------------------------------------
+
+    pub fn create_rule_continuation(
+        &self,
+        new_continuation: &NewOntRuleContinuation,
+    ) -> Result<OntRuleContinuation, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        diesel::insert_into(ont_rule_continuations::table)
+            .values(new_continuation)
+            .get_result(&mut conn)
+    }
+
+    /// Atomically claim every continuation whose resume_at has passed by
+    /// flipping it to "running". Claims are at-most-once: a "running" row is
+    /// never claimed again, because a resume that died midway may already
+    /// have sent its SMS or placed its call. Abandoned rows are removed by
+    /// `prune_rule_continuations`.
+    pub fn claim_due_rule_continuations(
+        &self,
+        now: i32,
+    ) -> Result<Vec<OntRuleContinuation>, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        diesel::update(
+            ont_rule_continuations::table
+                .filter(ont_rule_continuations::resume_at.le(now))
+                .filter(ont_rule_continuations::status.eq("pending")),
+        )
+        .set((
+            ont_rule_continuations::status.eq("running"),
+            ont_rule_continuations::updated_at.eq(now),
+        ))
+        .get_results(&mut conn)
+    }
+
+    /// Move a claimed continuation from "running" to its final status.
+    /// Returns false if the row was no longer running.
+    pub fn finish_rule_continuation(
+        &self,
+        continuation_id: i32,
+        status: &str,
+    ) -> Result<bool, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        let updated = diesel::update(
+            ont_rule_continuations::table
+                .filter(ont_rule_continuations::id.eq(continuation_id))
+                .filter(ont_rule_continuations::status.eq("running")),
+        )
+        .set((
+            ont_rule_continuations::status.eq(status),
+            ont_rule_continuations::updated_at.eq(Self::now()),
+        ))
+        .execute(&mut conn)?;
+        Ok(updated > 0)
+    }
+
+    /// Delete continuations that finished (or were abandoned while running)
+    /// before `cutoff`. Returns the number of rows deleted.
+    pub fn prune_rule_continuations(&self, cutoff: i32) -> Result<usize, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        diesel::delete(
+            ont_rule_continuations::table
+                .filter(ont_rule_continuations::status.ne("pending"))
+                .filter(ont_rule_continuations::updated_at.lt(cutoff)),
+        )
+        .execute(&mut conn)
+    }
+
+    /// Cancel every pending continuation of a rule (e.g. when it is paused
+    /// or its flow is edited). Returns the number of rows cancelled.
+    pub fn cancel_rule_continuations(&self, rule_id: i32) -> Result<usize, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        diesel::update(
+            ont_rule_continuations::table
+                .filter(ont_rule_continuations::rule_id.eq(rule_id))
+                .filter(ont_rule_continuations::status.eq("pending")),
+        )
+        .set((
+            ont_rule_continuations::status.eq("cancelled"),
+            ont_rule_continuations::updated_at.eq(Self::now()),
+        ))
+        .execute(&mut conn)
+    }
+
     /// Get all active ontology_change rules for a given user.
     /// Caller is responsible for matching trigger_config filters against the entity data.
     pub fn get_ontology_change_rules(&self, user_id: i32) -> Result<Vec<OntRule>, DieselError> {
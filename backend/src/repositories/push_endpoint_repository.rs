@@ -0,0 +1,170 @@
+//! Storage for each user's UnifiedPush / ntfy endpoint and which
+//! notifications are routed to it. The endpoint URL works as a bearer
+//! capability for the topic, so it is encrypted like the auth secret.
+
+use crate::{
+    models::push_channel_models::PushEndpointRow,
+    pg_schema::push_endpoints,
+    utils::encryption::{decrypt, encrypt, EncryptionError},
+    PgDbPool,
+};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Web Push subscription keys, base64url as the client reported them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PushKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PushEndpointRecord {
+    pub user_id: i32,
+    pub endpoint: String,
+    pub endpoint_hash: String,
+    pub keys: Option<PushKeys>,
+    pub push_digests: bool,
+    pub push_rules: bool,
+    pub created_at: i32,
+    pub updated_at: i32,
+}
+
+#[derive(Debug, Error)]
+pub enum PushEndpointRepositoryError {
+    #[error(transparent)]
+    Database(#[from] DieselError),
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
+}
+
+pub struct PushEndpointRepository {
+    pool: PgDbPool,
+}
+
+impl PushEndpointRepository {
+    pub fn new(pool: PgDbPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn find(
+        &self,
+        user_id: i32,
+    ) -> Result<Option<PushEndpointRecord>, PushEndpointRepositoryError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        let row = push_endpoints::table
+            .find(user_id)
+            .select(PushEndpointRow::as_select())
+            .first(&mut conn)
+            .optional()?;
+        row.map(decrypt_row).transpose().map_err(Into::into)
+    }
+
+    /// Register or replace the endpoint. Routing choices survive a
+    /// re-registration.
+    pub fn upsert(
+        &self,
+        user_id: i32,
+        endpoint: &str,
+        keys: Option<&PushKeys>,
+        now: i32,
+    ) -> Result<PushEndpointRecord, PushEndpointRepositoryError> {
+        let encrypted_endpoint = encrypt(endpoint)?;
+        let hash = endpoint_hash(endpoint);
+        let p256dh = keys.map(|keys| keys.p256dh.clone());
+        let encrypted_auth_secret = keys.map(|keys| encrypt(&keys.auth)).transpose()?;
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        let row = diesel::insert_into(push_endpoints::table)
+            .values((
+                push_endpoints::user_id.eq(user_id),
+                push_endpoints::encrypted_endpoint.eq(&encrypted_endpoint),
+                push_endpoints::endpoint_hash.eq(&hash),
+                push_endpoints::p256dh.eq(&p256dh),
+                push_endpoints::encrypted_auth_secret.eq(&encrypted_auth_secret),
+                push_endpoints::created_at.eq(now),
+                push_endpoints::updated_at.eq(now),
+            ))
+            .on_conflict(push_endpoints::user_id)
+            .do_update()
+            .set((
+                push_endpoints::encrypted_endpoint.eq(&encrypted_endpoint),
+                push_endpoints::endpoint_hash.eq(&hash),
+                push_endpoints::p256dh.eq(&p256dh),
+                push_endpoints::encrypted_auth_secret.eq(&encrypted_auth_secret),
+                push_endpoints::updated_at.eq(now),
+            ))
+            .returning(PushEndpointRow::as_returning())
+            .get_result(&mut conn)?;
+        decrypt_row(row).map_err(Into::into)
+    }
+
+    /// `None` when the user has no endpoint.
+    pub fn set_routing(
+        &self,
+        user_id: i32,
+        push_digests: bool,
+        push_rules: bool,
+        now: i32,
+    ) -> Result<Option<PushEndpointRecord>, PushEndpointRepositoryError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        let row = diesel::update(push_endpoints::table.find(user_id))
+            .set((
+                push_endpoints::push_digests.eq(push_digests),
+                push_endpoints::push_rules.eq(push_rules),
+                push_endpoints::updated_at.eq(now),
+            ))
+            .returning(PushEndpointRow::as_returning())
+            .get_result(&mut conn)
+            .optional()?;
+        row.map(decrypt_row).transpose().map_err(Into::into)
+    }
+
+    /// `Ok(false)` when there was nothing to delete.
+    pub fn delete(&self, user_id: i32) -> Result<bool, PushEndpointRepositoryError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        Ok(diesel::delete(push_endpoints::table.find(user_id)).execute(&mut conn)? > 0)
+    }
+
+    /// Drop an endpoint the push server reported gone, unless the user has
+    /// registered a different one since it was read.
+    pub fn delete_if_endpoint_hash(
+        &self,
+        user_id: i32,
+        expected_endpoint_hash: &str,
+    ) -> Result<bool, PushEndpointRepositoryError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        Ok(diesel::delete(
+            push_endpoints::table
+                .filter(push_endpoints::user_id.eq(user_id))
+                .filter(push_endpoints::endpoint_hash.eq(expected_endpoint_hash)),
+        )
+        .execute(&mut conn)?
+            > 0)
+    }
+}
+
+pub fn endpoint_hash(endpoint: &str) -> String {
+    hex::encode(Sha256::digest(endpoint.as_bytes()))
+}
+
+fn decrypt_row(row: PushEndpointRow) -> Result<PushEndpointRecord, EncryptionError> {
+    let keys = match (row.p256dh, row.encrypted_auth_secret) {
+        (Some(p256dh), Some(auth)) => Some(PushKeys {
+            p256dh,
+            auth: decrypt(&auth)?,
+        }),
+        _ => None,
+    };
+    Ok(PushEndpointRecord {
+        user_id: row.user_id,
+        endpoint: decrypt(&row.encrypted_endpoint)?,
+        endpoint_hash: row.endpoint_hash,
+        keys,
+        push_digests: row.push_digests,
+        push_rules: row.push_rules,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    })
+}
@@ -0,0 +1,87 @@
+//! Storage for the per-user ring buffer behind reply codes ("#A7"). Every
+//! tagged notification writes one row; only the newest `RING_SIZE` rows per
+//! user survive, and a code points at the newest row that carries it.
+
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+
+use crate::models::user_models::{NewReplyContext, ReplyContext};
+use crate::pg_schema::reply_contexts;
+use crate::PgDbPool;
+
+/// How many recent notifications a user can refer back to.
+pub const RING_SIZE: i64 = 20;
+
+#[derive(Clone)]
+pub struct ReplyContextsRepository {
+    pool: PgDbPool,
+}
+
+impl ReplyContextsRepository {
+    pub fn new(pool: PgDbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Codes of the user's buffered notifications sent after `since`, i.e.
+    /// the ones a new notification must not reuse.
+    pub fn codes_in_use(&self, user_id: i32, since: i32) -> Result<Vec<String>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        reply_contexts::table
+            .filter(reply_contexts::user_id.eq(user_id))
+            .filter(reply_contexts::created_at.gt(since))
+            .order(reply_contexts::created_at.desc())
+            .limit(RING_SIZE)
+            .select(reply_contexts::code)
+            .load(&mut conn)
+    }
+
+    /// Insert a row for a freshly sent notification. Older rows with the
+    /// same code are replaced and the buffer is trimmed to `RING_SIZE`.
+    pub fn record(&self, row: NewReplyContext) -> Result<ReplyContext, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        conn.transaction(|conn| {
+            diesel::delete(
+                reply_contexts::table
+                    .filter(reply_contexts::user_id.eq(row.user_id))
+                    .filter(reply_contexts::code.eq(&row.code)),
+            )
+            .execute(conn)?;
+            let inserted = diesel::insert_into(reply_contexts::table)
+                .values(&row)
+                .returning(ReplyContext::as_returning())
+                .get_result(conn)?;
+            let keep: Vec<i32> = reply_contexts::table
+                .filter(reply_contexts::user_id.eq(row.user_id))
+                .order((reply_contexts::created_at.desc(), reply_contexts::id.desc()))
+                .limit(RING_SIZE)
+                .select(reply_contexts::id)
+                .load(conn)?;
+            diesel::delete(
+                reply_contexts::table
+                    .filter(reply_contexts::user_id.eq(row.user_id))
+                    .filter(reply_contexts::id.ne_all(keep)),
+            )
+            .execute(conn)?;
+            Ok(inserted)
+        })
+    }
+
+    /// The buffered notification a reply code refers to, if it was sent
+    /// after `since`. `code` must already be upper-cased.
+    pub fn find_by_code(
+        &self,
+        user_id: i32,
+        code: &str,
+        since: i32,
+    ) -> Result<Option<ReplyContext>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        reply_contexts::table
+            .filter(reply_contexts::user_id.eq(user_id))
+            .filter(reply_contexts::code.eq(code))
+            .filter(reply_contexts::created_at.gt(since))
+            .order(reply_contexts::created_at.desc())
+            .select(ReplyContext::as_select())
+            .first(&mut conn)
+            .optional()
+    }
+}
@@ -98,6 +98,8 @@ pub trait UserCoreOps: Send + Sync {
         value: Option<&str>,
     ) -> Result<(), DieselError>;
     fn update_accountability_enabled(&self, user_id: i32, value: bool) -> Result<(), DieselError>;
+    fn update_sms_gsm7_transliteration(&self, user_id: i32, value: bool)
+        -> Result<(), DieselError>;
     fn set_refresh_token_hash(&self, user_id: i32, token_hash: &str) -> Result<(), DieselError>;
     fn mark_refresh_token_compromised(&self, user_id: i32) -> Result<(), DieselError>;
 
@@ -792,6 +794,18 @@ impl UserCoreOps for UserCore {
         Ok(())
     }
 
+    fn update_sms_gsm7_transliteration(
+        &self,
+        user_id: i32,
+        value: bool,
+    ) -> Result<(), DieselError> {
+        let mut pg_conn = self.pg_pool.get().expect("Failed to get PG connection");
+        diesel::update(users::table.find(user_id))
+            .set(users::sms_gsm7_transliteration.eq(value))
+            .execute(&mut pg_conn)?;
+        Ok(())
+    }
+
     fn update_info(&self, user_id: i32, info: &str) -> Result<(), DieselError> {
         let mut pg_conn = self.pg_pool.get().expect("Failed to get PG connection");
         self.ensure_user_info_exists(user_id)?;
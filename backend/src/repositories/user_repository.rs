@@ -80,6 +80,8 @@ pub struct LogUsageParams {
     pub status: Option<String>,
     pub recharge_threshold_timestamp: Option<i32>,
     pub zero_credits_timestamp: Option<i32>,
+    /// Billed SMS parts of the body that was sent, for outbound SMS.
+    pub segments: Option<i32>,
 }
 
 /// Time window for conversation history reads/cleanup. Anything older than
@@ -655,6 +657,7 @@ impl UserRepository {
             status: params.status,
             recharge_threshold_timestamp: params.recharge_threshold_timestamp,
             zero_credits_timestamp: params.zero_credits_timestamp,
+            segments: params.segments,
         };
 
         diesel::insert_into(usage_logs::table)
@@ -764,6 +767,41 @@ impl UserRepository {
         Ok(count > 0)
     }
 
+    /// The delivered important alert whose provider id is `sid`, for
+    /// feedback that names the alert by its reply code.
+    pub fn system_alert_by_sid(
+        &self,
+        user_id: i32,
+        sid: &str,
+    ) -> Result<Option<crate::pg_models::PgUsageLog>, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        usage_logs::table
+            .filter(usage_logs::user_id.eq(user_id))
+            .filter(usage_logs::sid.eq(sid))
+            .filter(usage_logs::activity_type.eq_any(SYSTEM_ALERT_ACTIVITY_TYPES))
+            .order(usage_logs::created_at.desc())
+            .first::<crate::pg_models::PgUsageLog>(&mut conn)
+            .optional()
+    }
+
+    /// Whether feedback was already recorded for this particular alert.
+    /// Feedback rows carry `alert_id={id} ...` as their reason.
+    pub fn has_system_alert_feedback_for(
+        &self,
+        user_id: i32,
+        alert_id: i32,
+    ) -> Result<bool, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        let count: i64 = usage_logs::table
+            .filter(usage_logs::user_id.eq(user_id))
+            .filter(usage_logs::activity_type.eq_any(SYSTEM_ALERT_FEEDBACK_ACTIVITY_TYPES))
+            .filter(usage_logs::reason.like(format!("alert_id={} %", alert_id)))
+            .count()
+            .get_result(&mut conn)?;
+
+        Ok(count > 0)
+    }
+
     /// Get recent usage logs for a user (for activity feed).
     pub fn get_recent_usage_logs(
         &self,
@@ -872,6 +910,17 @@ impl UserRepository {
         Ok(())
     }
 
+    /// Segments recorded when the message with this `sid` was sent.
+    pub fn get_usage_log_segments(&self, sid: &str) -> Result<Option<i32>, DieselError> {
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        let segments = usage_logs::table
+            .filter(usage_logs::sid.eq(sid))
+            .select(usage_logs::segments)
+            .first::<Option<i32>>(&mut conn)
+            .optional()?;
+        Ok(segments.flatten())
+    }
+
     pub fn update_usage_log_fields(
         &self,
         user_id: i32,
@@ -1528,6 +1577,22 @@ impl UserRepository {
         Ok(count > 0)
     }
 
+    /// Whether anything still needs the user's Matrix account: a connected
+    /// bridge or a Matrix DM channel. Gates client teardown and store
+    /// clearing, since clearing the store loses the DM room's keys too.
+    pub fn matrix_account_in_use(&self, user_id: i32) -> Result<bool, DieselError> {
+        use crate::pg_schema::matrix_dm_channels;
+        if self.has_active_bridges(user_id)? {
+            return Ok(true);
+        }
+        let mut conn = self.pool.get().expect("Failed to get DB connection");
+        let channels = matrix_dm_channels::table
+            .filter(matrix_dm_channels::user_id.eq(user_id))
+            .count()
+            .get_result::<i64>(&mut conn)?;
+        Ok(channels > 0)
+    }
+
     pub fn get_users_with_active_bridges(
         &self,
     ) -> Result<std::collections::HashMap<i32, Vec<PgBridge>>, DieselError> {
@@ -0,0 +1,60 @@
+//! Persistence for per-country voice provider order. Drives outbound call
+//! fallback in `ChannelRouter::place_call` the same way
+//! `provider_routes_repository` drives SMS: countries with a row use the
+//! stored `provider_order` (JSON array of voice channel ids), countries
+//! without a row inherit the router default.
+
+use crate::{models::user_models::VoiceRoute, pg_schema::voice_routes, PgDbPool};
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct VoiceRoutesRepository {
+    pool: PgDbPool,
+}
+
+impl VoiceRoutesRepository {
+    pub fn new(pool: PgDbPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn list_all(&self) -> Result<Vec<VoiceRoute>, DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        voice_routes::table
+            .order(voice_routes::country_code.asc())
+            .select(VoiceRoute::as_select())
+            .load::<VoiceRoute>(&mut conn)
+    }
+
+    /// Insert or update the order for a country. `provider_order_json` must be
+    /// a JSON array of voice channel ids; the caller validates them.
+    pub fn upsert(&self, country_code: &str, provider_order_json: &str) -> Result<(), DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i32;
+        let row = VoiceRoute {
+            country_code: country_code.to_string(),
+            provider_order: provider_order_json.to_string(),
+            updated_at: now,
+        };
+        diesel::insert_into(voice_routes::table)
+            .values(&row)
+            .on_conflict(voice_routes::country_code)
+            .do_update()
+            .set((
+                voice_routes::provider_order.eq(&row.provider_order),
+                voice_routes::updated_at.eq(row.updated_at),
+            ))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    pub fn delete(&self, country_code: &str) -> Result<(), DieselError> {
+        let mut conn = self.pool.get().map_err(|_| DieselError::NotFound)?;
+        diesel::delete(voice_routes::table.filter(voice_routes::country_code.eq(country_code)))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+}
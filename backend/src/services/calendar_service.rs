@@ -0,0 +1,185 @@
+//! Calendar access across all of a user's CalDAV connections.
+//!
+//! Shared by the `manage_calendar` tool, the rule `calendar` fetch source and
+//! the digest, so they all see the same events and resolve times the same way.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+
+use crate::api::caldav::{
+    parse_ics_events, update_event_ics, CalDavClient, CalendarEvent, CalendarInfo, EventPatch,
+};
+use crate::repositories::caldav_repository::CalDavRepository;
+use crate::AppState;
+
+/// An event together with the calendar it came from.
+#[derive(Debug, Clone)]
+pub struct UserCalendarEvent {
+    pub calendar: String,
+    pub event: CalendarEvent,
+    /// Start resolved to UTC (all-day and floating times in the user's zone).
+    pub starts_at: DateTime<Utc>,
+}
+
+struct OpenCalendar {
+    client: Arc<CalDavClient>,
+    calendar: CalendarInfo,
+    connection_name: String,
+}
+
+/// The user's configured timezone, UTC when unset or unknown.
+pub fn user_timezone(state: &AppState, user_id: i32) -> chrono_tz::Tz {
+    state
+        .user_core
+        .get_user_info(user_id)
+        .ok()
+        .and_then(|info| info.timezone)
+        .and_then(|name| name.parse::<chrono_tz::Tz>().ok())
+        .unwrap_or(chrono_tz::UTC)
+}
+
+/// Every calendar of every enabled connection, in connection order.
+fn open_calendars(state: &Arc<AppState>, user_id: i32) -> Result<Vec<OpenCalendar>, String> {
+    let repository = CalDavRepository::new(state.pg_pool.clone());
+    let mut calendars = Vec::new();
+    for connection in repository.get_enabled_connections_for_user(user_id)? {
+        let credentials = repository.get_credentials(&connection)?;
+        let client = Arc::new(CalDavClient::new(
+            &credentials.username,
+            &credentials.password,
+        )?);
+        for calendar in credentials.calendars {
+            calendars.push(OpenCalendar {
+                client: client.clone(),
+                calendar,
+                connection_name: connection.name.clone(),
+            });
+        }
+    }
+    Ok(calendars)
+}
+
+/// Events overlapping [start, end) from all enabled calendars, sorted by
+/// start. Calendars that fail to answer are skipped; an error is returned
+/// only when every calendar failed.
+pub async fn events_between(
+    state: &Arc<AppState>,
+    user_id: i32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<UserCalendarEvent>, String> {
+    let calendars = open_calendars(state, user_id)?;
+    if calendars.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tz = user_timezone(state, user_id);
+
+    let results = join_all(calendars.iter().map(|open| async move {
+        (
+            open,
+            open.client
+                .list_events(&open.calendar.url, start, end)
+                .await,
+        )
+    }))
+    .await;
+
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+    for (open, result) in results {
+        match result {
+            Ok(found) => events.extend(found.into_iter().map(|event| UserCalendarEvent {
+                calendar: open.calendar.name.clone(),
+                starts_at: event.start.to_utc(&tz),
+                event,
+            })),
+            Err(e) => {
+                tracing::warn!(
+                    "Calendar '{}' ({}) for user {} failed: {}",
+                    open.calendar.name,
+                    open.connection_name,
+                    user_id,
+                    e
+                );
+                errors.push(e);
+            }
+        }
+    }
+    if errors.len() == calendars.len() {
+        return Err(errors.swap_remove(0));
+    }
+    events.sort_by_key(|e| e.starts_at);
+    Ok(events)
+}
+
+/// Create an event in the named calendar (or connection), defaulting to the
+/// first calendar of the oldest enabled connection. Returns the calendar name.
+pub async fn create_event(
+    state: &Arc<AppState>,
+    user_id: i32,
+    calendar_name: Option<&str>,
+    event: &CalendarEvent,
+) -> Result<String, String> {
+    let calendars = open_calendars(state, user_id)?;
+    let target = match calendar_name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => calendars
+            .iter()
+            .find(|c| c.calendar.name.eq_ignore_ascii_case(name))
+            .or_else(|| {
+                calendars
+                    .iter()
+                    .find(|c| c.connection_name.eq_ignore_ascii_case(name))
+            })
+            .ok_or_else(|| {
+                let known: Vec<&str> = calendars.iter().map(|c| c.calendar.name.as_str()).collect();
+                format!(
+                    "No calendar named '{}'. Available: {}",
+                    name,
+                    known.join(", ")
+                )
+            })?,
+        None => calendars
+            .first()
+            .ok_or_else(|| "No calendar connected".to_string())?,
+    };
+    target
+        .client
+        .create_event(&target.calendar.url, event)
+        .await?;
+    Ok(target.calendar.name.clone())
+}
+
+/// Apply a patch to the event with this UID, wherever it lives. Returns the
+/// updated event and its calendar name.
+pub async fn update_event(
+    state: &Arc<AppState>,
+    user_id: i32,
+    uid: &str,
+    patch: &EventPatch,
+) -> Result<(CalendarEvent, String), String> {
+    for open in open_calendars(state, user_id)? {
+        let object = match open.client.find_event(&open.calendar.url, uid).await {
+            Ok(Some(object)) => object,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(
+                    "Calendar '{}' lookup for user {} failed: {}",
+                    open.calendar.name,
+                    user_id,
+                    e
+                );
+                continue;
+            }
+        };
+        let ics = update_event_ics(&object.data, patch, Utc::now())?;
+        open.client.replace_event(&object, ics.clone()).await?;
+        let updated = parse_ics_events(&ics)
+            .into_iter()
+            .find(|e| e.uid == uid)
+            .ok_or_else(|| "Updated event could not be read back".to_string())?;
+        return Ok((updated, open.calendar.name));
+    }
+    Err(format!("No calendar event with uid '{}'", uid))
+}
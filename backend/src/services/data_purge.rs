@@ -289,6 +289,8 @@ pub fn cascade_delete_user_data(pg_pool: &PgDbPool, user_id: i32) -> Result<()>
                 .filter(bridge_disconnection_events::user_id.eq(user_id)),
         )
         .execute(conn)?;
+        diesel::delete(caldav_connections::table.filter(caldav_connections::user_id.eq(user_id)))
+            .execute(conn)?;
         diesel::delete(imap_connection::table.filter(imap_connection::user_id.eq(user_id)))
             .execute(conn)?;
         diesel::delete(items::table.filter(items::user_id.eq(user_id))).execute(conn)?;
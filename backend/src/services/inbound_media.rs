@@ -0,0 +1,533 @@
+//! Inbound MMS images: download, normalize, OCR and extract, then delete.
+//!
+//! Dumbphone users photograph paper letters, receipts and posters and ask
+//! about them. For every image attached to an inbound message we:
+//!   1. download the bytes (Twilio media needs the account's credentials;
+//!      Telnyx hands out pre-signed URLs; web chat sends a data URL),
+//!   2. normalize HEIC/JPEG/PNG into a size-bounded JPEG. HEIC goes through
+//!      the `heif-convert` CLI first; re-encoding also drops EXIF, so GPS
+//!      tags never reach the model,
+//!   3. run local OCR with the `tesseract` CLI,
+//!   4. pull dates, times, amounts and addresses out of the OCR text.
+//!
+//! The model gets the OCR text and extractions as text plus each image as a
+//! `data:` URL, so nothing downstream needs the provider's copy. The caller
+//! deletes that copy straight after `process_inbound_images` returns, which
+//! keeps the delete-after-processing guarantee: the photo lives in memory
+//! for the length of one request (a HEIC original also spends a moment in
+//! a temp file for conversion) and is never stored.
+//!
+//! Both CLIs are optional. Without `tesseract` the model still sees the
+//! image; without `heif-convert` HEIC photos are skipped with a note.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use regex::Regex;
+use serde::Serialize;
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+
+use crate::api::twilio_sms::WebhookMedia;
+use crate::models::user_models::User;
+use crate::AppState;
+
+/// Twilio's own per-message cap.
+pub const MAX_IMAGES: usize = 10;
+
+/// Longest edge of the normalized JPEG. Enough for OCR of an A4 letter
+/// photo, small enough to keep the vision request cheap.
+const MAX_EDGE: u32 = 2000;
+
+const JPEG_QUALITY: u8 = 85;
+
+/// OCR text handed to the model per image, in characters.
+const MAX_OCR_CHARS: usize = 4000;
+
+/// Each extracted list is capped so a dense page can't flood the prompt.
+const MAX_FIELDS: usize = 10;
+
+/// Largest attachment downloaded, well above what carriers deliver over
+/// MMS; anything bigger is refused before it is read into memory.
+pub const MAX_DOWNLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// Decode limits, so a small compressed file can't expand into gigabytes.
+const MAX_DECODE_EDGE: u32 = 12_000;
+const MAX_DECODE_BYTES: u64 = 256 * 1024 * 1024;
+
+const TOOL_TIMEOUT: Duration = Duration::from_secs(30);
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum InboundMediaError {
+    #[error("Failed to download media: {0}")]
+    Download(String),
+
+    #[error("Unsupported media type: {0}")]
+    Unsupported(String),
+
+    #[error("Failed to decode image: {0}")]
+    Decode(String),
+
+    #[error("Failed to convert HEIC image: {0}")]
+    HeicConversion(String),
+}
+
+/// Dates, times, amounts and addresses found in OCR text, verbatim and in
+/// reading order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExtractedFields {
+    pub dates: Vec<String>,
+    pub times: Vec<String>,
+    pub amounts: Vec<String>,
+    pub addresses: Vec<String>,
+}
+
+impl ExtractedFields {
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+            && self.times.is_empty()
+            && self.amounts.is_empty()
+            && self.addresses.is_empty()
+    }
+}
+
+/// One attachment after the pipeline ran.
+#[derive(Debug, Clone)]
+pub enum InboundImage {
+    Processed {
+        jpeg: Vec<u8>,
+        ocr_text: Option<String>,
+        fields: ExtractedFields,
+    },
+    /// Kept so the model can tell the user which photo didn't come through.
+    Failed { reason: String },
+}
+
+impl InboundImage {
+    /// The normalized image as a `data:` URL for the vision model.
+    pub fn data_url(&self) -> Option<String> {
+        match self {
+            InboundImage::Processed { jpeg, .. } => {
+                Some(format!("data:image/jpeg;base64,{}", BASE64.encode(jpeg)))
+            }
+            InboundImage::Failed { .. } => None,
+        }
+    }
+}
+
+/// Split a `data:<mime>;base64,<payload>` URL into its MIME type and bytes.
+/// Tools that used to fetch the provider URL (QR scanning, forwarding a
+/// photo to a chat) go through this now that the provider copy is gone.
+pub fn decode_data_url(url: &str) -> Option<(String, Vec<u8>)> {
+    let (meta, payload) = url.strip_prefix("data:")?.split_once(',')?;
+    let mime = meta.strip_suffix(";base64")?;
+    let bytes = BASE64.decode(payload).ok()?;
+    Some((mime.to_string(), bytes))
+}
+
+/// Run every image attachment through the pipeline. Non-image attachments
+/// (vCards, audio) are left alone; they are still deleted by the caller.
+pub async fn process_inbound_images(
+    state: &Arc<AppState>,
+    user: &User,
+    media: &[WebhookMedia],
+) -> Vec<InboundImage> {
+    let mut images = Vec::new();
+    for item in media
+        .iter()
+        .filter(|m| m.content_type.starts_with("image/"))
+        .take(MAX_IMAGES)
+    {
+        let result = match download(state, user, &item.url).await {
+            Ok(bytes) => process_image(&item.content_type, &bytes).await,
+            Err(e) => Err(e),
+        };
+        images.push(match result {
+            Ok(image) => image,
+            Err(e) => {
+                tracing::warn!("Inbound image for user {} not processed: {}", user.id, e);
+                InboundImage::Failed {
+                    reason: e.to_string(),
+                }
+            }
+        });
+    }
+    images
+}
+
+async fn download(
+    state: &Arc<AppState>,
+    user: &User,
+    url: &str,
+) -> Result<Vec<u8>, InboundMediaError> {
+    // Web chat uploads come in as data URLs through the same payload.
+    if url.starts_with("data:") {
+        // Base64 carries three bytes per four characters.
+        if url.len() / 4 * 3 > MAX_DOWNLOAD_BYTES {
+            return Err(too_large(MAX_DOWNLOAD_BYTES));
+        }
+        return decode_data_url(url)
+            .map(|(_, bytes)| bytes)
+            .ok_or_else(|| InboundMediaError::Download("malformed data URL".to_string()));
+    }
+    if url.starts_with("https://api.twilio.com/") {
+        return state
+            .twilio_message_service
+            .fetch_message_media(user, url)
+            .await
+            .map_err(|e| InboundMediaError::Download(e.to_string()));
+    }
+
+    let res = reqwest::Client::new()
+        .get(url)
+        .timeout(DOWNLOAD_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| InboundMediaError::Download(e.to_string()))?;
+    if !res.status().is_success() {
+        return Err(InboundMediaError::Download(format!(
+            "http {}",
+            res.status()
+        )));
+    }
+    read_capped(res, MAX_DOWNLOAD_BYTES).await
+}
+
+/// Read a response body, refusing it once it passes `limit` bytes. The
+/// declared `Content-Length` is checked first; the count while streaming
+/// covers servers that leave it out or lie.
+pub async fn read_capped(
+    mut res: reqwest::Response,
+    limit: usize,
+) -> Result<Vec<u8>, InboundMediaError> {
+    if res.content_length().is_some_and(|len| len > limit as u64) {
+        return Err(too_large(limit));
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = res
+        .chunk()
+        .await
+        .map_err(|e| InboundMediaError::Download(e.to_string()))?
+    {
+        if bytes.len() + chunk.len() > limit {
+            return Err(too_large(limit));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+fn too_large(limit: usize) -> InboundMediaError {
+    InboundMediaError::Download(format!("larger than {} MB", limit / (1024 * 1024)))
+}
+
+async fn process_image(
+    content_type: &str,
+    bytes: &[u8],
+) -> Result<InboundImage, InboundMediaError> {
+    let jpeg = if is_heic(content_type, bytes) {
+        normalize_raster(&heic_to_jpeg(bytes).await?)?
+    } else {
+        normalize_raster(bytes)?
+    };
+    let ocr_text = run_ocr(&jpeg).await;
+    let fields = ocr_text.as_deref().map(extract_fields).unwrap_or_default();
+    Ok(InboundImage::Processed {
+        jpeg,
+        ocr_text,
+        fields,
+    })
+}
+
+/// HEIC/HEIF by its `ftyp` brand, falling back to the declared type.
+pub fn is_heic(content_type: &str, bytes: &[u8]) -> bool {
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return matches!(
+            &bytes[8..12],
+            b"heic" | b"heix" | b"hevc" | b"hevx" | b"heim" | b"heis" | b"mif1" | b"msf1"
+        );
+    }
+    matches!(
+        content_type.to_ascii_lowercase().as_str(),
+        "image/heic" | "image/heif" | "image/heic-sequence" | "image/heif-sequence"
+    )
+}
+
+/// Decode JPEG/PNG (or anything else `image` understands) within the decode
+/// limits, shrink it to `MAX_EDGE` and re-encode as JPEG without metadata.
+pub fn normalize_raster(bytes: &[u8]) -> Result<Vec<u8>, InboundMediaError> {
+    let format = image::guess_format(bytes)
+        .map_err(|_| InboundMediaError::Unsupported("unrecognized image data".to_string()))?;
+    let mut reader = image::io::Reader::with_format(std::io::Cursor::new(bytes), format);
+    let mut limits = image::io::Limits::default();
+    limits.max_image_width = Some(MAX_DECODE_EDGE);
+    limits.max_image_height = Some(MAX_DECODE_EDGE);
+    limits.max_alloc = Some(MAX_DECODE_BYTES);
+    reader.limits(limits);
+    let mut img = reader
+        .decode()
+        .map_err(|e| InboundMediaError::Decode(e.to_string()))?;
+    if img.width() > MAX_EDGE || img.height() > MAX_EDGE {
+        img = img.resize(MAX_EDGE, MAX_EDGE, image::imageops::FilterType::Triangle);
+    }
+    let rgb = img.to_rgb8();
+    let mut out = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)
+        .encode(
+            rgb.as_raw(),
+            rgb.width(),
+            rgb.height(),
+            image::ColorType::Rgb8,
+        )
+        .map_err(|e| InboundMediaError::Decode(e.to_string()))?;
+    Ok(out)
+}
+
+/// `heif-convert` only works on files, so the photo briefly touches disk.
+/// Both temp files are removed before returning, on every path.
+async fn heic_to_jpeg(bytes: &[u8]) -> Result<Vec<u8>, InboundMediaError> {
+    let bin = std::env::var("HEIF_CONVERT_BIN").unwrap_or_else(|_| "heif-convert".to_string());
+    let stem = std::env::temp_dir().join(format!("lf-mms-{}", uuid::Uuid::new_v4()));
+    let input = stem.with_extension("heic");
+    let output = stem.with_extension("jpg");
+
+    let result: Result<Vec<u8>, InboundMediaError> = async {
+        tokio::fs::write(&input, bytes)
+            .await
+            .map_err(|e| InboundMediaError::HeicConversion(e.to_string()))?;
+        let mut command = tokio::process::Command::new(&bin);
+        command.arg(&input).arg(&output).kill_on_drop(true);
+        let status = tokio::time::timeout(TOOL_TIMEOUT, command.output())
+            .await
+            .map_err(|_| InboundMediaError::HeicConversion(format!("{} timed out", bin)))?
+            .map_err(|e| InboundMediaError::HeicConversion(format!("{}: {}", bin, e)))?
+            .status;
+        if !status.success() {
+            return Err(InboundMediaError::HeicConversion(format!(
+                "{} exited with {}",
+                bin, status
+            )));
+        }
+        // The converted JPEG is decoded with limits next; just don't read
+        // an absurd file into memory first.
+        let converted = tokio::fs::metadata(&output)
+            .await
+            .map_err(|e| InboundMediaError::HeicConversion(e.to_string()))?;
+        if converted.len() > MAX_DECODE_BYTES {
+            return Err(InboundMediaError::HeicConversion(
+                "converted image is too large".to_string(),
+            ));
+        }
+        tokio::fs::read(&output)
+            .await
+            .map_err(|e| InboundMediaError::HeicConversion(e.to_string()))
+    }
+    .await;
+
+    let _ = tokio::fs::remove_file(&input).await;
+    let _ = tokio::fs::remove_file(&output).await;
+    result
+}
+
+/// OCR the JPEG through `tesseract stdin stdout`. `OCR_LANGUAGES` takes
+/// tesseract's `-l` syntax (`eng+fin`). Returns `None` when the binary is
+/// missing, fails, or finds no text.
+async fn run_ocr(jpeg: &[u8]) -> Option<String> {
+    let bin = std::env::var("OCR_TESSERACT_BIN").unwrap_or_else(|_| "tesseract".to_string());
+    let languages = std::env::var("OCR_LANGUAGES").unwrap_or_else(|_| "eng".to_string());
+
+    let mut child = match tokio::process::Command::new(&bin)
+        .args(["stdin", "stdout", "-l", languages.as_str()])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!("OCR unavailable ({}): {}", bin, e);
+            return None;
+        }
+    };
+
+    let mut stdin = child.stdin.take()?;
+    let jpeg = jpeg.to_vec();
+    let writer = tokio::spawn(async move {
+        let _ = stdin.write_all(&jpeg).await;
+    });
+    let output = match tokio::time::timeout(TOOL_TIMEOUT, child.wait_with_output()).await {
+        Ok(Ok(output)) if output.status.success() => output,
+        Ok(Ok(output)) => {
+            tracing::warn!("OCR failed with {}", output.status);
+            return None;
+        }
+        Ok(Err(e)) => {
+            tracing::warn!("OCR failed: {}", e);
+            return None;
+        }
+        Err(_) => {
+            tracing::warn!("OCR timed out");
+            return None;
+        }
+    };
+    let _ = writer.await;
+
+    let text = clean_ocr_text(&String::from_utf8_lossy(&output.stdout));
+    (!text.is_empty()).then_some(text)
+}
+
+/// Trim every line, drop empty runs and cap the length.
+pub fn clean_ocr_text(raw: &str) -> String {
+    let text = raw
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    match text.char_indices().nth(MAX_OCR_CHARS) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text,
+    }
+}
+
+fn date_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?ix)
+            \b\d{4}-\d{1,2}-\d{1,2}\b
+            | \b\d{1,2}[./-]\d{1,2}[./-](?:\d{4}|\d{2})\b
+            | \b(?:\d{1,2}(?:st|nd|rd|th)?\.?[\t\x20]+)?
+              (?:jan|feb|mar|apr|may|jun|jul|aug|sep|sept|oct|nov|dec)[a-z]*\.?
+              (?:[\t\x20]+\d{1,2}(?:st|nd|rd|th)?)?,?[\t\x20]+\d{4}\b",
+        )
+        .expect("valid date regex")
+    })
+}
+
+fn time_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?ix)
+            \b(?:[01]?\d|2[0-3]):[0-5]\d(?:[\t\x20]*[ap]m)?\b
+            | \b(?:1[0-2]|0?[1-9])[\t\x20]*[ap]m\b",
+        )
+        .expect("valid time regex")
+    })
+}
+
+fn amount_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?ix)
+            [$€£¥][\t\x20]?\d+(?:[.,]\d{3})*(?:[.,]\d{1,2})?
+            | \b\d+(?:[.,]\d{3})*(?:[.,]\d{1,2})?[\t\x20]?
+              (?:€|\$|£|(?:eur|euros?|usd|gbp|sek|nok|dkk|chf|kr)\b)
+            | \b(?:eur|usd|gbp|sek|nok|dkk|chf)[\t\x20]?\d+(?:[.,]\d{3})*(?:[.,]\d{1,2})?",
+        )
+        .expect("valid amount regex")
+    })
+}
+
+fn address_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // US/UK style "12 Baker Street" and Nordic/German style
+        // "Mannerheimintie 5 B" / "Hauptstraße 3". Matches never cross a
+        // line break: OCR lines are the only structure we can trust.
+        Regex::new(
+            r"(?x)
+            \b\d{1,5}[\t\x20]+(?:[A-Z][\p{L}'-]*\.?[\t\x20]+){1,4}
+              (?:Street|St|Avenue|Ave|Road|Rd|Boulevard|Blvd|Lane|Ln
+                |Drive|Dr|Court|Ct|Way|Place|Pl)\b\.?
+            | \b\p{Lu}[\p{L}-]*(?:katu|tie|kuja|polku|väylä|straße|strasse|gasse|weg
+                |gatan|vägen|veien|gade|vej)
+              [\t\x20]+\d{1,4}(?:[\t\x20]?[A-Z]\b)?",
+        )
+        .expect("valid address regex")
+    })
+}
+
+fn collect(re: &Regex, text: &str) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    for m in re.find_iter(text) {
+        let value = m.as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+        if !found.contains(&value) {
+            found.push(value);
+        }
+        if found.len() == MAX_FIELDS {
+            break;
+        }
+    }
+    found
+}
+
+/// Pull the fields a user is most likely to act on out of OCR text. Values
+/// stay verbatim; the model interprets formats in the user's locale.
+pub fn extract_fields(text: &str) -> ExtractedFields {
+    ExtractedFields {
+        dates: collect(date_regex(), text),
+        times: collect(time_regex(), text),
+        amounts: collect(amount_regex(), text),
+        addresses: collect(address_regex(), text),
+    }
+}
+
+/// The text part that goes next to the images in the user's message.
+pub fn describe_for_model(images: &[InboundImage]) -> String {
+    let mut out = format!(
+        "[The user attached {} photo{}. Text read from them by OCR is below; it can \
+         contain recognition errors, so trust the image where the two disagree.]",
+        images.len(),
+        if images.len() == 1 { "" } else { "s" }
+    );
+    let mut any_fields = false;
+    for (i, image) in images.iter().enumerate() {
+        let n = i + 1;
+        match image {
+            InboundImage::Failed { reason } => {
+                out.push_str(&format!(
+                    "\nPhoto {}: could not be processed ({}).",
+                    n, reason
+                ));
+            }
+            InboundImage::Processed { ocr_text: None, .. } => {
+                out.push_str(&format!("\nPhoto {}: no readable text.", n));
+            }
+            InboundImage::Processed {
+                ocr_text: Some(text),
+                fields,
+                ..
+            } => {
+                out.push_str(&format!("\nPhoto {} text:\n\"\"\"\n{}\n\"\"\"", n, text));
+                if !fields.is_empty() {
+                    any_fields = true;
+                    let lists = [
+                        ("dates", &fields.dates),
+                        ("times", &fields.times),
+                        ("amounts", &fields.amounts),
+                        ("addresses", &fields.addresses),
+                    ];
+                    let details: Vec<String> = lists
+                        .iter()
+                        .filter(|(_, values)| !values.is_empty())
+                        .map(|(label, values)| format!("{}: {}", label, values.join("; ")))
+                        .collect();
+                    out.push_str(&format!("\nPhoto {} details: {}", n, details.join(" | ")));
+                }
+            }
+        }
+    }
+    if any_fields {
+        out.push_str(
+            "\nIf a date above is a deadline or appointment, offer to set a reminder or \
+             calendar event for it, or create one if the user asked.",
+        );
+    }
+    out
+}
@@ -0,0 +1,375 @@
+//! Re-encrypts stored secrets under the newest key in the key ring.
+//!
+//! After a new key is added to `ENCRYPTION_KEYS`, fresh writes use it right
+//! away but existing rows keep their old key id. This job walks every
+//! encrypted column in primary key order, rewrites values that aren't on the
+//! active key, and records a cursor per column in
+//! `encryption_rotation_progress` so a restart picks up where it stopped.
+//! Once every column reports done the old key can be removed.
+
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{error, info, warn};
+
+use crate::utils::encryption::KeyRing;
+use crate::PgDbPool;
+
+const BATCH_SIZE: i64 = 200;
+
+/// Only one pass runs at a time per process.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// A column holding `utils::encryption` ciphertexts.
+#[derive(Debug, Clone, Copy)]
+pub struct EncryptedColumn {
+    pub table: &'static str,
+    pub column: &'static str,
+    /// Primary key column and its SQL type, for the cursor.
+    pub key: &'static str,
+    pub key_type: &'static str,
+}
+
+const fn column(table: &'static str, column: &'static str) -> EncryptedColumn {
+    EncryptedColumn {
+        table,
+        column,
+        key: "id",
+        key_type: "int4",
+    }
+}
+
+/// Every encrypted column. New ones must be added here or they will keep
+/// needing the old key.
+pub const ENCRYPTED_COLUMNS: &[EncryptedColumn] = &[
+    column("user_secrets", "encrypted_matrix_access_token"),
+    column("user_secrets", "encrypted_matrix_password"),
+    column(
+        "user_secrets",
+        "encrypted_matrix_secret_storage_recovery_key",
+    ),
+    column("user_secrets", "encrypted_twilio_account_sid"),
+    column("user_secrets", "encrypted_twilio_auth_token"),
+    column("imap_connection", "encrypted_password"),
+    column("message_history", "encrypted_content"),
+    column("tesla", "encrypted_access_token"),
+    column("tesla", "encrypted_refresh_token"),
+    column("youtube", "encrypted_access_token"),
+    column("youtube", "encrypted_refresh_token"),
+    column("mcp_servers", "url_encrypted"),
+    column("mcp_servers", "auth_token_encrypted"),
+    column("mcp_servers", "oauth_config_encrypted"),
+    column("mcp_servers", "oauth_access_token_encrypted"),
+    column("mcp_servers", "oauth_refresh_token_encrypted"),
+    column("caldav_connections", "url_encrypted"),
+    column("caldav_connections", "username_encrypted"),
+    column("caldav_connections", "password_encrypted"),
+    column("caldav_connections", "calendars_encrypted"),
+    column("totp_secrets", "encrypted_secret"),
+    column("webauthn_credentials", "encrypted_public_key"),
+    column("message_status_log", "encrypted_body"),
+    column("reply_contexts", "encrypted_summary"),
+    EncryptedColumn {
+        table: "light_tool_runs",
+        column: "encrypted_user_message",
+        key: "id",
+        key_type: "text",
+    },
+    EncryptedColumn {
+        table: "light_tool_runs",
+        column: "encrypted_image_data_url",
+        key: "id",
+        key_type: "text",
+    },
+    EncryptedColumn {
+        table: "light_tool_runs",
+        column: "encrypted_activity_text",
+        key: "id",
+        key_type: "text",
+    },
+    EncryptedColumn {
+        table: "light_tool_runs",
+        column: "encrypted_assistant_message",
+        key: "id",
+        key_type: "text",
+    },
+    EncryptedColumn {
+        table: "light_tool_runs",
+        column: "encrypted_error_message",
+        key: "id",
+        key_type: "text",
+    },
+    EncryptedColumn {
+        table: "light_tool_push_registrations",
+        column: "encrypted_endpoint",
+        key: "device_id",
+        key_type: "int4",
+    },
+    EncryptedColumn {
+        table: "push_endpoints",
+        column: "encrypted_endpoint",
+        key: "user_id",
+        key_type: "int4",
+    },
+    EncryptedColumn {
+        table: "push_endpoints",
+        column: "encrypted_auth_secret",
+        key: "user_id",
+        key_type: "int4",
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnProgress {
+    pub table: String,
+    pub column: String,
+    pub target_key_id: Option<i32>,
+    pub rows_scanned: i64,
+    pub rows_rewritten: i64,
+    pub rows_failed: i64,
+    pub done: bool,
+    pub updated_at: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RotationStatus {
+    pub active_key_id: u32,
+    pub key_ids: Vec<u32>,
+    pub running: bool,
+    /// Every column is done for the active key, so older keys can go.
+    pub complete: bool,
+    pub columns: Vec<ColumnProgress>,
+}
+
+#[derive(QueryableByName)]
+struct ProgressRow {
+    #[diesel(sql_type = Integer)]
+    target_key_id: i32,
+    #[diesel(sql_type = Nullable<Text>)]
+    last_key: Option<String>,
+    #[diesel(sql_type = BigInt)]
+    rows_scanned: i64,
+    #[diesel(sql_type = BigInt)]
+    rows_rewritten: i64,
+    #[diesel(sql_type = BigInt)]
+    rows_failed: i64,
+    #[diesel(sql_type = Nullable<Integer>)]
+    completed_at: Option<i32>,
+    #[diesel(sql_type = Integer)]
+    updated_at: i32,
+}
+
+#[derive(QueryableByName)]
+struct ValueRow {
+    #[diesel(sql_type = Text)]
+    key: String,
+    #[diesel(sql_type = Text)]
+    value: String,
+}
+
+fn now() -> i32 {
+    chrono::Utc::now().timestamp() as i32
+}
+
+fn load_progress(
+    conn: &mut PgConnection,
+    col: &EncryptedColumn,
+) -> QueryResult<Option<ProgressRow>> {
+    diesel::sql_query(
+        "SELECT target_key_id, last_key, rows_scanned, rows_rewritten, rows_failed, \
+         completed_at, updated_at FROM encryption_rotation_progress \
+         WHERE table_name = $1 AND column_name = $2",
+    )
+    .bind::<Text, _>(col.table)
+    .bind::<Text, _>(col.column)
+    .get_result(conn)
+    .optional()
+}
+
+fn save_progress(
+    conn: &mut PgConnection,
+    col: &EncryptedColumn,
+    progress: &ProgressRow,
+) -> QueryResult<()> {
+    diesel::sql_query(
+        "INSERT INTO encryption_rotation_progress \
+         (table_name, column_name, target_key_id, last_key, rows_scanned, rows_rewritten, \
+          rows_failed, completed_at, updated_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+         ON CONFLICT (table_name, column_name) DO UPDATE SET \
+           target_key_id = EXCLUDED.target_key_id, last_key = EXCLUDED.last_key, \
+           rows_scanned = EXCLUDED.rows_scanned, rows_rewritten = EXCLUDED.rows_rewritten, \
+           rows_failed = EXCLUDED.rows_failed, completed_at = EXCLUDED.completed_at, \
+           updated_at = EXCLUDED.updated_at",
+    )
+    .bind::<Text, _>(col.table)
+    .bind::<Text, _>(col.column)
+    .bind::<Integer, _>(progress.target_key_id)
+    .bind::<Nullable<Text>, _>(progress.last_key.as_deref())
+    .bind::<BigInt, _>(progress.rows_scanned)
+    .bind::<BigInt, _>(progress.rows_rewritten)
+    .bind::<BigInt, _>(progress.rows_failed)
+    .bind::<Nullable<Integer>, _>(progress.completed_at)
+    .bind::<Integer, _>(progress.updated_at)
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Bring one column onto the active key, resuming from its saved cursor.
+fn rotate_column(
+    conn: &mut PgConnection,
+    ring: &KeyRing,
+    col: &EncryptedColumn,
+) -> QueryResult<ProgressRow> {
+    let target = ring.active_key_id() as i32;
+    let mut progress = match load_progress(conn, col)? {
+        Some(p) if p.target_key_id == target => p,
+        _ => ProgressRow {
+            target_key_id: target,
+            last_key: None,
+            rows_scanned: 0,
+            rows_rewritten: 0,
+            rows_failed: 0,
+            completed_at: None,
+            updated_at: now(),
+        },
+    };
+    if progress.completed_at.is_some() {
+        return Ok(progress);
+    }
+
+    // Identifiers come from ENCRYPTED_COLUMNS, never from input.
+    let select = format!(
+        "SELECT {key}::text AS key, {column} AS value FROM {table} \
+         WHERE {column} IS NOT NULL AND ($1::text IS NULL OR {key} > $1::{key_type}) \
+         ORDER BY {key} LIMIT $2",
+        key = col.key,
+        key_type = col.key_type,
+        column = col.column,
+        table = col.table,
+    );
+    // Only swap if the value is unchanged, so a concurrent write wins.
+    let update = format!(
+        "UPDATE {table} SET {column} = $1 WHERE {key} = $2::{key_type} AND {column} = $3",
+        key = col.key,
+        key_type = col.key_type,
+        column = col.column,
+        table = col.table,
+    );
+
+    loop {
+        let rows: Vec<ValueRow> = diesel::sql_query(&select)
+            .bind::<Nullable<Text>, _>(progress.last_key.as_deref())
+            .bind::<BigInt, _>(BATCH_SIZE)
+            .load(conn)?;
+
+        for row in &rows {
+            match ring.reencrypt(&row.value) {
+                Ok(None) => {}
+                Ok(Some(rewritten)) => {
+                    progress.rows_rewritten += diesel::sql_query(&update)
+                        .bind::<Text, _>(&rewritten)
+                        .bind::<Text, _>(&row.key)
+                        .bind::<Text, _>(&row.value)
+                        .execute(conn)? as i64;
+                }
+                Err(e) => {
+                    progress.rows_failed += 1;
+                    warn!(
+                        "Cannot re-encrypt {}.{} row {}: {}",
+                        col.table, col.column, row.key, e
+                    );
+                }
+            }
+        }
+
+        progress.rows_scanned += rows.len() as i64;
+        progress.updated_at = now();
+        match rows.last() {
+            Some(last) => progress.last_key = Some(last.key.clone()),
+            None => progress.completed_at = Some(progress.updated_at),
+        }
+        save_progress(conn, col, &progress)?;
+        if progress.completed_at.is_some() {
+            info!(
+                "Re-encrypted {}.{} under key {}: {} rewritten, {} failed",
+                col.table, col.column, target, progress.rows_rewritten, progress.rows_failed
+            );
+            break;
+        }
+    }
+    Ok(progress)
+}
+
+/// Run a full pass over every encrypted column. Blocking; call from
+/// `spawn_blocking`. Returns false if another pass is already running.
+pub fn run(pool: &PgDbPool) -> Result<bool, String> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Ok(false);
+    }
+    let result = run_pass(pool);
+    RUNNING.store(false, Ordering::SeqCst);
+    result.map(|_| true)
+}
+
+fn run_pass(pool: &PgDbPool) -> Result<(), String> {
+    let ring = KeyRing::from_env().map_err(|e| e.to_string())?;
+    let mut conn = pool.get().map_err(|e| e.to_string())?;
+    info!(
+        "Starting re-encryption pass to key {} (ring: {:?})",
+        ring.active_key_id(),
+        ring.key_ids()
+    );
+    for col in ENCRYPTED_COLUMNS {
+        rotate_column(&mut conn, &ring, col)
+            .map_err(|e| format!("{}.{}: {}", col.table, col.column, e))?;
+    }
+    Ok(())
+}
+
+/// Start a pass on a blocking thread unless one is already running.
+/// Returns whether a new pass was started.
+pub fn start_background(pool: PgDbPool) -> bool {
+    if RUNNING.load(Ordering::SeqCst) {
+        return false;
+    }
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = run(&pool) {
+            error!("Re-encryption pass failed: {}", e);
+        }
+    });
+    true
+}
+
+/// Progress of every encrypted column towards the active key.
+pub fn status(pool: &PgDbPool) -> Result<RotationStatus, String> {
+    let ring = KeyRing::from_env().map_err(|e| e.to_string())?;
+    let target = ring.active_key_id() as i32;
+    let mut conn = pool.get().map_err(|e| e.to_string())?;
+
+    let mut columns = Vec::with_capacity(ENCRYPTED_COLUMNS.len());
+    for col in ENCRYPTED_COLUMNS {
+        let progress = load_progress(&mut conn, col).map_err(|e| e.to_string())?;
+        // Progress towards an older key says nothing about the current one.
+        let progress = progress.filter(|p| p.target_key_id == target);
+        columns.push(ColumnProgress {
+            table: col.table.to_string(),
+            column: col.column.to_string(),
+            target_key_id: progress.as_ref().map(|p| p.target_key_id),
+            rows_scanned: progress.as_ref().map_or(0, |p| p.rows_scanned),
+            rows_rewritten: progress.as_ref().map_or(0, |p| p.rows_rewritten),
+            rows_failed: progress.as_ref().map_or(0, |p| p.rows_failed),
+            done: progress.as_ref().is_some_and(|p| p.completed_at.is_some()),
+            updated_at: progress.as_ref().map(|p| p.updated_at),
+        });
+    }
+
+    Ok(RotationStatus {
+        active_key_id: ring.active_key_id(),
+        key_ids: ring.key_ids(),
+        running: RUNNING.load(Ordering::SeqCst),
+        complete: columns.iter().all(|c| c.done && c.rows_failed == 0),
+        columns,
+    })
+}
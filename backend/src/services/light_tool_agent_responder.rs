@@ -499,7 +499,12 @@ fn tool_activity(name: &str) -> &'static str {
         "get_weather" => "CHECKING WEATHER",
         "search_firecrawl" => "SEARCHING THE WEB",
         "query_message" | "query_event" | "query_person" => "CHECKING YOUR ACCOUNT",
-        "send_chat_message" | "send_email" | "respond_to_email" => "PREPARING MESSAGE",
+        "send_chat_message"
+        | "react_to_chat_message"
+        | "edit_chat_message"
+        | "send_email"
+        | "respond_to_email" => "PREPARING MESSAGE",
+        "send_image" => "PREPARING IMAGE",
         _ => WORKING_ACTIVITY,
     }
 }
@@ -1,16 +1,60 @@
 //! MCP Client Service
 //!
-//! Connects to remote MCP servers over HTTP (Streamable HTTP transport)
-//! to list available tools and execute tool calls.
+//! Connects to remote MCP servers to list and call tools, list and read
+//! resources, and list and render prompts.
 //!
-//! MCP uses JSON-RPC 2.0 over HTTP with Server-Sent Events for responses.
+//! Servers are reached over the Streamable HTTP transport (protocol
+//! 2025-06-18 / 2025-03-26). Servers that reject a POSTed `initialize` fall
+//! back to the older HTTP+SSE transport (2024-11-05), where requests are
+//! POSTed to an endpoint announced on a long-lived event stream.
+//!
+//! Each server gets one `initialize` handshake. The negotiated protocol
+//! version, `Mcp-Session-Id` and capabilities are cached per URL + token and
+//! reused until the server expires the session (HTTP 404), after which the
+//! client re-initializes and retries once. Responses may be plain JSON or an
+//! SSE stream that interleaves progress, log and list-changed notifications
+//! (and server pings) before the result.
 
-use reqwest::Client;
+use futures::stream::{BoxStream, StreamExt};
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::time::Duration;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+/// Protocol version we ask for in `initialize`.
+pub const LATEST_PROTOCOL_VERSION: &str = "2025-06-18";
+/// Versions we can talk; anything else from the server is refused.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Longest silence tolerated on a response stream. Progress notifications
+/// count as activity, so long-running tools that report progress survive.
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Hard cap on a single request, progress or not.
+const DEFAULT_MAX_REQUEST_TIME: Duration = Duration::from_secs(300);
+/// Re-handshake after this long even if the server never expired the session.
+const SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+/// How long a tools/list result is reused (dropped early on list_changed).
+const TOOLS_CACHE_TTL: Duration = Duration::from_secs(60);
+const MAX_PAGES: usize = 10;
+const MAX_STREAM_RESUMES: usize = 3;
+
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+const PROTOCOL_HEADER: &str = "MCP-Protocol-Version";
+const ACCEPT_BOTH: &str = "application/json, text/event-stream";
+const UNAUTHORIZED: &str = "MCP server requires authorization (HTTP 401). Reconnect it with OAuth or check the auth token.";
+
+static NEXT_REQUEST_ID: AtomicI64 = AtomicI64::new(1);
+
+/// Sessions keyed by a hash of URL + auth token.
+static SESSIONS: LazyLock<Mutex<HashMap<String, CachedSession>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 /// MCP JSON-RPC request
 #[derive(Debug, Serialize)]
 struct JsonRpcRequest {
@@ -27,7 +71,7 @@ struct JsonRpcResponse {
     #[allow(dead_code)]
     jsonrpc: String,
     #[allow(dead_code)]
-    id: Option<i64>,
+    id: Option<Value>,
     #[serde(default)]
     result: Option<Value>,
     #[serde(default)]
@@ -53,10 +97,94 @@ pub struct McpTool {
     pub input_schema: Value,
 }
 
-/// Response from tools/list
-#[derive(Debug, Deserialize)]
-struct ListToolsResult {
-    tools: Vec<McpTool>,
+/// Resource advertised by resources/list
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpResource {
+    pub uri: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, rename = "mimeType")]
+    pub mime_type: Option<String>,
+}
+
+/// One entry of a resources/read result. Binary resources carry `blob`
+/// (base64) instead of `text`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpResourceContent {
+    pub uri: String,
+    #[serde(default, rename = "mimeType")]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub blob: Option<String>,
+}
+
+/// Prompt template advertised by prompts/list
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpPrompt {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpPromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// A notifications/progress update for a running request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpProgress {
+    pub progress: f64,
+    pub total: Option<f64>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpTransport {
+    StreamableHttp,
+    LegacySse,
+}
+
+/// What the server told us during the handshake.
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServerInfo {
+    pub protocol_version: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub instructions: Option<String>,
+    pub capabilities: Value,
+    pub transport: McpTransport,
+}
+
+impl McpServerInfo {
+    /// Whether the server advertised a capability ("tools", "resources",
+    /// "prompts", "logging", ...).
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities
+            .get(capability)
+            .map(|v| !v.is_null())
+            .unwrap_or(false)
+    }
+}
+
+/// Everything a connection test shows the user.
+#[derive(Debug, Clone)]
+pub struct McpServerProbe {
+    pub info: McpServerInfo,
+    pub tools: Vec<McpTool>,
+    pub resources: Vec<McpResource>,
+    pub prompts: Vec<McpPrompt>,
 }
 
 /// Response from tools/call
@@ -64,6 +192,8 @@ struct ListToolsResult {
 struct CallToolResult {
     #[serde(default)]
     content: Vec<ToolContent>,
+    #[serde(default, rename = "structuredContent")]
+    structured_content: Option<Value>,
     #[serde(default, rename = "isError")]
     is_error: bool,
 }
@@ -80,21 +210,441 @@ struct ToolContent {
     #[serde(default, rename = "mimeType")]
     #[allow(dead_code)]
     mime_type: Option<String>,
+    /// Embedded resource (`type: "resource"`)
+    #[serde(default)]
+    resource: Option<McpResourceContent>,
+    /// Resource link (`type: "resource_link"`)
+    #[serde(default)]
+    uri: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedSession {
+    info: McpServerInfo,
+    session_id: Option<String>,
+    established: Instant,
+    tools: Option<(Instant, Vec<McpTool>)>,
+}
+
+/// Headers that tie a Streamable HTTP request to its session.
+#[derive(Debug, Clone, Default)]
+struct SessionHeaders {
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+}
+
+/// Where to POST answers to server-initiated requests (pings) seen on a stream.
+struct ReplyTarget<'a> {
+    url: &'a str,
+    auth_token: Option<&'a str>,
+    headers: &'a SessionHeaders,
+}
+
+type ProgressSink<'a, 'f> = Option<&'a mut (dyn FnMut(&McpProgress) + Send + 'f)>;
+
+enum RequestError {
+    /// The server no longer knows our Mcp-Session-Id.
+    SessionExpired,
+    /// HTTP 401: the bearer token is missing, expired or revoked.
+    Unauthorized,
+    Failed(String),
+}
+
+impl From<String> for RequestError {
+    fn from(message: String) -> Self {
+        RequestError::Failed(message)
+    }
+}
+
+impl From<RequestError> for String {
+    fn from(error: RequestError) -> Self {
+        match error {
+            RequestError::SessionExpired => "MCP server rejected the session".to_string(),
+            RequestError::Unauthorized => UNAUTHORIZED.to_string(),
+            RequestError::Failed(message) => message,
+        }
+    }
+}
+
+/// Supplies bearer tokens that expire and rotate (OAuth). The client asks
+/// again with `force_refresh` when the server answers 401.
+#[async_trait::async_trait]
+pub trait McpTokenSource: Send + Sync {
+    /// Stable identity for session caching, since the token itself rotates.
+    fn identity(&self) -> String;
+
+    async fn access_token(&self, force_refresh: bool) -> Result<String, String>;
+}
+
+fn cache_key(url: &str, auth_token: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(auth_token.unwrap_or("").as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn cached_session(key: &str) -> Option<CachedSession> {
+    let sessions = SESSIONS.lock().ok()?;
+    sessions
+        .get(key)
+        .filter(|s| s.established.elapsed() < SESSION_TTL)
+        .cloned()
+}
+
+fn store_session(key: &str, session: CachedSession) {
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        sessions.insert(key.to_string(), session);
+    }
+}
+
+fn forget_session(key: &str) {
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        sessions.remove(key);
+    }
+}
+
+fn cached_tools(key: &str) -> Option<Vec<McpTool>> {
+    let sessions = SESSIONS.lock().ok()?;
+    let (fetched, tools) = sessions.get(key)?.tools.as_ref()?;
+    (fetched.elapsed() < TOOLS_CACHE_TTL).then(|| tools.clone())
+}
+
+fn store_tools(key: &str, tools: &[McpTool]) {
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        if let Some(session) = sessions.get_mut(key) {
+            session.tools = Some((Instant::now(), tools.to_vec()));
+        }
+    }
+}
+
+fn invalidate_tools(key: &str) {
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        if let Some(session) = sessions.get_mut(key) {
+            session.tools = None;
+        }
+    }
+}
+
+fn next_request_id() -> i64 {
+    NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn id_matches(id: Option<&Value>, expected: i64) -> bool {
+    match id {
+        Some(Value::Number(n)) => n.as_i64() == Some(expected),
+        Some(Value::String(s)) => s.parse::<i64>().ok() == Some(expected),
+        _ => false,
+    }
+}
+
+fn into_result(response: JsonRpcResponse) -> Result<Value, String> {
+    if let Some(err) = response.error {
+        return Err(format!(
+            "MCP server error: {} (code {})",
+            err.message, err.code
+        ));
+    }
+    response
+        .result
+        .ok_or_else(|| "No result from MCP server".to_string())
+}
+
+fn initialize_params() -> Value {
+    json!({
+        "protocolVersion": LATEST_PROTOCOL_VERSION,
+        "capabilities": {},
+        "clientInfo": {
+            "name": "Lightfriend",
+            "version": env!("CARGO_PKG_VERSION")
+        }
+    })
+}
+
+/// Read an `initialize` result into server info.
+fn parse_initialize_result(
+    result: &Value,
+    transport: McpTransport,
+) -> Result<McpServerInfo, String> {
+    let protocol_version = result
+        .get("protocolVersion")
+        .and_then(|v| v.as_str())
+        .unwrap_or(LATEST_PROTOCOL_VERSION)
+        .to_string();
+    if !SUPPORTED_PROTOCOL_VERSIONS.contains(&protocol_version.as_str()) {
+        return Err(format!(
+            "MCP server uses unsupported protocol version {}",
+            protocol_version
+        ));
+    }
+    let server_info = result.get("serverInfo");
+    let text = |v: Option<&Value>| v.and_then(|v| v.as_str()).map(String::from);
+    Ok(McpServerInfo {
+        protocol_version,
+        name: text(server_info.and_then(|s| s.get("name"))),
+        version: text(server_info.and_then(|s| s.get("version"))),
+        instructions: text(result.get("instructions")),
+        capabilities: result.get("capabilities").cloned().unwrap_or(json!({})),
+        transport,
+    })
+}
+
+/// Render a tools/call result as text for the model.
+fn render_tool_result(result: Value) -> Result<String, String> {
+    let call_result: CallToolResult = serde_json::from_value(result)
+        .map_err(|e| format!("Failed to parse tool result: {}", e))?;
+
+    if call_result.is_error {
+        // Extract error message from content
+        let error_texts: Vec<String> = call_result
+            .content
+            .iter()
+            .filter_map(|c| c.text.clone())
+            .collect();
+        let error_text = error_texts.join("\n");
+        return Err(format!("Tool error: {}", error_text));
+    }
+
+    // Combine text content from response
+    let text_parts: Vec<String> = call_result
+        .content
+        .iter()
+        .filter_map(|c| match c.content_type.as_str() {
+            "text" => c.text.clone(),
+            "resource" => Some(match c.resource.as_ref().and_then(|r| r.text.clone()) {
+                Some(text) => text,
+                None => "[resource content]".to_string(),
+            }),
+            "resource_link" => Some(format!(
+                "[resource: {}]",
+                c.uri.as_deref().unwrap_or("unknown")
+            )),
+            // For non-text content, return a description
+            "image" | "audio" => Some(format!("[{} content]", c.content_type)),
+            _ => c.text.clone(),
+        })
+        .collect();
+    let text_result = text_parts.join("\n");
+
+    if !text_result.is_empty() {
+        Ok(text_result)
+    } else if let Some(structured) = call_result.structured_content {
+        Ok(structured.to_string())
+    } else {
+        Ok("Tool executed successfully (no output)".to_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Server-Sent Events
+// ---------------------------------------------------------------------------
+
+/// One dispatched `text/event-stream` event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    /// `event:` field, "message" when absent.
+    pub event: String,
+    /// `data:` lines joined with "\n".
+    pub data: String,
+    pub id: Option<String>,
 }
 
+/// Incremental `text/event-stream` parser. Accepts arbitrary chunk
+/// boundaries (including inside a CRLF or a UTF-8 sequence).
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk and return the events it completed.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n' || b == b'\r') {
+            let is_cr = self.buffer[pos] == b'\r';
+            // A trailing CR may be the first half of a CRLF split across chunks.
+            if is_cr && pos + 1 == self.buffer.len() {
+                break;
+            }
+            let line: Vec<u8> = self.buffer.drain(..pos).collect();
+            let terminator = if is_cr && self.buffer.get(1) == Some(&b'\n') {
+                2
+            } else {
+                1
+            };
+            self.buffer.drain(..terminator);
+            if let Some(event) = self.process_line(&String::from_utf8_lossy(&line)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// End of stream: dispatch whatever is pending, even without the final
+    /// blank line.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\r').to_string();
+            if let Some(event) = self.process_line(&line) {
+                return Some(event);
+            }
+        }
+        self.dispatch()
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        let id = self.id.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            event: event.unwrap_or_else(|| "message".to_string()),
+            data: std::mem::take(&mut self.data).join("\n"),
+            id,
+        })
+    }
+}
+
+/// A response body read as a stream of SSE events.
+struct EventStream {
+    body: BoxStream<'static, Result<Vec<u8>, String>>,
+    parser: SseParser,
+    queued: VecDeque<SseEvent>,
+    ended: bool,
+}
+
+impl EventStream {
+    fn new(response: reqwest::Response) -> Self {
+        let body = response
+            .bytes_stream()
+            .map(|chunk| chunk.map(|b| b.to_vec()).map_err(|e| e.to_string()))
+            .boxed();
+        Self {
+            body,
+            parser: SseParser::new(),
+            queued: VecDeque::new(),
+            ended: false,
+        }
+    }
+
+    /// Next event, `None` once the server closes the stream.
+    async fn next(&mut self, idle_timeout: Duration) -> Result<Option<SseEvent>, String> {
+        loop {
+            if let Some(event) = self.queued.pop_front() {
+                return Ok(Some(event));
+            }
+            if self.ended {
+                return Ok(None);
+            }
+            match tokio::time::timeout(idle_timeout, self.body.next()).await {
+                Err(_) => return Err("MCP server stopped responding".to_string()),
+                Ok(Some(Ok(chunk))) => self.queued.extend(self.parser.feed(&chunk)),
+                Ok(Some(Err(e))) => return Err(format!("MCP stream failed: {}", e)),
+                Ok(None) => {
+                    self.ended = true;
+                    self.queued.extend(self.parser.finish());
+                }
+            }
+        }
+    }
+}
+
+fn is_event_stream(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("text/event-stream"))
+        .unwrap_or(false)
+}
+
+async fn error_body(response: reqwest::Response) -> String {
+    response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Client
+// ---------------------------------------------------------------------------
+
 /// MCP Client for connecting to remote MCP servers
 pub struct McpClientService {
     client: Client,
+    idle_timeout: Duration,
+    max_request_time: Duration,
+    token_source: Option<Arc<dyn McpTokenSource>>,
 }
 
 impl McpClientService {
     pub fn new() -> Self {
+        // No overall timeout: streamed responses may legitimately run for
+        // minutes. Silence is bounded by `idle_timeout` instead.
         let client = Client::builder()
-            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10))
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client }
+        Self {
+            client,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            max_request_time: DEFAULT_MAX_REQUEST_TIME,
+            token_source: None,
+        }
+    }
+
+    /// Override how long a response may stay silent before it is abandoned.
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Authenticate with tokens from `source` (refreshed on expiry and on
+    /// 401) instead of the static `auth_token` arguments.
+    pub fn with_token_source(mut self, source: Arc<dyn McpTokenSource>) -> Self {
+        self.token_source = Some(source);
+        self
+    }
+
+    /// Handshake result for a server, from cache when the session is live.
+    pub async fn server_info(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+    ) -> Result<McpServerInfo, String> {
+        Ok(self.session(url, auth_token).await?.info)
     }
 
     /// List available tools from an MCP server
@@ -103,35 +653,22 @@ impl McpClientService {
         url: &str,
         auth_token: Option<&str>,
     ) -> Result<Vec<McpTool>, String> {
+        let key = self.cache_key(url, auth_token);
+        if let Some(tools) = cached_tools(&key) {
+            return Ok(tools);
+        }
         info!("Listing tools from MCP server: {}", url);
 
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0",
-            id: 1,
-            method: "tools/list".to_string(),
-            params: None,
-        };
-
-        let response = self.send_request(url, auth_token, &request).await?;
-
-        match response.result {
-            Some(result) => {
-                let list_result: ListToolsResult = serde_json::from_value(result)
-                    .map_err(|e| format!("Failed to parse tools list: {}", e))?;
-                debug!("Found {} tools on MCP server", list_result.tools.len());
-                Ok(list_result.tools)
-            }
-            None => {
-                if let Some(err) = response.error {
-                    Err(format!(
-                        "MCP server error: {} (code {})",
-                        err.message, err.code
-                    ))
-                } else {
-                    Err("No result from MCP server".to_string())
-                }
-            }
+        let info = self.session(url, auth_token).await?.info;
+        if !info.supports("tools") {
+            return Ok(Vec::new());
         }
+        let tools: Vec<McpTool> = self
+            .list_paginated(url, auth_token, "tools/list", "tools")
+            .await?;
+        debug!("Found {} tools on MCP server", tools.len());
+        store_tools(&key, &tools);
+        Ok(tools)
     }
 
     /// Call a tool on an MCP server
@@ -142,211 +679,930 @@ impl McpClientService {
         tool_name: &str,
         arguments: Value,
     ) -> Result<String, String> {
+        self.call_tool_with_progress(url, auth_token, tool_name, arguments, |_| {})
+            .await
+    }
+
+    /// Call a tool, reporting the server's progress notifications as they
+    /// arrive on the response stream.
+    pub async fn call_tool_with_progress<F>(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+        tool_name: &str,
+        arguments: Value,
+        mut on_progress: F,
+    ) -> Result<String, String>
+    where
+        F: FnMut(&McpProgress) + Send,
+    {
         info!("Calling MCP tool '{}' on server: {}", tool_name, url);
 
-        let params = serde_json::json!({
+        let params = json!({
             "name": tool_name,
             "arguments": arguments
         });
+        let result = self
+            .request(
+                url,
+                auth_token,
+                "tools/call",
+                Some(params),
+                Some(&mut on_progress),
+            )
+            .await?;
+        render_tool_result(result)
+    }
 
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0",
-            id: 2,
-            method: "tools/call".to_string(),
-            params: Some(params),
-        };
+    /// List resources the server exposes (empty when it has none).
+    pub async fn list_resources(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+    ) -> Result<Vec<McpResource>, String> {
+        if !self
+            .session(url, auth_token)
+            .await?
+            .info
+            .supports("resources")
+        {
+            return Ok(Vec::new());
+        }
+        self.list_paginated(url, auth_token, "resources/list", "resources")
+            .await
+    }
 
-        let response = self.send_request(url, auth_token, &request).await?;
-
-        match response.result {
-            Some(result) => {
-                let call_result: CallToolResult = serde_json::from_value(result)
-                    .map_err(|e| format!("Failed to parse tool result: {}", e))?;
-
-                if call_result.is_error {
-                    // Extract error message from content
-                    let error_texts: Vec<String> = call_result
-                        .content
-                        .iter()
-                        .filter_map(|c| c.text.clone())
-                        .collect();
-                    let error_text = error_texts.join("\n");
-                    return Err(format!("Tool error: {}", error_text));
-                }
+    /// Read a resource by URI.
+    pub async fn read_resource(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+        uri: &str,
+    ) -> Result<Vec<McpResourceContent>, String> {
+        if !self
+            .session(url, auth_token)
+            .await?
+            .info
+            .supports("resources")
+        {
+            return Err("MCP server does not expose resources".to_string());
+        }
+        let result = self
+            .request(
+                url,
+                auth_token,
+                "resources/read",
+                Some(json!({ "uri": uri })),
+                None,
+            )
+            .await?;
+        serde_json::from_value(result.get("contents").cloned().unwrap_or(json!([])))
+            .map_err(|e| format!("Failed to parse resource contents: {}", e))
+    }
+
+    /// List prompt templates the server exposes (empty when it has none).
+    pub async fn list_prompts(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+    ) -> Result<Vec<McpPrompt>, String> {
+        if !self
+            .session(url, auth_token)
+            .await?
+            .info
+            .supports("prompts")
+        {
+            return Ok(Vec::new());
+        }
+        self.list_paginated(url, auth_token, "prompts/list", "prompts")
+            .await
+    }
 
-                // Combine text content from response
-                let text_parts: Vec<String> = call_result
-                    .content
+    /// Render a prompt template into "role: text" lines.
+    pub async fn get_prompt(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+        name: &str,
+        arguments: Value,
+    ) -> Result<String, String> {
+        let result = self
+            .request(
+                url,
+                auth_token,
+                "prompts/get",
+                Some(json!({ "name": name, "arguments": arguments })),
+                None,
+            )
+            .await?;
+        let lines: Vec<String> = result
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .map(|messages| {
+                messages
                     .iter()
-                    .filter_map(|c| {
-                        if c.content_type == "text" {
-                            c.text.clone()
-                        } else if c.content_type == "resource" || c.content_type == "image" {
-                            // For non-text content, return a description
-                            Some(format!("[{} content]", c.content_type))
-                        } else {
-                            c.text.clone()
-                        }
+                    .filter_map(|m| {
+                        let role = m.get("role").and_then(|r| r.as_str()).unwrap_or("user");
+                        let text = m.get("content")?.get("text")?.as_str()?;
+                        Some(format!("{}: {}", role, text))
                     })
-                    .collect();
-                let text_result = text_parts.join("\n");
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(lines.join("\n"))
+    }
 
-                if text_result.is_empty() {
-                    Ok("Tool executed successfully (no output)".to_string())
-                } else {
-                    Ok(text_result)
-                }
+    /// Test connection to an MCP server
+    pub async fn test_connection(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+    ) -> Result<Vec<McpTool>, String> {
+        // Always handshake afresh so the test reflects the server right now.
+        forget_session(&self.cache_key(url, auth_token));
+        self.list_tools(url, auth_token).await
+    }
+
+    /// Fresh handshake plus everything the server offers. Resource and
+    /// prompt listing failures are logged rather than failing the probe.
+    pub async fn probe(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+    ) -> Result<McpServerProbe, String> {
+        let tools = self.test_connection(url, auth_token).await?;
+        let info = self.server_info(url, auth_token).await?;
+        let resources = self
+            .list_resources(url, auth_token)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("MCP resources/list failed for {}: {}", url, e);
+                Vec::new()
+            });
+        let prompts = self
+            .list_prompts(url, auth_token)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("MCP prompts/list failed for {}: {}", url, e);
+                Vec::new()
+            });
+        Ok(McpServerProbe {
+            info,
+            tools,
+            resources,
+            prompts,
+        })
+    }
+
+    async fn list_paginated<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+        method: &str,
+        field: &str,
+    ) -> Result<Vec<T>, String> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_PAGES {
+            let params = cursor.as_ref().map(|c| json!({ "cursor": c }));
+            let result = self.request(url, auth_token, method, params, None).await?;
+            let page: Vec<T> =
+                serde_json::from_value(result.get(field).cloned().unwrap_or(json!([])))
+                    .map_err(|e| format!("Failed to parse {}: {}", method, e))?;
+            items.extend(page);
+            cursor = result
+                .get("nextCursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if cursor.is_none() {
+                break;
             }
-            None => {
-                if let Some(err) = response.error {
-                    Err(format!(
-                        "MCP server error: {} (code {})",
-                        err.message, err.code
-                    ))
-                } else {
-                    Err("No result from tool call".to_string())
+        }
+        Ok(items)
+    }
+
+    /// Session cache key. With a token source the identity stands in for
+    /// the rotating token.
+    fn cache_key(&self, url: &str, auth_token: Option<&str>) -> String {
+        match &self.token_source {
+            Some(source) => cache_key(url, Some(&format!("token-source:{}", source.identity()))),
+            None => cache_key(url, auth_token),
+        }
+    }
+
+    /// The bearer token to send: from the token source when there is one.
+    async fn bearer(
+        &self,
+        auth_token: Option<&str>,
+        force_refresh: bool,
+    ) -> Result<Option<String>, String> {
+        match &self.token_source {
+            Some(source) => Ok(Some(source.access_token(force_refresh).await?)),
+            None => Ok(auth_token.map(String::from)),
+        }
+    }
+
+    /// The live session for a server, refreshing the token once if the
+    /// handshake is rejected.
+    async fn session(&self, url: &str, auth_token: Option<&str>) -> Result<CachedSession, String> {
+        let key = self.cache_key(url, auth_token);
+        let bearer = self.bearer(auth_token, false).await?;
+        match self.session_with(url, bearer.as_deref(), &key).await {
+            Err(RequestError::Unauthorized) if self.token_source.is_some() => {
+                let bearer = self.bearer(auth_token, true).await?;
+                Ok(self.session_with(url, bearer.as_deref(), &key).await?)
+            }
+            other => Ok(other?),
+        }
+    }
+
+    /// The cached session under `key`, performing the handshake if needed.
+    async fn session_with(
+        &self,
+        url: &str,
+        bearer: Option<&str>,
+        key: &str,
+    ) -> Result<CachedSession, RequestError> {
+        if let Some(session) = cached_session(key) {
+            return Ok(session);
+        }
+        let session = self.initialize(url, bearer).await?;
+        store_session(key, session.clone());
+        Ok(session)
+    }
+
+    /// Send a request and return its JSON-RPC result. A session the server
+    /// has expired is re-established once, and a rejected token is
+    /// refreshed once when a token source is set.
+    async fn request(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+        method: &str,
+        params: Option<Value>,
+        mut progress: ProgressSink<'_, '_>,
+    ) -> Result<Value, String> {
+        let key = self.cache_key(url, auth_token);
+        let mut bearer = self.bearer(auth_token, false).await?;
+        let mut reinitialized = false;
+        let mut refreshed = false;
+        loop {
+            let outcome = match self.session_with(url, bearer.as_deref(), &key).await {
+                Err(e) => Err(e),
+                Ok(session) => match session.info.transport {
+                    McpTransport::StreamableHttp => {
+                        self.streamable_request(
+                            url,
+                            bearer.as_deref(),
+                            &key,
+                            &session,
+                            method,
+                            params.clone(),
+                            progress.as_deref_mut(),
+                        )
+                        .await
+                    }
+                    McpTransport::LegacySse => self
+                        .legacy_request(
+                            url,
+                            bearer.as_deref(),
+                            &key,
+                            method,
+                            params.clone(),
+                            progress.as_deref_mut(),
+                        )
+                        .await
+                        .map_err(RequestError::Failed),
+                },
+            };
+            match outcome {
+                Ok(response) => return into_result(response),
+                Err(RequestError::SessionExpired) if !reinitialized => {
+                    info!("MCP session for {} expired, re-initializing", url);
+                    reinitialized = true;
+                    forget_session(&key);
                 }
+                Err(RequestError::SessionExpired) => {
+                    return Err("MCP server keeps rejecting the session".to_string())
+                }
+                Err(RequestError::Unauthorized) if self.token_source.is_some() && !refreshed => {
+                    info!("MCP server {} rejected the access token, refreshing", url);
+                    refreshed = true;
+                    forget_session(&key);
+                    bearer = self.bearer(auth_token, true).await?;
+                }
+                Err(e) => return Err(e.into()),
             }
         }
     }
 
-    /// Test connection to an MCP server
-    pub async fn test_connection(
+    fn post<T: Serialize + ?Sized>(
         &self,
         url: &str,
         auth_token: Option<&str>,
-    ) -> Result<Vec<McpTool>, String> {
-        // First, try to initialize the connection
-        let init_request = JsonRpcRequest {
+        headers: &SessionHeaders,
+        body: &T,
+    ) -> reqwest::RequestBuilder {
+        let mut req = self
+            .client
+            .post(url)
+            .header(reqwest::header::ACCEPT, ACCEPT_BOTH)
+            .json(body);
+        if let Some(token) = auth_token {
+            req = req.bearer_auth(token);
+        }
+        if let Some(session_id) = &headers.session_id {
+            req = req.header(SESSION_HEADER, session_id);
+        }
+        if let Some(version) = &headers.protocol_version {
+            req = req.header(PROTOCOL_HEADER, version);
+        }
+        req
+    }
+
+    /// Initialize over Streamable HTTP, falling back to the legacy HTTP+SSE
+    /// transport when the server refuses the POST.
+    async fn initialize(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+    ) -> Result<CachedSession, RequestError> {
+        let id = next_request_id();
+        let request = JsonRpcRequest {
             jsonrpc: "2.0",
-            id: 0,
+            id,
             method: "initialize".to_string(),
-            params: Some(serde_json::json!({
-                "protocolVersion": "2024-11-05",
-                "capabilities": {
-                    "tools": {}
-                },
-                "clientInfo": {
-                    "name": "Lightfriend",
-                    "version": "1.0.0"
+            params: Some(initialize_params()),
+        };
+        let no_session = SessionHeaders::default();
+        let response = self
+            .post(url, auth_token, &no_session, &request)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to MCP server: {}", e))?;
+
+        let status = response.status();
+        if matches!(
+            status,
+            StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+        ) {
+            let body = error_body(response).await;
+            debug!(
+                "MCP initialize POST to {} returned {}, trying HTTP+SSE transport",
+                url, status
+            );
+            return match self.legacy_initialize(url, auth_token).await {
+                Ok(info) => Ok(CachedSession {
+                    info,
+                    session_id: None,
+                    established: Instant::now(),
+                    tools: None,
+                }),
+                Err(e) => {
+                    debug!("MCP HTTP+SSE fallback for {} failed: {}", url, e);
+                    Err(format!("MCP server returned {}: {}", status, body).into())
                 }
-            })),
+            };
+        }
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(RequestError::Unauthorized);
+        }
+        if !status.is_success() {
+            return Err(format!(
+                "MCP server returned {}: {}",
+                status,
+                error_body(response).await
+            )
+            .into());
+        }
+
+        let session_id = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let target = ReplyTarget {
+            url,
+            auth_token,
+            headers: &no_session,
         };
+        let response = self.read_reply(response, &target, "", id, None).await?;
 
-        // Try to initialize - some servers require this, others don't
-        match self.send_request(url, auth_token, &init_request).await {
-            Ok(response) => {
-                debug!("MCP initialize response: {:?}", response.result);
-                // Send initialized notification (some servers require this)
-                let _ = self
-                    .send_notification(url, auth_token, "notifications/initialized")
-                    .await;
-            }
+        let info = match into_result(response) {
+            Ok(result) => parse_initialize_result(&result, McpTransport::StreamableHttp)?,
             Err(e) => {
-                // Some servers don't require initialization, try listing tools anyway
+                // Some servers skip the handshake entirely; assume tools only.
                 warn!("MCP initialize failed (may be optional): {}", e);
+                return Ok(CachedSession {
+                    info: McpServerInfo {
+                        protocol_version: "2024-11-05".to_string(),
+                        name: None,
+                        version: None,
+                        instructions: None,
+                        capabilities: json!({ "tools": {} }),
+                        transport: McpTransport::StreamableHttp,
+                    },
+                    session_id: None,
+                    established: Instant::now(),
+                    tools: None,
+                });
             }
+        };
+        debug!(
+            "MCP server {:?} at {} negotiated protocol {}",
+            info.name, url, info.protocol_version
+        );
+
+        let headers = SessionHeaders {
+            session_id: session_id.clone(),
+            protocol_version: Some(info.protocol_version.clone()),
+        };
+        let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        if let Err(e) = self
+            .post(url, auth_token, &headers, &initialized)
+            .send()
+            .await
+        {
+            warn!("MCP initialized notification to {} failed: {}", url, e);
         }
 
-        // Now list tools to verify the connection works
-        self.list_tools(url, auth_token).await
+        Ok(CachedSession {
+            info,
+            session_id,
+            established: Instant::now(),
+            tools: None,
+        })
     }
 
-    /// Send a JSON-RPC request to the MCP server
-    async fn send_request(
+    #[allow(clippy::too_many_arguments)]
+    async fn streamable_request(
         &self,
         url: &str,
         auth_token: Option<&str>,
-        request: &JsonRpcRequest,
-    ) -> Result<JsonRpcResponse, String> {
-        let mut req = self.client.post(url).json(request);
+        key: &str,
+        session: &CachedSession,
+        method: &str,
+        params: Option<Value>,
+        progress: ProgressSink<'_, '_>,
+    ) -> Result<JsonRpcResponse, RequestError> {
+        let id = next_request_id();
+        let params = with_progress_token(params, id, progress.is_some());
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0",
+            id,
+            method: method.to_string(),
+            params,
+        };
+        let headers = SessionHeaders {
+            session_id: session.session_id.clone(),
+            protocol_version: Some(session.info.protocol_version.clone()),
+        };
+        let response = self
+            .post(url, auth_token, &headers, &request)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to MCP server: {}", e))?;
 
-        if let Some(token) = auth_token {
-            req = req.bearer_auth(token);
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND && session.session_id.is_some() {
+            return Err(RequestError::SessionExpired);
+        }
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(RequestError::Unauthorized);
+        }
+        if !status.is_success() {
+            return Err(RequestError::Failed(format!(
+                "MCP server returned {}: {}",
+                status,
+                error_body(response).await
+            )));
+        }
+        let target = ReplyTarget {
+            url,
+            auth_token,
+            headers: &headers,
+        };
+        self.read_reply(response, &target, key, id, progress).await
+    }
+
+    /// Read the response to request `id` from a POST reply, either a JSON
+    /// body or an event stream. Streams that close early are resumed with
+    /// `Last-Event-ID` when the server numbered its events.
+    async fn read_reply(
+        &self,
+        response: reqwest::Response,
+        target: &ReplyTarget<'_>,
+        key: &str,
+        id: i64,
+        mut progress: ProgressSink<'_, '_>,
+    ) -> Result<JsonRpcResponse, RequestError> {
+        if response.status() == StatusCode::ACCEPTED {
+            return Err(RequestError::Failed(
+                "MCP server accepted the request without answering it".to_string(),
+            ));
+        }
+        if !is_event_stream(&response) {
+            let text = tokio::time::timeout(self.idle_timeout, response.text())
+                .await
+                .map_err(|_| "MCP server stopped responding".to_string())?
+                .map_err(|e| format!("Failed to read MCP response: {}", e))?;
+            let value: Value = serde_json::from_str(&text)
+                .map_err(|e| format!("Failed to parse MCP response: {}", e))?;
+            let messages = match value {
+                Value::Array(batch) => batch,
+                single => vec![single],
+            };
+            for message in messages {
+                if let Some(response) = self
+                    .handle_message(message, target, key, id, progress.as_deref_mut())
+                    .await
+                {
+                    return Ok(response);
+                }
+            }
+            return Err(RequestError::Failed(
+                "No result from MCP server".to_string(),
+            ));
+        }
+
+        let mut stream = EventStream::new(response);
+        let mut last_event_id: Option<String> = None;
+        let mut resumes = 0;
+        let deadline = Instant::now() + self.max_request_time;
+        loop {
+            if Instant::now() > deadline {
+                return Err(RequestError::Failed("MCP request timed out".to_string()));
+            }
+            match stream.next(self.idle_timeout).await? {
+                Some(event) => {
+                    if event.id.is_some() {
+                        last_event_id = event.id.clone();
+                    }
+                    if let Some(response) = self
+                        .handle_event(&event, target, key, id, progress.as_deref_mut())
+                        .await
+                    {
+                        return Ok(response);
+                    }
+                }
+                None => {
+                    let Some(last) = last_event_id.as_deref() else {
+                        return Err(RequestError::Failed(
+                            "MCP server closed the stream before responding".to_string(),
+                        ));
+                    };
+                    if resumes >= MAX_STREAM_RESUMES {
+                        return Err(RequestError::Failed(
+                            "MCP stream kept closing before the response".to_string(),
+                        ));
+                    }
+                    resumes += 1;
+                    debug!("Resuming MCP stream after event {}", last);
+                    stream = self.resume_stream(target, last).await?;
+                }
+            }
         }
+    }
 
+    async fn resume_stream(
+        &self,
+        target: &ReplyTarget<'_>,
+        last_event_id: &str,
+    ) -> Result<EventStream, RequestError> {
+        let mut req = self
+            .client
+            .get(target.url)
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .header("Last-Event-ID", last_event_id);
+        if let Some(token) = target.auth_token {
+            req = req.bearer_auth(token);
+        }
+        if let Some(session_id) = &target.headers.session_id {
+            req = req.header(SESSION_HEADER, session_id);
+        }
+        if let Some(version) = &target.headers.protocol_version {
+            req = req.header(PROTOCOL_HEADER, version);
+        }
         let response = req
-            .header("Accept", "application/json, text/event-stream")
             .send()
             .await
-            .map_err(|e| format!("Failed to connect to MCP server: {}", e))?;
+            .map_err(|e| format!("Failed to resume MCP stream: {}", e))?;
+        if response.status() == StatusCode::NOT_FOUND && target.headers.session_id.is_some() {
+            return Err(RequestError::SessionExpired);
+        }
+        if !response.status().is_success() || !is_event_stream(&response) {
+            return Err(RequestError::Failed(format!(
+                "MCP server could not resume the stream ({})",
+                response.status()
+            )));
+        }
+        Ok(EventStream::new(response))
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let error_text = response
-                .text()
+    async fn handle_event(
+        &self,
+        event: &SseEvent,
+        target: &ReplyTarget<'_>,
+        key: &str,
+        id: i64,
+        mut progress: ProgressSink<'_, '_>,
+    ) -> Option<JsonRpcResponse> {
+        if event.event != "message" || event.data.trim().is_empty() {
+            return None;
+        }
+        let value: Value = match serde_json::from_str(&event.data) {
+            Ok(value) => value,
+            Err(e) => {
+                debug!("Ignoring unparseable MCP event: {}", e);
+                return None;
+            }
+        };
+        let messages = match value {
+            Value::Array(batch) => batch,
+            single => vec![single],
+        };
+        for message in messages {
+            if let Some(response) = self
+                .handle_message(message, target, key, id, progress.as_deref_mut())
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("MCP server returned {}: {}", status, error_text));
+            {
+                return Some(response);
+            }
         }
+        None
+    }
 
-        // Check content type for SSE vs JSON
-        let content_type = response
-            .headers()
-            .get("content-type")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
+    /// Dispatch one incoming JSON-RPC message. Returns it when it is the
+    /// response we are waiting for.
+    async fn handle_message(
+        &self,
+        message: Value,
+        target: &ReplyTarget<'_>,
+        key: &str,
+        id: i64,
+        progress: ProgressSink<'_, '_>,
+    ) -> Option<JsonRpcResponse> {
+        match message.get("method").and_then(|m| m.as_str()) {
+            Some(method) if message.get("id").is_some() => {
+                self.answer_server_request(target, &message["id"], method)
+                    .await;
+                None
+            }
+            Some(method) => {
+                handle_notification(key, method, message.get("params"), id, progress);
+                None
+            }
+            None if id_matches(message.get("id"), id) => serde_json::from_value(message).ok(),
+            None => None,
+        }
+    }
 
-        if content_type.contains("text/event-stream") {
-            // Handle SSE response
-            self.parse_sse_response(response).await
+    /// Servers may ping us mid-stream; anything else we don't offer.
+    async fn answer_server_request(&self, target: &ReplyTarget<'_>, id: &Value, method: &str) {
+        let reply = if method == "ping" {
+            json!({ "jsonrpc": "2.0", "id": id, "result": {} })
         } else {
-            // Handle regular JSON response
-            response
-                .json::<JsonRpcResponse>()
-                .await
-                .map_err(|e| format!("Failed to parse MCP response: {}", e))
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {
+                    "code": -32601,
+                    "message": format!("Client does not support {}", method)
+                }
+            })
+        };
+        if let Err(e) = self
+            .post(target.url, target.auth_token, target.headers, &reply)
+            .send()
+            .await
+        {
+            debug!("Failed to answer MCP {} request: {}", method, e);
         }
     }
 
-    /// Parse Server-Sent Events response
-    async fn parse_sse_response(
+    // -- HTTP+SSE transport (2024-11-05) --
+
+    /// Open the event stream and wait for the endpoint to POST messages to.
+    async fn legacy_connect(
         &self,
-        response: reqwest::Response,
+        url: &str,
+        auth_token: Option<&str>,
+    ) -> Result<(EventStream, String), String> {
+        let mut req = self
+            .client
+            .get(url)
+            .header(reqwest::header::ACCEPT, "text/event-stream");
+        if let Some(token) = auth_token {
+            req = req.bearer_auth(token);
+        }
+        let response = req
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to MCP server: {}", e))?;
+        if !response.status().is_success() || !is_event_stream(&response) {
+            return Err(format!(
+                "MCP server returned {} for the event stream",
+                response.status()
+            ));
+        }
+
+        let mut stream = EventStream::new(response);
+        while let Some(event) = stream.next(self.idle_timeout).await? {
+            if event.event != "endpoint" {
+                continue;
+            }
+            let base = url::Url::parse(url).map_err(|e| format!("Invalid MCP URL: {}", e))?;
+            let endpoint = base
+                .join(event.data.trim())
+                .map_err(|e| format!("Invalid MCP endpoint: {}", e))?;
+            // The endpoint must not move our requests (and token) elsewhere.
+            if endpoint.origin() != base.origin() {
+                return Err("MCP server announced an endpoint on another origin".to_string());
+            }
+            return Ok((stream, endpoint.to_string()));
+        }
+        Err("MCP event stream closed before announcing an endpoint".to_string())
+    }
+
+    /// POST a request to the legacy endpoint and wait for its response on
+    /// the event stream.
+    #[allow(clippy::too_many_arguments)]
+    async fn legacy_exchange(
+        &self,
+        stream: &mut EventStream,
+        endpoint: &str,
+        auth_token: Option<&str>,
+        key: &str,
+        method: &str,
+        params: Option<Value>,
+        mut progress: ProgressSink<'_, '_>,
     ) -> Result<JsonRpcResponse, String> {
-        let text = response
-            .text()
+        let id = next_request_id();
+        let params = with_progress_token(params, id, progress.is_some());
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0",
+            id,
+            method: method.to_string(),
+            params,
+        };
+        let headers = SessionHeaders::default();
+        let response = self
+            .post(endpoint, auth_token, &headers, &request)
+            .send()
             .await
-            .map_err(|e| format!("Failed to read SSE response: {}", e))?;
+            .map_err(|e| format!("Failed to reach MCP endpoint: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "MCP server returned {}: {}",
+                response.status(),
+                error_body(response).await
+            ));
+        }
 
-        // Parse SSE format - look for data: lines
-        for line in text.lines() {
-            if let Some(data) = line.strip_prefix("data: ") {
-                if data.trim().is_empty() {
-                    continue;
-                }
-                // Try to parse as JSON-RPC response
-                if let Ok(response) = serde_json::from_str::<JsonRpcResponse>(data) {
-                    return Ok(response);
-                }
+        let target = ReplyTarget {
+            url: endpoint,
+            auth_token,
+            headers: &headers,
+        };
+        let deadline = Instant::now() + self.max_request_time;
+        while Instant::now() < deadline {
+            let Some(event) = stream.next(self.idle_timeout).await? else {
+                return Err("MCP event stream closed before responding".to_string());
+            };
+            if let Some(response) = self
+                .handle_event(&event, &target, key, id, progress.as_deref_mut())
+                .await
+            {
+                return Ok(response);
             }
         }
+        Err("MCP request timed out".to_string())
+    }
+
+    /// Connect and handshake on a fresh legacy stream.
+    async fn legacy_open(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+        key: &str,
+    ) -> Result<(EventStream, String, McpServerInfo), String> {
+        let (mut stream, endpoint) = self.legacy_connect(url, auth_token).await?;
+        let response = self
+            .legacy_exchange(
+                &mut stream,
+                &endpoint,
+                auth_token,
+                key,
+                "initialize",
+                Some(initialize_params()),
+                None,
+            )
+            .await?;
+        let info = parse_initialize_result(&into_result(response)?, McpTransport::LegacySse)?;
+        let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        if let Err(e) = self
+            .post(
+                &endpoint,
+                auth_token,
+                &SessionHeaders::default(),
+                &initialized,
+            )
+            .send()
+            .await
+        {
+            warn!("MCP initialized notification to {} failed: {}", endpoint, e);
+        }
+        Ok((stream, endpoint, info))
+    }
 
-        // If no valid JSON-RPC response found, try parsing the whole thing as JSON
-        serde_json::from_str::<JsonRpcResponse>(&text)
-            .map_err(|e| format!("Failed to parse SSE response: {}", e))
+    async fn legacy_initialize(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+    ) -> Result<McpServerInfo, String> {
+        let key = cache_key(url, auth_token);
+        let (_, _, info) = self.legacy_open(url, auth_token, &key).await?;
+        Ok(info)
     }
 
-    /// Send a notification (no response expected)
-    async fn send_notification(
+    /// Legacy servers tie state to the open stream, so each request gets
+    /// its own connection and handshake.
+    async fn legacy_request(
         &self,
         url: &str,
         auth_token: Option<&str>,
+        key: &str,
         method: &str,
-    ) -> Result<(), String> {
-        let notification = serde_json::json!({
-            "jsonrpc": "2.0",
-            "method": method
-        });
+        params: Option<Value>,
+        progress: ProgressSink<'_, '_>,
+    ) -> Result<JsonRpcResponse, String> {
+        let (mut stream, endpoint, _) = self.legacy_open(url, auth_token, key).await?;
+        self.legacy_exchange(
+            &mut stream,
+            &endpoint,
+            auth_token,
+            key,
+            method,
+            params,
+            progress,
+        )
+        .await
+    }
+}
 
-        let mut req = self.client.post(url).json(&notification);
+/// Ask for progress notifications by tagging the request with its own id.
+fn with_progress_token(params: Option<Value>, id: i64, wanted: bool) -> Option<Value> {
+    if !wanted {
+        return params;
+    }
+    let mut params = params.unwrap_or_else(|| json!({}));
+    if let Some(object) = params.as_object_mut() {
+        object.insert("_meta".to_string(), json!({ "progressToken": id }));
+    }
+    Some(params)
+}
 
-        if let Some(token) = auth_token {
-            req = req.bearer_auth(token);
+fn handle_notification(
+    key: &str,
+    method: &str,
+    params: Option<&Value>,
+    id: i64,
+    progress: ProgressSink<'_, '_>,
+) {
+    match method {
+        "notifications/progress" => {
+            let Some(params) = params else { return };
+            if !id_matches(params.get("progressToken"), id) {
+                return;
+            }
+            let update = McpProgress {
+                progress: params
+                    .get("progress")
+                    .and_then(|p| p.as_f64())
+                    .unwrap_or(0.0),
+                total: params.get("total").and_then(|t| t.as_f64()),
+                message: params
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .map(String::from),
+            };
+            debug!("MCP progress {:?}", update);
+            if let Some(sink) = progress {
+                sink(&update);
+            }
+        }
+        "notifications/tools/list_changed" => {
+            debug!("MCP server changed its tool list");
+            if !key.is_empty() {
+                invalidate_tools(key);
+            }
+        }
+        "notifications/message" => {
+            debug!("MCP server log: {:?}", params);
+        }
+        other => {
+            debug!("Ignoring MCP notification {}", other);
         }
-
-        let _ = req.send().await;
-        Ok(())
     }
 }
 
@@ -388,4 +1644,14 @@ mod tests {
         let json = serde_json::to_string(&request).unwrap();
         assert!(json.contains("\"params\""));
     }
+
+    #[test]
+    fn test_progress_token_is_request_id() {
+        let params = with_progress_token(Some(json!({ "name": "slow" })), 7, true).unwrap();
+        assert_eq!(params["_meta"]["progressToken"], 7);
+        assert_eq!(params["name"], "slow");
+        assert_eq!(with_progress_token(None, 7, false), None);
+        assert!(id_matches(Some(&json!("7")), 7));
+        assert!(!id_matches(Some(&json!(8)), 7));
+    }
 }
@@ -0,0 +1,670 @@
+//! OAuth 2.1 authorization for remote MCP servers.
+//!
+//! Follows the MCP authorization spec: the server's 401 challenge points at
+//! its protected resource metadata (RFC 9728), which names the authorization
+//! server; that server's metadata (RFC 8414 / OpenID discovery) gives the
+//! endpoints. Lightfriend registers itself as a public client (RFC 7591),
+//! runs the authorization code flow with PKCE and binds every token to the
+//! MCP server with the `resource` parameter (RFC 8707).
+//!
+//! Token persistence is behind [`OAuthTokenStore`] so the refresh logic can
+//! be exercised without a database.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use reqwest::{header, Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{debug, info};
+use url::Url;
+
+use crate::services::mcp_client::{McpTokenSource, LATEST_PROTOCOL_VERSION};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(15);
+/// Refresh this long before the access token actually expires.
+const EXPIRY_MARGIN_SECS: i64 = 60;
+const CLIENT_NAME: &str = "Lightfriend";
+
+/// One lock per token identity, so concurrent tool calls don't race to
+/// spend the same (possibly single-use) refresh token.
+static REFRESH_LOCKS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Endpoints discovered for an MCP server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuthMetadata {
+    /// Canonical resource identifier sent as `resource` (RFC 8707).
+    pub resource: String,
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub registration_endpoint: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// Everything needed to authorize and refresh against one MCP server.
+/// Stored encrypted alongside the server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpOAuthConfig {
+    pub resource: String,
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    /// `none`, `client_secret_post` or `client_secret_basic`.
+    #[serde(default)]
+    pub token_endpoint_auth_method: Option<String>,
+    pub redirect_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Unix seconds; `None` when the server didn't say.
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+impl OAuthTokens {
+    pub fn expires_soon(&self) -> bool {
+        self.expires_at
+            .map(|at| at - chrono::Utc::now().timestamp() < EXPIRY_MARGIN_SECS)
+            .unwrap_or(false)
+    }
+}
+
+/// Where a server's tokens live between requests.
+pub trait OAuthTokenStore: Send + Sync {
+    fn load(&self) -> Result<OAuthTokens, String>;
+    fn save(&self, tokens: &OAuthTokens) -> Result<(), String>;
+}
+
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    #[serde(default)]
+    resource: Option<String>,
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorizationServerMetadata {
+    #[serde(default)]
+    issuer: Option<String>,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    registration_endpoint: Option<String>,
+    #[serde(default)]
+    code_challenge_methods_supported: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+    #[serde(default)]
+    token_endpoint_auth_method: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+/// Parameters of a `WWW-Authenticate: Bearer ...` challenge, keys lowercased.
+pub fn parse_bearer_challenge(header_value: &str) -> Option<HashMap<String, String>> {
+    let rest = header_value.trim();
+    let (scheme, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    if !scheme.eq_ignore_ascii_case("bearer") {
+        return None;
+    }
+
+    let mut params = HashMap::new();
+    let mut chars = rest.chars().peekable();
+    loop {
+        while matches!(chars.peek(), Some(c) if *c == ',' || c.is_whitespace()) {
+            chars.next();
+        }
+        let key: String = chars
+            .by_ref()
+            .take_while(|c| *c != '=')
+            .collect::<String>()
+            .trim()
+            .to_ascii_lowercase();
+        if key.is_empty() {
+            break;
+        }
+        let value = if chars.peek() == Some(&'"') {
+            chars.next();
+            let mut value = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+            value
+        } else {
+            chars
+                .by_ref()
+                .take_while(|c| *c != ',')
+                .collect::<String>()
+                .trim()
+                .to_string()
+        };
+        params.insert(key, value);
+    }
+    Some(params)
+}
+
+/// The MCP URL as an RFC 8707 resource: no fragment, no trailing slash on a
+/// bare origin.
+fn canonical_resource(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    let s = url.to_string();
+    if url.path() == "/" && url.query().is_none() {
+        s.trim_end_matches('/').to_string()
+    } else {
+        s
+    }
+}
+
+fn origin(url: &Url) -> String {
+    url.origin().ascii_serialization()
+}
+
+/// `{origin}/.well-known/{name}{path}`, dropping the path when `with_path`
+/// is false or the path is just `/`.
+fn well_known(url: &Url, name: &str, with_path: bool) -> String {
+    let path = url.path().trim_end_matches('/');
+    if with_path && !path.is_empty() {
+        format!("{}/.well-known/{}{}", origin(url), name, path)
+    } else {
+        format!("{}/.well-known/{}", origin(url), name)
+    }
+}
+
+fn form_error(status: StatusCode, body: &str) -> String {
+    let detail = serde_json::from_str::<Value>(body)
+        .ok()
+        .map(|v| {
+            let error = v.get("error").and_then(|e| e.as_str()).unwrap_or("");
+            let description = v
+                .get("error_description")
+                .and_then(|e| e.as_str())
+                .unwrap_or("");
+            format!("{} {}", error, description).trim().to_string()
+        })
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| body.chars().take(200).collect());
+    format!("HTTP {}: {}", status, detail)
+}
+
+/// HTTP side of the OAuth flow. Every URL it fetches, including ones the
+/// servers hand back, is checked against private address ranges unless
+/// that check is switched off.
+#[derive(Clone)]
+pub struct McpOAuthClient {
+    http: Client,
+    allow_private_hosts: bool,
+}
+
+impl Default for McpOAuthClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpOAuthClient {
+    pub fn new() -> Self {
+        let http = Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            http,
+            allow_private_hosts: false,
+        }
+    }
+
+    /// Skip the private address check, for local mock servers.
+    pub fn allowing_private_hosts(mut self) -> Self {
+        self.allow_private_hosts = true;
+        self
+    }
+
+    async fn check_url(&self, url: &str) -> Result<(), String> {
+        let parsed = Url::parse(url).map_err(|_| format!("Invalid OAuth URL: {}", url))?;
+        if parsed.scheme() != "https" && parsed.scheme() != "http" {
+            return Err(format!("OAuth URL must be http(s): {}", url));
+        }
+        if self.allow_private_hosts {
+            return Ok(());
+        }
+        crate::handlers::mcp_handlers::validate_public_mcp_url(url).await
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Option<T> {
+        self.check_url(url).await.ok()?;
+        let response = self
+            .http
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            debug!("OAuth metadata {} returned {}", url, response.status());
+            return None;
+        }
+        response.json::<T>().await.ok()
+    }
+
+    /// Hit the MCP endpoint without a token and read the Bearer challenge.
+    async fn challenge(&self, mcp_url: &str) -> HashMap<String, String> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": LATEST_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "lightfriend", "version": env!("CARGO_PKG_VERSION")}
+            }
+        });
+        let response = match self
+            .http
+            .post(mcp_url)
+            .header(header::ACCEPT, "application/json, text/event-stream")
+            .json(&body)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                debug!("Unauthenticated MCP probe failed: {}", e);
+                return HashMap::new();
+            }
+        };
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return HashMap::new();
+        }
+        response
+            .headers()
+            .get_all(header::WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(parse_bearer_challenge)
+            .unwrap_or_default()
+    }
+
+    /// Find the authorization server protecting an MCP server.
+    pub async fn discover(&self, mcp_url: &str) -> Result<OAuthMetadata, String> {
+        self.check_url(mcp_url).await?;
+        let mcp = Url::parse(mcp_url).map_err(|_| "Invalid MCP URL".to_string())?;
+        let challenge = self.challenge(mcp_url).await;
+
+        let mut candidates = Vec::new();
+        if let Some(url) = challenge.get("resource_metadata") {
+            candidates.push(url.clone());
+        }
+        candidates.push(well_known(&mcp, "oauth-protected-resource", true));
+        candidates.push(well_known(&mcp, "oauth-protected-resource", false));
+        candidates.dedup();
+
+        let mut prm = None;
+        for url in &candidates {
+            if let Some(found) = self.get_json::<ProtectedResourceMetadata>(url).await {
+                prm = Some(found);
+                break;
+            }
+        }
+
+        let default_resource = canonical_resource(&mcp);
+        let (resource, issuer, scope) = match prm {
+            Some(prm) => {
+                let issuer = prm.authorization_servers.first().cloned().ok_or_else(|| {
+                    "Protected resource metadata lists no authorization server".to_string()
+                })?;
+                let resource = prm.resource.unwrap_or(default_resource);
+                let matches = Url::parse(&resource)
+                    .map(|r| origin(&r) == origin(&mcp))
+                    .unwrap_or(false);
+                if !matches {
+                    return Err(format!(
+                        "Protected resource metadata is for {}, not this server",
+                        resource
+                    ));
+                }
+                let scope = challenge.get("scope").cloned().or_else(|| {
+                    (!prm.scopes_supported.is_empty()).then(|| prm.scopes_supported.join(" "))
+                });
+                (resource, issuer, scope)
+            }
+            // Servers predating RFC 9728 host the authorization server themselves.
+            None => (
+                default_resource,
+                origin(&mcp),
+                challenge.get("scope").cloned(),
+            ),
+        };
+
+        let issuer_url =
+            Url::parse(&issuer).map_err(|_| format!("Invalid authorization server: {}", issuer))?;
+        let mut metadata_urls = vec![
+            well_known(&issuer_url, "oauth-authorization-server", true),
+            well_known(&issuer_url, "openid-configuration", true),
+        ];
+        let issuer_path = issuer_url.path().trim_end_matches('/');
+        if !issuer_path.is_empty() {
+            metadata_urls.push(format!(
+                "{}{}/.well-known/openid-configuration",
+                origin(&issuer_url),
+                issuer_path
+            ));
+        }
+
+        let mut as_metadata = None;
+        for url in &metadata_urls {
+            if let Some(found) = self.get_json::<AuthorizationServerMetadata>(url).await {
+                as_metadata = Some(found);
+                break;
+            }
+        }
+        let as_metadata = as_metadata.ok_or_else(|| {
+            format!(
+                "Could not find authorization server metadata for {}",
+                issuer
+            )
+        })?;
+
+        if let Some(methods) = &as_metadata.code_challenge_methods_supported {
+            if !methods.iter().any(|m| m == "S256") {
+                return Err("Authorization server does not support PKCE (S256)".to_string());
+            }
+        }
+
+        Ok(OAuthMetadata {
+            resource,
+            issuer: as_metadata.issuer.unwrap_or(issuer),
+            authorization_endpoint: as_metadata.authorization_endpoint,
+            token_endpoint: as_metadata.token_endpoint,
+            registration_endpoint: as_metadata.registration_endpoint,
+            scope,
+        })
+    }
+
+    /// Register Lightfriend as a public client (RFC 7591).
+    pub async fn register_client(
+        &self,
+        metadata: &OAuthMetadata,
+        redirect_uri: &str,
+    ) -> Result<McpOAuthConfig, String> {
+        let endpoint = metadata.registration_endpoint.as_deref().ok_or_else(|| {
+            "Authorization server does not support dynamic client registration".to_string()
+        })?;
+        self.check_url(endpoint).await?;
+
+        let mut body = json!({
+            "client_name": CLIENT_NAME,
+            "redirect_uris": [redirect_uri],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "response_types": ["code"],
+            "token_endpoint_auth_method": "none",
+        });
+        if let Some(scope) = &metadata.scope {
+            body["scope"] = json!(scope);
+        }
+
+        let response = self
+            .http
+            .post(endpoint)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Client registration failed: {}", e))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!(
+                "Client registration failed: {}",
+                form_error(status, &text)
+            ));
+        }
+        let registration: ClientRegistration = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid client registration response: {}", e))?;
+
+        Ok(McpOAuthConfig {
+            resource: metadata.resource.clone(),
+            issuer: metadata.issuer.clone(),
+            authorization_endpoint: metadata.authorization_endpoint.clone(),
+            token_endpoint: metadata.token_endpoint.clone(),
+            scope: metadata.scope.clone(),
+            client_id: registration.client_id,
+            client_secret: registration.client_secret,
+            token_endpoint_auth_method: registration.token_endpoint_auth_method,
+            redirect_uri: redirect_uri.to_string(),
+        })
+    }
+
+    /// Discover and register in one go.
+    pub async fn prepare(
+        &self,
+        mcp_url: &str,
+        redirect_uri: &str,
+    ) -> Result<McpOAuthConfig, String> {
+        let metadata = self.discover(mcp_url).await?;
+        info!(
+            "MCP server {} is protected by {}",
+            metadata.resource, metadata.issuer
+        );
+        self.register_client(&metadata, redirect_uri).await
+    }
+
+    pub async fn exchange_code(
+        &self,
+        config: &McpOAuthConfig,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OAuthTokens, String> {
+        self.token_request(
+            config,
+            vec![
+                ("grant_type", "authorization_code".to_string()),
+                ("code", code.to_string()),
+                ("redirect_uri", config.redirect_uri.clone()),
+                ("code_verifier", code_verifier.to_string()),
+            ],
+            None,
+        )
+        .await
+    }
+
+    /// Refresh, keeping the old refresh token when the server doesn't rotate it.
+    pub async fn refresh(
+        &self,
+        config: &McpOAuthConfig,
+        refresh_token: &str,
+    ) -> Result<OAuthTokens, String> {
+        self.token_request(
+            config,
+            vec![
+                ("grant_type", "refresh_token".to_string()),
+                ("refresh_token", refresh_token.to_string()),
+            ],
+            Some(refresh_token),
+        )
+        .await
+    }
+
+    async fn token_request(
+        &self,
+        config: &McpOAuthConfig,
+        mut form: Vec<(&str, String)>,
+        previous_refresh_token: Option<&str>,
+    ) -> Result<OAuthTokens, String> {
+        self.check_url(&config.token_endpoint).await?;
+        form.push(("resource", config.resource.clone()));
+
+        let basic = config.client_secret.is_some()
+            && config.token_endpoint_auth_method.as_deref() != Some("client_secret_post")
+            && config.token_endpoint_auth_method.as_deref() != Some("none");
+        let mut request = self
+            .http
+            .post(&config.token_endpoint)
+            .header(header::ACCEPT, "application/json");
+        if basic {
+            request = request.basic_auth(&config.client_id, config.client_secret.as_deref());
+        } else {
+            form.push(("client_id", config.client_id.clone()));
+            if let Some(secret) = &config.client_secret {
+                form.push(("client_secret", secret.clone()));
+            }
+        }
+
+        let response = request
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| format!("Token request failed: {}", e))?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!(
+                "Token request failed: {}",
+                form_error(status, &text)
+            ));
+        }
+        let tokens: TokenResponse =
+            serde_json::from_str(&text).map_err(|e| format!("Invalid token response: {}", e))?;
+
+        Ok(OAuthTokens {
+            access_token: tokens.access_token,
+            refresh_token: tokens
+                .refresh_token
+                .or_else(|| previous_refresh_token.map(String::from)),
+            expires_at: tokens
+                .expires_in
+                .map(|secs| chrono::Utc::now().timestamp() + secs),
+        })
+    }
+}
+
+/// Builds the browser URL for the authorization code + PKCE flow.
+pub fn authorization_url(
+    config: &McpOAuthConfig,
+    state: &str,
+    code_challenge: &str,
+) -> Result<String, String> {
+    let mut url = Url::parse(&config.authorization_endpoint)
+        .map_err(|_| "Invalid authorization endpoint".to_string())?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", &config.redirect_uri)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", state)
+            .append_pair("resource", &config.resource);
+        if let Some(scope) = &config.scope {
+            query.append_pair("scope", scope);
+        }
+    }
+    Ok(url.to_string())
+}
+
+/// Hands the MCP client a fresh access token, refreshing through the
+/// authorization server when the stored one is expiring or was rejected.
+pub struct OAuthTokenSource<S> {
+    oauth: McpOAuthClient,
+    config: McpOAuthConfig,
+    store: S,
+    identity: String,
+    last_token: Mutex<Option<String>>,
+}
+
+impl<S: OAuthTokenStore> OAuthTokenSource<S> {
+    pub fn new(
+        oauth: McpOAuthClient,
+        config: McpOAuthConfig,
+        store: S,
+        identity: impl Into<String>,
+    ) -> Self {
+        Self {
+            oauth,
+            config,
+            store,
+            identity: identity.into(),
+            last_token: Mutex::new(None),
+        }
+    }
+
+    fn refresh_lock(&self) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = REFRESH_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(self.identity.clone()).or_default().clone()
+    }
+
+    fn remember(&self, token: &str) {
+        if let Ok(mut last) = self.last_token.lock() {
+            *last = Some(token.to_string());
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: OAuthTokenStore> McpTokenSource for OAuthTokenSource<S> {
+    fn identity(&self) -> String {
+        self.identity.clone()
+    }
+
+    async fn access_token(&self, force_refresh: bool) -> Result<String, String> {
+        let lock = self.refresh_lock();
+        let _guard = lock.lock().await;
+
+        let tokens = self.store.load()?;
+        let rejected = self.last_token.lock().ok().and_then(|last| last.clone());
+        // Someone else already refreshed past the token that was rejected.
+        let already_rotated = force_refresh
+            && rejected
+                .as_deref()
+                .is_some_and(|rejected| rejected != tokens.access_token);
+        if already_rotated || (!force_refresh && !tokens.expires_soon()) {
+            self.remember(&tokens.access_token);
+            return Ok(tokens.access_token);
+        }
+
+        let refresh_token = tokens.refresh_token.as_deref().ok_or_else(|| {
+            "MCP authorization expired and cannot be refreshed. Reconnect the server.".to_string()
+        })?;
+        let fresh = self
+            .oauth
+            .refresh(&self.config, refresh_token)
+            .await
+            .map_err(|e| format!("{}. Reconnect the server.", e))?;
+        self.store.save(&fresh)?;
+        debug!("Refreshed OAuth token for {}", self.identity);
+        self.remember(&fresh.access_token);
+        Ok(fresh.access_token)
+    }
+}
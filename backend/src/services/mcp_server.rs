@@ -0,0 +1,290 @@
+//! Lightfriend as an MCP server.
+//!
+//! Desktop AI clients reach `POST /api/mcp` over the Streamable HTTP
+//! transport with an `mcp_access_tokens` bearer. The server is stateless: it
+//! issues no `Mcp-Session-Id`, answers every request with a single JSON body
+//! and never opens a server-initiated stream.
+//!
+//! Only the tools in `MCP_SERVER_TOOLS` exist, and a token sees just the ones
+//! on its allow-list; asking for any other name gets the same "unknown tool"
+//! error. Reminders and weather run through the assistant's own
+//! `ToolRegistry` handlers. Events and self-SMS are native tools with the
+//! same narrow shape as the local agent API: event descriptions and times
+//! only, and texts that can only go to the token owner's number.
+
+use crate::models::mcp_models::{McpAccessToken, McpServerTool};
+use crate::tools::registry::{ToolContext, ToolResult};
+use crate::{AppState, UserCoreOps};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Streamable HTTP versions we serve, newest first. The older HTTP+SSE
+/// transport (2024-11-05) isn't offered.
+pub const SERVER_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26"];
+
+const MAX_EVENTS: usize = 50;
+const MAX_SELF_SMS_CHARS: usize = 320;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+const INSTRUCTIONS: &str = "Lightfriend is the user's assistant for dumbphones. \
+These tools act on the user's own account: reminders and texts are delivered \
+to the user's phone. Ask before texting the user unless they asked for it.";
+
+/// Handles one JSON-RPC message. Returns the response to send, or `None` for
+/// notifications and client responses, which get `202 Accepted`.
+pub async fn handle_message(
+    state: &Arc<AppState>,
+    token: &McpAccessToken,
+    message: Value,
+) -> Option<Value> {
+    let Some(object) = message.as_object() else {
+        return Some(error_response(
+            Value::Null,
+            INVALID_REQUEST,
+            "Batches are not supported",
+        ));
+    };
+    let id = object.get("id").cloned();
+    let Some(method) = object.get("method").and_then(Value::as_str) else {
+        // A response to a server request; we never send any.
+        return None;
+    };
+    if object.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Some(error_response(
+            id.unwrap_or(Value::Null),
+            INVALID_REQUEST,
+            "Invalid request",
+        ));
+    }
+    let id = id?;
+    let params = object.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({
+            "tools": token.tools().into_iter().map(|tool| tool_definition(state, tool)).collect::<Vec<_>>(),
+        })),
+        "tools/call" => call_tool(state, token, &params).await,
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    };
+    Some(match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err((code, message)) => error_response(id, code, &message),
+    })
+}
+
+/// The JSON-RPC error for a body that isn't JSON.
+pub fn parse_error() -> Value {
+    error_response(Value::Null, PARSE_ERROR, "Parse error")
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message},
+    })
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|version| SERVER_PROTOCOL_VERSIONS.contains(version))
+        .unwrap_or(SERVER_PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": {"tools": {"listChanged": false}},
+        "serverInfo": {"name": "lightfriend", "version": env!("CARGO_PKG_VERSION")},
+        "instructions": INSTRUCTIONS,
+    })
+}
+
+fn tool_definition(state: &Arc<AppState>, tool: &McpServerTool) -> Value {
+    let (description, input_schema) = match tool.name {
+        "query_events" => (
+            "List the user's upcoming reminders and tracked events: id, description and times (Unix seconds).".to_string(),
+            json!({"type": "object", "properties": {}}),
+        ),
+        "send_sms_to_self" => (
+            "Send a short SMS to the user's own phone number. It can't be sent anywhere else.".to_string(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "message": {
+                        "type": "string",
+                        "description": "Text to send, at most 320 characters.",
+                        "maxLength": MAX_SELF_SMS_CHARS,
+                    }
+                },
+                "required": ["message"],
+            }),
+        ),
+        name => match state.tool_registry.get(name) {
+            Some(handler) => {
+                let function = handler.definition().function;
+                let mut schema = serde_json::to_value(&function.parameters)
+                    .unwrap_or_else(|_| json!({"type": "object"}));
+                strip_nulls(&mut schema);
+                (function.description.unwrap_or_default(), schema)
+            }
+            None => (tool.description.to_string(), json!({"type": "object"})),
+        },
+    };
+    json!({
+        "name": tool.name,
+        "description": description,
+        "inputSchema": input_schema,
+    })
+}
+
+/// Registry definitions serialize unset schema fields as null, which some
+/// MCP clients reject.
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+async fn call_tool(
+    state: &Arc<AppState>,
+    token: &McpAccessToken,
+    params: &Value,
+) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+    if !token.allows(name) {
+        return Err((INVALID_PARAMS, format!("Unknown tool: {}", name)));
+    }
+    let arguments = match params.get("arguments") {
+        None | Some(Value::Null) => json!({}),
+        Some(arguments @ Value::Object(_)) => arguments.clone(),
+        Some(_) => {
+            return Err((
+                INVALID_PARAMS,
+                "Tool arguments must be an object".to_string(),
+            ))
+        }
+    };
+
+    let outcome = match name {
+        "query_events" => query_events(state, token.user_id),
+        "send_sms_to_self" => send_sms_to_self(state, token, &arguments).await,
+        _ => run_registry_tool(state, token.user_id, name, &arguments).await,
+    };
+    tracing::info!(
+        token_id = token.id,
+        tool = name,
+        ok = outcome.is_ok(),
+        "MCP tool call"
+    );
+    Ok(match outcome {
+        Ok(text) => json!({"content": [{"type": "text", "text": text}], "isError": false}),
+        Err(text) => json!({"content": [{"type": "text", "text": text}], "isError": true}),
+    })
+}
+
+fn query_events(state: &Arc<AppState>, user_id: i32) -> Result<String, String> {
+    let events = state
+        .ontology_repository
+        .get_active_events(user_id)
+        .map_err(|e| {
+            tracing::error!(error = %e, "MCP event read failed");
+            "Events are unavailable right now.".to_string()
+        })?;
+    let events = events
+        .into_iter()
+        .take(MAX_EVENTS)
+        .map(|event| {
+            json!({
+                "id": event.id,
+                "description": event.description,
+                "remind_at": event.remind_at,
+                "due_at": event.due_at,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(json!({ "events": events }).to_string())
+}
+
+async fn send_sms_to_self(
+    state: &Arc<AppState>,
+    token: &McpAccessToken,
+    arguments: &Value,
+) -> Result<String, String> {
+    let message = arguments
+        .get("message")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or_default();
+    if message.is_empty()
+        || message.chars().count() > MAX_SELF_SMS_CHARS
+        || message.chars().any(char::is_control)
+    {
+        return Err(format!(
+            "message must be 1 to {} characters without control characters",
+            MAX_SELF_SMS_CHARS
+        ));
+    }
+    if crate::proactive::utils::send_notification(
+        state,
+        token.user_id,
+        message,
+        "mcp_sms".to_string(),
+        None,
+    )
+    .await
+    {
+        Ok("Sent.".to_string())
+    } else {
+        tracing::warn!(token_id = token.id, "MCP self SMS delivery failed");
+        Err("The SMS could not be sent.".to_string())
+    }
+}
+
+async fn run_registry_tool(
+    state: &Arc<AppState>,
+    user_id: i32,
+    name: &str,
+    arguments: &Value,
+) -> Result<String, String> {
+    let handler = state
+        .tool_registry
+        .get(name)
+        .ok_or_else(|| format!("Unknown tool: {}", name))?;
+    let user = match state.user_core.find_by_id(user_id) {
+        Ok(Some(user)) => user,
+        _ => return Err("Your account is unavailable right now.".to_string()),
+    };
+    let arguments = arguments.to_string();
+    let ctx = ToolContext {
+        state,
+        user: &user,
+        user_id,
+        arguments: &arguments,
+        image_url: None,
+        tool_call_id: format!("mcp_{}", uuid::Uuid::new_v4().simple()),
+        user_given_info: "",
+        current_time: chrono::Utc::now().timestamp() as i32,
+        skip_sms: true,
+        tools: None,
+        completion_messages: None,
+        assistant_content: None,
+        tool_call: None,
+    };
+    match handler.execute(ctx).await? {
+        ToolResult::Answer(answer) | ToolResult::AnswerWithTask { answer, .. } => Ok(answer),
+        ToolResult::EarlyReturn { response, .. } => Ok(response.message),
+    }
+}
@@ -0,0 +1,538 @@
+//! Outgoing media for bridged chats: classify, validate, thumbnail, build
+//! the Matrix event.
+//!
+//! `send_chat_message` can forward the photo on the user's current MMS or an
+//! attachment someone sent them in another bridged chat. Both arrive here as
+//! raw bytes plus a MIME type, and before anything is uploaded we:
+//!   1. classify the media into the msgtype bridges expect: `m.image` for
+//!      photos they render inline, `m.audio` flagged as a voice note for
+//!      Ogg/Opus, `m.video`, and `m.file` for everything else,
+//!   2. check it against the target network's size limits and file support,
+//!      so the user hears "too large" in the confirmation SMS instead of a
+//!      bridge error a minute later,
+//!   3. read image dimensions and render a small JPEG thumbnail, and the
+//!      duration of voice notes, which WhatsApp and Signal show on the
+//!      bubble.
+//!
+//! Attachments travel through the send queue as `data:` URLs, like inbound
+//! MMS photos. A filename rides along as a `name=` parameter
+//! (`data:application/pdf;name=report.pdf;base64,...`).
+
+use std::io::Cursor;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+const MB: usize = 1024 * 1024;
+
+/// Longest edge of the thumbnail attached to outgoing photos.
+pub const THUMBNAIL_EDGE: u32 = 320;
+
+const THUMBNAIL_QUALITY: u8 = 75;
+
+/// Decode limits for thumbnailing. A photo past them still goes out, just
+/// without a thumbnail.
+const THUMBNAIL_MAX_SOURCE_EDGE: u32 = 12_000;
+const THUMBNAIL_MAX_DECODE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Photo formats every bridge sends as a native photo. Other images (HEIC,
+/// TIFF, SVG) go out as files.
+const INLINE_IMAGE_TYPES: &[&str] = &["image/jpeg", "image/png", "image/gif", "image/webp"];
+
+/// Executables are refused by WhatsApp and flagged by the rest; never
+/// forward them.
+const BLOCKED_TYPES: &[&str] = &[
+    "application/x-msdownload",
+    "application/x-msdos-program",
+    "application/x-msi",
+    "application/vnd.microsoft.portable-executable",
+    "application/x-executable",
+    "application/x-sh",
+    "application/x-bat",
+    "application/vnd.android.package-archive",
+];
+
+#[derive(Debug, Error)]
+pub enum OutgoingMediaError {
+    #[error("The attachment is empty")]
+    Empty,
+
+    #[error(
+        "{network} accepts {} up to {}; this one is {}",
+        .kind.plural(),
+        format_size(*.limit),
+        format_size(*.size)
+    )]
+    TooLarge {
+        network: String,
+        kind: MediaKind,
+        size: usize,
+        limit: usize,
+    },
+
+    #[error("{0}")]
+    Unsupported(String),
+
+    #[error("Failed to read image: {0}")]
+    Decode(String),
+}
+
+/// How an attachment is sent, one per Matrix msgtype (voice notes are
+/// `m.audio` with the MSC3245 voice flag).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    Voice,
+    Audio,
+    Video,
+    File,
+}
+
+impl MediaKind {
+    pub fn msgtype(self) -> &'static str {
+        match self {
+            MediaKind::Image => "m.image",
+            MediaKind::Voice | MediaKind::Audio => "m.audio",
+            MediaKind::Video => "m.video",
+            MediaKind::File => "m.file",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MediaKind::Image => "photo",
+            MediaKind::Voice => "voice note",
+            MediaKind::Audio => "audio",
+            MediaKind::Video => "video",
+            MediaKind::File => "file",
+        }
+    }
+
+    fn plural(self) -> &'static str {
+        match self {
+            MediaKind::Image => "photos",
+            MediaKind::Voice => "voice notes",
+            MediaKind::Audio => "audio files",
+            MediaKind::Video => "videos",
+            MediaKind::File => "files",
+        }
+    }
+}
+
+/// Pick the msgtype for a MIME type.
+pub fn classify(mimetype: &str) -> MediaKind {
+    let mimetype = mimetype.to_ascii_lowercase();
+    if INLINE_IMAGE_TYPES.contains(&mimetype.as_str()) {
+        MediaKind::Image
+    } else if mimetype == "audio/ogg" || mimetype == "audio/opus" {
+        MediaKind::Voice
+    } else if mimetype.starts_with("audio/") {
+        MediaKind::Audio
+    } else if mimetype.starts_with("video/") {
+        MediaKind::Video
+    } else {
+        MediaKind::File
+    }
+}
+
+/// Largest attachment each network takes, in bytes. `file` is `None` where
+/// the network has no documents at all (Instagram and Google Messages take
+/// photos, videos and voice notes only).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkMediaLimits {
+    pub image: usize,
+    pub audio: usize,
+    pub video: usize,
+    pub file: Option<usize>,
+}
+
+impl NetworkMediaLimits {
+    fn for_kind(&self, kind: MediaKind) -> Option<usize> {
+        match kind {
+            MediaKind::Image => Some(self.image),
+            MediaKind::Voice | MediaKind::Audio => Some(self.audio),
+            MediaKind::Video => Some(self.video),
+            MediaKind::File => self.file,
+        }
+    }
+}
+
+pub fn media_limits(service: &str) -> NetworkMediaLimits {
+    match service {
+        "whatsapp" => NetworkMediaLimits {
+            image: 16 * MB,
+            audio: 16 * MB,
+            video: 16 * MB,
+            file: Some(100 * MB),
+        },
+        "telegram" => NetworkMediaLimits {
+            image: 10 * MB,
+            audio: 50 * MB,
+            video: 50 * MB,
+            file: Some(50 * MB),
+        },
+        "signal" | "slack" => NetworkMediaLimits {
+            image: 100 * MB,
+            audio: 100 * MB,
+            video: 100 * MB,
+            file: Some(100 * MB),
+        },
+        "discord" => NetworkMediaLimits {
+            image: 10 * MB,
+            audio: 10 * MB,
+            video: 10 * MB,
+            file: Some(10 * MB),
+        },
+        "messenger" => NetworkMediaLimits {
+            image: 25 * MB,
+            audio: 25 * MB,
+            video: 25 * MB,
+            file: Some(25 * MB),
+        },
+        "instagram" => NetworkMediaLimits {
+            image: 8 * MB,
+            audio: 25 * MB,
+            video: 25 * MB,
+            file: None,
+        },
+        "gmessages" => NetworkMediaLimits {
+            image: 10 * MB,
+            audio: 10 * MB,
+            video: 10 * MB,
+            file: None,
+        },
+        _ => NetworkMediaLimits {
+            image: 20 * MB,
+            audio: 20 * MB,
+            video: 20 * MB,
+            file: Some(20 * MB),
+        },
+    }
+}
+
+/// Check an attachment's type and size against the target network. Works
+/// from the size an event declares, so oversized media is refused before it
+/// is downloaded.
+pub fn check_limits(
+    service: &str,
+    mimetype: &str,
+    size: usize,
+) -> Result<MediaKind, OutgoingMediaError> {
+    let mimetype = normalize_mimetype(mimetype);
+    if BLOCKED_TYPES.contains(&mimetype.as_str()) {
+        return Err(OutgoingMediaError::Unsupported(
+            "Executable files can't be forwarded".to_string(),
+        ));
+    }
+    let kind = classify(&mimetype);
+    let Some(limit) = media_limits(service).for_kind(kind) else {
+        return Err(OutgoingMediaError::Unsupported(format!(
+            "{} only takes photos, videos and voice notes, not {} files",
+            network_name(service),
+            mimetype
+        )));
+    };
+    if size > limit {
+        return Err(OutgoingMediaError::TooLarge {
+            network: network_name(service),
+            kind,
+            size,
+            limit,
+        });
+    }
+    Ok(kind)
+}
+
+fn normalize_mimetype(mimetype: &str) -> String {
+    mimetype
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn network_name(service: &str) -> String {
+    match service {
+        "whatsapp" => "WhatsApp".to_string(),
+        "telegram" => "Telegram".to_string(),
+        "signal" => "Signal".to_string(),
+        other => crate::utils::bridgev2_provisioning::network(other)
+            .map(|n| n.name.to_string())
+            .unwrap_or_else(|| other.to_string()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaThumbnail {
+    pub jpeg: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// One attachment, validated for the network it is going to.
+#[derive(Debug, Clone)]
+pub struct OutgoingMedia {
+    pub kind: MediaKind,
+    pub mimetype: String,
+    pub filename: String,
+    pub bytes: Vec<u8>,
+    pub size: usize,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub duration_ms: Option<u64>,
+    pub thumbnail: Option<MediaThumbnail>,
+}
+
+impl OutgoingMedia {
+    /// Classify and validate without rendering a thumbnail; enough for the
+    /// confirmation message.
+    pub fn inspect(
+        service: &str,
+        mimetype: &str,
+        bytes: Vec<u8>,
+        filename: Option<&str>,
+    ) -> Result<Self, OutgoingMediaError> {
+        if bytes.is_empty() {
+            return Err(OutgoingMediaError::Empty);
+        }
+        let mimetype = normalize_mimetype(mimetype);
+        let kind = check_limits(service, &mimetype, bytes.len())?;
+
+        let (width, height) = if kind == MediaKind::Image {
+            let (w, h) = image::io::Reader::new(Cursor::new(&bytes))
+                .with_guessed_format()
+                .map_err(|e| OutgoingMediaError::Decode(e.to_string()))?
+                .into_dimensions()
+                .map_err(|e| OutgoingMediaError::Decode(e.to_string()))?;
+            (Some(w), Some(h))
+        } else {
+            (None, None)
+        };
+        let duration_ms = if kind == MediaKind::Voice {
+            ogg_opus_duration_ms(&bytes)
+        } else {
+            None
+        };
+
+        let filename = filename
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| default_filename(kind, &mimetype));
+
+        Ok(Self {
+            kind,
+            mimetype,
+            filename,
+            size: bytes.len(),
+            bytes,
+            width,
+            height,
+            duration_ms,
+            thumbnail: None,
+        })
+    }
+
+    /// Attach a JPEG thumbnail to photos larger than `THUMBNAIL_EDGE`.
+    /// Best-effort: a photo whose thumbnail fails still goes out.
+    pub fn with_thumbnail(mut self) -> Self {
+        if self.kind != MediaKind::Image {
+            return self;
+        }
+        match render_thumbnail(&self.bytes) {
+            Ok(thumbnail) => self.thumbnail = thumbnail,
+            Err(e) => tracing::warn!("No thumbnail for outgoing {}: {}", self.mimetype, e),
+        }
+        self
+    }
+
+    /// Voice notes have no caption on WhatsApp or Signal, so the caption is
+    /// sent as its own text message first.
+    pub fn carries_caption(&self) -> bool {
+        self.kind != MediaKind::Voice
+    }
+
+    /// "photo (1.2 MB)", "voice note (0:12, 48 KB)", "file 'report.pdf' (340 KB)".
+    pub fn describe(&self) -> String {
+        match self.kind {
+            MediaKind::File => format!("file '{}' ({})", self.filename, format_size(self.size)),
+            MediaKind::Voice | MediaKind::Audio | MediaKind::Video => match self.duration_ms {
+                Some(ms) => format!(
+                    "{} ({}:{:02}, {})",
+                    self.kind.label(),
+                    ms / 60_000,
+                    (ms / 1000) % 60,
+                    format_size(self.size)
+                ),
+                None => format!("{} ({})", self.kind.label(), format_size(self.size)),
+            },
+            MediaKind::Image => format!("photo ({})", format_size(self.size)),
+        }
+    }
+
+    /// The `m.room.message` content for the uploaded media. With a caption
+    /// the body carries it and `filename` names the file, as bridges expect
+    /// for captioned media.
+    pub fn event_content(&self, caption: &str, url: &str, thumbnail_url: Option<&str>) -> Value {
+        let caption = caption.trim();
+        let body = if caption.is_empty() || !self.carries_caption() {
+            self.filename.clone()
+        } else {
+            caption.to_string()
+        };
+
+        let mut info = Map::new();
+        info.insert("mimetype".into(), json!(self.mimetype));
+        info.insert("size".into(), json!(self.size));
+        if let (Some(w), Some(h)) = (self.width, self.height) {
+            info.insert("w".into(), json!(w));
+            info.insert("h".into(), json!(h));
+        }
+        if let Some(ms) = self.duration_ms {
+            info.insert("duration".into(), json!(ms));
+        }
+        if let (Some(thumb), Some(thumb_url)) = (&self.thumbnail, thumbnail_url) {
+            info.insert("thumbnail_url".into(), json!(thumb_url));
+            info.insert(
+                "thumbnail_info".into(),
+                json!({
+                    "mimetype": "image/jpeg",
+                    "size": thumb.jpeg.len(),
+                    "w": thumb.width,
+                    "h": thumb.height,
+                }),
+            );
+        }
+
+        let mut content = json!({
+            "msgtype": self.kind.msgtype(),
+            "body": body,
+            "filename": self.filename,
+            "url": url,
+            "info": info,
+        });
+        if self.kind == MediaKind::Voice {
+            content["org.matrix.msc1767.audio"] = match self.duration_ms {
+                Some(ms) => json!({ "duration": ms }),
+                None => json!({}),
+            };
+            content["org.matrix.msc3245.voice"] = json!({});
+        }
+        content
+    }
+}
+
+fn render_thumbnail(bytes: &[u8]) -> Result<Option<MediaThumbnail>, OutgoingMediaError> {
+    let mut reader = image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| OutgoingMediaError::Decode(e.to_string()))?;
+    let mut limits = image::io::Limits::default();
+    limits.max_image_width = Some(THUMBNAIL_MAX_SOURCE_EDGE);
+    limits.max_image_height = Some(THUMBNAIL_MAX_SOURCE_EDGE);
+    limits.max_alloc = Some(THUMBNAIL_MAX_DECODE_BYTES);
+    reader.limits(limits);
+    let img = reader
+        .decode()
+        .map_err(|e| OutgoingMediaError::Decode(e.to_string()))?;
+    if img.width() <= THUMBNAIL_EDGE && img.height() <= THUMBNAIL_EDGE {
+        return Ok(None);
+    }
+    let rgb = img.thumbnail(THUMBNAIL_EDGE, THUMBNAIL_EDGE).to_rgb8();
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, THUMBNAIL_QUALITY)
+        .encode(
+            rgb.as_raw(),
+            rgb.width(),
+            rgb.height(),
+            image::ColorType::Rgb8,
+        )
+        .map_err(|e| OutgoingMediaError::Decode(e.to_string()))?;
+    Ok(Some(MediaThumbnail {
+        jpeg,
+        width: rgb.width(),
+        height: rgb.height(),
+    }))
+}
+
+fn default_filename(kind: MediaKind, mimetype: &str) -> String {
+    let stem = match kind {
+        MediaKind::Image => "photo",
+        MediaKind::Voice => "voice",
+        MediaKind::Audio => "audio",
+        MediaKind::Video => "video",
+        MediaKind::File => "file",
+    };
+    let ext = match mimetype {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "audio/ogg" | "audio/opus" => "ogg",
+        "audio/mpeg" => "mp3",
+        "audio/mp4" | "audio/aac" => "m4a",
+        "video/mp4" => "mp4",
+        "application/pdf" => "pdf",
+        other => mime_guess::get_mime_extensions_str(other)
+            .and_then(|exts| exts.first().copied())
+            .unwrap_or("bin"),
+    };
+    format!("{}.{}", stem, ext)
+}
+
+/// Split a content type with an optional `name=` parameter (as carried in
+/// our `data:` URLs) into the bare MIME type and the filename.
+pub fn parse_media_type(content_type: &str) -> (String, Option<String>) {
+    let mut parts = content_type.split(';');
+    let mimetype = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let filename = parts.find_map(|p| {
+        let (key, value) = p.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case("name") {
+            urlencoding::decode(value.trim())
+                .ok()
+                .map(|v| v.into_owned())
+        } else {
+            None
+        }
+    });
+    (mimetype, filename)
+}
+
+/// Wrap an attachment as a `data:` URL that `parse_media_type` reads back.
+pub fn to_data_url(mimetype: &str, filename: Option<&str>, bytes: &[u8]) -> String {
+    match filename {
+        Some(name) => format!(
+            "data:{};name={};base64,{}",
+            mimetype,
+            urlencoding::encode(name),
+            BASE64.encode(bytes)
+        ),
+        None => format!("data:{};base64,{}", mimetype, BASE64.encode(bytes)),
+    }
+}
+
+/// Duration of an Ogg/Opus voice note from the granule position of its last
+/// page. Opus always counts 48 kHz samples; the encoder's pre-skip from the
+/// `OpusHead` header is not audio.
+pub fn ogg_opus_duration_ms(bytes: &[u8]) -> Option<u64> {
+    let head = bytes.windows(8).position(|w| w == b"OpusHead")?;
+    let pre_skip = u16::from_le_bytes(bytes.get(head + 10..head + 12)?.try_into().ok()?);
+    let last_page = bytes.windows(4).rposition(|w| w == b"OggS")?;
+    let granule = i64::from_le_bytes(bytes.get(last_page + 6..last_page + 14)?.try_into().ok()?);
+    if granule <= 0 {
+        return None;
+    }
+    let samples = (granule as u64).saturating_sub(u64::from(pre_skip));
+    Some(samples * 1000 / 48_000)
+}
+
+pub fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < MB {
+        format!("{} KB", bytes / 1024)
+    } else {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    }
+}
@@ -0,0 +1,283 @@
+//! Locations shared in WhatsApp/Telegram chats: parse, name, describe.
+//!
+//! Bridges deliver a shared pin as `m.location` with a `geo:` URI and a
+//! free-text body. mautrix-whatsapp writes "Location: <name>\n<address>\n
+//! <maps url>", mautrix-telegram writes the venue title and address or just
+//! "Location". We keep the coordinates plus whatever human label the body
+//! carries, and name the spot with an offline gazetteer so "where did Anna
+//! say to meet?" can be answered without sending coordinates anywhere.
+//!
+//! The gazetteer is a GeoNames dump (`cities1000.txt` or similar, the plain
+//! tab-separated format) pointed to by `GAZETTEER_PATH`. It is loaded once at
+//! startup and shared through `AppState`; without it locations are still
+//! stored, just unnamed.
+
+use std::collections::HashMap;
+
+/// Within this distance a place is reported by name alone.
+const AT_PLACE_KM: f64 = 2.0;
+
+/// Beyond this the nearest gazetteer entry says more about the gazetteer
+/// than about the location, so the place is left out.
+const MAX_PLACE_KM: f64 = 50.0;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// A location share parsed from a bridged event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedLocation {
+    pub lat: f64,
+    pub lon: f64,
+    /// Venue name/address from the sender, if the bridge passed one on.
+    pub label: Option<String>,
+    /// Nearest gazetteer place, e.g. "Kallio, FI" or "near Espoo, FI (4 km)".
+    pub place: Option<String>,
+}
+
+impl SharedLocation {
+    /// Parse an `m.location` event. `None` when the geo URI is unusable.
+    pub fn from_event(body: &str, geo_uri: &str) -> Option<Self> {
+        let (lat, lon) = parse_geo_uri(geo_uri)?;
+        Some(Self {
+            lat,
+            lon,
+            label: location_label(body),
+            place: None,
+        })
+    }
+
+    /// Fill in `place` from the gazetteer, if one is loaded.
+    pub fn resolve_place(&mut self, gazetteer: Option<&Gazetteer>) {
+        self.place = gazetteer.and_then(|g| g.describe(self.lat, self.lon));
+    }
+
+    /// "lat,lon" with five decimals (about a metre), the form Tesla's share
+    /// endpoint and map apps accept.
+    pub fn coordinates(&self) -> String {
+        format!("{:.5},{:.5}", self.lat, self.lon)
+    }
+
+    /// Message content for the ontology: readable by rules, digests and
+    /// message queries like any text message.
+    pub fn content(&self) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        if let Some(label) = self.label.as_deref() {
+            parts.push(label);
+        }
+        if let Some(place) = self.place.as_deref() {
+            let already_named = self
+                .label
+                .as_deref()
+                .is_some_and(|l| l.to_lowercase().contains(&place_name(place).to_lowercase()));
+            if !already_named {
+                parts.push(place);
+            }
+        }
+        if parts.is_empty() {
+            format!("[Location] {}", self.coordinates())
+        } else {
+            format!("[Location] {} ({})", parts.join(" - "), self.coordinates())
+        }
+    }
+}
+
+/// How a location event reads in fetched chat history. No gazetteer
+/// lookup; history is fetched in bulk and the label usually says enough.
+pub fn history_text(body: &str, geo_uri: &str) -> String {
+    SharedLocation::from_event(body, geo_uri)
+        .map(|loc| format!("📍 {}", loc.content()))
+        .unwrap_or_else(|| "📍 LOCATION".to_string())
+}
+
+/// The place name without "near " and the distance suffix.
+fn place_name(place: &str) -> &str {
+    let name = place.strip_prefix("near ").unwrap_or(place);
+    let name = name.split(" (").next().unwrap_or(name);
+    name.split(", ").next().unwrap_or(name)
+}
+
+/// Parse an RFC 5870 `geo:` URI ("geo:60.1699,24.9384;u=35") into
+/// latitude and longitude.
+pub fn parse_geo_uri(uri: &str) -> Option<(f64, f64)> {
+    let rest = uri.trim();
+    let rest = rest
+        .get(..4)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("geo:"))
+        .map(|_| &rest[4..])?;
+    let coords = rest.split([';', '?']).next()?;
+    let mut parts = coords.split(',');
+    let lat: f64 = parts.next()?.trim().parse().ok()?;
+    let lon: f64 = parts.next()?.trim().parse().ok()?;
+    if !lat.is_finite() || !lon.is_finite() || lat.abs() > 90.0 || lon.abs() > 180.0 {
+        return None;
+    }
+    Some((lat, lon))
+}
+
+/// The human part of a location body: drops the "Location:" prefix, URLs,
+/// geo URIs and bare coordinates. `None` when nothing else is left.
+pub fn location_label(body: &str) -> Option<String> {
+    let lines: Vec<&str> = body
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let lower = line.to_ascii_lowercase();
+            if lower.starts_with("location:") {
+                line["location:".len()..].trim()
+            } else {
+                line
+            }
+        })
+        .filter(|line| {
+            let lower = line.to_ascii_lowercase();
+            !line.is_empty()
+                && lower != "location"
+                && lower != "shared location"
+                && lower != "live location"
+                && !lower.starts_with("geo:")
+                && !lower.starts_with("http://")
+                && !lower.starts_with("https://")
+                && !is_bare_coordinates(line)
+        })
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(", "))
+    }
+}
+
+/// "60.1699, 24.9384" or "60.1699° N 24.9384° E".
+fn is_bare_coordinates(text: &str) -> bool {
+    text.chars().any(|c| c.is_ascii_digit())
+        && text.chars().all(|c| {
+            c.is_ascii_digit()
+                || c.is_whitespace()
+                || matches!(c, '.' | ',' | '-' | '+' | '°' | 'N' | 'S' | 'E' | 'W')
+        })
+}
+
+/// Great-circle distance in kilometres.
+pub fn distance_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (a.0.to_radians(), a.1.to_radians());
+    let (lat2, lon2) = (b.0.to_radians(), b.1.to_radians());
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+struct GazetteerEntry {
+    name: String,
+    country_code: String,
+    lat: f64,
+    lon: f64,
+}
+
+/// Offline reverse geocoder over GeoNames rows, bucketed by whole degree.
+pub struct Gazetteer {
+    entries: Vec<GazetteerEntry>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Gazetteer {
+    /// Build from GeoNames' tab-separated dump (name in column 2, latitude
+    /// and longitude in 5 and 6, country code in 9). Malformed rows are
+    /// skipped.
+    pub fn from_geonames_tsv(data: &str) -> Self {
+        let mut entries = Vec::new();
+        let mut cells: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for line in data.lines() {
+            let cols: Vec<&str> = line.split('\t').collect();
+            if cols.len() < 9 {
+                continue;
+            }
+            let (Ok(lat), Ok(lon)) = (cols[4].parse::<f64>(), cols[5].parse::<f64>()) else {
+                continue;
+            };
+            if cols[1].is_empty() {
+                continue;
+            }
+            cells.entry(cell(lat, lon)).or_default().push(entries.len());
+            entries.push(GazetteerEntry {
+                name: cols[1].to_string(),
+                country_code: cols[8].to_string(),
+                lat,
+                lon,
+            });
+        }
+        Self { entries, cells }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Nearest place and its distance in km. Looks at the 3x3 degree cells
+    /// around the point, so anything past roughly `MAX_PLACE_KM` may be
+    /// missed, which `describe` would drop anyway.
+    fn nearest(&self, lat: f64, lon: f64) -> Option<(&GazetteerEntry, f64)> {
+        let (cy, cx) = cell(lat, lon);
+        let mut best: Option<(&GazetteerEntry, f64)> = None;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let key = (cy + dy, wrap_lon_cell(cx + dx));
+                for &i in self.cells.get(&key).into_iter().flatten() {
+                    let entry = &self.entries[i];
+                    let d = distance_km((lat, lon), (entry.lat, entry.lon));
+                    if best.is_none_or(|(_, bd)| d < bd) {
+                        best = Some((entry, d));
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// "Kallio, FI" when the point is in a place, "near Espoo, FI (4 km)"
+    /// when it is close to one, `None` in the middle of nowhere.
+    pub fn describe(&self, lat: f64, lon: f64) -> Option<String> {
+        let (entry, km) = self.nearest(lat, lon)?;
+        let name = if entry.country_code.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}, {}", entry.name, entry.country_code)
+        };
+        if km <= AT_PLACE_KM {
+            Some(name)
+        } else if km <= MAX_PLACE_KM {
+            Some(format!("near {} ({:.0} km)", name, km))
+        } else {
+            None
+        }
+    }
+}
+
+fn cell(lat: f64, lon: f64) -> (i32, i32) {
+    (lat.floor() as i32, wrap_lon_cell(lon.floor() as i32))
+}
+
+fn wrap_lon_cell(x: i32) -> i32 {
+    (x + 180).rem_euclid(360) - 180
+}
+
+/// Read the gazetteer from `GAZETTEER_PATH`. `None` when unset or
+/// unreadable. Parsing a full dump takes seconds, so call this at startup
+/// or from `spawn_blocking`, never on an async worker.
+pub fn load_gazetteer() -> Option<Gazetteer> {
+    let path = std::env::var("GAZETTEER_PATH").ok()?;
+    match std::fs::read_to_string(&path) {
+        Ok(data) => {
+            let gazetteer = Gazetteer::from_geonames_tsv(&data);
+            tracing::info!("Loaded {} gazetteer places from {}", gazetteer.len(), path);
+            Some(gazetteer)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read gazetteer {}: {}", path, e);
+            None
+        }
+    }
+}
@@ -494,6 +494,28 @@ impl<T: TwilioClient> TwilioMessageService<T> {
         })
     }
 
+    /// Place a call that runs `twiml` once answered. The FROM number is
+    /// `from_override` when given, else the user's preferred number.
+    pub async fn place_call(
+        &self,
+        user: &User,
+        twiml: &str,
+        from_override: Option<&str>,
+    ) -> Result<String, TwilioMessageError> {
+        let credentials = self.resolve_credentials(user)?;
+        let from_number = match from_override {
+            Some(from) => from.to_string(),
+            None => user
+                .preferred_number
+                .clone()
+                .ok_or(TwilioMessageError::NoSendingNumber)?,
+        };
+        Ok(self
+            .twilio_client
+            .make_call(&credentials, &user.phone_number, &from_number, twiml)
+            .await?)
+    }
+
     /// Delete a Twilio message with retry logic.
     ///
     /// Waits 60 seconds before the first attempt to avoid 'resource not complete' errors,
@@ -549,6 +571,19 @@ impl<T: TwilioClient> TwilioMessageService<T> {
         Ok(())
     }
 
+    /// Download inbound MMS media so it can be processed before deletion.
+    pub async fn fetch_message_media(
+        &self,
+        user: &User,
+        media_url: &str,
+    ) -> Result<Vec<u8>, TwilioMessageError> {
+        let credentials = self.resolve_credentials(user)?;
+        Ok(self
+            .twilio_client
+            .fetch_message_media(&credentials, media_url)
+            .await?)
+    }
+
     /// Fetch the price of a sent message.
     pub async fn fetch_message_price(
         &self,
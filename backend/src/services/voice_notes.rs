@@ -0,0 +1,334 @@
+//! Inbound voice notes from WhatsApp, Telegram and Signal: download, decode,
+//! transcribe.
+//!
+//! Bridges deliver voice notes as `m.audio` events whose body is just a
+//! filename, so without this urgency classification and digests only ever
+//! saw "AUDIO". For every voice note we store we:
+//!   1. download the Matrix media (the bridge uploads it to our homeserver),
+//!   2. decode Opus/OGG (WhatsApp, Telegram, Signal) or AAC/M4A (iOS) into
+//!      16 kHz mono WAV with the `ffmpeg` CLI,
+//!   3. transcribe through a `SpeechToText` backend: Tinfoil's attested
+//!      Whisper by default, or whisper.cpp on the local CPU.
+//!
+//! The caller writes the result into the message content as
+//! "[Voice message 0:42] ..." so rules, digests and message queries read it
+//! like any text message, and keeps the raw transcript on
+//! `ont_messages.transcript`. Audio bytes only live in memory and in one
+//! temp file per decode, removed before returning.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::AppState;
+
+/// Longer notes are cut here; the start carries the gist and the cost of a
+/// rambling ten-minute note stays bounded.
+pub const MAX_DURATION_SECS: u32 = 300;
+
+/// Larger downloads are skipped rather than decoded.
+pub const MAX_AUDIO_BYTES: usize = 20 * 1024 * 1024;
+
+/// Transcript kept per message, in characters.
+const MAX_TRANSCRIPT_CHARS: usize = 4000;
+
+const DECODE_TIMEOUT: Duration = Duration::from_secs(60);
+const LOCAL_STT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Whisper prints these for silence or noise; they are not speech.
+const NON_SPEECH: &[&str] = &[
+    "thank you for watching",
+    "thanks for watching",
+    "please subscribe",
+    "like and subscribe",
+    "subtitles by the amara.org community",
+    "[music]",
+    "(music)",
+    "[silence]",
+    "(silence)",
+    "*silence*",
+    "[blank_audio]",
+    "you",
+];
+
+#[derive(Debug, Error)]
+pub enum VoiceNoteError {
+    #[error("Failed to download voice note: {0}")]
+    Download(String),
+
+    #[error("Unsupported audio format: {0}")]
+    Unsupported(String),
+
+    #[error("Voice note too large: {0} bytes")]
+    TooLarge(usize),
+
+    #[error("Failed to decode audio: {0}")]
+    Decode(String),
+
+    #[error("Transcription failed: {0}")]
+    Transcription(String),
+}
+
+/// Container/codec families we know how to hand to ffmpeg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// Ogg with Opus (or Vorbis) inside: WhatsApp, Telegram and Signal.
+    Ogg,
+    /// Raw ADTS AAC.
+    Aac,
+    /// MP4/M4A with AAC inside, as sent from iPhones.
+    Mp4,
+}
+
+impl AudioFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::Aac => "aac",
+            AudioFormat::Mp4 => "m4a",
+        }
+    }
+}
+
+/// Sniff the format from magic bytes, falling back to the declared MIME
+/// type. `None` for anything else (MP3 music files, AMR, ...).
+pub fn detect_audio_format(mimetype: Option<&str>, bytes: &[u8]) -> Option<AudioFormat> {
+    if bytes.starts_with(b"OggS") {
+        return Some(AudioFormat::Ogg);
+    }
+    if bytes.len() >= 8 && &bytes[4..8] == b"ftyp" {
+        return Some(AudioFormat::Mp4);
+    }
+    // ADTS sync word: 12 set bits, layer 00.
+    if bytes.len() >= 2 && bytes[0] == 0xFF && bytes[1] & 0xF6 == 0xF0 {
+        return Some(AudioFormat::Aac);
+    }
+    let mime = mimetype?.split(';').next()?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "audio/ogg" | "audio/opus" | "audio/x-opus+ogg" => Some(AudioFormat::Ogg),
+        "audio/aac" | "audio/x-aac" | "audio/aacp" => Some(AudioFormat::Aac),
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => Some(AudioFormat::Mp4),
+        _ => None,
+    }
+}
+
+/// A speech-to-text backend. Takes 16 kHz mono WAV.
+#[async_trait]
+pub trait SpeechToText: Send + Sync {
+    /// Short backend name for logs.
+    fn name(&self) -> &'static str;
+
+    async fn transcribe(&self, wav: &[u8]) -> Result<String, VoiceNoteError>;
+}
+
+/// Whisper inside Tinfoil's attested enclave, the same endpoint voice
+/// calls use.
+pub struct TinfoilSpeechToText {
+    client: crate::api::tinfoil_client::TinfoilVoiceClient,
+}
+
+impl TinfoilSpeechToText {
+    pub fn new(ai_config: &crate::AiConfig) -> Self {
+        Self {
+            client: crate::api::tinfoil_client::TinfoilVoiceClient::new(ai_config),
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechToText for TinfoilSpeechToText {
+    fn name(&self) -> &'static str {
+        "tinfoil"
+    }
+
+    async fn transcribe(&self, wav: &[u8]) -> Result<String, VoiceNoteError> {
+        self.client
+            .transcribe(wav)
+            .await
+            .map_err(VoiceNoteError::Transcription)
+    }
+}
+
+/// whisper.cpp's `whisper-cli` on the local CPU. Needs a ggml model file;
+/// nothing leaves the machine.
+pub struct LocalWhisperSpeechToText {
+    pub bin: String,
+    pub model_path: String,
+    /// Whisper language code, or "auto".
+    pub language: String,
+}
+
+#[async_trait]
+impl SpeechToText for LocalWhisperSpeechToText {
+    fn name(&self) -> &'static str {
+        "whisper.cpp"
+    }
+
+    async fn transcribe(&self, wav: &[u8]) -> Result<String, VoiceNoteError> {
+        let input = std::env::temp_dir().join(format!("lf-voice-{}.wav", uuid::Uuid::new_v4()));
+        let result: Result<String, VoiceNoteError> = async {
+            tokio::fs::write(&input, wav)
+                .await
+                .map_err(|e| VoiceNoteError::Transcription(e.to_string()))?;
+            let mut command = tokio::process::Command::new(&self.bin);
+            command
+                .arg("-m")
+                .arg(&self.model_path)
+                .arg("-f")
+                .arg(&input)
+                .args(["-l", self.language.as_str(), "-nt", "-np"])
+                .stdin(std::process::Stdio::null())
+                .kill_on_drop(true);
+            let output = tokio::time::timeout(LOCAL_STT_TIMEOUT, command.output())
+                .await
+                .map_err(|_| VoiceNoteError::Transcription(format!("{} timed out", self.bin)))?
+                .map_err(|e| VoiceNoteError::Transcription(format!("{}: {}", self.bin, e)))?;
+            if !output.status.success() {
+                return Err(VoiceNoteError::Transcription(format!(
+                    "{} exited with {}",
+                    self.bin, output.status
+                )));
+            }
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        .await;
+        let _ = tokio::fs::remove_file(&input).await;
+        result
+    }
+}
+
+/// The configured backend, or `None` when transcription is off.
+///
+/// `VOICE_NOTE_STT` picks it: `tinfoil`, `local` or `off`. Unset means
+/// Tinfoil when its key is configured, else whisper.cpp when
+/// `WHISPER_MODEL_PATH` is set, else off.
+pub fn speech_to_text(state: &Arc<AppState>) -> Option<Box<dyn SpeechToText>> {
+    let local = || {
+        let model_path = std::env::var("WHISPER_MODEL_PATH").ok()?;
+        Some(Box::new(LocalWhisperSpeechToText {
+            bin: std::env::var("WHISPER_CPP_BIN").unwrap_or_else(|_| "whisper-cli".to_string()),
+            model_path,
+            language: std::env::var("WHISPER_LANGUAGE").unwrap_or_else(|_| "auto".to_string()),
+        }) as Box<dyn SpeechToText>)
+    };
+    let tinfoil_configured = state
+        .ai_config
+        .provider_configured(crate::AiProvider::Tinfoil);
+    let tinfoil = || {
+        tinfoil_configured
+            .then(|| Box::new(TinfoilSpeechToText::new(&state.ai_config)) as Box<dyn SpeechToText>)
+    };
+
+    match std::env::var("VOICE_NOTE_STT").as_deref() {
+        Ok("off") => None,
+        Ok("local") => local(),
+        Ok("tinfoil") => tinfoil(),
+        _ => tinfoil().or_else(local),
+    }
+}
+
+/// Decode to 16 kHz mono WAV with `ffmpeg`, keeping the first
+/// `MAX_DURATION_SECS`. MP4 needs a seekable input, so every format goes
+/// through a temp file for symmetry.
+pub async fn decode_to_wav(format: AudioFormat, bytes: &[u8]) -> Result<Vec<u8>, VoiceNoteError> {
+    let bin = std::env::var("FFMPEG_BIN").unwrap_or_else(|_| "ffmpeg".to_string());
+    let input = std::env::temp_dir().join(format!(
+        "lf-voice-{}.{}",
+        uuid::Uuid::new_v4(),
+        format.extension()
+    ));
+
+    let result: Result<Vec<u8>, VoiceNoteError> = async {
+        tokio::fs::write(&input, bytes)
+            .await
+            .map_err(|e| VoiceNoteError::Decode(e.to_string()))?;
+        let mut command = tokio::process::Command::new(&bin);
+        command
+            .args(["-hide_banner", "-loglevel", "error", "-i"])
+            .arg(&input)
+            .args(["-t", &MAX_DURATION_SECS.to_string()])
+            .args(["-vn", "-ac", "1", "-ar", "16000", "-f", "wav", "pipe:1"])
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true);
+        let output = tokio::time::timeout(DECODE_TIMEOUT, command.output())
+            .await
+            .map_err(|_| VoiceNoteError::Decode(format!("{} timed out", bin)))?
+            .map_err(|e| VoiceNoteError::Decode(format!("{}: {}", bin, e)))?;
+        if !output.status.success() || output.stdout.is_empty() {
+            return Err(VoiceNoteError::Decode(format!(
+                "{} exited with {}: {}",
+                bin,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+    .await;
+
+    let _ = tokio::fs::remove_file(&input).await;
+    result
+}
+
+/// Collapse whitespace, drop whisper's non-speech filler and cap the
+/// length. `None` when nothing spoken is left.
+pub fn clean_transcript(raw: &str) -> Option<String> {
+    let not_word = |c: char| !c.is_alphanumeric();
+    let text = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lowered = text.to_lowercase();
+    let bare = lowered.trim_matches(not_word);
+    if bare.is_empty()
+        || NON_SPEECH
+            .iter()
+            .any(|filler| bare == filler.trim_matches(not_word))
+    {
+        return None;
+    }
+    Some(match text.char_indices().nth(MAX_TRANSCRIPT_CHARS) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text,
+    })
+}
+
+/// What a voice note's message content becomes once transcribed.
+pub fn voice_note_content(transcript: &str, duration_secs: Option<u32>) -> String {
+    match duration_secs {
+        Some(secs) => format!(
+            "[Voice message {}:{:02}] {}",
+            secs / 60,
+            secs % 60,
+            transcript
+        ),
+        None => format!("[Voice message] {}", transcript),
+    }
+}
+
+/// Check the size a voice note's event declares, before it is downloaded.
+/// A note that doesn't say how large it is gets skipped too: there is no
+/// way to bound its download.
+pub fn check_declared_size(size: Option<u64>) -> Result<(), VoiceNoteError> {
+    let size = size.ok_or_else(|| VoiceNoteError::Download("size not declared".to_string()))?;
+    let size = usize::try_from(size).unwrap_or(usize::MAX);
+    if size > MAX_AUDIO_BYTES {
+        return Err(VoiceNoteError::TooLarge(size));
+    }
+    Ok(())
+}
+
+/// Decode and transcribe one downloaded voice note.
+pub async fn transcribe_voice_note(
+    stt: &dyn SpeechToText,
+    mimetype: Option<&str>,
+    bytes: &[u8],
+) -> Result<Option<String>, VoiceNoteError> {
+    if bytes.len() > MAX_AUDIO_BYTES {
+        return Err(VoiceNoteError::TooLarge(bytes.len()));
+    }
+    let format = detect_audio_format(mimetype, bytes)
+        .ok_or_else(|| VoiceNoteError::Unsupported(mimetype.unwrap_or("unknown").to_string()))?;
+    let wav = decode_to_wav(format, bytes).await?;
+    let raw = stt.transcribe(&wav).await?;
+    Ok(clean_transcript(&raw))
+}
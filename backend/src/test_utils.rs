@@ -182,9 +182,20 @@ pub fn create_test_state() -> Arc<crate::AppState> {
     ));
 
     let mut router = crate::channels::router::ChannelRouter::new();
+    let twilio_channel = Arc::new(crate::channels::twilio_channel::TwilioChannel::new(
+        twilio_message_service.clone(),
+    ));
+    router.register(twilio_channel.clone());
+    router.register_voice(twilio_channel);
     router.register(Arc::new(
-        crate::channels::twilio_channel::TwilioChannel::new(twilio_message_service.clone()),
+        crate::channels::push_channel::PushChannel::new(pg_pool.clone())
+            .expect("Failed to build push channel"),
     ));
+    router.register(Arc::new(crate::channels::email_channel::EmailChannel::new(
+        pg_pool.clone(),
+        user_repository.clone(),
+        crate::channels::email_channel::SmtpRelay::from_env(),
+    )));
     let channel_router = Arc::new(router);
 
     Arc::new(crate::AppState {
@@ -222,6 +233,9 @@ pub fn create_test_state() -> Arc<crate::AppState> {
         pending_reply_watches_repository,
         webhook_tokens_repository,
         pending_totp_logins: DashMap::new(),
+        voice_stream_tickets: crate::api::voice_stream_bridge::StreamTickets::new(
+            "https://localhost:3000",
+        ),
         pending_password_resets: DashMap::new(),
         session_to_token: DashMap::new(),
         totp_verify_limiter: DashMap::new(),
@@ -234,6 +248,7 @@ pub fn create_test_state() -> Arc<crate::AppState> {
         whatsapp_bridge_repository: None,
         telegram_bridge_repository: None,
         ontology_registry: crate::ontology::registry::OntologyRegistry::build(),
+        gazetteer: None,
         tool_registry: crate::build_tool_registry(),
         pending_rule_tests: Arc::new(dashmap::DashMap::new()),
         maintenance_mode: Arc::new(std::sync::atomic::AtomicBool::new(false)),
@@ -965,6 +980,14 @@ pub mod mock_user_core {
             Ok(())
         }
 
+        fn update_sms_gsm7_transliteration(
+            &self,
+            _user_id: i32,
+            _value: bool,
+        ) -> Result<(), DieselError> {
+            Ok(())
+        }
+
         fn set_refresh_token_hash(
             &self,
             _user_id: i32,
@@ -55,6 +55,14 @@ impl MatrixTestConfig {
             _ => format!("@{}bot:{}", service, self.domain),
         }
     }
+
+    /// Provisioning API base URL of a bridge in the test stack, from
+    /// `TEST_{SERVICE}_BRIDGE_URL`. `None` when that bridge isn't running.
+    pub fn bridge_provisioning_url(&self, service: &str) -> Option<String> {
+        std::env::var(format!("TEST_{}_BRIDGE_URL", service.to_uppercase()))
+            .ok()
+            .filter(|url| !url.is_empty())
+    }
 }
 
 /// Credentials returned from user registration
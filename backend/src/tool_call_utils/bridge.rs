@@ -43,6 +43,35 @@ pub fn get_send_chat_message_tool() -> openai_api_rs::v1::chat_completion::Tool
             ..Default::default()
         }),
     );
+    properties.insert(
+        "reply_to".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some(
+                "Optional. Send the message as a reply to an earlier message in the chat, e.g. \"reply to the dinner question in the family group saying yes\" gives 'dinner question'. Use 'last' for the newest message from the other side, or words from the message (or its sender's name) to pick another one.".to_string()
+            ),
+            ..Default::default()
+        }),
+    );
+    properties.insert(
+        "forward_attachment_from".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some(
+                "Optional. Chat name whose most recent photo, voice note, video or file should be forwarded with this message, e.g. \"forward the photo Anna sent me to the family group\" gives 'Anna'. Leave out to attach the photo from the user's current message, if there is one.".to_string()
+            ),
+            ..Default::default()
+        }),
+    );
+    properties.insert(
+        "attachment_platform".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some("Optional. Platform of the forward_attachment_from chat when it differs from 'platform'.".to_string()),
+            enum_values: Some(vec!["telegram".to_string(), "whatsapp".to_string(), "signal".to_string()]),
+            ..Default::default()
+        }),
+    );
     chat_completion::Tool {
         r#type: chat_completion::ToolType::Function,
         function: types::Function {
@@ -53,6 +82,7 @@ pub fn get_send_chat_message_tool() -> openai_api_rs::v1::chat_completion::Tool
                     Use this when the user asks to send a message RIGHT NOW to a contact or group on Telegram, WhatsApp or Signal. \
                     IMPORTANT: If the user specifies a future time (e.g. 'at 5pm text...', 'in 2 hours send...'), do NOT call this tool - use create_item instead to schedule it. This tool executes immediately and cannot be scheduled. \
                     This tool will fuzzy search for the chat_name, add the message to the sending queue and unless user replies cancel the message will be sent after 60 seconds. \
+                    A photo on the user's current message is sent along with the message as its caption; to forward a photo, voice note or file someone sent in another chat, set forward_attachment_from. The confirmation tells the user what is attached so they can cancel. \
                     Only use this tool if the user has explicitly mentioned the message content or it is obviously clear what content they want to send; otherwise, ask the user to specify the message content, recipient and platform before calling the tool."
                 )),
             parameters: types::FunctionParameters {
@@ -64,17 +94,148 @@ pub fn get_send_chat_message_tool() -> openai_api_rs::v1::chat_completion::Tool
     }
 }
 
+pub fn get_react_to_chat_message_tool() -> openai_api_rs::v1::chat_completion::Tool {
+    use openai_api_rs::v1::{chat_completion, types};
+    use std::collections::HashMap;
+    let mut properties = HashMap::new();
+    properties.insert(
+        "platform".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some(
+                "The platform of the chat. Must be either 'telegram', 'whatsapp' or 'signal'."
+                    .to_string(),
+            ),
+            enum_values: Some(vec![
+                "telegram".to_string(),
+                "whatsapp".to_string(),
+                "signal".to_string(),
+            ]),
+            ..Default::default()
+        }),
+    );
+    properties.insert(
+        "chat_name".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some("The chat or room name the message is in. Doesn't have to be exact since fuzzy search is used.".to_string()),
+            ..Default::default()
+        }),
+    );
+    properties.insert(
+        "reaction".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some(
+                "A single emoji, e.g. '👍' for thumbs up or '❤️' for a heart.".to_string(),
+            ),
+            ..Default::default()
+        }),
+    );
+    properties.insert(
+        "target_message".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some("Optional. Words from the message to react to, or its sender's name in a group. Leave out to react to the newest message from the other side.".to_string()),
+            ..Default::default()
+        }),
+    );
+    chat_completion::Tool {
+        r#type: chat_completion::ToolType::Function,
+        function: types::Function {
+            name: String::from("react_to_chat_message"),
+            description: Some(String::from(
+                "Reacts with an emoji to a message in a Telegram, WhatsApp or Signal chat, e.g. 'react thumbs up to Mom's last message'. \
+                The reaction is queued like send_chat_message and added after 60 seconds unless the user replies cancel.",
+            )),
+            parameters: types::FunctionParameters {
+                schema_type: types::JSONSchemaType::Object,
+                properties: Some(properties),
+                required: Some(vec![String::from("platform"), String::from("chat_name"), String::from("reaction")]),
+            },
+        },
+    }
+}
+
+pub fn get_edit_chat_message_tool() -> openai_api_rs::v1::chat_completion::Tool {
+    use openai_api_rs::v1::{chat_completion, types};
+    use std::collections::HashMap;
+    let mut properties = HashMap::new();
+    properties.insert(
+        "platform".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some(
+                "The platform of the chat. Must be either 'telegram', 'whatsapp' or 'signal'."
+                    .to_string(),
+            ),
+            enum_values: Some(vec![
+                "telegram".to_string(),
+                "whatsapp".to_string(),
+                "signal".to_string(),
+            ]),
+            ..Default::default()
+        }),
+    );
+    properties.insert(
+        "chat_name".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some("The chat or room name the user's message was sent to. Doesn't have to be exact since fuzzy search is used.".to_string()),
+            ..Default::default()
+        }),
+    );
+    properties.insert(
+        "message".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some("The complete corrected text that replaces the old message, not just the changed part.".to_string()),
+            ..Default::default()
+        }),
+    );
+    chat_completion::Tool {
+        r#type: chat_completion::ToolType::Function,
+        function: types::Function {
+            name: String::from("edit_chat_message"),
+            description: Some(String::from(
+                "Edits the user's own most recent message in a Telegram, WhatsApp or Signal chat, e.g. 'fix my last message to say 7pm'. \
+                WhatsApp allows edits for 15 minutes after sending, Signal for 24 hours and Telegram for 48 hours. \
+                The edit is queued like send_chat_message and applied after 60 seconds unless the user replies cancel.",
+            )),
+            parameters: types::FunctionParameters {
+                schema_type: types::JSONSchemaType::Object,
+                properties: Some(properties),
+                required: Some(vec![String::from("platform"), String::from("chat_name"), String::from("message")]),
+            },
+        },
+    }
+}
+
 use crate::api::twilio_sms::TwilioResponse;
 use crate::models::user_models::User;
+use crate::utils::bridge::BridgeRelation;
 use axum::http::{HeaderName, StatusCode};
 
 #[derive(Deserialize)]
 struct SendChatMessageArgs {
     platform: String,
     chat_name: String,
+    #[serde(default)]
     message: String,
     #[serde(default)]
     notify_on_reply: bool,
+    /// Reply target hint; `react_to_chat_message` sends it as `target_message`.
+    #[serde(default, alias = "target_message")]
+    reply_to: Option<String>,
+    #[serde(default)]
+    reaction: Option<String>,
+    /// Set by `edit_chat_message`.
+    #[serde(default)]
+    edit_last_message: bool,
+    #[serde(default)]
+    forward_attachment_from: Option<String>,
+    #[serde(default)]
+    attachment_platform: Option<String>,
 }
 /// Resolved chat target for the send path.
 ///
@@ -611,6 +772,27 @@ pub async fn handle_send_chat_message(
             }),
         ));
     }
+    // Resolve and validate the attachment before queueing, so a missing or
+    // oversized file is reported now and the confirmation can say what is
+    // attached.
+    let media = match resolve_outgoing_media(state, user_id, &args, image_url).await {
+        Ok(media) => media,
+        Err(error_msg) => {
+            tracing::warn!("SEND_FLOW Attachment rejected: {}", error_msg);
+            return Ok(send_error_reply(state, user, skip_sms, error_msg).await);
+        }
+    };
+    // `message` may be left out for reactions, but a text send or an edit
+    // needs something to say.
+    let has_reaction = args
+        .reaction
+        .as_deref()
+        .is_some_and(|key| !key.trim().is_empty());
+    if args.message.trim().is_empty() && media.is_none() && !has_reaction {
+        tracing::warn!("SEND_FLOW Rejected send with no message, reaction or attachment");
+        let error_msg = "There's nothing to send: the message is empty.".to_string();
+        return Ok(send_error_reply(state, user, skip_sms, error_msg).await);
+    }
     // Step 0: Resolve from the same cached index shown by the dashboard
     // picker. This includes bridge-DB contacts/chats that have never sent an
     // inbound message and therefore do not exist in the ontology yet.
@@ -848,17 +1030,49 @@ pub async fn handle_send_chat_message(
         best_match.chat_id,
         best_match.room_id
     );
-    // Format the queued message with the found contact name and image if present
-    let queued_msg = if image_url.is_some() {
-        format!(
-            "Will send {} to '{}' with image and caption '{}' in 60s. Reply 'C' to discard.",
-            capitalized_platform, exact_name, args.message
-        )
-    } else {
-        format!(
-            "Will send {} to '{}' with content '{}' in 60s. Reply 'C' to discard.",
-            capitalized_platform, exact_name, args.message
-        )
+    // Resolve what a reply, reaction or edit points at before queueing, so a
+    // missing target fails now and the confirmation can quote it.
+    let relation = match resolve_relation(
+        state,
+        user_id,
+        &args,
+        best_match.room_id.as_deref(),
+        &exact_name,
+    )
+    .await
+    {
+        Ok(relation) => relation,
+        Err(error_msg) => {
+            tracing::warn!("SEND_FLOW Relation target rejected: {}", error_msg);
+            return Ok(send_error_reply(state, user, skip_sms, error_msg).await);
+        }
+    };
+    // Format the queued message with the found contact name and attachment if present
+    let reply_note = match &relation {
+        Some((BridgeRelation::Reply { .. }, target)) => format!(" replying to {}", target),
+        _ => String::new(),
+    };
+    let queued_msg = match (&relation, &media) {
+        (Some((BridgeRelation::Reaction { key, .. }, target)), _) => format!(
+            "Will react {} on {} to {} in 60s. Reply 'C' to discard.",
+            key, capitalized_platform, target
+        ),
+        (Some((BridgeRelation::Edit { .. }, target)), _) => format!(
+            "Will edit your {} message to '{}' from {} to '{}' in 60s. Reply 'C' to discard.",
+            capitalized_platform, exact_name, target, args.message
+        ),
+        (_, Some((_, description, _))) if args.message.trim().is_empty() => format!(
+            "Will send {} to '{}'{} with {} in 60s. Reply 'C' to discard.",
+            capitalized_platform, exact_name, reply_note, description
+        ),
+        (_, Some((_, description, _))) => format!(
+            "Will send {} to '{}'{} with {} and caption '{}' in 60s. Reply 'C' to discard.",
+            capitalized_platform, exact_name, reply_note, description, args.message
+        ),
+        (_, None) => format!(
+            "Will send {} to '{}'{} with content '{}' in 60s. Reply 'C' to discard.",
+            capitalized_platform, exact_name, reply_note, args.message
+        ),
     };
     // Send the queued confirmation SMS (best-effort, don't block the actual send)
     // Skip when request came from web dashboard - confirmation is returned inline
@@ -887,7 +1101,11 @@ pub async fn handle_send_chat_message(
     let cloned_room_id = best_match.room_id.clone();
     let cloned_chat_id = best_match.chat_id.clone();
     // Log outbound bandwidth estimate
-    let outbound_bytes = args.message.len() as i32 + if image_url.is_some() { 50_000 } else { 0 };
+    let outbound_bytes = args.message.len() as i32
+        + media
+            .as_ref()
+            .map(|(_, _, size)| (*size).min(i32::MAX as usize) as i32)
+            .unwrap_or(0);
     if let Err(e) = state.bandwidth_repository.log_bandwidth(
         user_id,
         &args.platform,
@@ -901,9 +1119,12 @@ pub async fn handle_send_chat_message(
         );
     }
 
-    let cloned_image_url = image_url.map(|s| s.to_string());
+    let cloned_media_url = media.map(|(url, _, _)| url);
     let cloned_skip_sms = skip_sms;
-    let cloned_notify_on_reply = args.notify_on_reply;
+    // Reactions and edits don't start a conversation worth watching.
+    let cloned_notify_on_reply =
+        args.notify_on_reply && matches!(relation, None | Some((BridgeRelation::Reply { .. }, _)));
+    let cloned_relation = relation.map(|(relation, _)| relation);
     tracing::info!(
         "SEND_FLOW About to tokio::spawn delayed send task for user={}, room_id={:?}, chat_id={:?}",
         user_id,
@@ -941,9 +1162,10 @@ pub async fn handle_send_chat_message(
                 cloned_user_id,
                 &cloned_exact_name,
                 &cloned_message,
-                cloned_image_url,
+                cloned_media_url,
                 cloned_room_id.as_deref(),
                 cloned_chat_id.as_deref(),
+                cloned_relation,
             )
             .await
             {
@@ -1051,6 +1273,216 @@ pub async fn handle_send_chat_message(
     ))
 }
 
+/// The attachment for a send: the latest one from `forward_attachment_from`,
+/// else the photo on the user's current message. Returns the media URL, a
+/// description for the confirmation ("photo (1.2 MB)") and its size, or the
+/// message to send the user when it can't go to this network.
+async fn resolve_outgoing_media(
+    state: &Arc<AppState>,
+    user_id: i32,
+    args: &SendChatMessageArgs,
+    image_url: Option<&str>,
+) -> Result<Option<(String, String, usize)>, String> {
+    use crate::services::outgoing_media::{parse_media_type, OutgoingMedia};
+
+    // Reactions and edits never carry the photo the user's MMS came with.
+    if args.reaction.is_some() || args.edit_last_message {
+        return Ok(None);
+    }
+    let url = match args.forward_attachment_from.as_deref() {
+        Some(source_chat) if !source_chat.trim().is_empty() => {
+            let source_platform = args
+                .attachment_platform
+                .as_deref()
+                .unwrap_or(&args.platform);
+            let (data_url, _) = crate::utils::bridge::fetch_latest_attachment(
+                state,
+                user_id,
+                source_platform,
+                source_chat,
+                &args.platform,
+            )
+            .await
+            .map_err(|e| format!("Couldn't forward the attachment: {}", e))?;
+            data_url
+        }
+        _ => match image_url {
+            Some(url) => url.to_string(),
+            None => return Ok(None),
+        },
+    };
+
+    match crate::services::inbound_media::decode_data_url(&url) {
+        Some((content_type, bytes)) => {
+            let (mimetype, filename) = parse_media_type(&content_type);
+            let media =
+                OutgoingMedia::inspect(&args.platform, &mimetype, bytes, filename.as_deref())
+                    .map_err(|e| format!("Couldn't send the attachment: {}", e))?;
+            let description = media.describe();
+            Ok(Some((url, description, media.size)))
+        }
+        // Remote URLs are only fetched at send time; 50 KB is the old
+        // bandwidth estimate for an MMS photo.
+        None => Ok(Some((url, "attachment".to_string(), 50_000))),
+    }
+}
+
+/// Send `error_msg` to the user (unless the request came from the web
+/// dashboard, which shows it inline) and build the tool response for it.
+async fn send_error_reply(
+    state: &Arc<AppState>,
+    user: &User,
+    skip_sms: bool,
+    error_msg: String,
+) -> (
+    StatusCode,
+    [(HeaderName, &'static str); 1],
+    Json<TwilioResponse>,
+) {
+    if !skip_sms {
+        if let Err(e) = state
+            .channel_router
+            .send_to_user(user, &error_msg, None)
+            .await
+        {
+            eprintln!("Failed to send error message: {}", e);
+        }
+    }
+    (
+        StatusCode::OK,
+        [(axum::http::header::CONTENT_TYPE, "application/json")],
+        Json(TwilioResponse {
+            message: error_msg,
+            created_item_id: None,
+        }),
+    )
+}
+
+/// First 40 characters of a message, for quoting it in a confirmation.
+fn quote_preview(content: &str) -> String {
+    if content.chars().count() > 40 {
+        format!("'{}...'", content.chars().take(37).collect::<String>())
+    } else {
+        format!("'{}'", content)
+    }
+}
+
+/// Work out the message a reply, reaction or edit points at. Returns the
+/// relation with a description for the confirmation ("Anna's message
+/// 'dinner at 7?'"), `None` for a plain send, or the message to send the
+/// user when there is nothing to point at.
+async fn resolve_relation(
+    state: &Arc<AppState>,
+    user_id: i32,
+    args: &SendChatMessageArgs,
+    room_id: Option<&str>,
+    chat_name: &str,
+) -> Result<Option<(BridgeRelation, String)>, String> {
+    let capitalized_platform = crate::utils::bridge::capitalize(&args.platform);
+    let reply_to = args
+        .reply_to
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty());
+    if reply_to.is_none() && args.reaction.is_none() && !args.edit_last_message {
+        return Ok(None);
+    }
+    let Some(room_id) = room_id else {
+        return Err(format!(
+            "There are no {} messages with '{}' yet.",
+            capitalized_platform, chat_name
+        ));
+    };
+
+    if args.edit_last_message {
+        if args.message.trim().is_empty() {
+            return Err("Tell me what the message should say instead.".to_string());
+        }
+        let sent = crate::utils::bridge::get_latest_sent_message_in_room(
+            &args.platform,
+            state,
+            user_id,
+            room_id,
+        )
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| {
+            format!(
+                "You haven't sent anything to '{}' on {} recently.",
+                chat_name, capitalized_platform
+            )
+        })?;
+        if sent.message_type != "text" {
+            return Err(format!(
+                "Your last message to '{}' isn't text, so it can't be edited.",
+                chat_name
+            ));
+        }
+        if let Some(window) = crate::utils::bridge::edit_window_secs(&args.platform) {
+            if chrono::Utc::now().timestamp() - sent.timestamp > window {
+                let window = if window % 3600 == 0 {
+                    format!("{} hours", window / 3600)
+                } else {
+                    format!("{} minutes", window / 60)
+                };
+                return Err(format!(
+                    "{} only allows edits for {} after sending; your last message to '{}' is older than that.",
+                    capitalized_platform, window, chat_name
+                ));
+            }
+        }
+        let event_id = sent
+            .event_id
+            .ok_or_else(|| "Couldn't find your last message to edit.".to_string())?;
+        return Ok(Some((
+            BridgeRelation::Edit { event_id },
+            quote_preview(&sent.content),
+        )));
+    }
+
+    if let Some(key) = &args.reaction {
+        let key = key.trim();
+        if key.is_empty()
+            || key.chars().count() > 8
+            || key.chars().any(|c| c.is_ascii_alphanumeric())
+        {
+            return Err("Reactions must be a single emoji, e.g. 👍".to_string());
+        }
+    }
+
+    let hint = reply_to.filter(|r| !matches!(r.to_lowercase().as_str(), "last" | "latest"));
+    let target = crate::utils::bridge::get_triggering_message_in_room(
+        &args.platform,
+        state,
+        user_id,
+        room_id,
+        hint,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| match hint {
+        Some(hint) => format!("No recent message in '{}' matches '{}'.", chat_name, hint),
+        None => format!("No recent messages from '{}'.", chat_name),
+    })?;
+    let event_id = target
+        .event_id
+        .clone()
+        .ok_or_else(|| "Couldn't find that message.".to_string())?;
+    let description = format!(
+        "{}'s message {}",
+        target.sender_display_name,
+        quote_preview(&target.content)
+    );
+    let relation = match &args.reaction {
+        Some(key) => BridgeRelation::Reaction {
+            event_id,
+            key: key.trim().to_string(),
+        },
+        None => BridgeRelation::Reply { event_id },
+    };
+    Ok(Some((relation, description)))
+}
+
 #[derive(Deserialize)]
 struct FetchChatMessagesArgs {
     platform: Option<String>,
@@ -176,36 +176,48 @@ pub async fn handle_qr_scan(image_url: Option<&str>) -> String {
 }
 
 pub async fn scan_qr_code(image_url: &str) -> Result<MenuContent, Box<dyn Error>> {
-    tracing::info!("Starting QR code scan for URL: {}", image_url);
+    // Inbound MMS photos arrive inline; the provider copy is already deleted.
+    let image_bytes =
+        if let Some((_, bytes)) = crate::services::inbound_media::decode_data_url(image_url) {
+            tracing::info!(
+                "Starting QR code scan for inline image ({} bytes)",
+                bytes.len()
+            );
+            bytes
+        } else {
+            tracing::info!("Starting QR code scan for URL: {}", image_url);
 
-    // Download the image
-    tracing::info!("Downloading image...");
-    let response = match reqwest::get(image_url).await {
-        Ok(resp) => {
-            if !resp.status().is_success() {
-                tracing::error!("Failed to download image. Status: {}", resp.status());
-                return Err(format!("Failed to download image. Status: {}", resp.status()).into());
-            }
-            resp
-        }
-        Err(e) => {
-            tracing::error!("Failed to make request: {}", e);
-            return Err(Box::new(e));
-        }
-    };
+            // Download the image
+            tracing::info!("Downloading image...");
+            let response = match reqwest::get(image_url).await {
+                Ok(resp) => {
+                    if !resp.status().is_success() {
+                        tracing::error!("Failed to download image. Status: {}", resp.status());
+                        return Err(
+                            format!("Failed to download image. Status: {}", resp.status()).into(),
+                        );
+                    }
+                    resp
+                }
+                Err(e) => {
+                    tracing::error!("Failed to make request: {}", e);
+                    return Err(Box::new(e));
+                }
+            };
 
-    // Get image bytes
-    tracing::info!("Getting image bytes...");
-    let image_bytes = match response.bytes().await {
-        Ok(bytes) => {
-            tracing::info!("Downloaded {} bytes", bytes.len());
-            bytes
-        }
-        Err(e) => {
-            tracing::error!("Failed to get image bytes: {}", e);
-            return Err(Box::new(e));
-        }
-    };
+            // Get image bytes
+            tracing::info!("Getting image bytes...");
+            match response.bytes().await {
+                Ok(bytes) => {
+                    tracing::info!("Downloaded {} bytes", bytes.len());
+                    bytes.to_vec()
+                }
+                Err(e) => {
+                    tracing::error!("Failed to get image bytes: {}", e);
+                    return Err(Box::new(e));
+                }
+            }
+        };
 
     // Convert bytes to image
     tracing::info!("Converting bytes to image...");
@@ -45,7 +45,6 @@ pub async fn get_mcp_tools_for_user(
         user_id
     );
 
-    let mcp_client = McpClientService::new();
     let server_count = servers.len();
 
     for server in servers {
@@ -72,6 +71,17 @@ pub async fn get_mcp_tools_for_user(
             }
         };
 
+        let mcp_client = match mcp_repository.client_for_server(&server) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!(
+                    "Failed to load OAuth settings for MCP server '{}': {}",
+                    server.name, e
+                );
+                continue;
+            }
+        };
+
         // List tools from this server
         match mcp_client.list_tools(&url, auth_token.as_deref()).await {
             Ok(mcp_tools) => {
@@ -144,49 +154,9 @@ pub async fn handle_mcp_tool_call(
         server_name, actual_tool_name, user_id
     );
 
-    let mcp_repository = McpRepository::new(state.pg_pool.clone());
-
-    // Look up server config
-    let server = match mcp_repository.get_server_by_name(user_id, server_name) {
-        Ok(Some(s)) => s,
-        Ok(None) => {
-            return format!(
-                "MCP server '{}' not found or not enabled for your account",
-                server_name
-            );
-        }
-        Err(e) => {
-            error!("Failed to get MCP server '{}': {}", server_name, e);
-            return format!("Error accessing MCP server: {}", e);
-        }
-    };
-
-    // Check if server is enabled
-    if server.is_enabled != 1 {
-        return format!("MCP server '{}' is currently disabled", server_name);
-    }
-
-    // Decrypt credentials
-    let url = match mcp_repository.get_decrypted_url(&server) {
-        Ok(u) => u,
-        Err(e) => {
-            error!(
-                "Failed to decrypt URL for MCP server '{}': {}",
-                server_name, e
-            );
-            return "Error: Failed to access server credentials".to_string();
-        }
-    };
-
-    let auth_token = match mcp_repository.get_decrypted_auth_token(&server) {
-        Ok(t) => t,
-        Err(e) => {
-            error!(
-                "Failed to decrypt auth token for MCP server '{}': {}",
-                server_name, e
-            );
-            return "Error: Failed to access server credentials".to_string();
-        }
+    let (mcp_client, url, auth_token) = match resolve_server(state, user_id, server_name) {
+        Ok(connection) => connection,
+        Err(e) => return e,
     };
 
     // Parse arguments
@@ -199,9 +169,22 @@ pub async fn handle_mcp_tool_call(
     };
 
     // Call the MCP tool
-    let mcp_client = McpClientService::new();
     match mcp_client
-        .call_tool(&url, auth_token.as_deref(), actual_tool_name, args)
+        .call_tool_with_progress(
+            &url,
+            auth_token.as_deref(),
+            actual_tool_name,
+            args,
+            |progress| {
+                debug!(
+                    "MCP tool '{}' progress {}/{:?}: {}",
+                    actual_tool_name,
+                    progress.progress,
+                    progress.total,
+                    progress.message.as_deref().unwrap_or("")
+                );
+            },
+        )
         .await
     {
         Ok(result) => {
@@ -221,6 +204,94 @@ pub async fn handle_mcp_tool_call(
     }
 }
 
+/// Read a resource from one of the user's MCP servers as text.
+///
+/// Binary contents are summarised rather than inlined.
+pub async fn read_mcp_resource(
+    state: &Arc<AppState>,
+    user_id: i32,
+    server_name: &str,
+    uri: &str,
+) -> Result<String, String> {
+    let (mcp_client, url, auth_token) = resolve_server(state, user_id, server_name)?;
+    let contents = mcp_client
+        .read_resource(&url, auth_token.as_deref(), uri)
+        .await
+        .map_err(|e| format!("MCP resource error: {}", e))?;
+    let parts: Vec<String> = contents
+        .into_iter()
+        .map(|c| match c.text {
+            Some(text) => text,
+            None => format!(
+                "[binary resource {} ({})]",
+                c.uri,
+                c.mime_type.as_deref().unwrap_or("unknown type")
+            ),
+        })
+        .collect();
+    Ok(parts.join("\n"))
+}
+
+/// Look up an enabled server by name and decrypt its URL and auth token,
+/// returning a client set up for its auth. Errors are user-facing.
+fn resolve_server(
+    state: &Arc<AppState>,
+    user_id: i32,
+    server_name: &str,
+) -> Result<(McpClientService, String, Option<String>), String> {
+    let mcp_repository = McpRepository::new(state.pg_pool.clone());
+
+    // Look up server config
+    let server = match mcp_repository.get_server_by_name(user_id, server_name) {
+        Ok(Some(s)) => s,
+        Ok(None) => {
+            return Err(format!(
+                "MCP server '{}' not found or not enabled for your account",
+                server_name
+            ));
+        }
+        Err(e) => {
+            error!("Failed to get MCP server '{}': {}", server_name, e);
+            return Err(format!("Error accessing MCP server: {}", e));
+        }
+    };
+
+    // Check if server is enabled
+    if server.is_enabled != 1 {
+        return Err(format!(
+            "MCP server '{}' is currently disabled",
+            server_name
+        ));
+    }
+
+    // Decrypt credentials
+    let url = mcp_repository.get_decrypted_url(&server).map_err(|e| {
+        error!(
+            "Failed to decrypt URL for MCP server '{}': {}",
+            server_name, e
+        );
+        "Error: Failed to access server credentials".to_string()
+    })?;
+    let auth_token = mcp_repository
+        .get_decrypted_auth_token(&server)
+        .map_err(|e| {
+            error!(
+                "Failed to decrypt auth token for MCP server '{}': {}",
+                server_name, e
+            );
+            "Error: Failed to access server credentials".to_string()
+        })?;
+    let mcp_client = mcp_repository.client_for_server(&server).map_err(|e| {
+        error!(
+            "Failed to load OAuth settings for MCP server '{}': {}",
+            server_name, e
+        );
+        "Error: Failed to access server credentials".to_string()
+    })?;
+
+    Ok((mcp_client, url, auth_token))
+}
+
 /// Check if a tool name is an MCP tool
 pub fn is_mcp_tool(tool_name: &str) -> bool {
     tool_name.starts_with("mcp:")
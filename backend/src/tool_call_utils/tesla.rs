@@ -19,7 +19,7 @@ pub fn get_tesla_control_tool() -> openai_api_rs::v1::chat_completion::Tool {
         "command".to_string(),
         Box::new(types::JSONSchemaDefine {
             schema_type: Some(types::JSONSchemaType::String),
-            description: Some("Command to execute: 'lock', 'unlock', 'climate_on', 'climate_off', 'defrost', 'remote_start', 'charge_status', 'cabin_overheat_on', 'cabin_overheat_off', 'cabin_overheat_fan_only', or 'precondition_battery'".to_string()),
+            description: Some("Command to execute: 'lock', 'unlock', 'climate_on', 'climate_off', 'defrost', 'remote_start', 'charge_status', 'cabin_overheat_on', 'cabin_overheat_off', 'cabin_overheat_fan_only', 'precondition_battery', or 'navigate'".to_string()),
             enum_values: Some(vec![
                 "lock".to_string(),
                 "unlock".to_string(),
@@ -32,6 +32,7 @@ pub fn get_tesla_control_tool() -> openai_api_rs::v1::chat_completion::Tool {
                 "cabin_overheat_off".to_string(),
                 "cabin_overheat_fan_only".to_string(),
                 "precondition_battery".to_string(),
+                "navigate".to_string(),
             ]),
             ..Default::default()
         }),
@@ -46,12 +47,30 @@ pub fn get_tesla_control_tool() -> openai_api_rs::v1::chat_completion::Tool {
         }),
     );
 
+    properties.insert(
+        "destination".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::String),
+            description: Some("For navigate: an address, place name or 'lat,lon' coordinates to send to the car's navigation.".to_string()),
+            ..Default::default()
+        }),
+    );
+
+    properties.insert(
+        "message_id".to_string(),
+        Box::new(types::JSONSchemaDefine {
+            schema_type: Some(types::JSONSchemaType::Number),
+            description: Some("For navigate: the [id=N] of a '[Location] ...' message from query_message. Sends that shared location to the car; use instead of destination when someone shared a pin.".to_string()),
+            ..Default::default()
+        }),
+    );
+
     chat_completion::Tool {
         r#type: chat_completion::ToolType::Function,
         function: types::Function {
             name: String::from("control_tesla"),
             description: Some(String::from(
-                "Control Tesla vehicle functions: lock/unlock doors, start/stop climate control, defrost vehicle (max heat + heated seats/steering wheel for deep ice), remote start driving, check charge status, control cabin overheat protection (on/off/fan-only), or precondition battery for fast charging (warms battery by setting nav to distant Supercharger - use when user is leaving within 30 min to charge). For climate_on/defrost, can optionally notify user when car is ready.",
+                "Control Tesla vehicle functions: lock/unlock doors, start/stop climate control, defrost vehicle (max heat + heated seats/steering wheel for deep ice), remote start driving, check charge status, control cabin overheat protection (on/off/fan-only), precondition battery for fast charging (warms battery by setting nav to distant Supercharger - use when user is leaving within 30 min to charge), or navigate to a destination (an address, or a location someone shared in a chat). For climate_on/defrost, can optionally notify user when car is ready.",
             )),
             parameters: types::FunctionParameters {
                 schema_type: types::JSONSchemaType::Object,
@@ -118,6 +137,17 @@ pub async fn handle_tesla_command(
         return "You haven't connected your Tesla account yet. Please connect it first in the app settings.".to_string();
     }
 
+    // Resolve the navigation target before waking the car, so a bad
+    // message id or missing destination costs nothing.
+    let navigation = if command == "navigate" {
+        match resolve_navigation_target(state, user_id, &args_value) {
+            Ok(target) => Some(target),
+            Err(msg) => return msg,
+        }
+    } else {
+        None
+    };
+
     // Get valid access token
     let access_token = match get_valid_tesla_access_token(state, user_id).await {
         Ok(token) => token,
@@ -164,31 +194,7 @@ pub async fn handle_tesla_command(
         );
     }
 
-    // Try to use selected vehicle, fall back to first vehicle if none selected
-    let selected_vin = state
-        .user_repository
-        .get_selected_vehicle_vin(user_id)
-        .ok()
-        .flatten();
-
-    let vehicle = if let Some(vin) = selected_vin.as_ref() {
-        match vehicles.iter().find(|v| &v.vin == vin) {
-            Some(v) => {
-                info!("Using selected vehicle with VIN: {}", vin);
-                v
-            }
-            None => {
-                info!(
-                    "Selected vehicle VIN {} not found, falling back to first vehicle",
-                    vin
-                );
-                &vehicles[0]
-            }
-        }
-    } else {
-        info!("No vehicle selected, using first vehicle");
-        &vehicles[0]
-    };
+    let vehicle = select_vehicle(state, user_id, &vehicles);
 
     let vehicle_id = vehicle.id.to_string();
     let vehicle_vin = &vehicle.vin; // VIN is required for signed commands
@@ -242,6 +248,7 @@ pub async fn handle_tesla_command(
         vehicle_vin,
         vehicle_name,
         command,
+        navigation.as_ref(),
     )
     .await;
 
@@ -261,12 +268,93 @@ pub async fn handle_tesla_command(
     result
 }
 
+/// Battery level (percent) of the user's selected vehicle, read from the
+/// vehicle data without waking the car. Used by rule conditions that
+/// compare against a number.
+pub async fn get_battery_level(state: &Arc<AppState>, user_id: i32) -> Result<f64, String> {
+    let user = state
+        .user_core
+        .find_by_id(user_id)
+        .map_err(|e| format!("Failed to get user: {}", e))?
+        .ok_or("User not found")?;
+    if user.sub_tier != Some("tier 2".to_string()) {
+        return Err("Tesla control requires a Tier 2 subscription".to_string());
+    }
+    if !state
+        .user_repository
+        .has_active_tesla(user_id)
+        .map_err(|e| format!("Failed to check Tesla connection: {}", e))?
+    {
+        return Err("Tesla not connected".to_string());
+    }
+
+    let access_token = get_valid_tesla_access_token(state, user_id)
+        .await
+        .map_err(|(_, msg)| msg)?;
+    let region = state
+        .user_repository
+        .get_tesla_region(user_id)
+        .map_err(|e| format!("Failed to get Tesla region: {}", e))?;
+    let tesla_client = TeslaClient::new_with_proxy(&region);
+
+    let vehicles = tesla_client
+        .get_vehicles(&access_token)
+        .await
+        .map_err(|e| format!("Failed to get vehicles: {}", e))?;
+    if vehicles.is_empty() {
+        return Err("No vehicles found".to_string());
+    }
+    let vehicle = select_vehicle(state, user_id, &vehicles);
+
+    let data = tesla_client
+        .get_vehicle_data(&access_token, &vehicle.vin)
+        .await
+        .map_err(|e| format!("Failed to get vehicle data: {}", e))?;
+    data.charge_state
+        .map(|charge_state| f64::from(charge_state.battery_level))
+        .ok_or_else(|| "No charge state".to_string())
+}
+
+/// The user's selected vehicle, falling back to the first one if none is
+/// selected or the selection is gone. `vehicles` must not be empty.
+fn select_vehicle<'a>(
+    state: &Arc<AppState>,
+    user_id: i32,
+    vehicles: &'a [crate::api::tesla::TeslaVehicle],
+) -> &'a crate::api::tesla::TeslaVehicle {
+    let selected_vin = state
+        .user_repository
+        .get_selected_vehicle_vin(user_id)
+        .ok()
+        .flatten();
+
+    if let Some(vin) = selected_vin.as_ref() {
+        match vehicles.iter().find(|v| &v.vin == vin) {
+            Some(v) => {
+                info!("Using selected vehicle with VIN: {}", vin);
+                v
+            }
+            None => {
+                info!(
+                    "Selected vehicle VIN {} not found, falling back to first vehicle",
+                    vin
+                );
+                &vehicles[0]
+            }
+        }
+    } else {
+        info!("No vehicle selected, using first vehicle");
+        &vehicles[0]
+    }
+}
+
 async fn execute_tesla_command(
     tesla_client: &crate::api::tesla::TeslaClient,
     access_token: &str,
     vehicle_vin: &str,
     vehicle_name: &str,
     command: &str,
+    navigation: Option<&NavigationTarget>,
 ) -> String {
     match command {
         "lock" => {
@@ -435,8 +523,79 @@ async fn execute_tesla_command(
                 Err(e) => format!("Error starting navigation for preconditioning: {}", e),
             }
         }
+        "navigate" => {
+            let Some(target) = navigation else {
+                return "No destination given for navigation.".to_string();
+            };
+            match tesla_client.share_destination(access_token, vehicle_vin, &target.destination).await {
+                Ok(true) => format!("Sent {} to your {}'s navigation.", target.description, vehicle_name),
+                Ok(false) => format!("Failed to send the destination to your {}. This may be a temporary Tesla server issue.", vehicle_name),
+                Err(e) => format!("Error sending destination. This may be a Tesla server or connectivity issue. {}", e),
+            }
+        }
+        _ => {
+            format!("Unknown Tesla command: '{}'. Available commands are: lock, unlock, climate_on, climate_off, defrost, remote_start, charge_status, cabin_overheat_on, cabin_overheat_off, cabin_overheat_fan_only, precondition_battery, navigate", command)
+        }
+    }
+}
+
+/// Where `navigate` sends the car, and how to name it in the reply.
+struct NavigationTarget {
+    destination: String,
+    description: String,
+}
+
+/// A shared location's coordinates when `message_id` points at one,
+/// otherwise the free-text `destination`.
+fn resolve_navigation_target(
+    state: &Arc<AppState>,
+    user_id: i32,
+    args: &Value,
+) -> Result<NavigationTarget, String> {
+    let message_id = args["message_id"].as_i64().or_else(|| {
+        args["message_id"]
+            .as_str()
+            .and_then(|id| id.trim().parse().ok())
+    });
+    if let Some(message_id) = message_id {
+        let message = state
+            .ontology_repository
+            .get_message_by_id_for_user(user_id, message_id)
+            .map_err(|e| {
+                error!("Failed to look up message {}: {}", message_id, e);
+                "Error: Failed to look up that message".to_string()
+            })?
+            .ok_or_else(|| format!("No message with id {} found.", message_id))?;
+        let (Some(lat), Some(lon)) = (message.location_lat, message.location_lon) else {
+            return Err(format!(
+                "Message {} is not a shared location. Pass the address as destination instead.",
+                message_id
+            ));
+        };
+        let location = crate::services::shared_location::SharedLocation {
+            lat,
+            lon,
+            label: message.location_label,
+            place: message.location_place,
+        };
+        let name = location
+            .label
+            .clone()
+            .or_else(|| location.place.clone())
+            .unwrap_or_else(|| location.coordinates());
+        return Ok(NavigationTarget {
+            destination: location.coordinates(),
+            description: format!("{}'s location ({})", message.sender_name, name),
+        });
+    }
+
+    match args["destination"].as_str().map(str::trim) {
+        Some(destination) if !destination.is_empty() => Ok(NavigationTarget {
+            destination: destination.to_string(),
+            description: destination.to_string(),
+        }),
         _ => {
-            format!("Unknown Tesla command: '{}'. Available commands are: lock, unlock, climate_on, climate_off, defrost, remote_start, charge_status, cabin_overheat_on, cabin_overheat_off, cabin_overheat_fan_only, precondition_battery", command)
+            Err("Tell me where to navigate: an address, or a location someone shared.".to_string())
         }
     }
 }
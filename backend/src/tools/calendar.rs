@@ -0,0 +1,240 @@
+use openai_api_rs::v1::{chat_completion, types};
+use std::collections::HashMap;
+
+use crate::api::caldav::{parse_user_time, CalendarEvent, EventPatch, EventTime};
+use crate::repositories::caldav_repository::CalDavRepository;
+use crate::services::calendar_service;
+use crate::tools::registry::{ToolContext, ToolHandler, ToolResult};
+use crate::AppState;
+
+const DEFAULT_LIST_DAYS: i64 = 7;
+const MAX_LIST_DAYS: i64 = 60;
+const MAX_LISTED_EVENTS: usize = 30;
+
+fn string_property(description: &str) -> Box<types::JSONSchemaDefine> {
+    Box::new(types::JSONSchemaDefine {
+        schema_type: Some(types::JSONSchemaType::String),
+        description: Some(description.to_string()),
+        ..Default::default()
+    })
+}
+
+fn optional_text(args: &serde_json::Value, key: &str) -> Option<String> {
+    args[key]
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Default length for events created with only a start time.
+fn default_end(start: &EventTime) -> EventTime {
+    match *start {
+        EventTime::Date(date) => EventTime::Date(date + chrono::Duration::days(1)),
+        EventTime::Instant(at) => EventTime::Instant(at + chrono::Duration::hours(1)),
+        EventTime::Floating(at) => EventTime::Floating(at + chrono::Duration::hours(1)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CalendarHandler - list/create/update events on the user's CalDAV calendars
+// ---------------------------------------------------------------------------
+
+pub struct CalendarHandler;
+
+#[async_trait::async_trait]
+impl ToolHandler for CalendarHandler {
+    fn name(&self) -> &'static str {
+        "manage_calendar"
+    }
+
+    fn definition(&self) -> chat_completion::Tool {
+        let mut properties = HashMap::new();
+
+        properties.insert(
+            "action".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::String),
+                description: Some(
+                    "'list' upcoming events, 'create' a new event, or 'update' an existing one by uid."
+                        .to_string(),
+                ),
+                enum_values: Some(vec![
+                    "list".to_string(),
+                    "create".to_string(),
+                    "update".to_string(),
+                ]),
+                ..Default::default()
+            }),
+        );
+        properties.insert(
+            "days".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::Number),
+                description: Some(
+                    "For 'list': how many days ahead to look, starting now (default 7, max 60)."
+                        .to_string(),
+                ),
+                ..Default::default()
+            }),
+        );
+        properties.insert(
+            "uid".to_string(),
+            string_property("For 'update': the event uid, as shown by 'list'."),
+        );
+        properties.insert(
+            "title".to_string(),
+            string_property("Event title. Required for 'create'."),
+        );
+        properties.insert(
+            "start".to_string(),
+            string_property(
+                "Local wall-clock start in the user's timezone, e.g. '2026-03-19T14:30', or a date '2026-03-19' for an all-day event. Required for 'create'.",
+            ),
+        );
+        properties.insert(
+            "end".to_string(),
+            string_property(
+                "Local wall-clock end, same format as start. Defaults to one hour (or one day for all-day events).",
+            ),
+        );
+        properties.insert("location".to_string(), string_property("Event location."));
+        properties.insert(
+            "description".to_string(),
+            string_property("Longer notes for the event."),
+        );
+        properties.insert(
+            "calendar".to_string(),
+            string_property(
+                "For 'create': calendar name to add the event to. Defaults to the user's first calendar.",
+            ),
+        );
+
+        chat_completion::Tool {
+            r#type: chat_completion::ToolType::Function,
+            function: types::Function {
+                name: "manage_calendar".to_string(),
+                description: Some(
+                    "Read and edit the user's connected CalDAV calendars (Nextcloud, Fastmail, iCloud...). Use 'list' for questions like 'what's on my calendar tomorrow', 'create' to add a meeting or appointment, 'update' to move or rename one. Ask for clarification instead of guessing a date or time."
+                        .to_string(),
+                ),
+                parameters: types::FunctionParameters {
+                    schema_type: types::JSONSchemaType::Object,
+                    properties: Some(properties),
+                    required: Some(vec!["action".to_string()]),
+                },
+            },
+        }
+    }
+
+    fn enabled_for_user(&self, state: &AppState, user_id: i32) -> bool {
+        CalDavRepository::new(state.pg_pool.clone()).has_enabled_connection(user_id)
+    }
+
+    fn is_restricted(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: ToolContext<'_>) -> Result<ToolResult, String> {
+        let args: serde_json::Value =
+            serde_json::from_str(ctx.arguments).map_err(|e| format!("Invalid JSON: {}", e))?;
+        let tz = calendar_service::user_timezone(ctx.state, ctx.user_id);
+
+        match args["action"].as_str().unwrap_or("list") {
+            "list" => {
+                let days = args["days"]
+                    .as_f64()
+                    .map(|d| d as i64)
+                    .unwrap_or(DEFAULT_LIST_DAYS)
+                    .clamp(1, MAX_LIST_DAYS);
+                let start = chrono::Utc::now();
+                let end = start + chrono::Duration::days(days);
+                let events =
+                    calendar_service::events_between(ctx.state, ctx.user_id, start, end).await?;
+                if events.is_empty() {
+                    return Ok(ToolResult::Answer(format!(
+                        "No calendar events in the next {} day(s).",
+                        days
+                    )));
+                }
+                let mut lines: Vec<String> = events
+                    .iter()
+                    .take(MAX_LISTED_EVENTS)
+                    .map(|e| {
+                        format!(
+                            "[uid={}] {} ({})",
+                            e.event.uid,
+                            e.event.describe(&tz),
+                            e.calendar
+                        )
+                    })
+                    .collect();
+                if events.len() > MAX_LISTED_EVENTS {
+                    lines.push(format!("...and {} more", events.len() - MAX_LISTED_EVENTS));
+                }
+                Ok(ToolResult::Answer(lines.join("\n")))
+            }
+            "create" => {
+                let title = optional_text(&args, "title")
+                    .ok_or_else(|| "'title' is required to create an event".to_string())?;
+                let start = optional_text(&args, "start")
+                    .ok_or_else(|| "'start' is required to create an event".to_string())?;
+                let start = parse_user_time(&start, &tz)?;
+                let end = match optional_text(&args, "end") {
+                    Some(end) => parse_user_time(&end, &tz)?,
+                    None => default_end(&start),
+                };
+                if end.to_utc(&tz) < start.to_utc(&tz) {
+                    return Err("Event end is before its start".to_string());
+                }
+                let event = CalendarEvent {
+                    uid: format!("{}@lightfriend", uuid::Uuid::new_v4()),
+                    summary: title,
+                    start,
+                    end: Some(end),
+                    location: optional_text(&args, "location"),
+                    description: optional_text(&args, "description"),
+                };
+                let calendar = calendar_service::create_event(
+                    ctx.state,
+                    ctx.user_id,
+                    optional_text(&args, "calendar").as_deref(),
+                    &event,
+                )
+                .await?;
+                Ok(ToolResult::Answer(format!(
+                    "Added to {}: {} [uid={}]",
+                    calendar,
+                    event.describe(&tz),
+                    event.uid
+                )))
+            }
+            "update" => {
+                let uid = optional_text(&args, "uid")
+                    .ok_or_else(|| "'uid' is required to update an event".to_string())?;
+                let patch = EventPatch {
+                    summary: optional_text(&args, "title"),
+                    start: optional_text(&args, "start")
+                        .map(|s| parse_user_time(&s, &tz))
+                        .transpose()?,
+                    end: optional_text(&args, "end")
+                        .map(|s| parse_user_time(&s, &tz))
+                        .transpose()?,
+                    location: optional_text(&args, "location"),
+                    description: optional_text(&args, "description"),
+                };
+                if patch.is_empty() {
+                    return Err("Nothing to update".to_string());
+                }
+                let (event, calendar) =
+                    calendar_service::update_event(ctx.state, ctx.user_id, &uid, &patch).await?;
+                Ok(ToolResult::Answer(format!(
+                    "Updated in {}: {}",
+                    calendar,
+                    event.describe(&tz)
+                )))
+            }
+            other => Err(format!("Unknown calendar action '{}'", other)),
+        }
+    }
+}
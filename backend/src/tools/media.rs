@@ -0,0 +1,254 @@
+use openai_api_rs::v1::{chat_completion, types};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::channels::traits::MediaRef;
+use crate::rendering::canvas::RenderedImage;
+use crate::rendering::chart::{render_chart, ChartSpec, ChartStyle};
+use crate::rendering::map::{render_map, MapMarker};
+use crate::rendering::qr::render_qr;
+use crate::repositories::user_repository::LogUsageParams;
+use crate::tools::registry::{ToolContext, ToolHandler, ToolResult};
+
+// ─── send_image ──────────────────────────────────────────────────────────────
+
+pub struct SendImageHandler;
+
+#[derive(Deserialize)]
+struct SendImageArgs {
+    kind: String,
+    #[serde(default)]
+    caption: Option<String>,
+    #[serde(default)]
+    qr_data: Option<String>,
+    #[serde(default)]
+    markers: Vec<MapMarker>,
+    #[serde(default)]
+    chart_title: Option<String>,
+    #[serde(default)]
+    chart_unit: Option<String>,
+    #[serde(default)]
+    chart_labels: Vec<String>,
+    #[serde(default)]
+    chart_values: Vec<f64>,
+    #[serde(default)]
+    chart_style: Option<ChartStyle>,
+}
+
+async fn render(args: &SendImageArgs) -> Result<RenderedImage, String> {
+    let result = match args.kind.as_str() {
+        "qr_code" => {
+            let data = args
+                .qr_data
+                .as_deref()
+                .ok_or("qr_data is required for a QR code")?;
+            render_qr(data)
+        }
+        "map" => render_map(&args.markers).await,
+        "chart" => render_chart(&ChartSpec {
+            title: args.chart_title.clone().unwrap_or_default(),
+            unit: args.chart_unit.clone(),
+            labels: args.chart_labels.clone(),
+            values: args.chart_values.clone(),
+            style: args.chart_style.unwrap_or_default(),
+        }),
+        other => return Err(format!("Unknown image kind '{}'", other)),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for SendImageHandler {
+    fn name(&self) -> &'static str {
+        "send_image"
+    }
+
+    fn definition(&self) -> chat_completion::Tool {
+        let string = |description: &str| {
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::String),
+                description: Some(description.to_string()),
+                ..Default::default()
+            })
+        };
+        let mut marker_properties = HashMap::new();
+        marker_properties.insert(
+            "latitude".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::Number),
+                ..Default::default()
+            }),
+        );
+        marker_properties.insert(
+            "longitude".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::Number),
+                ..Default::default()
+            }),
+        );
+        marker_properties.insert("label".to_string(), string("Short place name"));
+
+        let mut properties = HashMap::new();
+        properties.insert(
+            "kind".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::String),
+                description: Some("What to draw.".to_string()),
+                enum_values: Some(vec!["qr_code".into(), "map".into(), "chart".into()]),
+                ..Default::default()
+            }),
+        );
+        properties.insert(
+            "caption".to_string(),
+            string("Short text sent along with the image."),
+        );
+        properties.insert(
+            "qr_data".to_string(),
+            string("qr_code only. The exact text or URL to encode, e.g. the boarding pass or ticket code found in an email. Never invent it."),
+        );
+        properties.insert(
+            "markers".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::Array),
+                description: Some("map only. 1-8 places, in route order when showing a route (e.g. the user's car, then the supercharger).".to_string()),
+                items: Some(Box::new(types::JSONSchemaDefine {
+                    schema_type: Some(types::JSONSchemaType::Object),
+                    properties: Some(marker_properties),
+                    required: Some(vec!["latitude".to_string(), "longitude".to_string()]),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }),
+        );
+        properties.insert("chart_title".to_string(), string("chart only. Title."));
+        properties.insert(
+            "chart_unit".to_string(),
+            string("chart only. Unit of the values, e.g. '°C' or 'EUR'."),
+        );
+        properties.insert(
+            "chart_labels".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::Array),
+                description: Some(
+                    "chart only. One short label per value, e.g. hours or weekdays.".to_string(),
+                ),
+                items: Some(string("Label")),
+                ..Default::default()
+            }),
+        );
+        properties.insert(
+            "chart_values".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::Array),
+                description: Some("chart only. Up to 60 numbers.".to_string()),
+                items: Some(Box::new(types::JSONSchemaDefine {
+                    schema_type: Some(types::JSONSchemaType::Number),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }),
+        );
+        properties.insert(
+            "chart_style".to_string(),
+            Box::new(types::JSONSchemaDefine {
+                schema_type: Some(types::JSONSchemaType::String),
+                description: Some("chart only. Default: line.".to_string()),
+                enum_values: Some(vec!["line".into(), "bar".into()]),
+                ..Default::default()
+            }),
+        );
+
+        chat_completion::Tool {
+            r#type: chat_completion::ToolType::Function,
+            function: types::Function {
+                name: self.name().to_string(),
+                description: Some("Send the user a picture message: a QR code (boarding pass, ticket), a static map with markers (a place, a charger, a short route) or a line/bar chart (forecast, prices). Use only when a picture helps more than text. Phones without picture messages get a text version automatically. Use data from other tools; never make up coordinates or codes.".to_string()),
+                parameters: types::FunctionParameters {
+                    schema_type: types::JSONSchemaType::Object,
+                    properties: Some(properties),
+                    required: Some(vec!["kind".to_string()]),
+                },
+            },
+        }
+    }
+
+    async fn execute(&self, ctx: ToolContext<'_>) -> Result<ToolResult, String> {
+        let args: SendImageArgs = serde_json::from_str(ctx.arguments)
+            .map_err(|e| format!("Invalid send_image arguments: {}", e))?;
+        let image = render(&args).await?;
+        let alt_text = image.alt_text.clone();
+
+        // Web chat replies are text only, so hand the text version back.
+        if ctx.skip_sms {
+            return Ok(ToolResult::Answer(format!(
+                "Images cannot be shown here. Give the user this instead: {}",
+                alt_text
+            )));
+        }
+
+        // A picture message is billed like any other outbound message.
+        if let Err(e) =
+            crate::utils::usage::check_user_credits(ctx.state, ctx.user, "message", None).await
+        {
+            tracing::warn!("Not sending image to user {}: {}", ctx.user_id, e);
+            return Ok(ToolResult::Answer(format!(
+                "The image could not be sent ({}). Give the user this instead: {}",
+                e, alt_text
+            )));
+        }
+
+        let caption = args.caption.unwrap_or_default();
+        let token = crate::rendering::store::put(image);
+        let (sent, body) = match crate::rendering::store::public_url(&token) {
+            Some(url) => (
+                ctx.state
+                    .channel_router
+                    .send_to_user(ctx.user, &caption, Some(MediaRef::Url(url)))
+                    .await,
+                caption,
+            ),
+            None => {
+                tracing::warn!("SERVER_URL not set; sending rendered image as text");
+                let body = format!("{}\n\n{}", caption, alt_text).trim().to_string();
+                (
+                    ctx.state
+                        .channel_router
+                        .send_to_user(ctx.user, &body, None)
+                        .await,
+                    body,
+                )
+            }
+        };
+        let (sid, error_status) = match &sent {
+            Ok(sid) => (Some(sid.as_str().to_string()), None),
+            Err(e) => (None, Some(format!("failed to send: {}", e))),
+        };
+        if let Err(e) = ctx.state.user_repository.log_usage(LogUsageParams {
+            user_id: ctx.user_id,
+            sid,
+            activity_type: "sms".to_string(),
+            credits: None,
+            time_consumed: None,
+            success: error_status.is_some().then_some(false),
+            reason: None,
+            status: error_status,
+            recharge_threshold_timestamp: None,
+            zero_credits_timestamp: None,
+            segments: Some(crate::utils::sms_encoding::outbound_sms_segments(
+                ctx.user, &body,
+            )),
+        }) {
+            tracing::error!("Failed to log image message usage: {}", e);
+        }
+        match sent {
+            Ok(_) => {
+                tracing::info!("Sent rendered {} image to user {}", args.kind, ctx.user_id);
+                Ok(ToolResult::Answer(format!(
+                    "The image was sent to the user as its own message. Its text version: {}. Confirm briefly; do not repeat the contents.",
+                    alt_text
+                )))
+            }
+            Err(e) => Err(format!("Failed to send image: {}", e)),
+        }
+    }
+}
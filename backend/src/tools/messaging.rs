@@ -34,67 +34,144 @@ impl ToolHandler for SendMessageHandler {
     }
 
     async fn execute(&self, ctx: ToolContext<'_>) -> Result<ToolResult, String> {
-        tracing::info!(
-            "SEND_FLOW send_chat_message tool execute() called for user={}, args={}",
-            ctx.user_id,
-            ctx.arguments
-        );
-        match crate::tool_call_utils::bridge::handle_send_chat_message(
-            ctx.state,
-            ctx.user_id,
-            ctx.arguments,
-            ctx.user,
-            ctx.image_url,
-            ctx.skip_sms,
-        )
-        .await
-        {
-            Ok((status, _headers, axum::Json(twilio_response))) => {
-                tracing::info!(
-                    "SEND_FLOW handle_send_chat_message returned OK for user={}, status={}, msg={}",
-                    ctx.user_id,
-                    status,
-                    twilio_response.message
-                );
-                write_outgoing_history(
-                    ctx.state,
-                    ctx.user_id,
-                    "send_chat_message",
-                    &ctx.tool_call_id,
-                    &twilio_response.message,
-                    ctx.current_time,
-                );
-                tracing::info!(
-                    "SEND_FLOW Returning EarlyReturn for user={}, this should spawn delayed task and return immediately",
-                    ctx.user_id
-                );
-                Ok(ToolResult::EarlyReturn {
-                    response: twilio_response,
-                    status,
-                })
-            }
-            Err(e) => {
-                tracing::error!(
-                    "SEND_FLOW handle_send_chat_message FAILED for user={}: {}",
-                    ctx.user_id,
-                    e
-                );
-                write_outgoing_error_history(
-                    ctx.state,
-                    ctx.user_id,
-                    "send_chat_message",
-                    &ctx.tool_call_id,
-                    "Failed to send chat message",
-                    ctx.current_time,
-                );
-                Ok(ToolResult::EarlyReturn {
-                    response: TwilioResponse {
-                        message: "Failed to process chat message request".to_string(),
-                        created_item_id: None,
-                    },
-                    status: StatusCode::INTERNAL_SERVER_ERROR,
-                })
-            }
+        let arguments = ctx.arguments;
+        execute_send_flow(ctx, "send_chat_message", arguments).await
+    }
+}
+
+// ─── react_to_chat_message (outgoing) ───────────────────────────────────────
+
+pub struct ReactMessageHandler;
+
+#[async_trait::async_trait]
+impl ToolHandler for ReactMessageHandler {
+    fn name(&self) -> &'static str {
+        "react_to_chat_message"
+    }
+
+    fn definition(&self) -> chat_completion::Tool {
+        crate::tool_call_utils::bridge::get_react_to_chat_message_tool()
+    }
+
+    fn is_outgoing(&self) -> bool {
+        true
+    }
+
+    fn is_restricted(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: ToolContext<'_>) -> Result<ToolResult, String> {
+        let arguments = ctx.arguments;
+        execute_send_flow(ctx, "react_to_chat_message", arguments).await
+    }
+}
+
+// ─── edit_chat_message (outgoing) ───────────────────────────────────────────
+
+pub struct EditMessageHandler;
+
+#[async_trait::async_trait]
+impl ToolHandler for EditMessageHandler {
+    fn name(&self) -> &'static str {
+        "edit_chat_message"
+    }
+
+    fn definition(&self) -> chat_completion::Tool {
+        crate::tool_call_utils::bridge::get_edit_chat_message_tool()
+    }
+
+    fn is_outgoing(&self) -> bool {
+        true
+    }
+
+    fn is_restricted(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, ctx: ToolContext<'_>) -> Result<ToolResult, String> {
+        // The send flow edits instead of sending when this flag is set.
+        let mut arguments: serde_json::Value =
+            serde_json::from_str(ctx.arguments).map_err(|e| e.to_string())?;
+        if let Some(fields) = arguments.as_object_mut() {
+            fields.insert(
+                "edit_last_message".to_string(),
+                serde_json::Value::Bool(true),
+            );
+        }
+        let arguments = arguments.to_string();
+        execute_send_flow(ctx, "edit_chat_message", &arguments).await
+    }
+}
+
+/// Shared by the three chat tools: resolve the chat, queue the send behind
+/// the 60-second cancel window and record the outgoing history entry.
+async fn execute_send_flow(
+    ctx: ToolContext<'_>,
+    tool_name: &str,
+    arguments: &str,
+) -> Result<ToolResult, String> {
+    tracing::info!(
+        "SEND_FLOW {} tool execute() called for user={}, args={}",
+        tool_name,
+        ctx.user_id,
+        arguments
+    );
+    match crate::tool_call_utils::bridge::handle_send_chat_message(
+        ctx.state,
+        ctx.user_id,
+        arguments,
+        ctx.user,
+        ctx.image_url,
+        ctx.skip_sms,
+    )
+    .await
+    {
+        Ok((status, _headers, axum::Json(twilio_response))) => {
+            tracing::info!(
+                "SEND_FLOW handle_send_chat_message returned OK for user={}, status={}, msg={}",
+                ctx.user_id,
+                status,
+                twilio_response.message
+            );
+            write_outgoing_history(
+                ctx.state,
+                ctx.user_id,
+                tool_name,
+                &ctx.tool_call_id,
+                &twilio_response.message,
+                ctx.current_time,
+            );
+            tracing::info!(
+                "SEND_FLOW Returning EarlyReturn for user={}, this should spawn delayed task and return immediately",
+                ctx.user_id
+            );
+            Ok(ToolResult::EarlyReturn {
+                response: twilio_response,
+                status,
+            })
+        }
+        Err(e) => {
+            tracing::error!(
+                "SEND_FLOW handle_send_chat_message FAILED for user={}: {}",
+                ctx.user_id,
+                e
+            );
+            write_outgoing_error_history(
+                ctx.state,
+                ctx.user_id,
+                tool_name,
+                &ctx.tool_call_id,
+                "Failed to send chat message",
+                ctx.current_time,
+            );
+            Ok(ToolResult::EarlyReturn {
+                response: TwilioResponse {
+                    message: "Failed to process chat message request".to_string(),
+                    created_item_id: None,
+                },
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+            })
         }
     }
 }
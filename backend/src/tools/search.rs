@@ -92,8 +92,8 @@ impl ToolHandler for QrScanHandler {
 
     async fn execute(&self, ctx: ToolContext<'_>) -> Result<ToolResult, String> {
         tracing::debug!(
-            "Executing scan_qr_code tool call with url: {:#?}",
-            ctx.image_url
+            "Executing scan_qr_code tool call (image attached: {})",
+            ctx.image_url.is_some()
         );
         let response = crate::tool_call_utils::internet::handle_qr_scan(ctx.image_url).await;
         Ok(ToolResult::Answer(response))
@@ -5,6 +5,7 @@ pub use crate::api::matrix_client::{
     is_health_check_message, should_process_message, IncomingBridgeEvent, IncomingMessageContent,
     MatrixClientWrapper, RoomInterface, RoomWrapper,
 };
+use crate::utils::bridgev2_provisioning;
 use crate::UserCoreOps;
 use anyhow::{anyhow, Result};
 use matrix_sdk::{
@@ -143,6 +144,9 @@ pub struct BridgeMessage {
     pub room_name: String,
     pub media_url: Option<String>,
     pub room_id: Option<String>,
+    /// Matrix event to point replies, reactions and edits at.
+    #[serde(default)]
+    pub event_id: Option<String>,
 }
 
 fn format_timestamp(timestamp: i64, timezone: Option<String>) -> String {
@@ -209,7 +213,8 @@ fn room_name_matches_service(room_name: &str, service: &str) -> bool {
         "whatsapp" => room_name.contains("(wa)") || room_name.contains("(wa~)"),
         "telegram" => room_name.contains("(telegram)") || room_name.contains("(tg)"),
         "signal" => room_name.contains("(signal)") || room_name.contains("(signal~)"),
-        _ => false,
+        other => bridgev2_provisioning::network(other)
+            .is_some_and(|network| network.room_name_matches(&room_name)),
     }
 }
 
@@ -296,6 +301,13 @@ pub fn remove_bridge_suffix(chat_name: &str) -> String {
             return trimmed[..end].trim().to_string();
         }
     }
+    if let Some(network) = bridgev2_provisioning::NETWORKS
+        .iter()
+        .find(|network| network.room_name_matches(trimmed))
+    {
+        let end = trimmed.len().saturating_sub(network.room_suffix.len());
+        return trimmed[..end].trim().to_string();
+    }
     trimmed.to_string()
 }
 
@@ -357,6 +369,10 @@ fn infer_service(room_name: &str, sender_localpart: &str) -> Option<String> {
         tracing::debug!("Detected Signal");
         return Some("signal".to_string());
     }
+    if let Some(network) = bridgev2_provisioning::infer_network(&room_name, &sender_localpart) {
+        tracing::debug!("Detected {}", network.name);
+        return Some(network.id.to_string());
+    }
     tracing::debug!("No service detected");
     None
 }
@@ -376,6 +392,12 @@ async fn infer_service_from_room_members(room: &Room) -> Option<String> {
         if localpart.starts_with("signal_") {
             return Some("signal".to_string());
         }
+        if let Some(network) = bridgev2_provisioning::NETWORKS
+            .iter()
+            .find(|network| network.is_ghost(localpart))
+        {
+            return Some(network.id.to_string());
+        }
     }
     None
 }
@@ -628,6 +650,7 @@ pub async fn send_bridge_message_trait(
         room_name,
         media_url: None,
         room_id: Some(room_id_str),
+        event_id: None,
     })
 }
 
@@ -751,19 +774,57 @@ pub fn get_best_matches(bridge_rooms: &[BridgeRoom], search_term: &str) -> Vec<S
     matches.into_iter().take(5).map(|(_, name)| name).collect()
 }
 
-const AI_PROMPT_TEXT: &str = "Hi, I'm Lightfriend, your friend's AI assistant. This message looks time-sensitive—since they're not currently on their computer, would you like me to send them a notification about it? Reply \"yes\" or \"no.\"";
+/// How well a reply/reaction hint ("the dinner question", "Anna's photo")
+/// matches a message: the share of the hint's words found in the sender's
+/// name or the message text. Short filler words are ignored.
+pub fn reply_target_score(hint: &str, sender_name: &str, body: &str) -> f64 {
+    const FILLER: &[&str] = &[
+        "the", "and", "about", "message", "last", "latest", "one", "that", "their", "his", "her",
+        "from", "said", "says",
+    ];
+    let haystack = format!("{} {}", sender_name, body).to_lowercase();
+    let haystack_words: Vec<&str> = haystack
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let words: Vec<String> = hint
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .map(|w| w.trim_end_matches('s').to_string())
+        .filter(|w| w.chars().count() >= 3 && !FILLER.contains(&w.as_str()))
+        .collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    let found = words
+        .iter()
+        .filter(|w| {
+            haystack.contains(w.as_str())
+                || haystack_words
+                    .iter()
+                    .any(|h| strsim::jaro_winkler(w, h) >= 0.9)
+        })
+        .count();
+    found as f64 / words.len() as f64
+}
 
+/// The incoming message a reply or reaction is aimed at: the newest message
+/// from the other side of the chat, or with `hint` the newest one whose
+/// sender or text matches it best ("the dinner question"). Edits and bridge
+/// notices are never targets.
 pub async fn get_triggering_message_in_room(
     service: &str,
     state: &Arc<AppState>,
     user_id: i32,
     room_id_str: &str,
+    hint: Option<&str>,
 ) -> Result<Option<BridgeMessage>> {
     tracing::info!(
-        "Fetching triggering message in {} - User: {}, room_id: {}",
+        "Fetching triggering message in {} - User: {}, room_id: {}, hint: {:?}",
         capitalize(service),
         user_id,
-        room_id_str
+        room_id_str,
+        hint
     );
 
     // Validate bridge connection
@@ -798,111 +859,122 @@ pub async fn get_triggering_message_in_room(
 
     let response = room.messages(options).await?;
 
-    // Sender prefix for bridge bots (incoming messages start with this)
+    // Sender prefix for bridge ghosts (incoming messages start with this)
     let sender_prefix = get_sender_prefix(service);
+    let hint = hint.map(str::trim).filter(|h| !h.is_empty());
 
-    // User's Matrix user ID (for sent messages)
-    let user_matrix_id = client.user_id().ok_or(anyhow!("User ID not available"))?;
-
-    // Iterate through messages from latest to oldest
-    let mut found_prompt = false;
+    let mut best: Option<(f64, BridgeMessage)> = None;
     for event in response.chunk {
-        if let Ok(AnySyncTimelineEvent::MessageLike(
+        let Ok(AnySyncTimelineEvent::MessageLike(
             matrix_sdk::ruma::events::AnySyncMessageLikeEvent::RoomMessage(
                 SyncRoomMessageEvent::Original(e),
             ),
         )) = event.raw().deserialize()
+        else {
+            continue;
+        };
+        let sender_localpart = e.sender.localpart().to_string();
+        if !sender_localpart.starts_with(&sender_prefix)
+            || matches!(e.content.relates_to, Some(Relation::Replacement(_)))
         {
-            let sender_localpart = e.sender.localpart().to_string();
+            continue;
+        }
+        let timestamp = i64::from(e.origin_server_ts.0) / 1000;
+
+        // Extract message type and body
+        let (msgtype, body) = match e.content.msgtype {
+            MessageType::Text(t) => ("text", t.body),
+            MessageType::Image(i) => (
+                "image",
+                if i.body.is_empty() {
+                    "📎 IMAGE".into()
+                } else {
+                    i.body
+                },
+            ),
+            MessageType::Video(v) => (
+                "video",
+                if v.body.is_empty() {
+                    "📎 VIDEO".into()
+                } else {
+                    v.body
+                },
+            ),
+            MessageType::File(f) => (
+                "file",
+                if f.body.is_empty() {
+                    "📎 FILE".into()
+                } else {
+                    f.body
+                },
+            ),
+            MessageType::Audio(a) => (
+                "audio",
+                if a.body.is_empty() {
+                    "📎 AUDIO".into()
+                } else {
+                    a.body
+                },
+            ),
+            MessageType::Location(l) => (
+                "location",
+                crate::services::shared_location::history_text(&l.body, &l.geo_uri),
+            ),
+            MessageType::Emote(t) => ("emote", t.body),
+            _ => continue,
+        };
 
-            if !found_prompt {
-                // Look for the AI prompt sent by the user
-                if e.sender == user_matrix_id && !sender_localpart.starts_with(&sender_prefix) {
-                    let body = match e.content.msgtype {
-                        MessageType::Text(ref t) => t.body.clone(),
-                        _ => continue,
-                    };
-                    if body.contains(AI_PROMPT_TEXT) {
-                        found_prompt = true;
-                        continue; // Skip to the next message (older)
-                    }
-                }
-            } else {
-                // After finding the prompt, look for the next incoming message
-                if sender_localpart.starts_with(&sender_prefix) {
-                    let timestamp = i64::from(e.origin_server_ts.0) / 1000;
-
-                    // Extract message type and body
-                    let (msgtype, body) = match e.content.msgtype {
-                        MessageType::Text(t) => ("text", t.body),
-                        MessageType::Notice(n) => ("notice", n.body),
-                        MessageType::Image(i) => (
-                            "image",
-                            if i.body.is_empty() {
-                                "📎 IMAGE".into()
-                            } else {
-                                i.body
-                            },
-                        ),
-                        MessageType::Video(v) => (
-                            "video",
-                            if v.body.is_empty() {
-                                "📎 VIDEO".into()
-                            } else {
-                                v.body
-                            },
-                        ),
-                        MessageType::File(f) => (
-                            "file",
-                            if f.body.is_empty() {
-                                "📎 FILE".into()
-                            } else {
-                                f.body
-                            },
-                        ),
-                        MessageType::Audio(a) => (
-                            "audio",
-                            if a.body.is_empty() {
-                                "📎 AUDIO".into()
-                            } else {
-                                a.body
-                            },
-                        ),
-                        MessageType::Location(_l) => ("location", "📍 LOCATION".into()),
-                        MessageType::Emote(t) => ("emote", t.body),
-                        _ => continue,
-                    };
+        // Skip error-like messages
+        if is_error_message(&body) {
+            continue;
+        }
 
-                    // Skip error-like messages
-                    if is_error_message(&body) {
-                        continue;
-                    }
+        let sender_display_name = match room.get_member_no_sync(&e.sender).await {
+            Ok(Some(member)) => member
+                .display_name()
+                .map(str::to_string)
+                .unwrap_or_else(|| sender_localpart.clone()),
+            _ => sender_localpart.clone(),
+        };
+        let score = match hint {
+            Some(hint) => reply_target_score(hint, &sender_display_name, &body),
+            None => 1.0,
+        };
+        if score < 0.5
+            || best
+                .as_ref()
+                .is_some_and(|(best_score, _)| *best_score >= score)
+        {
+            continue;
+        }
 
-                    return Ok(Some(BridgeMessage {
-                        sender: e.sender.to_string(),
-                        sender_display_name: sender_localpart,
-                        content: body,
-                        timestamp,
-                        formatted_timestamp: format_timestamp(
-                            timestamp,
-                            user_info.timezone.clone(),
-                        ),
-                        message_type: msgtype.to_string(),
-                        room_name: cleaned_room_name,
-                        media_url: None,
-                        room_id: Some(room_id.to_string()),
-                    }));
-                }
-            }
+        let message = BridgeMessage {
+            sender: e.sender.to_string(),
+            sender_display_name: remove_bridge_suffix(&sender_display_name),
+            content: body,
+            timestamp,
+            formatted_timestamp: format_timestamp(timestamp, user_info.timezone.clone()),
+            message_type: msgtype.to_string(),
+            room_name: cleaned_room_name.clone(),
+            media_url: None,
+            room_id: Some(room_id.to_string()),
+            event_id: Some(e.event_id.to_string()),
+        };
+        // Newest first, so without a hint the first message wins.
+        if score >= 1.0 {
+            return Ok(Some(message));
         }
+        best = Some((score, message));
     }
 
-    // If no triggering message found after the prompt
-    tracing::info!(
-        "No triggering incoming message found before the AI prompt in room '{}'",
-        room_id_str
-    );
-    Ok(None)
+    if best.is_none() {
+        tracing::info!(
+            "No incoming message matching {:?} in the last 100 messages for room '{}'",
+            hint,
+            room_id_str
+        );
+    }
+    Ok(best.map(|(_, message)| message))
 }
 
 pub async fn get_latest_sent_message_in_room(
@@ -964,8 +1036,12 @@ pub async fn get_latest_sent_message_in_room(
         )) = event.raw().deserialize()
         {
             let sender_localpart = e.sender.localpart().to_string();
-            // Check if sender is the user (matches user_matrix_id and not a bridge bot prefix)
-            if e.sender == user_matrix_id && !sender_localpart.starts_with(&sender_prefix) {
+            // Check if sender is the user (matches user_matrix_id and not a bridge bot prefix).
+            // Edits point at the original, which is what a new edit replaces too.
+            if e.sender == user_matrix_id
+                && !sender_localpart.starts_with(&sender_prefix)
+                && !matches!(e.content.relates_to, Some(Relation::Replacement(_)))
+            {
                 let timestamp = i64::from(e.origin_server_ts.0) / 1000;
 
                 // Extract message type and body
@@ -1004,7 +1080,10 @@ pub async fn get_latest_sent_message_in_room(
                             a.body
                         },
                     ),
-                    MessageType::Location(_l) => ("location", "📍 LOCATION".into()),
+                    MessageType::Location(l) => (
+                        "location",
+                        crate::services::shared_location::history_text(&l.body, &l.geo_uri),
+                    ),
                     MessageType::Emote(t) => ("emote", t.body),
                     _ => continue,
                 };
@@ -1024,6 +1103,7 @@ pub async fn get_latest_sent_message_in_room(
                     room_name: cleaned_room_name,
                     media_url: None,
                     room_id: Some(room_id.to_string()),
+                    event_id: Some(e.event_id.to_string()),
                 }));
             }
         }
@@ -1134,6 +1214,7 @@ fn ont_message_to_bridge_message(
         room_name: m.sender_name.clone(),
         media_url: None,
         room_id: Some(m.room_id.clone()),
+        event_id: None,
     }
 }
 
@@ -1564,6 +1645,78 @@ async fn start_chat_telegram(
     ))
 }
 
+/// How a message relates to an earlier one in the chat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeRelation {
+    /// Quote-reply to an incoming message.
+    Reply { event_id: String },
+    /// Replace the text of one of the user's own messages.
+    Edit { event_id: String },
+    /// React with an emoji instead of sending a message.
+    Reaction { event_id: String, key: String },
+}
+
+impl BridgeRelation {
+    pub fn event_id(&self) -> &str {
+        match self {
+            BridgeRelation::Reply { event_id }
+            | BridgeRelation::Edit { event_id }
+            | BridgeRelation::Reaction { event_id, .. } => event_id,
+        }
+    }
+}
+
+/// The event type and content for a text message with an optional relation:
+/// `m.in_reply_to` for replies, `m.replace` with `m.new_content` for edits,
+/// and an `m.annotation` reaction, which carries no text of its own.
+pub fn relation_event_content(
+    message: &str,
+    relation: Option<&BridgeRelation>,
+) -> (&'static str, serde_json::Value) {
+    use serde_json::json;
+
+    match relation {
+        None => (
+            "m.room.message",
+            json!({ "msgtype": "m.text", "body": message }),
+        ),
+        Some(BridgeRelation::Reply { event_id }) => (
+            "m.room.message",
+            json!({
+                "msgtype": "m.text",
+                "body": message,
+                "m.relates_to": { "m.in_reply_to": { "event_id": event_id } },
+            }),
+        ),
+        Some(BridgeRelation::Edit { event_id }) => (
+            "m.room.message",
+            json!({
+                "msgtype": "m.text",
+                "body": format!("* {}", message),
+                "m.new_content": { "msgtype": "m.text", "body": message },
+                "m.relates_to": { "rel_type": "m.replace", "event_id": event_id },
+            }),
+        ),
+        Some(BridgeRelation::Reaction { event_id, key }) => (
+            "m.reaction",
+            json!({
+                "m.relates_to": { "rel_type": "m.annotation", "event_id": event_id, "key": key },
+            }),
+        ),
+    }
+}
+
+/// How long after sending a network still accepts an edit, in seconds.
+/// `None` where the bridge doesn't enforce a window.
+pub fn edit_window_secs(service: &str) -> Option<i64> {
+    match service {
+        "whatsapp" => Some(15 * 60),
+        "signal" => Some(24 * 60 * 60),
+        "telegram" => Some(48 * 60 * 60),
+        _ => None,
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn send_bridge_message(
     service: &str,
@@ -1574,11 +1727,15 @@ pub async fn send_bridge_message(
     media_url: Option<String>,
     target_room_id: Option<&str>,
     target_chat_id: Option<&str>,
+    relation: Option<BridgeRelation>,
 ) -> Result<BridgeMessage> {
     tracing::info!(
-        "SEND_FLOW_BRIDGE send_bridge_message ENTER: service={}, user={}, chat_name='{}', room_id={:?}, chat_id={:?}, has_media={}",
-        service, user_id, chat_name, target_room_id, target_chat_id, media_url.is_some()
+        "SEND_FLOW_BRIDGE send_bridge_message ENTER: service={}, user={}, chat_name='{}', room_id={:?}, chat_id={:?}, has_media={}, relation={:?}",
+        service, user_id, chat_name, target_room_id, target_chat_id, media_url.is_some(), relation
     );
+    if media_url.is_some() && !matches!(relation, None | Some(BridgeRelation::Reply { .. })) {
+        return Err(anyhow!("Edits and reactions can't carry an attachment"));
+    }
 
     tracing::info!(
         "SEND_FLOW_BRIDGE Getting cached Matrix client for user={}",
@@ -1720,54 +1877,90 @@ pub async fn send_bridge_message(
         "SEND_FLOW_BRIDGE Got Matrix room object: room_id={}, display_name will be fetched after send",
         room.room_id()
     );
-    use matrix_sdk::ruma::events::room::message::{
-        ImageMessageEventContent, MessageType, RoomMessageEventContent,
-    };
+    use matrix_sdk::ruma::events::room::message::RoomMessageEventContent;
+    let mut sent_kind = None;
     if let Some(url) = media_url {
+        tracing::info!("SEND_FLOW_BRIDGE Sending media message with caption, loading media...");
+        // ── 1. Load the media and validate it for this network ──────────────────
+        let (content_type, bytes) = load_media_url(&url).await?;
+        let (mimetype, filename) = crate::services::outgoing_media::parse_media_type(&content_type);
+        let mut media = crate::services::outgoing_media::OutgoingMedia::inspect(
+            service,
+            &mimetype,
+            bytes,
+            filename.as_deref(),
+        )?
+        .with_thumbnail();
         tracing::info!(
-            "SEND_FLOW_BRIDGE Sending IMAGE message with caption, downloading from URL..."
+            "SEND_FLOW_BRIDGE Loaded {}: mime={}",
+            media.describe(),
+            media.mimetype
         );
-        // ── 1. Download the image and get MIME type ────────────────────────────────
-        let resp = reqwest::get(&url).await?;
-        // Get MIME type from headers before consuming the response
-        let mime: mime_guess::mime::Mime = resp
-            .headers()
-            .get(reqwest::header::CONTENT_TYPE)
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.parse().ok())
-            .unwrap_or_else(|| mime_guess::MimeGuess::from_path(&url).first_or_octet_stream());
-        // Now consume the response to get the bytes
-        let bytes = resp.bytes().await?;
-        let size = bytes.len();
+        // ── 2. Upload the media and its thumbnail to the homeserver ─────────────
+        let mime: mime_guess::mime::Mime = media
+            .mimetype
+            .parse()
+            .unwrap_or(mime_guess::mime::APPLICATION_OCTET_STREAM);
+        let upload_resp = client
+            .media()
+            .upload(&mime, std::mem::take(&mut media.bytes), None)
+            .await?;
+        let mxc = upload_resp.content_uri.to_string();
+        tracing::info!("SEND_FLOW_BRIDGE Uploaded to homeserver: mxc={}", mxc);
+        let thumbnail_mxc = match &media.thumbnail {
+            Some(thumb) => match client
+                .media()
+                .upload(&mime_guess::mime::IMAGE_JPEG, thumb.jpeg.clone(), None)
+                .await
+            {
+                Ok(resp) => Some(resp.content_uri.to_string()),
+                Err(e) => {
+                    tracing::warn!(
+                        "SEND_FLOW_BRIDGE Thumbnail upload failed (non-fatal): {}",
+                        e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        let rid_str = room.room_id().to_string();
+        // ── 3. Voice notes can't carry a caption; send it as its own message ────
+        if !media.carries_caption() && !message.trim().is_empty() {
+            room.send(RoomMessageEventContent::text_plain(message))
+                .await?;
+        }
+        // ── 4. Send the media event with caption, info and thumbnail ────────────
+        let mut content = media.event_content(message, &mxc, thumbnail_mxc.as_deref());
+        if let Some(BridgeRelation::Reply { event_id }) = &relation {
+            content["m.relates_to"] =
+                serde_json::json!({ "m.in_reply_to": { "event_id": event_id } });
+        }
         tracing::info!(
-            "SEND_FLOW_BRIDGE Downloaded image: {} bytes, mime={}",
-            size,
-            mime
+            "SEND_FLOW_BRIDGE Calling room.send_raw for {} message, room_id={}",
+            media.kind.msgtype(),
+            rid_str
         );
-        // ── 2. Upload to the homeserver ──────────────────────────────────────────
-        let upload_resp = client.media().upload(&mime, bytes.to_vec(), None).await?;
-        let mxc: matrix_sdk::ruma::OwnedMxcUri = upload_resp.content_uri;
-        tracing::info!("SEND_FLOW_BRIDGE Uploaded to homeserver: mxc={}", mxc);
-        // ── 4. Build the image-message content with caption in *one* event ──────
-        let mut img = ImageMessageEventContent::plain(
-            message.to_owned(), // the caption / body
-            mxc,
+        room.send_raw("m.room.message", content).await?;
+        tracing::info!(
+            "SEND_FLOW_BRIDGE room.send_raw for media returned OK, room_id={}",
+            rid_str
         );
-        // Optional but nice: add basic metadata so bridges & clients know the size
-        let mut imageinfo = matrix_sdk::ruma::events::room::ImageInfo::new();
-        imageinfo.size = Some(matrix_sdk::ruma::UInt::new(size as u64).unwrap_or_default());
-        img.info = Some(Box::new(imageinfo));
-        // Wrap it as a generic "m.room.message"
-        let content = RoomMessageEventContent::new(MessageType::Image(img));
-        // ── 5. Send it ───────────────────────────────────────────────────────────
+        sent_kind = Some(media.kind);
+    } else if let Some(relation) = &relation {
+        // reply, edit or reaction
         let rid_str = room.room_id().to_string();
+        let (event_type, content) = relation_event_content(message, Some(relation));
         tracing::info!(
-            "SEND_FLOW_BRIDGE Calling room.send for image message, room_id={}",
+            "SEND_FLOW_BRIDGE Sending {} relating to {} in room_id={}",
+            event_type,
+            relation.event_id(),
             rid_str
         );
-        room.send(content).await?;
+        room.send_raw(event_type, content).await?;
         tracing::info!(
-            "SEND_FLOW_BRIDGE room.send for image returned OK, room_id={}",
+            "SEND_FLOW_BRIDGE room.send_raw for {} returned OK, room_id={}",
+            event_type,
             rid_str
         );
     } else {
@@ -1831,13 +2024,150 @@ pub async fn send_bridge_message(
         content: message.to_string(),
         timestamp: current_timestamp,
         formatted_timestamp: format_timestamp(current_timestamp, user_info.timezone),
-        message_type: "text".to_string(),
+        message_type: sent_kind
+            .map(|kind| kind.msgtype().trim_start_matches("m.").to_string())
+            .unwrap_or_else(|| "text".to_string()),
         room_name: display_name,
         media_url: None,
         room_id: Some(room.room_id().to_string()),
+        event_id: None,
     })
 }
 
+/// Load media for `send_bridge_message`: attachments already in memory
+/// arrive as `data:` URLs, anything else is downloaded. Returns the content
+/// type (with a `name=` parameter when the data URL has one) and the bytes.
+async fn load_media_url(url: &str) -> Result<(String, Vec<u8>)> {
+    if let Some((content_type, bytes)) = crate::services::inbound_media::decode_data_url(url) {
+        return Ok((content_type, bytes));
+    }
+    let resp = reqwest::get(url).await?.error_for_status()?;
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            mime_guess::MimeGuess::from_path(url)
+                .first_or_octet_stream()
+                .to_string()
+        });
+    Ok((content_type, resp.bytes().await?.to_vec()))
+}
+
+/// The most recent photo, voice note, video or file someone else sent in a
+/// bridged chat, downloaded and wrapped as a `data:` URL so it can be
+/// forwarded with `send_bridge_message`. Returns the room's display name
+/// alongside it. The size the event declares is checked against `target`'s
+/// limits first, so an oversized file is never downloaded.
+pub async fn fetch_latest_attachment(
+    state: &Arc<AppState>,
+    user_id: i32,
+    service: &str,
+    chat_name: &str,
+    target: &str,
+) -> Result<(String, String)> {
+    use matrix_sdk::media::{MediaFormat, MediaRequestParameters};
+
+    let client = crate::utils::matrix_auth::get_cached_client(user_id, state).await?;
+    let rooms = get_service_rooms(&client, service).await?;
+    let bridge_room = search_best_match(&rooms, chat_name).ok_or_else(|| {
+        anyhow!(
+            "No {} chat found matching '{}'",
+            capitalize(service),
+            chat_name
+        )
+    })?;
+    let room_id = matrix_sdk::ruma::OwnedRoomId::try_from(bridge_room.room_id.as_str())?;
+    let room = client
+        .get_room(&room_id)
+        .ok_or_else(|| anyhow!("Room not found"))?;
+    let own_user_id = client
+        .user_id()
+        .ok_or_else(|| anyhow!("User ID not available"))?
+        .to_owned();
+    let chat_display_name = remove_bridge_suffix(&bridge_room.display_name);
+
+    let mut options = MessagesOptions::backward();
+    options.limit = matrix_sdk::ruma::UInt::new(50).unwrap();
+    let response = room.messages(options).await?;
+
+    for event in response.chunk {
+        let Ok(AnySyncTimelineEvent::MessageLike(
+            matrix_sdk::ruma::events::AnySyncMessageLikeEvent::RoomMessage(
+                SyncRoomMessageEvent::Original(e),
+            ),
+        )) = event.raw().deserialize()
+        else {
+            continue;
+        };
+        if e.sender == own_user_id {
+            continue;
+        }
+        let (source, mimetype, size, filename) = match e.content.msgtype {
+            MessageType::Image(i) => {
+                let info = i.info.map(|info| (info.mimetype, info.size));
+                let (mimetype, size) = info.unwrap_or_default();
+                (i.source, mimetype, size, i.filename.unwrap_or(i.body))
+            }
+            MessageType::Video(v) => {
+                let info = v.info.map(|info| (info.mimetype, info.size));
+                let (mimetype, size) = info.unwrap_or_default();
+                (v.source, mimetype, size, v.filename.unwrap_or(v.body))
+            }
+            MessageType::Audio(a) => {
+                let info = a.info.map(|info| (info.mimetype, info.size));
+                let (mimetype, size) = info.unwrap_or_default();
+                (a.source, mimetype, size, a.filename.unwrap_or(a.body))
+            }
+            MessageType::File(f) => {
+                let info = f.info.map(|info| (info.mimetype, info.size));
+                let (mimetype, size) = info.unwrap_or_default();
+                (f.source, mimetype, size, f.filename.unwrap_or(f.body))
+            }
+            _ => continue,
+        };
+        let mimetype = mimetype.unwrap_or_else(|| {
+            mime_guess::MimeGuess::from_path(&filename)
+                .first_or_octet_stream()
+                .to_string()
+        });
+
+        // Bridges always declare the size; without it there is no way to
+        // bound the download.
+        let size = size
+            .map(|size| usize::try_from(u64::from(size)).unwrap_or(usize::MAX))
+            .ok_or_else(|| anyhow!("the latest attachment doesn't say how large it is"))?;
+        crate::services::outgoing_media::check_limits(target, &mimetype, size)?;
+
+        let request = MediaRequestParameters {
+            source,
+            format: MediaFormat::File,
+        };
+        let bytes = client.media().get_media_content(&request, false).await?;
+        tracing::info!(
+            "Loaded {} attachment ({} bytes) from {} room {} for user {}",
+            mimetype,
+            bytes.len(),
+            service,
+            room_id,
+            user_id
+        );
+        let data_url = crate::services::outgoing_media::to_data_url(
+            &mimetype,
+            Some(filename.as_str()),
+            &bytes,
+        );
+        return Ok((data_url, chat_display_name));
+    }
+
+    Err(anyhow!(
+        "No recent photo, voice note or file from '{}' on {}",
+        chat_display_name,
+        capitalize(service)
+    ))
+}
+
 use matrix_sdk::ruma::events::room::message::{OriginalSyncRoomMessageEvent, Relation};
 use matrix_sdk::RoomMemberships;
 use strsim;
@@ -2085,10 +2415,19 @@ pub async fn handle_bridge_message(
     };
     let user_id = user.id;
 
+    // The user's Matrix DM room talks to the assistant; it is not bridged chat.
+    if crate::channels::matrix_channel::handle_dm_message(&state, &user, &room, &event).await {
+        return;
+    }
+
     // Track which bridges are currently connecting (used below to skip
     // management-room processing for those bridges only).
+    let bridge_types: Vec<&str> = ["signal", "telegram", "whatsapp"]
+        .into_iter()
+        .chain(bridgev2_provisioning::NETWORKS.iter().map(|n| n.id))
+        .collect();
     let mut connecting_bridges: Vec<String> = Vec::new();
-    for bridge_type in &["signal", "telegram", "whatsapp"] {
+    for bridge_type in &bridge_types {
         if let Ok(Some(bridge)) = state.user_repository.get_bridge(user_id, bridge_type) {
             if bridge.status == "connecting" {
                 connecting_bridges.push(bridge_type.to_string());
@@ -2098,7 +2437,6 @@ pub async fn handle_bridge_message(
 
     // Check if this is a bridge management room
     let room_id_str = room.room_id().to_string();
-    let bridge_types = vec!["signal", "telegram", "whatsapp"];
     let mut bridges = Vec::new();
     for bridge_type in &bridge_types {
         if let Ok(Some(bridge)) = state.user_repository.get_bridge(user_id, bridge_type) {
@@ -2134,7 +2472,10 @@ pub async fn handle_bridge_message(
             "signal" => "SIGNAL_BRIDGE_BOT",
             "whatsapp" => "WHATSAPP_BRIDGE_BOT",
             "telegram" => "TELEGRAM_BRIDGE_BOT",
-            _ => return, // Unknown bridge type
+            other => match bridgev2_provisioning::network(other) {
+                Some(network) => network.bot_env,
+                None => return, // Unknown bridge type
+            },
         };
         let bridge_bot = match std::env::var(bridge_bot_var) {
             Ok(bot) => bot,
@@ -2295,7 +2636,8 @@ pub async fn handle_bridge_message(
         "WHATSAPP_BRIDGE_BOT",
         "SIGNAL_BRIDGE_BOT",
     ]
-    .iter()
+    .into_iter()
+    .chain(bridgev2_provisioning::NETWORKS.iter().map(|n| n.bot_env))
     .filter_map(|var| std::env::var(var).ok())
     .filter_map(|full_id| {
         // Extract localpart from @localpart:server
@@ -2325,6 +2667,7 @@ pub async fn handle_bridge_message(
     let sender_prefix = get_sender_prefix(&service);
     if !sender_localpart.starts_with(&sender_prefix) {
         // User's own outgoing message - store in ontology for context, skip AI processing
+        let shared_location = parse_shared_location(&state, &event.content.msgtype);
         let (content, cleanup_tuwunel_media) = match &event.content.msgtype {
             MessageType::Text(t) => (t.body.clone(), false),
             MessageType::Notice(n) => (n.body.clone(), false),
@@ -2333,7 +2676,13 @@ pub async fn handle_bridge_message(
             MessageType::Video(_) => ("VIDEO".to_string(), true),
             MessageType::File(_) => ("FILE".to_string(), true),
             MessageType::Audio(_) => ("AUDIO".to_string(), true),
-            MessageType::Location(_) => ("LOCATION".to_string(), false),
+            MessageType::Location(_) => (
+                shared_location
+                    .as_ref()
+                    .map(|loc| loc.content())
+                    .unwrap_or_else(|| "LOCATION".to_string()),
+                false,
+            ),
             _ => {
                 record_retained_unproven_event(
                     &state,
@@ -2418,6 +2767,9 @@ pub async fn handle_bridge_message(
                         return;
                     }
                     bump_stored(&stored_service);
+                    if let Some(ref location) = shared_location {
+                        store_shared_location(&state_clone, created.id, location);
+                    }
                     let snapshot = serde_json::json!({
                         "message_id": created.id,
                         "platform": msg.platform,
@@ -2546,8 +2898,23 @@ pub async fn handle_bridge_message(
                         current_room_id
                     );
                 } else {
-                    match state.channel_router.send_to_user(&user, &body, None).await {
+                    let reply_code = crate::proactive::reply_context::reserve_code(&state, user_id);
+                    let tagged = crate::proactive::reply_context::tag(&reply_code, &body);
+                    match state
+                        .channel_router
+                        .send_to_user(&user, &tagged, None)
+                        .await
+                    {
                         Ok(_) => {
+                            crate::proactive::reply_context::record(
+                                &state,
+                                user_id,
+                                &reply_code,
+                                &crate::proactive::reply_context::ReplyTarget::BridgeReplyWatch {
+                                    room_id: current_room_id.clone(),
+                                },
+                                &body,
+                            );
                             tracing::info!(
                                 "REPLY_WATCH fired+cleared bridge watch id={} user={} room={}",
                                 watch.id,
@@ -2624,6 +2991,8 @@ pub async fn handle_bridge_message(
     let cleanup_tuwunel_media = true;
     let cleanup_matrix_event_id = event.event_id.to_string();
 
+    let shared_location = parse_shared_location(&state, &event.content.msgtype);
+
     // Extract message content and estimate message size for bandwidth tracking
     let (content, bytes_estimate) = match event.content.msgtype {
         MessageType::Text(ref t) => (t.body.clone(), t.body.len() as i32),
@@ -2664,7 +3033,13 @@ pub async fn handle_bridge_message(
                 .unwrap_or(100_000i32);
             ("AUDIO".into(), size)
         }
-        MessageType::Location(_) => ("LOCATION".into(), 200i32),
+        MessageType::Location(_) => (
+            shared_location
+                .as_ref()
+                .map(|loc| loc.content())
+                .unwrap_or_else(|| "LOCATION".into()),
+            200i32,
+        ),
         MessageType::Emote(ref t) => (t.body.clone(), t.body.len() as i32),
         _ => {
             record_retained_unproven_event(
@@ -2679,6 +3054,21 @@ pub async fn handle_bridge_message(
         }
     };
 
+    // Voice notes arrive with a filename as their body; keep what the store
+    // task needs to download and transcribe them.
+    let voice_note = match event.content.msgtype {
+        MessageType::Audio(ref a) => Some((
+            a.source.clone(),
+            a.info.as_ref().and_then(|i| i.mimetype.clone()),
+            a.info
+                .as_ref()
+                .and_then(|i| i.duration)
+                .map(|d| d.as_secs().min(u32::MAX as u64) as u32),
+            a.info.as_ref().and_then(|i| i.size).map(u64::from),
+        )),
+        _ => None,
+    };
+
     // Log bandwidth estimate for bridge traffic tracking
     if let Err(e) =
         state
@@ -2807,6 +3197,26 @@ pub async fn handle_bridge_message(
         cleanup_tuwunel_media,
     );
     tokio::spawn(async move {
+        let mut msg = msg;
+        let mut transcript = None;
+        if let Some((source, mimetype, duration_secs, size)) = voice_note {
+            if attention.include_in_digest || attention.notify_or_evaluate {
+                transcript = transcribe_bridged_voice_note(
+                    &state_clone,
+                    &client,
+                    user_id,
+                    &cleanup_matrix_event_id,
+                    source,
+                    mimetype.as_deref(),
+                    size,
+                )
+                .await;
+                if let Some(ref text) = transcript {
+                    msg.content =
+                        crate::services::voice_notes::voice_note_content(text, duration_secs);
+                }
+            }
+        }
         match state_clone.ontology_repository.insert_message(&msg) {
             Ok((created, is_new)) => {
                 crate::utils::tuwunel_event_cleanup::enqueue_processed_bridge_event(
@@ -2823,6 +3233,21 @@ pub async fn handle_bridge_message(
                     return;
                 }
                 bump_stored(&stored_service);
+                if let Some(ref location) = shared_location {
+                    store_shared_location(&state_clone, created.id, location);
+                }
+                if let Some(ref text) = transcript {
+                    if let Err(e) = state_clone
+                        .ontology_repository
+                        .set_message_transcript(created.id, text)
+                    {
+                        tracing::warn!(
+                            "Failed to store voice note transcript for message {}: {}",
+                            created.id,
+                            e
+                        );
+                    }
+                }
                 let mut snapshot = serde_json::json!({
                     "message_id": created.id,
                     "platform": msg.platform,
@@ -2885,9 +3310,113 @@ pub async fn handle_bridge_message(
     });
 }
 
+/// Parse an `m.location` event and name it from the gazetteer. `None` for
+/// other message types and for pins without usable coordinates.
+fn parse_shared_location(
+    state: &Arc<AppState>,
+    msgtype: &MessageType,
+) -> Option<crate::services::shared_location::SharedLocation> {
+    let MessageType::Location(l) = msgtype else {
+        return None;
+    };
+    let mut location =
+        crate::services::shared_location::SharedLocation::from_event(&l.body, &l.geo_uri)?;
+    location.resolve_place(state.gazetteer.as_deref());
+    Some(location)
+}
+
+/// Keep a location share's coordinates next to its stored message so the
+/// agent can forward it (e.g. to the car) without re-parsing content.
+fn store_shared_location(
+    state: &Arc<AppState>,
+    message_id: i64,
+    location: &crate::services::shared_location::SharedLocation,
+) {
+    if let Err(e) = state.ontology_repository.set_message_location(
+        message_id,
+        location.lat,
+        location.lon,
+        location.label.as_deref(),
+        location.place.as_deref(),
+    ) {
+        tracing::warn!(
+            "Failed to store shared location for message {}: {}",
+            message_id,
+            e
+        );
+    }
+}
+
+/// Download a bridged voice note and transcribe it. `None` when transcription
+/// is off, the event was already stored (redelivery), the declared `size` is
+/// missing or over the limit (checked before downloading), or any step
+/// fails; the message is then stored as plain "AUDIO" like before.
+async fn transcribe_bridged_voice_note(
+    state: &Arc<AppState>,
+    client: &MatrixClient,
+    user_id: i32,
+    matrix_event_id: &str,
+    source: matrix_sdk::ruma::events::room::MediaSource,
+    mimetype: Option<&str>,
+    size: Option<u64>,
+) -> Option<String> {
+    use crate::services::voice_notes;
+    use matrix_sdk::media::{MediaFormat, MediaRequestParameters};
+
+    let stt = voice_notes::speech_to_text(state)?;
+    if state
+        .ontology_repository
+        .message_exists_for_event(user_id, matrix_event_id)
+        .unwrap_or(false)
+    {
+        return None;
+    }
+    if let Err(e) = voice_notes::check_declared_size(size) {
+        tracing::warn!(
+            "Voice note {} for user {} not downloaded: {}",
+            matrix_event_id,
+            user_id,
+            e
+        );
+        return None;
+    }
+
+    let request = MediaRequestParameters {
+        source,
+        format: MediaFormat::File,
+    };
+    let bytes = match client.media().get_media_content(&request, false).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to download voice note {} for user {}: {}",
+                matrix_event_id,
+                user_id,
+                e
+            );
+            return None;
+        }
+    };
+
+    match voice_notes::transcribe_voice_note(stt.as_ref(), mimetype, &bytes).await {
+        Ok(transcript) => transcript,
+        Err(e) => {
+            tracing::warn!(
+                "Voice note {} for user {} not transcribed ({}): {}",
+                matrix_event_id,
+                user_id,
+                stt.name(),
+                e
+            );
+            None
+        }
+    }
+}
+
 /// Fetch contacts via mautrix bridge provisioning API (v3).
 /// Returns (total_contacts, matched_results).
-/// Requires WHATSAPP_BRIDGE_URL / SIGNAL_BRIDGE_URL / TELEGRAM_BRIDGE_URL env var.
+/// Requires WHATSAPP_BRIDGE_URL / SIGNAL_BRIDGE_URL / TELEGRAM_BRIDGE_URL env var,
+/// or the network's own URL var for provisioning-API networks.
 async fn fetch_provision_contacts(
     client: &MatrixClient,
     service: &str,
@@ -2897,7 +3426,10 @@ async fn fetch_provision_contacts(
         "whatsapp" => "WHATSAPP_BRIDGE_URL",
         "signal" => "SIGNAL_BRIDGE_URL",
         "telegram" => "TELEGRAM_BRIDGE_URL",
-        _ => return Err(anyhow!("Unknown service: {}", service)),
+        other => match bridgev2_provisioning::network(other) {
+            Some(network) => network.url_env,
+            None => return Err(anyhow!("Unknown service: {}", service)),
+        },
     };
     let bridge_url =
         std::env::var(bridge_url_var).map_err(|_| anyhow!("{} not set", bridge_url_var))?;
@@ -0,0 +1,703 @@
+//! Generic onboarding for bridgev2 networks through the bridge's own
+//! provisioning API (`/_matrix/provision/v3`).
+//!
+//! WhatsApp, Signal and Telegram each have a hand-written auth module that
+//! drives the bridge bot with `!prefix` commands in a management room. For
+//! everything after those we talk to the provisioning API instead: it lists
+//! the login flows the bridge supports and walks any of them (QR, phone code,
+//! cookies, password) as a sequence of typed steps, so one client and one set
+//! of handlers cover every network.
+//!
+//! A network is onboarded by adding a `BridgeNetwork` entry below and setting
+//! its env vars:
+//!   - `{ID}_BRIDGE_URL`: provisioning base URL of the bridge (required,
+//!     the network is hidden without it),
+//!   - `{ID}_BRIDGE_BOT`: the bridge bot's Matrix ID,
+//!   - `{ID}_BRIDGE_DATABASE_URL`: read-only access for the cleanup worker's
+//!     login-count safety check.
+//!
+//! The bridge must be configured the way ours are: double puppeting through
+//! the shared appservice, provisioning with `allow_matrix_auth` (we pass the
+//! user's own Matrix access token), ghost usernames `{id}_{{.}}` so the rest
+//! of the pipeline can tell its rooms apart, and the network's `room_suffix`
+//! appended to portal display names. Network quirks go in a small
+//! `NetworkAdapter`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::utils::bridge_responses::BridgeLoginHealth;
+use crate::AppState;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// `display_and_wait` steps block until the user scans or confirms on their
+/// phone; bridges give up on a QR after a few minutes on their own.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// bridgev2 remote state events, as reported on each login in `whoami`.
+pub const STATE_CONNECTED: &str = "CONNECTED";
+pub const STATE_BAD_CREDENTIALS: &str = "BAD_CREDENTIALS";
+
+/// Per-network behaviour that the provisioning API doesn't describe.
+pub trait NetworkAdapter: Send + Sync {
+    /// Flow ids to offer first, best first. Flows the bridge reports that
+    /// aren't listed keep their order after these.
+    fn preferred_flows(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Flows the web app can't complete, e.g. ones that need a desktop
+    /// browser to sit on the network's site.
+    fn hidden_flows(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// What to show as "connected as" for a login.
+    fn account_label(&self, login: &RemoteLogin) -> Option<String> {
+        login.default_label()
+    }
+}
+
+/// A bridgev2 network onboarded through the provisioning API.
+pub struct BridgeNetwork {
+    /// `bridges.bridge_type`, ghost prefix (`{id}_`) and `platform` on
+    /// stored messages.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub url_env: &'static str,
+    pub bot_env: &'static str,
+    pub database_env: &'static str,
+    /// Marker the bridge's displayname template appends to portal names,
+    /// e.g. "(Discord)".
+    pub room_suffix: &'static str,
+    pub adapter: &'static dyn NetworkAdapter,
+}
+
+impl BridgeNetwork {
+    pub fn bridge_url(&self) -> Option<String> {
+        std::env::var(self.url_env)
+            .ok()
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+    }
+
+    pub fn bot_user_id(&self) -> Option<String> {
+        std::env::var(self.bot_env)
+            .ok()
+            .filter(|bot| !bot.trim().is_empty())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.bridge_url().is_some()
+    }
+
+    /// The bridge's flows in the order the adapter prefers, minus hidden ones.
+    pub fn order_flows(&self, flows: Vec<LoginFlow>) -> Vec<LoginFlow> {
+        let preferred = self.adapter.preferred_flows();
+        let hidden = self.adapter.hidden_flows();
+        let mut flows: Vec<LoginFlow> = flows
+            .into_iter()
+            .filter(|flow| !hidden.contains(&flow.id.as_str()))
+            .collect();
+        flows.sort_by_key(|flow| {
+            preferred
+                .iter()
+                .position(|id| *id == flow.id)
+                .unwrap_or(preferred.len())
+        });
+        flows
+    }
+
+    /// Whether a room display name carries this network's suffix.
+    pub fn room_name_matches(&self, room_name: &str) -> bool {
+        room_name
+            .trim()
+            .to_lowercase()
+            .ends_with(&self.room_suffix.to_lowercase())
+    }
+
+    /// Whether a Matrix localpart is one of this bridge's ghosts.
+    pub fn is_ghost(&self, localpart: &str) -> bool {
+        localpart
+            .to_lowercase()
+            .strip_prefix(self.id)
+            .is_some_and(|rest| rest.starts_with('_'))
+    }
+}
+
+struct DiscordAdapter;
+
+impl NetworkAdapter for DiscordAdapter {
+    fn preferred_flows(&self) -> &'static [&'static str] {
+        &["qr", "token"]
+    }
+
+    fn account_label(&self, login: &RemoteLogin) -> Option<String> {
+        login
+            .profile
+            .username
+            .clone()
+            .or_else(|| login.default_label())
+    }
+}
+
+/// mautrix-meta runs one instance per mode, so Messenger and Instagram are
+/// separate networks backed by the same adapter.
+struct MetaAdapter;
+
+impl NetworkAdapter for MetaAdapter {
+    fn preferred_flows(&self) -> &'static [&'static str] {
+        &["messenger", "instagram", "facebook"]
+    }
+
+    fn account_label(&self, login: &RemoteLogin) -> Option<String> {
+        login
+            .profile
+            .username
+            .as_deref()
+            .map(|username| format!("@{}", username.trim_start_matches('@')))
+            .or_else(|| login.default_label())
+    }
+}
+
+struct GoogleMessagesAdapter;
+
+impl NetworkAdapter for GoogleMessagesAdapter {
+    fn preferred_flows(&self) -> &'static [&'static str] {
+        &["qr", "google"]
+    }
+}
+
+struct SlackAdapter;
+
+impl NetworkAdapter for SlackAdapter {
+    fn preferred_flows(&self) -> &'static [&'static str] {
+        &["token", "cookies"]
+    }
+}
+
+pub static NETWORKS: &[BridgeNetwork] = &[
+    BridgeNetwork {
+        id: "discord",
+        name: "Discord",
+        url_env: "DISCORD_BRIDGE_URL",
+        bot_env: "DISCORD_BRIDGE_BOT",
+        database_env: "DISCORD_BRIDGE_DATABASE_URL",
+        room_suffix: "(Discord)",
+        adapter: &DiscordAdapter,
+    },
+    BridgeNetwork {
+        id: "messenger",
+        name: "Messenger",
+        url_env: "MESSENGER_BRIDGE_URL",
+        bot_env: "MESSENGER_BRIDGE_BOT",
+        database_env: "MESSENGER_BRIDGE_DATABASE_URL",
+        room_suffix: "(Messenger)",
+        adapter: &MetaAdapter,
+    },
+    BridgeNetwork {
+        id: "instagram",
+        name: "Instagram",
+        url_env: "INSTAGRAM_BRIDGE_URL",
+        bot_env: "INSTAGRAM_BRIDGE_BOT",
+        database_env: "INSTAGRAM_BRIDGE_DATABASE_URL",
+        room_suffix: "(Instagram)",
+        adapter: &MetaAdapter,
+    },
+    BridgeNetwork {
+        id: "gmessages",
+        name: "Google Messages",
+        url_env: "GMESSAGES_BRIDGE_URL",
+        bot_env: "GMESSAGES_BRIDGE_BOT",
+        database_env: "GMESSAGES_BRIDGE_DATABASE_URL",
+        room_suffix: "(GM)",
+        adapter: &GoogleMessagesAdapter,
+    },
+    BridgeNetwork {
+        id: "slack",
+        name: "Slack",
+        url_env: "SLACK_BRIDGE_URL",
+        bot_env: "SLACK_BRIDGE_BOT",
+        database_env: "SLACK_BRIDGE_DATABASE_URL",
+        room_suffix: "(Slack)",
+        adapter: &SlackAdapter,
+    },
+];
+
+/// A registered network by id, configured or not.
+pub fn network(id: &str) -> Option<&'static BridgeNetwork> {
+    NETWORKS.iter().find(|network| network.id == id)
+}
+
+/// Networks with a bridge URL configured.
+pub fn enabled_networks() -> impl Iterator<Item = &'static BridgeNetwork> {
+    NETWORKS.iter().filter(|network| network.is_enabled())
+}
+
+/// The network a portal message belongs to, from the ghost sender or the
+/// room name suffix.
+pub fn infer_network(room_name: &str, sender_localpart: &str) -> Option<&'static BridgeNetwork> {
+    NETWORKS.iter().find(|network| {
+        network.is_ghost(sender_localpart.trim()) || network.room_name_matches(room_name)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoginFlow {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepType {
+    /// Show something (QR, pairing code, emoji) and wait for the user to
+    /// act on their phone.
+    DisplayAndWait,
+    /// Ask for fields such as phone number, code or password.
+    UserInput,
+    /// Ask for cookies from the network's website.
+    Cookies,
+    Complete,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisplayAndWait {
+    /// "qr", "emoji", "code" or "nothing".
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputField {
+    /// "phone_number", "2fa_code", "password", "token", ...
+    #[serde(rename = "type")]
+    pub field_type: String,
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserInput {
+    pub fields: Vec<InputField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CookieField {
+    pub id: String,
+    #[serde(default)]
+    pub required: bool,
+    /// Where a browser would find the value (cookie, local storage, request
+    /// header); passed through to the frontend as-is.
+    #[serde(default)]
+    pub sources: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cookies {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<CookieField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Complete {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_login_id: Option<String>,
+}
+
+/// One step of a login process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoginStep {
+    #[serde(rename = "type")]
+    pub step_type: StepType,
+    pub step_id: String,
+    #[serde(default)]
+    pub instructions: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_and_wait: Option<DisplayAndWait>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_input: Option<UserInput>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookies: Option<Cookies>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complete: Option<Complete>,
+}
+
+impl LoginStep {
+    /// Field ids the user must fill in before this step can be submitted.
+    pub fn required_fields(&self) -> Vec<&str> {
+        match self.step_type {
+            StepType::UserInput => self
+                .user_input
+                .iter()
+                .flat_map(|input| input.fields.iter())
+                .map(|field| field.id.as_str())
+                .collect(),
+            StepType::Cookies => self
+                .cookies
+                .iter()
+                .flat_map(|cookies| cookies.fields.iter())
+                .filter(|field| field.required)
+                .map(|field| field.id.as_str())
+                .collect(),
+            StepType::DisplayAndWait | StepType::Complete => Vec::new(),
+        }
+    }
+}
+
+/// Response to starting a login or submitting a step. `login_id` is the
+/// login *process* id, stable across the steps of one attempt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoginProgress {
+    pub login_id: String,
+    #[serde(flatten)]
+    pub step: LoginStep,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoteProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteState {
+    pub state_event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A login the bridge holds for the Matrix user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteLogin {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub profile: RemoteProfile,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<RemoteState>,
+}
+
+impl RemoteLogin {
+    pub fn state_event(&self) -> Option<&str> {
+        self.state.as_ref().map(|state| state.state_event.as_str())
+    }
+
+    /// Phone, email, username or name, whichever the network fills in first.
+    pub fn default_label(&self) -> Option<String> {
+        let profile = &self.profile;
+        [
+            profile.phone.as_deref(),
+            profile.email.as_deref(),
+            profile.username.as_deref(),
+            Some(self.name.as_str()),
+            profile.name.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|label| !label.is_empty())
+        .map(str::to_string)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Whoami {
+    #[serde(default)]
+    pub login_flows: Vec<LoginFlow>,
+    #[serde(default)]
+    pub logins: Vec<RemoteLogin>,
+}
+
+#[derive(Deserialize)]
+struct FlowsResponse {
+    flows: Vec<LoginFlow>,
+}
+
+/// Login health from `whoami`, with the same contract as the list-logins
+/// classifier: any CONNECTED login wins, BAD_CREDENTIALS only counts when
+/// nothing is connected, everything else is inconclusive.
+pub fn classify_logins(logins: &[RemoteLogin]) -> BridgeLoginHealth {
+    if logins.is_empty() {
+        return BridgeLoginHealth::Empty;
+    }
+    if logins
+        .iter()
+        .any(|login| login.state_event() == Some(STATE_CONNECTED))
+    {
+        return BridgeLoginHealth::Connected;
+    }
+    if logins
+        .iter()
+        .any(|login| login.state_event() == Some(STATE_BAD_CREDENTIALS))
+    {
+        return BridgeLoginHealth::BadCredentials;
+    }
+    BridgeLoginHealth::Unknown
+}
+
+#[derive(Debug, Error)]
+pub enum ProvisioningError {
+    #[error("{0} bridge is not configured")]
+    NotConfigured(&'static str),
+
+    #[error("No Matrix access token")]
+    NoAccessToken,
+
+    #[error("Bridge request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The bridge rejected the request; `message` is its user-facing error,
+    /// e.g. a wrong 2FA code.
+    #[error("{message}")]
+    Api {
+        status: u16,
+        errcode: Option<String>,
+        message: String,
+    },
+
+    #[error("Unexpected bridge response: {0}")]
+    Decode(String),
+}
+
+/// Client for one bridge's provisioning API, acting as one Matrix user.
+pub struct ProvisioningClient {
+    base_url: String,
+    access_token: String,
+    http: reqwest::Client,
+}
+
+impl ProvisioningClient {
+    pub fn new(base_url: &str, access_token: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            access_token: access_token.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Client for `network` authenticated with the user's Matrix session.
+    pub async fn for_user(
+        state: &Arc<AppState>,
+        user_id: i32,
+        network: &BridgeNetwork,
+    ) -> anyhow::Result<Self> {
+        let base_url = network
+            .bridge_url()
+            .ok_or(ProvisioningError::NotConfigured(network.name))?;
+        let client = crate::utils::matrix_auth::get_cached_client(user_id, state).await?;
+        Ok(Self::from_matrix_client(&base_url, &client)?)
+    }
+
+    pub fn from_matrix_client(
+        base_url: &str,
+        client: &matrix_sdk::Client,
+    ) -> Result<Self, ProvisioningError> {
+        let access_token = client
+            .matrix_auth()
+            .session()
+            .map(|session| session.tokens.access_token.clone())
+            .filter(|token| !token.is_empty())
+            .ok_or(ProvisioningError::NoAccessToken)?;
+        Ok(Self::new(base_url, &access_token))
+    }
+
+    fn url(&self, segments: &[&str]) -> String {
+        let path: Vec<String> = segments
+            .iter()
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect();
+        format!("{}/_matrix/provision/v3/{}", self.base_url, path.join("/"))
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, ProvisioningError> {
+        let response = request.bearer_auth(&self.access_token).send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            let error: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+            return Err(ProvisioningError::Api {
+                status: status.as_u16(),
+                errcode: error["errcode"].as_str().map(str::to_string),
+                message: error["error"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("Bridge returned {}", status)),
+            });
+        }
+        serde_json::from_str(&body)
+            .map_err(|e| ProvisioningError::Decode(format!("{}; body={}", e, body)))
+    }
+
+    pub async fn whoami(&self) -> Result<Whoami, ProvisioningError> {
+        self.send(
+            self.http
+                .get(self.url(&["whoami"]))
+                .timeout(REQUEST_TIMEOUT),
+        )
+        .await
+    }
+
+    pub async fn login_flows(&self) -> Result<Vec<LoginFlow>, ProvisioningError> {
+        let response: FlowsResponse = self
+            .send(
+                self.http
+                    .get(self.url(&["login", "flows"]))
+                    .timeout(REQUEST_TIMEOUT),
+            )
+            .await?;
+        Ok(response.flows)
+    }
+
+    pub async fn start_login(&self, flow_id: &str) -> Result<LoginProgress, ProvisioningError> {
+        self.send(
+            self.http
+                .post(self.url(&["login", "start", flow_id]))
+                .json(&serde_json::json!({}))
+                .timeout(REQUEST_TIMEOUT),
+        )
+        .await
+    }
+
+    /// Submit the fields of a `user_input` or `cookies` step.
+    pub async fn submit_step(
+        &self,
+        process_id: &str,
+        step: &LoginStep,
+        fields: &HashMap<String, String>,
+    ) -> Result<LoginProgress, ProvisioningError> {
+        let step_type = match step.step_type {
+            StepType::UserInput => "user_input",
+            StepType::Cookies => "cookies",
+            StepType::DisplayAndWait | StepType::Complete => {
+                return Err(ProvisioningError::Decode(format!(
+                    "step {} takes no input",
+                    step.step_id
+                )))
+            }
+        };
+        self.send(
+            self.http
+                .post(self.url(&["login", "step", process_id, &step.step_id, step_type]))
+                .json(fields)
+                .timeout(REQUEST_TIMEOUT),
+        )
+        .await
+    }
+
+    /// Block until a `display_and_wait` step moves on: the user scanned the
+    /// QR, the code refreshed, or the bridge gave up.
+    pub async fn wait_step(
+        &self,
+        process_id: &str,
+        step_id: &str,
+    ) -> Result<LoginProgress, ProvisioningError> {
+        self.send(
+            self.http
+                .post(self.url(&["login", "step", process_id, step_id, "display_and_wait"]))
+                .json(&serde_json::json!({}))
+                .timeout(WAIT_TIMEOUT),
+        )
+        .await
+    }
+
+    pub async fn logout(&self, login_id: &str) -> Result<(), ProvisioningError> {
+        let _: serde_json::Value = self
+            .send(
+                self.http
+                    .post(self.url(&["logout", login_id]))
+                    .json(&serde_json::json!({}))
+                    .timeout(REQUEST_TIMEOUT),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Log out every login the user has on this bridge. Returns how many.
+    pub async fn logout_all(&self) -> Result<usize, ProvisioningError> {
+        let logins = self.whoami().await?.logins;
+        for login in &logins {
+            self.logout(&login.id).await?;
+        }
+        Ok(logins.len())
+    }
+}
+
+/// Health for the scheduler and the health endpoint: `Some(true)` connected,
+/// `Some(false)` credentials confirmed revoked, `None` inconclusive.
+pub async fn probe_login_health(
+    state: &Arc<AppState>,
+    user_id: i32,
+    network: &BridgeNetwork,
+) -> Option<bool> {
+    let client = match ProvisioningClient::for_user(state, user_id, network).await {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!(
+                "Bridge health: no provisioning client for {} user {}: {}",
+                network.id,
+                user_id,
+                e
+            );
+            return None;
+        }
+    };
+    match client.whoami().await {
+        Ok(whoami) => {
+            let health = classify_logins(&whoami.logins);
+            tracing::info!(
+                "Bridge health: user {} {} -> {:?}",
+                user_id,
+                network.id,
+                health
+            );
+            match health {
+                BridgeLoginHealth::Connected => Some(true),
+                BridgeLoginHealth::BadCredentials => Some(false),
+                BridgeLoginHealth::Empty | BridgeLoginHealth::Unknown => None,
+            }
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Bridge health: {} whoami failed for user {}: {}",
+                network.id,
+                user_id,
+                e
+            );
+            None
+        }
+    }
+}
@@ -1,11 +1,31 @@
+//! AES-256-GCM encryption for secrets stored in the database.
+//!
+//! Keys come from a key ring so they can be rotated without downtime:
+//!
+//! - `ENCRYPTION_KEYS` - comma-separated `<id>:<base64 key>` pairs, e.g.
+//!   `2:AbC...=,1:XyZ...=`.
+//! - `ENCRYPTION_KEY` - the original single key, treated as key id 0.
+//!
+//! New values are always encrypted with the highest key id and written as
+//! `lf1:<key id>:<base64(nonce || ciphertext)>`, with the header bound as
+//! associated data. Values written before versioning (bare base64) are still
+//! decrypted, by trying each key in the ring. `services::key_rotation`
+//! rewrites old values under the newest key.
+
 use aes_gcm::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, KeyInit, Payload},
     Aes256Gcm, Nonce,
 };
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use rand::Rng;
+use std::collections::BTreeMap;
 use thiserror::Error;
 
+/// Version tag of the ciphertext header. Base64 never contains `:`, so
+/// unversioned values can't be mistaken for versioned ones.
+const VERSION_TAG: &str = "lf1";
+const NONCE_LEN: usize = 12;
+
 #[derive(Error, Debug)]
 pub enum EncryptionError {
     #[error("Failed to decode key: {0}")]
@@ -22,71 +42,216 @@ pub enum EncryptionError {
     InvalidData,
     #[error("Environment error: {0}")]
     EnvError(#[from] std::env::VarError),
+    #[error("Invalid key ring: {0}")]
+    KeyRingError(String),
+    #[error("Encrypted with unknown key id {0}")]
+    UnknownKey(u32),
 }
 
-/// Encrypts a string using AES-GCM encryption
-///
-/// # Arguments
-/// * `value` - The string to encrypt
-///
-/// # Returns
-/// The encrypted string in base64 format
-pub fn encrypt(value: &str) -> Result<String, EncryptionError> {
-    let encryption_key = std::env::var("ENCRYPTION_KEY")?;
+/// The set of keys values can be decrypted with. The highest id encrypts.
+#[derive(Clone)]
+pub struct KeyRing {
+    keys: BTreeMap<u32, Aes256Gcm>,
+}
 
-    let key = BASE64
-        .decode(encryption_key)
-        .map_err(|e| EncryptionError::KeyDecodeError(e.to_string()))?;
+impl KeyRing {
+    /// Build the ring from `ENCRYPTION_KEYS` and `ENCRYPTION_KEY`.
+    ///
+    /// Read on every call, like the single key was, so tests and
+    /// re-derived keys take effect without a restart.
+    pub fn from_env() -> Result<Self, EncryptionError> {
+        let keys = std::env::var("ENCRYPTION_KEYS").ok();
+        let legacy = std::env::var("ENCRYPTION_KEY").ok();
+        if keys.as_deref().map(str::trim).unwrap_or("").is_empty() && legacy.is_none() {
+            return Err(EncryptionError::EnvError(std::env::VarError::NotPresent));
+        }
+        Self::parse(keys.as_deref(), legacy.as_deref())
+    }
+
+    /// Build a ring from an `ENCRYPTION_KEYS`-style list plus an optional
+    /// legacy key (id 0).
+    pub fn parse(keys: Option<&str>, legacy: Option<&str>) -> Result<Self, EncryptionError> {
+        let mut ring = BTreeMap::new();
+        if let Some(legacy) = legacy {
+            ring.insert(0, cipher_for(legacy)?);
+        }
+        for entry in keys.unwrap_or("").split(',').map(str::trim) {
+            if entry.is_empty() {
+                continue;
+            }
+            let (id, key) = entry.split_once(':').ok_or_else(|| {
+                EncryptionError::KeyRingError("entries must look like <id>:<base64 key>".into())
+            })?;
+            let id: u32 = id
+                .trim()
+                .parse()
+                .map_err(|_| EncryptionError::KeyRingError(format!("invalid key id '{}'", id)))?;
+            if id == 0 && legacy.is_some() {
+                return Err(EncryptionError::KeyRingError(
+                    "key id 0 is reserved for ENCRYPTION_KEY".into(),
+                ));
+            }
+            if ring.insert(id, cipher_for(key.trim())?).is_some() {
+                return Err(EncryptionError::KeyRingError(format!(
+                    "duplicate key id {}",
+                    id
+                )));
+            }
+        }
+        if ring.is_empty() {
+            return Err(EncryptionError::KeyRingError("no keys configured".into()));
+        }
+        Ok(Self { keys: ring })
+    }
 
-    let cipher =
-        Aes256Gcm::new_from_slice(&key).map_err(|e| EncryptionError::CipherError(e.to_string()))?;
+    /// The id new values are encrypted with.
+    pub fn active_key_id(&self) -> u32 {
+        *self
+            .keys
+            .keys()
+            .next_back()
+            .expect("key ring is never empty")
+    }
 
-    let mut rng = rand::thread_rng();
-    let mut nonce_bytes = [0u8; 12];
-    rng.fill(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
+    pub fn key_ids(&self) -> Vec<u32> {
+        self.keys.keys().copied().collect()
+    }
 
-    let ciphertext = cipher
-        .encrypt(nonce, value.as_bytes())
-        .map_err(|e| EncryptionError::EncryptFailed(e.to_string()))?;
+    pub fn encrypt(&self, value: &str) -> Result<String, EncryptionError> {
+        let key_id = self.active_key_id();
+        let cipher = &self.keys[&key_id];
+        let header = format!("{}:{}", VERSION_TAG, key_id);
 
-    let mut combined = nonce_bytes.to_vec();
-    combined.extend(ciphertext);
+        let mut nonce_bytes = [0u8; NONCE_LEN];
+        rand::thread_rng().fill(&mut nonce_bytes);
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce_bytes),
+                Payload {
+                    msg: value.as_bytes(),
+                    aad: header.as_bytes(),
+                },
+            )
+            .map_err(|e| EncryptionError::EncryptFailed(e.to_string()))?;
 
-    Ok(BASE64.encode(combined))
-}
+        let mut combined = nonce_bytes.to_vec();
+        combined.extend(ciphertext);
+        Ok(format!("{}:{}", header, BASE64.encode(combined)))
+    }
 
-/// Decrypts a string that was encrypted using AES-GCM
-///
-/// # Arguments
-/// * `encrypted` - The encrypted string in base64 format
-///
-/// # Returns
-/// The decrypted string
-pub fn decrypt(encrypted: &str) -> Result<String, EncryptionError> {
-    let encryption_key = std::env::var("ENCRYPTION_KEY")?;
+    pub fn decrypt(&self, encrypted: &str) -> Result<String, EncryptionError> {
+        match parse_header(encrypted)? {
+            Some((key_id, header, body)) => {
+                let cipher = self
+                    .keys
+                    .get(&key_id)
+                    .ok_or(EncryptionError::UnknownKey(key_id))?;
+                open(cipher, body, header.as_bytes())
+            }
+            None => {
+                // Unversioned: the original key first, then newest to oldest.
+                let mut last_error = EncryptionError::InvalidData;
+                let order = self.keys.get(&0).into_iter().chain(
+                    self.keys
+                        .iter()
+                        .rev()
+                        .filter(|(id, _)| **id != 0)
+                        .map(|(_, c)| c),
+                );
+                for cipher in order {
+                    match open(cipher, encrypted, b"") {
+                        Ok(plaintext) => return Ok(plaintext),
+                        Err(e) => last_error = e,
+                    }
+                }
+                Err(last_error)
+            }
+        }
+    }
 
+    /// Whether a value is already encrypted with the active key.
+    pub fn is_current(&self, encrypted: &str) -> bool {
+        key_id(encrypted) == Some(self.active_key_id())
+    }
+
+    /// Re-encrypt a value under the active key, or `None` if it already is.
+    pub fn reencrypt(&self, encrypted: &str) -> Result<Option<String>, EncryptionError> {
+        if self.is_current(encrypted) {
+            return Ok(None);
+        }
+        let plaintext = self.decrypt(encrypted)?;
+        self.encrypt(&plaintext).map(Some)
+    }
+}
+
+fn cipher_for(key: &str) -> Result<Aes256Gcm, EncryptionError> {
     let key = BASE64
-        .decode(encryption_key)
+        .decode(key)
         .map_err(|e| EncryptionError::KeyDecodeError(e.to_string()))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| EncryptionError::CipherError(e.to_string()))
+}
 
-    let cipher =
-        Aes256Gcm::new_from_slice(&key).map_err(|e| EncryptionError::CipherError(e.to_string()))?;
+/// Splits `lf1:<id>:<body>` into id, header and body. `None` for
+/// unversioned values.
+fn parse_header(encrypted: &str) -> Result<Option<(u32, &str, &str)>, EncryptionError> {
+    let Some(rest) = encrypted
+        .strip_prefix(VERSION_TAG)
+        .and_then(|r| r.strip_prefix(':'))
+    else {
+        return Ok(None);
+    };
+    let (id, body) = rest.split_once(':').ok_or(EncryptionError::InvalidData)?;
+    let key_id = id.parse().map_err(|_| EncryptionError::InvalidData)?;
+    let header = &encrypted[..VERSION_TAG.len() + 1 + id.len()];
+    Ok(Some((key_id, header, body)))
+}
 
+fn open(cipher: &Aes256Gcm, body: &str, aad: &[u8]) -> Result<String, EncryptionError> {
     let encrypted_data = BASE64
-        .decode(encrypted)
+        .decode(body)
         .map_err(|e| EncryptionError::KeyDecodeError(e.to_string()))?;
 
-    if encrypted_data.len() < 12 {
+    if encrypted_data.len() < NONCE_LEN {
         return Err(EncryptionError::InvalidData);
     }
 
-    let (nonce_bytes, ciphertext) = encrypted_data.split_at(12);
-    let nonce = Nonce::from_slice(nonce_bytes);
-
+    let (nonce_bytes, ciphertext) = encrypted_data.split_at(NONCE_LEN);
     let plaintext = cipher
-        .decrypt(nonce, ciphertext)
+        .decrypt(
+            Nonce::from_slice(nonce_bytes),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
         .map_err(|e| EncryptionError::DecryptionError(e.to_string()))?;
 
     String::from_utf8(plaintext).map_err(|e| EncryptionError::Utf8Error(e.to_string()))
 }
+
+/// The key id in a value's header, `None` for unversioned values.
+pub fn key_id(encrypted: &str) -> Option<u32> {
+    parse_header(encrypted).ok().flatten().map(|(id, _, _)| id)
+}
+
+/// Encrypts a string using AES-GCM encryption
+///
+/// # Arguments
+/// * `value` - The string to encrypt
+///
+/// # Returns
+/// The encrypted string, tagged with the id of the newest key
+pub fn encrypt(value: &str) -> Result<String, EncryptionError> {
+    KeyRing::from_env()?.encrypt(value)
+}
+
+/// Decrypts a string that was encrypted using AES-GCM
+///
+/// # Arguments
+/// * `encrypted` - The encrypted string, versioned or legacy base64
+///
+/// # Returns
+/// The decrypted string
+pub fn decrypt(encrypted: &str) -> Result<String, EncryptionError> {
+    KeyRing::from_env()?.decrypt(encrypted)
+}
@@ -882,20 +882,21 @@ pub async fn get_cached_client(user_id: i32, state: &Arc<AppState>) -> Result<Ar
 }
 
 /// Tear down the Matrix client and sync loop for `user_id`, but only if they
-/// have no remaining connected bridges. Call this from bridge disconnect flows
-/// instead of unconditionally removing the client - otherwise disconnecting one
-/// bridge kills sync for the others sharing the same Matrix account.
+/// have no remaining connected bridges and no Matrix DM channel. Call this
+/// from bridge disconnect flows instead of unconditionally removing the
+/// client - otherwise disconnecting one bridge kills sync for the others
+/// sharing the same Matrix account.
 ///
 /// On DB error we default to `true` (has bridges) so a transient pool blip
 /// never silently evicts a live client.
 pub async fn stop_matrix_user_if_no_bridges(user_id: i32, state: &Arc<AppState>) -> Result<()> {
     let has_bridges = state
         .user_repository
-        .has_active_bridges(user_id)
+        .matrix_account_in_use(user_id)
         .unwrap_or(true);
     if has_bridges {
         tracing::debug!(
-            "Keeping Matrix client alive for user {} - other bridges still connected",
+            "Keeping Matrix client alive for user {} - bridges or Matrix DM still in use",
             user_id
         );
         return Ok(());
@@ -268,6 +268,7 @@ async fn send_alert_with_context(
         status: None,
         recharge_threshold_timestamp: None,
         zero_credits_timestamp: None,
+        segments: None,
     });
 
     // Send the email
@@ -610,6 +611,7 @@ pub async fn send_admin_alert(
                 status: Some("sent".to_string()),
                 recharge_threshold_timestamp: None,
                 zero_credits_timestamp: None,
+                segments: None,
             }) {
                 tracing::warn!("Failed to log admin alert for cooldown tracking: {}", e);
             }
@@ -0,0 +1,255 @@
+//! SMS encoding and segment accounting.
+//!
+//! A body that only uses the GSM 03.38 alphabet is sent as GSM-7: 160
+//! septets in a single SMS, 153 per part once it has to be concatenated.
+//! One character outside that alphabet (an emoji, a curly quote) switches
+//! the whole message to UCS-2, which fits only 70 UTF-16 units, 67 per
+//! part. Carriers bill per part, so that one character can triple the cost.
+//!
+//! `segment_info` counts parts the way the carriers pack them: an escaped
+//! extension character (`€`, `[`, ...) and a surrogate pair are never split
+//! across two parts. `transliterate_to_gsm7` folds the usual offenders to
+//! GSM-7 equivalents; it only runs for users who turned
+//! `sms_gsm7_transliteration` on, because it changes what they read.
+
+use crate::models::user_models::User;
+use crate::utils::sms_sanitizer::{apply_sms_url_filter, clamp_sms_body};
+
+/// GSM 03.38 basic character set. Each costs one septet.
+const GSM7_BASIC: &str = "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?\
+¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà";
+
+/// Extension table, reached through an escape septet: two septets each.
+const GSM7_EXTENSION: &str = "\u{000C}^{}\\[~]|€";
+
+const GSM7_SINGLE_SEGMENT: usize = 160;
+const GSM7_MULTIPART_SEGMENT: usize = 153;
+const UCS2_SINGLE_SEGMENT: usize = 70;
+const UCS2_MULTIPART_SEGMENT: usize = 67;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsEncoding {
+    Gsm7,
+    Ucs2,
+}
+
+impl SmsEncoding {
+    /// Detect the encoding a provider will pick for `body`.
+    pub fn for_body(body: &str) -> Self {
+        if body.chars().all(|c| gsm7_septets(c).is_some()) {
+            SmsEncoding::Gsm7
+        } else {
+            SmsEncoding::Ucs2
+        }
+    }
+
+    fn limits(self) -> (usize, usize) {
+        match self {
+            SmsEncoding::Gsm7 => (GSM7_SINGLE_SEGMENT, GSM7_MULTIPART_SEGMENT),
+            SmsEncoding::Ucs2 => (UCS2_SINGLE_SEGMENT, UCS2_MULTIPART_SEGMENT),
+        }
+    }
+
+    /// Septets (GSM-7) or UTF-16 code units (UCS-2) taken by `c`.
+    fn cost(self, c: char) -> usize {
+        match self {
+            SmsEncoding::Gsm7 => gsm7_septets(c).unwrap_or(1),
+            SmsEncoding::Ucs2 => c.len_utf16(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentInfo {
+    pub encoding: SmsEncoding,
+    /// Septets for GSM-7, UTF-16 code units for UCS-2.
+    pub units: usize,
+    pub segments: usize,
+}
+
+fn gsm7_septets(c: char) -> Option<usize> {
+    if GSM7_BASIC.contains(c) {
+        Some(1)
+    } else if GSM7_EXTENSION.contains(c) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Encoding, size and number of billed parts for `body`. An empty body
+/// still goes out as one part.
+pub fn segment_info(body: &str) -> SegmentInfo {
+    let encoding = SmsEncoding::for_body(body);
+    let (single, multipart) = encoding.limits();
+    let units: usize = body.chars().map(|c| encoding.cost(c)).sum();
+    if units <= single {
+        return SegmentInfo {
+            encoding,
+            units,
+            segments: 1,
+        };
+    }
+
+    let mut segments = 1;
+    let mut used = 0;
+    for c in body.chars() {
+        let cost = encoding.cost(c);
+        if used + cost > multipart {
+            segments += 1;
+            used = 0;
+        }
+        used += cost;
+    }
+    SegmentInfo {
+        encoding,
+        units,
+        segments,
+    }
+}
+
+pub fn segment_count(body: &str) -> usize {
+    segment_info(body).segments
+}
+
+/// Characters of plain GSM-7 text that fit in `segments` parts.
+pub fn gsm7_chars_for_segments(segments: usize) -> usize {
+    match segments {
+        0 => 0,
+        1 => GSM7_SINGLE_SEGMENT,
+        n => n * GSM7_MULTIPART_SEGMENT,
+    }
+}
+
+/// How many leading characters of `body` fit in `max_segments` parts, in
+/// the encoding the whole body needs.
+pub fn max_chars_within_segments(body: &str, max_segments: usize) -> usize {
+    if max_segments == 0 {
+        return 0;
+    }
+    if segment_info(body).segments <= max_segments {
+        return body.chars().count();
+    }
+
+    let encoding = SmsEncoding::for_body(body);
+    let (single, multipart) = encoding.limits();
+    let mut chars = 0;
+    if max_segments == 1 {
+        let mut used = 0;
+        for c in body.chars() {
+            used += encoding.cost(c);
+            if used > single {
+                break;
+            }
+            chars += 1;
+        }
+        return chars;
+    }
+
+    let mut segments = 1;
+    let mut used = 0;
+    for c in body.chars() {
+        let cost = encoding.cost(c);
+        if used + cost > multipart {
+            if segments == max_segments {
+                break;
+            }
+            segments += 1;
+            used = 0;
+        }
+        used += cost;
+        chars += 1;
+    }
+    chars
+}
+
+/// Fold characters that would force UCS-2 to GSM-7 equivalents: curly
+/// quotes, dashes, ellipses, odd spaces, bullets and common emoji.
+/// Characters with no sensible stand-in are left alone, so the result can
+/// still need UCS-2.
+pub fn transliterate_to_gsm7(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    for c in body.chars() {
+        if gsm7_septets(c).is_some() {
+            out.push(c);
+            continue;
+        }
+        match gsm7_replacement(c) {
+            Some(replacement) => out.push_str(replacement),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+fn gsm7_replacement(c: char) -> Option<&'static str> {
+    let replacement = match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' | '\u{00B4}' | '`' => "'",
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' | '\u{00AB}'
+        | '\u{00BB}' => "\"",
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => "-",
+        '\u{2026}' => "...",
+        '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => " ",
+        '\t' => " ",
+        // Zero-width joiners, variation selectors and skin-tone modifiers
+        // only decorate the character before them.
+        '\u{200B}'..='\u{200D}'
+        | '\u{2060}'
+        | '\u{FEFF}'
+        | '\u{FE0E}'
+        | '\u{FE0F}'
+        | '\u{1F3FB}'..='\u{1F3FF}' => "",
+        '\u{2022}' | '\u{00B7}' | '\u{2023}' | '\u{25E6}' | '\u{2043}' => "-",
+        '\u{2192}' => "->",
+        '\u{2190}' => "<-",
+        '\u{00D7}' => "x",
+        '\u{2122}' => "TM",
+        '\u{00A9}' => "(c)",
+        '\u{00AE}' => "(R)",
+        'á' | 'â' | 'ã' => "a",
+        'Á' | 'Â' | 'Ã' | 'À' => "A",
+        'ê' | 'ë' => "e",
+        'È' | 'Ê' | 'Ë' => "E",
+        'í' | 'î' | 'ï' => "i",
+        'Í' | 'Ì' | 'Î' | 'Ï' => "I",
+        'ó' | 'ô' | 'õ' => "o",
+        'Ó' | 'Ò' | 'Ô' | 'Õ' => "O",
+        'ú' | 'û' => "u",
+        'Ú' | 'Ù' | 'Û' => "U",
+        'ý' | 'ÿ' => "y",
+        '🙂' | '😊' | '😀' | '😃' | '😄' | '😁' | '☺' => ":)",
+        '😂' | '🤣' | '😆' => ":D",
+        '😉' => ";)",
+        '🙁' | '😞' | '😢' | '😭' | '☹' => ":(",
+        '😛' | '😜' | '😝' => ":P",
+        '😮' | '😯' | '😲' => ":O",
+        '😘' => ":*",
+        '❤' | '♥' | '💕' | '💖' | '💗' | '💙' | '💚' | '💛' | '💜' => "<3",
+        '👍' => "+1",
+        '👎' => "-1",
+        '✅' | '✔' | '✓' => "OK",
+        '❌' | '✖' => "X",
+        '⚠' => "!",
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+/// The body `ChannelRouter` hands to SMS providers for `user`: URL filter,
+/// then transliteration when the user opted in. Clamping happens per
+/// attempt in the router.
+pub fn outbound_sms_body(user: &User, body: &str) -> String {
+    let body = apply_sms_url_filter(body);
+    if user.sms_gsm7_transliteration {
+        transliterate_to_gsm7(&body)
+    } else {
+        body
+    }
+}
+
+/// Segments of the first delivery attempt of `body` to `user`, as recorded
+/// in `usage_logs.segments`.
+pub fn outbound_sms_segments(user: &User, body: &str) -> i32 {
+    let body = clamp_sms_body(&outbound_sms_body(user, body));
+    segment_count(&body) as i32
+}
@@ -65,35 +65,6 @@ pub async fn get_weather_for_location(
     forecast_type: &str,
     preferred_timezone: Option<&str>,
 ) -> Result<String, Box<dyn Error>> {
-    let client = reqwest::Client::new();
-    let geoapify_key = std::env::var("GEOAPIFY_API_KEY")
-        .map_err(|_| "GEOAPIFY_API_KEY environment variable not set")?;
-    let pirate_weather_key = std::env::var("PIRATE_WEATHER_API_KEY")
-        .map_err(|_| "PIRATE_WEATHER_API_KEY environment variable not set")?;
-
-    // First, get coordinates using Geoapify
-    let geocoding_url = format!(
-        "https://api.geoapify.com/v1/geocode/search?text={}&format=json&apiKey={}",
-        urlencoding::encode(location),
-        geoapify_key
-    );
-
-    let geocoding_response: serde_json::Value =
-        client.get(&geocoding_url).send().await?.json().await?;
-
-    let results = geocoding_response["results"]
-        .as_array()
-        .ok_or("No results found")?;
-
-    if results.is_empty() {
-        return Err("Location not found".into());
-    }
-
-    let result = &results[0];
-    let lat = result["lat"].as_f64().ok_or("Latitude not found")?;
-    let lon = result["lon"].as_f64().ok_or("Longitude not found")?;
-    let location_name = result["formatted"].as_str().unwrap_or(location);
-
     // Get weather data using Pirate Weather
     let unit_system = match units {
         "imperial" => "us",
@@ -108,12 +79,8 @@ pub async fn get_weather_for_location(
         _ => "minutely,daily,alerts", // default to current + some hourly
     };
 
-    let weather_url = format!(
-        "https://api.pirateweather.net/forecast/{}/{},{}?units={}&exclude={}",
-        pirate_weather_key, lat, lon, unit_system, exclude
-    );
-
-    let weather_data: serde_json::Value = client.get(&weather_url).send().await?.json().await?;
+    let (location_name, weather_data) = fetch_forecast(location, unit_system, exclude).await?;
+    let location_name = location_name.as_deref().unwrap_or(location);
 
     let current = weather_data["currently"]
         .as_object()
@@ -213,6 +180,63 @@ pub async fn get_weather_for_location(
     Ok(response)
 }
 
+/// Current outside temperature at `location` in °C, for rule conditions
+/// that compare against a number rather than read the weather reply.
+pub async fn get_current_temperature(location: &str) -> Result<f64, Box<dyn Error>> {
+    let (_, weather_data) = fetch_forecast(location, "si", "minutely,hourly,daily,alerts").await?;
+    current_temperature(&weather_data).ok_or_else(|| "No current temperature".into())
+}
+
+/// The `currently.temperature` reading of a Pirate Weather forecast.
+pub fn current_temperature(weather_data: &serde_json::Value) -> Option<f64> {
+    weather_data["currently"]["temperature"].as_f64()
+}
+
+/// Geocode `location` with Geoapify and fetch its Pirate Weather forecast.
+/// Returns the geocoded place name (if any) and the raw forecast.
+async fn fetch_forecast(
+    location: &str,
+    unit_system: &str,
+    exclude: &str,
+) -> Result<(Option<String>, serde_json::Value), Box<dyn Error>> {
+    let client = reqwest::Client::new();
+    let geoapify_key = std::env::var("GEOAPIFY_API_KEY")
+        .map_err(|_| "GEOAPIFY_API_KEY environment variable not set")?;
+    let pirate_weather_key = std::env::var("PIRATE_WEATHER_API_KEY")
+        .map_err(|_| "PIRATE_WEATHER_API_KEY environment variable not set")?;
+
+    // First, get coordinates using Geoapify
+    let geocoding_url = format!(
+        "https://api.geoapify.com/v1/geocode/search?text={}&format=json&apiKey={}",
+        urlencoding::encode(location),
+        geoapify_key
+    );
+
+    let geocoding_response: serde_json::Value =
+        client.get(&geocoding_url).send().await?.json().await?;
+
+    let results = geocoding_response["results"]
+        .as_array()
+        .ok_or("No results found")?;
+
+    if results.is_empty() {
+        return Err("Location not found".into());
+    }
+
+    let result = &results[0];
+    let lat = result["lat"].as_f64().ok_or("Latitude not found")?;
+    let lon = result["lon"].as_f64().ok_or("Longitude not found")?;
+    let location_name = result["formatted"].as_str().map(str::to_string);
+
+    let weather_url = format!(
+        "https://api.pirateweather.net/forecast/{}/{},{}?units={}&exclude={}",
+        pirate_weather_key, lat, lon, unit_system, exclude
+    );
+
+    let weather_data: serde_json::Value = client.get(&weather_url).send().await?.json().await?;
+    Ok((location_name, weather_data))
+}
+
 /// Ask Perplexity - always uses OpenRouter since Perplexity models are only on OpenRouter
 pub async fn ask_perplexity(
     state: &Arc<AppState>,
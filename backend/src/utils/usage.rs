@@ -389,6 +389,46 @@ pub fn deduct_hosted_voice_phone_leg(
     Ok(())
 }
 
+/// Compare a provider's final SMS price with the segments counted before
+/// sending. Only logs a mismatch: the provider's price is what gets billed.
+fn cross_check_sms_segments(
+    state: &Arc<AppState>,
+    user: &crate::models::user_models::User,
+    sid: &str,
+    price_usd: f32,
+    provider: &str,
+) {
+    let segments = match state.user_repository.get_usage_log_segments(sid) {
+        Ok(Some(segments)) => segments,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!("Failed to read segments for sid {}: {}", sid, e);
+            return;
+        }
+    };
+    let Some(pricing) = get_country_code_from_phone(&user.phone_number).and_then(|code| {
+        crate::api::twilio_pricing::get_cached_notification_pricing_sync(state, &code)
+    }) else {
+        return;
+    };
+    if pricing.raw_sms_price <= 0.0 {
+        return;
+    }
+    let billed = (price_usd / pricing.raw_sms_price).round() as i32;
+    if billed != segments {
+        tracing::warn!(
+            "{} billed sid {} for user {} at {:.4} USD, about {} segment(s) at {:.4}; {} counted before sending",
+            provider,
+            sid,
+            user.id,
+            price_usd,
+            billed,
+            pricing.raw_sms_price,
+            segments
+        );
+    }
+}
+
 /// Calculate cost for voice events using cached pricing.
 /// Cost = Twilio phone leg (country-specific, direction-aware) + Tinfoil inference.
 fn get_activity_cost(
@@ -450,6 +490,9 @@ pub fn deduct_from_twilio_price(
     if abs_price == 0.0 {
         return Ok(0.0);
     }
+    if let Some(sid) = transaction_id {
+        cross_check_sms_segments(state, &user, sid, abs_price, provider);
+    }
 
     // Apply margin. Twilio price already reflects country-specific costs.
     let cost = abs_price * TWILIO_COST_MARGIN;
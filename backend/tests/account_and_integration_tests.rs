@@ -1,5 +1,7 @@
 #[path = "agent_integration_test.rs"]
 mod agent_integration_test;
+#[path = "caldav_test.rs"]
+mod caldav_test;
 #[path = "contact_send_resolution_test.rs"]
 mod contact_send_resolution_test;
 #[path = "country_service_test.rs"]
@@ -12,6 +14,12 @@ mod id_verifier_test;
 mod imap_auth_test;
 #[path = "imap_idle_test.rs"]
 mod imap_idle_test;
+#[path = "mcp_client_test.rs"]
+mod mcp_client_test;
+#[path = "mcp_oauth_test.rs"]
+mod mcp_oauth_test;
+#[path = "mcp_server_test.rs"]
+mod mcp_server_test;
 #[path = "sender_match_test.rs"]
 mod sender_match_test;
 #[path = "signup_service_test.rs"]
@@ -1,8 +1,12 @@
 use axum::body::{to_bytes, Body};
 use axum::http::{Request, StatusCode};
-use axum::routing::post;
+use axum::routing::{delete, get, post};
 use axum::Router;
-use backend::pg_schema::{agent_credentials, imap_connection, ont_events, pending_reply_watches};
+use backend::models::agent_integration_models::{canonical_scopes, DEFAULT_AGENT_SCOPES};
+use backend::models::ontology_models::{NewOntEvent, NewOntMessage};
+use backend::pg_schema::{
+    agent_action_audit, agent_credentials, imap_connection, ont_events, pending_reply_watches,
+};
 use backend::repositories::agent_integration_repository::{
     AgentIntegrationRepository, CredentialClaim, CredentialIssue, IdempotencyClaim, PairingPoll,
 };
@@ -21,6 +25,7 @@ fn mint_credential(
     user_id: i32,
     now: i32,
     raw_token: &str,
+    scopes: &str,
 ) -> backend::models::agent_integration_models::AgentCredential {
     let repository = AgentIntegrationRepository::new(state.pg_pool.clone());
     repository
@@ -34,7 +39,7 @@ fn mint_credential(
         .unwrap();
     assert_eq!(
         repository
-            .approve_pairing(user_id, &hash("ABCDEFGHJKLM"), now + 1)
+            .approve_pairing(user_id, &hash("ABCDEFGHJKLM"), scopes, now + 1)
             .unwrap(),
         Some("Codex".to_string())
     );
@@ -70,7 +75,7 @@ fn pairing_tokens_are_one_time_hashed_scoped_and_revocable() {
     let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
     let now = chrono::Utc::now().timestamp() as i32;
     let raw = format!("lfagent_{}", "a".repeat(64));
-    let credential = mint_credential(&state, user.id, now, &raw);
+    let credential = mint_credential(&state, user.id, now, &raw, DEFAULT_AGENT_SCOPES);
     let repository = AgentIntegrationRepository::new(state.pg_pool.clone());
     assert!(repository
         .consume_pairing(
@@ -95,7 +100,7 @@ fn pairing_tokens_are_one_time_hashed_scoped_and_revocable() {
         .unwrap();
     assert_eq!(stored_hash, hash(&raw));
     assert_ne!(stored_hash, raw);
-    assert_eq!(credential.scopes, "reminders,reply_watch_email");
+    assert_eq!(credential.scopes, "reminders:write,reply_watch:write");
     assert_eq!(
         repository.list_credentials(user.id, now + 3).unwrap().len(),
         1
@@ -106,7 +111,7 @@ fn pairing_tokens_are_one_time_hashed_scoped_and_revocable() {
         .is_empty());
 
     assert!(matches!(
-        repository.claim_credential(&hash(&raw), "reminders", now + 3),
+        repository.claim_credential(&hash(&raw), "reminders:write", now + 3),
         Ok(CredentialClaim::Accepted(_))
     ));
     let fresh = repository
@@ -127,7 +132,7 @@ fn pairing_tokens_are_one_time_hashed_scoped_and_revocable() {
         .revoke_by_token_hash(&hash(&raw), now + 5)
         .unwrap());
     assert!(repository
-        .authenticate_credential(&hash(&raw), "reminders", now + 6)
+        .authenticate_credential(&hash(&raw), "reminders:write", now + 6)
         .unwrap()
         .is_none());
 }
@@ -210,7 +215,7 @@ async fn reminder_action_returns_only_status_and_rejects_query_tokens() {
     let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
     let now = chrono::Utc::now().timestamp() as i32;
     let raw = format!("lfagent_{}", "b".repeat(64));
-    mint_credential(&state, user.id, now, &raw);
+    mint_credential(&state, user.id, now, &raw, DEFAULT_AGENT_SCOPES);
     let app = Router::new()
         .route(
             "/api/agent/actions/reminders",
@@ -282,3 +287,325 @@ async fn reminder_action_returns_only_status_and_rejects_query_tokens() {
         .unwrap();
     assert_eq!(missing_idempotency.status(), StatusCode::BAD_REQUEST);
 }
+
+#[test]
+fn scopes_are_validated_and_stored_canonically() {
+    assert_eq!(
+        canonical_scopes(&["events:read", "reminders:write", "events:read"]),
+        Some("reminders:write,events:read".to_string())
+    );
+    assert_eq!(canonical_scopes(&["sms:self", "contacts:read"]), None);
+    assert_eq!(canonical_scopes::<&str>(&[]), None);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn read_scope_returns_events_and_denies_writes_with_audit() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    let now = chrono::Utc::now().timestamp() as i32;
+    let raw = format!("lfagent_{}", "c".repeat(64));
+    let credential = mint_credential(&state, user.id, now, &raw, "events:read");
+    state
+        .ontology_repository
+        .create_reminder(
+            &NewOntEvent {
+                user_id: user.id,
+                description: "Renew passport".to_string(),
+                remind_at: Some(now + 3600),
+                due_at: Some(now + 3600),
+                status: "active".to_string(),
+                created_at: now,
+                updated_at: now,
+            },
+            "UTC",
+        )
+        .unwrap();
+    let app = Router::new()
+        .route(
+            "/api/agent/events",
+            get(backend::handlers::agent_integration_handlers::list_events),
+        )
+        .route(
+            "/api/agent/digest",
+            get(backend::handlers::agent_integration_handlers::list_digest),
+        )
+        .route(
+            "/api/agent/actions/reminders",
+            post(backend::handlers::agent_integration_handlers::create_reminder),
+        )
+        .with_state(state.clone());
+
+    let events = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/agent/events")
+                .header("authorization", format!("Bearer {raw}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.status(), StatusCode::OK);
+    assert_eq!(events.headers().get("cache-control").unwrap(), "no-store");
+    let events_json: Value =
+        serde_json::from_slice(&to_bytes(events.into_body(), 4096).await.unwrap()).unwrap();
+    assert_eq!(events_json["status"], "accepted");
+    assert_eq!(events_json["events"][0]["description"], "Renew passport");
+    assert_eq!(
+        events_json["events"][0].as_object().unwrap().len(),
+        4,
+        "events expose only id, description and times"
+    );
+
+    let digest = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/agent/digest")
+                .header("authorization", format!("Bearer {raw}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(digest.status(), StatusCode::FORBIDDEN);
+
+    let write = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/agent/actions/reminders")
+                .header("authorization", format!("Bearer {raw}"))
+                .header("idempotency-key", "request-4")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "message": "Not allowed",
+                        "at": (chrono::Utc::now() + chrono::Duration::minutes(5)).to_rfc3339(),
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(write.status(), StatusCode::FORBIDDEN);
+
+    let mut conn = state.pg_pool.get().unwrap();
+    let mut audit = agent_action_audit::table
+        .filter(agent_action_audit::credential_id.eq(credential.id))
+        .select((agent_action_audit::action_kind, agent_action_audit::outcome))
+        .load::<(String, String)>(&mut conn)
+        .unwrap();
+    audit.sort();
+    assert_eq!(
+        audit,
+        vec![
+            ("digest_read".to_string(), "rejected".to_string()),
+            ("events_read".to_string(), "accepted".to_string()),
+            ("reminder".to_string(), "rejected".to_string()),
+        ]
+    );
+    let used = agent_credentials::table
+        .find(credential.id)
+        .select(agent_credentials::daily_used)
+        .first::<i32>(&mut conn)
+        .unwrap();
+    assert_eq!(used, 0, "reads and denied writes do not consume the cap");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn digest_read_leaves_out_the_sender() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    let now = chrono::Utc::now().timestamp() as i32;
+    let raw = format!("lfagent_{}", "e".repeat(64));
+    mint_credential(&state, user.id, now, &raw, "digest:read");
+    let (message, _is_new) = state
+        .ontology_repository
+        .insert_message(&NewOntMessage {
+            user_id: user.id,
+            room_id: "!r1".to_string(),
+            platform: "whatsapp".to_string(),
+            sender_name: "Alice".to_string(),
+            sender_key: None,
+            content: "landlord says rent is due".to_string(),
+            person_id: None,
+            created_at: now,
+            matrix_event_id: None,
+        })
+        .unwrap();
+    state
+        .ontology_repository
+        .update_message_classification(
+            message.id,
+            "later",
+            "admin",
+            Some("Rent is due"),
+            None,
+            None,
+        )
+        .unwrap();
+    let app = Router::new()
+        .route(
+            "/api/agent/digest",
+            get(backend::handlers::agent_integration_handlers::list_digest),
+        )
+        .with_state(state.clone());
+
+    let digest = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/agent/digest")
+                .header("authorization", format!("Bearer {raw}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(digest.status(), StatusCode::OK);
+    let digest_json: Value =
+        serde_json::from_slice(&to_bytes(digest.into_body(), 4096).await.unwrap()).unwrap();
+    let item = digest_json["items"][0].as_object().unwrap();
+    let mut fields: Vec<&str> = item.keys().map(String::as_str).collect();
+    fields.sort();
+    assert_eq!(fields, ["created_at", "platform", "summary"]);
+    assert_eq!(item["summary"], "Rent is due");
+}
+
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    token: &str,
+    idempotency_key: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {token}"));
+    if let Some(key) = idempotency_key {
+        request = request.header("idempotency-key", key);
+    }
+    let request = match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    };
+    let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), 8192).await.unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn credentials_list_cancel_and_snooze_only_their_own_reminders() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    let now = chrono::Utc::now().timestamp() as i32;
+    let raw = format!("lfagent_{}", "d".repeat(64));
+    let other = format!("lfagent_{}", "e".repeat(64));
+    mint_credential(&state, user.id, now, &raw, DEFAULT_AGENT_SCOPES);
+    mint_credential(&state, user.id, now, &other, DEFAULT_AGENT_SCOPES);
+    use backend::handlers::agent_integration_handlers as agent;
+    let app = Router::new()
+        .route("/api/agent/actions/reminders", post(agent::create_reminder))
+        .route("/api/agent/reminders", get(agent::list_created_reminders))
+        .route(
+            "/api/agent/reminders/{item_id}",
+            delete(agent::cancel_created_reminder),
+        )
+        .route(
+            "/api/agent/reminders/{item_id}/snooze",
+            post(agent::snooze_created_reminder),
+        )
+        .with_state(state.clone());
+
+    let (status, _) = call(
+        &app,
+        "POST",
+        "/api/agent/actions/reminders",
+        &raw,
+        Some("create-1"),
+        Some(json!({
+            "message": "Stretch",
+            "at": (chrono::Utc::now() + chrono::Duration::minutes(10)).to_rfc3339(),
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, listed) = call(&app, "GET", "/api/agent/reminders", &raw, None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let reminder = &listed["reminders"][0];
+    assert_eq!(reminder["status"], "scheduled");
+    let mut fields = reminder
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    fields.sort();
+    assert_eq!(fields, ["created_at", "id", "remind_at", "status"]);
+    let id = reminder["id"].as_i64().unwrap();
+
+    let (_, others) = call(&app, "GET", "/api/agent/reminders", &other, None, None).await;
+    assert_eq!(others["reminders"], json!([]));
+    let (status, _) = call(
+        &app,
+        "DELETE",
+        &format!("/api/agent/reminders/{id}"),
+        &other,
+        Some("cancel-other"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = call(
+        &app,
+        "POST",
+        &format!("/api/agent/reminders/{id}/snooze"),
+        &raw,
+        Some("snooze-1"),
+        Some(json!({ "minutes": 30 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, listed) = call(&app, "GET", "/api/agent/reminders", &raw, None, None).await;
+    let remind_at = listed["reminders"][0]["remind_at"].as_i64().unwrap();
+    assert!((remind_at - i64::from(now) - 1800).abs() < 60);
+
+    let (status, _) = call(
+        &app,
+        "DELETE",
+        &format!("/api/agent/reminders/{id}"),
+        &raw,
+        Some("cancel-1"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, listed) = call(&app, "GET", "/api/agent/reminders", &raw, None, None).await;
+    assert_eq!(listed["reminders"], json!([]));
+
+    let mut conn = state.pg_pool.get().unwrap();
+    assert_eq!(
+        ont_events::table
+            .filter(ont_events::user_id.eq(user.id))
+            .filter(ont_events::status.eq("cancelled"))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .unwrap(),
+        1
+    );
+}
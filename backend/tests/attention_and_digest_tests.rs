@@ -14,8 +14,16 @@ mod digest_time_parser_test;
 mod group_attention_gate_test;
 #[path = "reminder_reliability_test.rs"]
 mod reminder_reliability_test;
+#[path = "reply_context_test.rs"]
+mod reply_context_test;
 #[path = "reply_watch_test.rs"]
 mod reply_watch_test;
+#[path = "rule_flow_control_test.rs"]
+mod rule_flow_control_test;
+#[path = "rule_schedule_test.rs"]
+mod rule_schedule_test;
+#[path = "rule_structured_condition_test.rs"]
+mod rule_structured_condition_test;
 #[path = "system_important_routing_test.rs"]
 mod system_important_routing_test;
 #[path = "temporary_alert_suppression_test.rs"]
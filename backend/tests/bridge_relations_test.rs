@@ -0,0 +1,117 @@
+//! Replies, edits and reactions on the send path: the event content we hand
+//! the homeserver must parse the way bridges (and our own ingest gate in
+//! `bridge_edit_test.rs`) read it, and reply/reaction targets are picked by
+//! matching the user's description against sender and text.
+
+use backend::utils::bridge::{
+    edit_window_secs, relation_event_content, reply_target_score, BridgeRelation,
+};
+use matrix_sdk::ruma::events::reaction::ReactionEventContent;
+use matrix_sdk::ruma::events::room::message::{Relation, RoomMessageEventContent};
+
+#[test]
+fn plain_send_has_no_relation() {
+    let (event_type, json) = relation_event_content("hello", None);
+    assert_eq!(event_type, "m.room.message");
+    let content: RoomMessageEventContent = serde_json::from_value(json).unwrap();
+    assert_eq!(content.body(), "hello");
+    assert!(content.relates_to.is_none());
+}
+
+#[test]
+fn reply_points_at_the_target_event() {
+    let relation = BridgeRelation::Reply {
+        event_id: "$dinner:example.com".to_string(),
+    };
+    let (event_type, json) = relation_event_content("yes!", Some(&relation));
+    assert_eq!(event_type, "m.room.message");
+    let content: RoomMessageEventContent = serde_json::from_value(json).unwrap();
+    assert_eq!(content.body(), "yes!");
+    match content.relates_to {
+        Some(Relation::Reply { in_reply_to }) => {
+            assert_eq!(in_reply_to.event_id.as_str(), "$dinner:example.com")
+        }
+        other => panic!("expected a reply relation, got {:?}", other),
+    }
+}
+
+#[test]
+fn edit_replaces_the_original_with_new_content() {
+    let relation = BridgeRelation::Edit {
+        event_id: "$mine:example.com".to_string(),
+    };
+    let (event_type, json) = relation_event_content("see you at 7pm", Some(&relation));
+    assert_eq!(event_type, "m.room.message");
+    assert_eq!(json["body"], "* see you at 7pm");
+    assert_eq!(json["m.new_content"]["body"], "see you at 7pm");
+    let content: RoomMessageEventContent = serde_json::from_value(json).unwrap();
+    match content.relates_to {
+        Some(Relation::Replacement(replacement)) => {
+            assert_eq!(replacement.event_id.as_str(), "$mine:example.com")
+        }
+        other => panic!("expected a replacement, got {:?}", other),
+    }
+}
+
+#[test]
+fn reaction_is_an_annotation_without_text() {
+    let relation = BridgeRelation::Reaction {
+        event_id: "$mom:example.com".to_string(),
+        key: "👍".to_string(),
+    };
+    let (event_type, json) = relation_event_content("ignored", Some(&relation));
+    assert_eq!(event_type, "m.reaction");
+    let content: ReactionEventContent = serde_json::from_value(json).unwrap();
+    assert_eq!(content.relates_to.event_id.as_str(), "$mom:example.com");
+    assert_eq!(content.relates_to.key, "👍");
+    assert_eq!(relation.event_id(), "$mom:example.com");
+}
+
+#[test]
+fn reply_targets_match_on_text_or_sender() {
+    let dinner = "Are we still on for dinner tonight?";
+    assert_eq!(reply_target_score("dinner", "Anna", dinner), 1.0);
+    // Half the words is enough to count as a match.
+    assert_eq!(
+        reply_target_score("the dinner question", "Anna", dinner),
+        0.5
+    );
+    assert_eq!(
+        reply_target_score("Anna's photo", "Anna Virtanen", "📎 IMAGE"),
+        0.5
+    );
+    assert_eq!(
+        reply_target_score("Anna's message", "Anna Virtanen", "hi"),
+        1.0
+    );
+    assert_eq!(
+        reply_target_score("dinner", "Mikko", "Running late, sorry"),
+        0.0
+    );
+    // Only filler words: no basis for a match.
+    assert_eq!(
+        reply_target_score("the last one", "Anna", "the last one"),
+        0.0
+    );
+}
+
+#[test]
+fn edit_windows_follow_each_network() {
+    assert_eq!(edit_window_secs("whatsapp"), Some(15 * 60));
+    assert_eq!(edit_window_secs("signal"), Some(24 * 60 * 60));
+    assert_eq!(edit_window_secs("telegram"), Some(48 * 60 * 60));
+    assert_eq!(edit_window_secs("slack"), None);
+}
+
+#[test]
+fn reaction_and_edit_tools_are_available_to_the_conversational_agent() {
+    let registry = backend::build_tool_registry();
+    for name in ["react_to_chat_message", "edit_chat_message"] {
+        let tool = registry.get(name).expect(name);
+        assert!(
+            tool.is_outgoing(),
+            "{} should go through the send queue",
+            name
+        );
+    }
+}
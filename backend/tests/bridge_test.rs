@@ -248,8 +248,11 @@ mod incoming_message_content_tests {
         };
         assert_eq!(image.body(), Some("photo.jpg"));
 
-        let location = IncomingMessageContent::Location;
-        assert_eq!(location.body(), Some("[Location]"));
+        let location = IncomingMessageContent::Location {
+            body: "Location: Café Regatta".to_string(),
+            geo_uri: "geo:60.18402,24.92200".to_string(),
+        };
+        assert_eq!(location.body(), Some("Location: Café Regatta"));
 
         let other = IncomingMessageContent::Other;
         assert_eq!(other.body(), None);
@@ -304,7 +307,11 @@ mod incoming_message_content_tests {
             "file"
         );
         assert_eq!(
-            IncomingMessageContent::Location.message_type_str(),
+            IncomingMessageContent::Location {
+                body: String::new(),
+                geo_uri: String::new()
+            }
+            .message_type_str(),
             "location"
         );
         assert_eq!(
@@ -493,6 +500,7 @@ mod workflow_tests {
             room_name: "".to_string(),
             media_url: None,
             room_id: None,
+            event_id: None,
         }
     }
 
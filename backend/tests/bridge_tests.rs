@@ -4,11 +4,21 @@ mod bridge_edit_test;
 mod bridge_integration_test;
 #[path = "bridge_redaction_test.rs"]
 mod bridge_redaction_test;
+#[path = "bridge_relations_test.rs"]
+mod bridge_relations_test;
 #[path = "bridge_responses_test.rs"]
 mod bridge_responses_test;
 #[path = "bridge_test.rs"]
 mod bridge_test;
+#[path = "bridgev2_provisioning_test.rs"]
+mod bridgev2_provisioning_test;
 #[path = "matrix_mock_test.rs"]
 mod matrix_mock_test;
+#[path = "outgoing_media_test.rs"]
+mod outgoing_media_test;
+#[path = "shared_location_test.rs"]
+mod shared_location_test;
 #[path = "trust_chain_history_test.rs"]
 mod trust_chain_history_test;
+#[path = "voice_notes_test.rs"]
+mod voice_notes_test;
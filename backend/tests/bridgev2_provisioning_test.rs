@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use backend::api::matrix_client::infer_service_from_room;
+use backend::repositories::bridge_login_repository::bridge_database_env_name;
+use backend::test_utils::matrix_test_server::{MatrixTestClient, MatrixTestConfig};
+use backend::utils::bridge::remove_bridge_suffix;
+use backend::utils::bridge_responses::BridgeLoginHealth;
+use backend::utils::bridgev2_provisioning::{
+    classify_logins, infer_network, network, LoginFlow, LoginProgress, ProvisioningClient,
+    ProvisioningError, RemoteLogin, StepType, Whoami,
+};
+use serde_json::json;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn flow(id: &str) -> LoginFlow {
+    LoginFlow {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: String::new(),
+    }
+}
+
+fn login(value: serde_json::Value) -> RemoteLogin {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn registry_networks_are_recognised_across_the_pipeline() {
+    let discord = network("discord").unwrap();
+    assert_eq!(discord.url_env, "DISCORD_BRIDGE_URL");
+    assert!(network("whatsapp").is_none());
+
+    assert_eq!(
+        infer_network("General (Discord)", "someone").map(|n| n.id),
+        Some("discord")
+    );
+    assert_eq!(
+        infer_network("Anna", "gmessages_1234").map(|n| n.id),
+        Some("gmessages")
+    );
+    // The bridge bot itself is not a ghost.
+    assert!(infer_network("Anna", "gmessagesbot").is_none());
+    assert_eq!(
+        infer_service_from_room("#random (Slack)", "alice"),
+        Some("slack".to_string())
+    );
+
+    assert_eq!(
+        remove_bridge_suffix("Anna Virtanen (Instagram)"),
+        "Anna Virtanen"
+    );
+    assert_eq!(remove_bridge_suffix("Anna (WA)"), "Anna");
+    assert_eq!(
+        bridge_database_env_name("messenger"),
+        Some("MESSENGER_BRIDGE_DATABASE_URL")
+    );
+    assert_eq!(bridge_database_env_name("irc"), None);
+}
+
+#[test]
+fn flows_follow_the_adapter_preference() {
+    let gmessages = network("gmessages").unwrap();
+    let ordered: Vec<String> = gmessages
+        .order_flows(vec![flow("google"), flow("other"), flow("qr")])
+        .into_iter()
+        .map(|f| f.id)
+        .collect();
+    assert_eq!(ordered, ["qr", "google", "other"]);
+}
+
+#[test]
+fn login_steps_parse_for_every_step_type() {
+    let qr: LoginProgress = serde_json::from_value(json!({
+        "login_id": "proc1",
+        "type": "display_and_wait",
+        "step_id": "fi.mau.gmessages.qr",
+        "instructions": "Scan the QR code on your phone",
+        "display_and_wait": {"type": "qr", "data": "https://support.google.com/messages/?p=web_computer#abc"}
+    }))
+    .unwrap();
+    assert_eq!(qr.login_id, "proc1");
+    assert_eq!(qr.step.step_type, StepType::DisplayAndWait);
+    assert_eq!(qr.step.display_and_wait.as_ref().unwrap().kind, "qr");
+    assert!(qr.step.required_fields().is_empty());
+
+    let input: LoginProgress = serde_json::from_value(json!({
+        "login_id": "proc2",
+        "type": "user_input",
+        "step_id": "fi.mau.slack.token",
+        "user_input": {"fields": [
+            {"type": "token", "id": "token", "name": "Token"},
+            {"type": "token", "id": "d_cookie", "name": "d cookie", "pattern": "^xoxd-"}
+        ]}
+    }))
+    .unwrap();
+    assert_eq!(input.step.required_fields(), ["token", "d_cookie"]);
+
+    let cookies: LoginProgress = serde_json::from_value(json!({
+        "login_id": "proc3",
+        "type": "cookies",
+        "step_id": "fi.mau.meta.cookies",
+        "cookies": {
+            "url": "https://www.messenger.com/",
+            "fields": [
+                {"id": "c_user", "required": true, "sources": [{"type": "cookie", "name": "c_user"}]},
+                {"id": "xs", "required": true},
+                {"id": "locale"}
+            ]
+        }
+    }))
+    .unwrap();
+    assert_eq!(cookies.step.required_fields(), ["c_user", "xs"]);
+
+    let complete: LoginProgress = serde_json::from_value(json!({
+        "login_id": "proc3",
+        "type": "complete",
+        "step_id": "fi.mau.meta.complete",
+        "complete": {"user_login_id": "100004"}
+    }))
+    .unwrap();
+    assert_eq!(
+        complete.step.complete.unwrap().user_login_id.as_deref(),
+        Some("100004")
+    );
+}
+
+#[test]
+fn login_health_and_labels_come_from_whoami() {
+    let connected = login(json!({
+        "id": "a", "name": "Anna",
+        "profile": {"phone": "+358401234567"},
+        "state": {"state_event": "CONNECTED"}
+    }));
+    let revoked = login(json!({"id": "b", "state": {"state_event": "BAD_CREDENTIALS"}}));
+    let transient = login(json!({"id": "c", "state": {"state_event": "TRANSIENT_DISCONNECT"}}));
+
+    assert_eq!(
+        classify_logins(&[revoked.clone(), connected.clone()]),
+        BridgeLoginHealth::Connected
+    );
+    assert_eq!(
+        classify_logins(&[transient.clone(), revoked]),
+        BridgeLoginHealth::BadCredentials
+    );
+    assert_eq!(classify_logins(&[transient]), BridgeLoginHealth::Unknown);
+    assert_eq!(classify_logins(&[]), BridgeLoginHealth::Empty);
+
+    assert_eq!(connected.default_label().as_deref(), Some("+358401234567"));
+    let instagram = network("instagram").unwrap();
+    let ig_login = login(json!({"id": "d", "name": "Anna V", "profile": {"username": "anna.v"}}));
+    assert_eq!(
+        instagram.adapter.account_label(&ig_login).as_deref(),
+        Some("@anna.v")
+    );
+}
+
+#[tokio::test]
+async fn provisioning_client_walks_a_login_and_surfaces_bridge_errors() {
+    let server = MockServer::start().await;
+    let auth = header("authorization", "Bearer user-token");
+
+    Mock::given(method("POST"))
+        .and(path("/_matrix/provision/v3/login/start/token"))
+        .and(auth.clone())
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "login_id": "proc1",
+            "type": "user_input",
+            "step_id": "fi.mau.slack.token",
+            "user_input": {"fields": [{"type": "token", "id": "token", "name": "Token"}]}
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/_matrix/provision/v3/login/step/proc1/fi.mau.slack.token/user_input",
+        ))
+        .and(auth.clone())
+        .and(body_json(json!({"token": "xoxc-bad"})))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "errcode": "FI.MAU.SLACK.INVALID_TOKEN",
+            "error": "Invalid token"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(
+            "/_matrix/provision/v3/login/step/proc1/fi.mau.slack.token/user_input",
+        ))
+        .and(auth.clone())
+        .and(body_json(json!({"token": "xoxc-good"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "login_id": "proc1",
+            "type": "complete",
+            "step_id": "fi.mau.slack.complete",
+            "complete": {"user_login_id": "T1-U1"}
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/_matrix/provision/v3/whoami"))
+        .and(auth.clone())
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "network": {"displayname": "Slack"},
+            "login_flows": [{"id": "token", "name": "Token"}],
+            "logins": [{
+                "id": "T1-U1",
+                "name": "anna @ Acme",
+                "state": {"state_event": "CONNECTED", "timestamp": 1700000000}
+            }]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/_matrix/provision/v3/logout/T1-U1"))
+        .and(auth)
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = ProvisioningClient::new(&format!("{}/", server.uri()), "user-token");
+    let started = client.start_login("token").await.unwrap();
+    assert_eq!(started.step.step_type, StepType::UserInput);
+
+    let bad = HashMap::from([("token".to_string(), "xoxc-bad".to_string())]);
+    match client.submit_step("proc1", &started.step, &bad).await {
+        Err(ProvisioningError::Api {
+            status, message, ..
+        }) => {
+            assert_eq!(status, 400);
+            assert_eq!(message, "Invalid token");
+        }
+        other => panic!("expected bridge error, got {:?}", other),
+    }
+
+    let good = HashMap::from([("token".to_string(), "xoxc-good".to_string())]);
+    let done = client
+        .submit_step("proc1", &started.step, &good)
+        .await
+        .unwrap();
+    assert_eq!(done.step.step_type, StepType::Complete);
+
+    let whoami: Whoami = client.whoami().await.unwrap();
+    assert_eq!(
+        classify_logins(&whoami.logins),
+        BridgeLoginHealth::Connected
+    );
+    assert_eq!(client.logout_all().await.unwrap(), 1);
+}
+
+/// Tier 3: run with a bridgev2 bridge in the docker stack and
+/// `TEST_{NETWORK}_BRIDGE_URL` pointing at it, e.g.
+/// `TEST_GMESSAGES_BRIDGE_URL=http://localhost:29336 cargo test --test bridge_tests provisioning_against_live_bridge -- --ignored`.
+#[tokio::test]
+#[ignore]
+async fn provisioning_against_live_bridge() {
+    let config = MatrixTestConfig::default();
+    let Some((network, url)) = backend::utils::bridgev2_provisioning::NETWORKS
+        .iter()
+        .find_map(|n| config.bridge_provisioning_url(n.id).map(|url| (n, url)))
+    else {
+        eprintln!("no TEST_*_BRIDGE_URL set, skipping");
+        return;
+    };
+    let matrix = MatrixTestClient::with_config(config);
+    let user = matrix
+        .register_unique_user("provisioning")
+        .await
+        .expect("register test user");
+
+    let client = ProvisioningClient::new(&url, &user.access_token);
+    let whoami = client.whoami().await.expect("whoami");
+    assert!(whoami.logins.is_empty());
+
+    let flows = network.order_flows(client.login_flows().await.expect("flows"));
+    assert!(!flows.is_empty(), "{} offers no login flows", network.name);
+    let started = client.start_login(&flows[0].id).await.expect("start login");
+    assert_ne!(started.step.step_type, StepType::Complete);
+}
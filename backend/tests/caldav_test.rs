@@ -0,0 +1,464 @@
+//! Tests for the CalDAV client: iCalendar parsing and generation, WebDAV
+//! multistatus parsing, and (ignored by default) a round trip against a
+//! local Radicale server.
+//!
+//! Run the server tests with:
+//!   docker compose -f docker-compose.radicale.yml up -d
+//!   CALDAV_ALLOW_PRIVATE_HOSTS=1 cargo test --test account_and_integration_tests caldav -- --ignored
+
+use backend::api::caldav::{
+    build_event_ics, join_url, parse_calendar_list, parse_calendar_objects, parse_discovery,
+    parse_ics_events, parse_user_time, update_event_ics, validate_caldav_url, CalDavClient,
+    CalendarEvent, EventPatch, EventTime,
+};
+use chrono::{NaiveDate, TimeZone, Utc};
+
+fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> EventTime {
+    EventTime::Instant(Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap())
+}
+
+const DENTIST_ICS: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+PRODID:-//Example//EN\r\n\
+BEGIN:VEVENT\r\n\
+UID:dentist-1@example.com\r\n\
+DTSTAMP:20260301T100000Z\r\n\
+DTSTART;TZID=Europe/Helsinki:20260304T090000\r\n\
+DTEND;TZID=Europe/Helsinki:20260304T100000\r\n\
+SUMMARY:Dentist\\, check-up\r\n\
+LOCATION:Kamppi clinic\r\n\
+DESCRIPTION:Bring the insurance card.\\nArrive 10 min early, please. This line is lo\r\n ng enough to be folded.\r\n\
+RRULE:FREQ=MONTHLY;COUNT=3\r\n\
+BEGIN:VALARM\r\n\
+ACTION:DISPLAY\r\n\
+DESCRIPTION:Reminder\r\n\
+TRIGGER:-PT30M\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+#[test]
+fn parses_events_with_zones_folding_and_escapes() {
+    let events = parse_ics_events(DENTIST_ICS);
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.uid, "dentist-1@example.com");
+    assert_eq!(event.summary, "Dentist, check-up");
+    // Helsinki is UTC+2 in March.
+    assert_eq!(event.start, utc(2026, 3, 4, 7, 0));
+    assert_eq!(event.end, Some(utc(2026, 3, 4, 8, 0)));
+    assert_eq!(event.location.as_deref(), Some("Kamppi clinic"));
+    // The VALARM description must not leak into the event.
+    assert_eq!(
+        event.description.as_deref(),
+        Some("Bring the insurance card.\nArrive 10 min early, please. This line is long enough to be folded.")
+    );
+    assert_eq!(
+        event.describe(&chrono_tz::Europe::Helsinki),
+        "Wed 04 Mar 09:00-10:00 Dentist, check-up @ Kamppi clinic"
+    );
+}
+
+#[test]
+fn parses_all_day_floating_and_duration_events() {
+    let ics = "BEGIN:VCALENDAR\n\
+BEGIN:VEVENT\n\
+UID:trip\n\
+DTSTART;VALUE=DATE:20260306\n\
+DTEND;VALUE=DATE:20260309\n\
+SUMMARY:Lapland trip\n\
+END:VEVENT\n\
+BEGIN:VEVENT\n\
+UID:standup\n\
+DTSTART:20260304T093000\n\
+DURATION:PT15M\n\
+SUMMARY:Standup\n\
+END:VEVENT\n\
+BEGIN:VEVENT\n\
+UID:call\n\
+DTSTART;TZID=/mozilla.org/20050126_1/America/New_York:20260304T120000\n\
+SUMMARY:\n\
+END:VEVENT\n\
+BEGIN:VEVENT\n\
+SUMMARY:No uid, dropped\n\
+DTSTART:20260304T093000Z\n\
+END:VEVENT\n\
+END:VCALENDAR\n";
+    let events = parse_ics_events(ics);
+    assert_eq!(events.len(), 3);
+
+    let trip = &events[0];
+    assert!(trip.start.is_all_day());
+    assert_eq!(
+        trip.describe(&chrono_tz::Europe::Helsinki),
+        "Fri 06 Mar-Sun 08 Mar (all day) Lapland trip"
+    );
+
+    let standup = &events[1];
+    let floating = NaiveDate::from_ymd_opt(2026, 3, 4)
+        .unwrap()
+        .and_hms_opt(9, 30, 0)
+        .unwrap();
+    assert_eq!(standup.start, EventTime::Floating(floating));
+    assert_eq!(
+        standup.end,
+        Some(EventTime::Floating(
+            floating + chrono::Duration::minutes(15)
+        ))
+    );
+    // Floating times read in the user's zone.
+    assert_eq!(
+        standup.start.to_utc(&chrono_tz::Europe::Helsinki),
+        Utc.with_ymd_and_hms(2026, 3, 4, 7, 30, 0).unwrap()
+    );
+
+    let call = &events[2];
+    assert_eq!(call.summary, "Untitled event");
+    assert_eq!(call.start, utc(2026, 3, 4, 17, 0));
+}
+
+#[test]
+fn built_events_round_trip() {
+    let event = CalendarEvent {
+        uid: "abc-123@lightfriend".to_string(),
+        summary: "Dinner; Anna, Mikko".to_string(),
+        start: utc(2026, 3, 4, 16, 0),
+        end: Some(utc(2026, 3, 4, 18, 0)),
+        location: Some("Ravintola Nokka".to_string()),
+        description: Some(format!("Line one\n{}", "ä".repeat(60))),
+    };
+    let ics = build_event_ics(&event, Utc::now());
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(ics.contains("SUMMARY:Dinner\\; Anna\\, Mikko\r\n"));
+    assert!(ics.contains("DTSTART:20260304T160000Z\r\n"));
+    assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    assert_eq!(parse_ics_events(&ics), vec![event]);
+}
+
+#[test]
+fn updates_keep_untouched_properties() {
+    let patch = EventPatch {
+        summary: Some("Dentist (moved)".to_string()),
+        start: Some(utc(2026, 3, 5, 12, 0)),
+        ..Default::default()
+    };
+    let updated = update_event_ics(DENTIST_ICS, &patch, Utc::now()).unwrap();
+    let events = parse_ics_events(&updated);
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.summary, "Dentist (moved)");
+    assert_eq!(event.start, utc(2026, 3, 5, 12, 0));
+    // The one-hour duration is kept when only the start moves.
+    assert_eq!(event.end, Some(utc(2026, 3, 5, 13, 0)));
+    assert_eq!(event.location.as_deref(), Some("Kamppi clinic"));
+    assert!(updated.contains("RRULE:FREQ=MONTHLY;COUNT=3\r\n"));
+    assert!(updated.contains("BEGIN:VALARM\r\n"));
+    assert!(updated.contains("SEQUENCE:1\r\n"));
+    assert_eq!(updated.matches("DTSTART").count(), 1);
+
+    // A second update bumps the sequence again.
+    let again = update_event_ics(
+        &updated,
+        &EventPatch {
+            location: Some("Online".to_string()),
+            ..Default::default()
+        },
+        Utc::now(),
+    )
+    .unwrap();
+    assert!(again.contains("SEQUENCE:2\r\n"));
+    assert_eq!(
+        parse_ics_events(&again)[0].location.as_deref(),
+        Some("Online")
+    );
+
+    assert!(update_event_ics("BEGIN:VCALENDAR\nEND:VCALENDAR\n", &patch, Utc::now()).is_err());
+}
+
+#[test]
+fn user_times_read_in_local_zone() {
+    let tz = chrono_tz::Europe::Helsinki;
+    assert_eq!(
+        parse_user_time("2026-03-04T09:00", &tz),
+        Ok(utc(2026, 3, 4, 7, 0))
+    );
+    assert_eq!(
+        parse_user_time("2026-03-04 09:00", &tz),
+        Ok(utc(2026, 3, 4, 7, 0))
+    );
+    assert_eq!(
+        parse_user_time("2026-03-04T09:00:00Z", &tz),
+        Ok(utc(2026, 3, 4, 9, 0))
+    );
+    assert_eq!(
+        parse_user_time("2026-03-04", &tz),
+        Ok(EventTime::Date(
+            NaiveDate::from_ymd_opt(2026, 3, 4).unwrap()
+        ))
+    );
+    assert!(parse_user_time("next tuesday", &tz).is_err());
+}
+
+#[test]
+fn parses_discovery_and_calendar_listing() {
+    let principal = r#"<?xml version="1.0"?>
+<multistatus xmlns="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <response>
+    <href>/</href>
+    <propstat>
+      <prop>
+        <resourcetype><collection/></resourcetype>
+        <current-user-principal><href>/anna/</href></current-user-principal>
+      </prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+    <propstat>
+      <prop><C:calendar-home-set/><displayname/></prop>
+      <status>HTTP/1.1 404 Not Found</status>
+    </propstat>
+  </response>
+</multistatus>"#;
+    let props = parse_discovery(principal).unwrap();
+    assert!(!props.is_calendar);
+    assert_eq!(props.principal.as_deref(), Some("/anna/"));
+    assert_eq!(props.calendar_home, None);
+
+    let listing = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response>
+    <d:href>/dav/calendars/anna/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/calendars/anna/personal/</d:href>
+    <d:propstat><d:prop>
+      <d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+      <d:displayname>Personal</d:displayname>
+      <cal:supported-calendar-component-set><cal:comp name="VEVENT"/><cal:comp name="VTODO"/></cal:supported-calendar-component-set>
+    </d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/calendars/anna/tasks/</d:href>
+    <d:propstat><d:prop>
+      <d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+      <d:displayname>Tasks</d:displayname>
+      <cal:supported-calendar-component-set><cal:comp name="VTODO"/></cal:supported-calendar-component-set>
+    </d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/calendars/anna/work%20shifts/</d:href>
+    <d:propstat><d:prop>
+      <d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+    </d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>http://169.254.169.254/latest/meta-data/</d:href>
+    <d:propstat><d:prop>
+      <d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+      <d:displayname>Elsewhere</d:displayname>
+    </d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+  </d:response>
+</d:multistatus>"#;
+    let calendars =
+        parse_calendar_list(listing, "https://dav.example.com/dav/calendars/anna/").unwrap();
+    let summary: Vec<(&str, &str)> = calendars
+        .iter()
+        .map(|c| (c.name.as_str(), c.url.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "Personal",
+                "https://dav.example.com/dav/calendars/anna/personal/"
+            ),
+            (
+                "work shifts",
+                "https://dav.example.com/dav/calendars/anna/work%20shifts/"
+            ),
+        ]
+    );
+
+    assert!(parse_discovery("not xml").is_err());
+}
+
+#[test]
+fn server_hrefs_stay_on_the_configured_origin() {
+    let base = "https://dav.example.com/dav/";
+    assert_eq!(
+        join_url(base, "/principals/anna/").unwrap(),
+        "https://dav.example.com/principals/anna/"
+    );
+    assert_eq!(
+        join_url(base, "https://dav.example.com/calendars/anna/").unwrap(),
+        "https://dav.example.com/calendars/anna/"
+    );
+    for href in [
+        "http://169.254.169.254/latest/meta-data/",
+        "//10.0.0.5/calendars/",
+        "http://dav.example.com/calendars/anna/",
+        "https://dav.example.com:8443/calendars/anna/",
+    ] {
+        assert!(join_url(base, href).is_err(), "{} should be rejected", href);
+    }
+}
+
+#[test]
+fn parses_calendar_query_report() {
+    let report = format!(
+        r#"<?xml version="1.0"?>
+<multistatus xmlns="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <response>
+    <href>/anna/personal/dentist.ics</href>
+    <propstat><prop>
+      <getetag>"etag-1"</getetag>
+      <C:calendar-data>{}</C:calendar-data>
+    </prop><status>HTTP/1.1 200 OK</status></propstat>
+  </response>
+</multistatus>"#,
+        DENTIST_ICS.replace('&', "&amp;")
+    );
+    let objects = parse_calendar_objects(&report, "http://localhost:5232/anna/personal/").unwrap();
+    assert_eq!(objects.len(), 1);
+    assert_eq!(
+        objects[0].url,
+        "http://localhost:5232/anna/personal/dentist.ics"
+    );
+    assert_eq!(objects[0].etag.as_deref(), Some("\"etag-1\""));
+    assert_eq!(
+        parse_ics_events(&objects[0].data)[0].summary,
+        "Dentist, check-up"
+    );
+}
+
+#[tokio::test]
+async fn private_and_non_http_urls_are_refused() {
+    for url in [
+        "http://localhost:5232/",
+        "http://127.0.0.1:5232/",
+        "https://[::1]/dav/",
+        "https://10.0.0.5/remote.php/dav/",
+    ] {
+        assert!(validate_caldav_url(url).await.is_err(), "{}", url);
+    }
+    assert_eq!(
+        validate_caldav_url("ftp://cal.example.com/").await,
+        Err("URL must start with http:// or https://".to_string())
+    );
+    // The client checks every request, not just the one at connect time.
+    let client = CalDavClient::new("user", "secret").unwrap();
+    assert!(client
+        .discover_calendars("http://127.0.0.1:5232/")
+        .await
+        .unwrap_err()
+        .contains("not allowed"));
+}
+
+// ============================================================
+// Radicale round trip (requires a running server)
+// ============================================================
+
+struct RadicaleConfig {
+    url: String,
+    username: String,
+    password: String,
+}
+
+impl Default for RadicaleConfig {
+    fn default() -> Self {
+        Self {
+            url: std::env::var("CALDAV_TEST_URL")
+                .unwrap_or_else(|_| "http://localhost:5232/".to_string()),
+            username: std::env::var("CALDAV_TEST_USER").unwrap_or_else(|_| "test".to_string()),
+            password: std::env::var("CALDAV_TEST_PASSWORD").unwrap_or_else(|_| "test".to_string()),
+        }
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a local Radicale server"]
+async fn radicale_create_list_update_round_trip() {
+    let config = RadicaleConfig::default();
+
+    // Radicale starts with no calendars; make a fresh one for this run.
+    let calendar_url = format!(
+        "{}/{}/lightfriend-{}/",
+        config.url.trim_end_matches('/'),
+        config.username,
+        uuid::Uuid::new_v4()
+    );
+    let mkcalendar = reqwest::Client::new()
+        .request(
+            reqwest::Method::from_bytes(b"MKCALENDAR").unwrap(),
+            &calendar_url,
+        )
+        .basic_auth(&config.username, Some(&config.password))
+        .send()
+        .await
+        .expect("Radicale should be reachable");
+    assert!(
+        mkcalendar.status().is_success(),
+        "MKCALENDAR failed: {}",
+        mkcalendar.status()
+    );
+
+    let client = CalDavClient::new(&config.username, &config.password).unwrap();
+    let calendars = client
+        .discover_calendars(&config.url)
+        .await
+        .expect("discovery from the server root should work");
+    assert!(calendars.iter().any(|c| c.url == calendar_url));
+
+    let uid = format!("{}@lightfriend-test", uuid::Uuid::new_v4());
+    let event = CalendarEvent {
+        uid: uid.clone(),
+        summary: "Lightfriend test".to_string(),
+        start: utc(2026, 3, 4, 7, 0),
+        end: Some(utc(2026, 3, 4, 8, 0)),
+        location: None,
+        description: None,
+    };
+    client.create_event(&calendar_url, &event).await.unwrap();
+    // Creating the same UID again must not overwrite it.
+    assert!(client.create_event(&calendar_url, &event).await.is_err());
+
+    let window_start = Utc.with_ymd_and_hms(2026, 3, 4, 0, 0, 0).unwrap();
+    let window_end = Utc.with_ymd_and_hms(2026, 3, 5, 0, 0, 0).unwrap();
+    let listed = client
+        .list_events(&calendar_url, window_start, window_end)
+        .await
+        .unwrap();
+    assert_eq!(listed, vec![event]);
+
+    let object = client
+        .find_event(&calendar_url, &uid)
+        .await
+        .unwrap()
+        .expect("created event should be found by UID");
+    let patch = EventPatch {
+        summary: Some("Lightfriend test (moved)".to_string()),
+        start: Some(utc(2026, 3, 4, 12, 0)),
+        ..Default::default()
+    };
+    let ics = update_event_ics(&object.data, &patch, Utc::now()).unwrap();
+    client.replace_event(&object, ics).await.unwrap();
+
+    let listed = client
+        .list_events(&calendar_url, window_start, window_end)
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].summary, "Lightfriend test (moved)");
+    assert_eq!(listed[0].end, Some(utc(2026, 3, 4, 13, 0)));
+
+    // A stale ETag is rejected instead of clobbering the newer version.
+    let stale = update_event_ics(&object.data, &patch, Utc::now()).unwrap();
+    assert!(client.replace_event(&object, stale).await.is_err());
+
+    let _ = reqwest::Client::new()
+        .delete(&calendar_url)
+        .basic_auth(&config.username, Some(&config.password))
+        .send()
+        .await;
+}
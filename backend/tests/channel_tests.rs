@@ -1,13 +1,25 @@
+#[path = "channels_email_test.rs"]
+mod channels_email_test;
+#[path = "channels_matrix_test.rs"]
+mod channels_matrix_test;
+#[path = "channels_push_test.rs"]
+mod channels_push_test;
 #[path = "channels_router_test.rs"]
 mod channels_router_test;
 #[path = "channels_sinch_test.rs"]
 mod channels_sinch_test;
 #[path = "channels_telnyx_test.rs"]
 mod channels_telnyx_test;
+#[path = "inbound_media_test.rs"]
+mod inbound_media_test;
+#[path = "rendering_test.rs"]
+mod rendering_test;
 #[path = "sinch_handlers_test.rs"]
 mod sinch_handlers_test;
 #[path = "sms_e2e.rs"]
 mod sms_e2e;
+#[path = "sms_encoding_test.rs"]
+mod sms_encoding_test;
 #[path = "sms_sanitizer_test.rs"]
 mod sms_sanitizer_test;
 #[path = "telnyx_handlers_test.rs"]
@@ -22,6 +34,8 @@ mod twilio_message_service_test;
 mod twilio_status_service_test;
 #[path = "voice_pipeline_test.rs"]
 mod voice_pipeline_test;
+#[path = "voice_stream_bridge_test.rs"]
+mod voice_stream_bridge_test;
 #[path = "webhook_sms_test.rs"]
 mod webhook_sms_test;
 #[path = "whatsapp_native_activity_reminder_test.rs"]
@@ -0,0 +1,387 @@
+use std::io::{BufRead, BufReader, Write};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use backend::channels::email_channel::{
+    notification_delivery, render_digest_html, send_to_email, EmailContent,
+};
+use backend::handlers::auth_middleware::AuthUser;
+use backend::handlers::email_channel_handlers::{
+    connect_email_channel, delete_email_channel, get_email_channel, update_email_delivery,
+};
+use backend::models::email_channel_models::{
+    ConnectEmailChannelRequest, EmailDeliveryMode, UpdateEmailDeliveryRequest,
+};
+use backend::models::matrix_channel_models::{DeliveryMode, NotificationCategory};
+use backend::proactive::utils::sends_sms_copy;
+use backend::test_utils::{create_test_state, create_test_user, TestUserParams};
+
+const RELAY_ENV: [&str; 6] = [
+    "SMTP_RELAY_HOST",
+    "SMTP_RELAY_PORT",
+    "SMTP_RELAY_TLS",
+    "SMTP_RELAY_USERNAME",
+    "SMTP_RELAY_PASSWORD",
+    "SMTP_RELAY_FROM",
+];
+
+const DIGEST: &str = "Your digest\n\nToday: 09:00 Dentist, Team sync\n\nImportant:\n- Alice (WhatsApp): landlord says <b>rent</b> is due\n- Bob: call back\n+ 2 more\n\nDone: Pay invoice";
+
+fn owner(user_id: i32) -> AuthUser {
+    AuthUser {
+        user_id,
+        is_admin: false,
+    }
+}
+
+fn clear_relay_env() {
+    for name in RELAY_ENV {
+        std::env::remove_var(name);
+    }
+}
+
+/// Minimal SMTP server that accepts one message and hands back its DATA.
+fn fake_smtp_server() -> (u16, mpsc::Receiver<String>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        writer.write_all(b"220 localhost ESMTP\r\n").unwrap();
+        let mut data = String::new();
+        let mut in_data = false;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                break;
+            }
+            if in_data {
+                if line == ".\r\n" {
+                    in_data = false;
+                    writer.write_all(b"250 queued\r\n").unwrap();
+                    sender.send(std::mem::take(&mut data)).ok();
+                } else {
+                    data.push_str(&line);
+                }
+                continue;
+            }
+            let verb = line.get(..4).unwrap_or_default().to_ascii_uppercase();
+            let reply: &[u8] = match verb.as_str() {
+                "EHLO" | "HELO" => b"250 localhost\r\n",
+                "DATA" => {
+                    in_data = true;
+                    b"354 end with .\r\n"
+                }
+                "QUIT" => {
+                    writer.write_all(b"221 bye\r\n").ok();
+                    break;
+                }
+                _ => b"250 OK\r\n",
+            };
+            writer.write_all(reply).unwrap();
+        }
+    });
+    (port, receiver)
+}
+
+#[test]
+fn digests_render_as_sectioned_html() {
+    let html = render_digest_html(DIGEST);
+    assert!(html.contains(">Your digest</h1>"));
+    assert!(html.contains(">Today</h2>"));
+    assert!(html.contains("09:00 Dentist, Team sync</p>"));
+    assert!(html.contains(
+        "<strong>Alice (WhatsApp)</strong>: landlord says &lt;b&gt;rent&lt;/b&gt; is due</li>"
+    ));
+    assert!(html.contains("<strong>Bob</strong>: call back</li>"));
+    assert!(html.contains(">+ 2 more</p>"));
+    assert!(html.contains(">Done</h2>"));
+    assert!(!html.contains("<b>"));
+
+    let digest = EmailContent::for_notification(NotificationCategory::Digest, DIGEST);
+    assert_eq!(digest.subject, "Your Lightfriend digest");
+    assert_eq!(digest.text, DIGEST);
+    assert!(digest.html.starts_with("<!DOCTYPE html>"));
+
+    let alert = EmailContent::for_notification(
+        NotificationCategory::Critical,
+        "Mom called twice\n\nShe asked you to call back.",
+    );
+    assert_eq!(alert.subject, "Lightfriend: Mom called twice");
+    assert!(alert
+        .html
+        .contains("Mom called twice</p><p style=\"margin:0 0 12px\">She asked you"));
+    let long = EmailContent::for_notification(NotificationCategory::Rules, &"x".repeat(80));
+    assert_eq!(long.subject, format!("Lightfriend: {}...", "x".repeat(60)));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn channel_addresses_must_belong_to_the_user() {
+    clear_relay_env();
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+
+    let error = connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: "someone-else@example.com".to_string(),
+            imap_connection_id: None,
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.0, StatusCode::BAD_REQUEST);
+
+    // No relay configured: only a connected account can send.
+    let error = connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: user.email.clone(),
+            imap_connection_id: None,
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.0, StatusCode::BAD_REQUEST);
+
+    let connection_id = state
+        .user_repository
+        .set_imap_credentials(user.id, "inbox@example.com", "pw", None, None)
+        .unwrap();
+    let Json(status) = get_email_channel(State(state.clone()), owner(user.id))
+        .await
+        .unwrap();
+    assert!(status.channel.is_none());
+    assert!(!status.relay_available);
+    assert_eq!(
+        status.allowed_addresses,
+        vec![user.email.clone(), "inbox@example.com".to_string()]
+    );
+
+    let Json(channel) = connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: "INBOX@example.com".to_string(),
+            imap_connection_id: Some(connection_id),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(channel.address, "inbox@example.com");
+    assert_eq!(channel.imap_connection_id, Some(connection_id));
+    assert_eq!(channel.delivery.digest, EmailDeliveryMode::Sms);
+
+    let error = connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: user.email.clone(),
+            imap_connection_id: Some(connection_id + 1000),
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn categories_route_to_email_sms_or_both() {
+    clear_relay_env();
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    let connection_id = state
+        .user_repository
+        .set_imap_credentials(user.id, "inbox@example.com", "pw", None, None)
+        .unwrap();
+    connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: "inbox@example.com".to_string(),
+            imap_connection_id: Some(connection_id),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        notification_delivery(&state, user.id, "digest"),
+        EmailDeliveryMode::Sms
+    );
+
+    let Json(updated) = update_email_delivery(
+        State(state.clone()),
+        owner(user.id),
+        Json(UpdateEmailDeliveryRequest {
+            digest: Some(EmailDeliveryMode::Email),
+            critical: Some(EmailDeliveryMode::Both),
+            ..UpdateEmailDeliveryRequest::default()
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated.delivery.digest, EmailDeliveryMode::Email);
+    assert_eq!(updated.delivery.rules, EmailDeliveryMode::Sms);
+
+    let digest = notification_delivery(&state, user.id, "digest");
+    assert!(digest.includes_email() && !digest.includes_sms());
+    let critical = notification_delivery(&state, user.id, "whatsapp_critical");
+    assert!(critical.includes_email() && critical.includes_sms());
+    let rule = notification_delivery(&state, user.id, "rule_sms");
+    assert!(!rule.includes_email() && rule.includes_sms());
+
+    // Switching the address keeps the modes.
+    let Json(reconnected) = connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: user.email.clone(),
+            imap_connection_id: Some(connection_id),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(reconnected.address, user.email);
+    assert_eq!(reconnected.delivery, updated.delivery);
+
+    assert_eq!(
+        delete_email_channel(State(state.clone()), owner(user.id))
+            .await
+            .unwrap(),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        notification_delivery(&state, user.id, "digest"),
+        EmailDeliveryMode::Sms
+    );
+}
+
+#[test]
+fn both_on_either_channel_keeps_the_sms_copy() {
+    // Matrix "both" with email-only: the email leg doesn't drop the SMS.
+    assert!(sends_sms_copy(
+        DeliveryMode::Both,
+        EmailDeliveryMode::Email,
+        true
+    ));
+    assert!(sends_sms_copy(
+        DeliveryMode::Matrix,
+        EmailDeliveryMode::Both,
+        true
+    ));
+    assert!(!sends_sms_copy(
+        DeliveryMode::Matrix,
+        EmailDeliveryMode::Email,
+        true
+    ));
+    assert!(!sends_sms_copy(
+        DeliveryMode::Sms,
+        EmailDeliveryMode::Email,
+        true
+    ));
+    // Nothing else went out, so SMS stands in.
+    assert!(sends_sms_copy(
+        DeliveryMode::Matrix,
+        EmailDeliveryMode::Email,
+        false
+    ));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn digests_go_out_through_the_relay() {
+    let (port, received) = fake_smtp_server();
+    std::env::set_var("SMTP_RELAY_HOST", "127.0.0.1");
+    std::env::set_var("SMTP_RELAY_PORT", port.to_string());
+    std::env::set_var("SMTP_RELAY_TLS", "none");
+    std::env::set_var("SMTP_RELAY_FROM", "Lightfriend <notify@lightfriend.test>");
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+
+    connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: user.email.clone(),
+            imap_connection_id: None,
+        }),
+    )
+    .await
+    .unwrap();
+    let id = send_to_email(&state, &user, "digest", DIGEST).await;
+    clear_relay_env();
+    let id = id.unwrap();
+    assert!(id.as_str().starts_with("email_"));
+
+    let data = received.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(data.contains("Subject: Your Lightfriend digest"));
+    assert!(data.contains("From: Lightfriend <notify@lightfriend.test>"));
+    assert!(data.contains(&format!("To: {}", user.email)));
+    assert!(data.contains("multipart/alternative"));
+    assert!(data.contains("text/html"));
+    assert!(data.contains(&format!("{}@lightfriend.test>", &id.as_str()[6..])));
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn connecting_requires_a_subscription() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user_with_tier(10.0, 5.0, None));
+    let error = connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: user.email.clone(),
+            imap_connection_id: None,
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.0, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+#[ignore = "requires a local Mailpit server"]
+async fn local_mailpit_receives_the_digest() {
+    let api = std::env::var("MAILPIT_TEST_URL").unwrap_or_else(|_| "http://localhost:8025".into());
+    std::env::set_var("SMTP_RELAY_HOST", "localhost");
+    std::env::set_var("SMTP_RELAY_PORT", "1025");
+    std::env::set_var("SMTP_RELAY_TLS", "none");
+    std::env::set_var("SMTP_RELAY_FROM", "Lightfriend <notify@lightfriend.test>");
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    connect_email_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectEmailChannelRequest {
+            address: user.email.clone(),
+            imap_connection_id: None,
+        }),
+    )
+    .await
+    .unwrap();
+    let sent = send_to_email(&state, &user, "digest", DIGEST).await;
+    clear_relay_env();
+    sent.unwrap();
+
+    let messages: serde_json::Value = reqwest::get(format!("{}/api/v1/messages", api))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let latest = &messages["messages"][0];
+    assert_eq!(latest["Subject"], "Your Lightfriend digest");
+    assert_eq!(latest["To"][0]["Address"], user.email);
+}
@@ -0,0 +1,150 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use backend::channels::matrix_channel::{notification_delivery, validate_target_mxid};
+use backend::handlers::auth_middleware::AuthUser;
+use backend::handlers::matrix_channel_handlers::{
+    connect_matrix_channel, delete_matrix_channel, get_matrix_channel, update_matrix_delivery,
+};
+use backend::models::matrix_channel_models::{
+    ConnectMatrixChannelRequest, DeliveryMode, NotificationCategory, UpdateMatrixDeliveryRequest,
+};
+use backend::repositories::matrix_dm_channel_repository::MatrixDmChannelRepository;
+use backend::test_utils::{create_test_state, create_test_user, TestUserParams};
+
+fn owner(user_id: i32) -> AuthUser {
+    AuthUser {
+        user_id,
+        is_admin: false,
+    }
+}
+
+#[test]
+fn content_types_map_to_notification_categories() {
+    for (content_type, category) in [
+        ("whatsapp_critical", NotificationCategory::Critical),
+        ("system_important_email", NotificationCategory::Critical),
+        ("digest", NotificationCategory::Digest),
+        ("event_notification", NotificationCategory::Reminders),
+        ("tracked_item_update", NotificationCategory::Reminders),
+        ("rule_sms", NotificationCategory::Rules),
+        ("mcp_sms", NotificationCategory::Other),
+        ("tesla_charging", NotificationCategory::Other),
+    ] {
+        assert_eq!(
+            NotificationCategory::from_content_type(content_type),
+            category,
+            "{}",
+            content_type
+        );
+    }
+    assert!(DeliveryMode::Both.includes_sms() && DeliveryMode::Both.includes_matrix());
+    assert!(!DeliveryMode::Matrix.includes_sms());
+    assert_eq!(DeliveryMode::parse("pigeon"), DeliveryMode::Sms);
+}
+
+#[test]
+fn target_must_be_a_matrix_id_on_another_homeserver() {
+    assert_eq!(
+        validate_target_mxid(" @alice:matrix.org ", "lightfriend.ai")
+            .unwrap()
+            .as_str(),
+        "@alice:matrix.org"
+    );
+    assert!(validate_target_mxid("alice", "lightfriend.ai").is_err());
+    assert!(validate_target_mxid("@alice:Lightfriend.AI", "lightfriend.ai").is_err());
+    assert!(validate_target_mxid("@alice:matrix.org", "").is_err());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn delivery_modes_are_per_category_and_default_to_sms() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+
+    let Json(none) = get_matrix_channel(State(state.clone()), owner(user.id))
+        .await
+        .unwrap();
+    assert!(none.is_none());
+    assert!(update_matrix_delivery(
+        State(state.clone()),
+        owner(user.id),
+        Json(UpdateMatrixDeliveryRequest::default()),
+    )
+    .await
+    .is_err());
+
+    let repository = MatrixDmChannelRepository::new(state.pg_pool.clone());
+    repository
+        .connect(user.id, "@alice:matrix.org", "!room:lightfriend.ai", 100)
+        .unwrap();
+    assert!(state
+        .user_repository
+        .matrix_account_in_use(user.id)
+        .unwrap());
+
+    let Json(updated) = update_matrix_delivery(
+        State(state.clone()),
+        owner(user.id),
+        Json(UpdateMatrixDeliveryRequest {
+            digest: Some(DeliveryMode::Matrix),
+            reminders: Some(DeliveryMode::Both),
+            ..UpdateMatrixDeliveryRequest::default()
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated.delivery.digest, DeliveryMode::Matrix);
+    assert_eq!(updated.delivery.reminders, DeliveryMode::Both);
+    assert_eq!(updated.delivery.critical, DeliveryMode::Sms);
+    assert!(!updated.joined);
+
+    // Reconnecting keeps the chosen modes.
+    let channel = repository
+        .connect(user.id, "@alice:beeper.com", "!other:lightfriend.ai", 200)
+        .unwrap();
+    assert_eq!(channel.target_mxid, "@alice:beeper.com");
+    assert_eq!(
+        channel.delivery_for(NotificationCategory::Digest),
+        DeliveryMode::Matrix
+    );
+    assert_eq!(repository.user_ids_with_rooms().unwrap(), vec![user.id]);
+
+    // Without a registered Matrix channel everything stays on SMS.
+    assert_eq!(
+        notification_delivery(&state, user.id, "digest"),
+        DeliveryMode::Sms
+    );
+
+    assert_eq!(
+        delete_matrix_channel(State(state.clone()), owner(user.id))
+            .await
+            .unwrap(),
+        StatusCode::NO_CONTENT
+    );
+    assert!(repository.find(user.id).unwrap().is_none());
+    assert!(!state
+        .user_repository
+        .matrix_account_in_use(user.id)
+        .unwrap());
+    assert!(delete_matrix_channel(State(state.clone()), owner(user.id))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn connecting_requires_a_subscription() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user_with_tier(10.0, 5.0, None));
+    let error = connect_matrix_channel(
+        State(state.clone()),
+        owner(user.id),
+        Json(ConnectMatrixChannelRequest {
+            target_mxid: "@alice:matrix.org".to_string(),
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.0, StatusCode::FORBIDDEN);
+}
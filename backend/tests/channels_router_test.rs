@@ -1,9 +1,13 @@
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use backend::channels::health::{CircuitState, HealthConfig};
 use backend::channels::router::ChannelRouter;
-use backend::channels::traits::{ChannelError, ChannelMessageId, MediaRef, MessageChannel};
+use backend::channels::traits::{
+    ChannelError, ChannelMessageId, MediaRef, MessageChannel, VoiceChannel,
+};
 use backend::models::user_models::User;
 
 /// Minimal test channel that records every send call and returns a stub id.
@@ -83,6 +87,7 @@ fn user_with(phone: &str, preferred: Option<&str>) -> User {
         accountability_enabled: false,
         included_usage_window_start_timestamp: None,
         included_usage_window_end_timestamp: None,
+        sms_gsm7_transliteration: false,
     }
 }
 
@@ -381,6 +386,143 @@ async fn overlong_body_is_clamped_before_channel_dispatch() {
     assert!(received.ends_with("[truncated]"));
 }
 
+#[tokio::test]
+#[serial_test::serial]
+async fn opted_in_users_get_gsm7_folded_bodies() {
+    use std::sync::Mutex;
+
+    struct CapturingChannel {
+        bodies: Mutex<Vec<String>>,
+    }
+    #[async_trait]
+    impl MessageChannel for CapturingChannel {
+        fn id(&self) -> &'static str {
+            "twilio"
+        }
+        async fn send(
+            &self,
+            _user: &User,
+            _address: &str,
+            body: &str,
+            _media: Option<MediaRef>,
+        ) -> Result<ChannelMessageId, ChannelError> {
+            self.bodies.lock().unwrap().push(body.to_string());
+            Ok(ChannelMessageId("captured".to_string()))
+        }
+    }
+
+    let chan = Arc::new(CapturingChannel {
+        bodies: Mutex::new(Vec::new()),
+    });
+    let mut router = ChannelRouter::new();
+    router.register(chan.clone());
+
+    let body = "It\u{2019}s done \u{2014} see you soon \u{1F642}";
+    let mut user = user_with_phone("+12025551234");
+    router.send_to_user(&user, body, None).await.unwrap();
+    user.sms_gsm7_transliteration = true;
+    router.send_to_user(&user, body, None).await.unwrap();
+
+    let bodies = chan.bodies.lock().unwrap().clone();
+    assert_eq!(bodies[0], body);
+    assert_eq!(bodies[1], "It's done - see you soon :)");
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn media_degrades_to_alt_text_on_channels_without_mms() {
+    use std::sync::Mutex;
+
+    struct CapturingChannel {
+        id: &'static str,
+        media: bool,
+        /// Body and media URL of every send.
+        sends: Mutex<Vec<(String, Option<String>)>>,
+    }
+    #[async_trait]
+    impl MessageChannel for CapturingChannel {
+        fn id(&self) -> &'static str {
+            self.id
+        }
+        fn supports_media(&self) -> bool {
+            self.media
+        }
+        async fn send(
+            &self,
+            _user: &User,
+            _address: &str,
+            body: &str,
+            media: Option<MediaRef>,
+        ) -> Result<ChannelMessageId, ChannelError> {
+            let url = media.map(|m| match m {
+                MediaRef::Url(url) => url,
+                MediaRef::Bytes { mime, .. } => mime,
+            });
+            self.sends.lock().unwrap().push((body.to_string(), url));
+            Ok(ChannelMessageId("captured".to_string()))
+        }
+    }
+
+    let token = backend::rendering::store::put(backend::rendering::canvas::RenderedImage {
+        png: vec![0x89, b'P', b'N', b'G'],
+        alt_text: "QR code content: ABC123".to_string(),
+    });
+    let url = format!("https://lf.example.com/api/media/rendered/{}.png", token);
+
+    let sinch = Arc::new(CapturingChannel {
+        id: "sinch",
+        media: false,
+        sends: Mutex::new(Vec::new()),
+    });
+    let mut router = ChannelRouter::new();
+    router.register(sinch.clone());
+    router.set_route("FI", vec!["sinch".to_string()]);
+    let user = user_with_phone("+358401234567");
+
+    router
+        .send_to_user(&user, "Boarding pass", Some(MediaRef::Url(url.clone())))
+        .await
+        .unwrap();
+    router
+        .send_to_user(
+            &user,
+            "",
+            Some(MediaRef::Url("https://example.com/chart.png".to_string())),
+        )
+        .await
+        .unwrap();
+
+    let sends = sinch.sends.lock().unwrap().clone();
+    assert_eq!(
+        sends,
+        vec![
+            ("Boarding pass\n\nQR code content: ABC123".to_string(), None),
+            ("https://example.com/chart.png".to_string(), None),
+        ]
+    );
+
+    // A channel that carries MMS gets the media untouched.
+    let twilio = Arc::new(CapturingChannel {
+        id: "twilio",
+        media: true,
+        sends: Mutex::new(Vec::new()),
+    });
+    let mut router = ChannelRouter::new();
+    router.register(twilio.clone());
+    router
+        .send_to_user(
+            &user_with_phone("+12025551234"),
+            "Boarding pass",
+            Some(MediaRef::Url(url.clone())),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        twilio.sends.lock().unwrap().clone(),
+        vec![("Boarding pass".to_string(), Some(url))]
+    );
+}
+
 #[test]
 fn pick_channel_returns_correct_id() {
     let twilio = Arc::new(RecordingChannel::new("twilio"));
@@ -592,3 +734,309 @@ async fn clear_route_reverts_to_default() {
     assert_eq!(twilio.send_count(), 1);
     assert_eq!(telnyx.send_count(), 0);
 }
+
+// ===== Delivery health tests =====
+//
+// Status callbacks feed per-(provider, country) scores. These drive the
+// router directly with `record_delivery_status`, as the webhook handlers do.
+
+fn health_config(cooldown_secs: u64) -> HealthConfig {
+    HealthConfig {
+        window: 10,
+        min_samples: 4,
+        min_success_rate: 0.5,
+        cooldown: Duration::from_secs(cooldown_secs),
+        retry_window: Duration::from_secs(600),
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn undelivered_reports_demote_provider_for_that_country_only() {
+    let twilio = Arc::new(RecordingChannel::new("twilio"));
+    let telnyx = Arc::new(RecordingChannel::new("telnyx"));
+    let mut router = ChannelRouter::with_health_config(health_config(600));
+    router.register(twilio.clone());
+    router.register(telnyx.clone());
+    router.set_route("US", vec!["twilio".to_string(), "telnyx".to_string()]);
+    router.set_route("CA", vec!["twilio".to_string(), "telnyx".to_string()]);
+
+    let us_user = user_with_phone("+12025551234");
+    for i in 0..4 {
+        router.record_delivery_status(
+            "twilio",
+            &format!("SM{}", i),
+            "undelivered",
+            Some("+12025551234"),
+        );
+    }
+
+    assert_eq!(router.pick_channels_for(&us_user), vec!["telnyx", "twilio"]);
+    let ca_user = user_with_phone("+14165550123");
+    assert_eq!(router.pick_channels_for(&ca_user), vec!["twilio", "telnyx"]);
+
+    let snapshot = router.health_snapshot();
+    let row = snapshot
+        .rows
+        .iter()
+        .find(|r| r.provider == "twilio" && r.country == "US")
+        .unwrap();
+    assert_eq!(row.circuit, CircuitState::Open);
+    assert_eq!(row.failed, 4);
+    assert_eq!(row.success_rate, Some(0.0));
+    assert!(row.reopens_in_secs.is_some());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn half_open_provider_closes_after_a_delivered_probe() {
+    let twilio = Arc::new(RecordingChannel::new("twilio"));
+    let telnyx = Arc::new(RecordingChannel::new("telnyx"));
+    let mut router = ChannelRouter::with_health_config(health_config(0));
+    router.register(twilio.clone());
+    router.register(telnyx.clone());
+    router.set_route("US", vec!["twilio".to_string(), "telnyx".to_string()]);
+    let user = user_with_phone("+12025551234");
+
+    for i in 0..4 {
+        router.record_delivery_status(
+            "twilio",
+            &format!("SM{}", i),
+            "failed",
+            Some("+12025551234"),
+        );
+    }
+    // Zero cooldown: the circuit is already half-open, so twilio keeps its slot.
+    assert_eq!(router.pick_channels_for(&user), vec!["twilio", "telnyx"]);
+    assert_eq!(
+        router.health_snapshot().rows[0].circuit,
+        CircuitState::HalfOpen
+    );
+
+    let id = router.send_to_user(&user, "hello", None).await.unwrap();
+    router.record_delivery_status("twilio", id.as_str(), "delivered", None);
+
+    let snapshot = router.health_snapshot();
+    let row = &snapshot.rows[0];
+    assert_eq!(row.circuit, CircuitState::Closed);
+    assert_eq!(row.success_rate, Some(1.0));
+    assert_eq!(row.sent, 1);
+    assert_eq!(row.delivered, 1);
+    assert!(row.median_latency_ms.is_some());
+    assert_eq!(snapshot.pending_deliveries, 0);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn async_failure_is_resent_via_next_provider() {
+    use backend::channels::router::FALLBACK_PREFIX;
+
+    let twilio = Arc::new(FailingChannel::new("twilio", 0));
+    let telnyx = Arc::new(FailingChannel::new("telnyx", 0));
+    let mut router = ChannelRouter::with_health_config(health_config(600));
+    router.register(twilio.clone());
+    router.register(telnyx.clone());
+    router.set_route("US", vec!["twilio".to_string(), "telnyx".to_string()]);
+    let user = user_with_phone("+12025551234");
+
+    let id = router.send_to_user(&user, "hello", None).await.unwrap();
+    assert_eq!(id.as_str(), "twilio-1");
+
+    // Intermediate statuses don't count; the final undelivered one does.
+    assert!(router
+        .record_delivery_status("twilio", id.as_str(), "sent", None)
+        .is_none());
+    let retry = router
+        .record_delivery_status("twilio", id.as_str(), "undelivered", None)
+        .unwrap();
+    assert_eq!(retry.user_id, user.id);
+    assert_eq!(retry.remaining, vec!["telnyx"]);
+    // A duplicate callback doesn't schedule a second re-send.
+    assert!(router
+        .record_delivery_status("twilio", id.as_str(), "undelivered", None)
+        .is_none());
+
+    let resent = router.retry_undelivered(&user, retry).await.unwrap();
+    assert_eq!(resent.as_str(), "telnyx-1");
+    assert_eq!(
+        *telnyx.last_body.lock().unwrap(),
+        format!("{}hello", FALLBACK_PREFIX)
+    );
+
+    // Telnyx was the last provider left, so its failure ends the chain.
+    assert!(router
+        .record_delivery_status("telnyx", resent.as_str(), "failed", None)
+        .is_none());
+    let snapshot = router.health_snapshot();
+    let twilio_row = snapshot
+        .rows
+        .iter()
+        .find(|r| r.provider == "twilio")
+        .unwrap();
+    assert_eq!(twilio_row.retried, 1);
+    assert_eq!(snapshot.pending_deliveries, 0);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn delivered_sends_are_not_resent() {
+    let twilio = Arc::new(RecordingChannel::new("twilio"));
+    let telnyx = Arc::new(RecordingChannel::new("telnyx"));
+    let mut router = ChannelRouter::new();
+    router.register(twilio.clone());
+    router.register(telnyx.clone());
+    router.set_route("US", vec!["twilio".to_string(), "telnyx".to_string()]);
+    let user = user_with_phone("+12025551234");
+
+    let id = router.send_to_user(&user, "hello", None).await.unwrap();
+    assert!(router
+        .record_delivery_status("twilio", id.as_str(), "delivered", None)
+        .is_none());
+    assert!(router
+        .record_delivery_status("twilio", id.as_str(), "undelivered", None)
+        .is_none());
+    assert_eq!(telnyx.send_count(), 0);
+}
+
+// ===== Voice routes =====
+
+/// Voice channel that records dialled greetings and optionally refuses.
+struct RecordingVoiceChannel {
+    id: &'static str,
+    fail: bool,
+    greetings: std::sync::Mutex<Vec<String>>,
+}
+
+impl RecordingVoiceChannel {
+    fn new(id: &'static str, fail: bool) -> Self {
+        Self {
+            id,
+            fail,
+            greetings: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    fn calls(&self) -> usize {
+        self.greetings.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl VoiceChannel for RecordingVoiceChannel {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    async fn place_call(
+        &self,
+        _user: &User,
+        _address: &str,
+        greeting: &str,
+    ) -> Result<ChannelMessageId, ChannelError> {
+        self.greetings.lock().unwrap().push(greeting.to_string());
+        if self.fail {
+            return Err(ChannelError::SendFailed(format!("{} refused", self.id)));
+        }
+        Ok(ChannelMessageId(format!("{}-call", self.id)))
+    }
+}
+
+#[test]
+fn voice_defaults_to_twilio_without_a_route() {
+    let mut router = ChannelRouter::new();
+    router.register_voice(Arc::new(RecordingVoiceChannel::new("telnyx", false)));
+    router.register_voice(Arc::new(RecordingVoiceChannel::new("twilio", false)));
+
+    assert_eq!(
+        router.pick_voice_channels_for(&user_with_phone("+358401234567")),
+        vec!["twilio"]
+    );
+}
+
+#[test]
+fn voice_route_orders_registered_channels_only() {
+    let mut router = ChannelRouter::new();
+    router.register_voice(Arc::new(RecordingVoiceChannel::new("twilio", false)));
+    router.register_voice(Arc::new(RecordingVoiceChannel::new("telnyx", false)));
+    router.set_voice_route(
+        "fi",
+        vec![
+            "sinch".to_string(),
+            "telnyx".to_string(),
+            "bogus".to_string(),
+            "twilio".to_string(),
+            "telnyx".to_string(),
+        ],
+    );
+
+    assert_eq!(
+        router.pick_voice_channels_for(&user_with_phone("+358401234567")),
+        vec!["telnyx", "twilio"]
+    );
+    // Other countries and the SMS order are untouched.
+    assert_eq!(
+        router.pick_voice_channels_for(&user_with_phone("+12025551234")),
+        vec!["twilio"]
+    );
+    assert!(router.current_routes().is_empty());
+
+    router.clear_voice_route("FI");
+    assert_eq!(
+        router.pick_voice_channels_for(&user_with_phone("+358401234567")),
+        vec!["twilio"]
+    );
+}
+
+#[test]
+fn voice_route_ignores_sms_provider_pin() {
+    let mut router = ChannelRouter::new();
+    router.register_voice(Arc::new(RecordingVoiceChannel::new("twilio", false)));
+    router.register_voice(Arc::new(RecordingVoiceChannel::new("sinch", false)));
+
+    assert_eq!(
+        router.pick_voice_channels_for(&user_with("+12025551234", Some("sinch"))),
+        vec!["twilio"]
+    );
+}
+
+#[tokio::test]
+async fn place_call_falls_through_to_next_voice_provider() {
+    let telnyx = Arc::new(RecordingVoiceChannel::new("telnyx", true));
+    let sinch = Arc::new(RecordingVoiceChannel::new("sinch", false));
+    let mut router = ChannelRouter::new();
+    router.register_voice(telnyx.clone());
+    router.register_voice(sinch.clone());
+    router.set_voice_route("US", vec!["telnyx".to_string(), "sinch".to_string()]);
+
+    let call = router
+        .place_call(&user_with_phone("+12025551234"), "Your flight is boarding")
+        .await
+        .unwrap();
+
+    assert_eq!(call.as_str(), "sinch-call");
+    assert_eq!(telnyx.calls(), 1);
+    // Unlike SMS fallbacks, the greeting is not tagged.
+    assert_eq!(
+        sinch.greetings.lock().unwrap().as_slice(),
+        ["Your flight is boarding"]
+    );
+}
+
+#[tokio::test]
+async fn place_call_reports_last_error_when_every_provider_refuses() {
+    let mut router = ChannelRouter::new();
+    router.register_voice(Arc::new(RecordingVoiceChannel::new("twilio", true)));
+
+    let err = router
+        .place_call(&user_with_phone("+12025551234"), "hi")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ChannelError::SendFailed(ref e) if e == "twilio refused"));
+
+    let empty = ChannelRouter::new();
+    let err = empty
+        .place_call(&user_with_phone("+12025551234"), "hi")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ChannelError::NotConfigured(_)));
+}
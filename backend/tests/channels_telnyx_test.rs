@@ -1,5 +1,7 @@
+use backend::api::voice_stream_bridge::{StreamProvider, StreamTickets};
 use backend::channels::telnyx_channel::TelnyxChannel;
-use backend::channels::traits::{MediaRef, MessageChannel};
+use backend::channels::telnyx_voice_channel::TelnyxVoiceChannel;
+use backend::channels::traits::{ChannelError, MediaRef, MessageChannel, VoiceChannel};
 use backend::models::user_models::User;
 use serde_json::Value;
 use wiremock::matchers::{header, method, path};
@@ -37,6 +39,7 @@ fn user() -> User {
         accountability_enabled: false,
         included_usage_window_start_timestamp: None,
         included_usage_window_end_timestamp: None,
+        sms_gsm7_transliteration: false,
     }
 }
 
@@ -145,3 +148,87 @@ async fn telnyx_rejects_inline_bytes_media() {
         backend::channels::traits::ChannelError::MediaNotSupported
     ));
 }
+
+fn stream_token(url: &str) -> String {
+    let url = url::Url::parse(url).unwrap();
+    let query: std::collections::HashMap<String, String> = url.query_pairs().into_owned().collect();
+    query["token"].clone()
+}
+
+#[tokio::test]
+async fn telnyx_voice_dials_with_bidirectional_one_time_stream() {
+    let server = MockServer::start().await;
+    let tickets = StreamTickets::new("https://lightfriend.test");
+    let issued = tickets.clone();
+
+    Mock::given(method("POST"))
+        .and(path("/v2/calls"))
+        .and(header("authorization", "Bearer test-key"))
+        .respond_with(move |req: &Request| {
+            let body: Value = serde_json::from_slice(&req.body).unwrap();
+            assert_eq!(body["connection_id"], "app-123");
+            assert_eq!(body["to"], "+12025551234");
+            assert_eq!(body["from"], "+18005551234");
+            assert_eq!(body["stream_track"], "inbound_track");
+            assert_eq!(body["stream_bidirectional_mode"], "rtp");
+            assert_eq!(body["stream_bidirectional_codec"], "PCMU");
+
+            let stream_url = body["stream_url"].as_str().unwrap();
+            assert!(stream_url.starts_with("wss://lightfriend.test/api/voice/telnyx/ws?"));
+            assert!(!stream_url.contains("package"));
+            let ticket = issued
+                .consume(
+                    StreamProvider::Telnyx,
+                    &stream_token(stream_url),
+                    chrono::Utc::now().timestamp(),
+                )
+                .unwrap();
+            assert_eq!(ticket.user_id, 1);
+            assert_eq!(ticket.greeting, "Your package & mail arrived");
+
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": {"call_control_id": "v3:call-1", "call_leg_id": "leg-1"}
+            }))
+        })
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let chan = TelnyxVoiceChannel::with_base_url(
+        "test-key",
+        "app-123",
+        "+18005551234",
+        tickets,
+        server.uri(),
+    );
+    let call = chan
+        .place_call(&user(), "+12025551234", "Your package & mail arrived")
+        .await
+        .unwrap();
+
+    assert_eq!(call.as_str(), "v3:call-1");
+}
+
+#[tokio::test]
+async fn telnyx_voice_returns_send_failed_on_http_4xx() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/calls"))
+        .respond_with(ResponseTemplate::new(422).set_body_string("invalid connection_id"))
+        .mount(&server)
+        .await;
+
+    let chan = TelnyxVoiceChannel::with_base_url(
+        "test-key",
+        "app-123",
+        "+18005551234",
+        StreamTickets::new("https://lightfriend.test"),
+        server.uri(),
+    );
+    let err = chan
+        .place_call(&user(), "+12025551234", "hi")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ChannelError::SendFailed(ref e) if e.contains("422")));
+}
@@ -96,6 +96,7 @@ fn durable_digest_checkpoint_counts_only_successful_deliveries() {
                 status: None,
                 recharge_threshold_timestamp: None,
                 zero_credits_timestamp: None,
+                segments: None,
             })
             .unwrap();
     };
@@ -153,6 +153,11 @@ fn digest_test_message(
         matrix_event_id: None,
         commitment_prompt: None,
         commitment_result: None,
+        transcript: None,
+        location_lat: None,
+        location_lon: None,
+        location_label: None,
+        location_place: None,
     }
 }
 
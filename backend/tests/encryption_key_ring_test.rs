@@ -0,0 +1,120 @@
+//! Key ring encryption: versioned ciphertexts, legacy values and rotation.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use backend::services::key_rotation::ENCRYPTED_COLUMNS;
+use backend::utils::encryption::{key_id, EncryptionError, KeyRing};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+const KEY_A: &str = "MTIzNDU2Nzg5MDEyMzQ1Njc4OTAxMjM0NTY3ODkwMTI=";
+const KEY_B: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+const KEY_C: &str = "YWJjZGVmZ2hpamtsbW5vcHFyc3R1dnd4eXphYmNkZWY=";
+
+/// A value as the pre-versioning code wrote it: base64(nonce || ciphertext).
+fn legacy_encrypt(key: &str, value: &str) -> String {
+    let cipher = Aes256Gcm::new_from_slice(&BASE64.decode(key).unwrap()).unwrap();
+    let nonce = [7u8; 12];
+    let mut combined = nonce.to_vec();
+    combined.extend(
+        cipher
+            .encrypt(Nonce::from_slice(&nonce), value.as_bytes())
+            .unwrap(),
+    );
+    BASE64.encode(combined)
+}
+
+#[test]
+fn values_carry_the_active_key_id() {
+    let ring = KeyRing::parse(Some(&format!("1:{},2:{}", KEY_B, KEY_C)), Some(KEY_A)).unwrap();
+    assert_eq!(ring.active_key_id(), 2);
+    assert_eq!(ring.key_ids(), vec![0, 1, 2]);
+
+    let encrypted = ring.encrypt("imap password").unwrap();
+    assert!(encrypted.starts_with("lf1:2:"));
+    assert_eq!(key_id(&encrypted), Some(2));
+    assert!(ring.is_current(&encrypted));
+    assert_eq!(ring.decrypt(&encrypted).unwrap(), "imap password");
+}
+
+#[test]
+fn legacy_values_still_decrypt() {
+    let legacy = legacy_encrypt(KEY_A, "tesla token");
+    assert_eq!(key_id(&legacy), None);
+
+    let single = KeyRing::parse(None, Some(KEY_A)).unwrap();
+    assert_eq!(single.decrypt(&legacy).unwrap(), "tesla token");
+
+    // Even when the old key was moved into ENCRYPTION_KEYS under another id.
+    let moved = KeyRing::parse(Some(&format!("1:{},2:{}", KEY_A, KEY_B)), None).unwrap();
+    assert_eq!(moved.decrypt(&legacy).unwrap(), "tesla token");
+}
+
+#[test]
+fn rotation_rewrites_under_the_newest_key() {
+    let old = KeyRing::parse(None, Some(KEY_A)).unwrap();
+    let stored = old.encrypt("twilio auth token").unwrap();
+    assert_eq!(key_id(&stored), Some(0));
+
+    let rotated = KeyRing::parse(Some(&format!("1:{}", KEY_B)), Some(KEY_A)).unwrap();
+    assert!(!rotated.is_current(&stored));
+    assert_eq!(rotated.decrypt(&stored).unwrap(), "twilio auth token");
+
+    let rewritten = rotated.reencrypt(&stored).unwrap().unwrap();
+    assert_eq!(key_id(&rewritten), Some(1));
+    assert!(rotated.reencrypt(&rewritten).unwrap().is_none());
+
+    // Once the old key is retired, only rewritten values are readable.
+    let retired = KeyRing::parse(Some(&format!("1:{}", KEY_B)), None).unwrap();
+    assert_eq!(retired.decrypt(&rewritten).unwrap(), "twilio auth token");
+    assert!(matches!(
+        retired.decrypt(&stored),
+        Err(EncryptionError::UnknownKey(0))
+    ));
+}
+
+#[test]
+fn header_is_authenticated() {
+    // Same key under two ids: relabelling a value must still fail.
+    let ring = KeyRing::parse(Some(&format!("1:{},2:{}", KEY_B, KEY_B)), None).unwrap();
+    let encrypted = ring.encrypt("secret").unwrap();
+    let relabelled = encrypted.replacen("lf1:2:", "lf1:1:", 1);
+    assert!(ring.decrypt(&relabelled).is_err());
+}
+
+#[test]
+fn invalid_key_rings_are_rejected() {
+    assert!(KeyRing::parse(None, None).is_err());
+    assert!(KeyRing::parse(Some("not-a-pair"), None).is_err());
+    assert!(KeyRing::parse(Some(&format!("x:{}", KEY_A)), None).is_err());
+    assert!(KeyRing::parse(Some(&format!("1:{},1:{}", KEY_A, KEY_B)), None).is_err());
+    assert!(KeyRing::parse(Some(&format!("0:{}", KEY_B)), Some(KEY_A)).is_err());
+    assert!(KeyRing::parse(Some("1:dG9vLXNob3J0"), None).is_err());
+}
+
+#[test]
+fn every_encrypted_column_is_rotated() {
+    // Walk pg_schema.rs and make sure no encrypted column is missing from
+    // the re-encryption job.
+    let schema = include_str!("../src/pg_schema.rs");
+    let mut table = "";
+    let mut missing = Vec::new();
+    for line in schema.lines().map(str::trim) {
+        if line.ends_with('{') && line.contains('(') {
+            table = line.split_whitespace().next().unwrap_or("");
+            continue;
+        }
+        let Some((column, _)) = line.split_once(" -> ") else {
+            continue;
+        };
+        if column.contains("encrypted")
+            && !ENCRYPTED_COLUMNS
+                .iter()
+                .any(|c| c.table == table && c.column == column)
+        {
+            missing.push(format!("{}.{}", table, column));
+        }
+    }
+    assert!(missing.is_empty(), "not re-encrypted: {:?}", missing);
+}
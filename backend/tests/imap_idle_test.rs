@@ -89,6 +89,11 @@ fn legacy_email_compatibility_requires_matching_sender_and_content() {
         matrix_event_id: None,
         commitment_prompt: None,
         commitment_result: None,
+        transcript: None,
+        location_lat: None,
+        location_lon: None,
+        location_label: None,
+        location_place: None,
     };
 
     assert!(legacy_email_matches_preview(&legacy, &preview));
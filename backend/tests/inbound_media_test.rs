@@ -0,0 +1,200 @@
+use backend::api::twilio_sms::{TwilioWebhookPayload, WebhookMedia};
+use backend::services::inbound_media::{
+    clean_ocr_text, decode_data_url, describe_for_model, extract_fields, is_heic, normalize_raster,
+    read_capped, ExtractedFields, InboundImage, InboundMediaError,
+};
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn webhook_media_lists_every_numbered_attachment() {
+    let form = "From=%2B15551234567&To=%2B18005551234&Body=what+is+this&MessageSid=MM1\
+        &NumMedia=2\
+        &MediaUrl0=https%3A%2F%2Fapi.twilio.com%2Fm0&MediaContentType0=image%2Fjpeg\
+        &MediaUrl1=https%3A%2F%2Fapi.twilio.com%2Fm1&MediaContentType1=image%2Fheic\
+        &AccountSid=AC1";
+    let payload: TwilioWebhookPayload = serde_urlencoded::from_str(form).unwrap();
+
+    assert_eq!(
+        payload.media(),
+        vec![
+            WebhookMedia {
+                url: "https://api.twilio.com/m0".to_string(),
+                content_type: "image/jpeg".to_string(),
+            },
+            WebhookMedia {
+                url: "https://api.twilio.com/m1".to_string(),
+                content_type: "image/heic".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn webhook_media_is_empty_without_num_media() {
+    let payload: TwilioWebhookPayload =
+        serde_urlencoded::from_str("From=a&To=b&Body=hi&MessageSid=SM1").unwrap();
+    assert!(payload.media().is_empty());
+}
+
+#[test]
+fn extracts_dates_times_amounts_and_addresses_from_ocr_text() {
+    let text = "Lasku / Invoice\n\
+        Eräpäivä 14.3.2026 klo 14:30\n\
+        Summa 42,50 €\n\
+        Total: $1,250.99 due March 14, 2026 at 5 pm\n\
+        Mannerheimintie 5 B, 00100 Helsinki\n\
+        221 Baker Street, London\n\
+        Hauptstraße 3\n\
+        EUR 100";
+
+    assert_eq!(
+        extract_fields(text),
+        ExtractedFields {
+            dates: vec!["14.3.2026".into(), "March 14, 2026".into()],
+            times: vec!["14:30".into(), "5 pm".into()],
+            amounts: vec!["42,50 €".into(), "$1,250.99".into(), "EUR 100".into()],
+            addresses: vec![
+                "Mannerheimintie 5 B".into(),
+                "221 Baker Street".into(),
+                "Hauptstraße 3".into(),
+            ],
+        }
+    );
+}
+
+#[test]
+fn extraction_deduplicates_and_finds_nothing_in_plain_prose() {
+    let fields = extract_fields("Due 2026-04-01. Reminder: due 2026-04-01.");
+    assert_eq!(fields.dates, vec!["2026-04-01".to_string()]);
+
+    assert!(extract_fields("Thanks for shopping with us, see you soon").is_empty());
+}
+
+#[test]
+fn ocr_text_is_trimmed_and_capped() {
+    assert_eq!(
+        clean_ocr_text("  Receipt \n\n\n  Milk 1,20 \n"),
+        "Receipt\nMilk 1,20"
+    );
+
+    let long = "x".repeat(5000);
+    let cleaned = clean_ocr_text(&long);
+    assert_eq!(cleaned.chars().count(), 4001);
+    assert!(cleaned.ends_with('…'));
+}
+
+#[test]
+fn heic_is_detected_by_brand_before_content_type() {
+    let mut heic = vec![0, 0, 0, 24];
+    heic.extend_from_slice(b"ftypheic");
+    heic.extend_from_slice(&[0; 8]);
+    assert!(is_heic("image/jpeg", &heic));
+
+    let mut avif = vec![0, 0, 0, 24];
+    avif.extend_from_slice(b"ftypavif");
+    assert!(!is_heic("image/heic", &avif));
+
+    assert!(is_heic("image/HEIF", &[]));
+    assert!(!is_heic("image/png", &[0x89, b'P', b'N', b'G']));
+}
+
+#[test]
+fn large_png_is_shrunk_and_reencoded_as_jpeg() {
+    let img = image::RgbaImage::from_pixel(3000, 1500, image::Rgba([200, 10, 10, 128]));
+    let mut png = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut png, image::ImageOutputFormat::Png)
+        .unwrap();
+
+    let jpeg = normalize_raster(png.get_ref()).unwrap();
+    assert_eq!(&jpeg[..3], &[0xff, 0xd8, 0xff]);
+    let decoded = image::load_from_memory(&jpeg).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (2000, 1000));
+}
+
+#[test]
+fn normalize_rejects_non_images() {
+    assert!(normalize_raster(b"BEGIN:VCARD").is_err());
+}
+
+#[test]
+fn normalize_refuses_images_past_the_decode_limits() {
+    let img = image::GrayImage::new(13_000, 4);
+    let mut png = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut png, image::ImageOutputFormat::Png)
+        .unwrap();
+    assert!(matches!(
+        normalize_raster(png.get_ref()),
+        Err(InboundMediaError::Decode(_))
+    ));
+}
+
+#[tokio::test]
+async fn downloads_stop_at_the_size_cap() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![7u8; 2048]))
+        .mount(&server)
+        .await;
+    let fetch = || async { reqwest::get(server.uri()).await.unwrap() };
+
+    assert_eq!(read_capped(fetch().await, 4096).await.unwrap().len(), 2048);
+    assert!(matches!(
+        read_capped(fetch().await, 1024).await,
+        Err(InboundMediaError::Download(_))
+    ));
+}
+
+#[test]
+fn data_urls_round_trip_through_processed_images() {
+    let image = InboundImage::Processed {
+        jpeg: vec![0xff, 0xd8, 0xff, 0xe0],
+        ocr_text: None,
+        fields: ExtractedFields::default(),
+    };
+    let url = image.data_url().unwrap();
+    assert!(url.starts_with("data:image/jpeg;base64,"));
+    assert_eq!(
+        decode_data_url(&url),
+        Some(("image/jpeg".to_string(), vec![0xff, 0xd8, 0xff, 0xe0]))
+    );
+
+    assert_eq!(decode_data_url("https://example.com/a.jpg"), None);
+    assert_eq!(decode_data_url("data:text/plain,hello"), None);
+    assert!(InboundImage::Failed {
+        reason: "x".to_string()
+    }
+    .data_url()
+    .is_none());
+}
+
+#[test]
+fn model_description_covers_every_photo_and_offers_events_for_dates() {
+    let images = vec![
+        InboundImage::Processed {
+            jpeg: vec![],
+            ocr_text: Some("Dentist 14.3.2026 14:30".to_string()),
+            fields: extract_fields("Dentist 14.3.2026 14:30"),
+        },
+        InboundImage::Processed {
+            jpeg: vec![],
+            ocr_text: None,
+            fields: ExtractedFields::default(),
+        },
+        InboundImage::Failed {
+            reason: "Failed to download media: http 404".to_string(),
+        },
+    ];
+
+    let text = describe_for_model(&images);
+    assert!(text.starts_with("[The user attached 3 photos."));
+    assert!(text.contains("Photo 1 text:\n\"\"\"\nDentist 14.3.2026 14:30\n\"\"\""));
+    assert!(text.contains("Photo 1 details: dates: 14.3.2026 | times: 14:30"));
+    assert!(text.contains("Photo 2: no readable text."));
+    assert!(text.contains("Photo 3: could not be processed (Failed to download media: http 404)."));
+    assert!(text.contains("offer to set a reminder"));
+
+    let plain = describe_for_model(&images[1..2]);
+    assert!(plain.starts_with("[The user attached 1 photo."));
+    assert!(!plain.contains("reminder"));
+}
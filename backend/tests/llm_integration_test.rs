@@ -71,6 +71,7 @@ async fn send_message(state: &Arc<AppState>, user: &User, body: &str) -> TwilioR
         media_url0: None,
         media_content_type0: None,
         message_sid: format!("SM_test_{}", uuid::Uuid::new_v4()),
+        extra_params: Default::default(),
     };
 
     let options = ProcessSmsOptions {
@@ -104,6 +105,7 @@ async fn send_message_with_mocks(
         media_url0: None,
         media_content_type0: None,
         message_sid: format!("SM_test_{}", uuid::Uuid::new_v4()),
+        extra_params: Default::default(),
     };
 
     let options = ProcessSmsOptions {
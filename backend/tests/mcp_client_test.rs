@@ -0,0 +1,457 @@
+//! Tests for the MCP client against an in-process mock server speaking
+//! Streamable HTTP (sessions, SSE responses, progress, pings, stream
+//! resumption) and the legacy HTTP+SSE transport.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use backend::services::mcp_client::{McpClientService, McpProgress, McpTransport, SseParser};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[derive(Default)]
+struct MockServer {
+    sessions: Mutex<HashSet<String>>,
+    initializes: AtomicUsize,
+    tools_list_calls: AtomicUsize,
+    pings_answered: AtomicUsize,
+    /// Request whose stream was cut off, answered on resumption.
+    interrupted: Mutex<Option<Value>>,
+    legacy_tx: Mutex<Option<mpsc::UnboundedSender<Value>>>,
+}
+
+type Mock = Arc<MockServer>;
+
+fn rpc_result(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn text_result(id: &Value, text: &str) -> Value {
+    rpc_result(id, json!({ "content": [{ "type": "text", "text": text }] }))
+}
+
+fn sse_frame(id: Option<&str>, message: &Value) -> String {
+    match id {
+        Some(id) => format!("id: {}\ndata: {}\n\n", id, message),
+        None => format!("data: {}\n\n", message),
+    }
+}
+
+fn sse_response(frames: impl futures::Stream<Item = String> + Send + 'static) -> Response {
+    use futures::StreamExt;
+    (
+        [(header::CONTENT_TYPE, "text/event-stream")],
+        Body::from_stream(frames.map(Ok::<_, Infallible>)),
+    )
+        .into_response()
+}
+
+/// Answers shared by both transports.
+fn answer(request: &Value) -> Value {
+    let id = &request["id"];
+    match request["method"].as_str().unwrap_or_default() {
+        "resources/list" => rpc_result(
+            id,
+            json!({ "resources": [
+                { "uri": "memo://today", "name": "Today", "mimeType": "text/plain" },
+                { "uri": "memo://list", "name": "List", "mimeType": "application/json" }
+            ] }),
+        ),
+        "resources/read" => {
+            let uri = request["params"]["uri"].as_str().unwrap_or_default();
+            let text = if uri == "memo://list" {
+                "[\"milk\",\"eggs\"]"
+            } else {
+                "Water the plants"
+            };
+            rpc_result(
+                id,
+                json!({ "contents": [{ "uri": uri, "mimeType": "text/plain", "text": text }] }),
+            )
+        }
+        "prompts/list" => rpc_result(
+            id,
+            json!({ "prompts": [{
+                "name": "summarize",
+                "description": "Summarize a memo",
+                "arguments": [{ "name": "uri", "required": true }]
+            }] }),
+        ),
+        "prompts/get" => rpc_result(
+            id,
+            json!({ "messages": [{
+                "role": "user",
+                "content": { "type": "text", "text": format!("Summarize {}", request["params"]["arguments"]["uri"].as_str().unwrap_or_default()) }
+            }] }),
+        ),
+        "tools/call" => text_result(
+            id,
+            &format!(
+                "echo: {}",
+                request["params"]["arguments"]["text"]
+                    .as_str()
+                    .unwrap_or_default()
+            ),
+        ),
+        method => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("unknown method {}", method) }
+        }),
+    }
+}
+
+async fn streamable_post(
+    State(mock): State<Mock>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> Response {
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !accept.contains("application/json") || !accept.contains("text/event-stream") {
+        return StatusCode::NOT_ACCEPTABLE.into_response();
+    }
+
+    // Our answer to a server ping.
+    if request.get("method").is_none() {
+        if request["id"] == "srv-ping" && request["result"] == json!({}) {
+            mock.pings_answered.fetch_add(1, Ordering::SeqCst);
+        }
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    let id = request["id"].clone();
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    if method == "initialize" {
+        let n = mock.initializes.fetch_add(1, Ordering::SeqCst) + 1;
+        let session = format!("session-{}", n);
+        mock.sessions.lock().unwrap().insert(session.clone());
+        let result = rpc_result(
+            &id,
+            json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": { "tools": { "listChanged": true }, "resources": {}, "prompts": {} },
+                "serverInfo": { "name": "mock", "version": "1.0" }
+            }),
+        );
+        return ([("Mcp-Session-Id", session)], Json(result)).into_response();
+    }
+
+    let session = headers
+        .get("Mcp-Session-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if session.is_empty() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    if !mock.sessions.lock().unwrap().contains(session) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if headers
+        .get("MCP-Protocol-Version")
+        .and_then(|v| v.to_str().ok())
+        != Some("2025-06-18")
+    {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    if id.is_null() {
+        // notifications/initialized and friends
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    match (method.as_str(), request["params"]["name"].as_str()) {
+        ("tools/list", _) => {
+            mock.tools_list_calls.fetch_add(1, Ordering::SeqCst);
+            let page = match request["params"]["cursor"].as_str() {
+                None => json!({
+                    "tools": [{ "name": "echo", "inputSchema": { "type": "object" } }],
+                    "nextCursor": "page-2"
+                }),
+                Some(_) => json!({ "tools": [{ "name": "slow", "description": "Counts slowly" }] }),
+            };
+            Json(rpc_result(&id, page)).into_response()
+        }
+        ("tools/call", Some("slow")) => {
+            let token = request["params"]["_meta"]["progressToken"].clone();
+            sse_response(async_stream::stream! {
+                yield ": keep-alive\r\n\r\n".to_string();
+                yield sse_frame(None, &json!({ "jsonrpc": "2.0", "id": "srv-ping", "method": "ping" }));
+                for step in 1..=4 {
+                    tokio::time::sleep(Duration::from_millis(60)).await;
+                    let progress = json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/progress",
+                        "params": { "progressToken": token, "progress": step, "total": 4, "message": format!("step {}", step) }
+                    });
+                    // Split the frame mid-way to exercise chunk reassembly.
+                    let frame = sse_frame(None, &progress).replace('\n', "\r\n");
+                    let (a, b) = frame.split_at(frame.len() / 2);
+                    yield a.to_string();
+                    yield b.to_string();
+                }
+                yield sse_frame(None, &json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" }));
+                yield sse_frame(None, &text_result(&id, "counted to 4"));
+            })
+        }
+        ("tools/call", Some("hang")) => sse_response(async_stream::stream! {
+            tokio::time::sleep(Duration::from_millis(600)).await;
+            yield sse_frame(None, &text_result(&id, "too late"));
+        }),
+        ("tools/call", Some("resumable")) => {
+            // Close the stream before the result; the client resumes via GET.
+            *mock.interrupted.lock().unwrap() = Some(id.clone());
+            sse_response(async_stream::stream! {
+                yield sse_frame(Some("evt-1"), &json!({ "jsonrpc": "2.0", "method": "notifications/message", "params": { "level": "info", "data": "working" } }));
+            })
+        }
+        _ => Json(answer(&request)).into_response(),
+    }
+}
+
+async fn streamable_get(State(mock): State<Mock>, headers: HeaderMap) -> Response {
+    let last_event = headers.get("Last-Event-ID").and_then(|v| v.to_str().ok());
+    match (last_event, mock.interrupted.lock().unwrap().take()) {
+        (Some("evt-1"), Some(id)) => sse_response(futures::stream::iter(vec![sse_frame(
+            Some("evt-2"),
+            &text_result(&id, "resumed"),
+        )])),
+        _ => StatusCode::METHOD_NOT_ALLOWED.into_response(),
+    }
+}
+
+async fn legacy_stream(State(mock): State<Mock>) -> Response {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    *mock.legacy_tx.lock().unwrap() = Some(tx);
+    sse_response(async_stream::stream! {
+        yield "event: endpoint\ndata: /legacy/messages?session=abc\n\n".to_string();
+        while let Some(message) = rx.recv().await {
+            yield format!("event: message\ndata: {}\n\n", message);
+        }
+    })
+}
+
+async fn legacy_message(State(mock): State<Mock>, Json(request): Json<Value>) -> StatusCode {
+    let Some(id) = request.get("id").cloned() else {
+        return StatusCode::ACCEPTED;
+    };
+    let response = match request["method"].as_str() {
+        Some("initialize") => {
+            mock.initializes.fetch_add(1, Ordering::SeqCst);
+            rpc_result(
+                &id,
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "legacy-mock", "version": "0.1" }
+                }),
+            )
+        }
+        Some("tools/list") => rpc_result(&id, json!({ "tools": [{ "name": "echo" }] })),
+        _ => answer(&request),
+    };
+    if let Some(tx) = mock.legacy_tx.lock().unwrap().as_ref() {
+        let _ = tx.send(response);
+    }
+    StatusCode::ACCEPTED
+}
+
+async fn spawn_mock() -> (String, Mock) {
+    let mock: Mock = Arc::new(MockServer::default());
+    let app = Router::new()
+        .route("/mcp", post(streamable_post).get(streamable_get))
+        .route("/legacy/sse", get(legacy_stream))
+        .route("/legacy/messages", post(legacy_message))
+        .with_state(mock.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}", addr), mock)
+}
+
+#[test]
+fn sse_parser_handles_crlf_comments_and_split_chunks() {
+    let mut parser = SseParser::new();
+    let mut events = parser.feed(b": comment\r\nevent: endpoint\r");
+    assert!(events.is_empty());
+    events.extend(parser.feed(b"\ndata: /messages\r\n\r\nid: 7\ndata: {\"a\":\ndata: 1}\n"));
+    events.extend(parser.feed("data: caf\u{e9}".as_bytes().split_at(9).0));
+    events.extend(parser.feed(&"data: caf\u{e9}".as_bytes()[9..]));
+    events.extend(parser.feed(b"\n\n"));
+    events.extend(parser.finish());
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event, "endpoint");
+    assert_eq!(events[0].data, "/messages");
+    assert_eq!(events[1].event, "message");
+    assert_eq!(events[1].data, "{\"a\":\n1}\ncaf\u{e9}");
+    assert_eq!(events[1].id.as_deref(), Some("7"));
+}
+
+#[tokio::test]
+async fn handshake_is_cached_and_tools_are_paginated() {
+    let (base, mock) = spawn_mock().await;
+    let url = format!("{}/mcp", base);
+    let client = McpClientService::new();
+
+    let info = client.server_info(&url, Some("token")).await.unwrap();
+    assert_eq!(info.protocol_version, "2025-06-18");
+    assert_eq!(info.name.as_deref(), Some("mock"));
+    assert_eq!(info.transport, McpTransport::StreamableHttp);
+    assert!(info.supports("resources"));
+    assert!(!info.supports("logging"));
+
+    let tools = client.list_tools(&url, Some("token")).await.unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["echo", "slow"]);
+
+    let echoed = client
+        .call_tool(&url, Some("token"), "echo", json!({ "text": "hi" }))
+        .await
+        .unwrap();
+    assert_eq!(echoed, "echo: hi");
+    assert_eq!(mock.initializes.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn streamed_call_reports_progress_and_answers_pings() {
+    let (base, mock) = spawn_mock().await;
+    let url = format!("{}/mcp", base);
+    // Each progress step arrives well within the idle timeout, but the whole
+    // call takes longer than it.
+    let client = McpClientService::new().with_idle_timeout(Duration::from_millis(150));
+
+    client.list_tools(&url, None).await.unwrap();
+    assert_eq!(mock.tools_list_calls.load(Ordering::SeqCst), 2);
+    client.list_tools(&url, None).await.unwrap();
+    assert_eq!(mock.tools_list_calls.load(Ordering::SeqCst), 2);
+
+    let mut updates: Vec<McpProgress> = Vec::new();
+    let result = client
+        .call_tool_with_progress(&url, None, "slow", json!({}), |p| updates.push(p.clone()))
+        .await
+        .unwrap();
+    assert_eq!(result, "counted to 4");
+    assert_eq!(updates.len(), 4);
+    assert_eq!(updates[3].progress, 4.0);
+    assert_eq!(updates[3].total, Some(4.0));
+    assert_eq!(updates[0].message.as_deref(), Some("step 1"));
+    assert_eq!(mock.pings_answered.load(Ordering::SeqCst), 1);
+
+    // tools/list_changed dropped the cached list.
+    client.list_tools(&url, None).await.unwrap();
+    assert_eq!(mock.tools_list_calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn silent_stream_times_out() {
+    let (base, _mock) = spawn_mock().await;
+    let url = format!("{}/mcp", base);
+    let client = McpClientService::new().with_idle_timeout(Duration::from_millis(150));
+
+    let err = client
+        .call_tool(&url, None, "hang", json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.contains("stopped responding"), "{}", err);
+}
+
+#[tokio::test]
+async fn expired_session_is_reinitialized() {
+    let (base, mock) = spawn_mock().await;
+    let url = format!("{}/mcp", base);
+    let client = McpClientService::new();
+
+    client
+        .call_tool(&url, None, "echo", json!({ "text": "one" }))
+        .await
+        .unwrap();
+    mock.sessions.lock().unwrap().clear();
+    let echoed = client
+        .call_tool(&url, None, "echo", json!({ "text": "two" }))
+        .await
+        .unwrap();
+    assert_eq!(echoed, "echo: two");
+    assert_eq!(mock.initializes.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn closed_stream_is_resumed_from_last_event() {
+    let (base, _mock) = spawn_mock().await;
+    let url = format!("{}/mcp", base);
+    let client = McpClientService::new();
+
+    let result = client
+        .call_tool(&url, None, "resumable", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result, "resumed");
+}
+
+#[tokio::test]
+async fn resources_and_prompts() {
+    let (base, _mock) = spawn_mock().await;
+    let url = format!("{}/mcp", base);
+    let client = McpClientService::new();
+
+    let resources = client.list_resources(&url, None).await.unwrap();
+    assert_eq!(resources.len(), 2);
+    assert_eq!(resources[0].uri, "memo://today");
+    assert_eq!(resources[0].mime_type.as_deref(), Some("text/plain"));
+
+    let contents = client
+        .read_resource(&url, None, "memo://today")
+        .await
+        .unwrap();
+    assert_eq!(contents[0].text.as_deref(), Some("Water the plants"));
+
+    let prompts = client.list_prompts(&url, None).await.unwrap();
+    assert_eq!(prompts[0].name, "summarize");
+    assert!(prompts[0].arguments[0].required);
+
+    let rendered = client
+        .get_prompt(&url, None, "summarize", json!({ "uri": "memo://today" }))
+        .await
+        .unwrap();
+    assert_eq!(rendered, "user: Summarize memo://today");
+
+    let probe = client.probe(&url, None).await.unwrap();
+    assert_eq!(probe.tools.len(), 2);
+    assert_eq!(probe.resources.len(), 2);
+    assert_eq!(probe.prompts.len(), 1);
+}
+
+#[tokio::test]
+async fn falls_back_to_legacy_sse_transport() {
+    let (base, mock) = spawn_mock().await;
+    let url = format!("{}/legacy/sse", base);
+    let client = McpClientService::new();
+
+    let info = client.server_info(&url, None).await.unwrap();
+    assert_eq!(info.transport, McpTransport::LegacySse);
+    assert_eq!(info.protocol_version, "2024-11-05");
+    assert_eq!(info.name.as_deref(), Some("legacy-mock"));
+
+    let tools = client.list_tools(&url, None).await.unwrap();
+    assert_eq!(tools[0].name, "echo");
+    let echoed = client
+        .call_tool(&url, None, "echo", json!({ "text": "old" }))
+        .await
+        .unwrap();
+    assert_eq!(echoed, "echo: old");
+    // Legacy servers handshake on every connection.
+    assert!(mock.initializes.load(Ordering::SeqCst) >= 3);
+}
@@ -0,0 +1,390 @@
+//! OAuth 2.1 for MCP servers against an in-process authorization server
+//! and protected MCP endpoint.
+
+use axum::{
+    extract::{Form, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use backend::services::mcp_client::McpClientService;
+use backend::services::mcp_oauth::{
+    authorization_url, parse_bearer_challenge, McpOAuthClient, McpOAuthConfig, OAuthTokenSource,
+    OAuthTokenStore, OAuthTokens,
+};
+use base64::Engine;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+const REDIRECT_URI: &str = "http://localhost:3000/api/mcp/oauth/callback";
+
+#[derive(Default)]
+struct AuthServer {
+    base: String,
+    registered_redirects: Vec<String>,
+    /// code -> (code_challenge, resource)
+    codes: HashMap<String, (String, String)>,
+    valid_access: HashSet<String>,
+    refresh_token: Option<String>,
+    issued: u32,
+    refresh_calls: u32,
+    mcp_unauthorized: u32,
+}
+
+type Mock = Arc<Mutex<AuthServer>>;
+
+impl AuthServer {
+    fn issue(&mut self) -> Value {
+        self.issued += 1;
+        let access = format!("access-{}", self.issued);
+        let refresh = format!("refresh-{}", self.issued);
+        self.valid_access.insert(access.clone());
+        self.refresh_token = Some(refresh.clone());
+        json!({
+            "access_token": access,
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "refresh_token": refresh,
+        })
+    }
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+async fn protected_resource(State(mock): State<Mock>) -> Json<Value> {
+    let base = mock.lock().unwrap().base.clone();
+    Json(json!({
+        "resource": format!("{}/mcp", base),
+        "authorization_servers": [format!("{}/auth", base)],
+        "scopes_supported": ["tools", "offline_access"],
+    }))
+}
+
+async fn auth_metadata(State(mock): State<Mock>) -> Json<Value> {
+    let base = mock.lock().unwrap().base.clone();
+    Json(json!({
+        "issuer": format!("{}/auth", base),
+        "authorization_endpoint": format!("{}/auth/authorize", base),
+        "token_endpoint": format!("{}/auth/token", base),
+        "registration_endpoint": format!("{}/auth/register", base),
+        "code_challenge_methods_supported": ["S256"],
+    }))
+}
+
+async fn register(State(mock): State<Mock>, Json(body): Json<Value>) -> Response {
+    assert_eq!(body["token_endpoint_auth_method"], "none");
+    let redirects: Vec<String> = serde_json::from_value(body["redirect_uris"].clone()).unwrap();
+    mock.lock().unwrap().registered_redirects = redirects;
+    (
+        StatusCode::CREATED,
+        Json(json!({"client_id": "lightfriend-client"})),
+    )
+        .into_response()
+}
+
+async fn authorize(
+    State(mock): State<Mock>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    let mut mock = mock.lock().unwrap();
+    assert_eq!(params["client_id"], "lightfriend-client");
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert!(mock.registered_redirects.contains(&params["redirect_uri"]));
+    let code = format!("code-{}", mock.codes.len() + 1);
+    mock.codes.insert(
+        code.clone(),
+        (params["code_challenge"].clone(), params["resource"].clone()),
+    );
+    Redirect::to(&format!(
+        "{}?code={}&state={}",
+        params["redirect_uri"], code, params["state"]
+    ))
+    .into_response()
+}
+
+async fn token(State(mock): State<Mock>, Form(form): Form<HashMap<String, String>>) -> Response {
+    let mut mock = mock.lock().unwrap();
+    let invalid =
+        |error: &str| (StatusCode::BAD_REQUEST, Json(json!({"error": error}))).into_response();
+    if form.get("client_id").map(String::as_str) != Some("lightfriend-client") {
+        return invalid("invalid_client");
+    }
+    if form.get("resource") != Some(&format!("{}/mcp", mock.base)) {
+        return invalid("invalid_target");
+    }
+    match form.get("grant_type").map(String::as_str) {
+        Some("authorization_code") => {
+            let Some((challenge, _)) = mock.codes.remove(&form["code"]) else {
+                return invalid("invalid_grant");
+            };
+            if pkce_challenge(&form["code_verifier"]) != challenge {
+                return invalid("invalid_grant");
+            }
+            Json(mock.issue()).into_response()
+        }
+        Some("refresh_token") => {
+            if mock.refresh_token.as_ref() != Some(&form["refresh_token"]) {
+                return invalid("invalid_grant");
+            }
+            mock.refresh_calls += 1;
+            Json(mock.issue()).into_response()
+        }
+        _ => invalid("unsupported_grant_type"),
+    }
+}
+
+async fn mcp(State(mock): State<Mock>, headers: HeaderMap, Json(body): Json<Value>) -> Response {
+    let authorized = {
+        let mut mock = mock.lock().unwrap();
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        let ok = token.is_some_and(|t| mock.valid_access.contains(t));
+        if !ok {
+            mock.mcp_unauthorized += 1;
+        }
+        ok
+    };
+    if !authorized {
+        let base = mock.lock().unwrap().base.clone();
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(
+                header::WWW_AUTHENTICATE,
+                format!(
+                    "Bearer error=\"invalid_token\", resource_metadata=\"{}/.well-known/oauth-protected-resource/mcp\", scope=\"tools\"",
+                    base
+                ),
+            )],
+        )
+            .into_response();
+    }
+
+    let Some(id) = body.get("id").cloned() else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+    let result = match body["method"].as_str().unwrap_or("") {
+        "initialize" => json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": {"tools": {}},
+            "serverInfo": {"name": "protected", "version": "1.0"}
+        }),
+        "tools/list" => json!({"tools": [{"name": "whoami", "inputSchema": {"type": "object"}}]}),
+        "tools/call" => json!({"content": [{"type": "text", "text": "you are authorized"}]}),
+        other => panic!("unexpected method {}", other),
+    };
+    Json(json!({"jsonrpc": "2.0", "id": id, "result": result})).into_response()
+}
+
+async fn start() -> (String, Mock) {
+    let mock: Mock = Arc::default();
+    let app = Router::new()
+        .route("/mcp", post(mcp))
+        .route(
+            "/.well-known/oauth-protected-resource/mcp",
+            get(protected_resource),
+        )
+        .route(
+            "/.well-known/oauth-authorization-server/auth",
+            get(auth_metadata),
+        )
+        .route("/auth/register", post(register))
+        .route("/auth/authorize", get(authorize))
+        .route("/auth/token", post(token))
+        .with_state(mock.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    mock.lock().unwrap().base = base.clone();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (base, mock)
+}
+
+#[derive(Clone, Default)]
+struct MemoryStore(Arc<Mutex<Option<OAuthTokens>>>);
+
+impl OAuthTokenStore for MemoryStore {
+    fn load(&self) -> Result<OAuthTokens, String> {
+        self.0.lock().unwrap().clone().ok_or("not connected".into())
+    }
+
+    fn save(&self, tokens: &OAuthTokens) -> Result<(), String> {
+        *self.0.lock().unwrap() = Some(tokens.clone());
+        Ok(())
+    }
+}
+
+/// The browser leg: follow the authorization URL and return the code the
+/// server hands back on the redirect.
+async fn browser_authorize(config: &McpOAuthConfig, verifier: &str) -> String {
+    let url = authorization_url(config, "session:csrf", &pkce_challenge(verifier)).unwrap();
+    let response = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+        .get(&url)
+        .send()
+        .await
+        .unwrap();
+    let location = response.headers()[header::LOCATION].to_str().unwrap();
+    assert!(location.starts_with(REDIRECT_URI));
+    let params: HashMap<_, _> = url::Url::parse(location)
+        .unwrap()
+        .query_pairs()
+        .into_owned()
+        .collect();
+    assert_eq!(params["state"], "session:csrf");
+    params["code"].clone()
+}
+
+/// Discovery, registration and code exchange, returning the config and the
+/// first tokens.
+async fn authorize_flow(base: &str) -> (McpOAuthConfig, OAuthTokens) {
+    let oauth = McpOAuthClient::new().allowing_private_hosts();
+    let config = oauth
+        .prepare(&format!("{}/mcp", base), REDIRECT_URI)
+        .await
+        .unwrap();
+    let verifier = "a-very-long-and-random-code-verifier-for-this-test-0123456789";
+    let code = browser_authorize(&config, verifier).await;
+    let tokens = oauth.exchange_code(&config, &code, verifier).await.unwrap();
+    (config, tokens)
+}
+
+#[test]
+fn bearer_challenge_is_parsed() {
+    let params = parse_bearer_challenge(
+        r#"Bearer realm="mcp", resource_metadata="https://x.test/.well-known/oauth-protected-resource", scope="a b",error=invalid_token"#,
+    )
+    .unwrap();
+    assert_eq!(
+        params["resource_metadata"],
+        "https://x.test/.well-known/oauth-protected-resource"
+    );
+    assert_eq!(params["scope"], "a b");
+    assert_eq!(params["error"], "invalid_token");
+    assert!(parse_bearer_challenge("Basic realm=\"x\"").is_none());
+}
+
+#[tokio::test]
+async fn discovers_registers_and_exchanges_code_with_pkce() {
+    let (base, mock) = start().await;
+
+    let metadata = McpOAuthClient::new()
+        .allowing_private_hosts()
+        .discover(&format!("{}/mcp", base))
+        .await
+        .unwrap();
+    assert_eq!(metadata.resource, format!("{}/mcp", base));
+    assert_eq!(metadata.token_endpoint, format!("{}/auth/token", base));
+    assert_eq!(metadata.scope.as_deref(), Some("tools"));
+
+    let (config, tokens) = authorize_flow(&base).await;
+    assert_eq!(config.client_id, "lightfriend-client");
+    assert_eq!(config.redirect_uri, REDIRECT_URI);
+    assert_eq!(tokens.access_token, "access-1");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("refresh-1"));
+    assert!(tokens.expires_at.is_some() && !tokens.expires_soon());
+    assert_eq!(
+        mock.lock().unwrap().registered_redirects,
+        vec![REDIRECT_URI.to_string()]
+    );
+
+    let code = browser_authorize(&config, "the-real-verifier-0123456789-0123456789-abc").await;
+    let err = McpOAuthClient::new()
+        .allowing_private_hosts()
+        .exchange_code(
+            &config,
+            &code,
+            "a-forged-verifier-0123456789-0123456789-abc",
+        )
+        .await
+        .unwrap_err();
+    assert!(err.contains("invalid_grant"), "{}", err);
+}
+
+#[tokio::test]
+async fn private_hosts_are_refused_by_default() {
+    let (base, _mock) = start().await;
+    let err = McpOAuthClient::new()
+        .discover(&format!("{}/mcp", base))
+        .await
+        .unwrap_err();
+    assert!(err.contains("private") || err.contains("local"), "{}", err);
+}
+
+#[tokio::test]
+async fn client_refreshes_rejected_and_expiring_tokens() {
+    let (base, mock) = start().await;
+    let (config, tokens) = authorize_flow(&base).await;
+    let store = MemoryStore::default();
+    store.save(&tokens).unwrap();
+
+    let source = Arc::new(OAuthTokenSource::new(
+        McpOAuthClient::new().allowing_private_hosts(),
+        config,
+        store.clone(),
+        format!("test:{}", base),
+    ));
+    let client = McpClientService::new().with_token_source(source);
+    let url = format!("{}/mcp", base);
+
+    // The server revoked the token: the 401 on the handshake triggers a refresh.
+    mock.lock().unwrap().valid_access.clear();
+    let tools = client.list_tools(&url, None).await.unwrap();
+    assert_eq!(tools[0].name, "whoami");
+    assert_eq!(mock.lock().unwrap().refresh_calls, 1);
+    let saved = store.load().unwrap();
+    assert_eq!(saved.access_token, "access-2");
+    assert_eq!(saved.refresh_token.as_deref(), Some("refresh-2"));
+
+    // Revoked again mid-session: the call itself is retried after a refresh.
+    mock.lock().unwrap().valid_access.clear();
+    let result = client
+        .call_tool(&url, None, "whoami", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result, "you are authorized");
+    assert_eq!(mock.lock().unwrap().refresh_calls, 2);
+
+    // About to expire: refreshed up front, without a round trip through 401.
+    let unauthorized_before = mock.lock().unwrap().mcp_unauthorized;
+    let mut expiring = store.load().unwrap();
+    expiring.expires_at = Some(chrono::Utc::now().timestamp() + 5);
+    store.save(&expiring).unwrap();
+    client
+        .call_tool(&url, None, "whoami", json!({}))
+        .await
+        .unwrap();
+    let mock = mock.lock().unwrap();
+    assert_eq!(mock.refresh_calls, 3);
+    assert_eq!(mock.mcp_unauthorized, unauthorized_before);
+    assert_eq!(store.load().unwrap().access_token, "access-4");
+}
+
+#[tokio::test]
+async fn missing_refresh_token_asks_to_reconnect() {
+    let (base, mock) = start().await;
+    let (config, mut tokens) = authorize_flow(&base).await;
+    tokens.refresh_token = None;
+    let store = MemoryStore::default();
+    store.save(&tokens).unwrap();
+    mock.lock().unwrap().valid_access.clear();
+
+    let client = McpClientService::new().with_token_source(Arc::new(OAuthTokenSource::new(
+        McpOAuthClient::new().allowing_private_hosts(),
+        config,
+        store,
+        format!("test:{}", base),
+    )));
+    let err = client
+        .list_tools(&format!("{}/mcp", base), None)
+        .await
+        .unwrap_err();
+    assert!(err.contains("Reconnect"), "{}", err);
+}
@@ -0,0 +1,221 @@
+use axum::body::{to_bytes, Bytes};
+use axum::extract::{OriginalUri, Path, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode, Uri};
+use axum::response::Response;
+use axum::Json;
+use backend::handlers::auth_middleware::AuthUser;
+use backend::handlers::mcp_server_handlers::{
+    create_access_token, list_access_tokens, mcp_endpoint, revoke_access_token, update_access_token,
+};
+use backend::models::mcp_models::{
+    canonical_mcp_tools, CreateMcpAccessTokenRequest, UpdateMcpAccessTokenRequest,
+};
+use backend::models::ontology_models::NewOntEvent;
+use backend::repositories::mcp_access_token_repository::McpAccessTokenRepository;
+use backend::test_utils::{create_test_state, create_test_user, TestUserParams};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+fn owner(user_id: i32) -> AuthUser {
+    AuthUser {
+        user_id,
+        is_admin: false,
+    }
+}
+
+async fn body_json(response: Response) -> Value {
+    let bytes = to_bytes(response.into_body(), 64 * 1024).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+async fn rpc(state: &Arc<backend::AppState>, token: &str, message: Value) -> (StatusCode, Value) {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+    );
+    let response = mcp_endpoint(
+        State(state.clone()),
+        OriginalUri(Uri::from_static("/api/mcp")),
+        headers,
+        Bytes::from(message.to_string()),
+    )
+    .await;
+    let status = response.status();
+    if status != StatusCode::OK {
+        return (status, Value::Null);
+    }
+    (status, body_json(response).await)
+}
+
+#[test]
+fn tool_allow_lists_are_validated_and_canonical() {
+    assert_eq!(
+        canonical_mcp_tools(&["send_sms_to_self", "query_events", "query_events"]).as_deref(),
+        Some("query_events,send_sms_to_self")
+    );
+    assert_eq!(canonical_mcp_tools::<&str>(&[]), None);
+    assert_eq!(canonical_mcp_tools(&["send_email"]), None);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn tokens_expose_only_their_allowed_tools_and_can_be_revoked() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    let now = chrono::Utc::now().timestamp() as i32;
+    state
+        .ontology_repository
+        .create_reminder(
+            &NewOntEvent {
+                user_id: user.id,
+                description: "Renew passport".to_string(),
+                remind_at: Some(now + 3600),
+                due_at: Some(now + 3600),
+                status: "active".to_string(),
+                created_at: now,
+                updated_at: now,
+            },
+            "UTC",
+        )
+        .unwrap();
+
+    let minted = create_access_token(
+        State(state.clone()),
+        owner(user.id),
+        Json(CreateMcpAccessTokenRequest {
+            label: "Desktop".to_string(),
+            tools: Some(vec!["query_events".to_string()]),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(minted.status(), StatusCode::CREATED);
+    let minted = body_json(minted).await;
+    let raw = minted["token"].as_str().unwrap().to_string();
+    let token_id = minted["id"].as_i64().unwrap() as i32;
+    assert!(raw.starts_with("lfmcp_") && raw.len() == 70);
+    assert_eq!(minted["tools"], json!(["query_events"]));
+
+    // Only the hash is stored and listing never repeats the token.
+    let repository = McpAccessTokenRepository::new(state.pg_pool.clone());
+    let stored = repository
+        .find_active(&hex::encode(Sha256::digest(raw.as_bytes())))
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.last_used_at, None);
+    let Json(listed) = list_access_tokens(State(state.clone()), owner(user.id))
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].token.is_none());
+
+    let (status, init) = rpc(
+        &state,
+        &raw,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+            "protocolVersion": "2025-06-18", "capabilities": {},
+            "clientInfo": {"name": "test", "version": "1"}
+        }}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(init["result"]["protocolVersion"], "2025-06-18");
+    assert_eq!(
+        rpc(
+            &state,
+            &raw,
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"})
+        )
+        .await
+        .0,
+        StatusCode::ACCEPTED
+    );
+
+    let (_, tools) = rpc(
+        &state,
+        &raw,
+        json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+    )
+    .await;
+    let names = tools["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["query_events"]);
+
+    let (_, events) = rpc(
+        &state,
+        &raw,
+        json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+               "params": {"name": "query_events", "arguments": {}}}),
+    )
+    .await;
+    assert_eq!(events["result"]["isError"], false);
+    let text = events["result"]["content"][0]["text"].as_str().unwrap();
+    let events: Value = serde_json::from_str(text).unwrap();
+    assert_eq!(events["events"][0]["description"], "Renew passport");
+    assert!(repository
+        .find_active(&stored.token_hash)
+        .unwrap()
+        .unwrap()
+        .last_used_at
+        .is_some());
+
+    // Tools outside the allow-list look like tools that don't exist.
+    let (_, denied) = rpc(
+        &state,
+        &raw,
+        json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call",
+               "params": {"name": "send_sms_to_self", "arguments": {"message": "hi"}}}),
+    )
+    .await;
+    assert_eq!(denied["error"]["code"], -32602);
+    assert_eq!(denied["error"]["message"], "Unknown tool: send_sms_to_self");
+
+    let Json(updated) = update_access_token(
+        State(state.clone()),
+        owner(user.id),
+        Path(token_id),
+        Json(UpdateMcpAccessTokenRequest {
+            tools: vec!["query_events".to_string(), "set_reminder".to_string()],
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated.tools, ["set_reminder", "query_events"]);
+    let (_, tools) = rpc(
+        &state,
+        &raw,
+        json!({"jsonrpc": "2.0", "id": 5, "method": "tools/list"}),
+    )
+    .await;
+    assert_eq!(tools["result"]["tools"].as_array().unwrap().len(), 2);
+
+    // Another user can't revoke it; the owner can, and the bearer stops working.
+    let other = create_test_user(&state, &TestUserParams::finland_user(10.0, 5.0));
+    assert!(
+        revoke_access_token(State(state.clone()), owner(other.id), Path(token_id))
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        revoke_access_token(State(state.clone()), owner(user.id), Path(token_id))
+            .await
+            .unwrap(),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        rpc(
+            &state,
+            &raw,
+            json!({"jsonrpc": "2.0", "id": 6, "method": "ping"})
+        )
+        .await
+        .0,
+        StatusCode::UNAUTHORIZED
+    );
+}
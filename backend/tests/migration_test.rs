@@ -90,12 +90,21 @@ fn test_migrations_apply_cleanly_with_existing_data() {
         "items",
         "imap_connection",
         "mcp_servers",
+        "caldav_connections",
         "totp_secrets",
         "totp_backup_codes",
         "agent_credentials",
         "agent_pairing_sessions",
         "agent_action_idempotency",
         "agent_action_audit",
+        "agent_created_items",
+        "matrix_dm_channels",
+        "push_endpoints",
+        "email_notification_channels",
+        "voice_routes",
+        "reply_contexts",
+        "ont_rule_continuations",
+        "encryption_rotation_progress",
     ] {
         let query = format!("SELECT count(*) as count FROM {table}");
         let result: CountResult = sql_query(&query)
@@ -108,9 +117,9 @@ fn test_migrations_apply_cleanly_with_existing_data() {
     let _ = sql_query(
         "TRUNCATE items, message_history, usage_logs, contact_profiles, \
          contact_profile_exceptions, bridges, bridge_disconnection_events, \
-         imap_connection, tesla, youtube, mcp_servers, totp_secrets, \
-         totp_backup_codes, webauthn_credentials, webauthn_challenges, \
-         user_secrets, user_info, processed_emails CASCADE",
+         imap_connection, tesla, youtube, mcp_servers, caldav_connections, \
+         totp_secrets, totp_backup_codes, webauthn_credentials, \
+         webauthn_challenges, user_secrets, user_info, processed_emails CASCADE",
     )
     .execute(&mut conn);
 }
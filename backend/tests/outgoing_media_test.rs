@@ -0,0 +1,179 @@
+use backend::services::inbound_media::decode_data_url;
+use backend::services::outgoing_media::{
+    check_limits, classify, format_size, ogg_opus_duration_ms, parse_media_type, to_data_url,
+    MediaKind, OutgoingMedia, OutgoingMediaError,
+};
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let img = image::RgbImage::from_pixel(width, height, image::Rgb([200, 40, 40]));
+    let mut out = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut out, image::ImageOutputFormat::Png)
+        .unwrap();
+    out.into_inner()
+}
+
+/// Two Ogg pages: the `OpusHead` header (pre-skip 312) and a final audio
+/// page whose granule position ends 12 seconds in.
+fn opus_note(seconds: u64) -> Vec<u8> {
+    let pre_skip: u16 = 312;
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"OggS\0\x02");
+    bytes.extend_from_slice(&0i64.to_le_bytes());
+    bytes.extend_from_slice(&[0; 13]);
+    bytes.extend_from_slice(b"OpusHead\x01\x01");
+    bytes.extend_from_slice(&pre_skip.to_le_bytes());
+    bytes.extend_from_slice(&48_000u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 3]);
+    bytes.extend_from_slice(b"OggS\0\x04");
+    let granule = seconds * 48_000 + u64::from(pre_skip);
+    bytes.extend_from_slice(&(granule as i64).to_le_bytes());
+    bytes.extend_from_slice(&[0; 64]);
+    bytes
+}
+
+#[test]
+fn media_is_sent_with_the_msgtype_bridges_expect() {
+    assert_eq!(classify("image/jpeg"), MediaKind::Image);
+    assert_eq!(classify("IMAGE/PNG"), MediaKind::Image);
+    // HEIC and friends don't render inline on every network; send as files.
+    assert_eq!(classify("image/heic"), MediaKind::File);
+    assert_eq!(classify("audio/ogg"), MediaKind::Voice);
+    assert_eq!(classify("audio/mpeg"), MediaKind::Audio);
+    assert_eq!(classify("video/mp4"), MediaKind::Video);
+    assert_eq!(classify("application/pdf"), MediaKind::File);
+
+    assert_eq!(MediaKind::Voice.msgtype(), "m.audio");
+    assert_eq!(MediaKind::File.msgtype(), "m.file");
+}
+
+#[test]
+fn attachments_are_checked_against_the_target_network() {
+    let big_photo = vec![0u8; 17 * 1024 * 1024];
+    match OutgoingMedia::inspect("whatsapp", "image/jpeg", big_photo.clone(), None) {
+        Err(e @ OutgoingMediaError::TooLarge { .. }) => assert_eq!(
+            e.to_string(),
+            "WhatsApp accepts photos up to 16.0 MB; this one is 17.0 MB"
+        ),
+        other => panic!("expected TooLarge, got {:?}", other.map(|m| m.kind)),
+    }
+    // Signal takes it; the bytes just have to be a readable image.
+    assert!(matches!(
+        OutgoingMedia::inspect("signal", "image/jpeg", big_photo, None),
+        Err(OutgoingMediaError::Decode(_))
+    ));
+
+    let pdf = b"%PDF-1.7 ...".to_vec();
+    assert!(matches!(
+        OutgoingMedia::inspect("instagram", "application/pdf", pdf.clone(), None),
+        Err(OutgoingMediaError::Unsupported(_))
+    ));
+    let file = OutgoingMedia::inspect("telegram", "application/pdf", pdf, None).unwrap();
+    assert_eq!(file.filename, "file.pdf");
+    assert_eq!(file.describe(), "file 'file.pdf' (12 B)");
+
+    assert!(matches!(
+        OutgoingMedia::inspect("whatsapp", "application/x-msdownload", b"MZ".to_vec(), None),
+        Err(OutgoingMediaError::Unsupported(_))
+    ));
+    assert!(matches!(
+        OutgoingMedia::inspect("whatsapp", "image/png", Vec::new(), None),
+        Err(OutgoingMediaError::Empty)
+    ));
+}
+
+#[test]
+fn declared_sizes_are_checked_before_download() {
+    // A 2 GB Telegram video never gets fetched for WhatsApp.
+    assert!(matches!(
+        check_limits("whatsapp", "video/mp4", 2 * 1024 * 1024 * 1024),
+        Err(OutgoingMediaError::TooLarge { .. })
+    ));
+    assert_eq!(
+        check_limits("whatsapp", "video/mp4; codecs=avc1", 5 * 1024 * 1024).unwrap(),
+        MediaKind::Video
+    );
+    assert!(matches!(
+        check_limits("gmessages", "application/pdf", 1024),
+        Err(OutgoingMediaError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn photos_carry_dimensions_and_a_thumbnail() {
+    let photo = OutgoingMedia::inspect("whatsapp", "image/png", png(800, 600), Some("dog.png"))
+        .unwrap()
+        .with_thumbnail();
+    assert_eq!((photo.width, photo.height), (Some(800), Some(600)));
+    let thumb = photo.thumbnail.as_ref().unwrap();
+    assert_eq!((thumb.width, thumb.height), (320, 240));
+    assert!(photo.describe().starts_with("photo ("));
+
+    let content = photo.event_content("Look at him", "mxc://hs/photo", Some("mxc://hs/thumb"));
+    assert_eq!(content["msgtype"], "m.image");
+    assert_eq!(content["body"], "Look at him");
+    assert_eq!(content["filename"], "dog.png");
+    assert_eq!(content["url"], "mxc://hs/photo");
+    assert_eq!(content["info"]["mimetype"], "image/png");
+    assert_eq!(content["info"]["w"], 800);
+    assert_eq!(content["info"]["thumbnail_url"], "mxc://hs/thumb");
+    assert_eq!(content["info"]["thumbnail_info"]["w"], 320);
+
+    // Small photos need no thumbnail, and without a caption the body is the
+    // filename.
+    let small = OutgoingMedia::inspect("whatsapp", "image/png", png(100, 80), None)
+        .unwrap()
+        .with_thumbnail();
+    assert!(small.thumbnail.is_none());
+    let content = small.event_content("  ", "mxc://hs/small", None);
+    assert_eq!(content["body"], "photo.png");
+    assert!(content["info"].get("thumbnail_url").is_none());
+
+    // Past the decode limits the photo still goes out, without a thumbnail.
+    let panorama = OutgoingMedia::inspect("signal", "image/png", png(13_000, 10), None)
+        .unwrap()
+        .with_thumbnail();
+    assert_eq!(panorama.width, Some(13_000));
+    assert!(panorama.thumbnail.is_none());
+}
+
+#[test]
+fn voice_notes_are_flagged_with_their_duration() {
+    assert_eq!(ogg_opus_duration_ms(&opus_note(12)), Some(12_000));
+    assert_eq!(ogg_opus_duration_ms(b"OggS not opus"), None);
+
+    let note =
+        OutgoingMedia::inspect("signal", "audio/ogg; codecs=opus", opus_note(72), None).unwrap();
+    assert_eq!(note.kind, MediaKind::Voice);
+    assert_eq!(note.mimetype, "audio/ogg");
+    assert!(!note.carries_caption());
+    assert!(note.describe().starts_with("voice note (1:12, "));
+
+    let content = note.event_content("listen", "mxc://hs/voice", None);
+    assert_eq!(content["msgtype"], "m.audio");
+    assert_eq!(content["body"], "voice.ogg");
+    assert_eq!(content["info"]["duration"], 72_000);
+    assert_eq!(content["org.matrix.msc1767.audio"]["duration"], 72_000);
+    assert!(content["org.matrix.msc3245.voice"].is_object());
+}
+
+#[test]
+fn data_urls_keep_the_attachment_filename() {
+    let url = to_data_url("application/pdf", Some("Lasku; maaliskuu.pdf"), b"%PDF");
+    let (content_type, bytes) = decode_data_url(&url).unwrap();
+    assert_eq!(bytes, b"%PDF");
+    assert_eq!(
+        parse_media_type(&content_type),
+        (
+            "application/pdf".to_string(),
+            Some("Lasku; maaliskuu.pdf".to_string())
+        )
+    );
+    assert_eq!(
+        parse_media_type("image/JPEG"),
+        ("image/jpeg".to_string(), None)
+    );
+
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(340 * 1024), "340 KB");
+    assert_eq!(format_size(1_258_291), "1.2 MB");
+}
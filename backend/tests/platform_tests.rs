@@ -1,5 +1,7 @@
 #[path = "digest_health_endpoint_test.rs"]
 mod digest_health_endpoint_test;
+#[path = "encryption_key_ring_test.rs"]
+mod encryption_key_ring_test;
 #[path = "event_lifecycle_test.rs"]
 mod event_lifecycle_test;
 #[path = "kani_signature_proofs.rs"]
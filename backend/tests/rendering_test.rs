@@ -0,0 +1,184 @@
+use backend::rendering::chart::{format_number, nice_axis, render_chart, ChartSpec, ChartStyle};
+use backend::rendering::font::{glyph, text_width};
+use backend::rendering::map::{choose_zoom, haversine_km, render_map, MapMarker};
+use backend::rendering::qr::render_qr;
+use backend::rendering::store;
+
+fn decode(png: &[u8]) -> image::DynamicImage {
+    assert_eq!(image::guess_format(png).unwrap(), image::ImageFormat::Png);
+    image::load_from_memory(png).unwrap()
+}
+
+fn marker(latitude: f64, longitude: f64, label: &str) -> MapMarker {
+    MapMarker {
+        latitude,
+        longitude,
+        label: Some(label.to_string()),
+    }
+}
+
+#[test]
+fn font_folds_case_and_accents_and_falls_back_to_question_mark() {
+    assert_eq!(glyph('a'), glyph('A'));
+    assert_eq!(glyph('ä'), glyph('A'));
+    assert_eq!(glyph('é'), glyph('E'));
+    assert_eq!(glyph('漢'), glyph('?'));
+    assert_eq!(text_width("", 2), 0);
+    assert_eq!(text_width("AB", 2), 22);
+}
+
+#[test]
+fn qr_code_scans_back_to_its_data() {
+    let data = "M1DOE/JOHN EABC123 HELAMSAY 1234 100Y012A0001 100";
+    let rendered = render_qr(data).unwrap();
+    let img = decode(&rendered.png).to_luma8();
+
+    assert_eq!(img.width(), img.height());
+    assert!(img.width() <= 360);
+    // Quiet zone: the corner is white.
+    assert_eq!(img.get_pixel(0, 0).0, [255]);
+
+    let mut decoder = quircs::Quirc::new();
+    let codes: Vec<_> = decoder
+        .identify(img.width() as usize, img.height() as usize, &img)
+        .collect();
+    assert_eq!(codes.len(), 1);
+    let payload = codes[0].as_ref().unwrap().decode().unwrap().payload;
+    assert_eq!(String::from_utf8(payload).unwrap(), data);
+    assert_eq!(rendered.alt_text, format!("QR code content: {}", data));
+}
+
+#[test]
+fn qr_code_rejects_empty_data() {
+    assert!(render_qr("  ").is_err());
+}
+
+#[test]
+fn nice_axis_rounds_out_to_short_steps() {
+    assert_eq!(nice_axis(3.0, 17.0), (0.0, 20.0, 5.0));
+    assert_eq!(nice_axis(-4.2, 1.3), (-6.0, 2.0, 2.0));
+    // A flat series still gets a visible range around it.
+    let (lo, hi, step) = nice_axis(5.0, 5.0);
+    assert!(lo < 5.0 && hi > 5.0 && step > 0.0);
+    assert_eq!(format_number(12.0), "12");
+    assert_eq!(format_number(0.25), "0.25");
+    assert_eq!(format_number(-0.001), "0");
+}
+
+#[test]
+fn line_chart_renders_with_summary_alt_text() {
+    let spec = ChartSpec {
+        title: "Helsinki tomorrow".into(),
+        unit: Some("°C".into()),
+        labels: vec!["06".into(), "09".into(), "12".into(), "15".into()],
+        values: vec![-2.0, 1.5, 4.0, 3.0],
+        style: ChartStyle::Line,
+    };
+    let rendered = render_chart(&spec).unwrap();
+    let img = decode(&rendered.png);
+
+    assert_eq!((img.width(), img.height()), (480, 320));
+    assert_eq!(
+        rendered.alt_text,
+        "Helsinki tomorrow: low -2°C (06), high 4°C (12). 06 -2, 09 1.5, 12 4, 15 3"
+    );
+}
+
+#[test]
+fn long_bar_chart_alt_text_skips_the_value_list() {
+    let spec: ChartSpec = serde_json::from_value(serde_json::json!({
+        "title": "Spot price",
+        "values": (0..24).map(|h| h as f64).collect::<Vec<_>>(),
+        "style": "bar"
+    }))
+    .unwrap();
+    let rendered = render_chart(&spec).unwrap();
+
+    assert_eq!(spec.style, ChartStyle::Bar);
+    assert_eq!(rendered.alt_text, "Spot price: low 0 (1), high 23 (24).");
+}
+
+#[test]
+fn chart_rejects_mismatched_labels_and_non_finite_values() {
+    let mismatched = ChartSpec {
+        title: "x".into(),
+        labels: vec!["a".into()],
+        values: vec![1.0, 2.0],
+        ..Default::default()
+    };
+    assert!(render_chart(&mismatched).is_err());
+
+    let nan = ChartSpec {
+        title: "x".into(),
+        values: vec![f64::NAN],
+        ..Default::default()
+    };
+    assert!(render_chart(&nan).is_err());
+}
+
+#[test]
+fn zoom_fits_markers_and_defaults_close_for_one() {
+    let charger = marker(60.1699, 24.9384, "Charger");
+    assert_eq!(choose_zoom(std::slice::from_ref(&charger)), 15);
+
+    let nearby = [charger.clone(), marker(60.1719, 24.9414, "Car")];
+    let far = [charger.clone(), marker(60.4518, 22.2666, "Turku")];
+    assert!(choose_zoom(&nearby) > choose_zoom(&far));
+    assert!(choose_zoom(&far) >= 7);
+}
+
+#[test]
+fn haversine_matches_known_distance() {
+    let helsinki = marker(60.1699, 24.9384, "Helsinki");
+    let turku = marker(60.4518, 22.2666, "Turku");
+    let km = haversine_km(&helsinki, &turku);
+    assert!((km - 150.0).abs() < 5.0, "got {}", km);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn map_without_tile_source_draws_schematic_with_alt_text() {
+    std::env::remove_var("MAP_TILE_URL");
+    let markers = [
+        marker(60.1699, 24.9384, "Your car"),
+        marker(60.1719, 24.9414, "Supercharger"),
+    ];
+    let rendered = render_map(&markers).await.unwrap();
+    let img = decode(&rendered.png);
+
+    assert_eq!((img.width(), img.height()), (480, 360));
+    assert!(rendered
+        .alt_text
+        .starts_with("Map: A) Your car (60.16990, 24.93840); B) Supercharger"));
+    assert!(rendered
+        .alt_text
+        .contains("Straight-line distance: 0.3 km."));
+    assert!(rendered.alt_text.contains("openstreetmap.org"));
+}
+
+#[tokio::test]
+async fn map_rejects_no_markers_and_out_of_range_coordinates() {
+    assert!(render_map(&[]).await.is_err());
+    assert!(render_map(&[marker(89.0, 0.0, "Pole")]).await.is_err());
+}
+
+#[test]
+#[serial_test::serial]
+fn store_serves_images_by_url_token_and_keeps_alt_text() {
+    std::env::set_var("SERVER_URL", "https://lf.example.com/");
+    let token = store::put(render_qr("hello").unwrap());
+    let url = store::public_url(&token).unwrap();
+    std::env::remove_var("SERVER_URL");
+
+    assert_eq!(
+        url,
+        format!("https://lf.example.com/api/media/rendered/{}.png", token)
+    );
+    assert!(store::get(&token).is_some());
+    assert_eq!(
+        store::alt_text_for_url(&url).as_deref(),
+        Some("QR code content: hello")
+    );
+    assert!(store::get("not-a-token").is_none());
+    assert!(store::alt_text_for_url("https://example.com/cat.png").is_none());
+}
@@ -0,0 +1,141 @@
+use backend::models::user_models::NewReplyContext;
+use backend::proactive::reply_context::{
+    alert_answer, commitment_answer, is_stop, parse_coded_reply, pick_code, record, tag,
+    try_resolve, ReplyResolution, ReplyTarget, KIND_NOTIFICATION,
+};
+use backend::repositories::commitment_repository::{REPLY_MUTE, REPLY_TRACK, REPLY_WRONG};
+use backend::repositories::reply_contexts_repository::{ReplyContextsRepository, RING_SIZE};
+use backend::test_utils::{create_test_state, create_test_user, TestUserParams};
+use backend::utils::encryption::{decrypt, encrypt};
+use rand::SeedableRng;
+use serial_test::serial;
+
+fn parsed(code: &str, rest: &str) -> Option<(String, String)> {
+    Some((code.to_string(), rest.to_string()))
+}
+
+#[test]
+fn code_is_read_from_either_end_of_the_reply() {
+    assert_eq!(parse_coded_reply("A7 yes"), parsed("A7", "yes"));
+    assert_eq!(
+        parse_coded_reply("#a7: yes do it"),
+        parsed("A7", "yes do it")
+    );
+    assert_eq!(parse_coded_reply("stop A7"), parsed("A7", "stop"));
+    assert_eq!(parse_coded_reply("(K9) - track"), parsed("K9", "track"));
+    assert_eq!(parse_coded_reply("A7"), parsed("A7", ""));
+}
+
+#[test]
+fn ordinary_messages_carry_no_code() {
+    assert_eq!(parse_coded_reply("yes do it"), None);
+    assert_eq!(parse_coded_reply("B2B meeting at 3"), None);
+    // I/O and 0/1 are never issued, so they never match.
+    assert_eq!(parse_coded_reply("I4 no"), None);
+    assert_eq!(parse_coded_reply("A1 yes"), None);
+    assert_eq!(parse_coded_reply("   "), None);
+}
+
+#[test]
+fn answers_map_to_each_subsystem() {
+    assert_eq!(alert_answer("Yes!"), Some(true));
+    assert_eq!(alert_answer("2"), Some(false));
+    assert_eq!(alert_answer("should wait"), Some(false));
+    assert_eq!(alert_answer("who sent it?"), None);
+
+    assert_eq!(commitment_answer("track"), Some(REPLY_TRACK));
+    assert_eq!(commitment_answer("stop"), Some(REPLY_MUTE));
+    assert_eq!(commitment_answer("Not a commitment."), Some(REPLY_WRONG));
+    assert_eq!(commitment_answer("remind me friday"), None);
+
+    assert!(is_stop("Pause"));
+    assert!(!is_stop("stop asking about this"));
+}
+
+#[test]
+fn picked_codes_avoid_ones_in_use() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    let mut taken = Vec::new();
+    for _ in 0..RING_SIZE {
+        let code = pick_code(&taken, &mut rng);
+        assert_eq!(parse_coded_reply(&code), parsed(&code, ""));
+        assert!(!taken.contains(&code));
+        taken.push(code);
+    }
+    assert_eq!(tag("A7", "Reply from Anna: ok"), "#A7 Reply from Anna: ok");
+}
+
+#[test]
+#[serial]
+fn buffer_keeps_the_newest_rows_and_one_row_per_code() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    let repo = ReplyContextsRepository::new(state.pg_pool.clone());
+    let row = |code: String, created_at: i32| NewReplyContext {
+        user_id: user.id,
+        code,
+        kind: KIND_NOTIFICATION.to_string(),
+        target_id: None,
+        target_ref: None,
+        created_at,
+        encrypted_summary: Some(encrypt(&format!("at {}", created_at)).unwrap()),
+    };
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    let mut codes = Vec::new();
+    for i in 0..(RING_SIZE as i32 + 5) {
+        let code = pick_code(&codes, &mut rng);
+        repo.record(row(code.clone(), 1_000 + i)).unwrap();
+        codes.push(code);
+    }
+    let in_use = repo.codes_in_use(user.id, 0).unwrap();
+    assert_eq!(in_use.len(), RING_SIZE as usize);
+    assert!(repo.find_by_code(user.id, &codes[0], 0).unwrap().is_none());
+
+    // Reusing a code replaces the old row instead of shadowing it.
+    let newest = codes.last().unwrap().clone();
+    repo.record(row(newest.clone(), 2_000)).unwrap();
+    let found = repo.find_by_code(user.id, &newest, 0).unwrap().unwrap();
+    assert_eq!(
+        decrypt(&found.encrypted_summary.unwrap()).unwrap(),
+        "at 2000"
+    );
+    assert_eq!(
+        repo.codes_in_use(user.id, 0)
+            .unwrap()
+            .iter()
+            .filter(|c| **c == newest)
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn coded_reply_to_a_plain_notification_reaches_the_agent_with_context() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    record(
+        &state,
+        user.id,
+        "C4",
+        &ReplyTarget::Notification,
+        "Package from DHL arrives tomorrow 9-12.",
+    );
+    // The quoted text is stored encrypted.
+    let stored = ReplyContextsRepository::new(state.pg_pool.clone())
+        .find_by_code(user.id, "C4", 0)
+        .unwrap()
+        .unwrap();
+    assert!(!stored.encrypted_summary.unwrap().contains("DHL"));
+
+    assert_eq!(
+        try_resolve(&state, &user, "c4 can they leave it at the door?").await,
+        Some(ReplyResolution::Agent(
+            "[Replying to #C4: \"Package from DHL arrives tomorrow 9-12.\"] can they leave it at the door?"
+                .to_string()
+        ))
+    );
+    // A code that was never sent is just text.
+    assert_eq!(try_resolve(&state, &user, "D5 yes").await, None);
+}
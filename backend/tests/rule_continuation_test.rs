@@ -0,0 +1,70 @@
+//! Continuations of delayed rule flows are claimed at most once and pruned
+//! once they are finished.
+
+use backend::models::ontology_models::{NewOntRule, NewOntRuleContinuation};
+use backend::test_utils::{create_test_state, create_test_user, TestUserParams};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn continuations_are_claimed_once_and_pruned_when_finished() {
+    let state = create_test_state();
+    let user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    let repo = &state.ontology_repository;
+    let rule = repo
+        .create_rule(&NewOntRule {
+            user_id: user.id,
+            name: "Remind me later".to_string(),
+            trigger_type: "schedule".to_string(),
+            trigger_config: "{}".to_string(),
+            logic_type: "always".to_string(),
+            logic_prompt: None,
+            logic_fetch: None,
+            action_type: "notify".to_string(),
+            action_config: "{}".to_string(),
+            status: "active".to_string(),
+            next_fire_at: None,
+            expires_at: None,
+            created_at: 1_000,
+            updated_at: 1_000,
+            flow_config: None,
+        })
+        .unwrap();
+    let continuation = |resume_at: i32| NewOntRuleContinuation {
+        rule_id: rule.id,
+        user_id: user.id,
+        flow_node: "{}".to_string(),
+        flow_context: "{}".to_string(),
+        resume_at,
+        status: "pending".to_string(),
+        created_at: 1_000,
+        updated_at: 1_000,
+    };
+    let due = repo.create_rule_continuation(&continuation(2_000)).unwrap();
+    let later = repo.create_rule_continuation(&continuation(9_000)).unwrap();
+
+    let claimed = repo.claim_due_rule_continuations(2_000).unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].id, due.id);
+    // A running row is never handed out again, however long it has run.
+    let claimed = repo.claim_due_rule_continuations(100_000).unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].id, later.id);
+    assert!(repo
+        .claim_due_rule_continuations(200_000)
+        .unwrap()
+        .is_empty());
+
+    assert!(repo.finish_rule_continuation(due.id, "done").unwrap());
+    // Finishing twice is a no-op.
+    assert!(!repo.finish_rule_continuation(due.id, "done").unwrap());
+
+    // Pending rows are kept; finished and abandoned ones go.
+    let pending = repo
+        .create_rule_continuation(&continuation(i32::MAX))
+        .unwrap();
+    assert_eq!(repo.prune_rule_continuations(i32::MAX).unwrap(), 2);
+    let claimed = repo.claim_due_rule_continuations(i32::MAX).unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].id, pending.id);
+}
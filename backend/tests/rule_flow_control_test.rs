@@ -0,0 +1,225 @@
+//! Tests for the control-flow nodes (sequence, parallel, delay, for_each):
+//! parsing, structural validation and how a delay folds the rest of a
+//! sequence into its continuation.
+
+use backend::proactive::rules::{FetchSource, FlowContext, FlowNode, Suspension};
+
+fn parse(json: serde_json::Value) -> FlowNode {
+    serde_json::from_value(json).expect("flow should parse")
+}
+
+fn notify(message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "action",
+        "action_type": "notify",
+        "config": { "method": "sms", "message": message }
+    })
+}
+
+fn keyword(word: &str, then: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "type": "keyword_condition",
+        "keyword": word,
+        "true_branch": then,
+        "false_branch": null
+    })
+}
+
+fn action_message(node: &FlowNode) -> Option<&str> {
+    match node {
+        FlowNode::Action { config, .. } => config.get("message").and_then(|v| v.as_str()),
+        _ => None,
+    }
+}
+
+#[test]
+fn control_nodes_parse_with_defaults() {
+    let node = parse(serde_json::json!({
+        "type": "sequence",
+        "steps": [
+            notify("first"),
+            { "type": "delay", "seconds": 3600, "next": notify("later") },
+            { "type": "parallel", "branches": [notify("a"), notify("b")] },
+            { "type": "for_each", "source": { "type": "events" }, "body": notify("item") }
+        ]
+    }));
+    let FlowNode::Sequence { steps } = &node else {
+        panic!("expected sequence");
+    };
+    assert_eq!(steps.len(), 4);
+    match &steps[3] {
+        FlowNode::ForEach { source, limit, .. } => {
+            assert_eq!(*source, FetchSource::Events);
+            assert_eq!(*limit, 10);
+        }
+        other => panic!("expected for_each, got {:?}", other),
+    }
+    assert_eq!(node.node_count(), 9);
+    assert!(node.validate().is_ok());
+
+    // A delay without `next` is allowed and simply ends the branch.
+    let bare = parse(serde_json::json!({ "type": "delay", "seconds": 60 }));
+    assert!(bare.validate().is_ok());
+}
+
+#[test]
+fn condition_depth_looks_through_control_nodes() {
+    let nested = keyword("a", keyword("b", notify("x")));
+    let node = parse(serde_json::json!({
+        "type": "sequence",
+        "steps": [
+            notify("first"),
+            { "type": "delay", "seconds": 600, "next": {
+                "type": "for_each",
+                "source": { "type": "email" },
+                "body": nested
+            }}
+        ]
+    }));
+    assert_eq!(node.condition_depth(), 2);
+
+    let too_deep = parse(serde_json::json!({
+        "type": "parallel",
+        "branches": [
+            notify("x"),
+            keyword("a", keyword("b", keyword("c", keyword("d", notify("x")))))
+        ]
+    }));
+    assert_eq!(too_deep.condition_depth(), 4);
+    assert!(too_deep.validate().unwrap_err().contains("max depth"));
+}
+
+#[test]
+fn validate_rejects_bad_control_nodes() {
+    let cases = [
+        (
+            serde_json::json!({ "type": "sequence", "steps": [] }),
+            "at least one step",
+        ),
+        (
+            serde_json::json!({ "type": "parallel", "branches": [] }),
+            "at least one branch",
+        ),
+        (
+            serde_json::json!({ "type": "delay", "seconds": 5, "next": notify("x") }),
+            "Delay must be",
+        ),
+        (
+            serde_json::json!({ "type": "delay", "seconds": 90 * 86400, "next": notify("x") }),
+            "Delay must be",
+        ),
+        (
+            serde_json::json!({
+                "type": "for_each",
+                "source": { "type": "events" },
+                "limit": 500,
+                "body": notify("x")
+            }),
+            "limit must be",
+        ),
+        (
+            serde_json::json!({
+                "type": "for_each",
+                "source": { "type": "events" },
+                "body": {
+                    "type": "sequence",
+                    "steps": [{
+                        "type": "for_each",
+                        "source": { "type": "email" },
+                        "body": notify("x")
+                    }]
+                }
+            }),
+            "cannot be nested",
+        ),
+    ];
+    for (json, expected) in cases {
+        let err = parse(json.clone()).validate().unwrap_err();
+        assert!(err.contains(expected), "{} -> {}", json, err);
+    }
+
+    let wide = serde_json::json!({
+        "type": "parallel",
+        "branches": (0..40).map(|i| notify(&i.to_string())).collect::<Vec<_>>()
+    });
+    assert!(parse(wide).validate().unwrap_err().contains("max of"));
+}
+
+#[test]
+fn delay_in_sequence_carries_remaining_steps() {
+    let steps: Vec<FlowNode> = ["b", "c"].iter().map(|m| parse(notify(m))).collect();
+    let suspension = Suspension {
+        resume_at: 1_000,
+        node: Some(parse(notify("a"))),
+        context: FlowContext::new("ctx", None),
+    }
+    .then_run(&steps);
+    let Some(FlowNode::Sequence { steps: remainder }) = &suspension.node else {
+        panic!("expected the remainder to be a sequence");
+    };
+    let messages: Vec<_> = remainder.iter().filter_map(action_message).collect();
+    assert_eq!(messages, ["a", "b", "c"]);
+    assert_eq!(suspension.resume_at, 1_000);
+
+    // A trailing delay with nothing after it resumes straight into the rest
+    // of the sequence, without wrapping a single step.
+    let suspension = Suspension {
+        resume_at: 1_000,
+        node: None,
+        context: FlowContext::new("ctx", None),
+    }
+    .then_run(&steps[1..]);
+    assert_eq!(suspension.node.as_ref().and_then(action_message), Some("c"));
+
+    let untouched = Suspension {
+        resume_at: 1_000,
+        node: None,
+        context: FlowContext::new("ctx", None),
+    }
+    .then_run(&[]);
+    assert!(untouched.node.is_none());
+}
+
+#[test]
+fn flow_context_round_trips_and_exposes_loop_item() {
+    let snapshot = serde_json::json!({ "sender_name": "Alice", "content": "hi" });
+    let mut ctx = FlowContext::new("Message from Alice: hi", Some(&snapshot));
+    assert_eq!(ctx.condition_input(), "Message from Alice: hi");
+
+    ctx.prev_message = Some("LLM says hi".to_string());
+    ctx.item = Some("[event_id=4] [status=active] Pay rent".to_string());
+    assert_eq!(
+        ctx.condition_input(),
+        "Message from Alice: hi\n\nCurrent item:\n[event_id=4] [status=active] Pay rent"
+    );
+
+    let stored = serde_json::to_string(&ctx).unwrap();
+    let restored: FlowContext = serde_json::from_str(&stored).unwrap();
+    assert_eq!(restored.trigger_snapshot, Some(snapshot));
+    assert_eq!(restored.prev_message.as_deref(), Some("LLM says hi"));
+    assert_eq!(restored.item, ctx.item);
+
+    // Older payloads without the optional fields still load.
+    let minimal: FlowContext =
+        serde_json::from_str(r#"{"trigger_context":"Schedule trigger fired"}"#).unwrap();
+    assert!(minimal.item.is_none() && minimal.prev_extras.is_none());
+}
+
+#[test]
+fn for_each_accepts_mcp_resource_source() {
+    let node = parse(serde_json::json!({
+        "type": "for_each",
+        "source": { "type": "mcp_resource", "server": "notes", "uri": "memo://list" },
+        "body": notify("item")
+    }));
+    let FlowNode::ForEach { source, .. } = &node else {
+        panic!("expected for_each");
+    };
+    assert_eq!(
+        *source,
+        FetchSource::McpResource {
+            server: "notes".into(),
+            uri: "memo://list".into()
+        }
+    );
+}
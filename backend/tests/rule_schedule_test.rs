@@ -0,0 +1,193 @@
+//! Tests for schedule-trigger patterns: legacy shorthand, RFC 5545 RRULEs
+//! and 5-field cron, evaluated in the user's timezone across DST changes.
+
+use backend::proactive::rules::compute_next_fire_at;
+use backend::proactive::schedule::{pin_schedule_anchor, SchedulePattern};
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+}
+
+fn next(pattern: &str, tz: Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    SchedulePattern::parse(pattern)
+        .unwrap()
+        .next_after(after, tz)
+}
+
+fn upcoming(pattern: &str, tz: Tz, after: DateTime<Utc>, n: usize) -> Vec<DateTime<Utc>> {
+    SchedulePattern::parse(pattern)
+        .unwrap()
+        .upcoming(after, tz, n)
+}
+
+#[test]
+fn legacy_daily_uses_user_timezone() {
+    let tz: Tz = "Europe/Helsinki".parse().unwrap();
+    assert_eq!(
+        next("daily 09:00", tz, utc(2026, 3, 10, 10, 0)),
+        Some(utc(2026, 3, 11, 7, 0))
+    );
+}
+
+#[test]
+fn legacy_weekdays_and_weekly_still_parse() {
+    // 2026-01-09 is a Friday.
+    assert_eq!(
+        next("weekdays 08:30", chrono_tz::UTC, utc(2026, 1, 9, 9, 0)),
+        Some(utc(2026, 1, 12, 8, 30))
+    );
+    assert_eq!(
+        next("weekly friday 18:00", chrono_tz::UTC, utc(2026, 1, 5, 0, 0)),
+        Some(utc(2026, 1, 9, 18, 0))
+    );
+    assert!(compute_next_fire_at("hourly", "UTC").is_some());
+}
+
+#[test]
+fn rrule_first_monday_of_month() {
+    assert_eq!(
+        next(
+            "RRULE:FREQ=MONTHLY;BYDAY=1MO;BYHOUR=9;BYMINUTE=0",
+            chrono_tz::UTC,
+            utc(2026, 1, 10, 0, 0)
+        ),
+        Some(utc(2026, 2, 2, 9, 0))
+    );
+}
+
+#[test]
+fn rrule_last_business_day_via_bysetpos() {
+    assert_eq!(
+        upcoming(
+            "RRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;BYHOUR=17;BYMINUTE=0",
+            chrono_tz::UTC,
+            utc(2026, 1, 1, 0, 0),
+            2
+        ),
+        vec![utc(2026, 1, 30, 17, 0), utc(2026, 2, 27, 17, 0)]
+    );
+}
+
+#[test]
+fn rrule_interval_counts_from_dtstart() {
+    assert_eq!(
+        upcoming(
+            "DTSTART:20260105T080000 RRULE:FREQ=WEEKLY;INTERVAL=2",
+            chrono_tz::UTC,
+            utc(2026, 1, 6, 0, 0),
+            2
+        ),
+        vec![utc(2026, 1, 19, 8, 0), utc(2026, 2, 2, 8, 0)]
+    );
+}
+
+#[test]
+fn rrule_count_until_and_exdate_end_or_skip_occurrences() {
+    assert_eq!(
+        upcoming(
+            "DTSTART:20260105T080000\nRRULE:FREQ=DAILY;COUNT=3",
+            chrono_tz::UTC,
+            utc(2026, 1, 6, 9, 0),
+            5
+        ),
+        vec![utc(2026, 1, 7, 8, 0)]
+    );
+    assert_eq!(
+        next(
+            "DTSTART:20260105T080000 RRULE:FREQ=DAILY EXDATE:20260106T080000",
+            chrono_tz::UTC,
+            utc(2026, 1, 5, 9, 0)
+        ),
+        Some(utc(2026, 1, 7, 8, 0))
+    );
+    assert_eq!(
+        next(
+            "RRULE:FREQ=DAILY;BYHOUR=9;BYMINUTE=0;UNTIL=20260110T000000Z",
+            chrono_tz::UTC,
+            utc(2026, 1, 10, 0, 0)
+        ),
+        None
+    );
+}
+
+#[test]
+fn cron_steps_ranges_and_weekdays() {
+    let pattern = "*/15 9-16 * * 1-5";
+    assert_eq!(
+        next(pattern, chrono_tz::UTC, utc(2026, 1, 9, 10, 5)),
+        Some(utc(2026, 1, 9, 10, 15))
+    );
+    // Friday after the window closes rolls over to Monday morning.
+    assert_eq!(
+        next(pattern, chrono_tz::UTC, utc(2026, 1, 9, 16, 50)),
+        Some(utc(2026, 1, 12, 9, 0))
+    );
+}
+
+#[test]
+fn cron_restricted_day_fields_are_ored() {
+    // Day 1 of the month OR any Monday.
+    assert_eq!(
+        next("0 9 1 * MON", chrono_tz::UTC, utc(2026, 1, 1, 10, 0)),
+        Some(utc(2026, 1, 5, 9, 0))
+    );
+}
+
+#[test]
+fn spring_forward_gap_shifts_forward() {
+    // Helsinki skips 03:00-04:00 local on 2026-03-29.
+    let tz: Tz = "Europe/Helsinki".parse().unwrap();
+    assert_eq!(
+        next("daily 03:30", tz, utc(2026, 3, 28, 12, 0)),
+        Some(utc(2026, 3, 29, 1, 30))
+    );
+}
+
+#[test]
+fn fall_back_ambiguity_fires_once() {
+    // Helsinki repeats 03:00-04:00 local on 2026-10-25.
+    let tz: Tz = "Europe/Helsinki".parse().unwrap();
+    assert_eq!(
+        upcoming("30 3 * * *", tz, utc(2026, 10, 24, 12, 0), 2),
+        vec![utc(2026, 10, 25, 0, 30), utc(2026, 10, 26, 1, 30)]
+    );
+}
+
+#[test]
+fn invalid_patterns_are_rejected_with_a_reason() {
+    for pattern in [
+        "",
+        "every tuesday",
+        "61 * * * *",
+        "RRULE:FREQ=WEEKLY;BYDAY=1MO",
+        "RRULE:FREQ=DAILY;COUNT=2;UNTIL=20260101",
+        "RRULE:BYDAY=MO",
+        "daily 25:00",
+    ] {
+        let err = SchedulePattern::parse(pattern).unwrap_err();
+        assert!(
+            !err.is_empty(),
+            "pattern {:?} should explain the error",
+            pattern
+        );
+    }
+}
+
+#[test]
+fn anchor_is_pinned_only_when_phase_matters() {
+    let now = utc(2026, 1, 5, 8, 0);
+    assert_eq!(
+        pin_schedule_anchor("RRULE:FREQ=WEEKLY;INTERVAL=2", "UTC", now),
+        "DTSTART:20260105T080000 RRULE:FREQ=WEEKLY;INTERVAL=2"
+    );
+    assert_eq!(
+        pin_schedule_anchor("RRULE:FREQ=WEEKLY;BYDAY=MO", "UTC", now),
+        "RRULE:FREQ=WEEKLY;BYDAY=MO"
+    );
+    assert_eq!(
+        pin_schedule_anchor("daily 09:00", "UTC", now),
+        "daily 09:00"
+    );
+}
@@ -0,0 +1,219 @@
+//! Tests for structured (non-LLM) condition nodes: predicate evaluation
+//! over the trigger snapshot, local time and numeric readings, save-time
+//! validation, and the weather reading they compare against.
+
+use std::collections::HashMap;
+
+use backend::proactive::conditions::{MetricKey, NumericMetric, Predicate, PredicateInput};
+use backend::proactive::rules::FlowNode;
+use backend::utils::tool_exec::current_temperature;
+use chrono::TimeZone;
+use chrono_tz::Tz;
+
+fn predicate(json: serde_json::Value) -> Predicate {
+    serde_json::from_value(json).expect("predicate should parse")
+}
+
+fn snapshot() -> serde_json::Value {
+    serde_json::json!({
+        "sender_name": "Mom",
+        "person_name": "Anna Virtanen",
+        "person_id": 7,
+        "room_id": "!abc:server",
+        "platform": "whatsapp",
+        "is_group": false,
+        "content": "Can you pick up the kids at 5?"
+    })
+}
+
+/// 2026-03-04 is a Wednesday.
+fn helsinki(hour: u32, minute: u32) -> chrono::DateTime<Tz> {
+    chrono_tz::Europe::Helsinki
+        .with_ymd_and_hms(2026, 3, 4, hour, minute, 0)
+        .unwrap()
+}
+
+fn eval_at(
+    p: &Predicate,
+    now: chrono::DateTime<Tz>,
+    metrics: &HashMap<MetricKey, Option<f64>>,
+) -> Result<bool, MetricKey> {
+    let snap = snapshot();
+    p.evaluate(&PredicateInput {
+        snapshot: Some(&snap),
+        body: "Can you pick up the kids at 5?",
+        now,
+        metrics,
+    })
+}
+
+fn eval(p: &Predicate) -> bool {
+    eval_at(p, helsinki(12, 0), &HashMap::new()).expect("no readings needed")
+}
+
+#[test]
+fn snapshot_fields_match() {
+    assert!(eval(&predicate(
+        serde_json::json!({ "type": "sender", "person_id": 7 })
+    )));
+    assert!(eval(&predicate(
+        serde_json::json!({ "type": "sender", "name": "anna virtanen" })
+    )));
+    assert!(!eval(&predicate(
+        serde_json::json!({ "type": "sender", "person_id": 7, "room_id": "!other:server" })
+    )));
+    assert!(eval(&predicate(
+        serde_json::json!({ "type": "platform", "platforms": ["telegram", "WhatsApp"] })
+    )));
+    assert!(eval(&predicate(
+        serde_json::json!({ "type": "is_group", "value": false })
+    )));
+    assert!(eval(&predicate(
+        serde_json::json!({ "type": "regex", "pattern": "(?i)pick up" })
+    )));
+    assert!(eval(&predicate(
+        serde_json::json!({ "type": "regex", "pattern": "^Mom$", "field": "sender_name" })
+    )));
+}
+
+#[test]
+fn time_window_and_day_use_local_clock() {
+    let evening = predicate(serde_json::json!({
+        "type": "time_window", "start": "18:00", "end": "23:00"
+    }));
+    let none = HashMap::new();
+    assert_eq!(eval_at(&evening, helsinki(18, 0), &none), Ok(true));
+    assert_eq!(eval_at(&evening, helsinki(23, 0), &none), Ok(false));
+    assert_eq!(eval_at(&evening, helsinki(17, 59), &none), Ok(false));
+
+    let night = predicate(serde_json::json!({
+        "type": "time_window", "start": "22:00", "end": "06:00"
+    }));
+    assert_eq!(eval_at(&night, helsinki(23, 30), &none), Ok(true));
+    assert_eq!(eval_at(&night, helsinki(5, 59), &none), Ok(true));
+    assert_eq!(eval_at(&night, helsinki(12, 0), &none), Ok(false));
+
+    let weekdays = predicate(serde_json::json!({ "type": "day_of_week", "days": ["weekdays"] }));
+    let weekend =
+        predicate(serde_json::json!({ "type": "day_of_week", "days": ["sat", "Sunday"] }));
+    assert!(eval(&weekdays));
+    assert!(!eval(&weekend));
+}
+
+#[test]
+fn combinators_and_numeric_metrics() {
+    // "Only on weekdays after 18:00 and only if the battery is below 30%"
+    let p = predicate(serde_json::json!({
+        "type": "and",
+        "conditions": [
+            { "type": "day_of_week", "days": ["weekdays"] },
+            { "type": "time_window", "start": "18:00", "end": "00:00" },
+            { "type": "numeric", "metric": "tesla_battery", "op": "lt", "value": 30 }
+        ]
+    }));
+    let battery = (NumericMetric::TeslaBattery, String::new());
+
+    // A failed day or time check settles it before the battery is read.
+    assert_eq!(eval_at(&p, helsinki(9, 0), &HashMap::new()), Ok(false));
+    // Past 18:00 the battery reading is asked for.
+    assert_eq!(
+        eval_at(&p, helsinki(19, 15), &HashMap::new()),
+        Err(battery.clone())
+    );
+
+    let low = HashMap::from([(battery.clone(), Some(22.0))]);
+    let high = HashMap::from([(battery.clone(), Some(80.0))]);
+    assert_eq!(eval_at(&p, helsinki(19, 15), &low), Ok(true));
+    assert_eq!(eval_at(&p, helsinki(19, 15), &high), Ok(false));
+    // A reading that couldn't be fetched makes the comparison false.
+    let unavailable = HashMap::from([(battery, None)]);
+    assert_eq!(eval_at(&p, helsinki(19, 15), &unavailable), Ok(false));
+
+    let not_group_or_cold = predicate(serde_json::json!({
+        "type": "or",
+        "conditions": [
+            { "type": "not", "condition": { "type": "is_group", "value": false } },
+            { "type": "numeric", "metric": "weather_temperature", "op": "le", "value": 0, "location": " Oulu " }
+        ]
+    }));
+    let oulu = (NumericMetric::WeatherTemperature, "Oulu".to_string());
+    assert_eq!(
+        eval_at(&not_group_or_cold, helsinki(12, 0), &HashMap::new()),
+        Err(oulu.clone())
+    );
+    let cold = HashMap::from([(oulu, Some(-3.0))]);
+    assert_eq!(
+        eval_at(&not_group_or_cold, helsinki(12, 0), &cold),
+        Ok(true)
+    );
+}
+
+#[test]
+fn validation_rejects_bad_predicates() {
+    let cases = [
+        (
+            serde_json::json!({ "type": "sender" }),
+            "Sender condition needs a person, room or name",
+        ),
+        (
+            serde_json::json!({ "type": "sender", "name": "  " }),
+            "Sender condition needs a person, room or name",
+        ),
+        (
+            serde_json::json!({ "type": "regex", "pattern": "" }),
+            "Regex pattern cannot be empty",
+        ),
+        (
+            serde_json::json!({ "type": "time_window", "start": "8am", "end": "17:00" }),
+            "Time window must use HH:MM",
+        ),
+        (
+            serde_json::json!({ "type": "time_window", "start": "08:00", "end": "08:00" }),
+            "Time window start and end must differ",
+        ),
+        (
+            serde_json::json!({ "type": "day_of_week", "days": ["someday"] }),
+            "Unknown day 'someday'",
+        ),
+        (
+            serde_json::json!({ "type": "and", "conditions": [] }),
+            "AND/OR needs at least one condition",
+        ),
+    ];
+    for (json, expected) in cases {
+        assert_eq!(predicate(json).validate(), Err(expected.to_string()));
+    }
+    let bad_regex = predicate(serde_json::json!({ "type": "regex", "pattern": "(unclosed" }));
+    assert!(bad_regex
+        .validate()
+        .unwrap_err()
+        .starts_with("Invalid regex"));
+
+    // Validation runs through the flow tree on save.
+    let flow: FlowNode = serde_json::from_value(serde_json::json!({
+        "type": "structured_condition",
+        "condition": { "type": "platform", "platforms": [] },
+        "true_branch": null,
+        "false_branch": null
+    }))
+    .unwrap();
+    assert_eq!(
+        flow.validate(),
+        Err("Platform condition needs at least one platform".to_string())
+    );
+    assert_eq!(flow.condition_depth(), 1);
+}
+
+#[test]
+fn temperature_is_read_from_the_forecast_data() {
+    let forecast = serde_json::json!({
+        "currently": { "temperature": -3.4, "summary": "Light Rain" },
+        "timezone": "Europe/Helsinki",
+    });
+    assert_eq!(current_temperature(&forecast), Some(-3.4));
+    assert_eq!(
+        current_temperature(&serde_json::json!({ "currently": {} })),
+        None
+    );
+    assert_eq!(current_temperature(&serde_json::json!({})), None);
+}
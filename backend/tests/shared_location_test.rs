@@ -0,0 +1,92 @@
+use backend::services::shared_location::{
+    distance_km, history_text, location_label, parse_geo_uri, Gazetteer, SharedLocation,
+};
+
+const GEONAMES: &str = "658225\tHelsinki\tHelsinki\t\t60.16952\t24.93545\tP\tPPLC\tFI\t\t01\t091\t\t\t558457\t\t26\tEurope/Helsinki\t2019-09-05
+660129\tEspoo\tEspoo\t\t60.2052\t24.6522\tP\tPPLA3\tFI\t\t18\t049\t\t\t256760\t\t23\tEurope/Helsinki\t2019-09-05
+634963\tTampere\tTampere\t\t61.49911\t23.78712\tP\tPPLA\tFI\t\t06\t837\t\t\t206000\t\t112\tEurope/Helsinki\t2019-09-05
+not a geonames row";
+
+#[test]
+fn geo_uris_parse_with_and_without_parameters() {
+    assert_eq!(
+        parse_geo_uri("geo:60.18402,24.922"),
+        Some((60.18402, 24.922))
+    );
+    assert_eq!(
+        parse_geo_uri(" GEO:-33.8568,151.2153,12;u=35 "),
+        Some((-33.8568, 151.2153))
+    );
+    assert_eq!(parse_geo_uri("geo:91.0,24.9"), None);
+    assert_eq!(parse_geo_uri("https://maps.google.com/?q=60.1,24.9"), None);
+    assert_eq!(parse_geo_uri(""), None);
+}
+
+#[test]
+fn labels_keep_only_the_human_part_of_the_body() {
+    // mautrix-whatsapp: name, address, maps link.
+    assert_eq!(
+        location_label(
+            "Location: Café Regatta\nMerikannontie 8, Helsinki\nhttps://maps.google.com/?q=60.18402,24.92200"
+        ),
+        Some("Café Regatta, Merikannontie 8, Helsinki".to_string())
+    );
+    // An unnamed pin only carries coordinates.
+    assert_eq!(
+        location_label(
+            "Location: 60.1840° N 24.9220° E\n\nhttps://maps.google.com/?q=60.18402,24.92200"
+        ),
+        None
+    );
+    assert_eq!(location_label("Location"), None);
+    assert_eq!(location_label("geo:60.18402,24.92200"), None);
+}
+
+#[test]
+fn gazetteer_names_the_nearest_place_within_range() {
+    let gazetteer = Gazetteer::from_geonames_tsv(GEONAMES);
+    assert_eq!(gazetteer.len(), 3);
+    assert_eq!(
+        gazetteer.describe(60.1700, 24.9400),
+        Some("Helsinki, FI".to_string())
+    );
+    assert_eq!(
+        gazetteer.describe(60.2200, 24.7500),
+        Some("near Espoo, FI (6 km)".to_string())
+    );
+    // Middle of the Baltic.
+    assert_eq!(gazetteer.describe(58.5, 20.0), None);
+    assert!((distance_km((60.16952, 24.93545), (61.49911, 23.78712)) - 160.0).abs() < 5.0);
+}
+
+#[test]
+fn content_reads_like_a_message_and_keeps_coordinates() {
+    let mut location = SharedLocation::from_event(
+        "Location: Café Regatta\nhttps://maps.google.com/?q=60.18402,24.92200",
+        "geo:60.18402,24.922",
+    )
+    .unwrap();
+    assert_eq!(location.coordinates(), "60.18402,24.92200");
+    assert_eq!(
+        location.content(),
+        "[Location] Café Regatta (60.18402,24.92200)"
+    );
+
+    location.place = Some("Helsinki, FI".to_string());
+    assert_eq!(
+        location.content(),
+        "[Location] Café Regatta - Helsinki, FI (60.18402,24.92200)"
+    );
+    // A label that already names the place doesn't repeat it.
+    location.label = Some("Kauppatori, Helsinki".to_string());
+    assert_eq!(
+        location.content(),
+        "[Location] Kauppatori, Helsinki (60.18402,24.92200)"
+    );
+
+    assert_eq!(
+        history_text("Location", "geo:60.18402,24.922"),
+        "📍 [Location] 60.18402,24.92200"
+    );
+    assert_eq!(history_text("Location", "not-a-uri"), "📍 LOCATION");
+}
@@ -267,6 +267,7 @@ async fn test_process_sms_us_user_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // Use mock LLM response
@@ -299,6 +300,7 @@ async fn test_process_sms_finland_user_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Sää on aurinkoinen");
@@ -329,6 +331,7 @@ async fn test_process_sms_uk_user_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Cloudy with a chance of rain");
@@ -359,6 +362,7 @@ async fn test_process_sms_germany_user_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Es ist sonnig");
@@ -390,6 +394,7 @@ async fn test_process_sms_credits_fallback_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Hello there!");
@@ -425,6 +430,7 @@ async fn test_process_sms_rejects_user_with_no_credits() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("This should not be called");
@@ -459,6 +465,7 @@ async fn test_process_sms_unknown_phone_returns_not_found() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Should not be called");
@@ -487,6 +494,7 @@ async fn test_process_sms_phone_service_deactivated() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Should not be called");
@@ -523,6 +531,7 @@ async fn test_process_sms_cancel_message() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Should not be called");
@@ -557,6 +566,7 @@ async fn test_process_sms_byot_user_skips_credit_check() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Hello BYOT user!");
@@ -592,6 +602,7 @@ async fn test_process_sms_empty_message_body() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("I received an empty message");
@@ -622,6 +633,7 @@ async fn test_process_sms_whitespace_only_message() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("I received whitespace");
@@ -647,6 +659,7 @@ async fn test_process_sms_very_long_message() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Received your long message");
@@ -672,6 +685,7 @@ async fn test_process_sms_unicode_emoji_message() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Hello! 你好!");
@@ -697,6 +711,7 @@ async fn test_process_sms_uppercase_cancel() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Should not be called");
@@ -731,6 +746,7 @@ async fn test_process_sms_low_credits_still_processes() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Hello!");
@@ -761,6 +777,7 @@ async fn test_process_sms_fractional_credits_accepted() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Hello there!");
@@ -792,6 +809,7 @@ async fn test_process_sms_negative_credits_rejected() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Should not be called");
@@ -822,6 +840,7 @@ async fn test_process_sms_with_image_attachment() {
         num_media: Some("1".to_string()),
         media_url0: Some("https://api.twilio.com/test/Media/ME123".to_string()),
         media_content_type0: Some("image/jpeg".to_string()),
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("I see an image");
@@ -843,7 +862,7 @@ async fn test_process_sms_with_multiple_attachments() {
     let params = TestUserParams::us_user(10.0, 5.0);
     let user = create_test_user(&state, &params);
 
-    // num_media = "2" but we only have media_url0 (first attachment)
+    // num_media = "2" but MediaUrl1 is missing, so only the first is picked up
     let payload = TwilioWebhookPayload {
         from: user.phone_number.clone(),
         to: "+18005551234".to_string(),
@@ -852,6 +871,7 @@ async fn test_process_sms_with_multiple_attachments() {
         num_media: Some("2".to_string()),
         media_url0: Some("https://api.twilio.com/test/Media/ME123".to_string()),
         media_content_type0: Some("image/png".to_string()),
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("I see the first image");
@@ -859,7 +879,7 @@ async fn test_process_sms_with_multiple_attachments() {
 
     let (status, _headers, _response) = process_sms(&state, payload, options).await;
 
-    // Should process successfully (only first media is handled)
+    // Should process successfully (the missing attachment is skipped)
     assert_eq!(status, StatusCode::OK);
 }
 
@@ -882,6 +902,7 @@ async fn test_process_sms_long_llm_response() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // LLM returns a 1000+ character response
@@ -909,6 +930,7 @@ async fn test_process_sms_empty_llm_response() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // LLM returns empty response (no tool calls, empty content)
@@ -937,6 +959,7 @@ async fn test_process_sms_invalid_tool_call() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // LLM returns invalid/malformed tool call
@@ -970,6 +993,7 @@ async fn test_process_sms_user_with_no_sub_tier() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Should not be called");
@@ -1002,6 +1026,7 @@ async fn test_process_sms_user_with_invalid_sub_tier() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Should not be called");
@@ -1033,6 +1058,7 @@ async fn test_process_sms_missing_function_name_returns_internal_error() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // LLM returns tool call with missing function name
@@ -1069,6 +1095,7 @@ async fn test_process_sms_missing_arguments_returns_internal_error() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // LLM returns tool call with missing arguments
@@ -1101,6 +1128,7 @@ async fn test_process_sms_malformed_json_continues_gracefully() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // LLM returns tool call with malformed JSON arguments (use terminal tool
@@ -1135,6 +1163,7 @@ async fn test_process_sms_error_message_does_not_leak_content() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // Trigger error with missing function name
@@ -1180,6 +1209,7 @@ async fn test_process_sms_terminal_tool_response_is_final_response() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mut options =
@@ -1211,6 +1241,7 @@ async fn test_process_sms_preserves_media_results_from_tool_answers() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let tool_answer = concat!(
@@ -1251,6 +1282,7 @@ async fn test_process_sms_web_chat_streaming_emits_tool_call_status() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let (tx, mut rx) = tokio::sync::mpsc::channel(4);
@@ -1291,6 +1323,7 @@ async fn test_process_sms_persists_user_and_assistant_messages() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Got it. I will keep replies concise.");
@@ -1338,6 +1371,7 @@ async fn test_contract_response_fits_sms() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // Even with a long LLM response, the final SMS should be truncated
@@ -1366,6 +1400,7 @@ async fn test_contract_successful_message_charges_user() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("4");
@@ -1404,6 +1439,7 @@ async fn test_contract_failed_message_preserves_credits() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // Trigger a system error with missing function name
@@ -1438,6 +1474,7 @@ async fn test_contract_error_messages_protect_privacy() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     // Trigger error
@@ -1469,6 +1506,7 @@ async fn test_contract_forget_command_clears_context() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock1 = MockLlmResponse::with_direct_response("Nice to meet you Alice!");
@@ -1485,6 +1523,7 @@ async fn test_contract_forget_command_clears_context() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock2 = MockLlmResponse::with_direct_response("I don't know your name.");
@@ -1517,6 +1556,7 @@ async fn test_process_sms_canada_user_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("It's cold in Toronto");
@@ -1546,6 +1586,7 @@ async fn test_process_sms_netherlands_user_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Het is bewolkt");
@@ -1576,6 +1617,7 @@ async fn test_process_sms_australia_user_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Sunny day mate!");
@@ -1606,6 +1648,7 @@ async fn test_process_sms_france_user_full_flow() {
         num_media: None,
         media_url0: None,
         media_content_type0: None,
+        extra_params: Default::default(),
     };
 
     let mock = MockLlmResponse::with_direct_response("Il fait beau");
@@ -0,0 +1,133 @@
+use backend::repositories::user_repository::LogUsageParams;
+use backend::test_utils::{create_test_state, create_test_user, TestUserParams};
+use backend::utils::sms_encoding::{
+    gsm7_chars_for_segments, max_chars_within_segments, outbound_sms_segments, segment_count,
+    segment_info, transliterate_to_gsm7, SmsEncoding,
+};
+
+#[test]
+fn gsm7_bodies_use_160_and_153_septet_parts() {
+    assert_eq!(segment_count(""), 1);
+    assert_eq!(segment_count(&"a".repeat(160)), 1);
+    assert_eq!(segment_count(&"a".repeat(161)), 2);
+    assert_eq!(segment_count(&"a".repeat(306)), 2);
+    assert_eq!(segment_count(&"a".repeat(307)), 3);
+
+    let info = segment_info("Meet at 5? Bring £20 & the Ä-team @ Ángel's");
+    assert_eq!(info.encoding, SmsEncoding::Ucs2);
+    let info = segment_info("Meet at 5? Bring £20 & the Ä-team @ Café");
+    assert_eq!(info.encoding, SmsEncoding::Gsm7);
+    assert_eq!(info.units, 40);
+}
+
+#[test]
+fn extension_characters_cost_two_septets_and_stay_whole() {
+    let info = segment_info(&"€".repeat(80));
+    assert_eq!(
+        (info.encoding, info.units, info.segments),
+        (SmsEncoding::Gsm7, 160, 1)
+    );
+    assert_eq!(segment_count(&"€".repeat(81)), 2);
+
+    // 306 septets would fit in two parts, but the escape pair can't be
+    // split across the first boundary.
+    let body = format!("{}€{}", "a".repeat(152), "a".repeat(152));
+    assert_eq!(segment_info(&body).units, 306);
+    assert_eq!(segment_count(&body), 3);
+}
+
+#[test]
+fn one_non_gsm_character_switches_to_ucs2() {
+    assert_eq!(segment_count(&format!("{}'", "a".repeat(70))), 1);
+    assert_eq!(segment_count(&format!("{}\u{2019}", "a".repeat(69))), 1);
+    assert_eq!(segment_count(&format!("{}\u{2019}", "a".repeat(70))), 2);
+    assert_eq!(segment_count(&format!("{}\u{2019}", "a".repeat(200))), 3);
+
+    // Emoji are surrogate pairs: two units that never straddle a boundary.
+    assert_eq!(segment_count(&"😀".repeat(35)), 1);
+    let body = format!("{}😀{}", "a".repeat(66), "a".repeat(66));
+    assert_eq!(segment_info(&body).units, 134);
+    assert_eq!(segment_count(&body), 3);
+}
+
+#[test]
+fn transliteration_folds_common_offenders() {
+    assert_eq!(
+        transliterate_to_gsm7("\u{201C}Hi\u{201D} \u{2014} it\u{2019}s\u{2026} 😂👍🏽"),
+        "\"Hi\" - it's... :D+1"
+    );
+    assert_eq!(transliterate_to_gsm7("❤️ you"), "<3 you");
+    assert_eq!(
+        transliterate_to_gsm7("10\u{00A0}km \u{2022} São Paulo"),
+        "10 km - Sao Paulo"
+    );
+    assert_eq!(
+        transliterate_to_gsm7("Hyvää yötä, Åsa!"),
+        "Hyvää yötä, Åsa!"
+    );
+
+    let folded = transliterate_to_gsm7("Meeting 会议 at 3 🙂");
+    assert_eq!(folded, "Meeting 会议 at 3 :)");
+    assert_eq!(segment_info(&folded).encoding, SmsEncoding::Ucs2);
+}
+
+#[test]
+fn character_budgets_follow_the_encoding() {
+    assert_eq!(gsm7_chars_for_segments(1), 160);
+    assert_eq!(gsm7_chars_for_segments(3), 459);
+
+    let plain = "a".repeat(500);
+    assert_eq!(max_chars_within_segments(&plain, 3), 459);
+    assert_eq!(max_chars_within_segments(&plain, 1), 160);
+    assert_eq!(max_chars_within_segments(&plain, 4), 500);
+    assert_eq!(max_chars_within_segments(&"😀".repeat(100), 1), 35);
+
+    let smart = format!("{}\u{2019}", "a".repeat(499));
+    let keep = max_chars_within_segments(&smart, 3);
+    assert_eq!(keep, 201);
+    let cut: String = smart.chars().take(keep).collect();
+    assert!(segment_count(&cut) <= 3);
+}
+
+#[test]
+#[serial_test::serial]
+fn sent_segments_are_recorded_per_message() {
+    let state = create_test_state();
+    let mut user = create_test_user(&state, &TestUserParams::us_user(10.0, 5.0));
+    let body = format!("Done \u{2014} {}", "x".repeat(100));
+    assert_eq!(outbound_sms_segments(&user, &body), 2);
+    user.sms_gsm7_transliteration = true;
+    let segments = outbound_sms_segments(&user, &body);
+    assert_eq!(segments, 1);
+
+    state
+        .user_repository
+        .log_usage(LogUsageParams {
+            user_id: user.id,
+            sid: Some("SMsegments".to_string()),
+            activity_type: "sms".to_string(),
+            credits: None,
+            time_consumed: None,
+            success: None,
+            reason: None,
+            status: None,
+            recharge_threshold_timestamp: None,
+            zero_credits_timestamp: None,
+            segments: Some(segments),
+        })
+        .unwrap();
+    assert_eq!(
+        state
+            .user_repository
+            .get_usage_log_segments("SMsegments")
+            .unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        state
+            .user_repository
+            .get_usage_log_segments("SMunknown")
+            .unwrap(),
+        None
+    );
+}
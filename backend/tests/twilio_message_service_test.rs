@@ -443,6 +443,32 @@ async fn test_delete_message_media_success() {
     );
 }
 
+#[tokio::test]
+#[serial]
+async fn test_fetch_message_media_returns_bytes() {
+    setup_test_env();
+    let state = create_test_state();
+    let params = TestUserParams::us_user(10.0, 5.0);
+    let user = create_test_user(&state, &params);
+
+    let mock_client =
+        Arc::new(MockTwilioClient::new().with_fetch_media_result(Ok(vec![0xff, 0xd8, 0xff])));
+    let service = TwilioMessageService::new(
+        mock_client.clone(),
+        state.pg_pool.clone(),
+        state.user_core.clone(),
+        state.user_repository.clone(),
+    );
+
+    let url = "https://api.twilio.com/2010-04-01/Accounts/AC1/Messages/MM1/Media/ME1";
+    let bytes = service.fetch_message_media(&user, url).await.unwrap();
+    assert_eq!(bytes, vec![0xff, 0xd8, 0xff]);
+    assert_eq!(
+        mock_client.get_calls().fetch_media_calls,
+        vec![url.to_string()]
+    );
+}
+
 // =========================================================================
 // Fetch Message Price Tests
 // =========================================================================
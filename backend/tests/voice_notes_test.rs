@@ -0,0 +1,82 @@
+use backend::services::voice_notes::{
+    check_declared_size, clean_transcript, detect_audio_format, voice_note_content, AudioFormat,
+    VoiceNoteError, MAX_AUDIO_BYTES,
+};
+
+#[test]
+fn magic_bytes_win_over_the_declared_mimetype() {
+    assert_eq!(
+        detect_audio_format(Some("audio/mpeg"), b"OggS\0\x02rest"),
+        Some(AudioFormat::Ogg)
+    );
+    assert_eq!(
+        detect_audio_format(None, b"\0\0\0\x20ftypM4A "),
+        Some(AudioFormat::Mp4)
+    );
+    assert_eq!(
+        detect_audio_format(None, &[0xFF, 0xF1, 0x50, 0x80]),
+        Some(AudioFormat::Aac)
+    );
+}
+
+#[test]
+fn mimetype_is_the_fallback_for_unknown_bytes() {
+    assert_eq!(
+        detect_audio_format(Some("audio/ogg; codecs=opus"), b"????"),
+        Some(AudioFormat::Ogg)
+    );
+    assert_eq!(
+        detect_audio_format(Some("audio/x-m4a"), b""),
+        Some(AudioFormat::Mp4)
+    );
+    // MP3 frame sync (layer bits set) is not ADTS.
+    assert_eq!(detect_audio_format(Some("audio/mpeg"), &[0xFF, 0xFB]), None);
+    assert_eq!(detect_audio_format(None, b"????"), None);
+}
+
+#[test]
+fn transcripts_are_tidied_and_filler_dropped() {
+    assert_eq!(
+        clean_transcript("  Hey,\n  call me back   when you can. "),
+        Some("Hey, call me back when you can.".to_string())
+    );
+    assert_eq!(clean_transcript(" [BLANK_AUDIO] "), None);
+    assert_eq!(clean_transcript("Thanks for watching!"), None);
+    assert_eq!(clean_transcript("  ...  "), None);
+    assert_eq!(clean_transcript("Yes."), Some("Yes.".to_string()));
+
+    let long = "word ".repeat(2000);
+    let cut = clean_transcript(&long).unwrap();
+    assert_eq!(cut.chars().count(), 4001);
+    assert!(cut.ends_with('…'));
+}
+
+#[test]
+fn content_carries_the_duration_when_known() {
+    assert_eq!(
+        voice_note_content("Running late, 10 min", Some(42)),
+        "[Voice message 0:42] Running late, 10 min"
+    );
+    assert_eq!(
+        voice_note_content("Call me", Some(125)),
+        "[Voice message 2:05] Call me"
+    );
+    assert_eq!(
+        voice_note_content("Call me", None),
+        "[Voice message] Call me"
+    );
+}
+
+#[test]
+fn oversized_or_unsized_notes_are_refused_before_download() {
+    assert!(check_declared_size(Some(48_000)).is_ok());
+    assert!(check_declared_size(Some(MAX_AUDIO_BYTES as u64)).is_ok());
+    assert!(matches!(
+        check_declared_size(Some(MAX_AUDIO_BYTES as u64 + 1)),
+        Err(VoiceNoteError::TooLarge(_))
+    ));
+    assert!(matches!(
+        check_declared_size(None),
+        Err(VoiceNoteError::Download(_))
+    ));
+}
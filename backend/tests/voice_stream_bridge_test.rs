@@ -0,0 +1,196 @@
+use backend::api::voice_stream_bridge::{
+    parse_pipeline_frame, parse_telnyx_frame, pipeline_event, sinch_audio_frames,
+    sinch_audio_to_mulaw, sinch_stream_rate, InboundFrame, OutboundFrame, StreamProvider,
+    StreamTicket, StreamTicketError, StreamTickets, STREAM_TICKET_TTL_SECONDS,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde_json::Value;
+
+const NOW: i64 = 1_800_000_000;
+
+fn token(url: &str) -> String {
+    let url = url::Url::parse(url).unwrap();
+    let query: std::collections::HashMap<String, String> = url.query_pairs().into_owned().collect();
+    assert_eq!(query.len(), 1, "only the token goes in the URL");
+    query["token"].clone()
+}
+
+fn ticket() -> StreamTicket {
+    StreamTicket {
+        user_id: 7,
+        greeting: "Mom called: ring back?".to_string(),
+    }
+}
+
+#[test]
+fn stream_url_is_single_use() {
+    let tickets = StreamTickets::new("https://lightfriend.test/");
+    let url = tickets.stream_url(StreamProvider::Telnyx, 7, "Mom called: ring back?", NOW);
+    assert!(url.starts_with("wss://lightfriend.test/api/voice/telnyx/ws?token="));
+    assert!(!url.contains("Mom"));
+
+    let token = token(&url);
+    assert_eq!(
+        tickets.consume(StreamProvider::Telnyx, &token, NOW + 60),
+        Ok(ticket())
+    );
+    assert_eq!(
+        tickets.consume(StreamProvider::Telnyx, &token, NOW + 61),
+        Err(StreamTicketError::Invalid)
+    );
+}
+
+#[test]
+fn stream_url_rejects_unknown_tokens_other_providers_and_expiry() {
+    let tickets = StreamTickets::new("https://lightfriend.test");
+    assert_eq!(
+        tickets.consume(StreamProvider::Sinch, "made-up", NOW),
+        Err(StreamTicketError::Invalid)
+    );
+
+    let url = tickets.stream_url(StreamProvider::Sinch, 7, "hello", NOW);
+    assert_eq!(
+        tickets.consume(StreamProvider::Telnyx, &token(&url), NOW),
+        Err(StreamTicketError::Invalid)
+    );
+
+    let url = tickets.stream_url(StreamProvider::Sinch, 7, "hello", NOW);
+    assert_eq!(
+        tickets.consume(
+            StreamProvider::Sinch,
+            &token(&url),
+            NOW + STREAM_TICKET_TTL_SECONDS
+        ),
+        Err(StreamTicketError::Expired)
+    );
+
+    // A clone shares the pending tickets.
+    let url = tickets.stream_url(StreamProvider::Sinch, 7, "hello", NOW);
+    assert!(tickets
+        .clone()
+        .consume(StreamProvider::Sinch, &token(&url), NOW)
+        .is_ok());
+}
+
+#[test]
+fn telnyx_frames_are_parsed() {
+    let start = r#"{"event":"start","sequence_number":"1","stream_id":"st-1",
+        "start":{"call_control_id":"v3:call","media_format":{"encoding":"PCMU","sample_rate":8000,"channels":1}}}"#;
+    assert_eq!(
+        parse_telnyx_frame(start),
+        InboundFrame::Start {
+            stream_id: "st-1".to_string(),
+            call_id: "v3:call".to_string(),
+        }
+    );
+
+    let media = format!(
+        r#"{{"event":"media","stream_id":"st-1","media":{{"track":"inbound","chunk":"2","payload":"{}"}}}}"#,
+        BASE64.encode([0xff, 0x7f, 0x00])
+    );
+    assert_eq!(
+        parse_telnyx_frame(&media),
+        InboundFrame::Media(vec![0xff, 0x7f, 0x00])
+    );
+    let outbound = media.replace("\"inbound\"", "\"outbound\"");
+    assert_eq!(parse_telnyx_frame(&outbound), InboundFrame::Ignore);
+
+    assert_eq!(
+        parse_telnyx_frame(r#"{"event":"stop","stream_id":"st-1"}"#),
+        InboundFrame::Stop
+    );
+    assert_eq!(
+        parse_telnyx_frame(r#"{"event":"connected","version":"1.0.0"}"#),
+        InboundFrame::Ignore
+    );
+    assert_eq!(parse_telnyx_frame("not json"), InboundFrame::Ignore);
+}
+
+#[test]
+fn pipeline_sees_twilio_start_with_the_ticket_parameters() {
+    let start = InboundFrame::Start {
+        stream_id: "st-1".to_string(),
+        call_id: "v3:call".to_string(),
+    };
+    let event: Value = serde_json::from_str(&pipeline_event(&start, &ticket()).unwrap()).unwrap();
+    assert_eq!(event["event"], "start");
+    assert_eq!(event["start"]["streamSid"], "st-1");
+    assert_eq!(event["start"]["callSid"], "v3:call");
+    assert_eq!(event["start"]["customParameters"]["user_id"], "7");
+    let greeting = event["start"]["customParameters"]["greeting"]
+        .as_str()
+        .unwrap();
+    assert_eq!(
+        urlencoding::decode(greeting).unwrap(),
+        "Mom called: ring back?"
+    );
+
+    let media: Value = serde_json::from_str(
+        &pipeline_event(&InboundFrame::Media(vec![1, 2, 3]), &ticket()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(media["media"]["payload"], BASE64.encode([1, 2, 3]));
+    assert!(pipeline_event(&InboundFrame::Ignore, &ticket()).is_none());
+}
+
+#[test]
+fn pipeline_output_is_parsed() {
+    let media = format!(
+        r#"{{"event":"media","streamSid":"st-1","media":{{"payload":"{}"}}}}"#,
+        BASE64.encode([9, 8, 7])
+    );
+    assert_eq!(
+        parse_pipeline_frame(&media),
+        Some(OutboundFrame::Media(vec![9, 8, 7]))
+    );
+    assert_eq!(
+        parse_pipeline_frame(r#"{"event":"mark","streamSid":"st-1","mark":{"name":"tts_3"}}"#),
+        Some(OutboundFrame::Mark("tts_3".to_string()))
+    );
+    assert_eq!(
+        parse_pipeline_frame(r#"{"event":"clear","streamSid":"st-1"}"#),
+        Some(OutboundFrame::Clear)
+    );
+    assert_eq!(parse_pipeline_frame(r#"{"type":"audio_end"}"#), None);
+}
+
+#[test]
+fn sinch_rate_comes_from_the_connected_message() {
+    assert_eq!(
+        sinch_stream_rate(
+            r#"{"event":"websocket:connected","contentType":"audio/l16;rate=16000"}"#
+        ),
+        Some(16000)
+    );
+    assert_eq!(
+        sinch_stream_rate(r#"{"event":"websocket:connected"}"#),
+        None
+    );
+}
+
+#[test]
+fn sinch_audio_converts_between_l16_and_mulaw() {
+    // 400 samples: two full 20ms frames and a partial one.
+    let mulaw: Vec<u8> = (0..400).map(|i| (i % 256) as u8).collect();
+
+    let frames = sinch_audio_frames(&mulaw, 8000);
+    assert_eq!(
+        frames.iter().map(Vec::len).collect::<Vec<_>>(),
+        vec![320, 320, 160]
+    );
+    let pcm: Vec<u8> = frames.concat();
+    // Mu-law has two codes for zero; everything else survives the trip.
+    let back = sinch_audio_to_mulaw(&pcm, 8000);
+    assert_eq!(back.len(), mulaw.len());
+    for (sent, received) in mulaw.iter().zip(&back) {
+        if *sent != 0x7f {
+            assert_eq!(sent, received);
+        }
+    }
+
+    // At 16kHz each sample is doubled out and averaged back in.
+    let wide = sinch_audio_frames(&mulaw[..160], 16000);
+    assert_eq!(wide.len(), 1);
+    assert_eq!(wide[0].len(), 640);
+    assert_eq!(sinch_audio_to_mulaw(&wide[0], 16000).len(), 160);
+}
@@ -20,6 +20,31 @@ struct UsSmsProviderStatus {
     on_telnyx: i64,
 }
 
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct ProviderHealthRow {
+    provider: String,
+    country: String,
+    samples: usize,
+    success_rate: Option<f64>,
+    median_latency_ms: Option<u64>,
+    p90_latency_ms: Option<u64>,
+    sent: u64,
+    delivered: u64,
+    failed: u64,
+    retried: u64,
+    circuit: String,
+    reopens_in_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct ProviderHealthSnapshot {
+    rows: Vec<ProviderHealthRow>,
+    pending_deliveries: usize,
+    min_success_rate: f64,
+    cooldown_secs: u64,
+    retry_window_secs: u64,
+}
+
 #[derive(Serialize)]
 struct BulkSetUsSmsProviderRequest {
     pin_to_telnyx: bool,
@@ -476,6 +501,9 @@ pub fn admin_dashboard() -> Html {
     let us_sms_status: UseStateHandle<Option<UsSmsProviderStatus>> = use_state(|| None);
     let sms_toggle_in_flight = use_state(|| false);
     let sms_toggle_status_msg = use_state(|| None::<String>);
+    // SMS provider health scoreboard, fetched when the section opens
+    let show_provider_health_section = use_state(|| false);
+    let provider_health: UseStateHandle<Option<ProviderHealthSnapshot>> = use_state(|| None);
 
     let users_effect = users.clone();
     let error_effect = error.clone();
@@ -873,6 +901,128 @@ pub fn admin_dashboard() -> Html {
                         }
                     </div>
 
+                    // SMS Provider Health Section
+                    <div class="collapsible-section provider-health-section">
+                        <div class="collapsible-header" onclick={{
+                            let show_provider_health_section = show_provider_health_section.clone();
+                            let provider_health = provider_health.clone();
+                            Callback::from(move |_| {
+                                let opening = !*show_provider_health_section;
+                                show_provider_health_section.set(opening);
+                                if opening {
+                                    let provider_health = provider_health.clone();
+                                    wasm_bindgen_futures::spawn_local(async move {
+                                        if let Ok(response) = Api::get("/api/admin/provider-health").send().await {
+                                            if let Ok(data) = response.json::<ProviderHealthSnapshot>().await {
+                                                provider_health.set(Some(data));
+                                            }
+                                        }
+                                    });
+                                }
+                            })
+                        }}>
+                            <h2>{"SMS Provider Health"}</h2>
+                            <span class="toggle-indicator">{if *show_provider_health_section { "▼" } else { "▶" }}</span>
+                        </div>
+                        {
+                            if *show_provider_health_section {
+                                let refresh = {
+                                    let provider_health = provider_health.clone();
+                                    Callback::from(move |_| {
+                                        let provider_health = provider_health.clone();
+                                        wasm_bindgen_futures::spawn_local(async move {
+                                            if let Ok(response) = Api::get("/api/admin/provider-health").send().await {
+                                                if let Ok(data) = response.json::<ProviderHealthSnapshot>().await {
+                                                    provider_health.set(Some(data));
+                                                }
+                                            }
+                                        });
+                                    })
+                                };
+                                match &*provider_health {
+                                    Some(health) => html! {
+                                        <div class="collapsible-content">
+                                            <p style="opacity: 0.7;">
+                                                {format!(
+                                                    "Providers under {:.0}% delivered go last for {} min; failed deliveries are re-sent for {} min. {} sends awaiting status. Resets on restart.",
+                                                    health.min_success_rate * 100.0,
+                                                    health.cooldown_secs / 60,
+                                                    health.retry_window_secs / 60,
+                                                    health.pending_deliveries,
+                                                )}
+                                            </p>
+                                            {
+                                                if health.rows.is_empty() {
+                                                    html! { <p>{"No delivery reports since the last restart."}</p> }
+                                                } else {
+                                                    html! {
+                                                        <table class="stats-table">
+                                                            <thead>
+                                                                <tr>
+                                                                    <th>{"Country"}</th>
+                                                                    <th>{"Provider"}</th>
+                                                                    <th>{"Circuit"}</th>
+                                                                    <th>{"Delivered"}</th>
+                                                                    <th>{"Latency p50 / p90"}</th>
+                                                                    <th>{"Sent"}</th>
+                                                                    <th>{"Failed"}</th>
+                                                                    <th>{"Re-sent"}</th>
+                                                                </tr>
+                                                            </thead>
+                                                            <tbody>
+                                                                {
+                                                                    health.rows.iter().map(|row| {
+                                                                        let circuit_color = match row.circuit.as_str() {
+                                                                            "open" => "#ff6b6b",
+                                                                            "half_open" => "#f5a623",
+                                                                            _ => "#4CAF50",
+                                                                        };
+                                                                        let circuit = match row.reopens_in_secs {
+                                                                            Some(secs) => format!("{} ({}s)", row.circuit, secs),
+                                                                            None => row.circuit.clone(),
+                                                                        };
+                                                                        let rate = match row.success_rate {
+                                                                            Some(rate) => format!("{:.0}% of {}", rate * 100.0, row.samples),
+                                                                            None => "-".to_string(),
+                                                                        };
+                                                                        let latency = |ms: Option<u64>| match ms {
+                                                                            Some(ms) => format!("{:.1}s", ms as f64 / 1000.0),
+                                                                            None => "-".to_string(),
+                                                                        };
+                                                                        html! {
+                                                                            <tr key={format!("{}-{}", row.country, row.provider)}>
+                                                                                <td>{if row.country.is_empty() { "?" } else { &row.country }}</td>
+                                                                                <td>{&row.provider}</td>
+                                                                                <td style={format!("color: {};", circuit_color)}>{circuit}</td>
+                                                                                <td>{rate}</td>
+                                                                                <td>{format!("{} / {}", latency(row.median_latency_ms), latency(row.p90_latency_ms))}</td>
+                                                                                <td>{row.sent}</td>
+                                                                                <td>{row.failed}</td>
+                                                                                <td>{row.retried}</td>
+                                                                            </tr>
+                                                                        }
+                                                                    }).collect::<Html>()
+                                                                }
+                                                            </tbody>
+                                                        </table>
+                                                    }
+                                                }
+                                            }
+                                            <button class="broadcast-button email" onclick={refresh}>{"Refresh"}</button>
+                                        </div>
+                                    },
+                                    None => html! {
+                                        <div class="collapsible-content">
+                                            <p>{"Loading..."}</p>
+                                        </div>
+                                    },
+                                }
+                            } else {
+                                html! {}
+                            }
+                        }
+                    </div>
+
                     // Change Password Section
                     <div class="collapsible-section password-section">
                         <div class="collapsible-header" onclick={{
@@ -1,3 +1,4 @@
+use crate::connections::calendar::CalendarConnect;
 use crate::connections::email::EmailConnect;
 use crate::connections::mcp::McpConnect;
 use crate::connections::signal::SignalConnect;
@@ -37,6 +38,7 @@ pub fn connect(props: &ConnectProps) -> Html {
     let tesla_connected = use_state(|| false);
     let youtube_connected = use_state(|| false);
     let mcp_server_count = use_state(|| 0_usize);
+    let calendar_count = use_state(|| 0_usize);
     let selected_app = use_state(|| None::<String>);
 
     {
@@ -47,6 +49,7 @@ pub fn connect(props: &ConnectProps) -> Html {
         let tesla_connected = tesla_connected.clone();
         let youtube_connected = youtube_connected.clone();
         let mcp_server_count = mcp_server_count.clone();
+        let calendar_count = calendar_count.clone();
         use_effect_with_deps(
             move |_| {
                 // Auth handled by cookies - check all connection statuses
@@ -153,6 +156,19 @@ pub fn connect(props: &ConnectProps) -> Html {
                         }
                     }
                 });
+                // CalDAV calendar connections count check
+                spawn_local({
+                    let calendar_count = calendar_count.clone();
+                    async move {
+                        if let Ok(response) = Api::get("/api/calendar/connections").send().await {
+                            if let Ok(data) = response.json::<Value>().await {
+                                if let Some(connections) = data.as_array() {
+                                    calendar_count.set(connections.len());
+                                }
+                            }
+                        }
+                    }
+                });
                 || ()
             },
             (),
@@ -224,6 +240,7 @@ pub fn connect(props: &ConnectProps) -> Html {
             "youtube" => {
                 html! { <YouTubeConnect user_id={props.user_id} sub_tier={props.sub_tier.clone()} /> }
             }
+            "calendar" => html! { <CalendarConnect /> },
             "mcp" => html! { <McpConnect /> },
             _ => html! {},
         }
@@ -293,6 +310,16 @@ pub fn connect(props: &ConnectProps) -> Html {
                                         <img src="https://upload.wikimedia.org/wikipedia/commons/0/09/YouTube_full-color_icon_%282017%29.svg" alt="YouTube" width="24" height="24"/>
                                         <span class="app-icon-label">{"YouTube"}</span>
                                     </button>
+                                    <button
+                                        class={classes!("app-icon", "connectable-app", "calendar-icon", if *calendar_count > 0 { "connected" } else { "" }, if selected_app.as_ref().map_or(false, |s| s == "calendar") { "selected" } else { "" })}
+                                        onclick={let selected_app = selected_app.clone(); Callback::from(move |_: MouseEvent| {
+                                            selected_app.set(if *selected_app == Some("calendar".to_string()) { None } else { Some("calendar".to_string()) });
+                                        })}
+                                        title="Calendar - Connect a CalDAV calendar"
+                                    >
+                                        <i class="fa-solid fa-calendar"></i>
+                                        <span class="app-icon-label">{"Calendar"}</span>
+                                    </button>
                                     <button
                                         class={classes!("app-icon", "connectable-app", "mcp-icon", if *mcp_server_count > 0 { "connected" } else { "" }, if selected_app.as_ref().map_or(false, |s| s == "mcp") { "selected" } else { "" })}
                                         onclick={let selected_app = selected_app.clone(); Callback::from(move |_: MouseEvent| {
@@ -555,6 +582,12 @@ pub fn connect(props: &ConnectProps) -> Html {
 .app-icon.mcp-icon {
     color: #A78BFA;
 }
+.app-icon.calendar-icon {
+    color: #F59E0B;
+}
+.app-icon.calendar-icon:hover {
+    background: rgba(255, 255, 255, 0.045);
+}
 .app-icon.mcp-icon.connected {
     background: transparent;
     box-shadow: none;
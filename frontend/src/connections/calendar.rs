@@ -0,0 +1,660 @@
+use crate::utils::api::Api;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Event, MouseEvent};
+use yew::prelude::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CalendarConnection {
+    pub id: i32,
+    pub name: String,
+    pub url: String,
+    pub username: String,
+    pub calendars: Vec<String>,
+    pub is_enabled: bool,
+    pub created_at: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarTestResponse {
+    pub success: bool,
+    pub calendars: Option<Vec<String>>,
+    pub upcoming_events: Option<usize>,
+    pub error: Option<String>,
+}
+
+fn text_input(state: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let state = state.clone();
+    Callback::from(move |e: InputEvent| {
+        if let Some(target) = e.target() {
+            if let Ok(input) = target.dyn_into::<web_sys::HtmlInputElement>() {
+                state.set(input.value());
+            }
+        }
+    })
+}
+
+#[function_component(CalendarConnect)]
+pub fn calendar_connect() -> Html {
+    let connections = use_state(Vec::<CalendarConnection>::new);
+    let loading = use_state(|| true);
+    let error = use_state(|| None::<String>);
+    let show_form = use_state(|| false);
+    let testing = use_state(|| None::<i32>);
+    let test_result = use_state(|| None::<(i32, CalendarTestResponse)>);
+
+    // Add form state
+    let new_name = use_state(String::new);
+    let new_url = use_state(String::new);
+    let new_username = use_state(String::new);
+    let new_password = use_state(String::new);
+    let adding = use_state(|| false);
+
+    // Fetch connections on mount
+    {
+        let connections = connections.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    match Api::get("/api/calendar/connections").send().await {
+                        Ok(response) => {
+                            if response.ok() {
+                                if let Ok(data) = response.json::<Vec<CalendarConnection>>().await {
+                                    connections.set(data);
+                                }
+                            } else {
+                                error.set(Some("Failed to fetch calendars".to_string()));
+                            }
+                        }
+                        Err(e) => {
+                            error.set(Some(format!("Network error: {}", e)));
+                        }
+                    }
+                    loading.set(false);
+                });
+                || ()
+            },
+            (),
+        );
+    }
+
+    let on_add = {
+        let connections = connections.clone();
+        let show_form = show_form.clone();
+        let new_name = new_name.clone();
+        let new_url = new_url.clone();
+        let new_username = new_username.clone();
+        let new_password = new_password.clone();
+        let adding = adding.clone();
+        let error = error.clone();
+
+        Callback::from(move |_: MouseEvent| {
+            let connections = connections.clone();
+            let show_form = show_form.clone();
+            let new_name = new_name.clone();
+            let new_url = new_url.clone();
+            let new_username = new_username.clone();
+            let new_password = new_password.clone();
+            let adding = adding.clone();
+            let error = error.clone();
+
+            let body = serde_json::json!({
+                "name": (*new_name).trim(),
+                "url": (*new_url).trim(),
+                "username": (*new_username).trim(),
+                "password": (*new_password).clone(),
+            });
+
+            adding.set(true);
+            error.set(None);
+            spawn_local(async move {
+                let request = match Api::post("/api/calendar/connections").json(&body) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        error.set(Some(format!("Failed to create request: {}", e)));
+                        adding.set(false);
+                        return;
+                    }
+                };
+                match request.send().await {
+                    Ok(response) => {
+                        if response.ok() {
+                            if let Ok(connection) = response.json::<CalendarConnection>().await {
+                                let mut updated = (*connections).clone();
+                                updated.insert(0, connection);
+                                connections.set(updated);
+                                show_form.set(false);
+                                new_name.set(String::new());
+                                new_url.set(String::new());
+                                new_username.set(String::new());
+                                new_password.set(String::new());
+                            }
+                        } else if let Ok(err_data) = response.json::<serde_json::Value>().await {
+                            error.set(Some(
+                                err_data
+                                    .get("error")
+                                    .and_then(|e| e.as_str())
+                                    .unwrap_or("Failed to connect calendar")
+                                    .to_string(),
+                            ));
+                        }
+                    }
+                    Err(e) => {
+                        error.set(Some(format!("Network error: {}", e)));
+                    }
+                }
+                adding.set(false);
+            });
+        })
+    };
+
+    let on_toggle = {
+        let connections = connections.clone();
+        Callback::from(move |connection_id: i32| {
+            let connections = connections.clone();
+            spawn_local(async move {
+                if let Ok(response) = Api::patch(&format!(
+                    "/api/calendar/connections/{}/toggle",
+                    connection_id
+                ))
+                .send()
+                .await
+                {
+                    if response.ok() {
+                        if let Ok(result) = response.json::<serde_json::Value>().await {
+                            if let Some(is_enabled) =
+                                result.get("is_enabled").and_then(|v| v.as_bool())
+                            {
+                                let mut updated = (*connections).clone();
+                                if let Some(c) = updated.iter_mut().find(|c| c.id == connection_id)
+                                {
+                                    c.is_enabled = is_enabled;
+                                }
+                                connections.set(updated);
+                            }
+                        }
+                    }
+                }
+            });
+        })
+    };
+
+    let on_delete = {
+        let connections = connections.clone();
+        Callback::from(move |connection_id: i32| {
+            let connections = connections.clone();
+            spawn_local(async move {
+                if let Ok(response) =
+                    Api::delete(&format!("/api/calendar/connections/{}", connection_id))
+                        .send()
+                        .await
+                {
+                    if response.ok() {
+                        let updated: Vec<CalendarConnection> = (*connections)
+                            .iter()
+                            .filter(|c| c.id != connection_id)
+                            .cloned()
+                            .collect();
+                        connections.set(updated);
+                    }
+                }
+            });
+        })
+    };
+
+    let on_test = {
+        let testing = testing.clone();
+        let test_result = test_result.clone();
+        let connections = connections.clone();
+        Callback::from(move |connection_id: i32| {
+            let testing = testing.clone();
+            let test_result = test_result.clone();
+            let connections = connections.clone();
+            testing.set(Some(connection_id));
+            test_result.set(None);
+            spawn_local(async move {
+                if let Ok(response) =
+                    Api::post(&format!("/api/calendar/connections/{}/test", connection_id))
+                        .send()
+                        .await
+                {
+                    if let Ok(result) = response.json::<CalendarTestResponse>().await {
+                        // The test re-discovers calendars; show the fresh list.
+                        if let Some(ref names) = result.calendars {
+                            let mut updated = (*connections).clone();
+                            if let Some(c) = updated.iter_mut().find(|c| c.id == connection_id) {
+                                c.calendars = names.clone();
+                            }
+                            connections.set(updated);
+                        }
+                        test_result.set(Some((connection_id, result)));
+                    }
+                }
+                testing.set(None);
+            });
+        })
+    };
+
+    let toggle_form = {
+        let show_form = show_form.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            show_form.set(!*show_form);
+            error.set(None);
+        })
+    };
+
+    let can_add = !*adding
+        && !(*new_name).trim().is_empty()
+        && !(*new_url).trim().is_empty()
+        && !(*new_username).trim().is_empty()
+        && !(*new_password).is_empty();
+
+    html! {
+        <div class="calendar-connect">
+            <div class="calendar-header">
+                <div class="calendar-title">
+                    <i class="fa-solid fa-calendar"></i>
+                    <span>{"Calendars"}</span>
+                </div>
+                <button class="calendar-add-btn" onclick={toggle_form}>
+                    { if *show_form {
+                        html! { "Cancel" }
+                    } else {
+                        html! { <><i class="fa-solid fa-plus"></i>{" Add Calendar"}</> }
+                    }}
+                </button>
+            </div>
+
+            <p class="calendar-description">
+                {"Connect any CalDAV calendar (Nextcloud, Fastmail, iCloud, Radicale...) so your assistant can read your schedule, add events, and include today's events in your digest. Use an app password where your provider offers one."}
+            </p>
+
+            if let Some(err) = (*error).as_ref() {
+                <div class="calendar-error">
+                    {err}
+                    <button class="calendar-dismiss" onclick={{
+                        let error = error.clone();
+                        Callback::from(move |_: MouseEvent| error.set(None))
+                    }}>{"x"}</button>
+                </div>
+            }
+
+            if *show_form {
+                <div class="calendar-form">
+                    <div class="calendar-form-group">
+                        <label>{"Name"}</label>
+                        <input
+                            type="text"
+                            autocomplete="off"
+                            placeholder="e.g., Work"
+                            value={(*new_name).clone()}
+                            oninput={text_input(&new_name)}
+                        />
+                    </div>
+                    <div class="calendar-form-group">
+                        <label>{"CalDAV URL"}</label>
+                        <input
+                            type="url"
+                            autocomplete="off"
+                            placeholder="https://cloud.example.com/remote.php/dav"
+                            value={(*new_url).clone()}
+                            oninput={text_input(&new_url)}
+                        />
+                        <span class="calendar-hint">{"Server root, principal or a single calendar URL"}</span>
+                    </div>
+                    <div class="calendar-form-group">
+                        <label>{"Username"}</label>
+                        <input
+                            type="text"
+                            autocomplete="off"
+                            value={(*new_username).clone()}
+                            oninput={text_input(&new_username)}
+                        />
+                    </div>
+                    <div class="calendar-form-group">
+                        <label>{"Password"}</label>
+                        <input
+                            type="password"
+                            autocomplete="new-password"
+                            value={(*new_password).clone()}
+                            oninput={text_input(&new_password)}
+                        />
+                    </div>
+                    <button class="calendar-save-btn" onclick={on_add} disabled={!can_add}>
+                        { if *adding {
+                            html! { <><i class="fa-solid fa-spinner fa-spin"></i>{" Connecting..."}</> }
+                        } else {
+                            html! { "Connect" }
+                        }}
+                    </button>
+                </div>
+            }
+
+            if *loading {
+                <div class="calendar-loading">{"Loading..."}</div>
+            } else if connections.is_empty() && !*show_form {
+                <div class="calendar-empty">
+                    <p>{"No calendars connected yet."}</p>
+                </div>
+            } else {
+                <div class="calendar-list">
+                    { for connections.iter().map(|connection| {
+                        let connection_id = connection.id;
+                        let is_enabled = connection.is_enabled;
+                        let toggle = {
+                            let on_toggle = on_toggle.clone();
+                            Callback::from(move |_: Event| on_toggle.emit(connection_id))
+                        };
+                        let delete = {
+                            let on_delete = on_delete.clone();
+                            Callback::from(move |_: MouseEvent| on_delete.emit(connection_id))
+                        };
+                        let test = {
+                            let on_test = on_test.clone();
+                            Callback::from(move |_: MouseEvent| on_test.emit(connection_id))
+                        };
+                        let is_testing = *testing == Some(connection_id);
+                        let result = (*test_result)
+                            .as_ref()
+                            .filter(|(id, _)| *id == connection_id)
+                            .map(|(_, r)| r.clone());
+
+                        html! {
+                            <div class={classes!("calendar-card", if !is_enabled { "disabled" } else { "" })}>
+                                <div class="calendar-info">
+                                    <div class="calendar-name">
+                                        <i class={classes!("fa-solid", "fa-circle", if is_enabled { "calendar-status-enabled" } else { "calendar-status-disabled" })}></i>
+                                        {&connection.name}
+                                    </div>
+                                    <div class="calendar-url">{format!("{} ({})", connection.url, connection.username)}</div>
+                                    <div class="calendar-names">
+                                        { for connection.calendars.iter().map(|name| html! {
+                                            <span class="calendar-chip">{name}</span>
+                                        })}
+                                    </div>
+                                </div>
+                                <div class="calendar-actions">
+                                    <button class="calendar-test-btn" onclick={test} disabled={is_testing}>
+                                        { if is_testing {
+                                            html! { <><i class="fa-solid fa-spinner fa-spin"></i>{" Testing..."}</> }
+                                        } else {
+                                            html! { <><i class="fa-solid fa-flask"></i>{" Test"}</> }
+                                        }}
+                                    </button>
+                                    <label class="calendar-toggle">
+                                        <input type="checkbox" checked={is_enabled} onchange={toggle} />
+                                        <span class="calendar-toggle-slider"></span>
+                                    </label>
+                                    <button class="calendar-delete-btn" onclick={delete}>
+                                        <i class="fa-solid fa-trash"></i>
+                                    </button>
+                                </div>
+                                { if let Some(res) = result {
+                                    html! {
+                                        <div class={classes!("calendar-test-result", if res.success { "success" } else { "error" })}>
+                                            { if res.success {
+                                                format!(
+                                                    "Connected - {} event(s) in the next 7 days",
+                                                    res.upcoming_events.unwrap_or(0)
+                                                )
+                                            } else {
+                                                format!("Failed: {}", res.error.unwrap_or_else(|| "Unknown error".to_string()))
+                                            }}
+                                        </div>
+                                    }
+                                } else {
+                                    html! {}
+                                }}
+                            </div>
+                        }
+                    })}
+                </div>
+            }
+
+            <style>
+            {r#"
+.calendar-connect {
+    padding: 1rem;
+}
+.calendar-header {
+    display: flex;
+    justify-content: space-between;
+    align-items: center;
+    margin-bottom: 0.75rem;
+}
+.calendar-title {
+    display: flex;
+    align-items: center;
+    gap: 0.5rem;
+    color: #F59E0B;
+    font-size: 1.1rem;
+    font-weight: 500;
+}
+.calendar-description {
+    color: #888;
+    font-size: 0.9rem;
+    margin-bottom: 1rem;
+}
+.calendar-add-btn,
+.calendar-save-btn {
+    background: linear-gradient(45deg, #F59E0B, #D97706);
+    color: white;
+    border: none;
+    padding: 0.5rem 1rem;
+    border-radius: 6px;
+    cursor: pointer;
+    font-size: 0.9rem;
+    transition: all 0.2s;
+}
+.calendar-save-btn {
+    width: 100%;
+    padding: 0.75rem;
+}
+.calendar-save-btn:disabled {
+    opacity: 0.5;
+    cursor: not-allowed;
+}
+.calendar-form {
+    background: rgba(0, 0, 0, 0.2);
+    border: 1px solid rgba(245, 158, 11, 0.2);
+    border-radius: 8px;
+    padding: 1rem;
+    margin-bottom: 1rem;
+}
+.calendar-form-group {
+    margin-bottom: 1rem;
+}
+.calendar-form-group label {
+    display: block;
+    color: #ccc;
+    margin-bottom: 0.5rem;
+    font-size: 0.9rem;
+}
+.calendar-form-group input {
+    width: 100%;
+    padding: 0.75rem;
+    background: rgba(0, 0, 0, 0.3);
+    border: 1px solid rgba(255, 255, 255, 0.1);
+    border-radius: 6px;
+    color: #fff;
+    font-size: 0.95rem;
+    box-sizing: border-box;
+}
+.calendar-form-group input:focus {
+    outline: none;
+    border-color: #F59E0B;
+}
+.calendar-hint {
+    display: block;
+    color: #666;
+    font-size: 0.8rem;
+    margin-top: 0.25rem;
+}
+.calendar-list {
+    display: flex;
+    flex-direction: column;
+    gap: 0.75rem;
+}
+.calendar-card {
+    background: rgba(0, 0, 0, 0.2);
+    border: 1px solid rgba(245, 158, 11, 0.2);
+    border-radius: 8px;
+    padding: 1rem;
+}
+.calendar-card.disabled {
+    opacity: 0.6;
+}
+.calendar-info {
+    display: flex;
+    flex-direction: column;
+    gap: 0.35rem;
+    margin-bottom: 0.75rem;
+}
+.calendar-name {
+    font-weight: 500;
+    color: #fff;
+    display: flex;
+    align-items: center;
+    gap: 0.5rem;
+}
+.calendar-url {
+    color: #888;
+    font-size: 0.85rem;
+    word-break: break-all;
+}
+.calendar-names {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 0.4rem;
+}
+.calendar-chip {
+    background: rgba(245, 158, 11, 0.15);
+    color: #F59E0B;
+    padding: 0.2rem 0.5rem;
+    border-radius: 4px;
+    font-size: 0.75rem;
+}
+.calendar-status-enabled {
+    color: #22C55E;
+    font-size: 0.5rem;
+}
+.calendar-status-disabled {
+    color: #666;
+    font-size: 0.5rem;
+}
+.calendar-actions {
+    display: flex;
+    align-items: center;
+    gap: 0.75rem;
+}
+.calendar-test-btn {
+    background: rgba(245, 158, 11, 0.1);
+    color: #F59E0B;
+    border: 1px solid rgba(245, 158, 11, 0.3);
+    padding: 0.4rem 0.75rem;
+    border-radius: 4px;
+    cursor: pointer;
+    font-size: 0.85rem;
+}
+.calendar-test-btn:disabled {
+    opacity: 0.5;
+    cursor: not-allowed;
+}
+.calendar-delete-btn {
+    background: transparent;
+    color: #EF4444;
+    border: none;
+    padding: 0.4rem 0.5rem;
+    border-radius: 4px;
+    cursor: pointer;
+}
+.calendar-delete-btn:hover {
+    background: rgba(239, 68, 68, 0.1);
+}
+.calendar-toggle {
+    position: relative;
+    width: 40px;
+    height: 22px;
+    cursor: pointer;
+}
+.calendar-toggle input {
+    opacity: 0;
+    width: 0;
+    height: 0;
+}
+.calendar-toggle-slider {
+    position: absolute;
+    top: 0;
+    left: 0;
+    right: 0;
+    bottom: 0;
+    background-color: rgba(100, 100, 100, 0.5);
+    transition: 0.3s;
+    border-radius: 22px;
+}
+.calendar-toggle-slider:before {
+    position: absolute;
+    content: "";
+    height: 16px;
+    width: 16px;
+    left: 3px;
+    bottom: 3px;
+    background-color: white;
+    transition: 0.3s;
+    border-radius: 50%;
+}
+.calendar-toggle input:checked + .calendar-toggle-slider {
+    background: linear-gradient(45deg, #F59E0B, #D97706);
+}
+.calendar-toggle input:checked + .calendar-toggle-slider:before {
+    transform: translateX(18px);
+}
+.calendar-test-result {
+    margin-top: 0.75rem;
+    padding: 0.5rem 0.75rem;
+    border-radius: 6px;
+    font-size: 0.85rem;
+}
+.calendar-test-result.success {
+    background: rgba(34, 197, 94, 0.1);
+    border: 1px solid rgba(34, 197, 94, 0.3);
+    color: #22C55E;
+}
+.calendar-test-result.error {
+    background: rgba(239, 68, 68, 0.1);
+    border: 1px solid rgba(239, 68, 68, 0.3);
+    color: #EF4444;
+}
+.calendar-empty,
+.calendar-loading {
+    text-align: center;
+    padding: 1rem;
+    color: #666;
+}
+.calendar-error {
+    background: rgba(239, 68, 68, 0.1);
+    border: 1px solid rgba(239, 68, 68, 0.3);
+    color: #EF4444;
+    padding: 0.75rem;
+    border-radius: 6px;
+    margin-bottom: 1rem;
+    display: flex;
+    justify-content: space-between;
+    align-items: center;
+}
+.calendar-dismiss {
+    background: none;
+    border: none;
+    color: #EF4444;
+    cursor: pointer;
+    font-size: 1rem;
+}
+            "#}
+            </style>
+        </div>
+    }
+}
@@ -13,6 +13,10 @@ pub struct McpServer {
     pub has_auth_token: bool,
     pub is_enabled: bool,
     pub created_at: i32,
+    #[serde(default)]
+    pub uses_oauth: bool,
+    #[serde(default)]
+    pub oauth_connected: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +31,71 @@ pub struct McpTestResponse {
     pub tools_count: Option<usize>,
     pub tools: Option<Vec<McpToolInfo>>,
     pub error: Option<String>,
+    #[serde(default)]
+    pub server_name: Option<String>,
+    #[serde(default)]
+    pub resources: Option<Vec<serde_json::Value>>,
+    #[serde(default)]
+    pub prompts: Option<Vec<serde_json::Value>>,
+}
+
+impl McpTestResponse {
+    /// ", 2 resources, 1 prompt" - empty when the server offers neither.
+    fn extras_summary(&self) -> String {
+        let count = |items: &Option<Vec<serde_json::Value>>, noun: &str| match items
+            .as_ref()
+            .map(|v| v.len())
+            .unwrap_or(0)
+        {
+            0 => String::new(),
+            1 => format!(", 1 {}", noun),
+            n => format!(", {} {}s", n, noun),
+        };
+        format!(
+            "{}{}",
+            count(&self.resources, "resource"),
+            count(&self.prompts, "prompt")
+        )
+    }
+
+    fn server_label(&self) -> String {
+        self.server_name
+            .as_ref()
+            .map(|name| format!(" to {}", name))
+            .unwrap_or_default()
+    }
+}
+
+/// Ask the backend to start an OAuth authorization and send the browser
+/// to the returned URL.
+async fn start_authorization(path: &str, body: Option<&serde_json::Value>) -> Result<(), String> {
+    let mut request = Api::post(path);
+    if let Some(body) = body {
+        request = request
+            .json(body)
+            .map_err(|e| format!("Failed to create request: {}", e))?;
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    let data = response
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|_| "Failed to parse response".to_string())?;
+    match data.get("auth_url").and_then(|u| u.as_str()) {
+        Some(auth_url) => {
+            if let Some(window) = web_sys::window() {
+                let _ = window.location().set_href(auth_url);
+            }
+            Ok(())
+        }
+        None => Err(data
+            .get("error")
+            .and_then(|e| e.as_str())
+            .unwrap_or("Failed to start authorization")
+            .to_string()),
+    }
 }
 
 #[function_component(McpConnect)]
@@ -34,6 +103,7 @@ pub fn mcp_connect() -> Html {
     let servers = use_state(Vec::<McpServer>::new);
     let loading = use_state(|| true);
     let error = use_state(|| None::<String>);
+    let notice = use_state(|| None::<String>);
     let show_add_modal = use_state(|| false);
     let testing_server = use_state(|| None::<i32>);
     let test_result = use_state(|| None::<McpTestResponse>);
@@ -42,10 +112,47 @@ pub fn mcp_connect() -> Html {
     let new_name = use_state(String::new);
     let new_url = use_state(String::new);
     let new_auth_token = use_state(String::new);
+    let use_oauth = use_state(|| false);
     let adding = use_state(|| false);
     let test_url_result = use_state(|| None::<McpTestResponse>);
     let testing_url = use_state(|| false);
 
+    // Outcome of an OAuth authorization redirect
+    {
+        let error = error.clone();
+        let notice = notice.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(window) = web_sys::window() {
+                    if let Ok(search) = window.location().search() {
+                        let params = web_sys::UrlSearchParams::new_with_str(&search).ok();
+                        if let Some(params) = params {
+                            if let Some(mcp_status) = params.get("mcp") {
+                                if mcp_status == "error" {
+                                    error.set(Some(params.get("message").unwrap_or_else(|| {
+                                        "Failed to authorize MCP server. Please try again."
+                                            .to_string()
+                                    })));
+                                } else if mcp_status == "success" {
+                                    notice.set(Some("MCP server authorized.".to_string()));
+                                }
+                                let _ = window.history().and_then(|h| {
+                                    h.replace_state_with_url(
+                                        &wasm_bindgen::JsValue::NULL,
+                                        "",
+                                        Some("/connections"),
+                                    )
+                                });
+                            }
+                        }
+                    }
+                }
+                || ()
+            },
+            (),
+        );
+    }
+
     // Fetch servers on mount
     {
         let servers = servers.clone();
@@ -85,12 +192,14 @@ pub fn mcp_connect() -> Html {
         let new_name = new_name.clone();
         let new_url = new_url.clone();
         let new_auth_token = new_auth_token.clone();
+        let use_oauth = use_oauth.clone();
         let adding = adding.clone();
         let error = error.clone();
         let test_url_result = test_url_result.clone();
 
         Callback::from(move |_: MouseEvent| {
             let servers = servers.clone();
+            let oauth = *use_oauth;
             let show_add_modal = show_add_modal.clone();
             let name = (*new_name).clone();
             let url = (*new_url).clone();
@@ -112,6 +221,18 @@ pub fn mcp_connect() -> Html {
             }
 
             adding.set(true);
+            if oauth {
+                let error = error.clone();
+                let adding = adding.clone();
+                spawn_local(async move {
+                    let body = serde_json::json!({ "name": name, "url": url });
+                    if let Err(e) = start_authorization("/api/mcp/oauth/start", Some(&body)).await {
+                        error.set(Some(e));
+                    }
+                    adding.set(false);
+                });
+                return;
+            }
             spawn_local(async move {
                 let body = serde_json::json!({
                     "name": name,
@@ -287,6 +408,19 @@ pub fn mcp_connect() -> Html {
         })
     };
 
+    let on_authorize_server = {
+        let error = error.clone();
+        Callback::from(move |server_id: i32| {
+            let error = error.clone();
+            spawn_local(async move {
+                let path = format!("/api/mcp/servers/{}/oauth/start", server_id);
+                if let Err(e) = start_authorization(&path, None).await {
+                    error.set(Some(e));
+                }
+            });
+        })
+    };
+
     let on_open_add_modal = {
         let show_add_modal = show_add_modal.clone();
         let test_url_result = test_url_result.clone();
@@ -303,12 +437,14 @@ pub fn mcp_connect() -> Html {
         let new_name = new_name.clone();
         let new_url = new_url.clone();
         let new_auth_token = new_auth_token.clone();
+        let use_oauth = use_oauth.clone();
         let test_url_result = test_url_result.clone();
         Callback::from(move |_: MouseEvent| {
             show_add_modal.set(false);
             new_name.set(String::new());
             new_url.set(String::new());
             new_auth_token.set(String::new());
+            use_oauth.set(false);
             test_url_result.set(None);
         })
     };
@@ -341,6 +477,16 @@ pub fn mcp_connect() -> Html {
                 </div>
             }
 
+            if let Some(message) = (*notice).as_ref() {
+                <div class="notice-message">
+                    {message}
+                    <button class="dismiss-error" onclick={{
+                        let notice = notice.clone();
+                        Callback::from(move |_: MouseEvent| notice.set(None))
+                    }}>{"x"}</button>
+                </div>
+            }
+
             if *loading {
                 <div class="loading">{"Loading..."}</div>
             } else if servers.is_empty() {
@@ -366,6 +512,10 @@ pub fn mcp_connect() -> Html {
                             let on_test_server = on_test_server.clone();
                             Callback::from(move |_: MouseEvent| on_test_server.emit(server_id))
                         };
+                        let on_authorize = {
+                            let on_authorize_server = on_authorize_server.clone();
+                            Callback::from(move |_: MouseEvent| on_authorize_server.emit(server_id))
+                        };
                         let is_testing = *testing_server == Some(server_id);
                         let result = if *testing_server == Some(server_id) || (*testing_server).is_none() {
                             (*test_result).clone()
@@ -381,13 +531,27 @@ pub fn mcp_connect() -> Html {
                                         {&server.name}
                                     </div>
                                     <div class="server-url">{&server.url}</div>
-                                    { if server.has_auth_token {
+                                    { if server.uses_oauth && !server.oauth_connected {
+                                        html! { <span class="auth-badge pending"><i class="fa-solid fa-lock"></i>{" Needs authorization"}</span> }
+                                    } else if server.uses_oauth {
+                                        html! { <span class="auth-badge"><i class="fa-solid fa-lock"></i>{" OAuth"}</span> }
+                                    } else if server.has_auth_token {
                                         html! { <span class="auth-badge"><i class="fa-solid fa-key"></i>{" Auth"}</span> }
                                     } else {
                                         html! {}
                                     }}
                                 </div>
                                 <div class="server-actions">
+                                    { if server.uses_oauth {
+                                        html! {
+                                            <button class="test-btn" onclick={on_authorize}>
+                                                <i class="fa-solid fa-right-to-bracket"></i>
+                                                { if server.oauth_connected { " Reconnect" } else { " Authorize" } }
+                                            </button>
+                                        }
+                                    } else {
+                                        html! {}
+                                    }}
                                     <button
                                         class="test-btn"
                                         onclick={on_test}
@@ -421,7 +585,7 @@ pub fn mcp_connect() -> Html {
                                                     html! {
                                                         <>
                                                             <i class="fa-solid fa-check-circle"></i>
-                                                            {format!(" Connected - {} tools available", res.tools_count.unwrap_or(0))}
+                                                            {format!(" Connected{} - {} tools available{}", res.server_label(), res.tools_count.unwrap_or(0), res.extras_summary())}
                                                             { if let Some(ref tools) = res.tools {
                                                                 html! {
                                                                     <div class="tools-list">
@@ -508,37 +672,57 @@ pub fn mcp_connect() -> Html {
                                     }}
                                 />
                             </div>
-                            <div class="form-group">
-                                <label>{"Auth Token"}<span class="optional">{" (optional)"}</span></label>
-                                <input
-                                    type="password"
-                                    autocomplete="new-password"
-                                    placeholder="Bearer token or API key"
-                                    value={(*new_auth_token).clone()}
-                                    oninput={{
-                                        let new_auth_token = new_auth_token.clone();
-                                        Callback::from(move |e: InputEvent| {
-                                            if let Some(target) = e.target() {
-                                                if let Ok(input) = target.dyn_into::<web_sys::HtmlInputElement>() {
-                                                    new_auth_token.set(input.value());
-                                                }
-                                            }
-                                        })
-                                    }}
-                                />
+                            <div class="form-group checkbox-group">
+                                <label>
+                                    <input
+                                        type="checkbox"
+                                        checked={*use_oauth}
+                                        onchange={{
+                                            let use_oauth = use_oauth.clone();
+                                            let test_url_result = test_url_result.clone();
+                                            Callback::from(move |_: Event| {
+                                                use_oauth.set(!*use_oauth);
+                                                test_url_result.set(None);
+                                            })
+                                        }}
+                                    />
+                                    {" Sign in with OAuth"}
+                                </label>
+                                <span class="hint">{"For servers that ask you to log in. You'll be sent to the server's sign-in page."}</span>
                             </div>
+                            if !*use_oauth {
+                                <div class="form-group">
+                                    <label>{"Auth Token"}<span class="optional">{" (optional)"}</span></label>
+                                    <input
+                                        type="password"
+                                        autocomplete="new-password"
+                                        placeholder="Bearer token or API key"
+                                        value={(*new_auth_token).clone()}
+                                        oninput={{
+                                            let new_auth_token = new_auth_token.clone();
+                                            Callback::from(move |e: InputEvent| {
+                                                if let Some(target) = e.target() {
+                                                    if let Ok(input) = target.dyn_into::<web_sys::HtmlInputElement>() {
+                                                        new_auth_token.set(input.value());
+                                                    }
+                                                }
+                                            })
+                                        }}
+                                    />
+                                </div>
 
-                            <button
-                                class="test-connection-btn"
-                                onclick={on_test_url}
-                                disabled={*testing_url || (*new_url).is_empty()}
-                            >
-                                { if *testing_url {
-                                    html! { <><i class="fa-solid fa-spinner fa-spin"></i>{" Testing..."}</> }
-                                } else {
-                                    html! { <><i class="fa-solid fa-flask"></i>{" Test Connection"}</> }
-                                }}
-                            </button>
+                                <button
+                                    class="test-connection-btn"
+                                    onclick={on_test_url}
+                                    disabled={*testing_url || (*new_url).is_empty()}
+                                >
+                                    { if *testing_url {
+                                        html! { <><i class="fa-solid fa-spinner fa-spin"></i>{" Testing..."}</> }
+                                    } else {
+                                        html! { <><i class="fa-solid fa-flask"></i>{" Test Connection"}</> }
+                                    }}
+                                </button>
+                            }
 
                             { if let Some(ref result) = *test_url_result {
                                 html! {
@@ -547,7 +731,7 @@ pub fn mcp_connect() -> Html {
                                             html! {
                                                 <>
                                                     <i class="fa-solid fa-check-circle"></i>
-                                                    {format!(" Connected! {} tools discovered:", result.tools_count.unwrap_or(0))}
+                                                    {format!(" Connected{}! {} tools discovered{}:", result.server_label(), result.tools_count.unwrap_or(0), result.extras_summary())}
                                                     { if let Some(ref tools) = result.tools {
                                                         html! {
                                                             <ul class="discovered-tools">
@@ -593,6 +777,8 @@ pub fn mcp_connect() -> Html {
                             >
                                 { if *adding {
                                     html! { <><i class="fa-solid fa-spinner fa-spin"></i>{" Adding..."}</> }
+                                } else if *use_oauth {
+                                    html! { "Continue to Sign In" }
                                 } else {
                                     html! { "Add Server" }
                                 }}
@@ -692,6 +878,10 @@ pub fn mcp_connect() -> Html {
     border-radius: 4px;
     font-size: 0.75rem;
 }
+.auth-badge.pending {
+    background: rgba(232, 168, 56, 0.15);
+    color: #e8a838;
+}
 .server-actions {
     display: flex;
     align-items: center;
@@ -818,6 +1008,17 @@ input:checked + .toggle-slider:before {
     padding: 1rem;
     color: #888;
 }
+.notice-message {
+    background: rgba(34, 197, 94, 0.1);
+    border: 1px solid rgba(34, 197, 94, 0.3);
+    color: #22C55E;
+    padding: 0.75rem;
+    border-radius: 6px;
+    margin-bottom: 1rem;
+    display: flex;
+    justify-content: space-between;
+    align-items: center;
+}
 .error-message {
     background: rgba(239, 68, 68, 0.1);
     border: 1px solid rgba(239, 68, 68, 0.3);
@@ -903,6 +1104,15 @@ input:checked + .toggle-slider:before {
     font-size: 0.95rem;
     box-sizing: border-box;
 }
+.form-group.checkbox-group label {
+    display: flex;
+    align-items: center;
+    gap: 0.5rem;
+    cursor: pointer;
+}
+.form-group.checkbox-group input {
+    width: auto;
+}
 .form-group input:focus {
     outline: none;
     border-color: #8B5CF6;
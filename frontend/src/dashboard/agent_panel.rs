@@ -5,7 +5,29 @@ use wasm_bindgen_futures::{spawn_local, JsFuture};
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
-const SETUP_INSTRUCTION: &str = "Set up Lightfriend's write-only local CLI. Clone https://github.com/ahtavarasmus/lightfriend, run `cargo install --path lightfriend/lightfriend-cli`, then run `lightfriend login` in the local terminal and give me the pairing code to approve in Lightfriend Settings > Connections > Webhooks & API & CLI. Never paste or request a Lightfriend token in this chat, any prompt, or any URL. After login, use only `lightfriend remind --at <RFC3339 time with offset> --message <text>` or `lightfriend watch-reply --email <sender> --for-minutes <15-1440>`. This integration can create those outbound actions but cannot read conversations, contacts, history, reminders, or account data.";
+const SETUP_INSTRUCTION: &str = "Set up Lightfriend's scoped local CLI. Clone https://github.com/ahtavarasmus/lightfriend, run `cargo install --path lightfriend/lightfriend-cli`, then run `lightfriend login` in the local terminal and give me the pairing code to approve in Lightfriend Settings > Connections > Webhooks & API & CLI. Never paste or request a Lightfriend token in this chat, any prompt, or any URL. After login, run `lightfriend --help` to see the commands; only the permissions I approve will work (for example `lightfriend remind --at <RFC3339 time with offset> --message <text>` or `lightfriend watch-reply --email <sender> --for-minutes <15-1440>`). This integration cannot read conversations, contacts, or message history.";
+
+/// Scopes offered at approval: (name, label, on by default).
+const SCOPE_CHOICES: &[(&str, &str, bool)] = &[
+    (
+        "reminders:write",
+        "Create reminders, and list, snooze or cancel its own",
+        true,
+    ),
+    (
+        "reply_watch:write",
+        "Watch for email replies, and cancel its own watches",
+        true,
+    ),
+    ("events:read", "Read upcoming events and reminders", false),
+    (
+        "digest:read",
+        "Read digest summaries (no message bodies)",
+        false,
+    ),
+    ("sms:self", "Text me", false),
+    ("rules:toggle", "Pause and resume my rules", false),
+];
 
 const AGENT_STYLES: &str = r#"
 .agent-panel { color: #111; background: #fff; border: 1px solid #111; border-radius: 10px; overflow: hidden; }
@@ -20,6 +42,9 @@ const AGENT_STYLES: &str = r#"
 .agent-step code { padding: .05rem .25rem; border-radius: 3px; background: #ececec; color: #111; font-size: .7rem; }
 .agent-copy-button { justify-self: start; margin-top: .2rem; }
 .agent-code-label { color: #333; font-size: .7rem; font-weight: 600; }
+.agent-scopes { display: grid; gap: .3rem; margin: 0; padding: 0; border: 0; }
+.agent-scopes legend { color: #333; font-size: .7rem; font-weight: 600; padding: 0; margin-bottom: .2rem; }
+.agent-scope { display: flex; align-items: center; gap: .45rem; color: #111; font-size: .74rem; }
 .agent-code-form { display: flex; gap: .45rem; }
 .agent-code-form input { min-width: 0; flex: 1; min-height: 42px; border: 1px solid #111; border-radius: 6px; background: #fff; color: #111; padding: .55rem .65rem; font: 600 .8rem/1 monospace; text-transform: uppercase; letter-spacing: .04em; }
 .agent-button { min-height: 42px; padding: .55rem .75rem; border: 1px solid #111; border-radius: 6px; background: #111; color: #fff; font: 600 .76rem/1 sans-serif; cursor: pointer; }
@@ -52,6 +77,13 @@ struct CredentialSummary {
 pub fn agent_panel() -> Html {
     let credentials = use_state(Vec::<CredentialSummary>::new);
     let code = use_state(String::new);
+    let scopes = use_state(|| {
+        SCOPE_CHOICES
+            .iter()
+            .filter(|(_, _, default)| *default)
+            .map(|(name, _, _)| name.to_string())
+            .collect::<Vec<_>>()
+    });
     let busy = use_state(|| false);
     let message = use_state(|| None::<(bool, String)>);
     let copied = use_state(|| false);
@@ -90,8 +122,22 @@ pub fn agent_panel() -> Html {
         })
     };
 
+    let toggle_scope = {
+        let scopes = scopes.clone();
+        Callback::from(move |name: &'static str| {
+            let mut selected = (*scopes).clone();
+            if selected.iter().any(|scope| scope == name) {
+                selected.retain(|scope| scope != name);
+            } else {
+                selected.push(name.to_string());
+            }
+            scopes.set(selected);
+        })
+    };
+
     let approve = {
         let code = code.clone();
+        let scopes = scopes.clone();
         let busy = busy.clone();
         let message = message.clone();
         let refresh = refresh.clone();
@@ -100,15 +146,20 @@ pub fn agent_panel() -> Html {
             if *busy || code.trim().is_empty() {
                 return;
             }
+            if scopes.is_empty() {
+                message.set(Some((false, "Choose at least one permission.".to_string())));
+                return;
+            }
             busy.set(true);
             message.set(None);
             let entered = (*code).clone();
+            let granted = (*scopes).clone();
             let code = code.clone();
             let busy = busy.clone();
             let message = message.clone();
             let refresh = refresh.clone();
             spawn_local(async move {
-                let body = serde_json::json!({ "user_code": entered });
+                let body = serde_json::json!({ "user_code": entered, "scopes": granted });
                 let accepted = match Api::post("/api/me/agent-pairing/approve").json(&body) {
                     Ok(request) => matches!(request.send().await, Ok(response) if response.ok()),
                     Err(_) => false,
@@ -203,10 +254,10 @@ pub fn agent_panel() -> Html {
             <section class="agent-panel" aria-labelledby="agent-panel-title">
                 <header class="agent-panel-header">
                     <h4 id="agent-panel-title">{"Local agent CLI"}</h4>
-                    <p>{"Use Codex, Claude Code, or another local assistant to create a reminder or start a short email reply watch."}</p>
+                    <p>{"Use Codex, Claude Code, or another local assistant to create reminders, watch for email replies, or check what's coming up."}</p>
                     <div class="agent-boundary" role="note">
-                        <strong>{"Write-only by design. "}</strong>
-                        {"Agents can ask Lightfriend to perform these limited outbound actions. They cannot read conversations, contacts, message history, existing reminders, or account data."}
+                        <strong>{"Only what you approve. "}</strong>
+                        {"Each agent gets the permissions you tick when approving it, and every call is logged without its content. Agents can never read conversations, contacts, or message history."}
                     </div>
                 </header>
                 <div class="agent-body">
@@ -217,7 +268,21 @@ pub fn agent_panel() -> Html {
                     </div>
                     <form class="agent-step" onsubmit={approve}>
                         <strong>{"2. Approve the pairing code"}</strong>
-                        <p>{"Paste the code shown by "}<code>{"lightfriend login"}</code>{" in the local terminal. It expires after 10 minutes, works once, and is not the agent's credential."}</p>
+                        <p>{"Paste the code shown by "}<code>{"lightfriend login"}</code>{" in the local terminal and choose what the agent may do. It expires after 10 minutes, works once, and is not the agent's credential."}</p>
+                        <fieldset class="agent-scopes">
+                            <legend>{"Permissions"}</legend>
+                            {for SCOPE_CHOICES.iter().map(|(name, label, _)| {
+                                let checked = scopes.iter().any(|scope| scope == name);
+                                let toggle_scope = toggle_scope.clone();
+                                let name: &'static str = name;
+                                html! {
+                                    <label class="agent-scope">
+                                        <input type="checkbox" checked={checked} onchange={Callback::from(move |_| toggle_scope.emit(name))} />
+                                        {*label}
+                                    </label>
+                                }
+                            })}
+                        </fieldset>
                         <label class="agent-code-label" for="agent-pairing-code">{"Pairing code from the local terminal"}</label>
                         <div class="agent-code-form">
                             <input id="agent-pairing-code" value={(*code).clone()} oninput={on_code} maxlength="14" autocomplete="one-time-code" spellcheck="false" placeholder="ABCD-EFGH-JKLM" />
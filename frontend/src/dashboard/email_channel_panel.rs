@@ -0,0 +1,371 @@
+use crate::utils::api::Api;
+use serde::Deserialize;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+/// Notification categories the user can route: (request key, label).
+const CATEGORIES: &[(&str, &str)] = &[
+    ("critical", "Critical alerts"),
+    ("digest", "Digests"),
+    ("reminders", "Reminders & tracked items"),
+    ("rules", "Rules"),
+    ("other", "Everything else"),
+];
+
+const MODES: &[(&str, &str)] = &[("sms", "SMS"), ("email", "Email"), ("both", "Both")];
+
+/// Sender select value for the operator's relay.
+const RELAY: &str = "relay";
+
+const EMAIL_STYLES: &str = r#"
+.email-panel { color: #111; background: #fff; border: 1px solid #111; border-radius: 10px; overflow: hidden; }
+.email-panel-header { padding: 1rem; border-bottom: 1px solid #111; }
+.email-panel-header h4 { margin: 0; color: #111; font-size: 1rem; }
+.email-panel-header p { margin: .45rem 0 0; color: #333; font-size: .78rem; line-height: 1.5; }
+.email-body { padding: 1rem; display: grid; gap: 1rem; }
+.email-step { display: grid; gap: .45rem; }
+.email-step strong { color: #111; font-size: .8rem; }
+.email-step p { color: #444; font-size: .74rem; line-height: 1.45; margin: 0; overflow-wrap: anywhere; }
+.email-step select { min-height: 42px; border: 1px solid #111; border-radius: 6px; background: #fff; color: #111; padding: .45rem .6rem; font-size: .78rem; }
+.email-actions { display: flex; flex-wrap: wrap; gap: .45rem; }
+.email-button { min-height: 42px; padding: .55rem .75rem; border: 1px solid #111; border-radius: 6px; background: #111; color: #fff; font: 600 .76rem/1 sans-serif; cursor: pointer; }
+.email-button.secondary { background: #fff; color: #111; }
+.email-button:disabled { opacity: .5; cursor: wait; }
+.email-message { margin: 0; font-size: .74rem; color: #111; }
+.email-message.error { color: #9b111e; }
+.email-routes { display: grid; gap: .4rem; }
+.email-route { display: flex; justify-content: space-between; align-items: center; gap: .75rem; color: #111; font-size: .76rem; }
+.email-route select { min-height: 34px; padding: .3rem .5rem; font-size: .74rem; }
+"#;
+
+#[derive(Clone, Deserialize, PartialEq)]
+struct EmailDelivery {
+    critical: String,
+    digest: String,
+    reminders: String,
+    rules: String,
+    other: String,
+}
+
+impl EmailDelivery {
+    fn mode(&self, category: &str) -> &str {
+        match category {
+            "critical" => &self.critical,
+            "digest" => &self.digest,
+            "reminders" => &self.reminders,
+            "rules" => &self.rules,
+            _ => &self.other,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+struct EmailChannel {
+    address: String,
+    imap_connection_id: Option<i32>,
+    delivery: EmailDelivery,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+struct SenderAccount {
+    imap_connection_id: i32,
+    email: String,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+struct EmailChannelStatus {
+    channel: Option<EmailChannel>,
+    relay_available: bool,
+    allowed_addresses: Vec<String>,
+    sender_accounts: Vec<SenderAccount>,
+}
+
+async fn error_message(response: gloo_net::http::Response, fallback: &str) -> String {
+    response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|value| value["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+fn sender_value(imap_connection_id: Option<i32>) -> String {
+    imap_connection_id
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| RELAY.to_string())
+}
+
+#[function_component(EmailChannelPanel)]
+pub fn email_channel_panel() -> Html {
+    let status = use_state(|| None::<EmailChannelStatus>);
+    let address = use_state(String::new);
+    let sender = use_state(String::new);
+    let busy = use_state(|| false);
+    let message = use_state(|| None::<(bool, String)>);
+
+    {
+        let status = status.clone();
+        let address = address.clone();
+        let sender = sender.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    if let Ok(response) = Api::get("/api/me/email-channel").send().await {
+                        if response.ok() {
+                            if let Ok(current) = response.json::<EmailChannelStatus>().await {
+                                match current.channel.as_ref() {
+                                    Some(channel) => {
+                                        address.set(channel.address.clone());
+                                        sender.set(sender_value(channel.imap_connection_id));
+                                    }
+                                    None => {
+                                        address.set(
+                                            current
+                                                .allowed_addresses
+                                                .first()
+                                                .cloned()
+                                                .unwrap_or_default(),
+                                        );
+                                        sender.set(if current.relay_available {
+                                            RELAY.to_string()
+                                        } else {
+                                            current
+                                                .sender_accounts
+                                                .first()
+                                                .map(|account| {
+                                                    account.imap_connection_id.to_string()
+                                                })
+                                                .unwrap_or_default()
+                                        });
+                                    }
+                                }
+                                status.set(Some(current));
+                            }
+                        }
+                    }
+                });
+                || ()
+            },
+            (),
+        );
+    }
+
+    let on_select = |state: UseStateHandle<String>| {
+        Callback::from(move |event: Event| {
+            state.set(event.target_unchecked_into::<HtmlSelectElement>().value());
+        })
+    };
+
+    let set_channel = {
+        let status = status.clone();
+        Callback::from(move |channel: Option<EmailChannel>| {
+            if let Some(current) = (*status).clone() {
+                status.set(Some(EmailChannelStatus { channel, ..current }));
+            }
+        })
+    };
+
+    let save = {
+        let address = address.clone();
+        let sender = sender.clone();
+        let busy = busy.clone();
+        let message = message.clone();
+        let set_channel = set_channel.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            if *busy || address.is_empty() || sender.is_empty() {
+                return;
+            }
+            busy.set(true);
+            message.set(None);
+            let body = serde_json::json!({
+                "address": *address,
+                "imap_connection_id": sender.parse::<i32>().ok(),
+            });
+            let busy = busy.clone();
+            let message = message.clone();
+            let set_channel = set_channel.clone();
+            spawn_local(async move {
+                let response = match Api::put("/api/me/email-channel").json(&body) {
+                    Ok(request) => request.send().await.ok(),
+                    Err(_) => None,
+                };
+                match response {
+                    Some(response) if response.ok() => {
+                        if let Ok(current) = response.json::<EmailChannel>().await {
+                            set_channel.emit(Some(current));
+                        }
+                        message.set(Some((true, "Email address saved.".to_string())));
+                    }
+                    Some(response) => {
+                        let error =
+                            error_message(response, "Could not save the email address.").await;
+                        message.set(Some((false, error)));
+                    }
+                    None => message.set(Some((
+                        false,
+                        "Could not save the email address.".to_string(),
+                    ))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let set_mode = {
+        let message = message.clone();
+        let set_channel = set_channel.clone();
+        Callback::from(move |(category, mode): (&'static str, String)| {
+            let message = message.clone();
+            let set_channel = set_channel.clone();
+            let mut body = serde_json::Map::new();
+            body.insert(category.to_string(), serde_json::Value::String(mode));
+            spawn_local(async move {
+                let response = match Api::patch("/api/me/email-channel/delivery").json(&body) {
+                    Ok(request) => request.send().await.ok(),
+                    Err(_) => None,
+                };
+                match response {
+                    Some(response) if response.ok() => {
+                        if let Ok(current) = response.json::<EmailChannel>().await {
+                            set_channel.emit(Some(current));
+                        }
+                    }
+                    _ => message.set(Some((
+                        false,
+                        "Could not save the delivery choice.".to_string(),
+                    ))),
+                }
+            });
+        })
+    };
+
+    let send_test = {
+        let busy = busy.clone();
+        let message = message.clone();
+        Callback::from(move |_| {
+            if *busy {
+                return;
+            }
+            busy.set(true);
+            let busy = busy.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                match Api::post("/api/me/email-channel/test").send().await {
+                    Ok(response) if response.ok() => {
+                        message.set(Some((true, "Test email sent.".to_string())))
+                    }
+                    Ok(response) => {
+                        let error = error_message(response, "The test email failed.").await;
+                        message.set(Some((false, error)));
+                    }
+                    Err(_) => message.set(Some((false, "The test email failed.".to_string()))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let remove = {
+        let message = message.clone();
+        let set_channel = set_channel.clone();
+        Callback::from(move |_| {
+            let message = message.clone();
+            let set_channel = set_channel.clone();
+            spawn_local(async move {
+                match Api::delete("/api/me/email-channel").send().await {
+                    Ok(response) if response.ok() => {
+                        set_channel.emit(None);
+                        message.set(Some((true, "Email notifications removed.".to_string())));
+                    }
+                    _ => message.set(Some((
+                        false,
+                        "Could not remove email notifications.".to_string(),
+                    ))),
+                }
+            });
+        })
+    };
+
+    let Some(current) = (*status).as_ref() else {
+        return html! {};
+    };
+    let can_send = current.relay_available || !current.sender_accounts.is_empty();
+
+    html! {
+        <>
+            <style>{EMAIL_STYLES}</style>
+            <section class="email-panel" aria-labelledby="email-panel-title">
+                <header class="email-panel-header">
+                    <h4 id="email-panel-title">{"Email"}</h4>
+                    <p>{"Get digests and other notifications by email. Digests arrive as a formatted summary; pick per category whether email replaces SMS or comes with it."}</p>
+                </header>
+                <div class="email-body">
+                    if can_send {
+                        <form class="email-step" onsubmit={save}>
+                            <strong>{"Send to"}</strong>
+                            <select onchange={on_select(address.clone())} aria-label="Email address">
+                                {for current.allowed_addresses.iter().map(|option| html! {
+                                    <option value={option.clone()} selected={*address == *option}>{option}</option>
+                                })}
+                            </select>
+                            <strong>{"Send from"}</strong>
+                            <select onchange={on_select(sender.clone())} aria-label="Sender">
+                                if current.relay_available {
+                                    <option value={RELAY} selected={*sender == RELAY}>{"Lightfriend's mail server"}</option>
+                                }
+                                {for current.sender_accounts.iter().map(|account| {
+                                    let value = account.imap_connection_id.to_string();
+                                    html! {
+                                        <option value={value.clone()} selected={*sender == value}>{format!("My account: {}", account.email)}</option>
+                                    }
+                                })}
+                            </select>
+                            <p>{"Email only goes to your account address or a mail account you connected. Sending from your own account uses its SMTP server and shows up in its Sent folder."}</p>
+                            <div class="email-actions">
+                                <button class="email-button" type="submit" disabled={*busy}>{if current.channel.is_some() { "Update" } else { "Save" }}</button>
+                            </div>
+                        </form>
+                    } else {
+                        <p class="email-message">{"Connect a mail account first to send notifications from it."}</p>
+                    }
+                    if let Some((ok, text)) = (*message).as_ref() {
+                        <p class={classes!("email-message", (!*ok).then_some("error"))} role="status">{text}</p>
+                    }
+                    if let Some(channel) = current.channel.as_ref() {
+                        <div class="email-step">
+                            <strong>{"Where notifications go"}</strong>
+                            <div class="email-routes">
+                                {for CATEGORIES.iter().map(|(category, label)| {
+                                    let category: &'static str = category;
+                                    let selected = channel.delivery.mode(category).to_string();
+                                    let set_mode = set_mode.clone();
+                                    let onchange = Callback::from(move |event: Event| {
+                                        let mode = event.target_unchecked_into::<HtmlSelectElement>().value();
+                                        set_mode.emit((category, mode));
+                                    });
+                                    html! {
+                                        <label class="email-route">
+                                            {*label}
+                                            <select {onchange}>
+                                                {for MODES.iter().map(|(value, name)| html! {
+                                                    <option value={*value} selected={selected == *value}>{*name}</option>
+                                                })}
+                                            </select>
+                                        </label>
+                                    }
+                                })}
+                            </div>
+                            <p>{"Email-only notifications fall back to SMS if the mail server rejects them. \"Both\" here or in the Matrix settings keeps the SMS copy."}</p>
+                        </div>
+                        <div class="email-actions">
+                            <button class="email-button secondary" type="button" onclick={send_test} disabled={*busy}>{"Send test"}</button>
+                            <button class="email-button secondary" type="button" onclick={remove}>{"Remove"}</button>
+                        </div>
+                    }
+                </div>
+            </section>
+        </>
+    }
+}
@@ -0,0 +1,283 @@
+use crate::utils::api::Api;
+use serde::Deserialize;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Notification categories the user can route: (request key, label).
+const CATEGORIES: &[(&str, &str)] = &[
+    ("critical", "Critical alerts"),
+    ("digest", "Digests"),
+    ("reminders", "Reminders & tracked items"),
+    ("rules", "Rules"),
+    ("other", "Everything else"),
+];
+
+const MODES: &[(&str, &str)] = &[("sms", "SMS"), ("matrix", "Matrix"), ("both", "Both")];
+
+const MATRIX_STYLES: &str = r#"
+.matrix-panel { color: #111; background: #fff; border: 1px solid #111; border-radius: 10px; overflow: hidden; }
+.matrix-panel-header { padding: 1rem; border-bottom: 1px solid #111; }
+.matrix-panel-header h4 { margin: 0; color: #111; font-size: 1rem; }
+.matrix-panel-header p { margin: .45rem 0 0; color: #333; font-size: .78rem; line-height: 1.5; }
+.matrix-body { padding: 1rem; display: grid; gap: 1rem; }
+.matrix-step { display: grid; gap: .45rem; }
+.matrix-step strong { color: #111; font-size: .8rem; }
+.matrix-step p { color: #444; font-size: .74rem; line-height: 1.45; margin: 0; overflow-wrap: anywhere; }
+.matrix-step code { padding: .05rem .25rem; border-radius: 3px; background: #ececec; color: #111; font-size: .7rem; }
+.matrix-form { display: flex; gap: .45rem; }
+.matrix-form input { min-width: 0; flex: 1; min-height: 42px; border: 1px solid #111; border-radius: 6px; background: #fff; color: #111; padding: .55rem .65rem; font: 600 .8rem/1 monospace; }
+.matrix-button { min-height: 42px; padding: .55rem .75rem; border: 1px solid #111; border-radius: 6px; background: #111; color: #fff; font: 600 .76rem/1 sans-serif; cursor: pointer; }
+.matrix-button.secondary { background: #fff; color: #111; justify-self: start; }
+.matrix-button:disabled { opacity: .5; cursor: wait; }
+.matrix-message { margin: 0; font-size: .74rem; color: #111; }
+.matrix-message.error { color: #9b111e; }
+.matrix-routes { display: grid; gap: .4rem; }
+.matrix-route { display: flex; justify-content: space-between; align-items: center; gap: .75rem; color: #111; font-size: .76rem; }
+.matrix-route select { min-height: 34px; border: 1px solid #111; border-radius: 6px; background: #fff; color: #111; padding: .3rem .5rem; font-size: .74rem; }
+@media (max-width: 540px) { .matrix-form { flex-direction: column; } }
+"#;
+
+#[derive(Clone, Deserialize, PartialEq)]
+struct MatrixDelivery {
+    critical: String,
+    digest: String,
+    reminders: String,
+    rules: String,
+    other: String,
+}
+
+impl MatrixDelivery {
+    fn mode(&self, category: &str) -> &str {
+        match category {
+            "critical" => &self.critical,
+            "digest" => &self.digest,
+            "reminders" => &self.reminders,
+            "rules" => &self.rules,
+            _ => &self.other,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+struct MatrixChannel {
+    target_mxid: String,
+    joined: bool,
+    sender_mxid: Option<String>,
+    delivery: MatrixDelivery,
+}
+
+#[function_component(MatrixChannelPanel)]
+pub fn matrix_channel_panel() -> Html {
+    let channel = use_state(|| None::<MatrixChannel>);
+    let target = use_state(String::new);
+    let busy = use_state(|| false);
+    let message = use_state(|| None::<(bool, String)>);
+
+    let refresh = {
+        let channel = channel.clone();
+        Callback::from(move |_| {
+            let channel = channel.clone();
+            spawn_local(async move {
+                if let Ok(response) = Api::get("/api/me/matrix-channel").send().await {
+                    if response.ok() {
+                        if let Ok(current) = response.json::<Option<MatrixChannel>>().await {
+                            channel.set(current);
+                        }
+                    }
+                }
+            });
+        })
+    };
+
+    {
+        let refresh = refresh.clone();
+        use_effect_with_deps(
+            move |_| {
+                refresh.emit(());
+                || ()
+            },
+            (),
+        );
+    }
+
+    let on_target = {
+        let target = target.clone();
+        Callback::from(move |event: InputEvent| {
+            target.set(event.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let connect = {
+        let channel = channel.clone();
+        let target = target.clone();
+        let busy = busy.clone();
+        let message = message.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            if *busy || target.trim().is_empty() {
+                return;
+            }
+            busy.set(true);
+            message.set(None);
+            let body = serde_json::json!({ "target_mxid": target.trim() });
+            let channel = channel.clone();
+            let target = target.clone();
+            let busy = busy.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                let response = match Api::put("/api/me/matrix-channel").json(&body) {
+                    Ok(request) => request.send().await.ok(),
+                    Err(_) => None,
+                };
+                match response {
+                    Some(response) if response.ok() => {
+                        if let Ok(current) = response.json::<MatrixChannel>().await {
+                            channel.set(Some(current));
+                        }
+                        target.set(String::new());
+                        message.set(Some((
+                            true,
+                            "Invite sent. Accept it in your Matrix app.".to_string(),
+                        )));
+                    }
+                    Some(response) => {
+                        let error = response
+                            .json::<serde_json::Value>()
+                            .await
+                            .ok()
+                            .and_then(|value| value["error"].as_str().map(str::to_string))
+                            .unwrap_or_else(|| "Could not set up the Matrix room.".to_string());
+                        message.set(Some((false, error)));
+                    }
+                    None => message.set(Some((
+                        false,
+                        "Could not set up the Matrix room.".to_string(),
+                    ))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let set_mode = {
+        let channel = channel.clone();
+        let message = message.clone();
+        Callback::from(move |(category, mode): (&'static str, String)| {
+            let channel = channel.clone();
+            let message = message.clone();
+            let mut body = serde_json::Map::new();
+            body.insert(category.to_string(), serde_json::Value::String(mode));
+            spawn_local(async move {
+                let response = match Api::patch("/api/me/matrix-channel/delivery").json(&body) {
+                    Ok(request) => request.send().await.ok(),
+                    Err(_) => None,
+                };
+                match response {
+                    Some(response) if response.ok() => {
+                        if let Ok(current) = response.json::<MatrixChannel>().await {
+                            channel.set(Some(current));
+                        }
+                    }
+                    _ => message.set(Some((
+                        false,
+                        "Could not save the delivery choice.".to_string(),
+                    ))),
+                }
+            });
+        })
+    };
+
+    let disconnect = {
+        let channel = channel.clone();
+        let message = message.clone();
+        Callback::from(move |_| {
+            let confirmed = web_sys::window()
+                .and_then(|window| {
+                    window
+                        .confirm_with_message(
+                            "Disconnect Matrix? Lightfriend leaves the room and everything goes back to SMS.",
+                        )
+                        .ok()
+                })
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+            let channel = channel.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                match Api::delete("/api/me/matrix-channel").send().await {
+                    Ok(response) if response.ok() => {
+                        channel.set(None);
+                        message.set(Some((true, "Matrix disconnected.".to_string())));
+                    }
+                    _ => message.set(Some((false, "Could not disconnect Matrix.".to_string()))),
+                }
+            });
+        })
+    };
+
+    html! {
+        <>
+            <style>{MATRIX_STYLES}</style>
+            <section class="matrix-panel" aria-labelledby="matrix-panel-title">
+                <header class="matrix-panel-header">
+                    <h4 id="matrix-panel-title">{"Matrix"}</h4>
+                    <p>{"Talk to Lightfriend from Element, Beeper, or any Matrix app. Messages you send in the room work like texts, and you choose which notifications arrive there."}</p>
+                </header>
+                <div class="matrix-body">
+                    <form class="matrix-step" onsubmit={connect}>
+                        <strong>{if channel.is_some() { "Change Matrix ID" } else { "Your Matrix ID" }}</strong>
+                        <p>{"Lightfriend invites this account to a private room. Use an account on another homeserver, like "}<code>{"@you:matrix.org"}</code>{"."}</p>
+                        <div class="matrix-form">
+                            <input value={(*target).clone()} oninput={on_target} spellcheck="false" autocomplete="off" placeholder="@you:matrix.org" aria-label="Matrix ID" />
+                            <button class="matrix-button" type="submit" disabled={*busy}>{if *busy { "Inviting..." } else { "Send invite" }}</button>
+                        </div>
+                    </form>
+                    if let Some((ok, text)) = (*message).as_ref() {
+                        <p class={classes!("matrix-message", (!*ok).then_some("error"))} role="status">{text}</p>
+                    }
+                    if let Some(current) = (*channel).as_ref() {
+                        <div class="matrix-step">
+                            <strong>{"Room"}</strong>
+                            <p>
+                                {format!("Connected to {}", current.target_mxid)}
+                                if let Some(sender) = current.sender_mxid.as_ref() {
+                                    {format!(" from {}", sender)}
+                                }
+                                {if current.joined { ". Joined." } else { ". Waiting for you to accept the invite; until then everything arrives by SMS." }}
+                            </p>
+                        </div>
+                        <div class="matrix-step">
+                            <strong>{"Where notifications go"}</strong>
+                            <div class="matrix-routes">
+                                {for CATEGORIES.iter().map(|(category, label)| {
+                                    let category: &'static str = category;
+                                    let selected = current.delivery.mode(category).to_string();
+                                    let set_mode = set_mode.clone();
+                                    let onchange = Callback::from(move |event: Event| {
+                                        let mode = event.target_unchecked_into::<HtmlSelectElement>().value();
+                                        set_mode.emit((category, mode));
+                                    });
+                                    html! {
+                                        <label class="matrix-route">
+                                            {*label}
+                                            <select {onchange}>
+                                                {for MODES.iter().map(|(value, name)| html! {
+                                                    <option value={*value} selected={selected == *value}>{*name}</option>
+                                                })}
+                                            </select>
+                                        </label>
+                                    }
+                                })}
+                            </div>
+                            <p>{"Matrix-only notifications fall back to SMS if the room can't be reached. \"Both\" here or in the email settings keeps the SMS copy. Replies always come back where you wrote."}</p>
+                        </div>
+                        <button class="matrix-button secondary" type="button" onclick={disconnect}>{"Disconnect"}</button>
+                    }
+                </div>
+            </section>
+        </>
+    }
+}
@@ -0,0 +1,272 @@
+use crate::utils::api::Api;
+use serde::Deserialize;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const PUSH_STYLES: &str = r#"
+.push-panel { color: #111; background: #fff; border: 1px solid #111; border-radius: 10px; overflow: hidden; }
+.push-panel-header { padding: 1rem; border-bottom: 1px solid #111; }
+.push-panel-header h4 { margin: 0; color: #111; font-size: 1rem; }
+.push-panel-header p { margin: .45rem 0 0; color: #333; font-size: .78rem; line-height: 1.5; }
+.push-body { padding: 1rem; display: grid; gap: 1rem; }
+.push-step { display: grid; gap: .45rem; }
+.push-step strong { color: #111; font-size: .8rem; }
+.push-step p { color: #444; font-size: .74rem; line-height: 1.45; margin: 0; overflow-wrap: anywhere; }
+.push-step code { padding: .05rem .25rem; border-radius: 3px; background: #ececec; color: #111; font-size: .7rem; }
+.push-step input[type=text] { min-width: 0; min-height: 42px; border: 1px solid #111; border-radius: 6px; background: #fff; color: #111; padding: .55rem .65rem; font: 600 .8rem/1 monospace; }
+.push-keys summary { cursor: pointer; color: #111; font-size: .74rem; }
+.push-keys[open] { display: grid; gap: .45rem; }
+.push-actions { display: flex; flex-wrap: wrap; gap: .45rem; }
+.push-button { min-height: 42px; padding: .55rem .75rem; border: 1px solid #111; border-radius: 6px; background: #111; color: #fff; font: 600 .76rem/1 sans-serif; cursor: pointer; }
+.push-button.secondary { background: #fff; color: #111; }
+.push-button:disabled { opacity: .5; cursor: wait; }
+.push-message { margin: 0; font-size: .74rem; color: #111; }
+.push-message.error { color: #9b111e; }
+.push-route { display: flex; align-items: center; gap: .5rem; color: #111; font-size: .76rem; }
+"#;
+
+#[derive(Clone, Deserialize, PartialEq)]
+struct PushEndpoint {
+    endpoint: String,
+    encrypted: bool,
+    digests: bool,
+    rules: bool,
+}
+
+async fn error_message(response: gloo_net::http::Response, fallback: &str) -> String {
+    response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|value| value["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+#[function_component(PushChannelPanel)]
+pub fn push_channel_panel() -> Html {
+    let registration = use_state(|| None::<PushEndpoint>);
+    let endpoint = use_state(String::new);
+    let p256dh = use_state(String::new);
+    let auth = use_state(String::new);
+    let busy = use_state(|| false);
+    let message = use_state(|| None::<(bool, String)>);
+
+    {
+        let registration = registration.clone();
+        let endpoint = endpoint.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    if let Ok(response) = Api::get("/api/me/push-endpoint").send().await {
+                        if response.ok() {
+                            if let Ok(Some(current)) = response.json::<Option<PushEndpoint>>().await
+                            {
+                                endpoint.set(current.endpoint.clone());
+                                registration.set(Some(current));
+                            }
+                        }
+                    }
+                });
+                || ()
+            },
+            (),
+        );
+    }
+
+    let on_input = |state: UseStateHandle<String>| {
+        Callback::from(move |event: InputEvent| {
+            state.set(event.target_unchecked_into::<HtmlInputElement>().value());
+        })
+    };
+
+    let save = {
+        let registration = registration.clone();
+        let endpoint = endpoint.clone();
+        let p256dh = p256dh.clone();
+        let auth = auth.clone();
+        let busy = busy.clone();
+        let message = message.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            if *busy || endpoint.trim().is_empty() {
+                return;
+            }
+            busy.set(true);
+            message.set(None);
+            let optional = |value: &str| {
+                let value = value.trim();
+                (!value.is_empty()).then(|| value.to_string())
+            };
+            let body = serde_json::json!({
+                "endpoint": endpoint.trim(),
+                "p256dh": optional(&p256dh),
+                "auth": optional(&auth),
+            });
+            let registration = registration.clone();
+            let endpoint = endpoint.clone();
+            let busy = busy.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                let response = match Api::put("/api/me/push-endpoint").json(&body) {
+                    Ok(request) => request.send().await.ok(),
+                    Err(_) => None,
+                };
+                match response {
+                    Some(response) if response.ok() => {
+                        if let Ok(current) = response.json::<PushEndpoint>().await {
+                            endpoint.set(current.endpoint.clone());
+                            registration.set(Some(current));
+                        }
+                        message.set(Some((true, "Push endpoint saved.".to_string())));
+                    }
+                    Some(response) => {
+                        let error =
+                            error_message(response, "Could not save the push endpoint.").await;
+                        message.set(Some((false, error)));
+                    }
+                    None => message.set(Some((
+                        false,
+                        "Could not save the push endpoint.".to_string(),
+                    ))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let set_route = {
+        let registration = registration.clone();
+        let message = message.clone();
+        Callback::from(move |(key, enabled): (&'static str, bool)| {
+            let registration = registration.clone();
+            let message = message.clone();
+            let mut body = serde_json::Map::new();
+            body.insert(key.to_string(), serde_json::Value::Bool(enabled));
+            spawn_local(async move {
+                let response = match Api::patch("/api/me/push-endpoint").json(&body) {
+                    Ok(request) => request.send().await.ok(),
+                    Err(_) => None,
+                };
+                match response {
+                    Some(response) if response.ok() => {
+                        if let Ok(current) = response.json::<PushEndpoint>().await {
+                            registration.set(Some(current));
+                        }
+                    }
+                    _ => message.set(Some((
+                        false,
+                        "Could not save the routing choice.".to_string(),
+                    ))),
+                }
+            });
+        })
+    };
+
+    let send_test = {
+        let busy = busy.clone();
+        let message = message.clone();
+        Callback::from(move |_| {
+            if *busy {
+                return;
+            }
+            busy.set(true);
+            let busy = busy.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                match Api::post("/api/me/push-endpoint/test").send().await {
+                    Ok(response) if response.ok() => {
+                        message.set(Some((true, "Test notification sent.".to_string())))
+                    }
+                    Ok(response) => {
+                        let error = error_message(response, "The test push failed.").await;
+                        message.set(Some((false, error)));
+                    }
+                    Err(_) => message.set(Some((false, "The test push failed.".to_string()))),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let remove = {
+        let registration = registration.clone();
+        let endpoint = endpoint.clone();
+        let message = message.clone();
+        Callback::from(move |_| {
+            let registration = registration.clone();
+            let endpoint = endpoint.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                match Api::delete("/api/me/push-endpoint").send().await {
+                    Ok(response) if response.ok() => {
+                        registration.set(None);
+                        endpoint.set(String::new());
+                        message.set(Some((true, "Push endpoint removed.".to_string())));
+                    }
+                    _ => message.set(Some((
+                        false,
+                        "Could not remove the push endpoint.".to_string(),
+                    ))),
+                }
+            });
+        })
+    };
+
+    let route = |key: &'static str, label: &'static str, checked: bool| {
+        let set_route = set_route.clone();
+        let onchange = Callback::from(move |event: Event| {
+            let enabled = event.target_unchecked_into::<HtmlInputElement>().checked();
+            set_route.emit((key, enabled));
+        });
+        html! {
+            <label class="push-route">
+                <input type="checkbox" {checked} {onchange} />
+                {label}
+            </label>
+        }
+    };
+
+    html! {
+        <>
+            <style>{PUSH_STYLES}</style>
+            <section class="push-panel" aria-labelledby="push-panel-title">
+                <header class="push-panel-header">
+                    <h4 id="push-panel-title">{"Push notifications"}</h4>
+                    <p>{"Get digests and rule notifications through ntfy or any UnifiedPush app instead of SMS. If a push fails, the notification is sent by SMS."}</p>
+                </header>
+                <div class="push-body">
+                    <form class="push-step" onsubmit={save}>
+                        <strong>{"Endpoint"}</strong>
+                        <p>{"An ntfy topic like "}<code>{"https://ntfy.sh/your-secret-topic"}</code>{", or the endpoint your UnifiedPush app shows. Anyone who knows a plain ntfy topic can read it, so pick a long random name or add encryption keys."}</p>
+                        <input type="text" value={(*endpoint).clone()} oninput={on_input(endpoint.clone())} spellcheck="false" autocomplete="off" placeholder="https://ntfy.sh/your-secret-topic" aria-label="Push endpoint" />
+                        <details class="push-keys">
+                            <summary>{"Encryption keys (optional)"}</summary>
+                            <p>{"The p256dh and auth keys of a Web Push subscription. With them, the push server only sees ciphertext."}</p>
+                            <input type="text" value={(*p256dh).clone()} oninput={on_input(p256dh.clone())} spellcheck="false" autocomplete="off" placeholder="p256dh" aria-label="p256dh key" />
+                            <input type="text" value={(*auth).clone()} oninput={on_input(auth.clone())} spellcheck="false" autocomplete="off" placeholder="auth" aria-label="auth secret" />
+                        </details>
+                        <div class="push-actions">
+                            <button class="push-button" type="submit" disabled={*busy}>{if registration.is_some() { "Update" } else { "Save" }}</button>
+                        </div>
+                    </form>
+                    if let Some((ok, text)) = (*message).as_ref() {
+                        <p class={classes!("push-message", (!*ok).then_some("error"))} role="status">{text}</p>
+                    }
+                    if let Some(current) = (*registration).as_ref() {
+                        <div class="push-step">
+                            <strong>{"Send by push"}</strong>
+                            {route("digests", "Digests", current.digests)}
+                            {route("rules", "Rule notifications", current.rules)}
+                            <p>{if current.encrypted { "Payloads are end-to-end encrypted." } else { "Payloads are sent as plain text." }}</p>
+                        </div>
+                        <div class="push-actions">
+                            <button class="push-button secondary" type="button" onclick={send_test} disabled={*busy}>{"Send test"}</button>
+                            <button class="push-button secondary" type="button" onclick={remove}>{"Remove"}</button>
+                        </div>
+                    }
+                </div>
+            </section>
+        </>
+    }
+}
@@ -78,7 +78,19 @@ enum SourceConfig {
         tool: String,
         args: String,
     },
+    McpResource {
+        server: String,
+        uri: String,
+    },
     Events,
+    Calendar {
+        #[serde(default = "default_calendar_days")]
+        days: u32,
+    },
+}
+
+fn default_calendar_days() -> u32 {
+    1
 }
 
 // ---------------------------------------------------------------------------
@@ -100,13 +112,68 @@ pub enum FlowNode {
         true_branch: Box<Option<FlowNode>>,
         false_branch: Box<Option<FlowNode>>,
     },
+    StructuredCondition {
+        condition: Predicate,
+        true_branch: Box<Option<FlowNode>>,
+        false_branch: Box<Option<FlowNode>>,
+    },
     Action {
         action_type: String,
         config: serde_json::Value,
     },
+    Sequence {
+        steps: Vec<FlowNode>,
+    },
+    Parallel {
+        branches: Vec<FlowNode>,
+    },
+    Delay {
+        seconds: i64,
+        #[serde(default)]
+        next: Box<Option<FlowNode>>,
+    },
+    ForEach {
+        source: SourceConfig,
+        #[serde(default = "default_for_each_limit")]
+        limit: u32,
+        body: Box<Option<FlowNode>>,
+    },
+}
+
+fn default_for_each_limit() -> u32 {
+    10
 }
 
 impl FlowNode {
+    fn children(&self) -> Vec<&FlowNode> {
+        match self {
+            FlowNode::LlmCondition {
+                true_branch,
+                false_branch,
+                ..
+            }
+            | FlowNode::KeywordCondition {
+                true_branch,
+                false_branch,
+                ..
+            }
+            | FlowNode::StructuredCondition {
+                true_branch,
+                false_branch,
+                ..
+            } => true_branch
+                .as_ref()
+                .iter()
+                .chain(false_branch.as_ref().iter())
+                .collect(),
+            FlowNode::Action { .. } => Vec::new(),
+            FlowNode::Sequence { steps } => steps.iter().collect(),
+            FlowNode::Parallel { branches } => branches.iter().collect(),
+            FlowNode::Delay { next, .. } => next.as_ref().iter().collect(),
+            FlowNode::ForEach { body, .. } => body.as_ref().iter().collect(),
+        }
+    }
+
     fn condition_depth(&self) -> usize {
         match self {
             FlowNode::LlmCondition {
@@ -118,6 +185,11 @@ impl FlowNode {
                 true_branch,
                 false_branch,
                 ..
+            }
+            | FlowNode::StructuredCondition {
+                true_branch,
+                false_branch,
+                ..
             } => {
                 let t = true_branch
                     .as_ref()
@@ -132,10 +204,297 @@ impl FlowNode {
                 1 + t.max(f)
             }
             FlowNode::Action { .. } => 0,
+            FlowNode::Sequence { .. }
+            | FlowNode::Parallel { .. }
+            | FlowNode::Delay { .. }
+            | FlowNode::ForEach { .. } => self
+                .children()
+                .into_iter()
+                .map(|n| n.condition_depth())
+                .max()
+                .unwrap_or(0),
         }
     }
 }
 
+// ---------------------------------------------------------------------------
+// Predicate: deterministic checks for structured_condition nodes
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Predicate {
+    Sender {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        person_id: Option<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        room_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    Platform {
+        platforms: Vec<String>,
+    },
+    IsGroup {
+        value: bool,
+    },
+    TimeWindow {
+        start: String,
+        end: String,
+    },
+    DayOfWeek {
+        days: Vec<String>,
+    },
+    Regex {
+        pattern: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        field: Option<String>,
+    },
+    Numeric {
+        /// "tesla_battery" or "weather_temperature"
+        metric: String,
+        /// "lt", "le", "gt", "ge", "eq" or "ne"
+        op: String,
+        value: f64,
+        #[serde(default)]
+        location: String,
+    },
+    And {
+        conditions: Vec<Predicate>,
+    },
+    Or {
+        conditions: Vec<Predicate>,
+    },
+    Not {
+        condition: Box<Predicate>,
+    },
+}
+
+const CHECK_DAYS: [(&str, &str); 7] = [
+    ("mon", "Mon"),
+    ("tue", "Tue"),
+    ("wed", "Wed"),
+    ("thu", "Thu"),
+    ("fri", "Fri"),
+    ("sat", "Sat"),
+    ("sun", "Sun"),
+];
+
+const CHECK_OPS: [(&str, &str); 6] = [
+    ("lt", "below"),
+    ("le", "at most"),
+    ("gt", "above"),
+    ("ge", "at least"),
+    ("eq", "exactly"),
+    ("ne", "not"),
+];
+
+impl Predicate {
+    fn describe(&self) -> String {
+        match self {
+            Predicate::Sender {
+                person_id, name, ..
+            } => match (name, person_id) {
+                (Some(n), _) => format!("from {}", n),
+                (None, Some(id)) => format!("from contact #{}", id),
+                (None, None) => "from a specific room".to_string(),
+            },
+            Predicate::Platform { platforms } => format!(
+                "on {}",
+                platforms
+                    .iter()
+                    .map(|p| capitalize_first(p))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ),
+            Predicate::IsGroup { value: true } => "in a group chat".to_string(),
+            Predicate::IsGroup { value: false } => "in a direct chat".to_string(),
+            Predicate::TimeWindow { start, end } => format!("between {} and {}", start, end),
+            Predicate::DayOfWeek { days } => format!("on {}", days.join(", ")),
+            Predicate::Regex { pattern, field } => match field {
+                Some(f) => format!("{} matches /{}/", f, pattern),
+                None => format!("message matches /{}/", pattern),
+            },
+            Predicate::Numeric {
+                metric, op, value, ..
+            } => {
+                let what = match metric.as_str() {
+                    "tesla_battery" => "battery",
+                    _ => "temperature",
+                };
+                let unit = match metric.as_str() {
+                    "tesla_battery" => "%",
+                    _ => "°C",
+                };
+                let op_text = CHECK_OPS
+                    .iter()
+                    .find(|(v, _)| v == op)
+                    .map(|(_, l)| *l)
+                    .unwrap_or("is");
+                format!("{} {} {}{}", what, op_text, value, unit)
+            }
+            Predicate::And { conditions } => conditions
+                .iter()
+                .map(|c| c.describe())
+                .collect::<Vec<_>>()
+                .join(" and "),
+            Predicate::Or { conditions } => conditions
+                .iter()
+                .map(|c| c.describe())
+                .collect::<Vec<_>>()
+                .join(" or "),
+            Predicate::Not { condition } => format!("not ({})", condition.describe()),
+        }
+    }
+
+    /// Default predicate for a check type picked in the builder.
+    fn new_check(kind: &str) -> Option<Predicate> {
+        Some(match kind {
+            "day_of_week" => Predicate::DayOfWeek {
+                days: vec!["weekdays".to_string()],
+            },
+            "time_window" => Predicate::TimeWindow {
+                start: "18:00".to_string(),
+                end: "23:00".to_string(),
+            },
+            "platform" => Predicate::Platform {
+                platforms: vec!["whatsapp".to_string()],
+            },
+            "is_group" => Predicate::IsGroup { value: false },
+            "sender" => Predicate::Sender {
+                person_id: None,
+                room_id: None,
+                name: Some(String::new()),
+            },
+            "regex" => Predicate::Regex {
+                pattern: String::new(),
+                field: None,
+            },
+            "tesla_battery" | "weather_temperature" => Predicate::Numeric {
+                metric: kind.to_string(),
+                op: "lt".to_string(),
+                value: if kind == "tesla_battery" { 30.0 } else { 0.0 },
+                location: String::new(),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Combine the builder's flat list of checks into one predicate.
+fn combine_checks(checks: &[Predicate], match_any: bool) -> Option<Predicate> {
+    match checks {
+        [] => None,
+        [single] => Some(single.clone()),
+        _ if match_any => Some(Predicate::Or {
+            conditions: checks.to_vec(),
+        }),
+        _ => Some(Predicate::And {
+            conditions: checks.to_vec(),
+        }),
+    }
+}
+
+/// Inverse of `combine_checks`: a top-level AND/OR becomes the list.
+fn split_checks(predicate: &Predicate) -> (Vec<Predicate>, bool) {
+    match predicate {
+        Predicate::And { conditions } => (conditions.clone(), false),
+        Predicate::Or { conditions } => (conditions.clone(), true),
+        other => (vec![other.clone()], false),
+    }
+}
+
+/// Wrap the THEN action in the optional "repeat for each" loop and delay
+/// picked in the builder: Delay { ForEach { action } }.
+fn wrap_then_node(
+    action: serde_json::Value,
+    delay_seconds: i64,
+    repeat: &Option<SourceConfig>,
+) -> serde_json::Value {
+    let mut node = action;
+    if let Some(source) = repeat {
+        node = serde_json::json!({
+            "type": "for_each",
+            "source": source,
+            "limit": default_for_each_limit(),
+            "body": node,
+        });
+    }
+    if delay_seconds > 0 {
+        node = serde_json::json!({
+            "type": "delay",
+            "seconds": delay_seconds,
+            "next": node,
+        });
+    }
+    node
+}
+
+/// Inverse of `wrap_then_node`: peel the builder's delay and loop off a
+/// THEN node. Returns (delay_seconds, repeat_source, leaf). The leaf is not
+/// an Action when the flow was built elsewhere (e.g. a sequence).
+fn unwrap_then_node(node: &FlowNode) -> (i64, Option<SourceConfig>, FlowNode) {
+    let mut delay = 0;
+    let mut repeat = None;
+    let mut cur = node;
+    if let FlowNode::Delay { seconds, next } = cur {
+        if let Some(inner) = next.as_ref() {
+            delay = *seconds;
+            cur = inner;
+        }
+    }
+    if let FlowNode::ForEach { source, body, .. } = cur {
+        if let Some(inner) = body.as_ref() {
+            repeat = Some(source.clone());
+            cur = inner;
+        }
+    }
+    (delay, repeat, cur.clone())
+}
+
+fn format_delay(seconds: i64) -> String {
+    if seconds >= 86400 && seconds % 86400 == 0 {
+        let days = seconds / 86400;
+        format!("{} day{}", days, if days == 1 { "" } else { "s" })
+    } else if seconds >= 3600 && seconds % 3600 == 0 {
+        let hours = seconds / 3600;
+        format!("{} hour{}", hours, if hours == 1 { "" } else { "s" })
+    } else {
+        format!("{} min", (seconds / 60).max(1))
+    }
+}
+
+fn describe_repeat_source(source: &SourceConfig) -> String {
+    match source {
+        SourceConfig::Email => "each recent email".to_string(),
+        SourceConfig::Chat { .. } => "each recent chat message".to_string(),
+        SourceConfig::Events => "each tracked obligation".to_string(),
+        SourceConfig::Calendar { .. } => "each upcoming calendar event".to_string(),
+        SourceConfig::Weather { .. } => "the weather".to_string(),
+        SourceConfig::Internet { query } => format!("each result for '{}'", query),
+        SourceConfig::Tesla => "the Tesla status".to_string(),
+        SourceConfig::Mcp { tool, .. } => format!("each {} result", tool),
+        SourceConfig::McpResource { uri, .. } => format!("each item in {}", uri),
+    }
+}
+
+/// "after 2 hours, for each tracked obligation, " style prefix for the
+/// THEN summary. Empty when the action runs immediately and once.
+fn then_modifier_text(delay_seconds: i64, repeat: &Option<SourceConfig>) -> String {
+    let mut parts = Vec::new();
+    if delay_seconds > 0 {
+        parts.push(format!("after {}", format_delay(delay_seconds)));
+    }
+    if let Some(source) = repeat {
+        parts.push(format!("for {}", describe_repeat_source(source)));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{}, ", parts.join(", "))
+    }
+}
+
 impl SourceConfig {
     fn type_key(&self) -> &str {
         match self {
@@ -144,8 +503,11 @@ impl SourceConfig {
             SourceConfig::Weather { .. } => "weather",
             SourceConfig::Internet { .. } => "internet",
             SourceConfig::Tesla => "tesla",
-            SourceConfig::Mcp { server, .. } => server.as_str(),
+            SourceConfig::Mcp { server, .. } | SourceConfig::McpResource { server, .. } => {
+                server.as_str()
+            }
             SourceConfig::Events => "events",
+            SourceConfig::Calendar { .. } => "calendar",
         }
     }
 
@@ -156,8 +518,11 @@ impl SourceConfig {
             SourceConfig::Weather { .. } => t == "weather",
             SourceConfig::Internet { .. } => t == "internet",
             SourceConfig::Tesla => t == "tesla",
-            SourceConfig::Mcp { server, .. } => t == format!("mcp:{}", server),
+            SourceConfig::Mcp { server, .. } | SourceConfig::McpResource { server, .. } => {
+                t == format!("mcp:{}", server)
+            }
             SourceConfig::Events => t == "events",
+            SourceConfig::Calendar { .. } => t == "calendar",
         }
     }
 }
@@ -169,6 +534,12 @@ struct McpToolOption {
     description: Option<String>,
 }
 
+#[derive(Clone, PartialEq, Deserialize)]
+struct McpResourceOption {
+    uri: String,
+    name: String,
+}
+
 const BUILDER_STYLES: &str = r#"
 .rule-builder-overlay {
     position: fixed;
@@ -1241,12 +1612,16 @@ enum RecurringFreq {
     Weekdays,
     Weekly,
     Hourly,
+    /// Free-form RRULE or cron pattern, validated by the backend.
+    Custom,
 }
 
 #[derive(Clone, PartialEq)]
 enum LogicMode {
     Always,
     Keyword,
+    /// Deterministic checks (structured_condition), no AI involved.
+    Structured,
     Llm,
 }
 
@@ -1303,6 +1678,9 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
     let recurring_freq = use_state(|| RecurringFreq::Daily);
     let recurring_time = use_state(|| "09:00".to_string());
     let recurring_day = use_state(|| "monday".to_string());
+    let recurring_custom = use_state(|| String::new());
+    // Next fire times (or parser error) for the custom pattern preview.
+    let schedule_preview = use_state(|| None::<Result<Vec<String>, String>>);
     let event_entity = use_state(|| "Message".to_string());
     let event_change = use_state(|| "created".to_string());
     let event_filter_key = use_state(|| "sender".to_string());
@@ -1332,9 +1710,14 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
     let active_sources = use_state(|| Vec::<SourceConfig>::new());
     let expanded_source = use_state(|| None::<String>);
     let mcp_source_tools = use_state(|| HashMap::<String, Vec<McpToolOption>>::new());
+    let mcp_source_resources = use_state(|| HashMap::<String, Vec<McpResourceOption>>::new());
     let selected_template = use_state(|| PromptTemplate::CheckCondition);
     let condition_input = use_state(|| String::new());
     let keyword_input = use_state(|| String::new());
+    // Checks for LogicMode::Structured, combined with AND (or OR when
+    // `structured_any` is set).
+    let structured_checks = use_state(|| Vec::<Predicate>::new());
+    let structured_any = use_state(|| false);
 
     // THEN state
     let action_mode = use_state(|| ActionMode::Notify);
@@ -1371,6 +1754,14 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
     // ELSE branch state (nested conditions)
     let else_flow = use_state(|| None::<FlowNode>);
 
+    // THEN modifiers: wait before acting (seconds, 0 = immediately) and
+    // repeat the action for each item of a source.
+    let then_delay = use_state(|| 0i64);
+    let then_repeat = use_state(|| None::<SourceConfig>);
+    // A THEN flow the builder can't edit (sequence/parallel built via the
+    // API). Kept verbatim on save unless the user replaces it.
+    let then_advanced = use_state(|| None::<FlowNode>);
+
     let saving = use_state(|| false);
     let error_msg = use_state(|| None::<String>);
 
@@ -1378,6 +1769,9 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
     let test_open = use_state(|| false);
     let test_message = use_state(|| String::new());
     let test_sender = use_state(|| "Test Sender".to_string());
+    // Snapshot fields for structured checks ("" = not set).
+    let test_platform = use_state(|| String::new());
+    let test_is_group = use_state(|| false);
     let test_running = use_state(|| false);
     let test_steps = use_state(|| Vec::<(String, String, String)>::new()); // (css_class, icon, text)
     let test_es_ref = use_mut_ref(|| None::<web_sys::EventSource>);
@@ -1627,6 +2021,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         let recurring_freq = recurring_freq.clone();
         let recurring_time = recurring_time.clone();
         let recurring_day = recurring_day.clone();
+        let recurring_custom = recurring_custom.clone();
         let event_entity = event_entity.clone();
         let event_change = event_change.clone();
         let event_filter_key = event_filter_key.clone();
@@ -1656,7 +2051,12 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         let selected_template = selected_template.clone();
         let condition_input = condition_input.clone();
         let keyword_input = keyword_input.clone();
+        let structured_checks = structured_checks.clone();
+        let structured_any = structured_any.clone();
         let else_flow_init = else_flow.clone();
+        let then_delay_init = then_delay.clone();
+        let then_repeat_init = then_repeat.clone();
+        let then_advanced_init = then_advanced.clone();
         let user_touched_form = user_touched_form.clone();
         let selected_group_mode = selected_group_mode.clone();
 
@@ -1670,16 +2070,43 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                     name.set(rule.name.clone());
                     expanded_card.set(None); // all collapsed in view mode
 
-                    // Parse else branch from flow_config if present
+                    // Parse else branch and THEN modifiers from flow_config if present
+                    then_delay_init.set(0);
+                    then_repeat_init.set(None);
+                    then_advanced_init.set(None);
                     if let Some(ref fc) = rule.flow_config {
                         if let Ok(node) = serde_json::from_str::<FlowNode>(fc) {
-                            match &node {
-                                FlowNode::LlmCondition { false_branch, .. }
-                                | FlowNode::KeywordCondition { false_branch, .. } => {
+                            let then_node = match &node {
+                                FlowNode::LlmCondition {
+                                    true_branch,
+                                    false_branch,
+                                    ..
+                                }
+                                | FlowNode::KeywordCondition {
+                                    true_branch,
+                                    false_branch,
+                                    ..
+                                }
+                                | FlowNode::StructuredCondition {
+                                    true_branch,
+                                    false_branch,
+                                    ..
+                                } => {
                                     else_flow_init.set(false_branch.as_ref().clone());
+                                    true_branch.as_ref().clone()
                                 }
-                                _ => {
+                                other => {
                                     else_flow_init.set(None);
+                                    Some(other.clone())
+                                }
+                            };
+                            if let Some(then_node) = then_node {
+                                let (delay, repeat, leaf) = unwrap_then_node(&then_node);
+                                if matches!(leaf, FlowNode::Action { .. }) {
+                                    then_delay_init.set(delay);
+                                    then_repeat_init.set(repeat);
+                                } else {
+                                    then_advanced_init.set(Some(then_node));
                                 }
                             }
                         } else {
@@ -1712,6 +2139,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                         &recurring_freq,
                                         &recurring_time,
                                         &recurring_day,
+                                        &recurring_custom,
                                     );
                                 }
                             }
@@ -1836,6 +2264,20 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                     } else if rule.logic_type == "keyword" {
                         logic_mode.set(LogicMode::Keyword);
                         keyword_input.set(rule.logic_prompt.clone().unwrap_or_default());
+                    } else if rule.logic_type == "structured" {
+                        logic_mode.set(LogicMode::Structured);
+                        let root = rule
+                            .flow_config
+                            .as_ref()
+                            .and_then(|fc| serde_json::from_str::<FlowNode>(fc).ok());
+                        let (checks, any) = match root {
+                            Some(FlowNode::StructuredCondition { condition, .. }) => {
+                                split_checks(&condition)
+                            }
+                            _ => (Vec::new(), false),
+                        };
+                        structured_checks.set(checks);
+                        structured_any.set(any);
                     } else {
                         logic_mode.set(LogicMode::Always);
                     }
@@ -1932,6 +2374,8 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                     selected_template.set(PromptTemplate::CheckCondition);
                     condition_input.set(String::new());
                     keyword_input.set(String::new());
+                    structured_checks.set(Vec::new());
+                    structured_any.set(false);
                     once_date.set(String::new());
                     once_time.set(String::new());
                     event_filter_value.set(String::new());
@@ -1948,6 +2392,9 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                     tc_tesla_cmd.set("lock".to_string());
                     tc_mcp_params.set(HashMap::new());
                     else_flow_init.set(None);
+                    then_delay_init.set(0);
+                    then_repeat_init.set(None);
+                    then_advanced_init.set(None);
                     error_msg_init.set(None);
                 }
                 || ()
@@ -1978,12 +2425,18 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         let tool_name = tool_name.clone();
         let expanded_card = expanded_card.clone();
         let else_flow = else_flow.clone();
+        let then_delay = then_delay.clone();
+        let then_repeat = then_repeat.clone();
+        let then_advanced = then_advanced.clone();
         let event_delay = event_delay.clone();
 
         use_effect_with_deps(
             move |tmpl: &Option<RuleTemplate>| {
                 if !is_editing {
                     if let Some(template) = tmpl {
+                        then_delay.set(0);
+                        then_repeat.set(None);
+                        then_advanced.set(None);
                         match template {
                             RuleTemplate::CriticalMessages => {
                                 name.set("Critical messages".to_string());
@@ -2102,6 +2555,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                         capitalize_first(&recurring_day),
                         format_time_display(&recurring_time)
                     ),
+                    RecurringFreq::Custom => "on a custom schedule".to_string(),
                 },
             },
             WhenMode::Event => {
@@ -2118,8 +2572,21 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         }
     };
 
+    let structured_condition = combine_checks(&structured_checks, *structured_any);
+
     let if_summary = match *logic_mode {
         LogicMode::Always => "always run".to_string(),
+        LogicMode::Structured => match structured_condition {
+            Some(ref p) => {
+                let d = p.describe();
+                if d.chars().count() > 30 {
+                    format!("{}...", d.chars().take(30).collect::<String>())
+                } else {
+                    d
+                }
+            }
+            None => "checks".to_string(),
+        },
         LogicMode::Keyword => {
             let k = (*keyword_input).clone();
             if k.is_empty() {
@@ -2158,7 +2625,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         },
     };
 
-    let then_summary = match *action_mode {
+    let then_action_summary = match *action_mode {
         ActionMode::Notify => match *notify_method {
             NotifyMethod::Sms => "notify via SMS".to_string(),
             NotifyMethod::Call => "notify via call".to_string(),
@@ -2196,6 +2663,15 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
             }
         }
     };
+    let then_summary = if then_advanced.is_some() {
+        "multi-step flow".to_string()
+    } else {
+        format!(
+            "{}{}",
+            then_modifier_text(*then_delay, &*then_repeat),
+            then_action_summary
+        )
+    };
 
     // Card toggle
     let toggle_card = {
@@ -2288,6 +2764,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         let recurring_freq = recurring_freq.clone();
         let recurring_time = recurring_time.clone();
         let recurring_day = recurring_day.clone();
+        let recurring_custom = recurring_custom.clone();
         let event_entity = event_entity.clone();
         let event_change = event_change.clone();
         let event_filter_key = event_filter_key.clone();
@@ -2300,6 +2777,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         let selected_template = selected_template.clone();
         let condition_input = condition_input.clone();
         let keyword_input = keyword_input.clone();
+        let structured_condition = structured_condition.clone();
         let action_mode = action_mode.clone();
         let notify_method = notify_method.clone();
         let notify_message = notify_message.clone();
@@ -2315,6 +2793,9 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         let tc_tesla_cmd = tc_tesla_cmd.clone();
         let tc_mcp_params = tc_mcp_params.clone();
         let else_flow_submit = else_flow.clone();
+        let then_delay_submit = then_delay.clone();
+        let then_repeat_submit = then_repeat.clone();
+        let then_advanced_submit = then_advanced.clone();
         let selected_group_mode = selected_group_mode.clone();
         let selected_contact = selected_contact.clone();
         let saving = saving.clone();
@@ -2340,6 +2821,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                 RecurringFreq::Weekly => {
                                     format!("weekly {} {}", *recurring_day, *recurring_time)
                                 }
+                                RecurringFreq::Custom => recurring_custom.trim().to_string(),
                             };
                             serde_json::json!({ "schedule": "recurring", "pattern": pattern })
                         }
@@ -2388,6 +2870,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
             let logic_type = match *logic_mode {
                 LogicMode::Always => "passthrough",
                 LogicMode::Keyword => "keyword",
+                LogicMode::Structured => "structured",
                 LogicMode::Llm => "llm",
             };
             let lp = match *logic_mode {
@@ -2504,11 +2987,18 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
             // Build flow_config from logic + action + else branch
             let action_config_json: serde_json::Value =
                 serde_json::from_str(&action_config).unwrap_or_default();
-            let action_node = serde_json::json!({
-                "type": "action",
-                "action_type": action_type,
-                "config": action_config_json
-            });
+            let action_node = match &*then_advanced_submit {
+                Some(node) => serde_json::to_value(node).unwrap_or(serde_json::Value::Null),
+                None => wrap_then_node(
+                    serde_json::json!({
+                        "type": "action",
+                        "action_type": action_type,
+                        "config": action_config_json
+                    }),
+                    *then_delay_submit,
+                    &*then_repeat_submit,
+                ),
+            };
             let else_branch_json: serde_json::Value = match &*else_flow_submit {
                 Some(node) => serde_json::to_value(node).unwrap_or(serde_json::Value::Null),
                 None => serde_json::Value::Null,
@@ -2535,6 +3025,14 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                         "false_branch": else_branch_json
                     })
                 }
+                "structured" => {
+                    serde_json::json!({
+                        "type": "structured_condition",
+                        "condition": structured_condition,
+                        "true_branch": action_node,
+                        "false_branch": else_branch_json
+                    })
+                }
                 _ => action_node, // passthrough
             };
 
@@ -2654,6 +3152,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
         &*logic_prompt,
         &*condition_input,
         &*keyword_input,
+        &*structured_checks,
     );
 
     // Strict-combobox guards: the autocomplete IS the correctness layer —
@@ -2719,12 +3218,15 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                             &*logic_prompt,
                             &*condition_input,
                             &*keyword_input,
+                            &structured_condition,
                             &*action_mode,
                             &*notify_method,
                             &*tool_name,
                             &*tc_platform,
                             &*tc_chat_name,
                             &*tc_tesla_cmd,
+                            &then_modifier_text(*then_delay, &*then_repeat),
+                            &*then_advanced,
                             &*else_flow,
                         )}
 
@@ -2818,6 +3320,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                         (RecurringFreq::Daily, "Daily"),
                                                         (RecurringFreq::Weekdays, "Weekdays"),
                                                         (RecurringFreq::Weekly, "Weekly"),
+                                                        (RecurringFreq::Custom, "Custom"),
                                                     ].iter().map(|(freq, label)| {
                                                         let is_active = *recurring_freq == *freq;
                                                         let freq_clone = freq.clone();
@@ -2832,7 +3335,59 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                 </div>
                                             </div>
 
-                                            if *recurring_freq != RecurringFreq::Hourly {
+                                            if *recurring_freq == RecurringFreq::Custom {
+                                                <div class="rb-field">
+                                                    <div class="rb-field-label">{"Pattern (RRULE or cron)"}</div>
+                                                    <div class="rb-row">
+                                                        <input
+                                                            class="rb-input"
+                                                            type="text"
+                                                            placeholder="RRULE:FREQ=MONTHLY;BYDAY=1MO;BYHOUR=9 or */15 9-16 * * 1-5"
+                                                            value={(*recurring_custom).clone()}
+                                                            oninput={{
+                                                                let s = recurring_custom.clone();
+                                                                let p = schedule_preview.clone();
+                                                                Callback::from(move |e: InputEvent| {
+                                                                    if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                                                                        s.set(input.value());
+                                                                        p.set(None);
+                                                                    }
+                                                                })
+                                                            }}
+                                                        />
+                                                        <button
+                                                            class="rb-toggle-btn"
+                                                            onclick={{
+                                                                let pattern = (*recurring_custom).clone();
+                                                                let p = schedule_preview.clone();
+                                                                Callback::from(move |_: MouseEvent| {
+                                                                    let pattern = pattern.clone();
+                                                                    let p = p.clone();
+                                                                    spawn_local(async move {
+                                                                        p.set(Some(fetch_schedule_preview(&pattern).await));
+                                                                    });
+                                                                })
+                                                            }}
+                                                        >{"Preview"}</button>
+                                                    </div>
+                                                    {match &*schedule_preview {
+                                                        Some(Ok(times)) if times.is_empty() => html! {
+                                                            <div class="rb-field-hint">{"This pattern never fires again."}</div>
+                                                        },
+                                                        Some(Ok(times)) => html! {
+                                                            <div class="rb-field-hint">
+                                                                {"Next: "}{times.join(" · ")}
+                                                            </div>
+                                                        },
+                                                        Some(Err(e)) => html! {
+                                                            <div class="rb-field-hint" style="color: #d33;">{e.clone()}</div>
+                                                        },
+                                                        None => html! {},
+                                                    }}
+                                                </div>
+                                            }
+
+                                            if *recurring_freq != RecurringFreq::Hourly && *recurring_freq != RecurringFreq::Custom {
                                                 <div class="rb-row">
                                                     if *recurring_freq == RecurringFreq::Weekly {
                                                         <div class="rb-field">
@@ -3185,6 +3740,13 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                 Callback::from(move |_: MouseEvent| lm.set(LogicMode::Keyword))
                                             }}
                                         >{"Keyword"}</button>
+                                        <button
+                                            class={classes!("rb-toggle-btn", (*logic_mode == LogicMode::Structured).then(|| "active"))}
+                                            onclick={{
+                                                let lm = logic_mode.clone();
+                                                Callback::from(move |_: MouseEvent| lm.set(LogicMode::Structured))
+                                            }}
+                                        >{"Checks"}</button>
                                         <button
                                             class={classes!("rb-toggle-btn", (*logic_mode == LogicMode::Llm).then(|| "active"))}
                                             disabled={!is_autopilot || selected_group_mode.is_some()}
@@ -3200,6 +3762,20 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                             "AI decides (Autopilot)"
                                         }}</button>
                                     </div>
+                                    if *logic_mode == LogicMode::Structured {
+                                        <StructuredChecksEditor
+                                            checks={(*structured_checks).clone()}
+                                            match_any={*structured_any}
+                                            on_change={{
+                                                let sc = structured_checks.clone();
+                                                let sa = structured_any.clone();
+                                                Callback::from(move |(checks, any): (Vec<Predicate>, bool)| {
+                                                    sc.set(checks);
+                                                    sa.set(any);
+                                                })
+                                            }}
+                                        />
+                                    }
                                     if *logic_mode == LogicMode::Keyword {
                                         <div class="rb-field">
                                             <div class="rb-field-label">{"Keyword"}</div>
@@ -3325,7 +3901,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                                 {for servers.into_iter().map(|srv| {
                                                                     let srv_name = srv.get("name").and_then(|n| n.as_str()).unwrap_or("mcp").to_string();
                                                                     let pill_key = format!("mcp:{}", srv_name);
-                                                                    let is_active = active_sources.iter().any(|s| matches!(s, SourceConfig::Mcp { server, .. } if *server == srv_name));
+                                                                    let is_active = active_sources.iter().any(|s| s.is_type(&pill_key));
                                                                     let pill_class = if !avail {
                                                                         "rb-source-pill disabled"
                                                                     } else if is_active {
@@ -3338,6 +3914,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                                     let srv_n = srv_name.clone();
                                                                     let pk = pill_key.clone();
                                                                     let mst = mcp_source_tools.clone();
+                                                                    let msr = mcp_source_resources.clone();
                                                                     let srv_id = srv.get("id").and_then(|i| i.as_i64()).unwrap_or(0);
                                                                     html! {
                                                                         <span class={pill_class}
@@ -3346,7 +3923,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                                                 if !avail { return; }
                                                                                 let mut current = (*as_pill).clone();
                                                                                 if is_active {
-                                                                                    current.retain(|s| !matches!(s, SourceConfig::Mcp { server, .. } if *server == srv_n));
+                                                                                    current.retain(|s| !s.is_type(&pk));
                                                                                     as_pill.set(current);
                                                                                     if *es == Some(pk.clone()) { es.set(None); }
                                                                                 } else {
@@ -3355,6 +3932,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                                                     // Fetch tools if not cached
                                                                                     if !mst.contains_key(&srv_n) {
                                                                                         let mst2 = mst.clone();
+                                                                                        let msr2 = msr.clone();
                                                                                         let sn = srv_n.clone();
                                                                                         let sid = srv_id;
                                                                                         spawn_local(async move {
@@ -3368,9 +3946,16 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                                                                             Some(McpToolOption { name, description: desc })
                                                                                                         }).collect();
                                                                                                         let mut map = (*mst2).clone();
-                                                                                                        map.insert(sn, tool_opts);
+                                                                                                        map.insert(sn.clone(), tool_opts);
                                                                                                         mst2.set(map);
                                                                                                     }
+                                                                                                    let resource_opts: Vec<McpResourceOption> = resp.get("resources")
+                                                                                                        .cloned()
+                                                                                                        .and_then(|v| serde_json::from_value(v).ok())
+                                                                                                        .unwrap_or_default();
+                                                                                                    let mut map = (*msr2).clone();
+                                                                                                    map.insert(sn, resource_opts);
+                                                                                                    msr2.set(map);
                                                                                                 }
                                                                                             }
                                                                                         });
@@ -3429,6 +4014,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                                         "internet" => SourceConfig::Internet { query: String::new() },
                                                                         "tesla" => SourceConfig::Tesla,
                                                                         "events" => SourceConfig::Events,
+                                                                        "calendar" => SourceConfig::Calendar { days: 1 },
                                                                         _ => return,
                                                                     };
                                                                     current.push(new_source);
@@ -3605,6 +4191,7 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                     let pill_key = format!("mcp:{}", srv_name);
                                                     if *expanded_source != Some(pill_key) { return None; }
                                                     let tools = mcp_source_tools.get(&srv_name).cloned().unwrap_or_default();
+                                                    let resources = mcp_source_resources.get(&srv_name).cloned().unwrap_or_default();
                                                     let as_mcp = active_sources.clone();
                                                     let sn = srv_name.clone();
                                                     Some(html! {
@@ -3670,6 +4257,38 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                                     />
                                                                 </div>
                                                             }
+                                                            if !resources.is_empty() {
+                                                                <div class="rb-field">
+                                                                    <div class="rb-field-label">{"Resource"}</div>
+                                                                    <select
+                                                                        class="rb-select"
+                                                                        onchange={{
+                                                                            let as_c = as_mcp.clone();
+                                                                            let sn_c = sn.clone();
+                                                                            Callback::from(move |e: Event| {
+                                                                                if let Some(sel) = e.target_dyn_into::<web_sys::HtmlSelectElement>() {
+                                                                                    let mut current = (*as_c).clone();
+                                                                                    current.retain(|s| !matches!(s, SourceConfig::McpResource { server, .. } if *server == sn_c));
+                                                                                    if !sel.value().is_empty() {
+                                                                                        current.push(SourceConfig::McpResource {
+                                                                                            server: sn_c.clone(),
+                                                                                            uri: sel.value(),
+                                                                                        });
+                                                                                    }
+                                                                                    as_c.set(current);
+                                                                                }
+                                                                            })
+                                                                        }}
+                                                                    >
+                                                                        <option value="">{"Select resource..."}</option>
+                                                                        {for resources.iter().map(|r| {
+                                                                            let sel = active_sources.iter().any(|s| matches!(s, SourceConfig::McpResource { server, uri } if *server == sn && *uri == r.uri));
+                                                                            let label = if r.name.is_empty() { r.uri.clone() } else { r.name.clone() };
+                                                                            html! { <option value={r.uri.clone()} selected={sel}>{label}</option> }
+                                                                        })}
+                                                                    </select>
+                                                                </div>
+                                                            }
                                                         </div>
                                                     })
                                                 })
@@ -4122,6 +4741,77 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                             {mcp_fields_html.clone()}
                                         }
                                     }
+
+                                    if let Some(ref advanced) = *then_advanced {
+                                        <div class="rb-field-hint" style="margin: 0.5rem 0;">
+                                            {"This rule runs a multi-step flow that can't be edited here. Saving keeps it as is."}
+                                        </div>
+                                        <div class="rb-review-flow">{render_else_review(advanced)}</div>
+                                        <button class="rb-add-condition-btn"
+                                            onclick={{
+                                                let ta = then_advanced.clone();
+                                                Callback::from(move |_: MouseEvent| ta.set(None))
+                                            }}
+                                        >
+                                            {"Replace with the action above"}
+                                        </button>
+                                    } else {
+                                        <div class="rb-field" style="margin-top: 0.5rem;">
+                                            <div class="rb-field-label">{"Repeat"}</div>
+                                            <div class="rb-field-hint">{
+                                                if then_repeat.is_some() {
+                                                    "Runs the action once per item (up to 10)"
+                                                } else {
+                                                    "Runs the action once"
+                                                }
+                                            }</div>
+                                            <div style="display: flex; flex-wrap: wrap; gap: 0.3rem; margin-top: 0.3rem;">
+                                                {for [
+                                                    (None, "Once"),
+                                                    (Some(SourceConfig::Events), "Each tracked obligation"),
+                                                    (Some(SourceConfig::Calendar { days: 1 }), "Each calendar event"),
+                                                    (Some(SourceConfig::Email), "Each recent email"),
+                                                    (Some(SourceConfig::Chat { platform: "all".to_string(), limit: 50 }), "Each recent chat"),
+                                                ].into_iter().map(|(source, label)| {
+                                                    let is_active = *then_repeat == source;
+                                                    let repeat = then_repeat.clone();
+                                                    html! {
+                                                        <button
+                                                            class={if is_active { "rb-toggle-btn active" } else { "rb-toggle-btn" }}
+                                                            onclick={Callback::from(move |_: MouseEvent| repeat.set(source.clone()))}
+                                                        >
+                                                            {label}
+                                                        </button>
+                                                    }
+                                                })}
+                                            </div>
+                                        </div>
+                                        <div class="rb-field" style="margin-top: 0.5rem;">
+                                            <div class="rb-field-label">{"Delay action"}</div>
+                                            <div class="rb-field-hint">{
+                                                if *then_delay == 0 {
+                                                    "Acts as soon as the rule fires".to_string()
+                                                } else {
+                                                    format!("Waits {} after the rule fires, then acts", format_delay(*then_delay))
+                                                }
+                                            }</div>
+                                            <div style="display: flex; flex-wrap: wrap; gap: 0.3rem; margin-top: 0.3rem;">
+                                                {for [(0, "No delay"), (1800, "30 min"), (7200, "2 hours"), (86400, "1 day")].iter().map(|(secs, label)| {
+                                                    let is_active = *then_delay == *secs;
+                                                    let delay = then_delay.clone();
+                                                    let val = *secs;
+                                                    html! {
+                                                        <button
+                                                            class={if is_active { "rb-toggle-btn active" } else { "rb-toggle-btn" }}
+                                                            onclick={Callback::from(move |_: MouseEvent| delay.set(val))}
+                                                        >
+                                                            {label}
+                                                        </button>
+                                                    }
+                                                })}
+                                            </div>
+                                        </div>
+                                    }
                                 </>
                             })}
                         )}
@@ -4180,12 +4870,15 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                             &*logic_prompt,
                             &*condition_input,
                             &*keyword_input,
+                            &structured_condition,
                             &*action_mode,
                             &*notify_method,
                             &*tool_name,
                             &*tc_platform,
                             &*tc_chat_name,
                             &*tc_tesla_cmd,
+                            &then_modifier_text(*then_delay, &*then_repeat),
+                            &*then_advanced,
                             &*else_flow,
                             &rule_missing,
                         )}
@@ -4256,6 +4949,48 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                         }}
                                     />
                                 </div>
+                                if *logic_mode == LogicMode::Structured {
+                                    <div class="rb-row">
+                                        <div class="rb-field">
+                                            <div class="rb-field-label">{"Platform"}</div>
+                                            <select
+                                                class="rb-select"
+                                                onchange={{
+                                                    let tp = test_platform.clone();
+                                                    Callback::from(move |e: Event| {
+                                                        if let Some(sel) = e.target_dyn_into::<web_sys::HtmlSelectElement>() {
+                                                            tp.set(sel.value());
+                                                        }
+                                                    })
+                                                }}
+                                            >
+                                                <option value="" selected={test_platform.is_empty()}>{"Not set"}</option>
+                                                {for ["whatsapp", "telegram", "signal", "email"].iter().map(|p| html! {
+                                                    <option value={*p} selected={*test_platform == *p}>{capitalize_first(p)}</option>
+                                                })}
+                                            </select>
+                                        </div>
+                                        <div class="rb-field">
+                                            <div class="rb-field-label">{"Chat type"}</div>
+                                            <div class="rb-toggle-group">
+                                                <button
+                                                    class={classes!("rb-toggle-btn", (!*test_is_group).then(|| "active"))}
+                                                    onclick={{
+                                                        let tg = test_is_group.clone();
+                                                        Callback::from(move |_: MouseEvent| tg.set(false))
+                                                    }}
+                                                >{"Direct"}</button>
+                                                <button
+                                                    class={classes!("rb-toggle-btn", (*test_is_group).then(|| "active"))}
+                                                    onclick={{
+                                                        let tg = test_is_group.clone();
+                                                        Callback::from(move |_: MouseEvent| tg.set(true))
+                                                    }}
+                                                >{"Group"}</button>
+                                            </div>
+                                        </div>
+                                    </div>
+                                }
                                 <div class="rb-field">
                                     <div class="rb-field-label">{"Message"}</div>
                                     <textarea
@@ -4280,8 +5015,11 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                         let test_steps = test_steps.clone();
                                         let test_message = test_message.clone();
                                         let test_sender = test_sender.clone();
+                                        let test_platform = test_platform.clone();
+                                        let test_is_group = test_is_group.clone();
                                         let name = name.clone();
                                         let logic_mode = logic_mode.clone();
+                                        let structured_condition = structured_condition.clone();
                                         let selected_template = selected_template.clone();
                                         let logic_prompt = logic_prompt.clone();
                                         let condition_input = condition_input.clone();
@@ -4302,6 +5040,9 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                         let tc_tesla_cmd = tc_tesla_cmd.clone();
                                         let tc_mcp_params = tc_mcp_params.clone();
                                         let else_flow = else_flow.clone();
+                                        let then_delay = then_delay.clone();
+                                        let then_repeat = then_repeat.clone();
+                                        let then_advanced = then_advanced.clone();
                                         let when_mode = when_mode.clone();
                                         let test_es_ref = test_es_ref.clone();
                                         Callback::from(move |_: MouseEvent| {
@@ -4366,11 +5107,18 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                 ActionMode::Notify => "notify",
                                                 ActionMode::ToolCall => "tool_call",
                                             };
-                                            let action_node = serde_json::json!({
-                                                "type": "action",
-                                                "action_type": action_type,
-                                                "config": action_config_json
-                                            });
+                                            let action_node = match &*then_advanced {
+                                                Some(node) => serde_json::to_value(node).unwrap_or(serde_json::Value::Null),
+                                                None => wrap_then_node(
+                                                    serde_json::json!({
+                                                        "type": "action",
+                                                        "action_type": action_type,
+                                                        "config": action_config_json
+                                                    }),
+                                                    *then_delay,
+                                                    &*then_repeat,
+                                                ),
+                                            };
                                             let else_branch_json: serde_json::Value = match &*else_flow {
                                                 Some(node) => serde_json::to_value(node).unwrap_or(serde_json::Value::Null),
                                                 None => serde_json::Value::Null,
@@ -4426,15 +5174,27 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                         "false_branch": else_branch_json
                                                     })
                                                 }
+                                                LogicMode::Structured => {
+                                                    serde_json::json!({
+                                                        "type": "structured_condition",
+                                                        "condition": structured_condition,
+                                                        "true_branch": action_node,
+                                                        "false_branch": else_branch_json
+                                                    })
+                                                }
                                                 _ => action_node,
                                             };
 
-                                            let body = serde_json::json!({
+                                            let mut body = serde_json::json!({
                                                 "flow_config": flow_config.to_string(),
                                                 "message": *test_message,
                                                 "sender": *test_sender,
                                                 "rule_name": *name,
+                                                "is_group": *test_is_group,
                                             });
+                                            if !test_platform.is_empty() {
+                                                body["platform"] = serde_json::json!(*test_platform);
+                                            }
 
                                             let test_running = test_running.clone();
                                             let test_steps = test_steps.clone();
@@ -4550,10 +5310,41 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
                                                                         ("no".to_string(), "-".to_string(), "No match".to_string())
                                                                     }
                                                                 }
+                                                                "checking_condition" => {
+                                                                    let desc = data["description"].as_str().unwrap_or("").to_string();
+                                                                    ("deciding".to_string(), "...".to_string(), format!("Checking {}...", desc))
+                                                                }
+                                                                "condition_result" => {
+                                                                    let matched = data["matched"].as_bool().unwrap_or(false);
+                                                                    if matched {
+                                                                        ("yes".to_string(), "Y".to_string(), "Checks passed".to_string())
+                                                                    } else {
+                                                                        ("no".to_string(), "-".to_string(), "Checks did not pass".to_string())
+                                                                    }
+                                                                }
                                                                 "would_execute" => {
                                                                     let desc = data["description"].as_str().unwrap_or("").to_string();
                                                                     ("action".to_string(), ">".to_string(), format!("Would {}", desc))
                                                                 }
+                                                                "would_wait" => {
+                                                                    let secs = data["seconds"].as_i64().unwrap_or(0);
+                                                                    ("deciding".to_string(), "...".to_string(), format!("Would wait {} before continuing", format_delay(secs)))
+                                                                }
+                                                                "running_branch" => {
+                                                                    let index = data["index"].as_u64().unwrap_or(0);
+                                                                    let total = data["total"].as_u64().unwrap_or(0);
+                                                                    ("deciding".to_string(), "...".to_string(), format!("Parallel branch {} of {}", index, total))
+                                                                }
+                                                                "loop_items" => {
+                                                                    let source = data["source"].as_str().unwrap_or("").to_string();
+                                                                    let count = data["count"].as_u64().unwrap_or(0);
+                                                                    ("deciding".to_string(), "...".to_string(), format!("Found {} item(s) in {}", count, source))
+                                                                }
+                                                                "loop_item" => {
+                                                                    let index = data["index"].as_u64().unwrap_or(0);
+                                                                    let preview = data["preview"].as_str().unwrap_or("").to_string();
+                                                                    ("deciding".to_string(), "...".to_string(), format!("Item {}: {}", index, preview))
+                                                                }
                                                                 "no_action" => {
                                                                     let reason = data["reason"].as_str().unwrap_or("").to_string();
                                                                     ("inactive".to_string(), "-".to_string(), format!("No action: {}", reason))
@@ -4640,6 +5431,311 @@ pub fn rule_builder(props: &RuleBuilderProps) -> Html {
     }
 }
 
+// ---------------------------------------------------------------------------
+// StructuredChecksEditor: list of deterministic checks for the IF card
+// ---------------------------------------------------------------------------
+
+#[derive(Properties, PartialEq)]
+struct StructuredChecksEditorProps {
+    checks: Vec<Predicate>,
+    match_any: bool,
+    on_change: Callback<(Vec<Predicate>, bool)>,
+}
+
+#[function_component(StructuredChecksEditor)]
+fn structured_checks_editor(props: &StructuredChecksEditorProps) -> Html {
+    let checks = props.checks.clone();
+    let match_any = props.match_any;
+    let on_change = props.on_change.clone();
+
+    // Replace check `i` with `next`.
+    let update = {
+        let checks = checks.clone();
+        let on_change = on_change.clone();
+        move |i: usize, next: Predicate| {
+            let mut list = checks.clone();
+            if let Some(slot) = list.get_mut(i) {
+                *slot = next;
+            }
+            on_change.emit((list, match_any));
+        }
+    };
+
+    let rows = checks.iter().enumerate().map(|(i, check)| {
+        let remove = {
+            let checks = checks.clone();
+            let on_change = on_change.clone();
+            Callback::from(move |_: MouseEvent| {
+                let mut list = checks.clone();
+                list.remove(i);
+                on_change.emit((list, match_any));
+            })
+        };
+        let (label, body) = match check {
+            Predicate::DayOfWeek { days } => {
+                let weekdays = days.iter().any(|d| d == "weekdays");
+                let weekends = days.iter().any(|d| d == "weekends");
+                let toggle = |value: &'static str| {
+                    let days = days.clone();
+                    let update = update.clone();
+                    Callback::from(move |_: MouseEvent| {
+                        // Picking a single day replaces the weekdays/weekends shortcuts.
+                        let mut next: Vec<String> = if value == "weekdays" || value == "weekends" {
+                            Vec::new()
+                        } else {
+                            days.iter()
+                                .filter(|d| *d != "weekdays" && *d != "weekends")
+                                .cloned()
+                                .collect()
+                        };
+                        if days.iter().any(|d| d == value) {
+                            next.retain(|d| d != value);
+                        } else {
+                            next.push(value.to_string());
+                        }
+                        update(i, Predicate::DayOfWeek { days: next });
+                    })
+                };
+                (
+                    "Day",
+                    html! {
+                        <div class="rb-toggle-group">
+                            <button class={classes!("rb-toggle-btn", weekdays.then(|| "active"))}
+                                onclick={toggle("weekdays")}>{"Weekdays"}</button>
+                            <button class={classes!("rb-toggle-btn", weekends.then(|| "active"))}
+                                onclick={toggle("weekends")}>{"Weekends"}</button>
+                            {for CHECK_DAYS.iter().map(|(value, text)| {
+                                let active = days.iter().any(|d| d == value);
+                                html! {
+                                    <button class={classes!("rb-toggle-btn", active.then(|| "active"))}
+                                        onclick={toggle(*value)}>{*text}</button>
+                                }
+                            })}
+                        </div>
+                    },
+                )
+            }
+            Predicate::TimeWindow { start, end } => {
+                let set_time = |is_start: bool| {
+                    let start = start.clone();
+                    let end = end.clone();
+                    let update = update.clone();
+                    Callback::from(move |e: InputEvent| {
+                        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                            let (s, e) = if is_start {
+                                (input.value(), end.clone())
+                            } else {
+                                (start.clone(), input.value())
+                            };
+                            update(i, Predicate::TimeWindow { start: s, end: e });
+                        }
+                    })
+                };
+                (
+                    "Time of day",
+                    html! {
+                        <div class="rb-row">
+                            <input class="rb-input" type="time" value={start.clone()} oninput={set_time(true)} />
+                            <input class="rb-input" type="time" value={end.clone()} oninput={set_time(false)} />
+                        </div>
+                    },
+                )
+            }
+            Predicate::Platform { platforms } => {
+                let current = platforms.first().cloned().unwrap_or_default();
+                let onchange = {
+                    let update = update.clone();
+                    Callback::from(move |e: Event| {
+                        if let Some(sel) = e.target_dyn_into::<web_sys::HtmlSelectElement>() {
+                            update(i, Predicate::Platform { platforms: vec![sel.value()] });
+                        }
+                    })
+                };
+                (
+                    "Platform",
+                    html! {
+                        <select class="rb-select" {onchange}>
+                            {for ["whatsapp", "telegram", "signal", "email"].iter().map(|p| html! {
+                                <option value={*p} selected={current == *p}>{capitalize_first(p)}</option>
+                            })}
+                        </select>
+                    },
+                )
+            }
+            Predicate::IsGroup { value } => {
+                let set = |v: bool| {
+                    let update = update.clone();
+                    Callback::from(move |_: MouseEvent| update(i, Predicate::IsGroup { value: v }))
+                };
+                (
+                    "Chat type",
+                    html! {
+                        <div class="rb-toggle-group">
+                            <button class={classes!("rb-toggle-btn", (!*value).then(|| "active"))}
+                                onclick={set(false)}>{"Direct chat"}</button>
+                            <button class={classes!("rb-toggle-btn", value.then(|| "active"))}
+                                onclick={set(true)}>{"Group chat"}</button>
+                        </div>
+                    },
+                )
+            }
+            Predicate::Sender { name, .. } => {
+                let oninput = {
+                    let update = update.clone();
+                    Callback::from(move |e: InputEvent| {
+                        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                            update(i, Predicate::Sender {
+                                person_id: None,
+                                room_id: None,
+                                name: Some(input.value()),
+                            });
+                        }
+                    })
+                };
+                (
+                    "Sender",
+                    html! {
+                        <input class="rb-input" type="text" placeholder="Contact name"
+                            value={name.clone().unwrap_or_default()} {oninput} />
+                    },
+                )
+            }
+            Predicate::Regex { pattern, field } => {
+                let oninput = {
+                    let field = field.clone();
+                    let update = update.clone();
+                    Callback::from(move |e: InputEvent| {
+                        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                            update(i, Predicate::Regex { pattern: input.value(), field: field.clone() });
+                        }
+                    })
+                };
+                (
+                    "Message matches (regex)",
+                    html! {
+                        <input class="rb-input" type="text" placeholder="e.g. (?i)invoice|receipt"
+                            value={pattern.clone()} {oninput} />
+                    },
+                )
+            }
+            Predicate::Numeric { metric, op, value, location } => {
+                let on_op = {
+                    let (metric, value, location) = (metric.clone(), *value, location.clone());
+                    let update = update.clone();
+                    Callback::from(move |e: Event| {
+                        if let Some(sel) = e.target_dyn_into::<web_sys::HtmlSelectElement>() {
+                            update(i, Predicate::Numeric {
+                                metric: metric.clone(),
+                                op: sel.value(),
+                                value,
+                                location: location.clone(),
+                            });
+                        }
+                    })
+                };
+                let on_value = {
+                    let (metric, op, location) = (metric.clone(), op.clone(), location.clone());
+                    let update = update.clone();
+                    Callback::from(move |e: InputEvent| {
+                        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                            if let Ok(v) = input.value().parse::<f64>() {
+                                update(i, Predicate::Numeric {
+                                    metric: metric.clone(),
+                                    op: op.clone(),
+                                    value: v,
+                                    location: location.clone(),
+                                });
+                            }
+                        }
+                    })
+                };
+                let label = if metric == "tesla_battery" {
+                    "Tesla battery (%)"
+                } else {
+                    "Outside temperature (°C)"
+                };
+                (
+                    label,
+                    html! {
+                        <div class="rb-row">
+                            <select class="rb-select" onchange={on_op}>
+                                {for CHECK_OPS.iter().map(|(v, text)| html! {
+                                    <option value={*v} selected={op == v}>{*text}</option>
+                                })}
+                            </select>
+                            <input class="rb-input" type="number" value={value.to_string()} oninput={on_value} />
+                        </div>
+                    },
+                )
+            }
+            // Combinators built through the API: shown read-only.
+            other => (
+                "Custom check",
+                html! { <div class="rb-field-hint">{other.describe()}</div> },
+            ),
+        };
+        html! {
+            <div class="rb-nested-card">
+                <div class="rb-nested-header">
+                    <span class="rb-nested-label">{label}</span>
+                    <button class="rb-remove-btn" onclick={remove}>{"Remove"}</button>
+                </div>
+                {body}
+            </div>
+        }
+    });
+
+    let on_add = {
+        let checks = checks.clone();
+        let on_change = on_change.clone();
+        Callback::from(move |e: Event| {
+            if let Some(sel) = e.target_dyn_into::<web_sys::HtmlSelectElement>() {
+                if let Some(check) = Predicate::new_check(&sel.value()) {
+                    let mut list = checks.clone();
+                    list.push(check);
+                    on_change.emit((list, match_any));
+                }
+                sel.set_value("");
+            }
+        })
+    };
+    let set_any = |any: bool| {
+        let checks = checks.clone();
+        let on_change = on_change.clone();
+        Callback::from(move |_: MouseEvent| on_change.emit((checks.clone(), any)))
+    };
+
+    html! {
+        <>
+            if checks.len() > 1 {
+                <div class="rb-toggle-group">
+                    <button class={classes!("rb-toggle-btn", (!match_any).then(|| "active"))}
+                        onclick={set_any(false)}>{"All must match"}</button>
+                    <button class={classes!("rb-toggle-btn", match_any.then(|| "active"))}
+                        onclick={set_any(true)}>{"Any can match"}</button>
+                </div>
+            }
+            {for rows}
+            <div class="rb-field">
+                <select class="rb-select" onchange={on_add}>
+                    <option value="" selected=true>{"+ Add a check"}</option>
+                    <option value="day_of_week">{"Day of week"}</option>
+                    <option value="time_window">{"Time of day"}</option>
+                    <option value="sender">{"Sender"}</option>
+                    <option value="platform">{"Platform"}</option>
+                    <option value="is_group">{"Direct or group chat"}</option>
+                    <option value="regex">{"Message matches pattern"}</option>
+                    <option value="tesla_battery">{"Tesla battery"}</option>
+                    <option value="weather_temperature">{"Outside temperature"}</option>
+                </select>
+            </div>
+            <div class="rb-field-hint">
+                {"Checks run instantly without AI and cost nothing."}
+            </div>
+        </>
+    }
+}
+
 // ---------------------------------------------------------------------------
 // NestedConditionEditor: a proper Yew function_component for nested IF+THEN
 // ---------------------------------------------------------------------------
@@ -4662,7 +5758,7 @@ fn nested_condition_editor(props: &NestedConditionEditorProps) -> Html {
     let mode = match &props.node {
         FlowNode::LlmCondition { .. } => "llm",
         FlowNode::KeywordCondition { .. } => "keyword",
-        FlowNode::Action { .. } => "always",
+        _ => "always",
     };
 
     let (prompt, keyword, fetch, true_branch, false_branch) = match &props.node {
@@ -4689,14 +5785,8 @@ fn nested_condition_editor(props: &NestedConditionEditorProps) -> Html {
             true_branch.clone(),
             false_branch.clone(),
         ),
-        FlowNode::Action {
-            action_type,
-            config,
-        } => {
-            let tb = Box::new(Some(FlowNode::Action {
-                action_type: action_type.clone(),
-                config: config.clone(),
-            }));
+        other => {
+            let tb = Box::new(Some(other.clone()));
             (String::new(), String::new(), vec![], tb, Box::new(None))
         }
     };
@@ -4832,6 +5922,7 @@ fn nested_condition_editor(props: &NestedConditionEditorProps) -> Html {
             }
             "send_email" => "send email".to_string(),
             "respond_to_email" => "reply to email".to_string(),
+            "send_image" => "send picture".to_string(),
             "control_tesla" => humanize_tesla_cmd_short(&cur_tc_tesla_cmd).to_string(),
             "create_event" => "create event".to_string(),
             "update_event" => "update event".to_string(),
@@ -5060,6 +6151,7 @@ fn nested_condition_editor(props: &NestedConditionEditorProps) -> Html {
                             "internet" => SourceConfig::Internet { query: String::new() },
                             "tesla" => SourceConfig::Tesla,
                             "events" => SourceConfig::Events,
+                            "calendar" => SourceConfig::Calendar { days: 1 },
                             _ => return,
                         };
                         new_fetch.push(new_source);
@@ -5910,12 +7002,15 @@ fn build_rule_summary(
     logic_prompt: &str,
     condition_input: &str,
     keyword_input: &str,
+    structured_condition: &Option<Predicate>,
     action_mode: &ActionMode,
     notify_method: &NotifyMethod,
     tool_name: &str,
     tc_platform: &str,
     tc_chat_name: &str,
     tc_tesla_cmd: &str,
+    then_prefix: &str,
+    then_advanced: &Option<FlowNode>,
     else_flow: &Option<FlowNode>,
 ) -> Html {
     // WHEN part
@@ -5924,6 +7019,10 @@ fn build_rule_summary(
     // IF part
     let if_part: Option<String> = match logic_mode {
         LogicMode::Always => None,
+        LogicMode::Structured => Some(match structured_condition {
+            Some(p) => format!("only if {}", p.describe()),
+            None => "runs some checks".to_string(),
+        }),
         LogicMode::Keyword => {
             if keyword_input.is_empty() {
                 Some("checks for a keyword".to_string())
@@ -5960,7 +7059,7 @@ fn build_rule_summary(
     };
 
     // THEN part
-    let then_part = match action_mode {
+    let then_action = match action_mode {
         ActionMode::Notify => match notify_method {
             NotifyMethod::Sms => "texts you".to_string(),
             NotifyMethod::Call => "calls you".to_string(),
@@ -6050,10 +7149,61 @@ fn build_rule_summary(
                 }
                 parts
             }
+            FlowNode::StructuredCondition {
+                condition,
+                true_branch,
+                false_branch,
+            } => {
+                let action = match true_branch.as_ref() {
+                    Some(FlowNode::Action {
+                        action_type,
+                        config,
+                    }) => describe_action_summary(action_type, config),
+                    Some(nested) => describe_node_summary(nested).join(", then "),
+                    None => "runs an action".to_string(),
+                };
+                let mut parts = vec![format!("if {}, then {}", condition.describe(), action)];
+                if let Some(fb) = false_branch.as_ref() {
+                    for p in describe_node_summary(fb) {
+                        parts.push(format!("otherwise, {}", p));
+                    }
+                }
+                parts
+            }
             FlowNode::Action {
                 action_type,
                 config,
             } => vec![describe_action_summary(action_type, config)],
+            FlowNode::Sequence { steps } => {
+                let parts: Vec<String> = steps
+                    .iter()
+                    .flat_map(|n| describe_node_summary(n))
+                    .collect();
+                vec![parts.join(", then ")]
+            }
+            FlowNode::Parallel { branches } => {
+                let parts: Vec<String> = branches
+                    .iter()
+                    .flat_map(|n| describe_node_summary(n))
+                    .collect();
+                vec![format!("at the same time {}", parts.join(" and "))]
+            }
+            FlowNode::Delay { seconds, next } => {
+                let rest = next
+                    .as_ref()
+                    .as_ref()
+                    .map(|n| describe_node_summary(n).join(", then "))
+                    .unwrap_or_else(|| "stops".to_string());
+                vec![format!("waits {}, then {}", format_delay(*seconds), rest)]
+            }
+            FlowNode::ForEach { source, body, .. } => {
+                let rest = body
+                    .as_ref()
+                    .as_ref()
+                    .map(|n| describe_node_summary(n).join(", then "))
+                    .unwrap_or_else(|| "does nothing".to_string());
+                vec![format!("for {}, {}", describe_repeat_source(source), rest)]
+            }
         }
     }
     fn describe_action_summary(action_type: &str, config: &serde_json::Value) -> String {
@@ -6067,6 +7217,7 @@ fn build_rule_summary(
                 Some("update_event") => "updates the event".to_string(),
                 Some("send_email") => "sends an email".to_string(),
                 Some("send_chat_message") => "sends a message".to_string(),
+                Some("send_image") => "sends you a picture".to_string(),
                 Some("control_tesla") => {
                     let cmd = config
                         .get("params")
@@ -6081,6 +7232,11 @@ fn build_rule_summary(
         }
     }
 
+    let then_part = match then_advanced {
+        Some(node) => describe_node_summary(node).join(", then "),
+        None => format!("{}{}", then_prefix, then_action),
+    };
+
     let else_lines: Vec<String> = else_flow
         .as_ref()
         .map(|node| describe_node_summary(node))
@@ -6135,11 +7291,49 @@ fn render_card(
     }
 }
 
+/// Ask the backend to validate a custom schedule pattern and return its
+/// next fire times, formatted in the user's timezone.
+async fn fetch_schedule_preview(pattern: &str) -> Result<Vec<String>, String> {
+    #[derive(Deserialize)]
+    struct Preview {
+        timezone: String,
+        next_fire_times: Vec<i64>,
+    }
+    let body = serde_json::json!({ "pattern": pattern, "count": 5 });
+    let request = Api::post("/api/rules/schedule-preview")
+        .json(&body)
+        .map_err(|e| format!("Failed to build request: {}", e))?;
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    if !response.ok() {
+        let err: serde_json::Value = response.json().await.unwrap_or_default();
+        return Err(err
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Invalid pattern")
+            .to_string());
+    }
+    let preview: Preview = response
+        .json()
+        .await
+        .map_err(|e| format!("Bad preview response: {}", e))?;
+    let tz: chrono_tz::Tz = preview.timezone.parse().unwrap_or(chrono_tz::UTC);
+    Ok(preview
+        .next_fire_times
+        .into_iter()
+        .filter_map(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.with_timezone(&tz).format("%a %b %-d, %H:%M").to_string())
+        .collect())
+}
+
 fn parse_pattern_into(
     pattern: &str,
     freq: &UseStateHandle<RecurringFreq>,
     time: &UseStateHandle<String>,
     day: &UseStateHandle<String>,
+    custom: &UseStateHandle<String>,
 ) {
     let parts: Vec<&str> = pattern.splitn(2, ' ').collect();
     if parts.is_empty() {
@@ -6169,7 +7363,10 @@ fn parse_pattern_into(
                 }
             }
         }
-        _ => {}
+        _ => {
+            freq.set(RecurringFreq::Custom);
+            custom.set(pattern.to_string());
+        }
     }
 }
 
@@ -6214,7 +7411,10 @@ fn auto_generate_name(
                     .and_then(|v| v.as_str())
                     .unwrap_or("daily");
                 let freq = pattern.split_whitespace().next().unwrap_or("daily");
-                capitalize_first(freq)
+                match freq {
+                    "hourly" | "daily" | "weekdays" | "weekly" => capitalize_first(freq),
+                    _ => "Custom schedule".to_string(),
+                }
             }
             Some("once") => "One-time".to_string(),
             _ => "Scheduled".to_string(),
@@ -6385,6 +7585,32 @@ fn extract_schema_fields(schema: Option<&serde_json::Value>) -> Vec<String> {
     fields
 }
 
+/// Whether a check from the builder has everything the backend requires.
+fn check_is_complete(check: &Predicate) -> bool {
+    match check {
+        Predicate::Sender {
+            person_id,
+            room_id,
+            name,
+        } => {
+            person_id.is_some()
+                || room_id.is_some()
+                || name.as_deref().is_some_and(|n| !n.trim().is_empty())
+        }
+        Predicate::Platform { platforms } => !platforms.is_empty(),
+        Predicate::DayOfWeek { days } => !days.is_empty(),
+        Predicate::TimeWindow { start, end } => {
+            !start.is_empty() && !end.is_empty() && start != end
+        }
+        Predicate::Regex { pattern, .. } => !pattern.is_empty(),
+        Predicate::And { conditions } | Predicate::Or { conditions } => {
+            !conditions.is_empty() && conditions.iter().all(check_is_complete)
+        }
+        Predicate::Not { condition } => check_is_complete(condition),
+        Predicate::IsGroup { .. } | Predicate::Numeric { .. } => true,
+    }
+}
+
 fn is_rule_complete(
     when_mode: &WhenMode,
     schedule_mode: &ScheduleMode,
@@ -6395,6 +7621,7 @@ fn is_rule_complete(
     logic_prompt: &str,
     condition_input: &str,
     keyword_input: &str,
+    structured_checks: &[Predicate],
 ) -> (bool, Vec<&'static str>) {
     let mut missing = Vec::new();
 
@@ -6412,6 +7639,13 @@ fn is_rule_complete(
                 missing.push("Enter a keyword to match");
             }
         }
+        LogicMode::Structured => {
+            if structured_checks.is_empty() {
+                missing.push("Add at least one check");
+            } else if structured_checks.iter().any(|c| !check_is_complete(c)) {
+                missing.push("Fill in every check");
+            }
+        }
         LogicMode::Llm => match selected_template {
             PromptTemplate::Custom => {
                 if logic_prompt.is_empty() {
@@ -6439,12 +7673,15 @@ fn render_review(
     logic_prompt: &str,
     condition_input: &str,
     keyword_input: &str,
+    structured_condition: &Option<Predicate>,
     action_mode: &ActionMode,
     notify_method: &NotifyMethod,
     tool_name: &str,
     tc_platform: &str,
     tc_chat_name: &str,
     tc_tesla_cmd: &str,
+    then_prefix: &str,
+    then_advanced: &Option<FlowNode>,
     else_flow: &Option<FlowNode>,
     missing: &[&str],
 ) -> Html {
@@ -6453,6 +7690,9 @@ fn render_review(
 
     let if_text: Option<String> = match logic_mode {
         LogicMode::Always => None,
+        LogicMode::Structured => structured_condition
+            .as_ref()
+            .map(|p| format!("Only if {}", p.describe())),
         LogicMode::Keyword => {
             if keyword_input.is_empty() {
                 None
@@ -6523,7 +7763,16 @@ fn render_review(
                 if let Some(ref check) = if_text {
                     <div class="rb-review-step">{check}</div>
                 }
-                <div class="rb-review-step">{&then_text}</div>
+                if let Some(node) = then_advanced {
+                    {render_else_review(node)}
+                } else {
+                    if !then_prefix.is_empty() {
+                        <div class="rb-review-step">
+                            {capitalize_first(then_prefix.trim_end_matches(", "))}
+                        </div>
+                    }
+                    <div class="rb-review-step">{&then_text}</div>
+                }
                 if let Some(node) = else_flow {
                     <br/>
                     {"Otherwise"}
@@ -6616,12 +7865,65 @@ fn render_else_review(node: &FlowNode) -> Html {
                 </>
             }
         }
+        FlowNode::StructuredCondition {
+            condition,
+            true_branch,
+            false_branch,
+        } => {
+            let check = format!("Only if {}", condition.describe());
+            html! {
+                <>
+                    <div class="rb-review-step">{check}</div>
+                    if let Some(tb) = true_branch.as_ref() {
+                        {render_else_review(tb)}
+                    }
+                    if let Some(fb) = false_branch.as_ref() {
+                        <br/>
+                        {"Otherwise"}
+                        {render_else_review(fb)}
+                    }
+                </>
+            }
+        }
         FlowNode::Action {
             action_type,
             config,
         } => {
             html! { <div class="rb-review-step">{review_action_text(action_type, config)}</div> }
         }
+        FlowNode::Sequence { steps } => {
+            html! { <>{for steps.iter().map(render_else_review)}</> }
+        }
+        FlowNode::Parallel { branches } => {
+            html! {
+                <>
+                    <div class="rb-review-step">{"At the same time:"}</div>
+                    {for branches.iter().map(render_else_review)}
+                </>
+            }
+        }
+        FlowNode::Delay { seconds, next } => {
+            html! {
+                <>
+                    <div class="rb-review-step">{format!("Waits {}", format_delay(*seconds))}</div>
+                    if let Some(n) = next.as_ref() {
+                        {render_else_review(n)}
+                    }
+                </>
+            }
+        }
+        FlowNode::ForEach { source, body, .. } => {
+            html! {
+                <>
+                    <div class="rb-review-step">
+                        {capitalize_first(&format!("for {}", describe_repeat_source(source)))}
+                    </div>
+                    if let Some(b) = body.as_ref() {
+                        {render_else_review(b)}
+                    }
+                </>
+            }
+        }
     }
 }
 
@@ -6635,6 +7937,7 @@ fn review_action_text(action_type: &str, config: &serde_json::Value) -> String {
             Some("create_event") => "Pins it to your dashboard".to_string(),
             Some("update_event") => "Updates the tracked obligation".to_string(),
             Some("send_email") => "Sends an email".to_string(),
+            Some("send_image") => "Sends you a picture".to_string(),
             Some("send_chat_message") => {
                 let plat = config
                     .get("params")
@@ -6752,6 +8055,11 @@ fn auto_detect_sources(prompt: &str, available: &[RuleSourceOption]) -> Vec<Sour
             "events",
             SourceConfig::Events,
         ),
+        (
+            &["calendar", "meeting", "appointment", "schedule"],
+            "calendar",
+            SourceConfig::Calendar { days: 1 },
+        ),
         (
             &["search", "look up", "find online", "news"],
             "internet",
@@ -1,6 +1,9 @@
 use super::agent_panel::AgentPanel;
 use super::always_show::AlwaysShowSettings;
+use super::email_channel_panel::EmailChannelPanel;
+use super::matrix_channel_panel::MatrixChannelPanel;
 use super::phone_device_panel::PhoneDevicePanel;
+use super::push_channel_panel::PushChannelPanel;
 use super::webhooks_panel::WebhooksPanel;
 use crate::auth::connect::Connect;
 use crate::profile::billing_credits::BillingPage;
@@ -407,13 +410,61 @@ pub fn settings_panel(props: &SettingsPanelProps) -> Html {
                                 </details>
                             </section>
 
+                            <section class="connections-group" aria-label="Matrix">
+                                <details class="connections-disclosure">
+                                    <summary>
+                                        <span>
+                                            {"Matrix"}
+                                            <span class="connections-disclosure-copy">
+                                                {"Chat with Lightfriend and get notifications in a Matrix app."}
+                                            </span>
+                                        </span>
+                                    </summary>
+                                    <div class="connections-disclosure-body">
+                                        <MatrixChannelPanel />
+                                    </div>
+                                </details>
+                            </section>
+
+                            <section class="connections-group" aria-label="Push notifications">
+                                <details class="connections-disclosure">
+                                    <summary>
+                                        <span>
+                                            {"Push notifications"}
+                                            <span class="connections-disclosure-copy">
+                                                {"Get digests and rule alerts through ntfy or UnifiedPush instead of SMS."}
+                                            </span>
+                                        </span>
+                                    </summary>
+                                    <div class="connections-disclosure-body">
+                                        <PushChannelPanel />
+                                    </div>
+                                </details>
+                            </section>
+
+                            <section class="connections-group" aria-label="Email notifications">
+                                <details class="connections-disclosure">
+                                    <summary>
+                                        <span>
+                                            {"Email notifications"}
+                                            <span class="connections-disclosure-copy">
+                                                {"Get digests and alerts by email, from Lightfriend or your own mail account."}
+                                            </span>
+                                        </span>
+                                    </summary>
+                                    <div class="connections-disclosure-body">
+                                        <EmailChannelPanel />
+                                    </div>
+                                </details>
+                            </section>
+
                             <section class="connections-group" aria-label="Webhooks, API, and CLI">
                                 <details class="connections-disclosure">
                                     <summary>
                                         <span>
                                             {"Webhooks & API & CLI"}
                                             <span class="connections-disclosure-copy">
-                                                {"Connect scripts, services, and write-only local agents."}
+                                                {"Connect scripts, services, and scoped local agents."}
                                             </span>
                                         </span>
                                     </summary>
@@ -60,11 +60,14 @@ mod dashboard {
     pub mod always_show;
     pub mod chat_box;
     pub mod dashboard_view;
+    pub mod email_channel_panel;
     pub mod emoji_utils;
     pub mod focused_dashboard;
     pub mod light_phone_panel;
+    pub mod matrix_channel_panel;
     pub mod media_panel;
     pub mod phone_device_panel;
+    pub mod push_channel_panel;
     pub mod rule_builder;
     pub mod rules_section;
     pub mod settings_panel;
@@ -78,6 +81,7 @@ mod proactive {
 }
 mod connections {
     pub mod bridge_connect;
+    pub mod calendar;
     pub mod email;
     pub mod mcp;
     pub mod signal;
@@ -74,6 +74,7 @@ pub struct UserProfile {
     pub accountability_enabled: Option<bool>,
     pub accountability_friend_phone: Option<String>,
     pub accountability_friend_name: Option<String>,
+    pub sms_gsm7_transliteration: Option<bool>,
 }
 
 pub const MIN_TOPUP_AMOUNT_CREDITS: f32 = 3.00;
@@ -288,6 +288,8 @@ pub fn SettingsPage(props: &SettingsPageProps) -> Html {
         use_state(|| (*user_profile).auto_confirm_tracked_items.unwrap_or(true));
     let accountability_enabled =
         use_state(|| (*user_profile).accountability_enabled.unwrap_or(false));
+    let sms_gsm7_transliteration =
+        use_state(|| (*user_profile).sms_gsm7_transliteration.unwrap_or(false));
     let accountability_friend_phone = use_state(|| {
         (*user_profile)
             .accountability_friend_phone
@@ -335,6 +337,7 @@ pub fn SettingsPage(props: &SettingsPageProps) -> Html {
     let auto_track_system_save_state = use_state(|| FieldSaveState::Idle);
     let auto_confirm_save_state = use_state(|| FieldSaveState::Idle);
     let accountability_enabled_save_state = use_state(|| FieldSaveState::Idle);
+    let sms_gsm7_transliteration_save_state = use_state(|| FieldSaveState::Idle);
     let accountability_friend_phone_save_state = use_state(|| FieldSaveState::Idle);
     let accountability_friend_name_save_state = use_state(|| FieldSaveState::Idle);
 
@@ -370,6 +373,7 @@ pub fn SettingsPage(props: &SettingsPageProps) -> Html {
         let auto_track_system = auto_track_system.clone();
         let auto_confirm_items = auto_confirm_items.clone();
         let accountability_enabled_eff = accountability_enabled.clone();
+        let sms_gsm7_transliteration_eff = sms_gsm7_transliteration.clone();
         let accountability_friend_phone_eff = accountability_friend_phone.clone();
         let accountability_friend_phone_original_eff = accountability_friend_phone_original.clone();
         let accountability_friend_name_eff = accountability_friend_name.clone();
@@ -406,6 +410,8 @@ pub fn SettingsPage(props: &SettingsPageProps) -> Html {
                 auto_confirm_items.set(props_profile.auto_confirm_tracked_items.unwrap_or(true));
                 accountability_enabled_eff
                     .set(props_profile.accountability_enabled.unwrap_or(false));
+                sms_gsm7_transliteration_eff
+                    .set(props_profile.sms_gsm7_transliteration.unwrap_or(false));
                 let phone_val = props_profile
                     .accountability_friend_phone
                     .clone()
@@ -1370,6 +1376,53 @@ pub fn SettingsPage(props: &SettingsPageProps) -> Html {
         })
     };
 
+    // Plain SMS characters toggle
+    let on_sms_gsm7_transliteration_toggle = {
+        let sms_gsm7_transliteration = sms_gsm7_transliteration.clone();
+        let save_state = sms_gsm7_transliteration_save_state.clone();
+        let user_profile = user_profile.clone();
+        let on_profile_update = props.on_profile_update.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let new_val = input.checked();
+            sms_gsm7_transliteration.set(new_val);
+            let save_state = save_state.clone();
+            let user_profile = user_profile.clone();
+            let on_profile_update = on_profile_update.clone();
+            save_state.set(FieldSaveState::Saving);
+            spawn_local(async move {
+                let request = PatchFieldRequest {
+                    field: "sms_gsm7_transliteration".to_string(),
+                    value: serde_json::Value::Bool(new_val),
+                };
+                match Api::patch("/api/profile/field")
+                    .json(&request)
+                    .unwrap()
+                    .send()
+                    .await
+                {
+                    Ok(response) if response.ok() => {
+                        let mut profile = (*user_profile).clone();
+                        profile.sms_gsm7_transliteration = Some(new_val);
+                        on_profile_update.emit(profile);
+                        save_state.set(FieldSaveState::Success);
+                        let s = save_state.clone();
+                        spawn_local(async move {
+                            gloo_timers::future::TimeoutFuture::new(3_000).await;
+                            s.set(FieldSaveState::Idle);
+                        });
+                    }
+                    Ok(_) => {
+                        save_state.set(FieldSaveState::Error("Failed to save".to_string()));
+                    }
+                    Err(_) => {
+                        save_state.set(FieldSaveState::Error("Network error".to_string()));
+                    }
+                }
+            });
+        })
+    };
+
     // Helper: save accountability friend phone (called from blur and Enter handlers)
     fn save_accountability_friend_phone(
         value: UseStateHandle<String>,
@@ -2752,6 +2805,31 @@ pub fn SettingsPage(props: &SettingsPageProps) -> Html {
                 html! {}
             }}
 
+            // Plain SMS characters field
+            <div class="profile-field">
+                <div class="field-label-group">
+                    <span class="field-label">{"Plain SMS Characters"}</span>
+                    <div class="tooltip">
+                        <span class="tooltip-icon">{"?"}</span>
+                        <span class="tooltip-text">
+                            {"Replace curly quotes, long dashes and common emoji with plain equivalents (e.g. :) for a smiley) in SMS. A single emoji makes a text count as up to three times as many messages, so this keeps replies cheaper."}
+                        </span>
+                    </div>
+                </div>
+                <div class="field-input-container">
+                    <label class="custom-checkbox">
+                        <input
+                            type="checkbox"
+                            checked={*sms_gsm7_transliteration}
+                            onchange={on_sms_gsm7_transliteration_toggle.clone()}
+                        />
+                        <span class="checkmark"></span>
+                        {if *sms_gsm7_transliteration { "Enabled" } else { "Disabled" }}
+                    </label>
+                    {render_save_indicator(&*sms_gsm7_transliteration_save_state)}
+                </div>
+            </div>
+
             // Feature Updates field
             <div class="profile-field">
                 <div class="field-label-group">
@@ -2,7 +2,7 @@ use anyhow::{anyhow, bail, Context, Result};
 use clap::{Parser, Subcommand};
 use keyring::Entry;
 use rand::{rngs::OsRng, RngCore};
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, RequestBuilder, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 use url::Url;
@@ -15,7 +15,7 @@ const KEYRING_SERVICE: &str = "ai.lightfriend.cli";
 #[derive(Parser)]
 #[command(
     name = "lightfriend",
-    about = "Create Lightfriend reminders and reply watches"
+    about = "Let a local agent use the Lightfriend scopes you approved"
 )]
 struct Cli {
     /// Lightfriend server. HTTPS is required except for localhost development.
@@ -54,10 +54,67 @@ enum Command {
         #[arg(long, default_value_t = 1440, value_parser = clap::value_parser!(u16).range(15..=1440))]
         for_minutes: u16,
     },
+    /// List, cancel or snooze reminders created with this credential.
+    Reminders {
+        #[command(subcommand)]
+        command: RemindersCommand,
+    },
+    /// List or cancel reply watches created with this credential.
+    Watches {
+        #[command(subcommand)]
+        command: WatchesCommand,
+    },
+    /// Text a short message to your own phone (needs `sms:self`).
+    TextMe {
+        #[arg(long)]
+        message: String,
+    },
+    /// Print active events and reminders as JSON (needs `events:read`).
+    Events,
+    /// Print pending digest summaries as JSON (needs `digest:read`).
+    Digest,
+    /// Print rules that can be paused or resumed as JSON (needs `rules:toggle`).
+    Rules,
+    /// Pause a rule by id (needs `rules:toggle`).
+    PauseRule { id: i32 },
+    /// Resume a paused rule by id (needs `rules:toggle`).
+    ResumeRule { id: i32 },
     /// Check whether a credential exists locally. No server data is read.
     Status,
 }
 
+#[derive(Subcommand)]
+enum RemindersCommand {
+    /// Show ids and times only; reminder text is never returned.
+    List {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Cancel a scheduled reminder.
+    Cancel { id: i32 },
+    /// Move a reminder to a new time, also after it has been delivered.
+    Snooze {
+        id: i32,
+        /// Minutes from now.
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=525_600), required_unless_present = "until", conflicts_with = "until")]
+        minutes: Option<u32>,
+        /// RFC 3339 time with an offset.
+        #[arg(long)]
+        until: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum WatchesCommand {
+    /// Show ids and expiry times only; watched addresses are never returned.
+    List {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Stop watching for a reply.
+    Cancel { id: i32 },
+}
+
 #[derive(Serialize)]
 struct StartPairingRequest<'a> {
     client_name: &'a str,
@@ -97,6 +154,24 @@ struct ReplyWatchRequest<'a> {
     expires_in_seconds: u32,
 }
 
+#[derive(Serialize)]
+struct SnoozeRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    minutes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    at: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct TextMeRequest<'a> {
+    message: &'a str,
+}
+
+#[derive(Serialize)]
+struct RuleStatusRequest {
+    status: &'static str,
+}
+
 #[derive(Deserialize)]
 struct ActionResponse {
     status: String,
@@ -149,6 +224,86 @@ async fn run() -> Result<()> {
             )
             .await
         }
+        Command::Reminders { command } => match command {
+            RemindersCommand::List { json } => {
+                let reminders = fetch(&client, &server, "api/agent/reminders", "reminders").await?;
+                print_items(&reminders, json, "no reminders", |item| {
+                    format!(
+                        "{}\t{}\t{}",
+                        item["id"],
+                        item["remind_at"]
+                            .as_i64()
+                            .map(format_utc)
+                            .unwrap_or_default(),
+                        item["status"].as_str().unwrap_or("unknown")
+                    )
+                })
+            }
+            RemindersCommand::Cancel { id } => {
+                delete_action(&client, &server, &format!("api/agent/reminders/{id}")).await
+            }
+            RemindersCommand::Snooze { id, minutes, until } => {
+                action(
+                    &client,
+                    &server,
+                    &format!("api/agent/reminders/{id}/snooze"),
+                    &SnoozeRequest {
+                        minutes,
+                        at: until.as_deref(),
+                    },
+                )
+                .await
+            }
+        },
+        Command::Watches { command } => match command {
+            WatchesCommand::List { json } => {
+                let watches =
+                    fetch(&client, &server, "api/agent/reply-watches", "reply_watches").await?;
+                print_items(&watches, json, "no reply watches", |item| {
+                    format!(
+                        "{}\tuntil {}",
+                        item["id"],
+                        item["expires_at"]
+                            .as_i64()
+                            .map(format_utc)
+                            .unwrap_or_default()
+                    )
+                })
+            }
+            WatchesCommand::Cancel { id } => {
+                delete_action(&client, &server, &format!("api/agent/reply-watches/{id}")).await
+            }
+        },
+        Command::TextMe { message } => {
+            action(
+                &client,
+                &server,
+                "api/agent/actions/sms",
+                &TextMeRequest { message: &message },
+            )
+            .await
+        }
+        Command::Events => read(&client, &server, "api/agent/events", "events").await,
+        Command::Digest => read(&client, &server, "api/agent/digest", "items").await,
+        Command::Rules => read(&client, &server, "api/agent/rules", "rules").await,
+        Command::PauseRule { id } => {
+            action(
+                &client,
+                &server,
+                &format!("api/agent/rules/{id}/status"),
+                &RuleStatusRequest { status: "paused" },
+            )
+            .await
+        }
+        Command::ResumeRule { id } => {
+            action(
+                &client,
+                &server,
+                &format!("api/agent/rules/{id}/status"),
+                &RuleStatusRequest { status: "active" },
+            )
+            .await
+        }
         Command::Status => {
             match credential_entry(&server).get_password() {
                 Ok(_) => println!("connected"),
@@ -252,14 +407,20 @@ async fn logout(client: &Client, server: &Url) -> Result<()> {
 }
 
 async fn action<T: Serialize>(client: &Client, server: &Url, path: &str, body: &T) -> Result<()> {
+    send_action(server, client.post(server.join(path)?).json(body)).await
+}
+
+async fn delete_action(client: &Client, server: &Url, path: &str) -> Result<()> {
+    send_action(server, client.delete(server.join(path)?)).await
+}
+
+async fn send_action(server: &Url, request: RequestBuilder) -> Result<()> {
     let token = credential_entry(server)
         .get_password()
         .context("not connected; run `lightfriend login` in your local terminal")?;
-    let response = client
-        .post(server.join(path)?)
+    let response = request
         .bearer_auth(token)
         .header("Idempotency-Key", random_idempotency_key())
-        .json(body)
         .send()
         .await
         .context("Lightfriend request failed")?;
@@ -272,6 +433,8 @@ async fn action<T: Serialize>(client: &Client, server: &Url, path: &str, body: &
     if status_code.is_success() && status == "accepted" {
         println!("accepted");
         Ok(())
+    } else if status_code == StatusCode::FORBIDDEN {
+        bail!("rejected; this credential was not granted that permission")
     } else {
         bail!(
             "{}",
@@ -284,6 +447,85 @@ async fn action<T: Serialize>(client: &Client, server: &Url, path: &str, body: &
     }
 }
 
+/// Prints one field of a scoped read as JSON. Times are Unix seconds.
+async fn read(client: &Client, server: &Url, path: &str, field: &str) -> Result<()> {
+    let value = fetch(client, server, path, field).await?;
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+/// One field of a scoped read.
+async fn fetch(
+    client: &Client,
+    server: &Url,
+    path: &str,
+    field: &str,
+) -> Result<serde_json::Value> {
+    let token = credential_entry(server)
+        .get_password()
+        .context("not connected; run `lightfriend login` in your local terminal")?;
+    let response = client
+        .get(server.join(path)?)
+        .bearer_auth(token)
+        .send()
+        .await
+        .context("Lightfriend request failed")?;
+    let status_code = response.status();
+    if status_code == StatusCode::FORBIDDEN {
+        bail!("rejected; this credential was not granted that permission");
+    }
+    let mut body = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .filter(|body| status_code.is_success() && body["status"] == "accepted")
+        .ok_or_else(|| anyhow!("failed"))?;
+    Ok(body[field].take())
+}
+
+/// Prints a list as pretty JSON, or one tab-separated line per item.
+fn print_items(
+    items: &serde_json::Value,
+    json: bool,
+    empty: &str,
+    line: impl Fn(&serde_json::Value) -> String,
+) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(items)?);
+        return Ok(());
+    }
+    let items = items.as_array().map(Vec::as_slice).unwrap_or_default();
+    if items.is_empty() {
+        println!("{empty}");
+    }
+    for item in items {
+        println!("{}", line(item));
+    }
+    Ok(())
+}
+
+/// Formats Unix seconds as an RFC 3339 UTC time, which `--until` accepts back.
+fn format_utc(timestamp: i64) -> String {
+    // Days to civil date, from Howard Hinnant's `civil_from_days`.
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
 fn validate_server(value: &str) -> Result<Url> {
     let mut url = Url::parse(value).context("invalid server URL")?;
     if url.username() != ""
@@ -2,7 +2,8 @@
 set -euo pipefail
 
 if [ $# -lt 1 ]; then
-    echo "Usage: $0 <domain-or-url> [--rpc-url <url>] [--build-metadata-url <url>]"
+    echo "Usage: $0 <domain-or-url> [--rpc-url <url>] [--build-metadata-url <url>] [--expected-pcrs <file>] [--json] [--watch]"
+    echo "       $0 --attestation-file <file> [--build-metadata-file <file>] [--expected-pcrs <file>] [--json]"
     exit 1
 fi
 
@@ -3,21 +3,44 @@ use clap::Parser;
 use oyster_sdk::attestation::{self, AttestationExpectations, AWS_ROOT_KEY};
 use rand::RngCore;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sha3::{Digest, Keccak256};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::Url;
 
 #[derive(Parser, Debug)]
 struct Args {
     /// Lightfriend base URL, for example https://lightfriend.ai
-    target: String,
+    #[arg(required_unless_present = "attestation_file")]
+    target: Option<String>,
 
     /// Optional public CI metadata URL to compare against
-    #[arg(long)]
+    #[arg(long, conflicts_with_all = ["build_metadata_file", "attestation_file"])]
     build_metadata_url: Option<String>,
 
+    /// Optional local CI metadata file to compare against
+    #[arg(long)]
+    build_metadata_file: Option<PathBuf>,
+
+    /// Verify a saved attestation document (raw or hex) offline instead of
+    /// fetching a fresh one
+    #[arg(long, conflicts_with_all = ["target", "watch", "save_attestation"])]
+    attestation_file: Option<PathBuf>,
+
+    /// Hex challenge the saved attestation was requested with, if known
+    #[arg(long, requires = "attestation_file")]
+    user_data: Option<String>,
+
+    /// JSON file with pinned pcr0/pcr1/pcr2 values the attestation must match
+    #[arg(long)]
+    expected_pcrs: Option<PathBuf>,
+
+    /// Write the fetched attestation document here for later offline audits
+    #[arg(long)]
+    save_attestation: Option<PathBuf>,
+
     /// Optional JSON-RPC URL for checking oysterKMSVerify on-chain
     #[arg(long, env = "ARBITRUM_RPC_URL")]
     rpc_url: Option<String>,
@@ -25,6 +48,22 @@ struct Args {
     /// Maximum allowed attestation age in milliseconds
     #[arg(long, default_value_t = 300_000)]
     max_age_ms: u64,
+
+    /// Print a JSON report listing every check
+    #[arg(long)]
+    json: bool,
+
+    /// Keep re-attesting and alert when the attested PCRs change
+    #[arg(long)]
+    watch: bool,
+
+    /// Seconds between attestations in watch mode
+    #[arg(long, default_value_t = 300)]
+    interval_secs: u64,
+
+    /// Optional URL to POST a JSON alert to when the PCRs change
+    #[arg(long, requires = "watch")]
+    alert_webhook: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,6 +81,24 @@ struct LiveMetadata {
     kms_contract_address: Option<String>,
 }
 
+/// Pinned PCRs. A build metadata file works too; other fields are ignored.
+#[derive(Debug, Deserialize)]
+struct ExpectedPcrs {
+    pcr0: Option<String>,
+    pcr1: Option<String>,
+    pcr2: Option<String>,
+}
+
+impl ExpectedPcrs {
+    fn pcrs(&self) -> [Option<&str>; 3] {
+        [
+            self.pcr0.as_deref(),
+            self.pcr1.as_deref(),
+            self.pcr2.as_deref(),
+        ]
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct BuildMetadata {
     commit_sha: Option<String>,
@@ -54,6 +111,16 @@ struct BuildMetadata {
     workflow_run_id: Option<String>,
 }
 
+impl BuildMetadata {
+    fn pcrs(&self) -> [Option<&str>; 3] {
+        [
+            self.pcr0.as_deref(),
+            self.pcr1.as_deref(),
+            self.pcr2.as_deref(),
+        ]
+    }
+}
+
 fn normalize_base_url(target: &str) -> Result<Url> {
     let with_scheme = if target.starts_with("http://") || target.starts_with("https://") {
         target.to_string()
@@ -171,125 +238,582 @@ async fn check_contract(client: &Client, rpc_url: &str, contract: &str, pcr0: &s
     Ok(())
 }
 
-fn compare_optional(label: &str, live: &Option<String>, build: &Option<String>) -> Result<()> {
-    if let (Some(live), Some(build)) = (live, build) {
-        if live != build {
-            bail!("{label} mismatch between live metadata and build metadata");
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    name: String,
+    status: CheckStatus,
+    detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Attested {
+    pcr0: String,
+    pcr1: String,
+    pcr2: String,
+    image_id: String,
+    enclave_key: String,
+}
+
+impl Attested {
+    fn pcrs(&self) -> [&str; 3] {
+        [&self.pcr0, &self.pcr1, &self.pcr2]
+    }
+}
+
+/// Every check that ran, passed, failed or was skipped, so one mismatch
+/// doesn't hide the rest.
+#[derive(Debug, Serialize)]
+struct Report {
+    mode: &'static str,
+    target: Option<String>,
+    verified_at_ms: u64,
+    passed: bool,
+    commit_sha: Option<String>,
+    workflow_run_id: Option<String>,
+    image_ref: Option<String>,
+    eif_sha256: Option<String>,
+    eif_key: Option<String>,
+    challenge: Option<String>,
+    attested: Option<Attested>,
+    checks: Vec<Check>,
+}
+
+impl Report {
+    fn new(mode: &'static str, target: Option<&str>) -> Result<Self> {
+        Ok(Self {
+            mode,
+            target: target.map(str::to_owned),
+            verified_at_ms: now_ms()?,
+            passed: false,
+            commit_sha: None,
+            workflow_run_id: None,
+            image_ref: None,
+            eif_sha256: None,
+            eif_key: None,
+            challenge: None,
+            attested: None,
+            checks: Vec::new(),
+        })
+    }
+
+    fn push(&mut self, name: &str, status: CheckStatus, detail: impl Into<String>) {
+        self.checks.push(Check {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    fn pass(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Pass, detail);
+    }
+
+    fn fail(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Fail, detail);
+    }
+
+    fn skip(&mut self, name: &str, detail: impl Into<String>) {
+        self.push(name, CheckStatus::Skipped, detail);
+    }
+
+    fn record(&mut self, name: &str, result: Result<String>) {
+        match result {
+            Ok(detail) => self.pass(name, detail),
+            Err(e) => self.fail(name, format!("{e:#}")),
         }
     }
-    Ok(())
+
+    fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .count()
+    }
+
+    fn finish(mut self) -> Self {
+        self.passed = self.failures() == 0;
+        self
+    }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
-    let base_url = normalize_base_url(&args.target)?;
-    let client = Client::builder().build()?;
+fn now_ms() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64)
+}
 
-    let metadata_url = base_url.join("/.well-known/lightfriend/attestation")?;
-    let live = fetch_json::<LiveMetadata>(&client, metadata_url).await?;
+fn compare_optional(report: &mut Report, label: &str, live: Option<&str>, build: Option<&str>) {
+    let name = format!("build_metadata.{label}");
+    match (live, build) {
+        (Some(live), Some(build)) if live == build => report.pass(&name, live),
+        (Some(_), Some(_)) => report.fail(
+            &name,
+            format!("{label} mismatch between live metadata and build metadata"),
+        ),
+        _ => report.skip(&name, "not present in both"),
+    }
+}
 
-    let pcr0 = live
-        .pcr0
-        .as_deref()
-        .ok_or_else(|| anyhow!("live metadata missing pcr0"))?;
-    let pcr1 = live
-        .pcr1
-        .as_deref()
-        .ok_or_else(|| anyhow!("live metadata missing pcr1"))?;
-    let pcr2 = live
-        .pcr2
-        .as_deref()
-        .ok_or_else(|| anyhow!("live metadata missing pcr2"))?;
+/// Compare the attested PCRs with one source of expected values. A PCR the
+/// source doesn't list fails when `required`, else is skipped. Returns how
+/// many were actually compared, so callers can tell a source that checked
+/// nothing.
+fn compare_pcrs(
+    report: &mut Report,
+    source: &str,
+    attested: &[[u8; 48]],
+    expected: [Option<&str>; 3],
+    required: bool,
+) -> usize {
+    let mut compared = 0;
+    for (index, expected) in expected.into_iter().enumerate() {
+        let name = format!("pcr{index}.{source}");
+        let Some(expected) = expected else {
+            let detail = format!("{source} has no pcr{index}");
+            if required {
+                report.fail(&name, detail);
+            } else {
+                report.skip(&name, detail);
+            }
+            continue;
+        };
+        compared += 1;
+        report.record(
+            &name,
+            parse_pcr(expected).and_then(|pcr| {
+                if attested[index] != pcr {
+                    bail!("attested PCR{index} does not match {source}");
+                }
+                Ok(bytes_to_hex_prefixed(&pcr))
+            }),
+        );
+    }
+    compared
+}
+
+fn read_json_file<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_slice(&bytes).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Accepts the document as served by either attestation URL: raw CBOR or hex.
+fn read_attestation_file(path: &Path) -> Result<Vec<u8>> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if let Ok(text) = std::str::from_utf8(&bytes) {
+        let text = text.trim();
+        let clean = text.strip_prefix("0x").unwrap_or(text);
+        if !clean.is_empty() && clean.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return hex::decode(clean).context("invalid attestation hex");
+        }
+    }
+    Ok(bytes)
+}
 
-    let build_metadata_url = args
-        .build_metadata_url
+fn load_expected_pcrs(args: &Args) -> Result<Option<ExpectedPcrs>> {
+    args.expected_pcrs
         .as_deref()
-        .map(str::to_owned)
-        .or_else(|| live.build_metadata_url.clone());
+        .map(read_json_file::<ExpectedPcrs>)
+        .transpose()
+}
+
+async fn load_build_metadata(
+    client: &Client,
+    args: &Args,
+    live_url: Option<&str>,
+    report: &mut Report,
+) -> Option<BuildMetadata> {
+    let result = if let Some(path) = args.build_metadata_file.as_deref() {
+        read_json_file::<BuildMetadata>(path)
+    } else if let Some(url) = args.build_metadata_url.as_deref().or(live_url) {
+        match Url::parse(url) {
+            Ok(url) => fetch_json::<BuildMetadata>(client, url).await,
+            Err(e) => Err(e.into()),
+        }
+    } else {
+        report.skip("build_metadata", "no build metadata given");
+        return None;
+    };
+    match result {
+        Ok(build) => {
+            report.pass("build_metadata", "loaded");
+            Some(build)
+        }
+        Err(e) => {
+            report.fail("build_metadata", format!("{e:#}"));
+            None
+        }
+    }
+}
 
-    if let Some(build_metadata_url) = build_metadata_url.as_deref() {
-        let build = fetch_json::<BuildMetadata>(&client, Url::parse(build_metadata_url)?).await?;
-        compare_optional("commit_sha", &live.commit_sha, &build.commit_sha)?;
+fn record_attested(report: &mut Report, decoded: &attestation::AttestationDecoded) {
+    report.attested = Some(Attested {
+        pcr0: bytes_to_hex_prefixed(&decoded.pcrs[0]),
+        pcr1: bytes_to_hex_prefixed(&decoded.pcrs[1]),
+        pcr2: bytes_to_hex_prefixed(&decoded.pcrs[2]),
+        image_id: bytes_to_hex_prefixed(&decoded.image_id),
+        enclave_key: bytes_to_hex_prefixed(&decoded.public_key),
+    });
+}
+
+async fn verify_live(client: &Client, args: &Args, target: &str) -> Result<Report> {
+    let mut report = Report::new("live", Some(target))?;
+    let expected = load_expected_pcrs(args)?;
+    let base_url = normalize_base_url(target)?;
+
+    let metadata_url = base_url.join("/.well-known/lightfriend/attestation")?;
+    let live = match fetch_json::<LiveMetadata>(client, metadata_url).await {
+        Ok(live) => {
+            report.pass("live_metadata", "fetched");
+            live
+        }
+        Err(e) => {
+            report.fail("live_metadata", format!("{e:#}"));
+            return Ok(report.finish());
+        }
+    };
+    report.commit_sha = live.commit_sha.clone();
+    report.workflow_run_id = live.workflow_run_id.clone();
+    report.image_ref = live.image_ref.clone();
+    report.eif_sha256 = live.eif_sha256.clone();
+
+    let build = load_build_metadata(
+        client,
+        args,
+        live.build_metadata_url.as_deref(),
+        &mut report,
+    )
+    .await;
+    if let Some(build) = &build {
+        compare_optional(
+            &mut report,
+            "commit_sha",
+            live.commit_sha.as_deref(),
+            build.commit_sha.as_deref(),
+        );
         compare_optional(
+            &mut report,
             "workflow_run_id",
-            &live.workflow_run_id,
-            &build.workflow_run_id,
-        )?;
-        compare_optional("image_ref", &live.image_ref, &build.image_ref)?;
-        compare_optional("eif_sha256", &live.eif_sha256, &build.eif_sha256)?;
-        compare_optional("pcr0", &live.pcr0, &build.pcr0)?;
-        compare_optional("pcr1", &live.pcr1, &build.pcr1)?;
-        compare_optional("pcr2", &live.pcr2, &build.pcr2)?;
-        if let Some(eif_key) = build.eif_key {
-            println!("Build metadata EIF key: {eif_key}");
-        }
+            live.workflow_run_id.as_deref(),
+            build.workflow_run_id.as_deref(),
+        );
+        compare_optional(
+            &mut report,
+            "image_ref",
+            live.image_ref.as_deref(),
+            build.image_ref.as_deref(),
+        );
+        compare_optional(
+            &mut report,
+            "eif_sha256",
+            live.eif_sha256.as_deref(),
+            build.eif_sha256.as_deref(),
+        );
+        report.eif_key = build.eif_key.clone();
     }
 
     let mut challenge = [0u8; 16];
     rand::thread_rng().fill_bytes(&mut challenge);
     let challenge_hex = hex::encode(challenge);
-    let attestation_doc = fetch_attestation(&client, &live, &challenge_hex).await?;
+    report.challenge = Some(format!("0x{challenge_hex}"));
+    let attestation_doc = match fetch_attestation(client, &live, &challenge_hex).await {
+        Ok(doc) => doc,
+        Err(e) => {
+            report.fail("attestation", format!("{e:#}"));
+            return Ok(report.finish());
+        }
+    };
+    if let Some(path) = args.save_attestation.as_deref() {
+        std::fs::write(path, &attestation_doc)
+            .with_context(|| format!("failed to save attestation to {}", path.display()))?;
+    }
 
-    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
-    let decoded = attestation::verify(
+    let decoded = match attestation::verify(
         &attestation_doc,
         AttestationExpectations {
             root_public_key: Some(&AWS_ROOT_KEY),
-            age_ms: Some((args.max_age_ms, now_ms)),
+            age_ms: Some((args.max_age_ms, now_ms()?)),
             user_data: Some(&challenge),
             ..Default::default()
         },
-    )
-    .context("attestation verification failed")?;
-
-    let expected_pcr0 = parse_pcr(pcr0)?;
-    let expected_pcr1 = parse_pcr(pcr1)?;
-    let expected_pcr2 = parse_pcr(pcr2)?;
-    if decoded.pcrs[0] != expected_pcr0 {
-        bail!("attested PCR0 does not match live metadata");
-    }
-    if decoded.pcrs[1] != expected_pcr1 {
-        bail!("attested PCR1 does not match live metadata");
+    ) {
+        Ok(decoded) => {
+            report.pass(
+                "attestation",
+                "signed by the AWS Nitro root, fresh, and bound to our challenge",
+            );
+            decoded
+        }
+        Err(e) => {
+            report.fail(
+                "attestation",
+                format!("attestation verification failed: {e}"),
+            );
+            return Ok(report.finish());
+        }
+    };
+    record_attested(&mut report, &decoded);
+
+    let live_pcrs = [
+        live.pcr0.as_deref(),
+        live.pcr1.as_deref(),
+        live.pcr2.as_deref(),
+    ];
+    compare_pcrs(&mut report, "live_metadata", &decoded.pcrs, live_pcrs, true);
+    if let Some(build) = &build {
+        compare_pcrs(
+            &mut report,
+            "build_metadata",
+            &decoded.pcrs,
+            build.pcrs(),
+            false,
+        );
     }
-    if decoded.pcrs[2] != expected_pcr2 {
-        bail!("attested PCR2 does not match live metadata");
+    // A pinned file must name every PCR; a typo in a key would otherwise
+    // pass without checking anything.
+    if let Some(expected) = &expected {
+        compare_pcrs(&mut report, "pinned", &decoded.pcrs, expected.pcrs(), true);
     }
 
-    if let (Some(rpc_url), Some(contract)) = (
+    match (
         args.rpc_url.as_deref(),
         live.kms_contract_address.as_deref(),
+        live.pcr0.as_deref(),
+    ) {
+        (Some(rpc_url), Some(contract), Some(pcr0)) => {
+            let result = check_contract(client, rpc_url, contract, pcr0).await;
+            report.record("kms_contract", result.map(|()| contract.to_string()));
+        }
+        _ => report.skip("kms_contract", "needs --rpc-url and a contract address"),
+    }
+
+    Ok(report.finish())
+}
+
+/// Checks a saved attestation document without any network access. The
+/// signature, PCRs and (if given) challenge are verified; freshness and the
+/// on-chain approval can't be, and are reported as skipped.
+fn verify_offline(args: &Args, path: &Path) -> Result<Report> {
+    let mut report = Report::new("offline", None)?;
+    let expected = load_expected_pcrs(args)?;
+    let attestation_doc = read_attestation_file(path)?;
+    let user_data = args
+        .user_data
+        .as_deref()
+        .map(|v| hex::decode(v.strip_prefix("0x").unwrap_or(v)).context("invalid --user-data hex"))
+        .transpose()?;
+    report.challenge = args.user_data.clone();
+
+    let build = match args.build_metadata_file.as_deref() {
+        Some(path) => match read_json_file::<BuildMetadata>(path) {
+            Ok(build) => {
+                report.pass("build_metadata", "loaded");
+                report.commit_sha = build.commit_sha.clone();
+                report.workflow_run_id = build.workflow_run_id.clone();
+                report.image_ref = build.image_ref.clone();
+                report.eif_sha256 = build.eif_sha256.clone();
+                report.eif_key = build.eif_key.clone();
+                Some(build)
+            }
+            Err(e) => {
+                report.fail("build_metadata", format!("{e:#}"));
+                None
+            }
+        },
+        None => {
+            report.skip("build_metadata", "no build metadata given");
+            None
+        }
+    };
+
+    let decoded = match attestation::verify(
+        &attestation_doc,
+        AttestationExpectations {
+            root_public_key: Some(&AWS_ROOT_KEY),
+            user_data: user_data.as_deref(),
+            ..Default::default()
+        },
     ) {
-        check_contract(&client, rpc_url, contract, pcr0).await?;
-        println!("Contract approval verified: {contract}");
+        Ok(decoded) => {
+            report.pass("attestation", "signed by the AWS Nitro root");
+            decoded
+        }
+        Err(e) => {
+            report.fail(
+                "attestation",
+                format!("attestation verification failed: {e}"),
+            );
+            return Ok(report.finish());
+        }
+    };
+    record_attested(&mut report, &decoded);
+    report.skip("freshness", "offline verification of a saved document");
+    if user_data.is_some() {
+        report.pass("challenge", "attestation carries the given user_data");
     } else {
-        println!("Contract approval check skipped");
+        report.skip("challenge", "no --user-data given");
+    }
+
+    let mut compared = 0;
+    if let Some(build) = &build {
+        compared += compare_pcrs(
+            &mut report,
+            "build_metadata",
+            &decoded.pcrs,
+            build.pcrs(),
+            false,
+        );
+    }
+    if let Some(expected) = &expected {
+        compared += compare_pcrs(&mut report, "pinned", &decoded.pcrs, expected.pcrs(), true);
+    }
+    if compared == 0 {
+        report.fail(
+            "pcrs",
+            "nothing to compare against; pass --build-metadata-file or --expected-pcrs",
+        );
+    }
+    report.skip("kms_contract", "offline verification");
+
+    Ok(report.finish())
+}
+
+fn print_report(report: &Report, json: bool, compact: bool) -> Result<()> {
+    if json {
+        if compact {
+            println!("{}", serde_json::to_string(report)?);
+        } else {
+            println!("{}", serde_json::to_string_pretty(report)?);
+        }
+        return Ok(());
     }
 
-    println!("Verification succeeded");
+    for check in &report.checks {
+        let status = match check.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Fail => "FAIL",
+            CheckStatus::Skipped => "SKIP",
+        };
+        println!("{status:<5} {}: {}", check.name, check.detail);
+    }
+    if report.passed {
+        println!("Verification succeeded");
+    } else {
+        println!(
+            "Verification failed: {} of {} checks failed",
+            report.failures(),
+            report.checks.len()
+        );
+    }
+    let unknown = || "unknown".to_string();
     println!(
         "Commit: {}",
-        live.commit_sha.unwrap_or_else(|| "unknown".to_string())
+        report.commit_sha.clone().unwrap_or_else(unknown)
     );
     println!(
         "Workflow run: {}",
-        live.workflow_run_id
-            .unwrap_or_else(|| "unknown".to_string())
+        report.workflow_run_id.clone().unwrap_or_else(unknown)
     );
     println!(
         "Image: {}",
-        live.image_ref.unwrap_or_else(|| "unknown".to_string())
+        report.image_ref.clone().unwrap_or_else(unknown)
     );
     println!(
         "EIF sha256: {}",
-        live.eif_sha256.unwrap_or_else(|| "unknown".to_string())
+        report.eif_sha256.clone().unwrap_or_else(unknown)
     );
-    println!("PCR0: {}", bytes_to_hex_prefixed(&decoded.pcrs[0]));
-    println!("PCR1: {}", bytes_to_hex_prefixed(&decoded.pcrs[1]));
-    println!("PCR2: {}", bytes_to_hex_prefixed(&decoded.pcrs[2]));
-    println!("Image ID: 0x{}", hex::encode(decoded.image_id));
-    println!("Enclave key: 0x{}", hex::encode(decoded.public_key));
-    println!("Fresh challenge (user_data): 0x{}", challenge_hex);
+    if let Some(eif_key) = &report.eif_key {
+        println!("Build metadata EIF key: {eif_key}");
+    }
+    if let Some(attested) = &report.attested {
+        println!("PCR0: {}", attested.pcr0);
+        println!("PCR1: {}", attested.pcr1);
+        println!("PCR2: {}", attested.pcr2);
+        println!("Image ID: {}", attested.image_id);
+        println!("Enclave key: {}", attested.enclave_key);
+    }
+    if let Some(challenge) = &report.challenge {
+        println!("Challenge (user_data): {challenge}");
+    }
+    Ok(())
+}
+
+async fn alert_pcr_change(client: &Client, args: &Args, previous: &Attested, report: &Report) {
+    let Some(current) = &report.attested else {
+        return;
+    };
+    eprintln!(
+        "ALERT: attested PCRs changed\n  before: {}\n  after:  {}",
+        previous.pcrs().join(" "),
+        current.pcrs().join(" ")
+    );
+    if let Some(webhook) = args.alert_webhook.as_deref() {
+        let payload = json!({
+            "event": "pcr_change",
+            "target": report.target,
+            "previous": previous,
+            "current": current,
+            "report": report,
+        });
+        let sent = client
+            .post(webhook)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status());
+        if let Err(e) = sent {
+            eprintln!("Failed to deliver PCR change alert: {e}");
+        }
+    }
+}
+
+/// Re-attests forever. Each round is reported; a change in the attested
+/// PCRs between successful rounds raises an alert.
+async fn watch(client: &Client, args: &Args, target: &str) -> Result<()> {
+    let mut previous: Option<Attested> = None;
+    loop {
+        let report = verify_live(client, args, target).await?;
+        print_report(&report, args.json, true)?;
+        if let Some(current) = &report.attested {
+            if let Some(prev) = &previous {
+                if prev.pcrs() != current.pcrs() {
+                    alert_pcr_change(client, args, prev, &report).await;
+                }
+            }
+            previous = Some(current.clone());
+        }
+        tokio::time::sleep(Duration::from_secs(args.interval_secs)).await;
+    }
+}
 
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let client = Client::builder().build()?;
+
+    let report = if let Some(path) = args.attestation_file.as_deref() {
+        verify_offline(&args, path)?
+    } else {
+        let target = args
+            .target
+            .as_deref()
+            .ok_or_else(|| anyhow!("a target URL is required"))?;
+        if args.watch {
+            return watch(&client, &args, target).await;
+        }
+        verify_live(&client, &args, target).await?
+    };
+
+    print_report(&report, args.json, false)?;
+    if !report.passed {
+        std::process::exit(1);
+    }
     Ok(())
 }